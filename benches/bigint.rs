@@ -0,0 +1,46 @@
+#[macro_use]
+extern crate criterion;
+extern crate pprust;
+
+use criterion::{BenchmarkId, Criterion};
+use pprust::bigint::BigInt;
+
+/// A number of `digits` 32-bit digits, as 2^(32 digits) / 3 plus noise
+fn number(digits: usize, seed: i64) -> BigInt {
+    let top = BigInt::from(2).pow(32 * digits as u32 - 2);
+    &(&top / &BigInt::from(3)) + &BigInt::from(seed)
+}
+
+/// Schoolbook against Karatsuba's method, both with the usual methods
+/// below the top level; the lines cross at `KARATSUBA_THRESHOLD`.
+fn karatsuba(c: &mut Criterion) {
+    let mut group = c.benchmark_group("karatsuba crossover");
+    for &digits in &[16, 32, 48, 56, 64, 96, 128] {
+        let (a, b) = (number(digits, 1), number(digits, 2));
+        group.bench_with_input(BenchmarkId::new("schoolbook", digits), &(&a, &b), |bench, &(a, b)| {
+            bench.iter(|| a.mul_schoolbook(b))
+        });
+        group.bench_with_input(BenchmarkId::new("karatsuba", digits), &(&a, &b), |bench, &(a, b)| {
+            bench.iter(|| a.mul_karatsuba(b))
+        });
+    }
+    group.finish();
+}
+
+/// Karatsuba against Toom-3; the lines cross at `TOOM3_THRESHOLD`.
+fn toom3(c: &mut Criterion) {
+    let mut group = c.benchmark_group("toom3 crossover");
+    for &digits in &[64, 128, 160, 192, 256, 512, 1024, 2048] {
+        let (a, b) = (number(digits, 1), number(digits, 2));
+        group.bench_with_input(BenchmarkId::new("karatsuba", digits), &(&a, &b), |bench, &(a, b)| {
+            bench.iter(|| a.mul_karatsuba(b))
+        });
+        group.bench_with_input(BenchmarkId::new("toom3", digits), &(&a, &b), |bench, &(a, b)| {
+            bench.iter(|| a.mul_toom3(b))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, karatsuba, toom3);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+#[macro_use]
+extern crate criterion;
+extern crate pprust;
+
+use criterion::{BenchmarkId, Criterion};
+use pprust::rope::Rope;
+
+/// An xorshift generator for edit positions
+struct Positions(u64);
+
+impl Positions {
+    fn next(&mut self, below: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % below as u64) as usize
+    }
+}
+
+fn document(bytes: usize) -> String {
+    "All work and no play makes Jack a dull boy.\n".chars().cycle().take(bytes).collect()
+}
+
+/// An insertion and a deletion of the same length at random places, so
+/// the document keeps its size. The text is ASCII, so byte and character
+/// positions agree.
+fn edits(c: &mut Criterion) {
+    let mut group = c.benchmark_group("edits");
+    group.sample_size(20);
+    for &megabytes in &[1, 4, 16] {
+        let text = document(megabytes << 20);
+        group.bench_with_input(BenchmarkId::new("rope", megabytes), &text, |b, text| {
+            let mut rope = Rope::from(&**text);
+            let mut at = Positions(88172645463325252);
+            b.iter(|| {
+                let len = rope.len();
+                rope.insert(at.next(len), "insert");
+                let start = at.next(len);
+                rope.delete(start..start + 6);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("string", megabytes), &text, |b, text| {
+            let mut string = text.clone();
+            let mut at = Positions(88172645463325252);
+            b.iter(|| {
+                let len = string.len();
+                string.insert_str(at.next(len), "insert");
+                let start = at.next(len);
+                string.replace_range(start..start + 6, "");
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Reading a character at a random place, where the string wins
+fn indexing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("indexing");
+    let text = document(4 << 20);
+    let rope = Rope::from(&*text);
+    let mut at = Positions(88172645463325252);
+    group.bench_function("rope", |b| b.iter(|| rope.char_at(at.next(text.len()))));
+    group.bench_function("string", |b| b.iter(|| text.as_bytes()[at.next(text.len())]));
+    group.finish();
+}
+
+criterion_group!(benches, edits, indexing);
+criterion_main!(benches);
@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate criterion;
+extern crate pprust;
+
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use pprust::sort::radix::{lsd_sort, msd_sort};
+use pprust::sort::{heapsort, mergesort, natural_mergesort, quicksort3, QuickSort, Sorter};
+
+const SIZE: usize = 10_000;
+
+fn pseudo_random(n: usize, modulus: u64) -> Vec<u64> {
+    let mut x: u64 = 88172645463325252;
+    (0..n).map(|_| {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        x % modulus
+    }).collect()
+}
+
+/// A textbook two-way (Lomuto) quicksort, which goes quadratic when
+/// most keys are equal: every partition puts all of them on one side.
+fn quicksort2(slice: &mut [u64]) {
+    if slice.len() <= 1 {
+        return;
+    }
+    let last = slice.len() - 1;
+    slice.swap(slice.len() / 2, last);
+    let mut store = 0;
+    for i in 0..last {
+        if slice[i] <= slice[last] {
+            slice.swap(i, store);
+            store += 1;
+        }
+    }
+    slice.swap(store, last);
+    let (left, right) = slice.split_at_mut(store);
+    quicksort2(left);
+    quicksort2(&mut right[1..]);
+}
+
+fn duplicate_keys(c: &mut Criterion) {
+    let mut group = c.benchmark_group("duplicate keys");
+    for &distinct in &[2u64, 100, SIZE as u64] {
+        let input = pseudo_random(SIZE, distinct);
+        group.bench_with_input(BenchmarkId::new("quicksort3", distinct), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| quicksort3(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("quicksort2", distinct), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| quicksort2(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("std", distinct), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| v.sort_unstable(), BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+fn distributions(c: &mut Criterion) {
+    let random = pseudo_random(SIZE, u64::MAX);
+    let sorted: Vec<u64> = (0..SIZE as u64).collect();
+    let reversed: Vec<u64> = sorted.iter().rev().cloned().collect();
+    let few_unique = pseudo_random(SIZE, 8);
+    let inputs = [("random", random), ("sorted", sorted), ("reversed", reversed), ("few unique", few_unique)];
+
+    let mut group = c.benchmark_group("distributions");
+    for &(name, ref input) in &inputs {
+        group.bench_with_input(BenchmarkId::new("heapsort", name), input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| heapsort(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("introsort", name), input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| QuickSort.sort(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("quicksort3", name), input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| quicksort3(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("mergesort", name), input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| mergesort(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("natural mergesort", name), input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| natural_mergesort(v), BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+/// Radix sorts pay a fixed cost per pass, so comparison sorts win on
+/// small inputs; these sizes show where the lines cross.
+fn radix(c: &mut Criterion) {
+    let mut group = c.benchmark_group("radix u64");
+    for &size in &[16, 128, 1024, 16384, 131072] {
+        let input = pseudo_random(size, u64::MAX);
+        group.bench_with_input(BenchmarkId::new("lsd", size), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| lsd_sort(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("introsort", size), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| QuickSort.sort(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("std", size), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| v.sort_unstable(), BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("radix strings");
+    for &size in &[16, 128, 1024, 16384] {
+        // Keys sharing long prefixes, where comparisons rescan the same bytes
+        let input: Vec<String> = pseudo_random(size, 1 << 20).into_iter().map(|x| format!("prefix/{:08}", x)).collect();
+        group.bench_with_input(BenchmarkId::new("msd", size), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| msd_sort(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("introsort", size), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| QuickSort.sort(v), BatchSize::SmallInput)
+        });
+        group.bench_with_input(BenchmarkId::new("std", size), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |v| v.sort_unstable(), BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, duplicate_keys, distributions, radix);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+#[macro_use]
+extern crate criterion;
+extern crate pprust;
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use pprust::strsearch::{AhoCorasick, Kmp};
+
+/// Lowercase letters from an xorshift generator, so patterns of a few
+/// letters match now and then
+fn pseudo_random_text(n: usize, seed: u64) -> Vec<u8> {
+    let mut x = seed;
+    (0..n).map(|_| {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        b'a' + (x % 26) as u8
+    }).collect()
+}
+
+/// One pass of Aho-Corasick against a KMP pass per pattern over a
+/// megabyte of text; the first stays flat as patterns are added
+fn many_patterns(c: &mut Criterion) {
+    let text = pseudo_random_text(1 << 20, 88172645463325252);
+    let mut group = c.benchmark_group("many patterns");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    for &count in &[1, 10, 100, 1000] {
+        let patterns: Vec<Vec<u8>> = (0..count).map(|i| pseudo_random_text(4 + i % 5, i as u64 + 1)).collect();
+        let ac = AhoCorasick::new(&patterns);
+        let kmps: Vec<Kmp> = patterns.iter().map(|p| Kmp::new(p)).collect();
+        group.bench_with_input(BenchmarkId::new("aho-corasick", count), &text, |b, text| {
+            b.iter(|| ac.find_all(text).len())
+        });
+        group.bench_with_input(BenchmarkId::new("kmp", count), &text, |b, text| {
+            b.iter(|| kmps.iter().map(|kmp| kmp.find_all(text).len()).sum::<usize>())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, many_patterns);
+criterion_main!(benches);
@@ -0,0 +1,421 @@
+//!
+//! # Problem description:
+//! #
+//! # Base64 and Base32
+//!
+//! Binary data often has to travel through channels made for text, like
+//! mail or URLs. Base64 writes every three bytes as four characters of a
+//! 64-symbol alphabet, six bits each; Base32 writes every five bytes as
+//! eight characters of a 32-symbol alphabet, five bits each, which is
+//! longer but has no lower case letters and no punctuation. Both are
+//! defined in RFC 4648.
+//!
+//! When the data doesn't fill the last group, the bits left are padded
+//! with zeros to a whole character, and the group is completed with `=`.
+//! The URL-safe Base64 alphabet uses `-` and `_` for `+` and `/`, which
+//! mean something in URLs and file names.
+//!
+//! Decoding takes the padding or its absence, but rejects characters
+//! outside the alphabet and lengths no encoding gives.
+//!
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+
+/// The padding character
+const PAD: u8 = b'=';
+
+/// The encodings of RFC 4648
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Base64 with `+` and `/`
+    Base64,
+    /// Base64 with `-` and `_`, for URLs and file names
+    Base64Url,
+    Base32,
+}
+
+impl Alphabet {
+    fn symbols(self) -> &'static [u8] {
+        match self {
+            Alphabet::Base64 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+            Alphabet::Base64Url => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+            Alphabet::Base32 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567",
+        }
+    }
+
+    /// The bits each character stands for
+    fn bits(self) -> u32 {
+        match self {
+            Alphabet::Base32 => 5,
+            _ => 6,
+        }
+    }
+
+    /// The characters of a whole group, which padding completes
+    fn group(self) -> usize {
+        match self {
+            Alphabet::Base32 => 8,
+            _ => 4,
+        }
+    }
+
+    fn value(self, symbol: u8) -> Option<u32> {
+        self.symbols().iter().position(|&s| s == symbol).map(|value| value as u32)
+    }
+}
+
+/// Why a text couldn't be decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A character outside the alphabet, and its byte offset in the text
+    InvalidSymbol { symbol: char, offset: usize },
+    /// The text ends in the middle of a byte
+    InvalidLength,
+    /// `=` where no padding can be, or the wrong number of them
+    InvalidPadding,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::InvalidSymbol { symbol, offset } => write!(f, "Invalid character {:?} at byte {}", symbol, offset),
+            DecodeError::InvalidLength => write!(f, "The text ends in the middle of a byte"),
+            DecodeError::InvalidPadding => write!(f, "Invalid padding"),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+impl From<DecodeError> for io::Error {
+    fn from(e: DecodeError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// Turns bytes into characters, a few bits at a time
+#[derive(Debug, Clone)]
+struct Packer {
+    alphabet: Alphabet,
+    buffer: u32,
+    /// Bits in `buffer` not written yet, always less than a character
+    bits: u32,
+    /// Characters written so far
+    written: usize,
+}
+
+impl Packer {
+    fn new(alphabet: Alphabet) -> Packer {
+        Packer { alphabet, buffer: 0, bits: 0, written: 0 }
+    }
+
+    fn push(&mut self, byte: u8, out: &mut Vec<u8>) {
+        let width = self.alphabet.bits();
+        self.buffer = (self.buffer << 8) | byte as u32;
+        self.bits += 8;
+        while self.bits >= width {
+            self.bits -= width;
+            out.push(self.alphabet.symbols()[((self.buffer >> self.bits) & ((1 << width) - 1)) as usize]);
+            self.written += 1;
+        }
+        self.buffer &= (1 << self.bits) - 1;
+    }
+
+    /// Writes the bits left, padded with zeros, and the `=` that complete
+    /// the group
+    fn finish(&mut self, out: &mut Vec<u8>) {
+        let width = self.alphabet.bits();
+        if self.bits > 0 {
+            out.push(self.alphabet.symbols()[((self.buffer << (width - self.bits)) & ((1 << width) - 1)) as usize]);
+            self.written += 1;
+        }
+        while !self.written.is_multiple_of(self.alphabet.group()) {
+            out.push(PAD);
+            self.written += 1;
+        }
+        self.buffer = 0;
+        self.bits = 0;
+    }
+}
+
+/// Turns characters back into bytes
+#[derive(Debug, Clone)]
+struct Unpacker {
+    alphabet: Alphabet,
+    buffer: u32,
+    bits: u32,
+    /// Characters of the alphabet read so far
+    symbols: usize,
+    /// `=` read so far
+    padding: usize,
+    /// Bytes of text read so far, for the offset of errors
+    offset: usize,
+}
+
+impl Unpacker {
+    fn new(alphabet: Alphabet) -> Unpacker {
+        Unpacker { alphabet, buffer: 0, bits: 0, symbols: 0, padding: 0, offset: 0 }
+    }
+
+    fn push(&mut self, symbol: u8, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let offset = self.offset;
+        self.offset += 1;
+        if symbol == PAD {
+            self.padding += 1;
+            return Ok(());
+        }
+        let value = self.alphabet.value(symbol).ok_or(DecodeError::InvalidSymbol { symbol: symbol as char, offset })?;
+        if self.padding > 0 {
+            return Err(DecodeError::InvalidPadding);
+        }
+        self.buffer = (self.buffer << self.alphabet.bits()) | value;
+        self.bits += self.alphabet.bits();
+        self.symbols += 1;
+        if self.bits >= 8 {
+            self.bits -= 8;
+            out.push((self.buffer >> self.bits) as u8);
+            self.buffer &= (1 << self.bits) - 1;
+        }
+        Ok(())
+    }
+
+    /// Checks the text ended where an encoding can end
+    fn finish(&self) -> Result<(), DecodeError> {
+        // The bits left over must be the zeros that filled a character,
+        // so fewer than a character has
+        if self.bits >= self.alphabet.bits() {
+            return Err(DecodeError::InvalidLength);
+        }
+        let group = self.alphabet.group();
+        if self.padding > 0 && (self.symbols.is_multiple_of(group) || !(self.symbols + self.padding).is_multiple_of(group)) {
+            return Err(DecodeError::InvalidPadding);
+        }
+        Ok(())
+    }
+}
+
+/// Encodes bytes as text, with padding
+///
+/// # Example
+/// ```
+/// use pprust::baseenc::{encode, Alphabet};
+///
+/// assert_eq!("TWFu", encode(b"Man", Alphabet::Base64));
+/// assert_eq!("-_8=", encode(&[0xfb, 0xff], Alphabet::Base64Url));
+/// assert_eq!("MZXW6===", encode(b"foo", Alphabet::Base32));
+/// ```
+pub fn encode(bytes: &[u8], alphabet: Alphabet) -> String {
+    let mut packer = Packer::new(alphabet);
+    let mut out = Vec::new();
+    for &byte in bytes {
+        packer.push(byte, &mut out);
+    }
+    packer.finish(&mut out);
+    String::from_utf8(out).expect("The alphabets are ASCII")
+}
+
+/// Decodes text made by `encode`, with or without its padding
+///
+/// # Example
+/// ```
+/// use pprust::baseenc::{decode, Alphabet, DecodeError};
+///
+/// assert_eq!(Ok(b"Man".to_vec()), decode("TWFu", Alphabet::Base64));
+/// assert_eq!(Ok(b"Ma".to_vec()), decode("TWE", Alphabet::Base64));
+/// assert_eq!(Ok(b"fo".to_vec()), decode("MZXQ====", Alphabet::Base32));
+/// assert_eq!(Err(DecodeError::InvalidSymbol { symbol: '-', offset: 1 }), decode("T-E", Alphabet::Base64));
+/// ```
+pub fn decode(text: &str, alphabet: Alphabet) -> Result<Vec<u8>, DecodeError> {
+    let mut unpacker = Unpacker::new(alphabet);
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for &symbol in text.as_bytes() {
+        unpacker.push(symbol, &mut out)?;
+    }
+    unpacker.finish()?;
+    Ok(out)
+}
+
+/// Encodes the bytes written to it into an underlying writer
+///
+/// # Example
+/// ```
+/// use pprust::baseenc::{Alphabet, Encoder};
+/// use std::io::Write;
+///
+/// let mut encoder = Encoder::new(Vec::new(), Alphabet::Base64);
+/// encoder.write_all(b"any carnal ").unwrap();
+/// encoder.write_all(b"pleas").unwrap();
+/// assert_eq!(b"YW55IGNhcm5hbCBwbGVhcw==".to_vec(), encoder.finish().unwrap());
+/// ```
+pub struct Encoder<W: Write> {
+    inner: W,
+    packer: Packer,
+    out: Vec<u8>,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(inner: W, alphabet: Alphabet) -> Encoder<W> {
+        Encoder { inner, packer: Packer::new(alphabet), out: Vec::new() }
+    }
+
+    /// Writes the last characters and the padding, flushes and returns
+    /// the writer. Without it the end of the data is lost.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.clear();
+        self.packer.finish(&mut self.out);
+        self.inner.write_all(&self.out)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.out.clear();
+        for &byte in bytes {
+            self.packer.push(byte, &mut self.out);
+        }
+        self.inner.write_all(&self.out)?;
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decodes the text of an underlying reader, skipping the line breaks and
+/// other whitespace encoded files are often wrapped with. Invalid text is
+/// an error of kind `InvalidData`, with the `DecodeError` inside.
+///
+/// # Example
+/// ```
+/// use pprust::baseenc::{Alphabet, Decoder};
+/// use std::io::Read;
+///
+/// let mut decoder = Decoder::new("MZXW6YTB\nOI======\n".as_bytes(), Alphabet::Base32);
+/// let mut text = String::new();
+/// decoder.read_to_string(&mut text).unwrap();
+/// assert_eq!("foobar", text);
+/// ```
+pub struct Decoder<R: Read> {
+    inner: R,
+    unpacker: Unpacker,
+    /// Decoded bytes not read yet, from `at` on
+    out: Vec<u8>,
+    at: usize,
+    done: bool,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(inner: R, alphabet: Alphabet) -> Decoder<R> {
+        Decoder { inner, unpacker: Unpacker::new(alphabet), out: Vec::new(), at: 0, done: false }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let mut text = [0; 4096];
+        while self.at == self.out.len() && !self.done && !buffer.is_empty() {
+            self.out.clear();
+            self.at = 0;
+            let n = match self.inner.read(&mut text) {
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if n == 0 {
+                self.done = true;
+                self.unpacker.finish()?;
+            }
+            for &symbol in &text[..n] {
+                if symbol.is_ascii_whitespace() {
+                    self.unpacker.offset += 1;
+                } else {
+                    self.unpacker.push(symbol, &mut self.out)?;
+                }
+            }
+        }
+        let n = buffer.len().min(self.out.len() - self.at);
+        buffer[..n].copy_from_slice(&self.out[self.at..self.at + n]);
+        self.at += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXTS: [&str; 7] = ["", "f", "fo", "foo", "foob", "fooba", "foobar"];
+
+    #[test]
+    fn should_match_the_rfc_4648_test_vectors() {
+        let base64 = ["", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy"];
+        let base32 = ["", "MY======", "MZXQ====", "MZXW6===", "MZXW6YQ=", "MZXW6YTB", "MZXW6YTBOI======"];
+        for (i, text) in TEXTS.iter().enumerate() {
+            assert_eq!(base64[i], encode(text.as_bytes(), Alphabet::Base64));
+            assert_eq!(base32[i], encode(text.as_bytes(), Alphabet::Base32));
+            assert_eq!(Ok(text.as_bytes().to_vec()), decode(base64[i], Alphabet::Base64));
+            assert_eq!(Ok(text.as_bytes().to_vec()), decode(base32[i], Alphabet::Base32));
+            let unpadded = base32[i].trim_end_matches('=');
+            assert_eq!(Ok(text.as_bytes().to_vec()), decode(unpadded, Alphabet::Base32), "{}", unpadded);
+        }
+        let bytes: Vec<u8> = (0..=255).collect();
+        let standard = encode(&bytes, Alphabet::Base64);
+        let url = encode(&bytes, Alphabet::Base64Url);
+        assert_eq!(standard.replace('+', "-").replace('/', "_"), url);
+        assert_eq!(Ok(bytes.clone()), decode(&url, Alphabet::Base64Url));
+        assert!(decode(&standard, Alphabet::Base64Url).is_err());
+    }
+
+    #[test]
+    fn should_reject_what_no_encoding_gives() {
+        let base64 = [
+            ("Z", DecodeError::InvalidLength),
+            ("Zm9vY", DecodeError::InvalidLength),
+            ("Zg=", DecodeError::InvalidPadding),
+            ("Zg===", DecodeError::InvalidPadding),
+            ("Zm9v====", DecodeError::InvalidPadding),
+            ("Zg==Zg==", DecodeError::InvalidPadding),
+            ("Zm9v YmFy", DecodeError::InvalidSymbol { symbol: ' ', offset: 4 }),
+        ];
+        for (text, error) in &base64 {
+            assert_eq!(Err(error.clone()), decode(text, Alphabet::Base64), "{}", text);
+        }
+        for text in &["MZX", "MZXW6Y", "MY=====", "mzxw6==="] {
+            assert!(decode(text, Alphabet::Base32).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn should_encode_and_decode_streams() {
+        let bytes: Vec<u8> = (0..10_000u32).map(|i| (i * 37 % 256) as u8).collect();
+        for &alphabet in &[Alphabet::Base64, Alphabet::Base64Url, Alphabet::Base32] {
+            for piece in &[1, 2, 7, 4096] {
+                let mut encoder = Encoder::new(Vec::new(), alphabet);
+                for chunk in bytes.chunks(*piece) {
+                    encoder.write_all(chunk).unwrap();
+                }
+                let text = encoder.finish().unwrap();
+                assert_eq!(encode(&bytes, alphabet).into_bytes(), text);
+                let wrapped: Vec<u8> = text.chunks(76).flat_map(|line| line.iter().cloned().chain(b"\r\n".iter().cloned())).collect();
+                let mut decoded = Vec::new();
+                Decoder::new(&wrapped[..], alphabet).read_to_end(&mut decoded).unwrap();
+                assert_eq!(bytes, decoded);
+            }
+        }
+        let error = Decoder::new("Zm9v\nYm!y".as_bytes(), Alphabet::Base64).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert_eq!("Invalid character '!' at byte 7", error.to_string());
+        let error = Decoder::new("Zm9vY".as_bytes(), Alphabet::Base64).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+}
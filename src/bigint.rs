@@ -0,0 +1,745 @@
+//!
+//! # Problem description:
+//! #
+//! # Big Integers
+//!
+//! Integers of any size, stored as a sign and a magnitude of 32-bit
+//! digits, least significant first. Addition and subtraction work digit
+//! by digit with a carry, as by hand, and division is Knuth's Algorithm
+//! D, which guesses each quotient digit from the top digits of the
+//! dividend and divisor and is never off by more than two.
+//!
+//! Schoolbook multiplication takes n² digit products. Karatsuba's method
+//! splits each factor in two halves, x = x1 B + x0, and gets the three
+//! products x1 y1, x0 y0 and (x1 + x0)(y1 + y0) where four seemed needed,
+//! for O(n^1.585) work. Toom-3 splits in thirds, evaluates both factors
+//! as polynomials at five points (0, 1, -1, -2 and infinity), multiplies
+//! the values and interpolates, for five products of a third of the size
+//! and O(n^1.465). The recursions cost more per level, so each method
+//! only pays off above a size threshold, measured by the `bigint`
+//! benchmark.
+//!
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::result;
+use std::str::FromStr;
+
+/// Errors parsing an integer
+#[derive(Debug, Clone, PartialEq)]
+pub enum BigIntError {
+    /// The text is not a decimal integer
+    Syntax(String),
+}
+
+impl fmt::Display for BigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BigIntError::Syntax(ref text) => write!(f, "'{}' is not an integer", text),
+        }
+    }
+}
+
+/// The result of parsing an integer
+pub type BigIntResult<T> = result::Result<T, BigIntError>;
+
+/// An integer of any size
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    /// Base 2^32 digits, least significant first, without leading zeros
+    digits: Vec<u32>,
+}
+
+/// Factors with fewer digits than this are multiplied by the schoolbook
+/// method; the benchmark has the two methods level at 56 to 64 digits
+pub const KARATSUBA_THRESHOLD: usize = 56;
+
+/// Factors with at least this many digits are multiplied by Toom-3; the
+/// benchmark has it overtaking Karatsuba's method between 160 and 192
+/// digits, with the two close up to 512 and Toom-3 well ahead by 1024
+pub const TOOM3_THRESHOLD: usize = 192;
+
+/// The largest power of ten in a digit, for decimal conversions
+const DECIMAL_BASE: u32 = 1_000_000_000;
+const DECIMAL_DIGITS: usize = 9;
+
+impl BigInt {
+    /// Zero
+    pub fn zero() -> BigInt {
+        BigInt { negative: false, digits: Vec::new() }
+    }
+
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> BigInt {
+        trim(&mut digits);
+        BigInt { negative: negative && !digits.is_empty(), digits }
+    }
+
+    /// Whether the number is zero
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Whether the number is less than zero
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The absolute value
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, digits: self.digits.clone() }
+    }
+
+    /// Number of bits in the absolute value
+    pub fn bits(&self) -> u64 {
+        match self.digits.last() {
+            Some(&top) => 32 * self.digits.len() as u64 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// The value as an `i64`, if it fits
+    pub fn to_i64(&self) -> Option<i64> {
+        let magnitude = self.to_u64_magnitude()?;
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            if magnitude <= i64::MAX as u64 { Some(magnitude as i64) } else { None }
+        }
+    }
+
+    /// The value as a `u64`, if it fits
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.negative { None } else { self.to_u64_magnitude() }
+    }
+
+    fn to_u64_magnitude(&self) -> Option<u64> {
+        match self.digits.len() {
+            0 => Some(0),
+            1 => Some(self.digits[0] as u64),
+            2 => Some((self.digits[1] as u64) << 32 | self.digits[0] as u64),
+            _ => None,
+        }
+    }
+
+    /// The nearest `f64`, or an infinity if it is too large
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.digits.iter().rev().fold(0.0, |acc, &d| acc * 4294967296.0 + d as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    /// Raises the number to the power `exponent` by repeated squaring
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::bigint::BigInt;
+    ///
+    /// assert_eq!("1267650600228229401496703205376", BigInt::from(2).pow(100).to_string());
+    /// ```
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::from(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// The quotient rounded towards zero and the remainder, which takes
+    /// the sign of the dividend, as for the built-in integers
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::bigint::BigInt;
+    ///
+    /// let (q, r) = BigInt::from(-7).div_rem(&BigInt::from(2));
+    /// assert_eq!((BigInt::from(-3), BigInt::from(-1)), (q, r));
+    /// ```
+    pub fn div_rem(&self, divisor: &BigInt) -> (BigInt, BigInt) {
+        assert!(!divisor.is_zero(), "Division by zero");
+        let (q, r) = div_rem_magnitudes(&self.digits, &divisor.digits);
+        (BigInt::from_parts(self.negative != divisor.negative, q), BigInt::from_parts(self.negative, r))
+    }
+
+    /// The product by the schoolbook method alone
+    pub fn mul_schoolbook(&self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_schoolbook(&self.digits, &other.digits))
+    }
+
+    /// The product by Karatsuba's method at the top level, with the
+    /// smaller products done by whatever method suits their size
+    pub fn mul_karatsuba(&self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_karatsuba(&self.digits, &other.digits))
+    }
+
+    /// The product by Toom-3 at the top level, with the smaller products
+    /// done by whatever method suits their size
+    pub fn mul_toom3(&self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_toom3(&self.digits, &other.digits))
+    }
+
+    /// The greatest common divisor, never negative
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+}
+
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &x) in long.iter().enumerate() {
+        let s = x as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(s as u32);
+        carry = s >> 32;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+/// `a - b`, where `a` is at least `b`
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &x) in a.iter().enumerate() {
+        let mut d = x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if d < 0 {
+            d += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(d as u32);
+    }
+    trim(&mut difference);
+    difference
+}
+
+/// Multiplies by the method suited to the sizes of the factors
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    if short.len() < KARATSUBA_THRESHOLD {
+        return mul_schoolbook(long, short);
+    }
+    if long.len() >= 2 * short.len() {
+        return mul_unbalanced(long, short);
+    }
+    if short.len() < TOOM3_THRESHOLD {
+        mul_karatsuba(long, short)
+    } else {
+        mul_toom3(long, short)
+    }
+}
+
+fn mul_schoolbook(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = x as u64 * y as u64 + product[i + j] as u64 + carry;
+            product[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    trim(&mut product);
+    product
+}
+
+/// Multiplies a long factor by a short one a slice of the long one at a
+/// time, so the splitting methods get factors of similar sizes
+fn mul_unbalanced(long: &[u32], short: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; long.len() + short.len()];
+    for (i, chunk) in long.chunks(short.len()).enumerate() {
+        add_at(&mut product, &mul_magnitudes(chunk, short), i * short.len());
+    }
+    trim(&mut product);
+    product
+}
+
+/// Adds `digits` into `sum` starting at digit `offset`; `sum` must be
+/// long enough for the result
+fn add_at(sum: &mut [u32], digits: &[u32], offset: usize) {
+    let mut carry = 0u64;
+    let mut i = 0;
+    while i < digits.len() || carry > 0 {
+        let t = sum[offset + i] as u64 + *digits.get(i).unwrap_or(&0) as u64 + carry;
+        sum[offset + i] = t as u32;
+        carry = t >> 32;
+        i += 1;
+    }
+}
+
+fn split_at(digits: &[u32], at: usize) -> (Vec<u32>, Vec<u32>) {
+    let at = at.min(digits.len());
+    let mut low = digits[..at].to_vec();
+    trim(&mut low);
+    (low, digits[at..].to_vec())
+}
+
+/// Karatsuba's method at the top level, the parts multiplied by whatever
+/// suits their size
+fn mul_karatsuba(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let half = a.len().max(b.len()) / 2;
+    let (a0, a1) = split_at(a, half);
+    let (b0, b1) = split_at(b, half);
+    let low = mul_magnitudes(&a0, &b0);
+    let high = mul_magnitudes(&a1, &b1);
+    let cross = mul_magnitudes(&add_magnitudes(&a0, &a1), &add_magnitudes(&b0, &b1));
+    let middle = sub_magnitudes(&sub_magnitudes(&cross, &low), &high);
+    let mut product = vec![0u32; a.len() + b.len() + 1];
+    add_at(&mut product, &low, 0);
+    add_at(&mut product, &middle, half);
+    add_at(&mut product, &high, 2 * half);
+    trim(&mut product);
+    product
+}
+
+/// Toom-3 at the top level, with Bodrato's interpolation sequence
+fn mul_toom3(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let k = a.len().max(b.len()).div_ceil(3);
+    let parts = |x: &[u32]| {
+        let (x0, rest) = split_at(x, k);
+        let (x1, x2) = split_at(&rest, k);
+        (BigInt::from_parts(false, x0), BigInt::from_parts(false, x1), BigInt::from_parts(false, x2))
+    };
+    let (a0, a1, a2) = parts(a);
+    let (b0, b1, b2) = parts(b);
+    // Values at 0, 1, -1, -2 and infinity
+    let evaluate = |x0: &BigInt, x1: &BigInt, x2: &BigInt| {
+        let even = x0 + x2;
+        let at_minus_1 = &even - x1;
+        let at_minus_2 = &(&(&at_minus_1 + x2) * &BigInt::from(2)) - x0;
+        (x0.clone(), &even + x1, at_minus_1, at_minus_2, x2.clone())
+    };
+    let (p0, p1, pm1, pm2, pinf) = evaluate(&a0, &a1, &a2);
+    let (q0, q1, qm1, qm2, qinf) = evaluate(&b0, &b1, &b2);
+    let r0 = &p0 * &q0;
+    let r1 = &p1 * &q1;
+    let rm1 = &pm1 * &qm1;
+    let rm2 = &pm2 * &qm2;
+    let r4 = &pinf * &qinf;
+    // Interpolation; every division is exact
+    let (two, three) = (BigInt::from(2), BigInt::from(3));
+    let mut c3 = &(&rm2 - &r1) / &three;
+    let mut c1 = &(&r1 - &rm1) / &two;
+    let mut c2 = &rm1 - &r0;
+    c3 = &(&(&c2 - &c3) / &two) + &(&r4 * &two);
+    c2 = &(&c2 + &c1) - &r4;
+    c1 = &c1 - &c3;
+    let mut product = vec![0u32; a.len() + b.len() + 1];
+    for (i, c) in [r0, c1, c2, c3, r4].iter().enumerate() {
+        debug_assert!(!c.is_negative());
+        add_at(&mut product, &c.digits, i * k);
+    }
+    trim(&mut product);
+    product
+}
+
+/// Multiplies by `factor` and adds `addend` in place
+fn mul_add_small(digits: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for d in digits.iter_mut() {
+        let t = *d as u64 * factor as u64 + carry;
+        *d = t as u32;
+        carry = t >> 32;
+    }
+    if carry > 0 {
+        digits.push(carry as u32);
+    }
+}
+
+fn div_rem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (i, &d) in a.iter().enumerate().rev() {
+        let t = remainder << 32 | d as u64;
+        quotient[i] = (t / divisor as u64) as u32;
+        remainder = t % divisor as u64;
+    }
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+/// Shifts left by `shift` bits, less than 32, adding one digit on top
+fn shift_left(digits: &[u32], shift: u32) -> Vec<u32> {
+    let mut shifted = Vec::with_capacity(digits.len() + 1);
+    let mut carry = 0u32;
+    for &d in digits {
+        shifted.push(d << shift | carry);
+        carry = if shift == 0 { 0 } else { d >> (32 - shift) };
+    }
+    shifted.push(carry);
+    shifted
+}
+
+fn shift_right(digits: &[u32], shift: u32) -> Vec<u32> {
+    let mut shifted = vec![0u32; digits.len()];
+    for i in 0..digits.len() {
+        let high = if shift == 0 { 0 } else { digits.get(i + 1).map_or(0, |&d| d << (32 - shift)) };
+        shifted[i] = digits[i] >> shift | high;
+    }
+    trim(&mut shifted);
+    shifted
+}
+
+/// Knuth's Algorithm D
+fn div_rem_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitudes(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let (q, r) = div_rem_small(a, b[0]);
+        let mut r = vec![r];
+        trim(&mut r);
+        return (q, r);
+    }
+    // Normalize so the divisor's top digit has its high bit set
+    let shift = b[b.len() - 1].leading_zeros();
+    let mut v = shift_left(b, shift);
+    v.pop();
+    let mut u = shift_left(a, shift);
+    let n = v.len();
+    let m = u.len() - n;
+    let mut quotient = vec![0u32; m];
+    let base = 1u64 << 32;
+    for j in (0..m).rev() {
+        let top = (u[j + n] as u64) << 32 | u[j + n - 1] as u64;
+        let mut qhat = top / v[n - 1] as u64;
+        let mut rhat = top % v[n - 1] as u64;
+        while qhat >= base || qhat * v[n - 2] as u64 > (rhat << 32 | u[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += v[n - 1] as u64;
+            if rhat >= base {
+                break;
+            }
+        }
+        // Multiply and subtract
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let p = qhat * v[i] as u64 + carry;
+            carry = p >> 32;
+            let t = u[i + j] as i64 - borrow - (p & 0xffff_ffff) as i64;
+            u[i + j] = t as u32;
+            borrow = if t < 0 { 1 } else { 0 };
+        }
+        let t = u[j + n] as i64 - borrow - carry as i64;
+        u[j + n] = t as u32;
+        // The guess was one too large: add the divisor back
+        if t < 0 {
+            qhat -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let s = u[i + j] as u64 + v[i] as u64 + carry;
+                u[i + j] = s as u32;
+                carry = s >> 32;
+            }
+            u[j + n] = u[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = qhat as u32;
+    }
+    trim(&mut quotient);
+    (quotient, shift_right(&u[..n], shift))
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> BigInt {
+        let mut b = BigInt::from(n.unsigned_abs());
+        b.negative = n < 0;
+        b
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(n: u64) -> BigInt {
+        BigInt::from_parts(false, vec![n as u32, (n >> 32) as u32])
+    }
+}
+
+impl From<i32> for BigInt {
+    fn from(n: i32) -> BigInt {
+        BigInt::from(n as i64)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.digits, &other.digits),
+            (true, true) => compare_magnitudes(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.digits.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.digits)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.digits, &other.digits));
+        }
+        match compare_magnitudes(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_magnitudes(&other.digits, &self.digits)),
+            _ => BigInt::from_parts(self.negative, sub_magnitudes(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_magnitudes(&self.digits, &other.digits))
+    }
+}
+
+impl Div for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).1
+    }
+}
+
+macro_rules! forward_binop {
+    ($trait:ident, $method:ident) => {
+        impl $trait for BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: BigInt) -> BigInt {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+forward_binop!(Add, add);
+forward_binop!(Sub, sub);
+forward_binop!(Mul, mul);
+forward_binop!(Div, div);
+forward_binop!(Rem, rem);
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut chunks = Vec::new();
+        let mut rest = self.digits.clone();
+        while !rest.is_empty() {
+            let (q, r) = div_rem_small(&rest, DECIMAL_BASE);
+            chunks.push(r);
+            rest = q;
+        }
+        let mut text = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            text.push_str(&format!("{:0w$}", chunk, w = DECIMAL_DIGITS));
+        }
+        f.pad_integral(!self.negative, "", &text)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = BigIntError;
+
+    /// Parses a decimal integer with an optional sign
+    fn from_str(s: &str) -> BigIntResult<BigInt> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(BigIntError::Syntax(s.to_string()));
+        }
+        let mut magnitude = Vec::new();
+        let first = digits.len() % DECIMAL_DIGITS;
+        let mut start = 0;
+        for end in (first..=digits.len()).step_by(DECIMAL_DIGITS).filter(|&e| e > 0) {
+            let chunk = &digits[start..end];
+            mul_add_small(&mut magnitude, 10u32.pow(chunk.len() as u32), chunk.parse().unwrap());
+            start = end;
+        }
+        Ok(BigInt::from_parts(negative, magnitude))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    fn random(rng: &mut Rng, digits: usize) -> BigInt {
+        let magnitude: Vec<u32> = (0..digits).map(|_| rng.next_u32()).collect();
+        BigInt::from_parts(rng.next_u32() & 1 == 1, magnitude)
+    }
+
+    #[test]
+    fn should_agree_with_i128() {
+        let mut rng = Rng::new(5);
+        for _ in 0..2000 {
+            let a = rng.next_u64() as i64 as i128 >> (rng.next_u32() % 64);
+            let b = rng.next_u64() as i64 as i128 >> (rng.next_u32() % 64);
+            let (x, y) = (BigInt::from(a as i64), BigInt::from(b as i64));
+            assert_eq!((a + b).to_string(), (&x + &y).to_string());
+            assert_eq!((a - b).to_string(), (&x - &y).to_string());
+            assert_eq!((a * b).to_string(), (&x * &y).to_string());
+            if b != 0 {
+                assert_eq!((a / b).to_string(), (&x / &y).to_string());
+                assert_eq!((a % b).to_string(), (&x % &y).to_string());
+            }
+            assert_eq!(a.cmp(&b), x.cmp(&y));
+        }
+    }
+
+    #[test]
+    fn should_divide_large_numbers() {
+        let mut rng = Rng::new(6);
+        for &(m, n) in &[(2, 2), (5, 2), (8, 3), (20, 7), (40, 39), (3, 5)] {
+            for _ in 0..50 {
+                let a = random(&mut rng, m);
+                let b = random(&mut rng, n);
+                if b.is_zero() {
+                    continue;
+                }
+                let (q, r) = a.div_rem(&b);
+                assert_eq!(a, &(&q * &b) + &r);
+                assert!(r.abs() < b.abs());
+                assert!(r.is_zero() || r.is_negative() == a.is_negative());
+            }
+        }
+    }
+
+    #[test]
+    fn should_handle_rare_correction_step() {
+        // Divisions where the first estimated quotient digit is too large
+        let a = big("340282366920938463463374607431768211455");
+        let b = big("18446744073709551617");
+        assert_eq!((big("18446744073709551615"), BigInt::zero()), a.div_rem(&b));
+        let a = BigInt::from_parts(false, vec![0, 0, 0x8000_0000, 0x7fff_ffff]);
+        let b = BigInt::from_parts(false, vec![1, 0, 0x8000_0000]);
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(a, &(&q * &b) + &r);
+        assert!(r < b);
+    }
+
+    #[test]
+    fn should_multiply_alike_by_every_method() {
+        let mut rng = Rng::new(7);
+        for &(m, n) in &[(1, 1), (3, 50), (45, 45), (100, 70), (250, 250), (600, 450), (1000, 90), (2, 700)] {
+            let a = random(&mut rng, m);
+            let b = random(&mut rng, n);
+            let expected = a.mul_schoolbook(&b);
+            assert_eq!(expected, a.mul_karatsuba(&b), "{} by {}", m, n);
+            assert_eq!(expected, a.mul_toom3(&b), "{} by {}", m, n);
+            assert_eq!(expected, &a * &b, "{} by {}", m, n);
+        }
+        // All-ones digits stress the carries
+        let ones = BigInt::from_parts(false, vec![u32::MAX; 700]);
+        assert_eq!(ones.mul_schoolbook(&ones), ones.mul_toom3(&ones));
+        assert_eq!(ones.mul_schoolbook(&ones), ones.mul_karatsuba(&ones));
+        assert!(ones.mul_toom3(&BigInt::zero()).is_zero());
+    }
+
+    #[test]
+    fn should_parse_and_format() {
+        for s in &["0", "1", "-1", "999999999", "1000000000", "-123456789012345678901234567890", "4294967296"] {
+            assert_eq!(*s, big(s).to_string());
+        }
+        assert_eq!("0", big("-0").to_string());
+        assert_eq!("7", big("+007").to_string());
+        assert_eq!(Err(BigIntError::Syntax("12a".to_string())), "12a".parse::<BigInt>());
+        assert_eq!(Err(BigIntError::Syntax("-".to_string())), "-".parse::<BigInt>());
+    }
+
+    #[test]
+    fn should_compute_powers_and_gcd() {
+        assert_eq!(big("93326215443944152681699238856266700490715968264381621468592963895217599993229915608941463976156518286253697920827223758251185210916864000000000000000000000000"),
+                   (1..=100).fold(BigInt::from(1), |acc, i| &acc * &BigInt::from(i)));
+        assert_eq!(BigInt::from(1), BigInt::from(-5).pow(0));
+        assert_eq!(BigInt::from(-125), BigInt::from(-5).pow(3));
+        let a = &BigInt::from(2).pow(100) * &BigInt::from(3).pow(50);
+        let b = &BigInt::from(2).pow(60) * &BigInt::from(5).pow(40);
+        assert_eq!(BigInt::from(2).pow(60), a.gcd(&-&b));
+        assert_eq!(Some(i64::MIN), BigInt::from(i64::MIN).to_i64());
+        assert_eq!(None, BigInt::from(u64::MAX).to_i64());
+        assert_eq!(101, BigInt::from(2).pow(100).bits());
+    }
+}
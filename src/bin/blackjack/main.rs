@@ -0,0 +1,42 @@
+extern crate pprust;
+use pprust::blackjack::{basic_strategy, dealer_strategy, simulate, Rules, Strategy};
+use std::env;
+use std::process;
+
+const USAGE: &str = "Usage: blackjack [-n HANDS] [--decks N] [--h17] [--mimic] [--seed N]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("blackjack: {}", message);
+    process::exit(2);
+}
+
+fn number<T: std::str::FromStr>(args: &mut dyn Iterator<Item = String>, what: &str) -> T {
+    let value = args.next().unwrap_or_else(|| fail(USAGE));
+    value.parse().unwrap_or_else(|_| fail(&format!("invalid {}: {}", what, value)))
+}
+
+fn main() {
+    let mut rules = Rules::default();
+    let mut hands = 1_000_000;
+    let mut seed = 1;
+    let mut strategy: Strategy = basic_strategy;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "-n" => hands = number(&mut args, "number of hands"),
+            "--decks" => rules.decks = number(&mut args, "number of decks"),
+            "--seed" => seed = number(&mut args, "seed"),
+            "--h17" => rules.dealer_hits_soft_17 = true,
+            "--mimic" => strategy = dealer_strategy,
+            _ => fail(USAGE),
+        }
+    }
+    if rules.decks == 0 || hands == 0 {
+        fail(USAGE);
+    }
+
+    let report = simulate(strategy, &rules, hands, seed);
+    println!("Hands played: {}", report.hands);
+    println!("Player net:   {:+.1} units", report.net);
+    println!("House edge:   {:.3}%", 100.0 * report.house_edge());
+}
@@ -0,0 +1,61 @@
+extern crate pprust;
+use pprust::checksum::{Adler32, Crc32};
+use pprust::digest::{digest_reader, hex, Digest, Md5, Sha1};
+use std::env;
+use std::fs::File;
+use std::io;
+use std::process;
+
+const USAGE: &str = "Usage: checksum [--md5 | --sha1 | --crc32 | --adler32] [FILE]...";
+
+fn fail(message: &str) -> ! {
+    eprintln!("checksum: {}", message);
+    process::exit(2);
+}
+
+/// The digest of a file, or of standard input for `-`
+fn digest_file<D: Digest>(path: &str) -> io::Result<Vec<u8>> {
+    if path == "-" {
+        digest_reader::<D, _>(io::stdin().lock())
+    } else {
+        digest_reader::<D, _>(io::BufReader::new(File::open(path)?))
+    }
+}
+
+fn main() {
+    let mut algorithm = "sha1".to_string();
+    let mut paths = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--md5" | "--sha1" | "--crc32" | "--adler32" => algorithm = arg[2..].to_string(),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with("--") => fail(&format!("unknown argument {}\n{}", arg, USAGE)),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.is_empty() {
+        paths.push("-".to_string());
+    }
+    let mut failed = false;
+    for path in &paths {
+        let digest = match algorithm.as_str() {
+            "md5" => digest_file::<Md5>(path),
+            "crc32" => digest_file::<Crc32>(path),
+            "adler32" => digest_file::<Adler32>(path),
+            _ => digest_file::<Sha1>(path),
+        };
+        match digest {
+            Ok(digest) => println!("{}  {}", hex(&digest), path),
+            Err(e) => {
+                eprintln!("checksum: {}: {}", path, e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,75 @@
+extern crate pprust;
+use pprust::connect4::{Engine, Position, WIDTH};
+use std::env;
+use std::io::prelude::*;
+use std::io;
+use std::process;
+
+const USAGE: &str = "Usage: connect4 [-d DEPTH] [--computer-first]";
+const DEFAULT_DEPTH: u32 = 8;
+
+fn fail(message: &str) -> ! {
+    eprintln!("connect4: {}", message);
+    process::exit(2);
+}
+
+fn read_move(position: &Position) -> Option<usize> {
+    let stdin = io::stdin();
+    loop {
+        print!("Your move (1-{}, q to quit): ", WIDTH);
+        io::stdout().flush().expect("Could not flush stdout");
+        let mut input = String::new();
+        if stdin.lock().read_line(&mut input).expect("Could not read stdin") == 0 {
+            return None;
+        }
+        match input.trim() {
+            "q" => return None,
+            col => match col.parse::<usize>() {
+                Ok(n) if n >= 1 && position.can_play(n - 1) => return Some(n - 1),
+                _ => println!("Pick a column that is not full."),
+            },
+        }
+    }
+}
+
+fn main() {
+    let mut depth = DEFAULT_DEPTH;
+    let mut computer_first = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "-d" => {
+                let value = args.next().unwrap_or_else(|| fail(USAGE));
+                depth = value.parse().ok().filter(|&d| d > 0).unwrap_or_else(|| fail(&format!("invalid depth: {}", value)));
+            }
+            "--computer-first" => computer_first = true,
+            _ => fail(USAGE),
+        }
+    }
+
+    let mut engine = Engine::new(depth);
+    let mut position = Position::new();
+    let computer_parity = if computer_first { 0 } else { 1 };
+    while !position.is_over() {
+        println!("{}", position);
+        let col = if position.moves() % 2 == computer_parity {
+            let col = engine.choose(&position).best.expect("Game is not over");
+            println!("Computer plays {}.", col + 1);
+            col
+        } else {
+            match read_move(&position) {
+                Some(col) => col,
+                None => return,
+            }
+        };
+        position = position.play(col).expect("Move was checked");
+    }
+    println!("{}", position);
+    if !position.last_player_won() {
+        println!("Draw.");
+    } else if position.moves() % 2 == 1 - computer_parity {
+        println!("Computer wins.");
+    } else {
+        println!("You win.");
+    }
+}
@@ -0,0 +1,108 @@
+//! A toy Diffie-Hellman exchange between two processes talking over
+//! pipes, for learning only: the keys are far too small to be secure.
+//!
+//! The program plays Alice. It starts a second copy of itself as Bob,
+//! sends the public parameters and its public key down Bob's standard
+//! input and reads Bob's public key back from his standard output. Each
+//! side prints the secret it derived, which should be the same.
+
+extern crate pprust;
+use pprust::dh::{KeyPair, Params, MAX_BITS};
+use pprust::rng::Rng;
+use std::env;
+use std::io::prelude::*;
+use std::io::{self, BufReader};
+use std::process::{self, Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "Usage: dh [--bits N] [--seed N]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("dh: {}", message);
+    process::exit(2);
+}
+
+fn number<T: std::str::FromStr>(args: &mut dyn Iterator<Item = String>, what: &str) -> T {
+    let value = args.next().unwrap_or_else(|| fail(USAGE));
+    value.parse().unwrap_or_else(|_| fail(&format!("invalid {}: {}", what, value)))
+}
+
+fn parse_numbers(line: &str, count: usize) -> Vec<u64> {
+    let numbers: Vec<u64> = line.split_whitespace().map(|w| w.parse().ok()).collect::<Option<_>>()
+        .unwrap_or_else(|| fail(&format!("garbled message: {}", line.trim())));
+    if numbers.len() != count {
+        fail(&format!("garbled message: {}", line.trim()));
+    }
+    numbers
+}
+
+fn alice(bits: u32, seed: u64) {
+    let mut rng = Rng::new(seed);
+    let params = Params::generate(bits, &mut rng);
+    let keys = KeyPair::generate(&params, &mut rng);
+    println!("alice: p = {}, g = {}", params.p, params.g);
+    println!("alice: sending public key {}", keys.public);
+
+    let exe = env::current_exe().unwrap_or_else(|e| fail(&format!("cannot find myself: {}", e)));
+    let mut bob = Command::new(exe)
+        .args(["--bob", "--seed", &(seed ^ 0x9e37_79b9_7f4a_7c15).to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| fail(&format!("cannot start Bob: {}", e)));
+    {
+        let to_bob = bob.stdin.as_mut().expect("Bob's input is piped");
+        writeln!(to_bob, "{} {} {}", params.p, params.g, keys.public).unwrap_or_else(|e| fail(&e.to_string()));
+    }
+    let mut reply = String::new();
+    BufReader::new(bob.stdout.take().expect("Bob's output is piped")).read_line(&mut reply)
+        .unwrap_or_else(|e| fail(&e.to_string()));
+    let bob_public = parse_numbers(&reply, 1)[0];
+    println!("alice: received public key {}", bob_public);
+    match keys.shared_secret(&params, bob_public) {
+        Some(secret) => println!("alice: shared secret {}", secret),
+        None => fail("Bob's public key is not in the group"),
+    }
+    bob.wait().unwrap_or_else(|e| fail(&e.to_string()));
+}
+
+fn bob(seed: u64) {
+    let mut message = String::new();
+    io::stdin().read_line(&mut message).unwrap_or_else(|e| fail(&e.to_string()));
+    let numbers = parse_numbers(&message, 3);
+    let params = Params { p: numbers[0], g: numbers[1] };
+    if !params.is_valid() {
+        fail("bad parameters from Alice");
+    }
+    let keys = KeyPair::generate(&params, &mut Rng::new(seed));
+    println!("{}", keys.public);
+    io::stdout().flush().unwrap_or_else(|e| fail(&e.to_string()));
+    match keys.shared_secret(&params, numbers[2]) {
+        Some(secret) => eprintln!("bob:   shared secret {}", secret),
+        None => fail("Alice's public key is not in the group"),
+    }
+}
+
+fn main() {
+    let mut bits = 48;
+    let mut seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(1);
+    let mut is_bob = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--bits" => bits = number(&mut args, "number of bits"),
+            "--seed" => seed = number(&mut args, "seed"),
+            "--bob" => is_bob = true,
+            _ => fail(USAGE),
+        }
+    }
+    if !(8..=MAX_BITS).contains(&bits) {
+        fail(&format!("the prime must have 8 to {} bits", MAX_BITS));
+    }
+    if is_bob {
+        bob(seed);
+    } else {
+        println!("Toy Diffie-Hellman, for learning only: these keys are not secure.");
+        alice(bits, seed);
+    }
+}
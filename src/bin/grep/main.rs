@@ -0,0 +1,125 @@
+extern crate pprust;
+use pprust::regex_lite::Regex;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::process;
+
+const USAGE: &str = "Usage: grep [-n] [-v] [-i] [-c] PATTERN [FILE...]";
+
+struct Options {
+    line_numbers: bool,
+    invert: bool,
+    ignore_case: bool,
+    count: bool,
+    pattern: String,
+    files: Vec<String>,
+}
+
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut options = Options {
+        line_numbers: false,
+        invert: false,
+        ignore_case: false,
+        count: false,
+        pattern: String::new(),
+        files: Vec::new(),
+    };
+    let mut pattern = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if pattern.is_none() && arg == "--" {
+            pattern = args.next();
+        } else if pattern.is_none() && arg.starts_with('-') && arg.len() > 1 {
+            for flag in arg.chars().skip(1) {
+                match flag {
+                    'n' => options.line_numbers = true,
+                    'v' => options.invert = true,
+                    'i' => options.ignore_case = true,
+                    'c' => options.count = true,
+                    _ => return Err(format!("Unknown flag -{}", flag)),
+                }
+            }
+        } else if pattern.is_none() {
+            pattern = Some(arg);
+        } else {
+            options.files.push(arg);
+        }
+    }
+    options.pattern = pattern.ok_or_else(|| "Missing pattern".to_string())?;
+    Ok(options)
+}
+
+/// Prints the matching lines of one input and returns how many matched
+fn grep<R: BufRead>(input: R, name: Option<&str>, regex: &Regex, options: &Options) -> io::Result<usize> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut count = 0;
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        if regex.is_match(&line) == options.invert {
+            continue;
+        }
+        count += 1;
+        if options.count {
+            continue;
+        }
+        if let Some(name) = name {
+            write!(out, "{}:", name)?;
+        }
+        if options.line_numbers {
+            write!(out, "{}:", i + 1)?;
+        }
+        writeln!(out, "{}", line)?;
+    }
+    if options.count {
+        match name {
+            Some(name) => writeln!(out, "{}:{}", name, count)?,
+            None => writeln!(out, "{}", count)?,
+        }
+    }
+    Ok(count)
+}
+
+fn main() {
+    let options = parse_args(env::args().skip(1).collect()).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(2);
+    });
+    let compiled = if options.ignore_case {
+        Regex::case_insensitive(&options.pattern)
+    } else {
+        Regex::new(&options.pattern)
+    };
+    let regex = compiled.unwrap_or_else(|e| {
+        eprintln!("grep: invalid pattern: {}", e);
+        process::exit(2);
+    });
+
+    let mut matched = false;
+    let mut failed = false;
+    if options.files.is_empty() {
+        let stdin = io::stdin();
+        match grep(stdin.lock(), None, &regex, &options) {
+            Ok(n) => matched |= n > 0,
+            Err(e) => {
+                eprintln!("grep: {}", e);
+                failed = true;
+            }
+        }
+    }
+    let show_names = options.files.len() > 1;
+    for file in &options.files {
+        let name = if show_names { Some(file.as_str()) } else { None };
+        let result = fs::File::open(file).and_then(|f| grep(io::BufReader::new(f), name, &regex, &options));
+        match result {
+            Ok(n) => matched |= n > 0,
+            Err(e) => {
+                eprintln!("grep: {}: {}", file, e);
+                failed = true;
+            }
+        }
+    }
+    process::exit(if failed { 2 } else if matched { 0 } else { 1 });
+}
@@ -0,0 +1,156 @@
+extern crate pprust;
+use pprust::hangman::{random_word, Game, Guesser, Outcome, Status, HIDDEN, MAX_MISSES};
+use pprust::rng::Rng;
+use std::env;
+use std::fs;
+use std::io::prelude::*;
+use std::io;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "Usage: hangman [--guess] [--words FILE] [--seed N]";
+
+/// Where the words come from without `--words`
+const DEFAULT_WORDS: &str = "/usr/share/dict/words";
+
+/// The shortest word the computer picks
+const MIN_LENGTH: usize = 5;
+
+fn fail(message: &str) -> ! {
+    eprintln!("hangman: {}", message);
+    process::exit(2);
+}
+
+fn prompt(message: &str) -> Option<String> {
+    print!("{}", message);
+    io::stdout().flush().expect("Could not flush stdout");
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_lowercase()),
+    }
+}
+
+/// The words of a file, one per line, in lower case, leaving out names,
+/// abbreviations and the like
+fn read_words(path: &str) -> Vec<String> {
+    let text = fs::read_to_string(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+    text.lines()
+        .map(str::trim)
+        .filter(|w| !w.is_empty() && w.chars().all(|c| c.is_ascii_lowercase()))
+        .map(String::from)
+        .collect()
+}
+
+/// The computer picks a word and the player guesses
+fn play(words: &[String], seed: u64) {
+    let dictionary: Vec<&str> = words.iter().map(String::as_str).collect();
+    let secret = random_word(&dictionary, MIN_LENGTH, &mut Rng::new(seed))
+        .unwrap_or_else(|| fail(&format!("no word of {} letters or more", MIN_LENGTH)));
+    let mut game = Game::new(secret, MAX_MISSES);
+    println!("I picked a word of {} letters. Guess a letter at a time.", secret.chars().count());
+    while game.status() == Status::Playing {
+        let input = match prompt(&format!("{}  ({} misses left) ", game.pattern(), game.misses_left())) {
+            Some(input) => input,
+            None => return,
+        };
+        let mut letters = input.chars();
+        let letter = match (letters.next(), letters.next()) {
+            (Some(letter), None) if letter.is_ascii_lowercase() => letter,
+            _ => {
+                println!("Guess one letter.");
+                continue;
+            }
+        };
+        match game.guess(letter) {
+            Outcome::Hit(_) => (),
+            Outcome::Miss => println!("No {}.", letter),
+            Outcome::Repeated => println!("You already guessed {}.", letter),
+        }
+    }
+    match game.status() {
+        Status::Won => println!("{}  You got it with {} misses.", game.pattern(), game.misses()),
+        _ => println!("Hanged. The word was {}.", game.secret()),
+    }
+}
+
+/// Whether the pattern the player answered keeps what was revealed before
+/// and only adds the letter guessed
+fn follows(before: &str, after: &str, letter: char) -> bool {
+    before.chars().count() == after.chars().count()
+        && before.chars().zip(after.chars()).all(|(b, a)| a == b || (b == HIDDEN && a == letter))
+}
+
+/// The player thinks of a word and the computer guesses
+fn guess(words: &[String]) {
+    let length = loop {
+        match prompt("Think of a word. How many letters does it have? ") {
+            Some(input) => match input.parse::<usize>() {
+                Ok(length) if length > 0 => break length,
+                _ => println!("Answer with a number."),
+            },
+            None => return,
+        }
+    };
+    let mut guesser = Guesser::new(words, length);
+    let mut revealed: String = (0..length).map(|_| HIDDEN).collect();
+    let mut misses = 0;
+    println!("Answer each guess with the word as it shows, like __a_a_, or just enter for a miss.");
+    while misses < MAX_MISSES && revealed.contains(HIDDEN) {
+        let letter = match guesser.guess() {
+            Some(letter) => letter,
+            None => {
+                println!("I don't know that word.");
+                return;
+            }
+        };
+        let answer = loop {
+            let input = match prompt(&format!("{}  Any {}? ", revealed, letter)) {
+                Some(input) => input,
+                None => return,
+            };
+            let answer = if input.is_empty() { revealed.clone() } else { input };
+            if follows(&revealed, &answer, letter) {
+                break answer;
+            }
+            println!("Show the word as before, with every {} in its place.", letter);
+        };
+        if answer == revealed {
+            misses += 1;
+        }
+        guesser.answer(letter, &answer);
+        revealed = answer;
+    }
+    if revealed.contains(HIDDEN) {
+        println!("You win, I was hanged after {} misses.", misses);
+    } else {
+        println!("It's {}, with {} misses.", revealed, misses);
+    }
+}
+
+fn main() {
+    let mut guess_mode = false;
+    let mut path = DEFAULT_WORDS.to_string();
+    let mut seed = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--guess" => guess_mode = true,
+            "--words" => path = args.next().unwrap_or_else(|| fail(USAGE)),
+            "--seed" => {
+                let value = args.next().unwrap_or_else(|| fail(USAGE));
+                seed = Some(value.parse().unwrap_or_else(|_| fail(&format!("invalid seed: {}", value))));
+            }
+            _ => fail(USAGE),
+        }
+    }
+    let words = read_words(&path);
+    if guess_mode {
+        guess(&words);
+    } else {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+        });
+        play(&words, seed);
+    }
+}
@@ -0,0 +1,127 @@
+extern crate pprust;
+use pprust::mastermind::{random_code, score, Code, Score, Solver, COLORS, PEGS};
+use pprust::rng::Rng;
+use std::env;
+use std::io::prelude::*;
+use std::io;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "Usage: mastermind [--solve] [--seed N]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("mastermind: {}", message);
+    process::exit(2);
+}
+
+fn prompt(message: &str) -> Option<String> {
+    print!("{}", message);
+    io::stdout().flush().expect("Could not flush stdout");
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
+fn show(code: &Code) -> String {
+    code.iter().map(|&c| (c + 1).to_string()).collect()
+}
+
+fn parse_code(input: &str) -> Option<Code> {
+    let digits: Vec<u8> = input.chars().map(|c| c.to_digit(10).map(|d| d as u8)).collect::<Option<_>>()?;
+    if digits.len() != PEGS || digits.iter().any(|&d| d == 0 || d > COLORS) {
+        return None;
+    }
+    let mut code = [0; PEGS];
+    for (peg, d) in code.iter_mut().zip(digits) {
+        *peg = d - 1;
+    }
+    Some(code)
+}
+
+fn parse_score(input: &str) -> Option<Score> {
+    let numbers: Vec<u8> = input.split_whitespace().map(|w| w.parse().ok()).collect::<Option<_>>()?;
+    match numbers[..] {
+        [black, white] if (black + white) as usize <= PEGS => Some(Score { black, white }),
+        _ => None,
+    }
+}
+
+/// The computer picks a secret and the player guesses
+fn play(seed: u64) {
+    let secret = random_code(&mut Rng::new(seed));
+    println!("I picked {} pegs of colours 1 to {}. Guess them, like 1234.", PEGS, COLORS);
+    for turn in 1.. {
+        let guess = loop {
+            let input = match prompt(&format!("Guess {}: ", turn)) {
+                Some(input) => input,
+                None => return,
+            };
+            match parse_code(&input) {
+                Some(code) => break code,
+                None => println!("A guess is {} digits from 1 to {}.", PEGS, COLORS),
+            }
+        };
+        let s = score(&secret, &guess);
+        if s.black as usize == PEGS {
+            println!("Right in {} guesses.", turn);
+            return;
+        }
+        println!("{} black, {} white", s.black, s.white);
+    }
+}
+
+/// The player picks a secret and the computer guesses
+fn solve() {
+    println!("Think of {} pegs of colours 1 to {}. Answer each guess with black and white counts, like 1 2.", PEGS, COLORS);
+    let mut solver = Solver::new();
+    for turn in 1.. {
+        let guess = match solver.guess() {
+            Some(guess) => guess,
+            None => {
+                println!("No code fits those answers.");
+                return;
+            }
+        };
+        let answer = loop {
+            let input = match prompt(&format!("Guess {}: {}? ", turn, show(&guess))) {
+                Some(input) => input,
+                None => return,
+            };
+            match parse_score(&input) {
+                Some(answer) => break answer,
+                None => println!("Answer with two numbers, black and white."),
+            }
+        };
+        if answer.black as usize == PEGS {
+            println!("Found in {} guesses.", turn);
+            return;
+        }
+        solver.answer(&guess, answer);
+    }
+}
+
+fn main() {
+    let mut solve_mode = false;
+    let mut seed = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--solve" => solve_mode = true,
+            "--seed" => {
+                let value = args.next().unwrap_or_else(|| fail(USAGE));
+                seed = Some(value.parse().unwrap_or_else(|_| fail(&format!("invalid seed: {}", value))));
+            }
+            _ => fail(USAGE),
+        }
+    }
+    if solve_mode {
+        solve();
+    } else {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+        });
+        play(seed);
+    }
+}
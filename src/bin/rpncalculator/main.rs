@@ -1,33 +1,389 @@
 #[macro_use]
 extern crate pprust;
+extern crate rustyline;
+#[macro_use]
+extern crate serde_json;
+use pprust::complex::Complex;
+use pprust::decimal::Decimal;
+use pprust::infix;
+use pprust::interval::Interval;
+use pprust::roman::Roman;
+use pprust::units::Quantity;
 use pprust::rpncalculator::*;
-use std::io::prelude::*;
-use std::io;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use serde_json::Value;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, IsTerminal};
+use std::process;
+
+const USAGE: &str = "Usage: rpncalculator [--complex | --bigint | --decimal | --interval | --roman | --units] \
+                     [--init FILE] [--infix] [--json] [-e EXPRESSION]...";
 
-fn repl_step(calc: &mut RpnCalculator) -> CalcResult {
-    print!("> ");
-    io::stdout().flush().ok().expect("Could not flush stdout");
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Fudeu");
-    calc.evaluate(&input)
+fn fail(message: &str) -> ! {
+    eprintln!("rpncalculator: {}", message);
+    process::exit(2);
 }
 
-fn main() {
-    let mut ops = default_operators();
-    new_operator!(ops, "q", _s, { Result::Err(RpnCalculatorError::Quit) });
+/// Changes the precision of a calculator, for the number types that have one
+type PrecisionFn<T> = fn(&mut RpnCalculator<T>, u32);
+
+/// The commands the REPL handles itself rather than the calculator
+const COMMANDS: [&str; 4] = ["explain", "load", "precision", "save"];
+
+/// Completes the word under the cursor with the names the calculator
+/// knows; arrow-key history and Ctrl-R search come with the editor
+struct NameCompleter {
+    names: Vec<String>,
+}
+
+impl NameCompleter {
+    /// Takes the names again, after a line that may have defined words
+    fn refresh<T: Number>(&mut self, calc: &RpnCalculator<T>) {
+        self.names = calc.names().into_iter().chain(COMMANDS.iter().cloned()).map(String::from).collect();
+        self.names.sort();
+        self.names.dedup();
+    }
+}
+
+impl Completer for NameCompleter {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _: &Context) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos].rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let prefix = &line[start..pos];
+        Ok((start, self.names.iter().filter(|name| name.starts_with(prefix)).cloned().collect()))
+    }
+}
+
+impl Hinter for NameCompleter {
+    type Hint = String;
+}
+
+impl Highlighter for NameCompleter {}
+
+impl Validator for NameCompleter {}
+
+impl Helper for NameCompleter {}
+
+/// Saves the session to a file or loads it from one
+fn save_or_load<T: Number>(calc: &mut RpnCalculator<T>, command: &str, path: &str) -> Result<(), String> {
+    if path.is_empty() {
+        return Err(format!("{} takes a file name", command));
+    }
+    let result = if command == "save" {
+        let file = File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+        calc.save(io::BufWriter::new(file))
+    } else {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        calc.load(io::BufReader::new(file))
+    };
+    result.map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Runs a line that is a command of the REPL rather than an expression,
+/// giving the text it prints, if any, or returns `None` if it isn't one
+fn command<T: Number>(
+    calc: &mut RpnCalculator<T>,
+    set_precision: Option<PrecisionFn<T>>,
+    input: &str,
+) -> Option<Result<Option<String>, String>> {
+    let mut words = input.split_whitespace();
+    match (words.next(), set_precision) {
+        (Some("precision"), Some(set)) => {
+            Some(match (words.next().map(str::parse), words.next()) {
+                (Some(Ok(digits)), None) => {
+                    set(calc, digits);
+                    Ok(None)
+                }
+                _ => Err("precision takes a number of digits".to_string()),
+            })
+        }
+        (Some(command @ ("save" | "load")), _) => {
+            Some(save_or_load(calc, command, &words.collect::<Vec<&str>>().join(" ")).map(|()| None))
+        }
+        (Some("explain"), _) => Some(infix::explain(calc, &words.collect::<Vec<&str>>().join(" ")).map(Some).map_err(|e| e.to_string())),
+        _ => None,
+    }
+}
+
+/// Prints the result of a line: what `help` printed and the top of the
+/// stack, or with `--json` an object with the help, if any, the top, as a
+/// JSON number when it is one exactly and else as the text the calculator
+/// shows, and the depth of the stack
+fn print_result<T: Number>(calc: &mut RpnCalculator<T>, json: bool) {
+    let help = calc.take_output();
+    if json {
+        let top = calc.format_top().map(|text| match serde_json::from_str::<serde_json::Number>(&text) {
+            Ok(number) if number.to_string() == text => Value::Number(number),
+            _ => Value::String(text),
+        });
+        let mut result = json!({ "ok": true, "top": top, "stack_depth": calc.stack_len() });
+        if !help.is_empty() {
+            result["help"] = json!(help.join("\n"));
+        }
+        println!("{}", result);
+        return;
+    }
+    for text in help {
+        println!("{}", text);
+    }
+    if let Some(top) = calc.format_top() {
+        println!("{}", top);
+    }
+}
+
+/// Prints what a command printed, or with `--json` an object with it as
+/// the infix form, since `explain` is the command that prints
+fn print_explained(text: &str, json: bool) {
+    if json {
+        println!("{}", json!({ "ok": true, "infix": text }));
+    } else {
+        println!("{}", text);
+    }
+}
+
+/// Prints an error as a JSON object, with the line of the input it
+/// happened on if there is one
+fn print_json_error(message: &str, line: Option<usize>) {
+    let mut error = json!({ "ok": false, "error": message });
+    if let Some(line) = line {
+        error["line"] = json!(line);
+    }
+    println!("{}", error);
+}
+
+/// Evaluates a line, in infix notation with `--infix`
+fn evaluate<T: Number>(calc: &mut RpnCalculator<T>, input: &str, infix: bool) -> CalcResult {
+    if infix {
+        infix::evaluate(calc, input)
+    } else {
+        calc.evaluate(input)
+    }
+}
+
+/// Evaluates expressions given as arguments, in order, running the
+/// commands of the REPL among them, and prints the top of the stack, or
+/// what the last command printed, exiting with status 1 on the first
+/// error
+fn evaluate_all<T: Number>(
+    calc: &mut RpnCalculator<T>,
+    set_precision: Option<PrecisionFn<T>>,
+    expressions: &[String],
+    json: bool,
+    infix: bool,
+) {
+    let mut printed = None;
+    for expression in expressions {
+        let result = match command(calc, set_precision, expression) {
+            Some(result) => result,
+            None => match evaluate(calc, expression, infix) {
+                Err(RpnCalculatorError::Quit) => break,
+                result => result.map(|()| None).map_err(|e| e.to_string()),
+            },
+        };
+        match result {
+            Ok(text) => printed = text,
+            Err(message) if json => {
+                print_json_error(&format!("{}: {}", expression, message), None);
+                process::exit(1);
+            }
+            Err(message) => {
+                eprintln!("rpncalculator: {}: {}", expression, message);
+                process::exit(1);
+            }
+        }
+    }
+    match printed {
+        Some(text) => print_explained(&text, json),
+        None => print_result(calc, json),
+    }
+}
+
+/// Prints the result of each line of standard input when it isn't a
+/// terminal, with no prompt, after running the commands of the REPL
+struct Batch<T: Number> {
+    set_precision: Option<PrecisionFn<T>>,
+    json: bool,
+    infix: bool,
+    /// What the last line did if it was a command
+    command: Option<Result<Option<String>, String>>,
+    failed: bool,
+}
+
+impl<T: Number> LineSink<T> for Batch<T> {
+    fn evaluate(&mut self, calc: &mut RpnCalculator<T>, input: &str) -> CalcResult {
+        self.command = command(calc, self.set_precision, input);
+        match self.command {
+            Some(_) => Ok(()),
+            None => evaluate(calc, input, self.infix),
+        }
+    }
+
+    fn line(&mut self, calc: &mut RpnCalculator<T>, number: usize, _: &str, result: CalcResult) -> bool {
+        let result = match self.command.take() {
+            Some(result) => result,
+            None => match result {
+                Err(RpnCalculatorError::Quit) => return false,
+                result => result.map(|()| None).map_err(|e| e.to_string()),
+            },
+        };
+        match result {
+            Ok(Some(text)) => print_explained(&text, self.json),
+            Ok(None) if self.json => print_result(calc, self.json),
+            Ok(None) => {
+                for text in calc.take_output() {
+                    println!("{}", text);
+                }
+                println!("{}", calc.format_top().unwrap_or_default());
+            }
+            Err(message) => {
+                if self.json {
+                    print_json_error(&message, Some(number));
+                } else {
+                    eprintln!("rpncalculator: line {}: {}", number, message);
+                }
+                self.failed = true;
+            }
+        }
+        true
+    }
+}
+
+/// Evaluates the lines of standard input when it isn't a terminal,
+/// printing the top of the stack after each line that isn't blank or a
+/// `#` comment. A line that fails is reported on stderr and the next ones
+/// are still evaluated; the exit status is 1 if any line failed. With
+/// `--json` every line evaluated prints an object, errors too.
+fn batch<T: Number>(calc: &mut RpnCalculator<T>, set_precision: Option<PrecisionFn<T>>, json: bool, infix: bool) {
+    let mut batch = Batch { set_precision, json, infix, command: None, failed: false };
+    let stdin = io::stdin();
+    if let Err(e) = calc.evaluate_stream(stdin.lock(), &mut batch) {
+        fail(&e.to_string());
+    }
+    if batch.failed {
+        process::exit(1);
+    }
+}
+
+fn run<T: Number>(set_precision: Option<PrecisionFn<T>>, init: Option<&str>, expressions: &[String], json: bool, infix: bool) {
+    let mut ops = T::operators();
+    new_operator!(ops, "q", "Quits", "", _s, { Result::Err(RpnCalculatorError::Quit) });
+    new_operator!(ops, "quit", "Quits", "", _s, { Result::Err(RpnCalculatorError::Quit) });
     let mut calc = RpnCalculator::new_with_operators(ops);
-    println!("Calculator. Enter expressions, 'q' to quit.");
+    if let Some(path) = init {
+        let script = fs::read_to_string(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+        if let Err(e) = calc.evaluate_script(&script) {
+            fail(&format!("{}, {}", path, e));
+        }
+    }
+    if !expressions.is_empty() {
+        evaluate_all(&mut calc, set_precision, expressions, json, infix);
+        return;
+    }
+    if !io::stdin().is_terminal() {
+        batch(&mut calc, set_precision, json, infix);
+        return;
+    }
+    let mut editor: Editor<NameCompleter, DefaultHistory> =
+        Editor::new().unwrap_or_else(|e| fail(&format!("cannot start the line editor: {}", e)));
+    let mut completer = NameCompleter { names: Vec::new() };
+    completer.refresh(&calc);
+    editor.set_helper(Some(completer));
+    if infix {
+        println!("Calculator. Enter infix expressions, like (1 + 2) * 3, 'q' or 'quit' to quit.");
+    } else {
+        println!("Calculator. Enter expressions, 'q' or 'quit' to quit.");
+    }
+    println!("'help' lists the operators, 'help NAME' shows one of them.");
+    println!("'explain EXPRESSION' shows an RPN expression in infix.");
+    println!("'save FILE' and 'load FILE' keep the session in a file.");
+    println!("'undo' takes back the last line, even one that failed halfway.");
+    if set_precision.is_some() {
+        println!("'precision N' keeps N digits after the point in quotients.");
+    }
     loop {
-        let res = repl_step(&mut calc);
-        match res {
-            Result::Err(RpnCalculatorError::Quit) => break,
-            Result::Ok(_) => {
-                println!("{}", *calc.top().unwrap());
+        let input = match editor.readline("> ") {
+            Ok(input) => input,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => fail(&e.to_string()),
+        };
+        if !input.trim().is_empty() {
+            let _ = editor.add_history_entry(input.as_str());
+        }
+        match command(&mut calc, set_precision, &input) {
+            Some(Ok(Some(text))) => {
+                print_explained(&text, json);
+                continue;
+            }
+            Some(Ok(None)) => {
+                print_result(&mut calc, json);
+                continue;
+            }
+            Some(Err(message)) => {
+                if json {
+                    print_json_error(&message, None);
+                } else {
+                    println!("Erro: {}", message);
+                }
+                continue;
             }
+            None => (),
+        }
+        let result = evaluate(&mut calc, &input, infix);
+        if let Some(completer) = editor.helper_mut() {
+            completer.refresh(&calc);
+        }
+        match result {
+            Result::Err(RpnCalculatorError::Quit) => break,
+            Result::Ok(_) => print_result(&mut calc, json),
             Result::Err(x) => {
-                println!("Erro: {:?}", x);
-                break;
+                if json {
+                    print_json_error(&x.to_string(), None);
+                } else {
+                    println!("Erro: {}", x);
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    let mut mode = "float".to_string();
+    let mut init = None;
+    let mut expressions = Vec::new();
+    let mut json = false;
+    let mut infix = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--complex" | "--bigint" | "--decimal" | "--interval" | "--roman" | "--units" => mode = arg[2..].to_string(),
+            "--init" => init = Some(args.next().unwrap_or_else(|| fail(USAGE))),
+            "--infix" => infix = true,
+            "--json" => json = true,
+            "-e" => expressions.push(args.next().unwrap_or_else(|| fail(USAGE))),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
             }
+            _ => fail(&format!("unknown argument {}\n{}", arg, USAGE)),
         }
     }
+    let init = init.as_deref();
+    match mode.as_str() {
+        "complex" => run::<Complex>(None, init, &expressions, json, infix),
+        "bigint" => run::<BigNumber>(None, init, &expressions, json, infix),
+        "decimal" => run::<Decimal>(Some(RpnCalculator::set_precision), init, &expressions, json, infix),
+        "interval" => run::<Interval>(None, init, &expressions, json, infix),
+        "roman" => run::<Roman>(None, init, &expressions, json, infix),
+        "units" => run::<Quantity>(None, init, &expressions, json, infix),
+        _ => run::<f64>(None, init, &expressions, json, infix),
+    }
 }
@@ -0,0 +1,59 @@
+extern crate pprust;
+use pprust::extsort::{numeric_order, sort_lines_by};
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+
+const USAGE: &str = "Usage: sort [-n] [-r] [-S BYTES] [-o OUTPUT] [INPUT]";
+const DEFAULT_BUDGET: usize = 64 * 1024 * 1024;
+
+fn fail(message: &str) -> ! {
+    eprintln!("sort: {}", message);
+    process::exit(2);
+}
+
+fn main() {
+    let mut numeric = false;
+    let mut reverse = false;
+    let mut budget = DEFAULT_BUDGET;
+    let mut input = None;
+    let mut output = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "-n" => numeric = true,
+            "-r" => reverse = true,
+            "-nr" | "-rn" => {
+                numeric = true;
+                reverse = true;
+            }
+            "-S" => {
+                let value = args.next().unwrap_or_else(|| fail(USAGE));
+                budget = value.parse().unwrap_or_else(|_| fail(&format!("invalid memory budget: {}", value)));
+            }
+            "-o" => output = Some(args.next().unwrap_or_else(|| fail(USAGE))),
+            _ if input.is_none() && !arg.starts_with('-') => input = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+
+    let compare = move |a: &str, b: &str| -> Ordering {
+        let order = if numeric { numeric_order(a, b) } else { a.cmp(b) };
+        if reverse { order.reverse() } else { order }
+    };
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let reader: Box<dyn io::BufRead> = match input {
+        Some(ref path) => Box::new(io::BufReader::new(fs::File::open(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e))))),
+        None => Box::new(stdin.lock()),
+    };
+    let writer: Box<dyn io::Write> = match output {
+        Some(ref path) => Box::new(io::BufWriter::new(fs::File::create(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e))))),
+        None => Box::new(io::BufWriter::new(stdout.lock())),
+    };
+    if let Err(e) = sort_lines_by(reader, writer, budget, compare) {
+        fail(&e.to_string());
+    }
+}
@@ -0,0 +1,58 @@
+extern crate pprust;
+use pprust::tail::{last_lines, Follower};
+use std::env;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: tail [-n LINES] [-f] FILE";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn fail(message: &str) -> ! {
+    eprintln!("tail: {}", message);
+    process::exit(1);
+}
+
+fn main() {
+    let mut lines = 10;
+    let mut follow = false;
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "-f" => follow = true,
+            "-n" => {
+                let value = args.next().unwrap_or_else(|| fail(USAGE));
+                lines = value.parse().unwrap_or_else(|_| fail(&format!("invalid number of lines: {}", value)));
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+    let path = path.unwrap_or_else(|| fail(USAGE));
+
+    let mut file = fs::File::open(&path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+    let data = last_lines(&mut file, lines).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+    let stdout = io::stdout();
+    stdout.lock().write_all(&data).expect("Could not write to stdout");
+    if !follow {
+        return;
+    }
+
+    let position = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut follower = Follower::from_position(&path, position).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+    loop {
+        match follower.poll() {
+            Ok(data) => {
+                let mut out = stdout.lock();
+                out.write_all(&data).expect("Could not write to stdout");
+                out.flush().expect("Could not flush stdout");
+            }
+            Err(e) => eprintln!("tail: {}: {}", path, e),
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,62 @@
+extern crate pprust;
+use pprust::tictactoe::{Board, Player};
+use std::env;
+use std::io::prelude::*;
+use std::io;
+use std::process;
+
+const USAGE: &str = "Usage: tictactoe [--two-player | --computer-first]";
+
+fn read_move(board: &Board) -> Option<usize> {
+    let stdin = io::stdin();
+    loop {
+        print!("{} to move (1-9, q to quit): ", board.to_move());
+        io::stdout().flush().expect("Could not flush stdout");
+        let mut input = String::new();
+        if stdin.lock().read_line(&mut input).expect("Could not read stdin") == 0 {
+            return None;
+        }
+        match input.trim() {
+            "q" => return None,
+            cell => match cell.parse::<usize>() {
+                Ok(n) if n >= 1 && board.play(n - 1).is_some() => return Some(n - 1),
+                _ => println!("Pick a free cell from 1 to 9."),
+            },
+        }
+    }
+}
+
+fn main() {
+    let mut computer = Some(Player::O);
+    for arg in env::args().skip(1) {
+        match arg.as_ref() {
+            "--two-player" => computer = None,
+            "--computer-first" => computer = Some(Player::X),
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    let mut board = Board::new();
+    while !board.is_over() {
+        println!("{}", board);
+        let m = if Some(board.to_move()) == computer {
+            let m = board.best_move().expect("Game is not over");
+            println!("Computer plays {}.", m + 1);
+            m
+        } else {
+            match read_move(&board) {
+                Some(m) => m,
+                None => return,
+            }
+        };
+        board = board.play(m).expect("Move was checked");
+    }
+    println!("{}", board);
+    match board.winner() {
+        Some(p) => println!("{} wins.", p),
+        None => println!("Draw."),
+    }
+}
@@ -0,0 +1,49 @@
+extern crate pprust;
+use pprust::tinybasic::Interpreter;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::process;
+
+const USAGE: &str = "Usage: tinybasic [FILE]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("tinybasic: {}", message);
+    process::exit(1);
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next();
+    if args.next().is_some() {
+        fail(USAGE);
+    }
+
+    let mut basic = Interpreter::new();
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    if let Some(path) = path {
+        let source = fs::read_to_string(&path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+        basic.load(&source).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+        basic.run(&mut input, &mut output).unwrap_or_else(|e| fail(&e.to_string()));
+        return;
+    }
+
+    loop {
+        write!(output, "> ").and_then(|_| output.flush()).expect("Could not write to stdout");
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {
+                if let Err(e) = basic.enter(&line, &mut input, &mut output) {
+                    writeln!(output, "{}", e).expect("Could not write to stdout");
+                }
+            }
+            Err(e) => fail(&e.to_string()),
+        }
+    }
+}
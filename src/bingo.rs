@@ -0,0 +1,215 @@
+//!
+//! # Problem description:
+//! #
+//! # Bingo
+//!
+//! An American bingo card has five columns headed B, I, N, G and O. The
+//! B column holds five different numbers from 1 to 15, I from 16 to 30,
+//! and so on up to O, from 61 to 75; the centre square is free. The
+//! caller draws the numbers 1 to 75 in random order, and a card wins
+//! once a whole row, column or diagonal has been called.
+//!
+//! How many numbers are called, on average, before a single card wins?
+//! And before the first of 500 cards wins? The simulation answers both:
+//! for each card it finds when each line is complete from the order in
+//! which the numbers are drawn, so one game costs one pass over each
+//! card.
+//!
+
+use rng::Rng;
+use shuffle::shuffle;
+use std::fmt;
+
+/// Highest number drawn
+pub const NUMBERS: u8 = 75;
+
+/// The twelve winning lines: rows, columns and diagonals
+fn lines() -> Vec<[(usize, usize); 5]> {
+    let mut lines = Vec::new();
+    for i in 0..5 {
+        lines.push([(i, 0), (i, 1), (i, 2), (i, 3), (i, 4)]);
+        lines.push([(0, i), (1, i), (2, i), (3, i), (4, i)]);
+    }
+    lines.push([(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+    lines.push([(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)]);
+    lines
+}
+
+/// A bingo card; the free centre square holds 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    squares: [[u8; 5]; 5],
+}
+
+impl Card {
+    /// A random card
+    pub fn random(rng: &mut Rng) -> Card {
+        let columns: Vec<Vec<u8>> = (0..5).map(|col| {
+            let mut column: Vec<u8> = (1..=15).map(|n| 15 * col + n).collect();
+            shuffle(&mut column, rng);
+            column
+        }).collect();
+        let mut squares = [[0; 5]; 5];
+        for (row, line) in squares.iter_mut().enumerate() {
+            for (col, square) in line.iter_mut().enumerate() {
+                *square = columns[col][row];
+            }
+        }
+        squares[2][2] = 0;
+        Card { squares }
+    }
+
+    /// A card with the given squares, row by row, or `None` if they do
+    /// not follow the rules
+    pub fn from_squares(squares: [[u8; 5]; 5]) -> Option<Card> {
+        for (row, line) in squares.iter().enumerate() {
+            for (col, &n) in line.iter().enumerate() {
+                let free = row == 2 && col == 2;
+                let in_range = n > 15 * col as u8 && n <= 15 * (col as u8 + 1);
+                let repeated = (0..row).any(|r| squares[r][col] == n);
+                if (free && n != 0) || (!free && (!in_range || repeated)) {
+                    return None;
+                }
+            }
+        }
+        Some(Card { squares })
+    }
+
+    /// The number on a square, 0 for the free square
+    pub fn square(&self, row: usize, col: usize) -> u8 {
+        self.squares[row][col]
+    }
+
+    /// How many numbers of the draw are called before the card wins
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::bingo::{Card, draw};
+    /// use pprust::rng::Rng;
+    ///
+    /// let mut rng = Rng::new(7);
+    /// let card = Card::random(&mut rng);
+    /// let calls = card.calls_to_win(&draw(&mut rng));
+    /// assert!(4 <= calls && calls <= 75);
+    /// ```
+    pub fn calls_to_win(&self, draw: &[u8]) -> usize {
+        let mut called_at = [0usize; NUMBERS as usize + 1];
+        for (i, &n) in draw.iter().enumerate() {
+            called_at[n as usize] = i + 1;
+        }
+        // The free square is called before anything else
+        called_at[0] = 0;
+        lines().iter()
+            .map(|line| line.iter().map(|&(r, c)| called_at[self.squares[r][c] as usize]).max().unwrap())
+            .min()
+            .unwrap()
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "  B  I  N  G  O")?;
+        for row in self.squares.iter() {
+            let squares: Vec<String> = row.iter().map(|&n| if n == 0 { " *".to_string() } else { format!("{:2}", n) }).collect();
+            writeln!(f, " {}", squares.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The numbers 1 to 75 in random order
+pub fn draw(rng: &mut Rng) -> Vec<u8> {
+    let mut numbers: Vec<u8> = (1..=NUMBERS).collect();
+    shuffle(&mut numbers, rng);
+    numbers
+}
+
+/// Plays one game with `cards` random cards and returns the number of
+/// calls until the first card wins
+pub fn simulate(cards: usize, rng: &mut Rng) -> usize {
+    let cards: Vec<Card> = (0..cards).map(|_| Card::random(rng)).collect();
+    let numbers = draw(rng);
+    cards.iter().map(|c| c.calls_to_win(&numbers)).min().unwrap_or(NUMBERS as usize)
+}
+
+/// Estimates the expected number of calls until the first of `cards`
+/// cards wins, averaging over `games` games
+///
+/// # Example
+/// ```
+/// use pprust::bingo::expected_calls;
+///
+/// let one_card = expected_calls(1, 2000, 1);
+/// assert!(38.0 < one_card && one_card < 45.0);
+/// ```
+pub fn expected_calls(cards: usize, games: usize, seed: u64) -> f64 {
+    let mut rng = Rng::new(seed);
+    let total: usize = (0..games).map(|_| simulate(cards, &mut rng)).sum();
+    total as f64 / games as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_card() -> Card {
+        Card::from_squares([
+            [1, 16, 31, 46, 61],
+            [2, 17, 32, 47, 62],
+            [3, 18, 0, 48, 63],
+            [4, 19, 34, 49, 64],
+            [5, 20, 35, 50, 65],
+        ]).unwrap()
+    }
+
+    fn draw_starting_with(first: &[u8]) -> Vec<u8> {
+        let mut numbers = first.to_vec();
+        numbers.extend((1..=NUMBERS).filter(|n| !first.contains(n)));
+        numbers
+    }
+
+    #[test]
+    fn should_make_valid_random_cards() {
+        let mut rng = Rng::new(3);
+        for _ in 0..100 {
+            let card = Card::random(&mut rng);
+            assert_eq!(Some(card.clone()), Card::from_squares(card.squares));
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_cards() {
+        let mut squares = sample_card().squares;
+        squares[0][0] = 16;
+        assert_eq!(None, Card::from_squares(squares));
+        squares[0][0] = 2;
+        assert_eq!(None, Card::from_squares(squares), "Repeated number");
+        squares[0][0] = 1;
+        squares[2][2] = 33;
+        assert_eq!(None, Card::from_squares(squares), "Centre must be free");
+    }
+
+    #[test]
+    fn should_win_on_each_kind_of_line() {
+        let card = sample_card();
+        assert_eq!(5, card.calls_to_win(&draw_starting_with(&[1, 16, 31, 46, 61])), "Row");
+        assert_eq!(5, card.calls_to_win(&draw_starting_with(&[61, 62, 63, 64, 65])), "Column");
+        assert_eq!(4, card.calls_to_win(&draw_starting_with(&[1, 17, 49, 65])), "Diagonal through the free square");
+        assert_eq!(4, card.calls_to_win(&draw_starting_with(&[31, 32, 34, 35])), "Middle column");
+        assert_eq!(4, card.calls_to_win(&draw_starting_with(&[5, 19, 47, 61])), "Other diagonal");
+    }
+
+    #[test]
+    fn should_take_fewer_calls_with_more_cards() {
+        let one = expected_calls(1, 500, 11);
+        let many = expected_calls(500, 50, 11);
+        assert!(many < 15.0 && many < one, "1 card: {}, 500 cards: {}", one, many);
+    }
+
+    #[test]
+    fn should_print_card() {
+        let printed = sample_card().to_string();
+        assert!(printed.starts_with("  B  I  N  G  O\n  1 16 31 46 61\n"), "{}", printed);
+        assert!(printed.contains("  3 18  * 48 63\n"));
+    }
+}
@@ -0,0 +1,167 @@
+//!
+//! # Problem description:
+//! #
+//! # Bit Input and Output
+//!
+//! Variable-length codes, like Huffman or Fibonacci codes, don't line up
+//! with byte boundaries. A bit writer collects bits, most significant
+//! first, into a byte and writes it out when it is full, padding the last
+//! byte with zeros; a bit reader does the reverse. Both work on any
+//! `Write` or `Read`, so a code can be streamed to a file or to memory.
+//!
+
+use std::io;
+use std::io::prelude::*;
+
+/// Writes single bits to an underlying writer
+#[derive(Debug)]
+pub struct BitWriter<W: Write> {
+    inner: W,
+    current: u8,
+    /// Bits in `current`, always less than 8
+    filled: u32,
+    written: u64,
+}
+
+impl<W: Write> BitWriter<W> {
+    pub fn new(inner: W) -> BitWriter<W> {
+        BitWriter { inner, current: 0, filled: 0, written: 0 }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.current = (self.current << 1) | bit as u8;
+        self.filled += 1;
+        self.written += 1;
+        if self.filled == 8 {
+            self.inner.write_all(&[self.current])?;
+            self.current = 0;
+            self.filled = 0;
+        }
+        Ok(())
+    }
+
+    /// Writes the low `count` bits of `value`, most significant first
+    pub fn write_bits(&mut self, value: u64, count: u32) -> io::Result<()> {
+        assert!(count <= 64, "Cannot write {} bits at once", count);
+        for i in (0..count).rev() {
+            self.write_bit((value >> i) & 1 == 1)?;
+        }
+        Ok(())
+    }
+
+    /// Number of bits written so far, not counting padding
+    pub fn bits_written(&self) -> u64 {
+        self.written
+    }
+
+    /// Pads the last byte with zeros, flushes and returns the writer
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::bitio::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_bits(0b101, 3).unwrap();
+    /// assert_eq!(vec![0b1010_0000], writer.finish().unwrap());
+    /// ```
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.filled > 0 {
+            let padded = self.current << (8 - self.filled);
+            self.inner.write_all(&[padded])?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads single bits from an underlying reader
+#[derive(Debug)]
+pub struct BitReader<R: Read> {
+    inner: R,
+    current: u8,
+    /// Bits still unread in `current`
+    left: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    pub fn new(inner: R) -> BitReader<R> {
+        BitReader { inner, current: 0, left: 0 }
+    }
+
+    /// The next bit, or `None` at the end of the input
+    pub fn read_bit(&mut self) -> io::Result<Option<bool>> {
+        if self.left == 0 {
+            let mut byte = [0u8];
+            if self.inner.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+            self.current = byte[0];
+            self.left = 8;
+        }
+        self.left -= 1;
+        Ok(Some((self.current >> self.left) & 1 == 1))
+    }
+
+    /// The next `count` bits, most significant first, or `None` if the
+    /// input ends first
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::bitio::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0b1011_0001u8][..]);
+    /// assert_eq!(Some(0b101), reader.read_bits(3).unwrap());
+    /// assert_eq!(Some(0b10001), reader.read_bits(5).unwrap());
+    /// assert_eq!(None, reader.read_bits(1).unwrap());
+    /// ```
+    pub fn read_bits(&mut self, count: u32) -> io::Result<Option<u64>> {
+        assert!(count <= 64, "Cannot read {} bits at once", count);
+        let mut value = 0u64;
+        for _ in 0..count {
+            match self.read_bit()? {
+                Some(bit) => value = (value << 1) | bit as u64,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    #[test]
+    fn should_round_trip_bits() {
+        let mut rng = Rng::new(17);
+        let fields: Vec<(u64, u32)> = (0..500)
+            .map(|_| {
+                let count = rng.gen_range(0, 65) as u32;
+                let value = if count == 64 { rng.next_u64() } else { rng.next_u64() & ((1 << count) - 1) };
+                (value, count)
+            })
+            .collect();
+        let mut writer = BitWriter::new(Vec::new());
+        for &(value, count) in &fields {
+            writer.write_bits(value, count).unwrap();
+        }
+        let total = writer.bits_written();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(total.div_ceil(8), bytes.len() as u64);
+        let mut reader = BitReader::new(&bytes[..]);
+        for &(value, count) in &fields {
+            assert_eq!(Some(value), reader.read_bits(count).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_pad_last_byte_with_zeros() {
+        let mut writer = BitWriter::new(Vec::new());
+        for &bit in &[true, true, false, true, true, true, true, true, true] {
+            writer.write_bit(bit).unwrap();
+        }
+        assert_eq!(vec![0b1101_1111, 0b1000_0000], writer.finish().unwrap());
+        assert_eq!(Vec::<u8>::new(), BitWriter::new(Vec::new()).finish().unwrap());
+    }
+}
@@ -0,0 +1,352 @@
+//!
+//! # Problem description:
+//! #
+//! # Blackjack
+//!
+//! The player and the dealer each get two cards, one of the dealer's
+//! face up. Cards count their face value, picture cards 10 and aces 1 or
+//! 11, whichever is better; a hand counting 11 for an ace is soft. The
+//! player draws cards (hits) until they stand or go over 21 and lose,
+//! and may double the bet on the first two cards in exchange for taking
+//! exactly one more card. The dealer then plays by fixed rules, drawing
+//! to 16 and standing on 17, and the higher total wins. An ace and a
+//! ten-valued card dealt together are a blackjack, which pays 3 to 2.
+//!
+//! Basic strategy is the best way to play knowing only your hand and the
+//! dealer's up card. The simulation deals millions of hands from a shoe
+//! of several decks, shuffled again once most of it has been dealt, to
+//! measure the house edge: the average fraction of each bet lost. Pairs
+//! are played by their total, since this player never splits.
+//!
+
+use rng::Rng;
+use shuffle::shuffle;
+
+/// The table rules
+#[derive(Debug, Clone, Copy)]
+pub struct Rules {
+    /// Number of decks in the shoe
+    pub decks: usize,
+    /// Whether the dealer draws to a soft 17
+    pub dealer_hits_soft_17: bool,
+    /// What a blackjack pays, per unit bet
+    pub blackjack_pays: f64,
+    /// Fraction of the shoe dealt before shuffling again
+    pub penetration: f64,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules { decks: 6, dealer_hits_soft_17: false, blackjack_pays: 1.5, penetration: 0.75 }
+    }
+}
+
+/// A shoe of cards, each card being its value: 1 for an ace, 2 to 9, and
+/// 10 for tens and picture cards
+pub struct Shoe {
+    cards: Vec<u8>,
+    next: usize,
+    cut: usize,
+}
+
+impl Shoe {
+    /// A freshly shuffled shoe for the rules
+    pub fn new(rules: &Rules, rng: &mut Rng) -> Shoe {
+        let mut cards = Vec::with_capacity(52 * rules.decks);
+        for _ in 0..4 * rules.decks {
+            cards.extend(1..=9);
+            cards.extend(&[10, 10, 10, 10]);
+        }
+        let cut = (cards.len() as f64 * rules.penetration) as usize;
+        let mut shoe = Shoe { cards, next: 0, cut };
+        shoe.shuffle(rng);
+        shoe
+    }
+
+    fn shuffle(&mut self, rng: &mut Rng) {
+        shuffle(&mut self.cards, rng);
+        self.next = 0;
+    }
+
+    /// Whether the cut card has come out, so the shoe is due a shuffle
+    pub fn needs_shuffle(&self) -> bool {
+        self.next >= self.cut
+    }
+
+    /// Deals the next card, shuffling first if the shoe has run out
+    pub fn deal(&mut self, rng: &mut Rng) -> u8 {
+        if self.next == self.cards.len() {
+            self.shuffle(rng);
+        }
+        self.next += 1;
+        self.cards[self.next - 1]
+    }
+}
+
+/// The cards in a hand
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hand {
+    cards: Vec<u8>,
+}
+
+impl Hand {
+    /// A hand with the given card values
+    pub fn new(cards: &[u8]) -> Hand {
+        Hand { cards: cards.to_vec() }
+    }
+
+    /// Adds a card
+    pub fn add(&mut self, card: u8) {
+        self.cards.push(card);
+    }
+
+    /// Number of cards
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Whether the hand has no cards
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// The best total and whether it is soft, counting an ace as 11
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::blackjack::Hand;
+    ///
+    /// assert_eq!((17, true), Hand::new(&[1, 6]).total());
+    /// assert_eq!((17, false), Hand::new(&[1, 6, 10]).total());
+    /// ```
+    pub fn total(&self) -> (u8, bool) {
+        let hard: u8 = self.cards.iter().sum();
+        if self.cards.contains(&1) && hard + 10 <= 21 {
+            (hard + 10, true)
+        } else {
+            (hard, false)
+        }
+    }
+
+    /// Whether the hand is an ace and a ten-valued card
+    pub fn is_blackjack(&self) -> bool {
+        self.cards.len() == 2 && self.total().0 == 21
+    }
+
+    /// Whether the hand is over 21
+    pub fn is_bust(&self) -> bool {
+        self.total().0 > 21
+    }
+}
+
+/// What the player does next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Hit,
+    Stand,
+    Double,
+}
+
+/// Chooses an action from the player's hand and the dealer's up card
+/// (1 for an ace)
+pub type Strategy = fn(&Hand, u8) -> Action;
+
+/// Basic strategy for several decks when the dealer stands on soft 17,
+/// without splitting. `Double` means hit when doubling is not allowed,
+/// except on soft 18, which then stands.
+///
+/// # Example
+/// ```
+/// use pprust::blackjack::{basic_strategy, Action, Hand};
+///
+/// assert_eq!(Action::Double, basic_strategy(&Hand::new(&[6, 5]), 10));
+/// assert_eq!(Action::Stand, basic_strategy(&Hand::new(&[10, 3]), 6));
+/// assert_eq!(Action::Hit, basic_strategy(&Hand::new(&[10, 6]), 7));
+/// ```
+pub fn basic_strategy(hand: &Hand, dealer: u8) -> Action {
+    let up = if dealer == 1 { 11 } else { dealer };
+    let (total, soft) = hand.total();
+    let action = if soft {
+        match total {
+            13 | 14 if (5..=6).contains(&up) => Action::Double,
+            15 | 16 if (4..=6).contains(&up) => Action::Double,
+            17 if (3..=6).contains(&up) => Action::Double,
+            18 if (3..=6).contains(&up) => Action::Double,
+            18 if up <= 8 => Action::Stand,
+            t if t >= 19 => Action::Stand,
+            _ => Action::Hit,
+        }
+    } else {
+        match total {
+            9 if (3..=6).contains(&up) => Action::Double,
+            10 if up <= 9 => Action::Double,
+            11 if up <= 10 => Action::Double,
+            12 if (4..=6).contains(&up) => Action::Stand,
+            13..=16 if up <= 6 => Action::Stand,
+            t if t >= 17 => Action::Stand,
+            _ => Action::Hit,
+        }
+    };
+    match action {
+        Action::Double if hand.len() > 2 => if soft && total == 18 { Action::Stand } else { Action::Hit },
+        action => action,
+    }
+}
+
+/// Plays like the dealer: hit below 17, never double
+pub fn dealer_strategy(hand: &Hand, _dealer: u8) -> Action {
+    if hand.total().0 < 17 { Action::Hit } else { Action::Stand }
+}
+
+/// Draws cards for the dealer's hand by the house rules
+pub fn dealer_play(hand: &mut Hand, rules: &Rules, shoe: &mut Shoe, rng: &mut Rng) {
+    loop {
+        let (total, soft) = hand.total();
+        if total > 17 || (total == 17 && !(soft && rules.dealer_hits_soft_17)) {
+            return;
+        }
+        hand.add(shoe.deal(rng));
+    }
+}
+
+/// Plays one hand and returns the player's winnings per unit bet
+pub fn play_hand(strategy: Strategy, rules: &Rules, shoe: &mut Shoe, rng: &mut Rng) -> f64 {
+    let mut player = Hand::default();
+    let mut dealer = Hand::default();
+    for _ in 0..2 {
+        player.add(shoe.deal(rng));
+        dealer.add(shoe.deal(rng));
+    }
+    let up = dealer.cards[0];
+    match (player.is_blackjack(), dealer.is_blackjack()) {
+        (true, true) => return 0.0,
+        (true, false) => return rules.blackjack_pays,
+        (false, true) => return -1.0,
+        (false, false) => {}
+    }
+    let mut bet = 1.0;
+    loop {
+        match strategy(&player, up) {
+            Action::Stand => break,
+            Action::Hit => player.add(shoe.deal(rng)),
+            Action::Double if player.len() == 2 => {
+                bet = 2.0;
+                player.add(shoe.deal(rng));
+                break;
+            }
+            Action::Double => player.add(shoe.deal(rng)),
+        }
+        if player.total().0 >= 21 {
+            break;
+        }
+    }
+    if player.is_bust() {
+        return -bet;
+    }
+    dealer_play(&mut dealer, rules, shoe, rng);
+    let (mine, theirs) = (player.total().0, dealer.total().0);
+    if dealer.is_bust() || mine > theirs {
+        bet
+    } else if mine < theirs {
+        -bet
+    } else {
+        0.0
+    }
+}
+
+/// The outcome of a simulation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report {
+    /// Hands played
+    pub hands: u64,
+    /// Player's total winnings, in units bet
+    pub net: f64,
+}
+
+impl Report {
+    /// The fraction of each initial bet the house keeps on average
+    pub fn house_edge(&self) -> f64 {
+        -self.net / self.hands as f64
+    }
+}
+
+/// Plays `hands` hands with a strategy
+///
+/// # Example
+/// ```
+/// use pprust::blackjack::{simulate, basic_strategy, Rules};
+///
+/// let report = simulate(basic_strategy, &Rules::default(), 10_000, 42);
+/// assert_eq!(10_000, report.hands);
+/// assert!(report.house_edge().abs() < 0.1);
+/// ```
+pub fn simulate(strategy: Strategy, rules: &Rules, hands: u64, seed: u64) -> Report {
+    let mut rng = Rng::new(seed);
+    let mut shoe = Shoe::new(rules, &mut rng);
+    let mut net = 0.0;
+    for _ in 0..hands {
+        if shoe.needs_shuffle() {
+            shoe.shuffle(&mut rng);
+        }
+        net += play_hand(strategy, rules, &mut shoe, &mut rng);
+    }
+    Report { hands, net }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_aces_soft_and_hard() {
+        assert_eq!((21, true), Hand::new(&[1, 10]).total());
+        assert!(Hand::new(&[1, 10]).is_blackjack());
+        assert!(!Hand::new(&[1, 5, 5]).is_blackjack());
+        assert_eq!((12, true), Hand::new(&[1, 1]).total());
+        assert_eq!((13, false), Hand::new(&[1, 1, 1, 10]).total());
+        assert!(Hand::new(&[10, 6, 8]).is_bust());
+    }
+
+    #[test]
+    fn should_deal_whole_decks() {
+        let rules = Rules { decks: 2, ..Rules::default() };
+        let mut rng = Rng::new(1);
+        let mut shoe = Shoe::new(&rules, &mut rng);
+        let mut counts = [0; 11];
+        for _ in 0..104 {
+            counts[shoe.deal(&mut rng) as usize] += 1;
+        }
+        assert_eq!([0, 8, 8, 8, 8, 8, 8, 8, 8, 8, 32], counts);
+        assert!(shoe.needs_shuffle());
+    }
+
+    #[test]
+    fn should_follow_dealer_rules_on_soft_17() {
+        let mut rng = Rng::new(2);
+        let mut shoe = Shoe::new(&Rules::default(), &mut rng);
+        let mut stands = Hand::new(&[1, 6]);
+        dealer_play(&mut stands, &Rules::default(), &mut shoe, &mut rng);
+        assert_eq!(2, stands.len());
+        let mut hits = Hand::new(&[1, 6]);
+        dealer_play(&mut hits, &Rules { dealer_hits_soft_17: true, ..Rules::default() }, &mut shoe, &mut rng);
+        assert!(hits.len() > 2);
+        assert!(hits.total().0 >= 17);
+    }
+
+    #[test]
+    fn should_only_double_two_cards() {
+        assert_eq!(Action::Double, basic_strategy(&Hand::new(&[1, 7]), 4));
+        assert_eq!(Action::Stand, basic_strategy(&Hand::new(&[1, 3, 4]), 4));
+        assert_eq!(Action::Hit, basic_strategy(&Hand::new(&[2, 3, 5]), 6));
+        assert_eq!(Action::Hit, basic_strategy(&Hand::new(&[1, 7]), 10));
+    }
+
+    #[test]
+    fn should_beat_mimicking_the_dealer() {
+        let rules = Rules::default();
+        let basic = simulate(basic_strategy, &rules, 200_000, 9);
+        let mimic = simulate(dealer_strategy, &rules, 200_000, 9);
+        assert!(basic.house_edge() > -0.005 && basic.house_edge() < 0.025, "Basic strategy edge {}", basic.house_edge());
+        assert!(mimic.house_edge() > basic.house_edge() + 0.02, "Mimic edge {}", mimic.house_edge());
+    }
+}
@@ -0,0 +1,297 @@
+//!
+//! # Problem description:
+//! #
+//! # Boggle
+//!
+//! Boggle is played on a four by four board of letter dice. Players look
+//! for words of at least three letters made by moving from a die to any
+//! of its eight neighbours, using each die at most once in a word. Longer
+//! words score more: one point for three or four letters, two for five,
+//! three for six, five for seven and eleven for eight or more. One die
+//! has the face "Qu", a single face with two letters, because a lone Q is
+//! almost useless in English.
+//!
+//! The solver does a depth-first search from every die, walking a trie of
+//! the dictionary along with the path, so it abandons a path as soon as
+//! no word starts with its letters. Boards are made by shaking the sixteen
+//! dice of the current edition of the game into the tray.
+//!
+
+use rng::Rng;
+use shuffle::shuffle;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use trie::{Cursor, Trie};
+
+/// The shortest word that counts
+pub const MIN_LENGTH: usize = 3;
+
+/// The sixteen dice, one face per letter, with Q standing for the "Qu"
+/// face
+pub const DICE: [&str; 16] = [
+    "AAEEGN", "ABBJOO", "ACHOPS", "AFFKPS", "AOOTTW", "CIMOTU", "DEILRX", "DELRVY", "DISTTY", "EEGHNW", "EEINSU", "EHRTVW",
+    "EIOSST", "ELRTTY", "HIMNQU", "HLNNRZ",
+];
+
+/// A board of dice faces, in upper case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    faces: Vec<String>,
+}
+
+/// Errors reading a board
+#[derive(Debug, PartialEq, Eq)]
+pub enum BoardError {
+    /// No faces at all
+    Empty,
+    /// A row, counting from 0, with a different number of faces than the
+    /// first
+    Ragged(usize),
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BoardError::Empty => write!(f, "The board has no dice"),
+            BoardError::Ragged(row) => write!(f, "Row {} has a different length than the first", row),
+        }
+    }
+}
+
+impl Board {
+    /// Shakes the standard dice into a four by four board
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::boggle::Board;
+    /// use pprust::rng::Rng;
+    ///
+    /// let board = Board::random(&mut Rng::new(42));
+    /// assert_eq!((4, 4), (board.width(), board.height()));
+    /// ```
+    pub fn random(rng: &mut Rng) -> Board {
+        let mut dice = DICE;
+        shuffle(&mut dice, rng);
+        let faces = dice
+            .iter()
+            .map(|die| match die.as_bytes()[rng.gen_range(0, 6) as usize] {
+                b'Q' => "QU".to_string(),
+                face => (face as char).to_string(),
+            })
+            .collect();
+        Board { width: 4, height: 4, faces }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The face at a row and column
+    pub fn get(&self, row: usize, col: usize) -> Option<&str> {
+        if row < self.height && col < self.width {
+            Some(&self.faces[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    /// The cells touching a cell, diagonals included
+    fn neighbours(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let (row, col) = ((cell / self.width) as isize, (cell % self.width) as isize);
+        (-1..=1)
+            .flat_map(move |dr| (-1..=1).map(move |dc| (row + dr, col + dc)))
+            .filter(move |&(r, c)| {
+                (r, c) != (row, col) && r >= 0 && c >= 0 && (r as usize) < self.height && (c as usize) < self.width
+            })
+            .map(move |(r, c)| r as usize * self.width + c as usize)
+    }
+}
+
+impl FromStr for Board {
+    type Err = BoardError;
+
+    /// One row per line, faces separated by spaces. "Q" is read as "QU".
+    fn from_str(text: &str) -> Result<Board, BoardError> {
+        let rows: Vec<Vec<String>> = text
+            .lines()
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|face| match face.to_uppercase() {
+                        face if face == "Q" => "QU".to_string(),
+                        face => face,
+                    })
+                    .collect::<Vec<String>>()
+            })
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.first().ok_or(BoardError::Empty)?.len();
+        if let Some(ragged) = rows.iter().position(|row| row.len() != width) {
+            return Err(BoardError::Ragged(ragged));
+        }
+        Ok(Board { width, height: rows.len(), faces: rows.concat() })
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.faces.chunks(self.width) {
+            let faces: Vec<String> = row.iter().map(|face| format!("{:<2}", face)).collect();
+            writeln!(f, "{}", faces.join(" ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// The points a word scores
+///
+/// # Example
+/// ```
+/// use pprust::boggle::score;
+///
+/// assert_eq!(vec![0, 1, 1, 2, 3, 5, 11, 11],
+///            ["NO", "YES", "SURE", "MAYBE", "ALWAYS", "PERHAPS", "ABSOLUTE", "CERTAINLY"]
+///                .iter().map(|w| score(w)).collect::<Vec<u32>>());
+/// ```
+pub fn score(word: &str) -> u32 {
+    match word.chars().count() {
+        0..=2 => 0,
+        3 | 4 => 1,
+        5 => 2,
+        6 => 3,
+        7 => 5,
+        _ => 11,
+    }
+}
+
+/// Every dictionary word of at least `MIN_LENGTH` letters on the board, in
+/// order. The dictionary should be in upper case, like the faces.
+///
+/// # Example
+/// ```
+/// use pprust::boggle::{score, solve};
+/// use pprust::trie::Trie;
+///
+/// let board = "C A T\nR O D\nQ E S".parse().unwrap();
+/// let dictionary: Trie = ["CAT", "COAT", "CODE", "CODES", "DOOR", "QUEST", "QUOTE", "TO"].iter().collect();
+/// let words = solve(&board, &dictionary);
+/// assert_eq!(vec!["CAT", "COAT", "CODE", "CODES"], words);
+/// assert_eq!(5, words.iter().map(|w| score(w)).sum::<u32>());
+/// ```
+pub fn solve(board: &Board, dictionary: &Trie) -> Vec<String> {
+    let mut found = BTreeSet::new();
+    let mut used = vec![false; board.faces.len()];
+    let mut word = String::new();
+    for cell in 0..board.faces.len() {
+        search(board, cell, dictionary.root(), &mut used, &mut word, &mut found);
+    }
+    found.into_iter().collect()
+}
+
+fn search(
+    board: &Board,
+    cell: usize,
+    cursor: Cursor,
+    used: &mut [bool],
+    word: &mut String,
+    found: &mut BTreeSet<String>,
+) {
+    let face = &board.faces[cell];
+    let cursor = match face.chars().try_fold(cursor, |cursor, c| cursor.step(c)) {
+        Some(cursor) => cursor,
+        None => return,
+    };
+    word.push_str(face);
+    if cursor.is_word() && word.chars().count() >= MIN_LENGTH {
+        found.insert(word.clone());
+    }
+    used[cell] = true;
+    for next in board.neighbours(cell) {
+        if !used[next] {
+            search(board, next, cursor, used, word, found);
+        }
+    }
+    used[cell] = false;
+    word.truncate(word.len() - face.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_solve_boards() {
+        let board: Board = "
+            S E R S
+            P A T G
+            L I N E
+            S E R S
+        "
+        .parse()
+        .unwrap();
+        let dictionary: Trie = [
+            "SEA", "SEAT", "PAST", "PASTE", "LINE", "LINES", "TAP", "TAPE", "RATE", "GENT", "SPAT", "PATS", "SERS",
+            "NET", "ENTRAP", "PEA", "TINE", "TINES", "AT", "SEAS", "NINE",
+        ]
+        .iter()
+        .collect();
+        let words = solve(&board, &dictionary);
+        assert_eq!(
+            vec![
+                "ENTRAP", "GENT", "LINE", "LINES", "NET", "PATS", "PEA", "RATE", "SEA", "SEAT", "SERS", "SPAT", "TAP",
+                "TAPE", "TINE", "TINES"
+            ],
+            words
+        );
+        assert!(!words.contains(&"PAST".to_string()) && !words.contains(&"SEAS".to_string()));
+        assert!(!words.contains(&"AT".to_string()), "Too short");
+        assert!(!words.contains(&"NINE".to_string()), "Only one N, used once");
+        assert_eq!(20, words.iter().map(|w| score(w)).sum::<u32>());
+    }
+
+    #[test]
+    fn should_read_qu_as_two_letters() {
+        let board: Board = "q i\nt e".parse().unwrap();
+        assert_eq!(Some("QU"), board.get(0, 0));
+        assert_eq!("QU I\nT  E\n", board.to_string());
+        assert_eq!(board, board.to_string().parse().unwrap());
+        let dictionary: Trie = ["QUIET", "QUITE", "QIT", "TIE"].iter().collect();
+        assert_eq!(vec!["QUIET", "QUITE", "TIE"], solve(&board, &dictionary));
+        assert_eq!(Err(BoardError::Ragged(1)), "A B\nC".parse::<Board>());
+        assert_eq!(Err(BoardError::Empty), "".parse::<Board>());
+    }
+
+    /// Tries to give `face` a die, moving faces already given if needed
+    fn assign(face: usize, faces: &[char], owner: &mut [Option<usize>], seen: &mut [bool]) -> bool {
+        for die in 0..DICE.len() {
+            if DICE[die].contains(faces[face]) && !seen[die] {
+                seen[die] = true;
+                if owner[die].is_none_or(|other| assign(other, faces, owner, seen)) {
+                    owner[die] = Some(face);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn should_roll_the_standard_dice() {
+        let mut rng = Rng::new(269);
+        for _ in 0..100 {
+            let board = Board::random(&mut rng);
+            assert!(board.faces.iter().all(|f| f.len() == 1 || f == "QU"));
+            let faces: Vec<char> = board.faces.iter().map(|f| f.chars().next().unwrap()).collect();
+            let mut owner = vec![None; DICE.len()];
+            for face in 0..faces.len() {
+                assert!(assign(face, &faces, &mut owner, &mut [false; 16]), "No die left for a face in\n{}", board);
+            }
+        }
+    }
+}
@@ -0,0 +1,588 @@
+//!
+//! # Problem description:
+//! #
+//! # LRU and LFU Caches
+//!
+//! A cache keeps a bounded number of entries and, when full, evicts one
+//! to make room for a new one. A least recently used cache evicts the
+//! entry that was used longest ago; a least frequently used cache evicts
+//! the entry used fewest times, and of those the one used longest ago.
+//! Every operation should take constant time.
+//!
+//! Both caches keep their entries in a hash map from key to a slot in a
+//! slab, an array of nodes linked into doubly linked lists by index. The
+//! LRU cache keeps a single list, most recently used first, and moves an
+//! entry to the front when it is used. The LFU cache, after Shah, Mitra
+//! and Matani, keeps a list of frequencies in increasing order, each with
+//! a list of the entries used that many times: using an entry moves it to
+//! the next frequency, creating it after the current one if needed, and
+//! the entry to evict is at the back of the first frequency.
+//!
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The index of no node
+const NIL: usize = usize::MAX;
+
+struct Node<T> {
+    value: Option<T>,
+    prev: usize,
+    next: usize,
+}
+
+/// The ends of a doubly linked list of slab nodes
+#[derive(Clone, Copy)]
+struct List {
+    head: usize,
+    tail: usize,
+}
+
+impl List {
+    fn new() -> List {
+        List { head: NIL, tail: NIL }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.head == NIL
+    }
+}
+
+/// Nodes linked into lists by index, reusing the slots of removed nodes
+struct Slab<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+}
+
+impl<T> Slab<T> {
+    fn new() -> Slab<T> {
+        Slab { nodes: Vec::new(), free: Vec::new() }
+    }
+
+    /// Stores a value in an unlinked node, returning its index
+    fn insert(&mut self, value: T) -> usize {
+        let node = Node { value: Some(value), prev: NIL, next: NIL };
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Frees an unlinked node, returning its value
+    fn remove(&mut self, i: usize) -> T {
+        self.free.push(i);
+        self.nodes[i].value.take().expect("Removed a free node")
+    }
+
+    fn get(&self, i: usize) -> &T {
+        self.nodes[i].value.as_ref().expect("Read a free node")
+    }
+
+    fn get_mut(&mut self, i: usize) -> &mut T {
+        self.nodes[i].value.as_mut().expect("Wrote a free node")
+    }
+
+    fn next(&self, i: usize) -> usize {
+        self.nodes[i].next
+    }
+
+    /// Links node `i` after node `at`, or at the front if `at` is `NIL`
+    fn link_after(&mut self, list: &mut List, at: usize, i: usize) {
+        let next = if at == NIL { list.head } else { self.nodes[at].next };
+        self.nodes[i].prev = at;
+        self.nodes[i].next = next;
+        if at == NIL {
+            list.head = i;
+        } else {
+            self.nodes[at].next = i;
+        }
+        if next == NIL {
+            list.tail = i;
+        } else {
+            self.nodes[next].prev = i;
+        }
+    }
+
+    fn push_front(&mut self, list: &mut List, i: usize) {
+        self.link_after(list, NIL, i);
+    }
+
+    fn unlink(&mut self, list: &mut List, i: usize) {
+        let (prev, next) = (self.nodes[i].prev, self.nodes[i].next);
+        if prev == NIL {
+            list.head = next;
+        } else {
+            self.nodes[prev].next = next;
+        }
+        if next == NIL {
+            list.tail = prev;
+        } else {
+            self.nodes[next].prev = prev;
+        }
+    }
+}
+
+/// What a cache calls with each entry it evicts for lack of room
+type EvictFn<K, V> = Box<dyn FnMut(K, V)>;
+
+/// A cache evicting the least recently used entry
+///
+/// # Example
+/// ```
+/// use pprust::cache::LruCache;
+///
+/// let mut cache = LruCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// assert_eq!(Some(&1), cache.get(&"a"));
+/// cache.put("c", 3);
+/// assert_eq!(None, cache.get(&"b"));
+/// assert_eq!(vec![(&"c", &3), (&"a", &1)], cache.iter().collect::<Vec<_>>());
+/// ```
+pub struct LruCache<K, V> {
+    capacity: usize,
+    map: HashMap<K, usize>,
+    entries: Slab<(K, V)>,
+    /// Most recently used first
+    recency: List,
+    on_evict: Option<EvictFn<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// A cache holding up to `capacity` entries, which must be positive
+    pub fn new(capacity: usize) -> LruCache<K, V> {
+        assert!(capacity > 0, "A cache needs room for an entry");
+        LruCache { capacity, map: HashMap::new(), entries: Slab::new(), recency: List::new(), on_evict: None }
+    }
+
+    /// A cache that calls `on_evict` with every entry it evicts to make
+    /// room, but not with entries removed or replaced
+    pub fn with_eviction<F: FnMut(K, V) + 'static>(capacity: usize, on_evict: F) -> LruCache<K, V> {
+        let mut cache = LruCache::new(capacity);
+        cache.on_evict = Some(Box::new(on_evict));
+        cache
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+        where K: Borrow<Q>
+    {
+        self.map.contains_key(key)
+    }
+
+    /// The value for a key, making it the most recently used
+    pub fn get<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&V>
+        where K: Borrow<Q>
+    {
+        let i = *self.map.get(key)?;
+        self.entries.unlink(&mut self.recency, i);
+        self.entries.push_front(&mut self.recency, i);
+        Some(&self.entries.get(i).1)
+    }
+
+    /// The value for a key, leaving the order of use alone
+    pub fn peek<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+        where K: Borrow<Q>
+    {
+        self.map.get(key).map(|&i| &self.entries.get(i).1)
+    }
+
+    /// Stores a value as the most recently used, returning the value it
+    /// replaces. Storing a new key in a full cache evicts the least
+    /// recently used entry.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&i) = self.map.get(&key) {
+            self.entries.unlink(&mut self.recency, i);
+            self.entries.push_front(&mut self.recency, i);
+            return Some(::std::mem::replace(&mut self.entries.get_mut(i).1, value));
+        }
+        if self.map.len() == self.capacity {
+            let last = self.recency.tail;
+            self.entries.unlink(&mut self.recency, last);
+            let (old_key, old_value) = self.entries.remove(last);
+            self.map.remove(&old_key);
+            if let Some(ref mut on_evict) = self.on_evict {
+                on_evict(old_key, old_value);
+            }
+        }
+        let i = self.entries.insert((key.clone(), value));
+        self.entries.push_front(&mut self.recency, i);
+        self.map.insert(key, i);
+        None
+    }
+
+    /// Removes an entry, returning its value
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+        where K: Borrow<Q>
+    {
+        let i = self.map.remove(key)?;
+        self.entries.unlink(&mut self.recency, i);
+        Some(self.entries.remove(i).1)
+    }
+
+    /// The entries, most recently used first
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)> + 'a {
+        let mut i = self.recency.head;
+        ::std::iter::from_fn(move || {
+            if i == NIL {
+                return None;
+            }
+            let (key, value) = self.entries.get(i);
+            i = self.entries.next(i);
+            Some((key, value))
+        })
+    }
+}
+
+/// An entry of an LFU cache, with the frequency node it is listed under
+struct LfuEntry<K, V> {
+    key: K,
+    value: V,
+    frequency: usize,
+}
+
+/// A frequency of use, with the entries used that many times, most
+/// recently used first
+struct Frequency {
+    count: u64,
+    entries: List,
+}
+
+/// A cache evicting the least frequently used entry, and of those the
+/// least recently used
+///
+/// # Example
+/// ```
+/// use pprust::cache::LfuCache;
+///
+/// let mut cache = LfuCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// cache.get(&"a");
+/// cache.get(&"a");
+/// cache.get(&"b");
+/// cache.put("c", 3);
+/// assert!(cache.contains_key(&"a"));
+/// assert!(!cache.contains_key(&"b"));
+/// assert_eq!(Some(3), cache.frequency(&"a"));
+/// ```
+pub struct LfuCache<K, V> {
+    capacity: usize,
+    map: HashMap<K, usize>,
+    entries: Slab<LfuEntry<K, V>>,
+    frequencies: Slab<Frequency>,
+    /// The frequencies that have entries, lowest first
+    by_count: List,
+    on_evict: Option<EvictFn<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    /// A cache holding up to `capacity` entries, which must be positive
+    pub fn new(capacity: usize) -> LfuCache<K, V> {
+        assert!(capacity > 0, "A cache needs room for an entry");
+        LfuCache {
+            capacity,
+            map: HashMap::new(),
+            entries: Slab::new(),
+            frequencies: Slab::new(),
+            by_count: List::new(),
+            on_evict: None,
+        }
+    }
+
+    /// A cache that calls `on_evict` with every entry it evicts to make
+    /// room, but not with entries removed or replaced
+    pub fn with_eviction<F: FnMut(K, V) + 'static>(capacity: usize, on_evict: F) -> LfuCache<K, V> {
+        let mut cache = LfuCache::new(capacity);
+        cache.on_evict = Some(Box::new(on_evict));
+        cache
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+        where K: Borrow<Q>
+    {
+        self.map.contains_key(key)
+    }
+
+    /// How many times an entry was used, counting when it was stored
+    pub fn frequency<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<u64>
+        where K: Borrow<Q>
+    {
+        self.map.get(key).map(|&i| self.frequencies.get(self.entries.get(i).frequency).count)
+    }
+
+    /// The value for a key, counting a use of it
+    pub fn get<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&V>
+        where K: Borrow<Q>
+    {
+        let i = *self.map.get(key)?;
+        self.touch(i);
+        Some(&self.entries.get(i).value)
+    }
+
+    /// The value for a key, without counting a use
+    pub fn peek<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+        where K: Borrow<Q>
+    {
+        self.map.get(key).map(|&i| &self.entries.get(i).value)
+    }
+
+    /// Stores a value, returning the value it replaces. Replacing counts
+    /// as a use; storing a new key in a full cache evicts the least
+    /// frequently used entry.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&i) = self.map.get(&key) {
+            self.touch(i);
+            return Some(::std::mem::replace(&mut self.entries.get_mut(i).value, value));
+        }
+        if self.map.len() == self.capacity {
+            let lowest = self.by_count.head;
+            let last = self.frequencies.get(lowest).entries.tail;
+            self.detach(last);
+            let LfuEntry { key: old_key, value: old_value, .. } = self.entries.remove(last);
+            self.map.remove(&old_key);
+            if let Some(ref mut on_evict) = self.on_evict {
+                on_evict(old_key, old_value);
+            }
+        }
+        let first = self.by_count.head;
+        let once = if first != NIL && self.frequencies.get(first).count == 1 {
+            first
+        } else {
+            let once = self.frequencies.insert(Frequency { count: 1, entries: List::new() });
+            self.frequencies.push_front(&mut self.by_count, once);
+            once
+        };
+        let i = self.entries.insert(LfuEntry { key: key.clone(), value, frequency: once });
+        self.entries.push_front(&mut self.frequencies.get_mut(once).entries, i);
+        self.map.insert(key, i);
+        None
+    }
+
+    /// Removes an entry, returning its value
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+        where K: Borrow<Q>
+    {
+        let i = self.map.remove(key)?;
+        self.detach(i);
+        Some(self.entries.remove(i).value)
+    }
+
+    /// Moves an entry to the next frequency
+    fn touch(&mut self, i: usize) {
+        let current = self.entries.get(i).frequency;
+        let count = self.frequencies.get(current).count + 1;
+        let next = self.frequencies.next(current);
+        let target = if next != NIL && self.frequencies.get(next).count == count {
+            next
+        } else {
+            let target = self.frequencies.insert(Frequency { count, entries: List::new() });
+            self.frequencies.link_after(&mut self.by_count, current, target);
+            target
+        };
+        self.detach(i);
+        self.entries.push_front(&mut self.frequencies.get_mut(target).entries, i);
+        self.entries.get_mut(i).frequency = target;
+    }
+
+    /// Unlinks an entry from its frequency, dropping the frequency if no
+    /// other entry has it
+    fn detach(&mut self, i: usize) {
+        let frequency = self.entries.get(i).frequency;
+        self.entries.unlink(&mut self.frequencies.get_mut(frequency).entries, i);
+        if self.frequencies.get(frequency).entries.is_empty() {
+            self.frequencies.unlink(&mut self.by_count, frequency);
+            self.frequencies.remove(frequency);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// The operations the property tests run on a cache and its model
+    #[derive(Debug)]
+    enum Op {
+        Get(u64),
+        Peek(u64),
+        Put(u64, u64),
+        Remove(u64),
+    }
+
+    fn random_ops(rng: &mut Rng, n: usize, keys: u64) -> Vec<Op> {
+        (0..n)
+            .map(|_| {
+                let key = rng.gen_range(0, keys);
+                match rng.gen_range(0, 10) {
+                    0..=3 => Op::Get(key),
+                    4 => Op::Peek(key),
+                    5..=8 => Op::Put(key, rng.gen_range(0, 1000)),
+                    _ => Op::Remove(key),
+                }
+            })
+            .collect()
+    }
+
+    /// A cache as a list of entries with their frequency and when they
+    /// were last used, searched in full for every operation
+    struct Model {
+        capacity: usize,
+        lfu: bool,
+        entries: Vec<(u64, u64, u64, u64)>,
+        clock: u64,
+        evicted: Vec<(u64, u64)>,
+    }
+
+    impl Model {
+        fn position(&self, key: u64) -> Option<usize> {
+            self.entries.iter().position(|e| e.0 == key)
+        }
+
+        fn touch(&mut self, i: usize) {
+            self.clock += 1;
+            self.entries[i].2 += 1;
+            self.entries[i].3 = self.clock;
+        }
+
+        fn apply(&mut self, op: &Op) -> Option<u64> {
+            match *op {
+                Op::Get(key) => self.position(key).map(|i| {
+                    self.touch(i);
+                    self.entries[i].1
+                }),
+                Op::Peek(key) => self.position(key).map(|i| self.entries[i].1),
+                Op::Put(key, value) => {
+                    if let Some(i) = self.position(key) {
+                        self.touch(i);
+                        return Some(::std::mem::replace(&mut self.entries[i].1, value));
+                    }
+                    if self.entries.len() == self.capacity {
+                        let lfu = self.lfu;
+                        let victim = (0..self.entries.len())
+                            .min_by_key(|&i| {
+                                let e = self.entries[i];
+                                (if lfu { e.2 } else { 0 }, e.3)
+                            })
+                            .unwrap();
+                        let e = self.entries.remove(victim);
+                        self.evicted.push((e.0, e.1));
+                    }
+                    self.clock += 1;
+                    self.entries.push((key, value, 1, self.clock));
+                    None
+                }
+                Op::Remove(key) => self.position(key).map(|i| self.entries.remove(i).1),
+            }
+        }
+    }
+
+    #[test]
+    fn should_behave_like_the_model_lru() {
+        let mut rng = Rng::new(262);
+        for _ in 0..200 {
+            let capacity = rng.gen_range(1, 6) as usize;
+            let evicted = Rc::new(RefCell::new(Vec::new()));
+            let log = evicted.clone();
+            let mut cache = LruCache::with_eviction(capacity, move |k, v| log.borrow_mut().push((k, v)));
+            let mut model = Model { capacity, lfu: false, entries: Vec::new(), clock: 0, evicted: Vec::new() };
+            for op in random_ops(&mut rng, 100, 10) {
+                let result = match op {
+                    Op::Get(key) => cache.get(&key).cloned(),
+                    Op::Peek(key) => cache.peek(&key).cloned(),
+                    Op::Put(key, value) => cache.put(key, value),
+                    Op::Remove(key) => cache.remove(&key),
+                };
+                assert_eq!(model.apply(&op), result, "{:?}", op);
+                assert_eq!(model.evicted, *RefCell::borrow(&evicted));
+                let mut expected: Vec<(u64, u64)> = model.entries.iter().map(|e| (e.3, e.0)).collect();
+                expected.sort_by(|a, b| b.cmp(a));
+                let keys: Vec<u64> = cache.iter().map(|(&k, _)| k).collect();
+                assert_eq!(expected.iter().map(|e| e.1).collect::<Vec<_>>(), keys);
+            }
+        }
+    }
+
+    #[test]
+    fn should_behave_like_the_model_lfu() {
+        let mut rng = Rng::new(2622);
+        for _ in 0..200 {
+            let capacity = rng.gen_range(1, 6) as usize;
+            let evicted = Rc::new(RefCell::new(Vec::new()));
+            let log = evicted.clone();
+            let mut cache = LfuCache::with_eviction(capacity, move |k, v| log.borrow_mut().push((k, v)));
+            let mut model = Model { capacity, lfu: true, entries: Vec::new(), clock: 0, evicted: Vec::new() };
+            for op in random_ops(&mut rng, 100, 10) {
+                let result = match op {
+                    Op::Get(key) => cache.get(&key).cloned(),
+                    Op::Peek(key) => cache.peek(&key).cloned(),
+                    Op::Put(key, value) => cache.put(key, value),
+                    Op::Remove(key) => cache.remove(&key),
+                };
+                assert_eq!(model.apply(&op), result, "{:?}", op);
+                assert_eq!(model.evicted, *RefCell::borrow(&evicted));
+                assert_eq!(model.entries.len(), cache.len());
+                for e in &model.entries {
+                    assert_eq!(Some(e.2), cache.frequency(&e.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_reuse_slots() {
+        let mut cache = LfuCache::new(3);
+        for i in 0..1000 {
+            cache.put(i, i);
+            cache.get(&i);
+        }
+        assert_eq!(3, cache.len());
+        assert!(cache.entries.nodes.len() <= 4);
+        assert!(cache.frequencies.nodes.len() <= 4);
+        let mut cache = LruCache::new(2);
+        for i in 0..1000 {
+            cache.put(i.to_string(), i);
+        }
+        assert_eq!(Some(&999), cache.peek("999"));
+        assert_eq!(2, cache.entries.nodes.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_need_room_for_an_entry() {
+        LruCache::<u8, u8>::new(0);
+    }
+}
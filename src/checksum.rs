@@ -0,0 +1,210 @@
+//!
+//! # Problem description:
+//! #
+//! # CRC-32 and Adler-32
+//!
+//! A checksum is a short value computed from some data that changes when
+//! the data does, so that a copy can be checked against it. CRC-32, used
+//! by zip, gzip and PNG, is the remainder of dividing the bits of the
+//! data, as a polynomial over GF(2), by a fixed polynomial of degree 32;
+//! it catches every burst of errors up to 32 bits long. The division goes
+//! a byte at a time with a table of the remainders of the 256 bytes.
+//!
+//! Adler-32, used by zlib, is faster and weaker: two sums modulo 65521,
+//! the largest prime below 2^16, one of the bytes plus one and the other
+//! of the first sum after each byte.
+//!
+//! Both can be computed over a stream, a piece at a time, with the same
+//! result as over the whole data at once.
+//!
+
+use std::io;
+use std::io::prelude::*;
+
+/// The CRC-32 polynomial, with the bits reversed, as the bytes go least
+/// significant bit first
+const POLYNOMIAL: u32 = 0xedb8_8320;
+
+/// The remainder of each byte
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ POLYNOMIAL } else { crc >> 1 };
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+}
+
+/// The Adler-32 modulus
+const MOD_ADLER: u32 = 65521;
+
+/// The most bytes that can be added before the second sum of Adler-32
+/// may overflow a `u32`, so the modulo is taken once per this many
+const NMAX: usize = 5552;
+
+/// A CRC-32 computed a piece at a time
+///
+/// # Example
+/// ```
+/// use pprust::checksum::Crc32;
+///
+/// let mut crc = Crc32::new();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(0xcbf4_3926, crc.value());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    /// The remainder so far, inverted
+    crc: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Crc32 {
+        Crc32 { crc: !0 }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.crc = (self.crc >> 8) ^ TABLE[((self.crc ^ byte as u32) & 0xff) as usize];
+        }
+    }
+
+    /// The checksum of the bytes so far
+    pub fn value(&self) -> u32 {
+        !self.crc
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Crc32 {
+        Crc32::new()
+    }
+}
+
+/// An Adler-32 computed a piece at a time
+///
+/// # Example
+/// ```
+/// use pprust::checksum::Adler32;
+///
+/// let mut adler = Adler32::new();
+/// adler.update(b"Wiki");
+/// adler.update(b"pedia");
+/// assert_eq!(0x11e6_0398, adler.value());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    pub fn new() -> Adler32 {
+        Adler32 { a: 1, b: 0 }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(NMAX) {
+            for &byte in chunk {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= MOD_ADLER;
+            self.b %= MOD_ADLER;
+        }
+    }
+
+    /// The checksum of the bytes so far
+    pub fn value(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Adler32 {
+        Adler32::new()
+    }
+}
+
+/// Feeds everything a reader has to `update`
+fn read_all<R: Read, F: FnMut(&[u8])>(mut reader: R, mut update: F) -> io::Result<()> {
+    let mut buffer = [0; 8192];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => update(&buffer[..n]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The CRC-32 of everything a reader has
+///
+/// # Example
+/// ```
+/// use pprust::checksum::crc32;
+///
+/// let text = "The quick brown fox jumps over the lazy dog";
+/// assert_eq!(0x414f_a339, crc32(text.as_bytes()).unwrap());
+/// ```
+pub fn crc32<R: Read>(reader: R) -> io::Result<u32> {
+    let mut crc = Crc32::new();
+    read_all(reader, |bytes| crc.update(bytes))?;
+    Ok(crc.value())
+}
+
+/// The Adler-32 of everything a reader has
+pub fn adler32<R: Read>(reader: R) -> io::Result<u32> {
+    let mut adler = Adler32::new();
+    read_all(reader, |bytes| adler.update(bytes))?;
+    Ok(adler.value())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_known_vectors() {
+        assert_eq!(0, crc32(&b""[..]).unwrap());
+        assert_eq!(0xe8b7_be43, crc32(&b"a"[..]).unwrap());
+        assert_eq!(0xcbf4_3926, crc32(&b"123456789"[..]).unwrap());
+        assert_eq!(1, adler32(&b""[..]).unwrap());
+        assert_eq!(0x0062_0062, adler32(&b"a"[..]).unwrap());
+        assert_eq!(0x091e_01de, adler32(&b"123456789"[..]).unwrap());
+    }
+
+    #[test]
+    fn should_give_the_same_checksum_in_pieces() {
+        // Long enough for the Adler-32 sums to wrap around many times
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i * 7 % 256) as u8 | 0x80).collect();
+        let (mut crc, mut adler) = (Crc32::new(), Adler32::new());
+        for piece in data.chunks(777) {
+            crc.update(piece);
+            adler.update(piece);
+        }
+        assert_eq!(crc32(&data[..]).unwrap(), crc.value());
+        assert_eq!(adler32(&data[..]).unwrap(), adler.value());
+        let (mut a, mut b) = (1u64, 0u64);
+        for &byte in &data {
+            a = (a + byte as u64) % MOD_ADLER as u64;
+            b = (b + a) % MOD_ADLER as u64;
+        }
+        assert_eq!(((b << 16) | a) as u32, adler.value());
+        let mut corrupted = data.clone();
+        corrupted[50_000] ^= 4;
+        assert_ne!(crc.value(), crc32(&corrupted[..]).unwrap());
+        assert_ne!(adler.value(), adler32(&corrupted[..]).unwrap());
+    }
+}
@@ -0,0 +1,538 @@
+//!
+//! # Problem description:
+//! #
+//! # Permutations
+//!
+//! Generate all the permutations of *n* items. Heap's algorithm produces
+//! each permutation from the previous one with a single swap, which is
+//! the fastest way to visit them all; the lexicographic successor
+//! algorithm instead visits them in sorted order and can continue from
+//! any permutation. Permutations in lexicographic order can also be
+//! numbered: *rank* maps a permutation to its position and *unrank*
+//! maps a position back to the permutation, via the factorial number
+//! system.
+//!
+//! The iterators work on arrays of indices, so they can permute any
+//! collection by indexing into it.
+//!
+//! # Combinations
+//!
+//! Generate all the subsets of a set of *n* items, and all the subsets
+//! with exactly *k* items. Subsets of a small set fit in the bits of a
+//! machine word, and Gosper's hack steps from one *k*-bit word to the
+//! next larger one with a handful of arithmetic operations, without
+//! allocating anything.
+//!
+
+/// Returns n!, panicking if it does not fit in a u64 (n > 20)
+pub fn factorial(n: usize) -> u64 {
+    (1..=n as u64).fold(1u64, |acc, k| acc.checked_mul(k).expect("factorial overflows u64"))
+}
+
+/// Rearranges a slice into the next permutation in lexicographic order.
+///
+/// Returns false, leaving the slice sorted in ascending order, when the
+/// slice was already the last permutation.
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::next_permutation;
+///
+/// let mut v = vec![1, 3, 2];
+/// assert!(next_permutation(&mut v));
+/// assert_eq!(vec![2, 1, 3], v);
+/// let mut last = vec![3, 2, 1];
+/// assert!(!next_permutation(&mut last));
+/// assert_eq!(vec![1, 2, 3], last);
+/// ```
+pub fn next_permutation<T: Ord>(slice: &mut [T]) -> bool {
+    if slice.len() < 2 {
+        return false;
+    }
+    // Find the longest non-increasing suffix; its predecessor is the pivot
+    let mut i = slice.len() - 1;
+    while i > 0 && slice[i - 1] >= slice[i] {
+        i -= 1;
+    }
+    if i == 0 {
+        slice.reverse();
+        return false;
+    }
+    let pivot = i - 1;
+    let mut j = slice.len() - 1;
+    while slice[j] <= slice[pivot] {
+        j -= 1;
+    }
+    slice.swap(pivot, j);
+    slice[i..].reverse();
+    true
+}
+
+/// Iterator over all permutations of `0..n` using Heap's algorithm
+pub struct Permutations {
+    indices: Vec<usize>,
+    counters: Vec<usize>,
+    i: usize,
+    started: bool,
+    remaining: Option<u64>,
+}
+
+/// Returns an iterator over the permutations of `0..n`, in the order
+/// produced by Heap's algorithm, each differing from the previous one by
+/// a single swap
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::permutations_iter;
+///
+/// let all: Vec<Vec<usize>> = permutations_iter(3).collect();
+/// assert_eq!(6, all.len());
+/// assert_eq!(vec![0, 1, 2], all[0]);
+/// ```
+pub fn permutations_iter(n: usize) -> Permutations {
+    Permutations {
+        indices: (0..n).collect(),
+        counters: vec![0; n],
+        i: 1,
+        started: false,
+        remaining: (1..=n as u64).try_fold(1u64, |acc, k| acc.checked_mul(k)),
+    }
+}
+
+impl Iterator for Permutations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if !self.started {
+            self.started = true;
+        } else {
+            loop {
+                if self.i >= self.indices.len() {
+                    return None;
+                }
+                let i = self.i;
+                if self.counters[i] < i {
+                    let j = if i.is_multiple_of(2) { 0 } else { self.counters[i] };
+                    self.indices.swap(j, i);
+                    self.counters[i] += 1;
+                    self.i = 1;
+                    break;
+                }
+                self.counters[i] = 0;
+                self.i += 1;
+            }
+        }
+        self.remaining = self.remaining.map(|r| r - 1);
+        Some(self.indices.clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining {
+            Some(r) if r <= usize::MAX as u64 => (r as usize, Some(r as usize)),
+            _ => (usize::MAX, None),
+        }
+    }
+}
+
+/// Iterator over all permutations of `0..n` in lexicographic order
+pub struct LexicographicPermutations {
+    next: Option<Vec<usize>>,
+}
+
+/// Returns an iterator over the permutations of `0..n` in lexicographic
+/// order
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::lexicographic_permutations;
+///
+/// let all: Vec<Vec<usize>> = lexicographic_permutations(3).collect();
+/// assert_eq!(vec![vec![0, 1, 2], vec![0, 2, 1], vec![1, 0, 2],
+///                 vec![1, 2, 0], vec![2, 0, 1], vec![2, 1, 0]], all);
+/// ```
+pub fn lexicographic_permutations(n: usize) -> LexicographicPermutations {
+    LexicographicPermutations { next: Some((0..n).collect()) }
+}
+
+impl Iterator for LexicographicPermutations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let current = self.next.take()?;
+        let mut successor = current.clone();
+        if next_permutation(&mut successor) {
+            self.next = Some(successor);
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.next {
+            None => (0, Some(0)),
+            Some(ref p) if p.len() <= 20 => {
+                let remaining = factorial(p.len()) - rank(p);
+                (remaining as usize, Some(remaining as usize))
+            }
+            Some(_) => (usize::MAX, None),
+        }
+    }
+}
+
+/// Returns the position of a permutation of `0..n` in lexicographic
+/// order, counting from zero
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::rank;
+///
+/// assert_eq!(0, rank(&[0, 1, 2]));
+/// assert_eq!(3, rank(&[1, 2, 0]));
+/// assert_eq!(5, rank(&[2, 1, 0]));
+/// ```
+pub fn rank(permutation: &[usize]) -> u64 {
+    let n = permutation.len();
+    let mut result = 0;
+    for i in 0..n {
+        // The Lehmer code digit: how many later items are smaller
+        let smaller = permutation[i + 1..].iter().filter(|&&x| x < permutation[i]).count() as u64;
+        result += smaller * factorial(n - 1 - i);
+    }
+    result
+}
+
+/// Returns the permutation of `0..n` at position `rank` in lexicographic
+/// order, or `None` if `rank` is not smaller than n!
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::unrank;
+///
+/// assert_eq!(Some(vec![1, 2, 0]), unrank(3, 3));
+/// assert_eq!(None, unrank(3, 6));
+/// ```
+pub fn unrank(n: usize, mut rank: u64) -> Option<Vec<usize>> {
+    if rank >= factorial(n) {
+        return None;
+    }
+    let mut available: Vec<usize> = (0..n).collect();
+    let mut result = Vec::with_capacity(n);
+    for i in (0..n).rev() {
+        let f = factorial(i);
+        let digit = (rank / f) as usize;
+        rank %= f;
+        result.push(available.remove(digit));
+    }
+    Some(result)
+}
+
+/// Returns the binomial coefficient C(n, k), panicking on overflow
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::binomial;
+///
+/// assert_eq!(10, binomial(5, 2));
+/// assert_eq!(0, binomial(2, 5));
+/// ```
+pub fn binomial(n: u64, k: u64) -> u64 {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    // Each partial product C(n - k + i, i) is an integer, so the division is exact
+    (1..=k).fold(1u64, |acc, i| {
+        let wide = acc as u128 * (n - k + i) as u128 / i as u128;
+        assert!(wide <= u64::MAX as u128, "binomial overflows u64");
+        wide as u64
+    })
+}
+
+/// Iterator over all subsets of `0..n` as bitmasks, in increasing order
+pub struct Subsets {
+    next: u64,
+    end: u64,
+}
+
+/// Returns an iterator over all 2^n subsets of `0..n`, as bitmasks where
+/// bit *i* set means item *i* is in the subset. `n` must be at most 63.
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::subsets;
+///
+/// let all: Vec<u64> = subsets(2).collect();
+/// assert_eq!(vec![0b00, 0b01, 0b10, 0b11], all);
+/// ```
+pub fn subsets(n: usize) -> Subsets {
+    assert!(n < 64, "subsets supports at most 63 items");
+    Subsets { next: 0, end: 1 << n }
+}
+
+impl Iterator for Subsets {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(self.next - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.end - self.next) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Iterator over the k-subsets of `0..n` as bitmasks, using Gosper's hack
+pub struct CombinationMasks {
+    next: Option<u64>,
+    end: u64,
+    remaining: u64,
+}
+
+/// Returns an iterator over the subsets of `0..n` with exactly `k`
+/// items, as bitmasks in increasing order. `n` must be at most 63.
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::combination_masks;
+///
+/// let all: Vec<u64> = combination_masks(4, 2).collect();
+/// assert_eq!(vec![0b0011, 0b0101, 0b0110, 0b1001, 0b1010, 0b1100], all);
+/// ```
+pub fn combination_masks(n: usize, k: usize) -> CombinationMasks {
+    assert!(n < 64, "combination_masks supports at most 63 items");
+    let first = if k <= n { Some((1u64 << k) - 1) } else { None };
+    CombinationMasks { next: first, end: 1 << n, remaining: binomial(n as u64, k as u64) }
+}
+
+impl Iterator for CombinationMasks {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let x = self.next?;
+        self.next = if x == 0 {
+            None
+        } else {
+            // Gosper's hack: move the lowest block of ones up by one position
+            // and pack the rest of that block back at the bottom
+            let lowest = x & x.wrapping_neg();
+            let ripple = x + lowest;
+            let next = (((ripple ^ x) >> 2) / lowest) | ripple;
+            if next < self.end { Some(next) } else { None }
+        };
+        self.remaining -= 1;
+        Some(x)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+/// Iterator over the k-subsets of `0..n` as sorted index arrays
+pub struct Combinations {
+    indices: Vec<usize>,
+    n: usize,
+    started: bool,
+    done: bool,
+}
+
+/// Returns an iterator over the subsets of `0..n` with exactly `k` items,
+/// as sorted index arrays in lexicographic order. Unlike
+/// `combination_masks` there is no limit on `n`.
+///
+/// # Example
+/// ```
+/// use pprust::combinatorics::combinations;
+///
+/// let all: Vec<Vec<usize>> = combinations(4, 3).collect();
+/// assert_eq!(vec![vec![0, 1, 2], vec![0, 1, 3], vec![0, 2, 3], vec![1, 2, 3]], all);
+/// ```
+pub fn combinations(n: usize, k: usize) -> Combinations {
+    Combinations { indices: (0..k).collect(), n, started: false, done: k > n }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(self.indices.clone());
+        }
+        let k = self.indices.len();
+        // Find the rightmost index that can still move right
+        let i = match (0..k).rev().find(|&i| self.indices[i] < self.n - k + i) {
+            Some(i) => i,
+            None => {
+                self.done = true;
+                return None;
+            }
+        };
+        self.indices[i] += 1;
+        for j in i + 1..k {
+            self.indices[j] = self.indices[j - 1] + 1;
+        }
+        Some(self.indices.clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        // The combinations after the current one: count, for each position, the
+        // ways of completing it with a larger index there and the same prefix
+        let k = self.indices.len();
+        let mut remaining: u64 = if self.started { 0 } else { 1 };
+        for i in 0..k {
+            let rest = (k - 1 - i) as u64;
+            for v in self.indices[i] + 1..=self.n - k + i {
+                remaining += binomial((self.n - 1 - v) as u64, rest);
+            }
+        }
+        (remaining as usize, Some(remaining as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn should_generate_all_permutations_with_heaps_algorithm() {
+        for n in 0..7 {
+            let all: Vec<Vec<usize>> = permutations_iter(n).collect();
+            assert_eq!(factorial(n) as usize, all.len());
+            let distinct: HashSet<Vec<usize>> = all.iter().cloned().collect();
+            assert_eq!(all.len(), distinct.len(), "Permutations of {} should be distinct", n);
+        }
+    }
+
+    #[test]
+    fn should_differ_by_one_swap_in_heaps_algorithm() {
+        let all: Vec<Vec<usize>> = permutations_iter(5).collect();
+        for pair in all.windows(2) {
+            let differences = pair[0].iter().zip(pair[1].iter()).filter(|&(a, b)| a != b).count();
+            assert_eq!(2, differences);
+        }
+    }
+
+    #[test]
+    fn should_report_exact_size_hints() {
+        let mut perms = permutations_iter(4);
+        assert_eq!((24, Some(24)), perms.size_hint());
+        perms.next();
+        assert_eq!((23, Some(23)), perms.size_hint());
+        let mut lex = lexicographic_permutations(4);
+        lex.next();
+        lex.next();
+        assert_eq!((22, Some(22)), lex.size_hint());
+    }
+
+    #[test]
+    fn should_generate_lexicographic_order() {
+        let all: Vec<Vec<usize>> = lexicographic_permutations(5).collect();
+        assert_eq!(120, all.len());
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn should_step_through_permutations_with_duplicates() {
+        let mut v = vec![1, 1, 2];
+        let mut seen = vec![v.clone()];
+        while next_permutation(&mut v) {
+            seen.push(v.clone());
+        }
+        assert_eq!(vec![vec![1, 1, 2], vec![1, 2, 1], vec![2, 1, 1]], seen);
+    }
+
+    #[test]
+    fn should_rank_and_unrank_consistently() {
+        for (i, p) in lexicographic_permutations(5).enumerate() {
+            assert_eq!(i as u64, rank(&p));
+            assert_eq!(Some(p), unrank(5, i as u64));
+        }
+    }
+
+    #[test]
+    fn should_unrank_large_permutations() {
+        let last: Vec<usize> = (0..20).rev().collect();
+        assert_eq!(Some(last.clone()), unrank(20, factorial(20) - 1));
+        assert_eq!(factorial(20) - 1, rank(&last));
+    }
+
+    #[test]
+    fn should_calculate_binomials() {
+        assert_eq!(1, binomial(0, 0));
+        assert_eq!(1, binomial(10, 0));
+        assert_eq!(252, binomial(10, 5));
+        assert_eq!(7_219_428_434_016_265_740, binomial(66, 33));
+    }
+
+    #[test]
+    fn should_generate_all_subsets() {
+        for n in 0..10 {
+            let all: Vec<u64> = subsets(n).collect();
+            assert_eq!(1 << n, all.len());
+            assert_eq!(all.len(), subsets(n).size_hint().0);
+        }
+    }
+
+    #[test]
+    fn should_generate_binomial_many_masks() {
+        for n in 0..12 {
+            for k in 0..=n + 1 {
+                let masks: Vec<u64> = combination_masks(n, k).collect();
+                assert_eq!(binomial(n as u64, k as u64) as usize, masks.len(), "C({}, {})", n, k);
+                assert!(masks.iter().all(|m| m.count_ones() as usize == k && *m < 1 << n));
+                assert!(masks.windows(2).all(|w| w[0] < w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn should_generate_masks_near_the_word_limit() {
+        assert_eq!(63, combination_masks(63, 62).count());
+        assert_eq!(Some(u64::MAX >> 1), combination_masks(63, 63).next());
+    }
+
+    #[test]
+    fn should_generate_binomial_many_combinations() {
+        for n in 0..10 {
+            for k in 0..=n + 1 {
+                let all: Vec<Vec<usize>> = combinations(n, k).collect();
+                assert_eq!(binomial(n as u64, k as u64) as usize, all.len(), "C({}, {})", n, k);
+                assert!(all.windows(2).all(|w| w[0] < w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn should_match_masks_and_index_combinations() {
+        let from_masks: Vec<Vec<usize>> = combination_masks(6, 3)
+            .map(|m| (0..6).filter(|i| m & (1 << i) != 0).collect())
+            .collect();
+        let mut from_indices: Vec<Vec<usize>> = combinations(6, 3).collect();
+        from_indices.sort_by_key(|c| c.iter().map(|i| 1u64 << i).sum::<u64>());
+        assert_eq!(from_indices, from_masks);
+    }
+
+    #[test]
+    fn should_report_shrinking_size_hints_for_combinations() {
+        let mut c = combinations(6, 2);
+        for remaining in (0..=15).rev() {
+            assert_eq!((remaining, Some(remaining)), c.size_hint());
+            c.next();
+        }
+        let mut m = combination_masks(6, 2);
+        m.next();
+        assert_eq!((14, Some(14)), m.size_hint());
+    }
+}
@@ -0,0 +1,279 @@
+//!
+//! # Problem description:
+//! #
+//! # Complex Numbers
+//!
+//! A complex number a + bi is a pair of reals with i² = -1. Sums and
+//! products follow from the usual algebra; division multiplies by the
+//! conjugate, scaled as in Smith's method so that large or small parts
+//! don't overflow along the way. The polar form gives the modulus |z|
+//! and the argument, the angle from the positive real axis.
+//!
+//! Complex numbers are written like `3+2i`, `-1.5i` or `i`.
+//!
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::result;
+use std::str::FromStr;
+
+/// Errors reading a complex number
+#[derive(Debug, PartialEq)]
+pub enum ComplexError {
+    /// The text is not a complex number
+    Syntax(String),
+}
+
+impl fmt::Display for ComplexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ComplexError::Syntax(ref text) => write!(f, "Not a complex number: {}", text),
+        }
+    }
+}
+
+pub type ComplexResult<T> = result::Result<T, ComplexError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    /// The number with modulus `r` and argument `theta`
+    pub fn from_polar(r: f64, theta: f64) -> Complex {
+        Complex { re: r * theta.cos(), im: r * theta.sin() }
+    }
+
+    /// The modulus |z|
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::complex::Complex;
+    ///
+    /// assert_eq!(5.0, Complex::new(3.0, -4.0).abs());
+    /// ```
+    pub fn abs(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// The argument, in radians between -π and π
+    pub fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(&self) -> Complex {
+        Complex { re: self.re, im: -self.im }
+    }
+
+    pub fn is_real(&self) -> bool {
+        self.im == 0.0
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Complex {
+        Complex { re, im: 0.0 }
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex { re: self.re + other.re, im: self.im + other.im }
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex { re: self.re - other.re, im: self.im - other.im }
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+
+    /// Smith's division; dividing by zero gives infinities or NaN, as
+    /// with `f64`
+    fn div(self, other: Complex) -> Complex {
+        let (a, b, c, d) = (self.re, self.im, other.re, other.im);
+        if d == 0.0 {
+            return Complex { re: a / c, im: b / c };
+        }
+        if c.abs() >= d.abs() {
+            let r = d / c;
+            let denom = c + d * r;
+            Complex { re: (a + b * r) / denom, im: (b - a * r) / denom }
+        } else {
+            let r = c / d;
+            let denom = c * r + d;
+            Complex { re: (a * r + b) / denom, im: (b * r - a) / denom }
+        }
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex { re: -self.re, im: -self.im }
+    }
+}
+
+/// Only real numbers are ordered; other pairs compare as equal or not at
+/// all
+impl PartialOrd for Complex {
+    fn partial_cmp(&self, other: &Complex) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else if self.is_real() && other.is_real() {
+            self.re.partial_cmp(&other.re)
+        } else {
+            None
+        }
+    }
+}
+
+/// Writes parts that aren't finite as `inf` and `NaN`, which `parse`
+/// reads back
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let imaginary = |f: &mut fmt::Formatter, im: f64| match im {
+            1.0 => write!(f, "i"),
+            -1.0 => write!(f, "-i"),
+            _ => write!(f, "{}i", im),
+        };
+        if self.im == 0.0 {
+            write!(f, "{}", self.re)
+        } else if self.re == 0.0 {
+            imaginary(f, self.im)
+        } else {
+            write!(f, "{}", self.re)?;
+            if self.im >= 0.0 || self.im.is_nan() {
+                write!(f, "+")?;
+            }
+            imaginary(f, self.im)
+        }
+    }
+}
+
+impl FromStr for Complex {
+    type Err = ComplexError;
+
+    /// Reads `a`, `bi` or `a+bi`, where the coefficient of `i` may be
+    /// left out
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::complex::Complex;
+    ///
+    /// assert_eq!(Ok(Complex::new(3.0, 2.0)), "3+2i".parse());
+    /// assert_eq!(Ok(Complex::new(1e-3, -1.0)), "1e-3-i".parse());
+    /// assert_eq!(Ok(Complex::new(0.0, 1.0)), "i".parse());
+    /// assert!("3+2".parse::<Complex>().is_err());
+    /// ```
+    fn from_str(text: &str) -> ComplexResult<Complex> {
+        let syntax = || ComplexError::Syntax(text.to_string());
+        let number = |part: &str| part.parse::<f64>().map_err(|_| syntax());
+        let body = match text.strip_suffix('i') {
+            None => return number(text).map(Complex::from),
+            Some(body) => body,
+        };
+        // The imaginary part starts at the last sign that isn't leading
+        // or part of an exponent
+        let bytes = body.as_bytes();
+        let split = (1..bytes.len())
+            .rev()
+            .find(|&k| (bytes[k] == b'+' || bytes[k] == b'-') && bytes[k - 1] != b'e' && bytes[k - 1] != b'E');
+        let (re, im) = match split {
+            Some(k) => (number(&body[..k])?, &body[k..]),
+            None => (0.0, body),
+        };
+        let im = match im {
+            "" | "+" => 1.0,
+            "-" => -1.0,
+            _ => number(im)?,
+        };
+        Ok(Complex { re, im })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn close(a: Complex, b: Complex) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn should_do_arithmetic() {
+        let (z, w) = (Complex::new(3.0, 2.0), Complex::new(1.0, -4.0));
+        assert_eq!(Complex::new(4.0, -2.0), z + w);
+        assert_eq!(Complex::new(2.0, 6.0), z - w);
+        assert_eq!(Complex::new(11.0, -10.0), z * w);
+        assert!(close(z, z / w * w));
+        assert!(close(w, w / z * z));
+        assert_eq!(Complex::new(-1.0, 0.0), Complex::new(0.0, 1.0) * Complex::new(0.0, 1.0));
+        // Smith's method keeps huge parts finite
+        let big = Complex::new(1e300, 1e300);
+        assert!(close(Complex::new(1.0, 0.0), big / big));
+    }
+
+    #[test]
+    fn should_convert_to_and_from_polar() {
+        let z = Complex::from_polar(2.0, PI / 3.0);
+        assert!((z.abs() - 2.0).abs() < 1e-12);
+        assert!((z.arg() - PI / 3.0).abs() < 1e-12);
+        assert_eq!(PI, Complex::new(-1.0, 0.0).arg());
+    }
+
+    #[test]
+    fn should_parse_and_display() {
+        for &text in &["3+2i", "3-2i", "-3-2.5i", "2i", "-i", "i", "7", "-0.5", "1+i", "1e-7+1e20i"] {
+            let z: Complex = text.parse().unwrap();
+            assert_eq!(z, z.to_string().parse().unwrap(), "{}", text);
+        }
+        assert_eq!("3+2i", Complex::new(3.0, 2.0).to_string());
+        assert_eq!("-i", Complex::new(0.0, -1.0).to_string());
+        assert_eq!("1.5", Complex::new(1.5, 0.0).to_string());
+        assert_eq!(Ok(Complex::new(-2.0, 0.0)), "-2".parse());
+        assert_eq!(Ok(Complex::new(2e-5, 3.0)), "2E-5+3i".parse());
+        let z = Complex::new(f64::INFINITY, f64::NAN);
+        assert_eq!("inf+NaNi", z.to_string());
+        let w: Complex = z.to_string().parse().unwrap();
+        assert!(w.re == f64::INFINITY && w.im.is_nan());
+        assert_eq!(Ok(Complex::new(1.0, f64::NEG_INFINITY)), "1-infi".parse());
+        for &bad in &["", "ii", "3+", "+-i", "3 + 2i", "x"] {
+            assert!(bad.parse::<Complex>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn should_only_order_reals() {
+        assert!(Complex::from(1.0) < Complex::from(2.0));
+        assert_eq!(None, Complex::new(1.0, 1.0).partial_cmp(&Complex::from(2.0)));
+        assert_eq!(Some(Ordering::Equal), Complex::new(1.0, 1.0).partial_cmp(&Complex::new(1.0, 1.0)));
+    }
+}
@@ -0,0 +1,340 @@
+//!
+//! # Problem description:
+//! #
+//! # Connect Four
+//!
+//! Two players take turns dropping stones into the seven columns of an
+//! upright six-row grid; a stone falls to the lowest free row. The first
+//! player to line up four stones horizontally, vertically or diagonally
+//! wins.
+//!
+//! A position is kept as two bitboards, each column taking seven bits
+//! from the bottom up, the top one always empty as a sentinel: the stones
+//! of the player to move, and all the stones. Dropping a stone in a
+//! column is adding the bottom bit of the column to the mask, and four in
+//! a line is found with shifts: shifting by 1 looks along a column, by 7
+//! along a row and by 6 and 8 along the diagonals.
+//!
+//! The computer searches with alpha-beta, iterative deepening and a
+//! transposition table from the `gametree` module, trying central
+//! columns first.
+//!
+
+use gametree::{self, Choice, Game, Hashed, Table, WIN};
+use std::fmt;
+
+/// Number of columns
+pub const WIDTH: usize = 7;
+/// Number of rows
+pub const HEIGHT: usize = 6;
+
+const COLUMN_ORDER: [usize; WIDTH] = [3, 2, 4, 1, 5, 0, 6];
+
+fn bottom_mask(col: usize) -> u64 {
+    1 << (col * (HEIGHT + 1))
+}
+
+fn top_mask(col: usize) -> u64 {
+    1 << (HEIGHT - 1 + col * (HEIGHT + 1))
+}
+
+fn column_mask(col: usize) -> u64 {
+    ((1 << HEIGHT) - 1) << (col * (HEIGHT + 1))
+}
+
+fn board_mask() -> u64 {
+    (0..WIDTH).fold(0, |m, col| m | column_mask(col))
+}
+
+/// Whether the stones include four in a line
+fn has_four(stones: u64) -> bool {
+    [1, HEIGHT + 1, HEIGHT, HEIGHT + 2].iter().any(|&shift| {
+        let pairs = stones & (stones >> shift);
+        pairs & (pairs >> (2 * shift)) != 0
+    })
+}
+
+/// Empty cells that would complete four in a line for the stones
+fn threats(stones: u64, mask: u64) -> u64 {
+    let mut cells = (stones << 1) & (stones << 2) & (stones << 3);
+    for &shift in &[HEIGHT + 1, HEIGHT, HEIGHT + 2] {
+        let pair = (stones << shift) & (stones << (2 * shift));
+        cells |= pair & (stones << (3 * shift));
+        cells |= pair & (stones >> shift);
+        let pair = (stones >> shift) & (stones >> (2 * shift));
+        cells |= pair & (stones << shift);
+        cells |= pair & (stones >> (3 * shift));
+    }
+    cells & (board_mask() ^ mask)
+}
+
+/// A Connect Four position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    current: u64,
+    mask: u64,
+    moves: u32,
+}
+
+impl Position {
+    /// The empty board
+    pub fn new() -> Position {
+        Position { current: 0, mask: 0, moves: 0 }
+    }
+
+    /// Plays a sequence of columns written as digits from 1 to 7, or
+    /// returns `None` if one of the moves is not possible
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::connect4::Position;
+    ///
+    /// let position = Position::from_moves("4455").unwrap();
+    /// assert_eq!(4, position.moves());
+    /// assert!(Position::from_moves("4444444").is_none());
+    /// ```
+    pub fn from_moves(moves: &str) -> Option<Position> {
+        moves.chars().try_fold(Position::new(), |p, c| {
+            let col = c.to_digit(10)? as usize;
+            if col == 0 {
+                return None;
+            }
+            p.play(col - 1)
+        })
+    }
+
+    /// Number of stones played
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Whether a stone can be dropped in a column
+    pub fn can_play(&self, col: usize) -> bool {
+        col < WIDTH && !self.is_over() && self.mask & top_mask(col) == 0
+    }
+
+    /// The position after the player to move drops a stone in `col`, or
+    /// `None` if they cannot
+    pub fn play(&self, col: usize) -> Option<Position> {
+        if !self.can_play(col) {
+            return None;
+        }
+        Some(Position {
+            current: self.current ^ self.mask,
+            mask: self.mask | (self.mask + bottom_mask(col)),
+            moves: self.moves + 1,
+        })
+    }
+
+    /// Whether the player who just moved has four in a line
+    pub fn last_player_won(&self) -> bool {
+        has_four(self.current ^ self.mask)
+    }
+
+    /// Whether the game has ended in a win or a full board
+    pub fn is_over(&self) -> bool {
+        self.last_player_won() || self.moves as usize == WIDTH * HEIGHT
+    }
+
+    /// The stone at a cell, counting rows from the bottom: `Some(0)` for
+    /// the first player, `Some(1)` for the second
+    pub fn stone(&self, row: usize, col: usize) -> Option<u8> {
+        let cell = 1 << (row + col * (HEIGHT + 1));
+        if self.mask & cell == 0 {
+            None
+        } else if (self.current & cell != 0) == self.moves.is_multiple_of(2) {
+            Some(0)
+        } else {
+            Some(1)
+        }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Position {
+        Position::new()
+    }
+}
+
+impl Game for Position {
+    type Move = usize;
+
+    fn moves(&self) -> Vec<usize> {
+        COLUMN_ORDER.iter().cloned().filter(|&col| self.can_play(col)).collect()
+    }
+
+    fn play(&self, col: usize) -> Position {
+        Position::play(self, col).expect("Legal move")
+    }
+
+    fn is_over(&self) -> bool {
+        Position::is_over(self)
+    }
+
+    /// Lost positions are worth `-WIN`; otherwise each cell that would
+    /// complete four counts for its owner, and central stones a little
+    fn evaluate(&self) -> i32 {
+        if self.last_player_won() {
+            return -WIN;
+        }
+        let opponent = self.current ^ self.mask;
+        let centre = column_mask(WIDTH / 2);
+        let threat_balance = threats(self.current, self.mask).count_ones() as i32 - threats(opponent, self.mask).count_ones() as i32;
+        let centre_balance = (self.current & centre).count_ones() as i32 - (opponent & centre).count_ones() as i32;
+        10 * threat_balance + 3 * centre_balance
+    }
+}
+
+impl Hashed for Position {
+    fn key(&self) -> u64 {
+        self.current + self.mask
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in (0..HEIGHT).rev() {
+            let cells: Vec<&str> = (0..WIDTH).map(|col| match self.stone(row, col) {
+                Some(0) => "X",
+                Some(_) => "O",
+                None => ".",
+            }).collect();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+        let numbers: Vec<String> = (1..=WIDTH).map(|c| c.to_string()).collect();
+        writeln!(f, "{}", numbers.join(" "))
+    }
+}
+
+/// A computer player that searches to a fixed depth, keeping its
+/// transposition table between moves
+pub struct Engine {
+    depth: u32,
+    table: Table<usize>,
+}
+
+impl Engine {
+    /// Creates an engine that looks `depth` moves ahead
+    pub fn new(depth: u32) -> Engine {
+        Engine { depth, table: Table::new(1 << 18) }
+    }
+
+    /// Searches a position, returning its score for the player to move
+    /// and the best column found
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::connect4::{Engine, Position};
+    ///
+    /// // X has three in the first column and wins by playing on top
+    /// let position = Position::from_moves("121212").unwrap();
+    /// assert_eq!(Some(0), Engine::new(6).choose(&position).best);
+    /// ```
+    pub fn choose(&mut self, position: &Position) -> Choice<usize> {
+        gametree::iterative_deepening(position, self.depth, &mut self.table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_four_in_every_direction() {
+        let vertical = Position::from_moves("1212121").unwrap();
+        let horizontal = Position::from_moves("1122334").unwrap();
+        let diagonal = Position::from_moves("12234334544").unwrap();
+        let anti_diagonal = Position::from_moves("76654554344").unwrap();
+        for p in &[vertical, horizontal, diagonal, anti_diagonal] {
+            assert!(p.last_player_won(), "\n{}", p);
+            assert!(p.is_over());
+            assert_eq!(None, p.play(5));
+        }
+        assert!(!Position::from_moves("112233").unwrap().last_player_won());
+    }
+
+    #[test]
+    fn should_not_wrap_around_columns() {
+        // The top three cells of the first column and the bottom cell of
+        // the second would be consecutive bits without the sentinel row
+        assert!(!has_four(0b111 << 3 | bottom_mask(1)));
+        assert!(has_four(0b1111 << 2));
+        assert!(!has_four(top_mask(0) | bottom_mask(1) | bottom_mask(1) << 1 | bottom_mask(1) << 2));
+    }
+
+    #[test]
+    fn should_fill_columns_from_the_bottom() {
+        let p = Position::from_moves("444").unwrap();
+        assert_eq!(Some(0), p.stone(0, 3));
+        assert_eq!(Some(1), p.stone(1, 3));
+        assert_eq!(Some(0), p.stone(2, 3));
+        assert_eq!(None, p.stone(3, 3));
+        assert!(Position::from_moves("444444").unwrap().play(3).is_none());
+        assert!(Position::from_moves("8").is_none());
+        assert!(Position::from_moves("0").is_none());
+    }
+
+    #[test]
+    fn should_draw_on_full_board() {
+        let p = Position::from_moves("547125662261271266215743771576315353334444").unwrap();
+        assert_eq!(42, p.moves());
+        assert!(p.is_over());
+        assert!(!p.last_player_won());
+        assert!(Game::moves(&p).is_empty());
+    }
+
+    #[test]
+    fn should_block_immediate_threat() {
+        // X has three in the bottom row, blocked on the left by O
+        let p = Position::from_moves("21314").unwrap();
+        assert_eq!(Some(4), Engine::new(4).choose(&p).best);
+    }
+
+    #[test]
+    fn should_find_forced_win() {
+        // X makes an open three in the bottom row, and O can only block
+        // one end
+        let p = Position::from_moves("3141").unwrap();
+        let choice = Engine::new(6).choose(&p);
+        assert!(choice.score > WIN / 2, "Score {}", choice.score);
+        assert_eq!(Some(4), choice.best);
+    }
+
+    #[test]
+    fn should_agree_with_plain_alphabeta_near_the_end() {
+        let mut x: u64 = 88172645463325252;
+        let mut tested = 0;
+        while tested < 5 {
+            let mut p = Position::new();
+            while p.moves() < 32 && !p.is_over() {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                let col = (x % WIDTH as u64) as usize;
+                if let Some(next) = p.play(col) {
+                    p = next;
+                }
+            }
+            if p.is_over() {
+                continue;
+            }
+            tested += 1;
+            let mut table = Table::new(1 << 12);
+            let with_table = gametree::alphabeta_table(&p, 10, -WIN - 1, WIN + 1, &mut table);
+            assert_eq!(gametree::alphabeta(&p, 10).score, with_table.score, "\n{}", p);
+        }
+    }
+
+    #[test]
+    fn should_print_position() {
+        let p = Position::from_moves("443").unwrap();
+        let expected = ". . . . . . .\n\
+                        . . . . . . .\n\
+                        . . . . . . .\n\
+                        . . . . . . .\n\
+                        . . . O . . .\n\
+                        . . X X . . .\n\
+                        1 2 3 4 5 6 7\n";
+        assert_eq!(expected, p.to_string());
+    }
+}
@@ -0,0 +1,242 @@
+//!
+//! # Problem description:
+//! #
+//! # Consistent Hashing
+//!
+//! A distributed cache spreads its keys over a set of servers. Sending
+//! key *k* to server *hash(k) mod n* balances them well, but when a
+//! server joins or leaves almost every key changes server. Consistent
+//! hashing places servers and keys on a ring of hash values and gives
+//! each key to the first server clockwise from it; a server joining only
+//! takes keys from its neighbours, and a server leaving only gives its
+//! own keys away, so about *1/n* of the keys move.
+//!
+//! A single point per server makes the arcs between them, and so the
+//! loads, very uneven. Each server is therefore placed at many points,
+//! its virtual nodes, hashing the server together with the number of the
+//! replica, which evens out the arcs.
+//!
+//! The hash function is pluggable through `BuildHasher`, like the one of
+//! `HashMap`. The default is `DefaultHasher` with its fixed keys, so the
+//! ring is the same from run to run. FNV-1a is provided as a cheaper
+//! alternative, though it mixes the last bytes of its input poorly, so
+//! the virtual nodes of a server land close together and balance worse.
+//!
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+
+/// The 64-bit FNV-1a hash
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Builds `Fnv1a` hashers, to pass to `Ring::with_hasher`
+pub type FnvBuildHasher = BuildHasherDefault<Fnv1a>;
+
+/// A consistent hashing ring assigning keys to nodes
+///
+/// # Example
+/// ```
+/// use pprust::consistent_hash::Ring;
+///
+/// let mut ring = Ring::new(100);
+/// ring.add("alpha");
+/// ring.add("beta");
+/// let before = *ring.node_for("some key").unwrap();
+/// ring.add("gamma");
+/// let after = *ring.node_for("some key").unwrap();
+/// // A key only ever moves to the new node
+/// assert!(after == before || after == "gamma");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ring<N, S = BuildHasherDefault<DefaultHasher>> {
+    replicas: usize,
+    nodes: Vec<N>,
+    /// The virtual nodes, by their position on the ring
+    points: BTreeMap<u64, N>,
+    hasher: S,
+}
+
+impl<N: Hash + Eq + Clone> Ring<N> {
+    /// An empty ring placing each node at `replicas` points
+    pub fn new(replicas: usize) -> Ring<N> {
+        Ring::with_hasher(replicas, BuildHasherDefault::default())
+    }
+}
+
+impl<N: Hash + Eq + Clone, S: BuildHasher> Ring<N, S> {
+    /// An empty ring placing each node at `replicas` points, hashing
+    /// with `hasher`
+    pub fn with_hasher(replicas: usize, hasher: S) -> Ring<N, S> {
+        assert!(replicas > 0, "Nodes need a point on the ring");
+        Ring { replicas, nodes: Vec::new(), points: BTreeMap::new(), hasher }
+    }
+
+    fn hash<K: Hash + ?Sized>(&self, key: &K) -> u64 {
+        self.hasher.hash_one(key)
+    }
+
+    /// The position of a virtual node
+    fn point(&self, node: &N, replica: usize) -> u64 {
+        self.hash(&(node, replica))
+    }
+
+    /// Adds a node, returning false if it was already there
+    pub fn add(&mut self, node: N) -> bool {
+        if self.nodes.contains(&node) {
+            return false;
+        }
+        for replica in 0..self.replicas {
+            let point = self.point(&node, replica);
+            // On the rare collision the point stays with its first node
+            self.points.entry(point).or_insert_with(|| node.clone());
+        }
+        self.nodes.push(node);
+        true
+    }
+
+    /// Removes a node, returning false if it wasn't there
+    pub fn remove(&mut self, node: &N) -> bool {
+        let position = match self.nodes.iter().position(|n| n == node) {
+            Some(position) => position,
+            None => return false,
+        };
+        self.nodes.remove(position);
+        for replica in 0..self.replicas {
+            let point = self.point(node, replica);
+            if self.points.get(&point) == Some(node) {
+                self.points.remove(&point);
+            }
+        }
+        true
+    }
+
+    /// The node a key belongs to: the first point at or after its hash,
+    /// wrapping around; `None` if the ring is empty
+    pub fn node_for<K: Hash + ?Sized>(&self, key: &K) -> Option<&N> {
+        let h = self.hash(key);
+        self.points.range(h..).next().or_else(|| self.points.iter().next()).map(|(_, node)| node)
+    }
+
+    /// The nodes, in the order they were added
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn replicas(&self) -> usize {
+        self.replicas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const KEYS: usize = 10_000;
+
+    fn assign<S: BuildHasher>(ring: &Ring<String, S>) -> Vec<String> {
+        (0..KEYS).map(|k| ring.node_for(&format!("key-{}", k)).unwrap().clone()).collect()
+    }
+
+    fn ring_of<S: BuildHasher>(n: usize, mut ring: Ring<String, S>) -> Ring<String, S> {
+        for i in 0..n {
+            ring.add(format!("node-{}", i));
+        }
+        ring
+    }
+
+    #[test]
+    fn should_move_only_keys_of_the_changed_node() {
+        let mut ring = ring_of(10, Ring::new(100));
+        let before = assign(&ring);
+        ring.add("node-new".to_string());
+        let after = assign(&ring);
+        let moved = before.iter().zip(&after).filter(|&(b, a)| b != a).count();
+        assert!(before.iter().zip(&after).all(|(b, a)| b == a || a == "node-new"));
+        // About 1/11 of the keys, where hashing modulo n would move 10/11
+        assert!(moved > KEYS / 20 && moved < KEYS / 6, "{} keys moved", moved);
+        assert!(ring.remove(&"node-new".to_string()));
+        assert_eq!(before, assign(&ring));
+
+        ring.remove(&"node-3".to_string());
+        let after = assign(&ring);
+        for (b, a) in before.iter().zip(&after) {
+            assert!(b == a || b == "node-3");
+            assert_ne!("node-3", a);
+        }
+    }
+
+    #[test]
+    fn should_balance_with_virtual_nodes() {
+        let spread = |replicas| {
+            let ring = ring_of(10, Ring::new(replicas));
+            let mut loads = HashMap::new();
+            for node in assign(&ring) {
+                *loads.entry(node).or_insert(0) += 1;
+            }
+            let max = *loads.values().max().unwrap() as f64;
+            max / (KEYS / 10) as f64
+        };
+        let one = spread(1);
+        let many = spread(200);
+        assert!(many < 1.3, "the fullest node has {} times its share", many);
+        assert!(many < one);
+    }
+
+    #[test]
+    fn should_handle_membership_edge_cases() {
+        let mut ring: Ring<&str> = Ring::new(5);
+        assert_eq!(None, ring.node_for(&42));
+        assert!(ring.add("only"));
+        assert!(!ring.add("only"));
+        assert_eq!(5, ring.points.len());
+        assert!((0..100).all(|k| ring.node_for(&k) == Some(&"only")));
+        assert!(!ring.remove(&"other"));
+        assert!(ring.remove(&"only"));
+        assert!(ring.is_empty() && ring.points.is_empty());
+    }
+
+    #[test]
+    fn should_hash_with_fnv() {
+        let mut ring = ring_of(10, Ring::with_hasher(50, FnvBuildHasher::default()));
+        let before = assign(&ring);
+        ring.add("node-new".to_string());
+        assert!(before.iter().zip(&assign(&ring)).all(|(b, a)| b == a || a == "node-new"));
+
+        let mut hasher = Fnv1a::default();
+        hasher.write(b"a");
+        assert_eq!(0xaf63dc4c8601ec8c, hasher.finish());
+        let mut hasher = Fnv1a::default();
+        hasher.write(b"foobar");
+        assert_eq!(0x85944171f73967e8, hasher.finish());
+    }
+}
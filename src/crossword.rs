@@ -0,0 +1,141 @@
+//!
+//! # Problem description:
+//! #
+//! # Crossword Helper
+//!
+//! When filling a crossword, the crossing answers give some letters of a
+//! word and the grid gives its length, so the question is which words fit
+//! a pattern like `C?O?S`. In cryptic crosswords, many clues are anagrams:
+//! the clue contains the letters of the answer, the fodder, mixed up, and
+//! the crossing letters narrow down the arrangements.
+//!
+//! Both are searches in a trie of the dictionary. A pattern walks down
+//! every branch at a wildcard and only the matching one at a letter. An
+//! anagram walks down the branches whose letter is still left in the
+//! fodder, so it never builds arrangements no word starts with. The fodder
+//! may have wildcards too, for letters the clue doesn't give.
+//!
+
+use std::collections::BTreeMap;
+use trie::{Cursor, Trie, WILDCARD};
+
+/// The words that fit a pattern, in order; `?` stands for any letter
+///
+/// # Example
+/// ```
+/// use pprust::crossword::matches;
+/// use pprust::trie::Trie;
+///
+/// let dictionary: Trie = ["CHORD", "CHOPS", "CLOTS", "CROSS", "SCOWS"].iter().collect();
+/// assert_eq!(vec!["CHOPS", "CLOTS", "CROSS"], matches("C?O?S", &dictionary));
+/// ```
+pub fn matches(pattern: &str, dictionary: &Trie) -> Vec<String> {
+    dictionary.matching(pattern)
+}
+
+/// The words, in order, that use exactly the letters of `fodder`, where
+/// `?` is a letter not given, and that fit `pattern`. Spaces in the
+/// fodder are ignored, so it can be a phrase; a pattern of wildcards only
+/// looks for plain anagrams.
+///
+/// # Example
+/// ```
+/// use pprust::crossword::anagrams;
+/// use pprust::trie::Trie;
+///
+/// let dictionary: Trie = ["DORMITORY", "LISTEN", "SILENT", "TINSEL", "INLETS", "ENLIST"].iter().collect();
+/// assert_eq!(vec!["DORMITORY"], anagrams("DIRTY ROOM", "?????????", &dictionary));
+/// assert_eq!(vec!["ENLIST", "INLETS", "LISTEN", "SILENT", "TINSEL"], anagrams("LISTEN", "??????", &dictionary));
+/// assert_eq!(vec!["LISTEN"], anagrams("INLETS", "??S?E?", &dictionary));
+/// assert_eq!(vec!["LISTEN", "SILENT", "TINSEL"], anagrams("LIS?EN", "?I????", &dictionary));
+/// ```
+pub fn anagrams(fodder: &str, pattern: &str, dictionary: &Trie) -> Vec<String> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let mut letters = BTreeMap::new();
+    let mut blanks = 0;
+    for c in fodder.chars().filter(|c| !c.is_whitespace()) {
+        if c == WILDCARD {
+            blanks += 1;
+        } else {
+            *letters.entry(c).or_insert(0) += 1;
+        }
+    }
+    let mut words = Vec::new();
+    if letters.values().sum::<usize>() + blanks == pattern.len() {
+        let mut word = String::new();
+        arrange(dictionary.root(), &pattern, &mut letters, blanks, &mut word, &mut words);
+    }
+    words
+}
+
+/// Goes on with every letter left that the pattern allows next. A letter
+/// of the fodder is used before a blank, as keeping the blank can only
+/// leave more words possible.
+fn arrange(
+    cursor: Cursor,
+    pattern: &[char],
+    letters: &mut BTreeMap<char, usize>,
+    blanks: usize,
+    word: &mut String,
+    words: &mut Vec<String>,
+) {
+    let (&first, rest) = match pattern.split_first() {
+        Some(split) => split,
+        None => {
+            if cursor.is_word() {
+                words.push(word.clone());
+            }
+            return;
+        }
+    };
+    for (c, next) in cursor.children() {
+        if first != WILDCARD && first != c {
+            continue;
+        }
+        word.push(c);
+        match letters.get_mut(&c) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                arrange(next, rest, letters, blanks, word, words);
+                *letters.get_mut(&c).expect("The letter was there") += 1;
+            }
+            _ if blanks > 0 => arrange(next, rest, letters, blanks - 1, word, words),
+            _ => (),
+        }
+        word.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dictionary() -> Trie {
+        ["ALERT", "ALTER", "LATER", "RATEL", "TALER", "ARTEL", "ALTERS", "ALE", "TEA", "EAT", "ATE", "ETA", "TREAT"]
+            .iter()
+            .collect()
+    }
+
+    #[test]
+    fn should_find_words_matching_patterns() {
+        let dictionary = dictionary();
+        assert_eq!(vec!["ALERT", "ALTER"], matches("AL???", &dictionary));
+        assert_eq!(vec!["ALE", "ATE", "EAT", "ETA", "TEA"], matches("???", &dictionary));
+        assert_eq!(vec!["ALTERS"], matches("??????", &dictionary));
+        assert!(matches("Z????", &dictionary).is_empty() && matches("", &dictionary).is_empty());
+    }
+
+    #[test]
+    fn should_find_anagrams_fitting_patterns() {
+        let dictionary = dictionary();
+        assert_eq!(vec!["ALERT", "ALTER", "ARTEL", "LATER", "RATEL", "TALER"], anagrams("LATER", "?????", &dictionary));
+        assert_eq!(vec!["ALTER", "LATER"], anagrams("RE TAL", "??TER", &dictionary));
+        assert_eq!(vec!["ATE", "EAT", "ETA", "TEA"], anagrams("TEA", "???", &dictionary));
+        assert!(anagrams("TEA", "????", &dictionary).is_empty(), "Every letter is used");
+        assert_eq!(vec!["TREAT"], anagrams("TREAT", "?????", &dictionary), "Letters can repeat");
+        assert!(anagrams("TRET?", "?????", &dictionary).contains(&"TREAT".to_string()));
+        assert_eq!(vec!["ALTERS"], anagrams("A?T?R?", "?????S", &dictionary));
+        assert_eq!(vec!["ALE", "ATE", "EAT", "ETA", "TEA"], anagrams("???", "???", &dictionary));
+        assert!(anagrams("TAX", "???", &dictionary).is_empty());
+    }
+}
@@ -0,0 +1,311 @@
+//!
+//! # Problem description:
+//! #
+//! # Frequency Analysis
+//!
+//! Classical ciphers leave the statistics of the language behind. In
+//! English, E is about an eighth of the letters and Z almost never shows
+//! up, and a Caesar cipher only moves those frequencies around the
+//! alphabet, so trying every shift and keeping the one whose letters look
+//! most like English breaks it. A Vigenère cipher uses a different shift
+//! for each letter of a repeating key, which flattens the frequencies, but
+//! taking every key-length-th letter gives back a Caesar cipher.
+//!
+//! The tools: letter and bigram counts; the chi-squared statistic, which
+//! measures how far counts are from the frequencies expected, lower being
+//! closer; and the index of coincidence, the chance that two letters
+//! picked at random are the same, about 0.067 for English and 1/26 for
+//! random letters, which doesn't change when letters are substituted and
+//! so finds the key length of a Vigenère cipher.
+//!
+
+/// How often each letter appears in English text, from A to Z
+pub const ENGLISH: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153, 0.00772, 0.04025, 0.02406,
+    0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056, 0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// The index of coincidence of English text
+pub const ENGLISH_IC: f64 = 0.0667;
+
+/// The index of coincidence of random letters
+pub const RANDOM_IC: f64 = 1.0 / 26.0;
+
+/// The letters of a text as numbers from 0 to 25, ignoring case and
+/// everything that isn't an ASCII letter
+fn letters(text: &str) -> impl Iterator<Item = usize> + '_ {
+    text.bytes().filter(u8::is_ascii_alphabetic).map(|b| (b.to_ascii_lowercase() - b'a') as usize)
+}
+
+/// How many times each letter appears, from A to Z, ignoring case
+///
+/// # Example
+/// ```
+/// use pprust::cryptanalysis::letter_counts;
+///
+/// let counts = letter_counts("Attack at dawn!");
+/// assert_eq!((4, 3, 0), (counts[0], counts[19], counts[25]));
+/// ```
+pub fn letter_counts(text: &str) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for letter in letters(text) {
+        counts[letter] += 1;
+    }
+    counts
+}
+
+/// The share of the letters each letter is, from A to Z; all zeros if
+/// the text has no letters
+pub fn letter_frequencies(text: &str) -> [f64; 26] {
+    let counts = letter_counts(text);
+    let total: usize = counts.iter().sum();
+    let mut frequencies = [0.0; 26];
+    if total > 0 {
+        for (frequency, &count) in frequencies.iter_mut().zip(&counts) {
+            *frequency = count as f64 / total as f64;
+        }
+    }
+    frequencies
+}
+
+/// How many times each pair of letters appears one after the other, by
+/// first and then second letter. Only letters count, so pairs run across
+/// spaces and punctuation, as they do in ciphertext written in blocks.
+///
+/// # Example
+/// ```
+/// use pprust::cryptanalysis::bigram_counts;
+///
+/// let counts = bigram_counts("the then, theta");
+/// let th = counts[(b't' - b'a') as usize][(b'h' - b'a') as usize];
+/// assert_eq!(3, th);
+/// ```
+pub fn bigram_counts(text: &str) -> [[usize; 26]; 26] {
+    let mut counts = [[0usize; 26]; 26];
+    let mut previous: Option<usize> = None;
+    for letter in letters(text) {
+        if let Some(first) = previous {
+            counts[first][letter] += 1;
+        }
+        previous = Some(letter);
+    }
+    counts
+}
+
+/// The chi-squared statistic of letter counts against the frequencies
+/// expected: the sum over the letters of the squared difference between
+/// the count and the count expected, divided by the count expected. 0
+/// for no letters.
+///
+/// # Example
+/// ```
+/// use pprust::cryptanalysis::{chi_squared, letter_counts, ENGLISH};
+///
+/// let english = letter_counts("Defend the east wall of the castle");
+/// let shifted = letter_counts("Efgfoe uif fbtu xbmm pg uif dbtumf");
+/// assert!(chi_squared(&english, &ENGLISH) < chi_squared(&shifted, &ENGLISH));
+/// ```
+pub fn chi_squared(counts: &[usize; 26], expected: &[f64; 26]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .iter()
+        .zip(expected)
+        .filter(|&(_, &p)| p > 0.0)
+        .map(|(&count, &p)| {
+            let e = p * total as f64;
+            (count as f64 - e).powi(2) / e
+        })
+        .sum()
+}
+
+/// The chance that two letters picked at random from the counts, without
+/// putting the first back, are the same. 0 for fewer than two letters.
+///
+/// # Example
+/// ```
+/// use pprust::cryptanalysis::{index_of_coincidence, letter_counts};
+///
+/// assert_eq!(1.0, index_of_coincidence(&letter_counts("aaaa")));
+/// assert_eq!(0.0, index_of_coincidence(&letter_counts("abcd")));
+/// ```
+pub fn index_of_coincidence(counts: &[usize; 26]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total < 2 {
+        return 0.0;
+    }
+    let pairs: usize = counts.iter().map(|&n| n * n.saturating_sub(1)).sum();
+    pairs as f64 / (total * (total - 1)) as f64
+}
+
+/// Shifts a letter by `shift` places, keeping its case; other characters
+/// are left alone
+fn shift_letter(c: char, shift: usize) -> char {
+    let base = match c {
+        'a'..='z' => b'a',
+        'A'..='Z' => b'A',
+        _ => return c,
+    };
+    (base + ((c as u8 - base) as usize + shift) as u8 % 26) as char
+}
+
+/// Shifts every letter `shift` places forward; shifting by `26 - shift`
+/// deciphers
+///
+/// # Example
+/// ```
+/// use pprust::cryptanalysis::caesar;
+///
+/// assert_eq!("Dwwdfn dw gdzq!", caesar("Attack at dawn!", 3));
+/// assert_eq!("Attack at dawn!", caesar("Dwwdfn dw gdzq!", 23));
+/// ```
+pub fn caesar(text: &str, shift: usize) -> String {
+    text.chars().map(|c| shift_letter(c, shift % 26)).collect()
+}
+
+/// The shift a Caesar cipher most likely used: the one whose deciphered
+/// letters have the lowest chi-squared against English
+pub fn crack_caesar(ciphertext: &str) -> usize {
+    best_shift(&letter_counts(ciphertext))
+}
+
+/// The shift that makes counts of shifted letters look most like English
+fn best_shift(counts: &[usize; 26]) -> usize {
+    let score = |shift: usize| {
+        let mut plain = [0; 26];
+        for (letter, &count) in counts.iter().enumerate() {
+            plain[(letter + 26 - shift) % 26] = count;
+        }
+        chi_squared(&plain, &ENGLISH)
+    };
+    (0..26).min_by(|&a, &b| score(a).total_cmp(&score(b))).expect("There are shifts")
+}
+
+/// Shifts each letter by the matching letter of the key, A being 0, going
+/// through the key one letter of text at a time; `decipher` shifts back.
+/// Characters that aren't letters are left alone and don't use up the
+/// key.
+///
+/// # Example
+/// ```
+/// use pprust::cryptanalysis::vigenere;
+///
+/// assert_eq!("Lxfopv ef rnhr", vigenere("Attack at dawn", "lemon", false));
+/// assert_eq!("Attack at dawn", vigenere("Lxfopv ef rnhr", "LEMON", true));
+/// ```
+pub fn vigenere(text: &str, key: &str, decipher: bool) -> String {
+    let shifts: Vec<usize> = letters(key).map(|k| if decipher { (26 - k) % 26 } else { k }).collect();
+    assert!(!shifts.is_empty(), "The key has no letters");
+    let mut next = shifts.iter().cycle();
+    text.chars()
+        .map(|c| if c.is_ascii_alphabetic() { shift_letter(c, *next.next().expect("The key cycles")) } else { c })
+        .collect()
+}
+
+/// Splits the letters of a text into `n` columns: the first, n+1th,
+/// 2n+1th and so on; then the second, and so on
+fn columns(text: &str, n: usize) -> Vec<[usize; 26]> {
+    let mut counts = vec![[0; 26]; n];
+    for (i, letter) in letters(text).enumerate() {
+        counts[i % n][letter] += 1;
+    }
+    counts
+}
+
+/// The most likely length of a Vigenère key, up to `longest`: the
+/// shortest whose columns have an average index of coincidence closer to
+/// English than to random letters. Multiples of the key length score as
+/// well as the key length, so the shortest is the one.
+pub fn key_length(ciphertext: &str, longest: usize) -> usize {
+    let threshold = (ENGLISH_IC + RANDOM_IC) / 2.0;
+    let average = |n: usize| {
+        let columns = columns(ciphertext, n);
+        columns.iter().map(index_of_coincidence).sum::<f64>() / n as f64
+    };
+    (1..=longest)
+        .find(|&n| average(n) > threshold)
+        .unwrap_or_else(|| (1..=longest).max_by(|&a, &b| average(a).total_cmp(&average(b))).unwrap_or(1))
+}
+
+/// The most likely key of a Vigenère cipher, in upper case, of at most
+/// `longest` letters: each column of the key length is cracked as a
+/// Caesar cipher
+///
+/// # Example
+/// ```
+/// use pprust::cryptanalysis::{crack_vigenere, vigenere};
+///
+/// let plain = "It is a truth universally acknowledged, that a single man in \
+///              possession of a good fortune, must be in want of a wife. However \
+///              little known the feelings or views of such a man may be on his \
+///              first entering a neighbourhood, this truth is so well fixed in \
+///              the minds of the surrounding families, that he is considered as \
+///              the rightful property of some one or other of their daughters.";
+/// let cipher = vigenere(plain, "austen", false);
+/// assert_eq!("AUSTEN", crack_vigenere(&cipher, 10));
+/// ```
+pub fn crack_vigenere(ciphertext: &str, longest: usize) -> String {
+    let n = key_length(ciphertext, longest);
+    columns(ciphertext, n).iter().map(|counts| (b'A' + best_shift(counts) as u8) as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "The quick brown fox jumps over the lazy dog while the farmer sleeps under the old oak \
+                        tree. In the morning he will find the hens scattered across the yard and the gate left \
+                        open again, as it has been every day this week since the storm broke the latch and nobody \
+                        in the village had the time or the tools to mend it properly before market day. His \
+                        neighbours say that he should sell the place and move to the town, where his daughter \
+                        keeps a small shop, but he answers that a man who has worked the same fields for forty \
+                        years would not know what to do with himself among the crowds and the noise.";
+
+    #[test]
+    fn should_count_letters_and_pairs() {
+        let counts = letter_counts("Hello, World");
+        assert_eq!((3, 2, 1, 10), (counts[11], counts[14], counts[3], counts.iter().sum()));
+        let frequencies = letter_frequencies("aab");
+        assert_eq!((2.0 / 3.0, 1.0 / 3.0), (frequencies[0], frequencies[1]));
+        assert_eq!([0.0; 26], letter_frequencies("123"));
+        assert!((ENGLISH.iter().sum::<f64>() - 1.0).abs() < 1e-3);
+        let pairs = bigram_counts("ab-ba ab");
+        assert_eq!((2, 1, 2), (pairs[0][1], pairs[1][0], pairs[1][1] + pairs[0][0]));
+        assert_eq!(5, pairs.iter().flatten().sum::<usize>(), "One pair fewer than letters");
+    }
+
+    #[test]
+    fn should_score_english_against_random_letters() {
+        let english = letter_counts(TEXT);
+        let ic = index_of_coincidence(&english);
+        assert!((ic - ENGLISH_IC).abs() < 0.01, "{}", ic);
+        let flat = letter_counts("abcdefghijklmnopqrstuvwxyz".repeat(100).as_str());
+        assert!((index_of_coincidence(&flat) - RANDOM_IC).abs() < 0.001);
+        assert!(chi_squared(&english, &ENGLISH) < chi_squared(&flat, &ENGLISH));
+        assert_eq!(0.0, chi_squared(&[0; 26], &ENGLISH));
+        assert_eq!(ic, index_of_coincidence(&letter_counts(&caesar(TEXT, 11))), "Substitution keeps the index");
+    }
+
+    #[test]
+    fn should_crack_caesar_ciphers() {
+        for shift in 0..26 {
+            let cipher = caesar(TEXT, shift);
+            assert_eq!(shift, crack_caesar(&cipher));
+            assert_eq!(TEXT, caesar(&cipher, 26 - shift));
+        }
+        assert_eq!("xyzABC", caesar("abcDEF", 23));
+    }
+
+    #[test]
+    fn should_crack_vigenere_ciphers() {
+        for key in &["key", "LEMON", "cipher", "praxis"] {
+            let cipher = vigenere(TEXT, key, false);
+            assert_eq!(TEXT, vigenere(&cipher, key, true));
+            assert_eq!(key.len(), key_length(&cipher, 12), "{}", key);
+            assert_eq!(key.to_uppercase(), crack_vigenere(&cipher, 12));
+        }
+        assert_eq!(1, key_length(TEXT, 12));
+    }
+}
@@ -0,0 +1,247 @@
+//!
+//! # Problem description:
+//! #
+//! # Cryptarithms
+//!
+//! In a cryptarithm each letter stands for a different digit, and the
+//! words form a correct sum once the letters are replaced:
+//!
+//! ```text
+//!     SEND
+//!   + MORE
+//!   ------
+//!    MONEY
+//! ```
+//!
+//! No word may start with a zero. The solver works one column at a time
+//! from the right, like adding by hand: it tries digits for the letters
+//! of the operands in that column, and then the digit of the result is
+//! forced by the column sum and the carry, so most wrong guesses die as
+//! soon as their column is complete.
+//!
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::result;
+
+/// Errors reading a puzzle
+#[derive(Debug, PartialEq)]
+pub enum CryptarithmError {
+    /// The puzzle is not of the form `WORD + WORD ... = WORD`
+    Syntax(String),
+    /// The puzzle has more than ten distinct letters
+    TooManyLetters(usize),
+}
+
+impl fmt::Display for CryptarithmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CryptarithmError::Syntax(ref message) => write!(f, "syntax error: {}", message),
+            CryptarithmError::TooManyLetters(n) => write!(f, "{} distinct letters, at most 10 allowed", n),
+        }
+    }
+}
+
+/// The result of solving a puzzle
+pub type CryptarithmResult<T> = result::Result<T, CryptarithmError>;
+
+/// The digit assigned to each letter
+pub type Solution = BTreeMap<char, u8>;
+
+struct Puzzle {
+    letters: Vec<char>,
+    /// Operands as letter indices, least significant first
+    operands: Vec<Vec<usize>>,
+    result: Vec<usize>,
+    leading: Vec<bool>,
+}
+
+fn parse(puzzle: &str) -> CryptarithmResult<Puzzle> {
+    let sides: Vec<&str> = puzzle.split('=').collect();
+    if sides.len() != 2 {
+        return Err(CryptarithmError::Syntax("expected exactly one '='".to_string()));
+    }
+    let mut words: Vec<&str> = sides[0].split('+').map(|w| w.trim()).collect();
+    words.push(sides[1].trim());
+    if let Some(bad) = words.iter().find(|w| w.is_empty() || !w.chars().all(|c| c.is_alphabetic())) {
+        return Err(CryptarithmError::Syntax(format!("'{}' is not a word", bad)));
+    }
+    let mut letters: Vec<char> = words.iter().flat_map(|w| w.chars()).collect();
+    letters.sort();
+    letters.dedup();
+    if letters.len() > 10 {
+        return Err(CryptarithmError::TooManyLetters(letters.len()));
+    }
+    let mut leading = vec![false; letters.len()];
+    let mut indices: Vec<Vec<usize>> = words.iter().map(|w| {
+        let word: Vec<usize> = w.chars().rev().map(|c| letters.binary_search(&c).unwrap()).collect();
+        if word.len() > 1 {
+            leading[word[word.len() - 1]] = true;
+        }
+        word
+    }).collect();
+    let result = indices.pop().unwrap();
+    Ok(Puzzle { letters, operands: indices, result, leading })
+}
+
+struct Search<'a> {
+    puzzle: &'a Puzzle,
+    digits: Vec<Option<u8>>,
+    used: [bool; 10],
+    solutions: Vec<Solution>,
+}
+
+impl<'a> Search<'a> {
+    fn try_digit<F: FnMut(&mut Self)>(&mut self, letter: usize, digit: u8, mut next: F) {
+        if self.used[digit as usize] || (digit == 0 && self.puzzle.leading[letter]) {
+            return;
+        }
+        self.used[digit as usize] = true;
+        self.digits[letter] = Some(digit);
+        next(self);
+        self.digits[letter] = None;
+        self.used[digit as usize] = false;
+    }
+
+    fn column(&mut self, col: usize, carry: u32) {
+        if col == self.puzzle.result.len() {
+            if carry == 0 {
+                let solution = self.puzzle.letters.iter().zip(&self.digits)
+                    .map(|(&c, d)| (c, d.expect("Every letter is assigned")))
+                    .collect();
+                self.solutions.push(solution);
+            }
+            return;
+        }
+        let letters: Vec<usize> = self.puzzle.operands.iter().filter_map(|w| w.get(col).cloned()).collect();
+        self.assign(col, carry, &letters);
+    }
+
+    /// Assigns the operand letters of a column, then checks its sum
+    fn assign(&mut self, col: usize, carry: u32, letters: &[usize]) {
+        if let Some((&letter, rest)) = letters.split_first() {
+            if self.digits[letter].is_some() {
+                self.assign(col, carry, rest);
+            } else {
+                for digit in 0..10 {
+                    self.try_digit(letter, digit, |s| s.assign(col, carry, rest));
+                }
+            }
+            return;
+        }
+        let sum = carry + self.puzzle.operands.iter()
+            .filter_map(|w| w.get(col))
+            .map(|&l| self.digits[l].unwrap() as u32)
+            .sum::<u32>();
+        let digit = (sum % 10) as u8;
+        let letter = self.puzzle.result[col];
+        match self.digits[letter] {
+            Some(d) if d == digit => self.column(col + 1, sum / 10),
+            Some(_) => {}
+            None => self.try_digit(letter, digit, |s| s.column(col + 1, sum / 10)),
+        }
+    }
+}
+
+/// Solves a puzzle of the form `WORD + WORD ... = WORD`, returning every
+/// solution in lexicographic order of the digits of the sorted letters
+///
+/// # Example
+/// ```
+/// use pprust::cryptarithm::solve;
+///
+/// let solutions = solve("SEND + MORE = MONEY").unwrap();
+/// assert_eq!(1, solutions.len());
+/// let digits: String = "MONEY".chars().map(|c| solutions[0][&c].to_string()).collect();
+/// assert_eq!("10652", digits);
+/// ```
+pub fn solve(puzzle: &str) -> CryptarithmResult<Vec<Solution>> {
+    let puzzle = parse(puzzle)?;
+    if puzzle.operands.iter().any(|w| w.len() > puzzle.result.len()) {
+        return Ok(Vec::new());
+    }
+    let mut search = Search {
+        puzzle: &puzzle,
+        digits: vec![None; puzzle.letters.len()],
+        used: [false; 10],
+        solutions: Vec::new(),
+    };
+    search.column(0, 0);
+    let mut solutions = search.solutions;
+    solutions.sort();
+    Ok(solutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(word: &str, solution: &Solution) -> u64 {
+        word.chars().fold(0, |n, c| 10 * n + solution[&c] as u64)
+    }
+
+    #[test]
+    fn should_solve_send_more_money() {
+        let solutions = solve("SEND + MORE = MONEY").unwrap();
+        assert_eq!(1, solutions.len());
+        let s = &solutions[0];
+        assert_eq!(9567, value("SEND", s));
+        assert_eq!(1085, value("MORE", s));
+        assert_eq!(10652, value("MONEY", s));
+    }
+
+    #[test]
+    fn should_find_all_solutions() {
+        // Brute force: every assignment of distinct digits, no leading zeros
+        let solutions = solve("TO + GO = OUT").unwrap();
+        let mut expected = Vec::new();
+        for t in 1..10u64 {
+            for o in 1..10u64 {
+                for g in 1..10u64 {
+                    for u in 0..10u64 {
+                        let digits = [t, o, g, u];
+                        let distinct = (0..4).all(|i| (i + 1..4).all(|j| digits[i] != digits[j]));
+                        if distinct && 10 * t + o + 10 * g + o == 100 * o + 10 * u + t {
+                            expected.push(digits);
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(expected.len(), solutions.len());
+        for s in &solutions {
+            assert_eq!(value("TO", s) + value("GO", s), value("OUT", s));
+        }
+    }
+
+    #[test]
+    fn should_solve_many_operands() {
+        let solutions = solve("A + A + A = BA").unwrap();
+        assert_eq!(1, solutions.len());
+        assert_eq!(15, value("BA", &solutions[0]));
+    }
+
+    #[test]
+    fn should_allow_zero_only_inside_words() {
+        let solutions = solve("I + BB = ILL").unwrap();
+        assert_eq!(1, solutions.len());
+        assert_eq!(100, value("ILL", &solutions[0]));
+        let zero_operand = solve("AB + C = AB").unwrap();
+        assert_eq!(72, zero_operand.len());
+        assert!(zero_operand.iter().all(|s| s[&'C'] == 0));
+    }
+
+    #[test]
+    fn should_return_no_solutions() {
+        assert_eq!(Ok(Vec::new()), solve("ABC + D = E"));
+        assert_eq!(Ok(Vec::new()), solve("AB + AB = A"));
+    }
+
+    #[test]
+    fn should_reject_bad_puzzles() {
+        assert!(solve("SEND + MORE").is_err());
+        assert!(solve("SEND + = MONEY").is_err());
+        assert!(solve("SEND + M0RE = MONEY").is_err());
+        assert_eq!(Err(CryptarithmError::TooManyLetters(11)), solve("ABCDEF + GHIJK = ABCDEK"));
+    }
+}
@@ -0,0 +1,387 @@
+//!
+//! # Problem description:
+//! #
+//! # Decimal Arithmetic
+//!
+//! Binary floating point can't hold 0.1 exactly, so 0.1 + 0.2 comes out
+//! as 0.30000000000000004. A decimal number is an integer mantissa and a
+//! count of digits after the decimal point, and sums, differences and
+//! products of decimals are exact. Only division has to stop somewhere:
+//! like `bc`'s scale, the precision says how many digits after the point
+//! a quotient keeps, and the last one is rounded half to even.
+//!
+//! The precision is a setting of the current thread, as in the context
+//! of Python's decimal module, so quotients can be computed by plain
+//! operators. Code that keeps a precision of its own, like a calculator,
+//! lends it to the thread with `with_precision` while it computes.
+//!
+
+use bigint::BigInt;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::result;
+use std::str::FromStr;
+
+/// Digits after the decimal point kept by division unless set otherwise
+pub const DEFAULT_PRECISION: u32 = 20;
+
+/// The largest exponent accepted in literals like `1e30`
+const MAX_EXPONENT: i64 = 100_000;
+
+thread_local! {
+    static PRECISION: Cell<u32> = const { Cell::new(DEFAULT_PRECISION) };
+}
+
+/// Digits after the decimal point kept by division in this thread
+pub fn precision() -> u32 {
+    PRECISION.with(|p| p.get())
+}
+
+/// Sets the digits after the decimal point kept by division in this
+/// thread
+pub fn set_precision(digits: u32) {
+    PRECISION.with(|p| p.set(digits));
+}
+
+/// Puts the precision of the thread back when dropped
+struct Restore(u32);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        set_precision(self.0);
+    }
+}
+
+/// Runs a function with the precision of the thread set to `digits`,
+/// putting the one before back afterwards, even if it panics
+///
+/// # Example
+/// ```
+/// use pprust::decimal::{self, Decimal};
+///
+/// let before = decimal::precision();
+/// let third = decimal::with_precision(3, || "1".parse::<Decimal>().unwrap() / "3".parse().unwrap());
+/// assert_eq!("0.333", third.to_string());
+/// assert_eq!(before, decimal::precision());
+/// ```
+pub fn with_precision<R, F: FnOnce() -> R>(digits: u32, f: F) -> R {
+    let _restore = Restore(precision());
+    set_precision(digits);
+    f()
+}
+
+/// Errors reading a decimal number
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecimalError {
+    /// The text is not a decimal number
+    Syntax(String),
+    /// The exponent is too large to expand
+    ExponentTooLarge(String),
+}
+
+impl fmt::Display for DecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecimalError::Syntax(ref text) => write!(f, "'{}' is not a decimal number", text),
+            DecimalError::ExponentTooLarge(ref text) => write!(f, "The exponent of '{}' is too large", text),
+        }
+    }
+}
+
+pub type DecimalResult<T> = result::Result<T, DecimalError>;
+
+fn pow10(n: u32) -> BigInt {
+    BigInt::from(10).pow(n)
+}
+
+/// `n / d` rounded half to even
+fn div_round(n: &BigInt, d: &BigInt) -> BigInt {
+    let (q, r) = n.div_rem(d);
+    let twice = &r.abs() + &r.abs();
+    let up = match twice.cmp(&d.abs()) {
+        Ordering::Greater => true,
+        Ordering::Equal => !(&q % &BigInt::from(2)).is_zero(),
+        Ordering::Less => false,
+    };
+    if !up {
+        q
+    } else if n.is_negative() != d.is_negative() {
+        &q - &BigInt::from(1)
+    } else {
+        &q + &BigInt::from(1)
+    }
+}
+
+/// A decimal number: `mantissa / 10^scale`, without trailing zeros
+/// after the point
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Decimal {
+    mantissa: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    /// The number `mantissa / 10^scale`
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::decimal::Decimal;
+    ///
+    /// assert_eq!("-3.14", Decimal::new((-314).into(), 2).to_string());
+    /// assert_eq!("2.5", Decimal::new(2500.into(), 3).to_string());
+    /// ```
+    pub fn new(mut mantissa: BigInt, mut scale: u32) -> Decimal {
+        let ten = BigInt::from(10);
+        while scale > 0 {
+            let (q, r) = mantissa.div_rem(&ten);
+            if !r.is_zero() {
+                break;
+            }
+            mantissa = q;
+            scale -= 1;
+        }
+        Decimal { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        &self.mantissa
+    }
+
+    /// Digits after the decimal point
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    /// The nearest `f64`
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// Both mantissas over the larger of the two scales
+    fn align(&self, other: &Decimal) -> (BigInt, BigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        (&self.mantissa * &pow10(scale - self.scale), &other.mantissa * &pow10(scale - other.scale), scale)
+    }
+
+    /// Rounds half to even to `digits` after the decimal point
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::decimal::Decimal;
+    ///
+    /// let round = |text: &str| text.parse::<Decimal>().unwrap().round(2).to_string();
+    /// assert_eq!(("0.12", "0.14", "-0.13"), (round("0.125").as_str(), round("0.135").as_str(), round("-0.1251").as_str()));
+    /// ```
+    pub fn round(&self, digits: u32) -> Decimal {
+        if self.scale <= digits {
+            return self.clone();
+        }
+        Decimal::new(div_round(&self.mantissa, &pow10(self.scale - digits)), digits)
+    }
+
+    /// The quotient rounded to `digits` after the decimal point, or
+    /// `None` when dividing by zero
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::decimal::Decimal;
+    ///
+    /// let (two, three) = (Decimal::from(2), Decimal::from(3));
+    /// assert_eq!("0.66667", two.checked_div(&three, 5).unwrap().to_string());
+    /// assert_eq!(None, two.checked_div(&Decimal::from(0), 5));
+    /// ```
+    pub fn checked_div(&self, other: &Decimal, digits: u32) -> Option<Decimal> {
+        if other.is_zero() {
+            return None;
+        }
+        // self / other = (m1 10^(s2 + digits)) / (m2 10^s1) / 10^digits
+        let n = &self.mantissa * &pow10(other.scale + digits);
+        let d = &other.mantissa * &pow10(self.scale);
+        Some(Decimal::new(div_round(&n, &d), digits))
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(n: i64) -> Decimal {
+        Decimal { mantissa: BigInt::from(n), scale: 0 }
+    }
+}
+
+impl Add for Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: Decimal) -> Decimal {
+        let (a, b, scale) = self.align(&other);
+        Decimal::new(&a + &b, scale)
+    }
+}
+
+impl Sub for Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: Decimal) -> Decimal {
+        let (a, b, scale) = self.align(&other);
+        Decimal::new(&a - &b, scale)
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: Decimal) -> Decimal {
+        Decimal::new(&self.mantissa * &other.mantissa, self.scale + other.scale)
+    }
+}
+
+impl Div for Decimal {
+    type Output = Decimal;
+
+    /// Rounds to the precision of the current thread; panics when
+    /// dividing by zero
+    fn div(self, other: Decimal) -> Decimal {
+        self.checked_div(&other, precision()).expect("Division by zero")
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal { mantissa: -self.mantissa, scale: self.scale }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        let (a, b, _) = self.align(other);
+        a.cmp(&b)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut digits = self.mantissa.abs().to_string();
+        let scale = self.scale as usize;
+        if scale > 0 {
+            if digits.len() <= scale {
+                digits = "0".repeat(scale + 1 - digits.len()) + &digits;
+            }
+            digits.insert(digits.len() - scale, '.');
+        }
+        f.pad_integral(!self.mantissa.is_negative(), "", &digits)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    /// Reads an optional sign, digits with an optional decimal point and
+    /// an optional exponent, like `-12.5e-3`
+    fn from_str(text: &str) -> DecimalResult<Decimal> {
+        let syntax = || DecimalError::Syntax(text.to_string());
+        let (number, exponent) = match text.find(['e', 'E']) {
+            Some(k) => (&text[..k], text[k + 1..].parse::<i64>().map_err(|_| syntax())?),
+            None => (text, 0),
+        };
+        if exponent.abs() > MAX_EXPONENT {
+            return Err(DecimalError::ExponentTooLarge(text.to_string()));
+        }
+        let (negative, unsigned) = match number.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (whole, fraction) = match unsigned.find('.') {
+            Some(k) => (&unsigned[..k], &unsigned[k + 1..]),
+            None => (unsigned, ""),
+        };
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.len() + fraction.len() == 0 || !all_digits(whole) || !all_digits(fraction) {
+            return Err(syntax());
+        }
+        let mut mantissa: BigInt = format!("{}{}", whole, fraction).parse().map_err(|_| syntax())?;
+        if negative {
+            mantissa = -mantissa;
+        }
+        let scale = fraction.len() as i64 - exponent;
+        if scale < 0 {
+            Ok(Decimal::new(&mantissa * &pow10(-scale as u32), 0))
+        } else {
+            Ok(Decimal::new(mantissa, scale as u32))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(text: &str) -> Decimal {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn should_add_decimals_exactly() {
+        assert_eq!(dec("0.3"), dec("0.1") + dec("0.2"));
+        assert_eq!("0.3", (dec("0.1") + dec("0.2")).to_string());
+        assert_eq!(dec("-0.05"), dec("1.15") - dec("1.2"));
+        assert_eq!("1.5", (dec("0.75") + dec("0.75")).to_string());
+        assert_eq!(dec("0"), dec("12.34") - dec("12.340"));
+    }
+
+    #[test]
+    fn should_multiply_exactly() {
+        assert_eq!("0.0001", (dec("0.01") * dec("0.01")).to_string());
+        assert_eq!("-1.21", (dec("1.1") * dec("-1.1")).to_string());
+        let third = dec("0.333333333333333333333333333333");
+        assert_eq!("0.999999999999999999999999999999", (third * dec("3")).to_string());
+    }
+
+    #[test]
+    fn should_divide_to_precision() {
+        let (one, three) = (Decimal::from(1), Decimal::from(3));
+        assert_eq!("0.33333", one.checked_div(&three, 5).unwrap().to_string());
+        assert_eq!("0.3333333333", one.checked_div(&three, 10).unwrap().to_string());
+        assert_eq!("-0.67", dec("-2").checked_div(&three, 2).unwrap().to_string());
+        assert_eq!("0.5", one.checked_div(&Decimal::from(2), 3).unwrap().to_string());
+        // Ties go to the even digit
+        assert_eq!("0", dec("1").checked_div(&dec("2"), 0).unwrap().to_string());
+        assert_eq!("2", dec("3").checked_div(&dec("2"), 0).unwrap().to_string());
+        assert_eq!("12.5", dec("0.25").checked_div(&dec("0.02"), 4).unwrap().to_string());
+        set_precision(3);
+        assert_eq!("0.333", (one / three).to_string());
+        set_precision(DEFAULT_PRECISION);
+    }
+
+    #[test]
+    fn should_parse_and_display() {
+        for &(text, shown) in &[("0", "0"), ("-0.0", "0"), ("007.50", "7.5"), ("+.5", "0.5"), ("5.", "5"),
+                                ("-0.001", "-0.001"), ("1e3", "1000"), ("1.5E-3", "0.0015"), ("-2.5e1", "-25")] {
+            assert_eq!(shown, dec(text).to_string(), "{}", text);
+        }
+        assert_eq!("  1.5", format!("{:>5}", dec("1.5")));
+        for &bad in &["", ".", "-", "1.2.3", "1e", "e5", "0x10", "1,5", " 1"] {
+            assert_eq!(Err(DecimalError::Syntax(bad.to_string())), bad.parse::<Decimal>(), "{}", bad);
+        }
+        assert!("1e9999999".parse::<Decimal>().is_err());
+    }
+
+    #[test]
+    fn should_compare_across_scales() {
+        assert!(dec("0.1") < dec("0.11"));
+        assert!(dec("-0.1") > dec("-0.11"));
+        assert_eq!(Ordering::Equal, dec("2.50").cmp(&dec("2.5")));
+        assert_eq!(0.1, dec("0.1").to_f64());
+    }
+}
@@ -0,0 +1,165 @@
+//!
+//! # Problem description:
+//! #
+//! # Diffie-Hellman Key Exchange
+//!
+//! Alice and Bob agree in public on a prime p and a generator g. Alice
+//! picks a secret a and sends A = g^a mod p; Bob picks a secret b and
+//! sends B = g^b mod p. Each can then compute the same number,
+//! B^a = A^b = g^(ab) mod p, but an eavesdropper who sees only p, g, A
+//! and B would have to take a discrete logarithm to find it.
+//!
+//! This is a toy, for learning only: the 64-bit numbers here fall to
+//! baby-step giant-step in seconds (see `numtheory::bsgs_dlog`), the
+//! random numbers are not cryptographic and nothing is authenticated.
+//! Real exchanges use groups of 2048 bits or more, or elliptic curves.
+//!
+//! The prime is a safe prime p = 2q + 1 with q prime, and g generates
+//! the subgroup of order q, the squares modulo p, so that no power of g
+//! gives away a bit of the exponent.
+//!
+
+use numtheory::{is_prime, mod_pow};
+use rng::Rng;
+
+/// The largest prime size supported, in bits
+pub const MAX_BITS: u32 = 63;
+
+/// The public parameters: a safe prime and a generator of the subgroup
+/// of prime order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub p: u64,
+    pub g: u64,
+}
+
+impl Params {
+    /// Random parameters with a prime of exactly `bits` bits, from 8 to
+    /// `MAX_BITS`
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::dh::Params;
+    /// use pprust::numtheory::is_safe_prime;
+    /// use pprust::rng::Rng;
+    ///
+    /// let params = Params::generate(32, &mut Rng::new(1));
+    /// assert!(is_safe_prime(params.p));
+    /// assert_eq!(32, 64 - params.p.leading_zeros());
+    /// ```
+    pub fn generate(bits: u32, rng: &mut Rng) -> Params {
+        assert!((8..=MAX_BITS).contains(&bits), "Primes of 8 to {} bits supported", MAX_BITS);
+        loop {
+            // An odd q of bits - 1 bits, so p = 2q + 1 has exactly bits
+            let q = (rng.next_u64() >> (65 - bits)) | 1 << (bits - 2) | 1;
+            if is_prime(q) && is_prime(2 * q + 1) {
+                let p = 2 * q + 1;
+                return Params { p, g: generator(p) };
+            }
+        }
+    }
+
+    /// Checks that `p` is a safe prime and `g` has order (p - 1) / 2
+    pub fn is_valid(&self) -> bool {
+        let q = (self.p - 1) / 2;
+        self.p > 5 && is_prime(self.p) && is_prime(q) && self.g > 1 && self.g < self.p && mod_pow(self.g, q, self.p) == 1
+    }
+
+    /// Whether a public key is in the subgroup, and so safe to use
+    pub fn is_valid_public_key(&self, key: u64) -> bool {
+        key > 1 && key < self.p - 1 && mod_pow(key, (self.p - 1) / 2, self.p) == 1
+    }
+}
+
+/// A generator of the squares modulo the safe prime `p`
+fn generator(p: u64) -> u64 {
+    // Every square other than 1 has order q
+    (2..p).map(|h| mod_pow(h, 2, p)).find(|&g| g != 1).expect("p is larger than 3")
+}
+
+/// A private exponent and its public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPair {
+    pub private: u64,
+    pub public: u64,
+}
+
+impl KeyPair {
+    /// A random key pair, the private exponent between 2 and q - 1
+    pub fn generate(params: &Params, rng: &mut Rng) -> KeyPair {
+        let q = (params.p - 1) / 2;
+        let private = 2 + rng.next_u64() % (q - 2);
+        KeyPair { private, public: mod_pow(params.g, private, params.p) }
+    }
+
+    /// The shared secret from the other side's public key, or `None` if
+    /// the key is not in the subgroup, as a forged key might not be
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::dh::{KeyPair, Params};
+    /// use pprust::rng::Rng;
+    ///
+    /// let mut rng = Rng::new(7);
+    /// let params = Params::generate(48, &mut rng);
+    /// let alice = KeyPair::generate(&params, &mut rng);
+    /// let bob = KeyPair::generate(&params, &mut rng);
+    /// assert_eq!(alice.shared_secret(&params, bob.public), bob.shared_secret(&params, alice.public));
+    /// ```
+    pub fn shared_secret(&self, params: &Params, other_public: u64) -> Option<u64> {
+        if !params.is_valid_public_key(other_public) {
+            return None;
+        }
+        Some(mod_pow(other_public, self.private, params.p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use numtheory::bsgs_dlog;
+
+    #[test]
+    fn should_generate_valid_parameters() {
+        let mut rng = Rng::new(3);
+        for &bits in &[8, 16, 31, 40, 63] {
+            let params = Params::generate(bits, &mut rng);
+            assert!(params.is_valid(), "{:?}", params);
+            assert_eq!(bits, 64 - params.p.leading_zeros());
+        }
+    }
+
+    #[test]
+    fn should_agree_on_a_secret() {
+        let mut rng = Rng::new(4);
+        for _ in 0..20 {
+            let params = Params::generate(61, &mut rng);
+            let alice = KeyPair::generate(&params, &mut rng);
+            let bob = KeyPair::generate(&params, &mut rng);
+            let secret = alice.shared_secret(&params, bob.public).unwrap();
+            assert_eq!(Some(secret), bob.shared_secret(&params, alice.public));
+        }
+    }
+
+    #[test]
+    fn should_reject_keys_outside_the_subgroup() {
+        let params = Params { p: 1019, g: 4 };
+        assert!(params.is_valid());
+        let alice = KeyPair::generate(&params, &mut Rng::new(5));
+        for &bad in &[0, 1, 1018, 1019, 2] {
+            // 2 is not a square modulo 1019
+            assert_eq!(None, alice.shared_secret(&params, bad), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn should_fall_to_discrete_logarithms_when_small() {
+        let mut rng = Rng::new(6);
+        let params = Params::generate(32, &mut rng);
+        let alice = KeyPair::generate(&params, &mut rng);
+        let bob = KeyPair::generate(&params, &mut rng);
+        let recovered = bsgs_dlog(params.g, alice.public, params.p).unwrap();
+        assert_eq!(alice.public, mod_pow(params.g, recovered, params.p));
+        assert_eq!(bob.shared_secret(&params, alice.public), Some(mod_pow(bob.public, recovered, params.p)));
+    }
+}
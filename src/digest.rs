@@ -0,0 +1,338 @@
+//!
+//! # Problem description:
+//! #
+//! # MD5 and SHA-1
+//!
+//! A message digest is a checksum meant to be hard to forge: finding two
+//! messages with the same digest, or a message with a given digest,
+//! should take about as long as trying them all. MD5 (RFC 1321) and
+//! SHA-1 (RFC 3174) no longer live up to that, as collisions for both
+//! can be found, so these are for learning how they work and for
+//! checking files against copying errors, not for security.
+//!
+//! Both pad the message with a 1 bit, zeros and the length of the
+//! message in bits, to a multiple of 64 bytes, and run each 64-byte block
+//! through a compression function that mixes it into a state of 32-bit
+//! words with additions, rotations and bitwise functions. MD5 is little
+//! endian and has four words of state; SHA-1 is big endian and has five.
+//!
+
+use checksum::{Adler32, Crc32};
+use std::io;
+use std::io::prelude::*;
+
+/// A hash computed a piece at a time
+pub trait Digest {
+    /// Starts over, with no bytes
+    fn new() -> Self;
+
+    /// Adds bytes to the message
+    fn update(&mut self, bytes: &[u8]);
+
+    /// The digest of the message so far
+    fn finish(self) -> Vec<u8>;
+
+    /// The digest of a message at once
+    fn digest(bytes: &[u8]) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut digest = Self::new();
+        digest.update(bytes);
+        digest.finish()
+    }
+}
+
+/// The digest of everything a reader has
+///
+/// # Example
+/// ```
+/// use pprust::digest::{digest_reader, hex, Sha1};
+///
+/// let digest = digest_reader::<Sha1, _>("abc".as_bytes()).unwrap();
+/// assert_eq!("a9993e364706816aba3e25717850c26c9cd0d89d", hex(&digest));
+/// ```
+pub fn digest_reader<D: Digest, R: Read>(mut reader: R) -> io::Result<Vec<u8>> {
+    let mut digest = D::new();
+    let mut buffer = [0; 8192];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(digest.finish()),
+            Ok(n) => digest.update(&buffer[..n]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Bytes in lower case hexadecimal, the way digests are written
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Collects bytes into the 64-byte blocks both hashes work on
+#[derive(Debug, Clone)]
+struct Blocks {
+    buffer: [u8; 64],
+    filled: usize,
+    /// The bytes of the message so far
+    length: u64,
+}
+
+impl Blocks {
+    fn new() -> Blocks {
+        Blocks { buffer: [0; 64], filled: 0, length: 0 }
+    }
+
+    fn update<F: FnMut(&[u8; 64])>(&mut self, mut bytes: &[u8], mut compress: F) {
+        self.length = self.length.wrapping_add(bytes.len() as u64);
+        while !bytes.is_empty() {
+            let n = (64 - self.filled).min(bytes.len());
+            self.buffer[self.filled..self.filled + n].copy_from_slice(&bytes[..n]);
+            self.filled += n;
+            bytes = &bytes[n..];
+            if self.filled == 64 {
+                compress(&self.buffer);
+                self.filled = 0;
+            }
+        }
+    }
+
+    /// Pads the message with a 1 bit, zeros and its length in bits, in
+    /// the byte order given, and compresses the last blocks
+    fn finish<F: FnMut(&[u8; 64])>(mut self, length: fn(u64) -> [u8; 8], mut compress: F) {
+        let bits = length(self.length.wrapping_mul(8));
+        let zeros = (119 - self.filled) % 64;
+        let mut padding = vec![0x80];
+        padding.extend(vec![0; zeros]);
+        padding.extend(&bits);
+        self.update(&padding, &mut compress);
+        debug_assert_eq!(0, self.filled);
+    }
+}
+
+/// MD5, from RFC 1321
+///
+/// # Example
+/// ```
+/// use pprust::digest::{hex, Digest, Md5};
+///
+/// assert_eq!("900150983cd24fb0d6963f7d28e17f72", hex(&Md5::digest(b"abc")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Md5 {
+    state: [u32; 4],
+    blocks: Blocks,
+}
+
+/// The shifts of each round of MD5, four per round
+const MD5_SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+/// The integer part of 2^32 times the absolute value of the sine of 1 to
+/// 64, in radians, as RFC 1321 lists them
+const MD5_SINES: [u32; 64] = [
+    0xd76a_a478, 0xe8c7_b756, 0x2420_70db, 0xc1bd_ceee, 0xf57c_0faf, 0x4787_c62a, 0xa830_4613, 0xfd46_9501,
+    0x6980_98d8, 0x8b44_f7af, 0xffff_5bb1, 0x895c_d7be, 0x6b90_1122, 0xfd98_7193, 0xa679_438e, 0x49b4_0821,
+    0xf61e_2562, 0xc040_b340, 0x265e_5a51, 0xe9b6_c7aa, 0xd62f_105d, 0x0244_1453, 0xd8a1_e681, 0xe7d3_fbc8,
+    0x21e1_cde6, 0xc337_07d6, 0xf4d5_0d87, 0x455a_14ed, 0xa9e3_e905, 0xfcef_a3f8, 0x676f_02d9, 0x8d2a_4c8a,
+    0xfffa_3942, 0x8771_f681, 0x6d9d_6122, 0xfde5_380c, 0xa4be_ea44, 0x4bde_cfa9, 0xf6bb_4b60, 0xbebf_bc70,
+    0x289b_7ec6, 0xeaa1_27fa, 0xd4ef_3085, 0x0488_1d05, 0xd9d4_d039, 0xe6db_99e5, 0x1fa2_7cf8, 0xc4ac_5665,
+    0xf429_2244, 0x432a_ff97, 0xab94_23a7, 0xfc93_a039, 0x655b_59c3, 0x8f0c_cc92, 0xffef_f47d, 0x8584_5dd1,
+    0x6fa8_7e4f, 0xfe2c_e6e0, 0xa301_4314, 0x4e08_11a1, 0xf753_7e82, 0xbd3a_f235, 0x2ad7_d2bb, 0xeb86_d391,
+];
+
+fn md5_compress(state: &mut [u32; 4], block: &[u8; 64]) {
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    let [mut a, mut b, mut c, mut d] = *state;
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let rotated = a
+            .wrapping_add(f)
+            .wrapping_add(MD5_SINES[i])
+            .wrapping_add(words[g])
+            .rotate_left(MD5_SHIFTS[i / 16 * 4 + i % 4]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(rotated);
+    }
+    for (word, value) in state.iter_mut().zip(&[a, b, c, d]) {
+        *word = word.wrapping_add(*value);
+    }
+}
+
+impl Digest for Md5 {
+    fn new() -> Md5 {
+        Md5 { state: [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476], blocks: Blocks::new() }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        let state = &mut self.state;
+        self.blocks.update(bytes, |block| md5_compress(state, block));
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let state = &mut self.state;
+        self.blocks.finish(u64::to_le_bytes, |block| md5_compress(state, block));
+        self.state.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+}
+
+/// SHA-1, from RFC 3174
+///
+/// # Example
+/// ```
+/// use pprust::digest::{hex, Digest, Sha1};
+///
+/// let mut sha = Sha1::new();
+/// sha.update(b"The quick brown fox ");
+/// sha.update(b"jumps over the lazy dog");
+/// assert_eq!("2fd4e1c67a2d28fced849ee1bb76e7391b93eb12", hex(&sha.finish()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    blocks: Blocks,
+}
+
+fn sha1_compress(state: &mut [u32; 5], block: &[u8; 64]) {
+    let mut words = [0u32; 80];
+    for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for t in 16..80 {
+        words[t] = (words[t - 3] ^ words[t - 8] ^ words[t - 14] ^ words[t - 16]).rotate_left(1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (t, &word) in words.iter().enumerate() {
+        let (f, k) = match t / 20 {
+            0 => ((b & c) | (!b & d), 0x5a82_7999),
+            1 => (b ^ c ^ d, 0x6ed9_eba1),
+            2 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+            _ => (b ^ c ^ d, 0xca62_c1d6),
+        };
+        let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+    for (word, value) in state.iter_mut().zip(&[a, b, c, d, e]) {
+        *word = word.wrapping_add(*value);
+    }
+}
+
+impl Digest for Sha1 {
+    fn new() -> Sha1 {
+        Sha1 { state: [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0], blocks: Blocks::new() }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        let state = &mut self.state;
+        self.blocks.update(bytes, |block| sha1_compress(state, block));
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let state = &mut self.state;
+        self.blocks.finish(u64::to_be_bytes, |block| sha1_compress(state, block));
+        self.state.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+}
+
+/// The checksum, most significant byte first, as it is usually written
+impl Digest for Crc32 {
+    fn new() -> Crc32 {
+        Crc32::new()
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        Crc32::update(self, bytes)
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.value().to_be_bytes().to_vec()
+    }
+}
+
+/// The checksum, most significant byte first, as it is usually written
+impl Digest for Adler32 {
+    fn new() -> Adler32 {
+        Adler32::new()
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        Adler32::update(self, bytes)
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.value().to_be_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_the_rfc_1321_test_suite() {
+        let suite = [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("a", "0cc175b9c0f1b6a831c399e269772661"),
+            ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+            ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            ("abcdefghijklmnopqrstuvwxyz", "c3fcd3d76192e4007dfb496cca67e13b"),
+            ("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789", "d174ab98d277d9f5a5611c2c9f419d9f"),
+            (&"1234567890".repeat(8), "57edf4a22be3c955ac49da2e2107b67a"),
+        ];
+        for &(message, expected) in &suite {
+            assert_eq!(expected, hex(&Md5::digest(message.as_bytes())), "{:?}", message);
+        }
+    }
+
+    #[test]
+    fn should_match_the_rfc_3174_test_cases() {
+        let cases = [
+            ("abc".to_string(), "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".to_string(),
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+            ("a".repeat(1_000_000), "34aa973cd4c4daa4f61eeb2bdbad27316534016f"),
+            ("01234567".repeat(80), "dea356a2cddd90c7a7ecedc5ebb563934f460452"),
+            (String::new(), "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        ];
+        for (message, expected) in &cases {
+            assert_eq!(*expected, hex(&Sha1::digest(message.as_bytes())), "{} bytes", message.len());
+        }
+    }
+
+    #[test]
+    fn should_give_the_same_digest_in_pieces() {
+        // Pieces of every length around the block and padding boundaries
+        let message: Vec<u8> = (0..300u32).map(|i| (i * 31 % 251) as u8).collect();
+        for split in 0..message.len() {
+            let (mut md5, mut sha) = (Md5::new(), Sha1::new());
+            for piece in message.chunks(split + 1) {
+                md5.update(piece);
+                sha.update(piece);
+            }
+            assert_eq!(Md5::digest(&message), md5.finish());
+            assert_eq!(Sha1::digest(&message), sha.finish());
+        }
+        for n in 54..=66 {
+            let message = vec![b'x'; n];
+            assert_eq!(digest_reader::<Md5, _>(&message[..]).unwrap(), Md5::digest(&message));
+        }
+        assert_eq!("cbf43926", hex(&Crc32::digest(b"123456789")));
+        assert_eq!("091e01de", hex(&Adler32::digest(b"123456789")));
+    }
+}
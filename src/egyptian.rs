@@ -0,0 +1,203 @@
+//!
+//! # Problem description:
+//! #
+//! # Egyptian Fractions
+//!
+//! The ancient Egyptians wrote fractions as sums of distinct unit
+//! fractions, like 4/13 = 1/4 + 1/18 + 1/468. Fibonacci's greedy
+//! algorithm finds such a sum for any fraction between 0 and 1: take the
+//! largest unit fraction that fits, 1/⌈q/p⌉, and repeat on what is left.
+//! The numerator of the remainder always shrinks, so the process ends,
+//! but the denominators can grow explosively: 5/121 needs a denominator
+//! of 25 digits, far past 64 bits, so the arithmetic moves to big
+//! integers when it has to.
+//!
+//! The greedy sum is often not the shortest. 5/121 = 1/33 + 1/121 +
+//! 1/363 has three terms instead of five; a depth-first search with
+//! bounds on the number of terms and the size of the denominators finds
+//! such decompositions.
+//!
+
+use bigint::BigInt;
+
+/// Decomposes `p/q`, with `0 < p < q`, into distinct unit fractions by
+/// Fibonacci's greedy algorithm, returning the denominators in
+/// increasing order
+///
+/// # Example
+/// ```
+/// use pprust::egyptian::decompose;
+///
+/// let denominators: Vec<String> = decompose(4, 13).iter().map(|d| d.to_string()).collect();
+/// assert_eq!(vec!["4", "18", "468"], denominators);
+/// ```
+pub fn decompose(p: u64, q: u64) -> Vec<BigInt> {
+    assert!(0 < p && p < q, "The fraction must be between 0 and 1");
+    let mut denominators = Vec::new();
+    let (mut p, mut q) = (p as u128, q as u128);
+    // Stay with machine integers while the next step cannot overflow
+    while p > 0 {
+        let d = q.div_ceil(p);
+        let (next_p, next_q) = match (p.checked_mul(d), q.checked_mul(d)) {
+            (Some(pd), Some(qd)) => (pd - q, qd),
+            _ => return decompose_big(BigInt::from(p as u64), BigInt::from(q as u64), denominators),
+        };
+        denominators.push(BigInt::from(d as u64));
+        let g = gcd(next_p, next_q);
+        p = next_p / g;
+        q = next_q / g;
+        if q > u64::MAX as u128 {
+            return decompose_big(BigInt::from(p as u64), big_from_u128(q), denominators);
+        }
+    }
+    denominators
+}
+
+fn decompose_big(mut p: BigInt, mut q: BigInt, mut denominators: Vec<BigInt>) -> Vec<BigInt> {
+    let one = BigInt::from(1);
+    while !p.is_zero() {
+        let d = &(&(&q + &p) - &one) / &p;
+        let next_p = &(&p * &d) - &q;
+        let next_q = &q * &d;
+        let g = next_p.gcd(&next_q);
+        denominators.push(d);
+        p = &next_p / &g;
+        q = &next_q / &g;
+    }
+    denominators
+}
+
+fn big_from_u128(n: u128) -> BigInt {
+    &(&BigInt::from((n >> 64) as u64) * &BigInt::from(1u64 << 32).pow(2)) + &BigInt::from(n as u64)
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// Finds a decomposition of `p/q`, with `0 < p < q`, with the fewest
+/// terms, at most `max_terms`, all denominators at most
+/// `max_denominator`. Among the shortest, the one with the smallest
+/// largest denominator wins.
+///
+/// # Example
+/// ```
+/// use pprust::egyptian::shortest;
+///
+/// assert_eq!(Some(vec![33, 121, 363]), shortest(5, 121, 4, 1000));
+/// assert_eq!(None, shortest(5, 121, 2, 1_000_000));
+/// ```
+pub fn shortest(p: u64, q: u64, max_terms: usize, max_denominator: u64) -> Option<Vec<u64>> {
+    assert!(0 < p && p < q, "The fraction must be between 0 and 1");
+    let g = gcd(p as u128, q as u128);
+    let (p, q) = (p as u128 / g, q as u128 / g);
+    for terms in 1..=max_terms {
+        let mut search = Search { current: Vec::new(), best: None, limit: max_denominator as u128 };
+        search.run(p, q, terms, 1);
+        if search.best.is_some() {
+            return search.best;
+        }
+    }
+    None
+}
+
+struct Search {
+    current: Vec<u64>,
+    best: Option<Vec<u64>>,
+    /// The largest denominator still worth trying
+    limit: u128,
+}
+
+impl Search {
+    /// Completes `p/q` with exactly `terms` unit fractions of
+    /// denominators at least `smallest`
+    fn run(&mut self, p: u128, q: u128, terms: usize, smallest: u128) {
+        if terms == 1 {
+            if q.is_multiple_of(p) && q / p >= smallest && q / p <= self.limit {
+                self.current.push((q / p) as u64);
+                self.limit = q / p - 1;
+                self.best = Some(self.current.clone());
+                self.current.pop();
+            }
+            return;
+        }
+        // The next denominator is the smallest left, so 1/d must fit in
+        // p/q but `terms` copies of it must reach p/q
+        let low = smallest.max(q.div_ceil(p));
+        let mut d = low;
+        while d <= self.limit && d * p <= terms as u128 * q {
+            let next_q = match q.checked_mul(d) {
+                Some(next_q) => next_q,
+                None => break,
+            };
+            let next_p = p * d - q;
+            if next_p > 0 {
+                let g = gcd(next_p, next_q);
+                self.current.push(d as u64);
+                self.run(next_p / g, next_q / g, terms - 1, d + 1);
+                self.current.pop();
+            }
+            d += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rational::Ratio;
+
+    fn sum(denominators: &[u64]) -> Ratio {
+        denominators.iter().fold(Ratio::from_integer(0), |s, &d| s + Ratio::new(1, d as i64))
+    }
+
+    #[test]
+    fn should_decompose_greedily() {
+        for q in 2..40u64 {
+            for p in 1..q {
+                let denominators: Vec<u64> = decompose(p, q).iter().map(|d| d.to_u64().unwrap()).collect();
+                assert!(denominators.windows(2).all(|w| w[0] < w[1]), "{}/{}: {:?}", p, q, denominators);
+                assert_eq!(Ratio::new(p as i64, q as i64), sum(&denominators), "{}/{}", p, q);
+            }
+        }
+    }
+
+    #[test]
+    fn should_fall_back_to_big_integers() {
+        let denominators: Vec<String> = decompose(5, 121).iter().map(|d| d.to_string()).collect();
+        assert_eq!(vec!["25", "757", "763309", "873960180913", "1527612795642093418846225"], denominators);
+        // The last term of 31/311 has over 500 digits
+        let long = decompose(31, 311);
+        assert_eq!(10, long.len());
+        assert!(long[9].to_string().len() > 500);
+        let (mut p, mut q) = (BigInt::zero(), BigInt::from(1));
+        for d in &long {
+            p = &(&p * d) + &q;
+            q = &q * d;
+        }
+        assert_eq!(&p * &BigInt::from(311), &q * &BigInt::from(31));
+    }
+
+    #[test]
+    fn should_find_shortest_decompositions() {
+        assert_eq!(Some(vec![3]), shortest(2, 6, 3, 100));
+        assert_eq!(Some(vec![2, 6]), shortest(2, 3, 3, 100));
+        // The greedy 1/4 + 1/18 + 1/468 is beaten on its largest term
+        assert_eq!(Some(vec![4, 26, 52]), shortest(4, 13, 3, 10_000));
+        // Never longer than the greedy decomposition, which the search
+        // also considers
+        for q in 2..20u64 {
+            for p in 1..q {
+                let greedy: Vec<u64> = decompose(p, q).iter().map(|d| d.to_u64().unwrap()).collect();
+                let best = shortest(p, q, greedy.len(), *greedy.last().unwrap()).unwrap();
+                assert_eq!(Ratio::new(p as i64, q as i64), sum(&best));
+                assert!(best.len() <= greedy.len(), "{}/{}", p, q);
+            }
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//!
+//! # Problem description:
+//! #
+//! # Enigma
+//!
+//! The Enigma machine enciphers a letter by passing a current from the
+//! key through a plugboard, which swaps pairs of letters, then through
+//! three rotors from right to left, into a reflector that sends it back
+//! through the rotors from left to right and the plugboard again, to a
+//! lamp. Each rotor is a fixed scramble of the alphabet; turning it
+//! changes which contacts meet, so the machine changes its substitution
+//! at every key press. Because of the reflector, the same setting that
+//! enciphers also deciphers, and no letter is ever enciphered as itself.
+//!
+//! The rightmost rotor turns at every key press, before the letter is
+//! enciphered. A rotor that reaches its notch turns the one to its left
+//! at the next press. The middle rotor, when it is at its own notch,
+//! turns again along with the left rotor: it takes two steps in a row,
+//! the "double step" of the machine.
+//!
+//! The ring setting turns the wiring of a rotor against its letters and
+//! notch, and the position is the letter showing in the window. Settings
+//! are given as letters, A being the first.
+//!
+
+use std::fmt;
+
+/// The rotors of the Wehrmacht and Kriegsmarine machines: name, wiring
+/// and the letters in the window when the notch turns the next rotor
+pub const ROTORS: [(&str, &str, &str); 8] = [
+    ("I", "EKMFLGDQVZNTOWYHXUSPAIBRCJ", "Q"),
+    ("II", "AJDKSIRUXBLHWTMCQGZNPYFVOE", "E"),
+    ("III", "BDFHJLCPRTXVZNYEIWGAKMUSQO", "V"),
+    ("IV", "ESOVPZJAYQUIRHXLNFTGKDCMWB", "J"),
+    ("V", "VZBRGITYUPSDNHLXAWMJQOFECK", "Z"),
+    ("VI", "JPGVOUMFYQBENHZRDKASXLICTW", "ZM"),
+    ("VII", "NZJHGRCXMYSWBOUFAIVLPEKQDT", "ZM"),
+    ("VIII", "FKQHTLXOCBJSPDZRAMEWNIUYGV", "ZM"),
+];
+
+/// The reflectors: name and wiring
+pub const REFLECTORS: [(&str, &str); 3] = [
+    ("A", "EJMZALYXVBWFCRQUONTSPIKHGD"),
+    ("B", "YRUHQSLDPXNGOKMIEBFZCWVJAT"),
+    ("C", "FVPJIAOYEDRZXWGCTKUQSBNMHL"),
+];
+
+/// Errors setting up a machine
+#[derive(Debug, PartialEq, Eq)]
+pub enum EnigmaError {
+    /// No rotor has this name
+    UnknownRotor(String),
+    /// No reflector has this name
+    UnknownReflector(String),
+    /// The same rotor can't be in the machine twice
+    RepeatedRotor(String),
+    /// Ring settings and positions are three letters
+    BadSetting(String),
+    /// The plugboard is pairs of letters, each letter in one pair at most
+    BadPlugboard(String),
+}
+
+impl fmt::Display for EnigmaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EnigmaError::UnknownRotor(ref name) => write!(f, "There is no rotor {}", name),
+            EnigmaError::UnknownReflector(ref name) => write!(f, "There is no reflector {}", name),
+            EnigmaError::RepeatedRotor(ref name) => write!(f, "Rotor {} is in the machine twice", name),
+            EnigmaError::BadSetting(ref setting) => write!(f, "{} isn't three letters", setting),
+            EnigmaError::BadPlugboard(ref pair) => write!(f, "Bad plugboard pair {}", pair),
+        }
+    }
+}
+
+/// The index in the alphabet of an upper case letter
+fn index(c: char) -> Option<u8> {
+    if c.is_ascii_uppercase() {
+        Some(c as u8 - b'A')
+    } else {
+        None
+    }
+}
+
+fn letter(i: u8) -> char {
+    (b'A' + i) as char
+}
+
+/// A wiring as the index each contact leads to
+fn wiring(letters: &str) -> [u8; 26] {
+    let mut wiring = [0; 26];
+    for (i, c) in letters.chars().enumerate() {
+        wiring[i] = index(c).expect("Wirings are upper case letters");
+    }
+    wiring
+}
+
+/// Three letters as indices
+fn setting(text: &str) -> Result<[u8; 3], EnigmaError> {
+    let indices: Vec<u8> = text.chars().filter_map(|c| index(c.to_ascii_uppercase())).collect();
+    if indices.len() != 3 || text.chars().count() != 3 {
+        return Err(EnigmaError::BadSetting(text.to_string()));
+    }
+    Ok([indices[0], indices[1], indices[2]])
+}
+
+#[derive(Debug, Clone)]
+struct Rotor {
+    forward: [u8; 26],
+    backward: [u8; 26],
+    notches: Vec<u8>,
+    ring: u8,
+    position: u8,
+}
+
+impl Rotor {
+    fn new(name: &str) -> Result<Rotor, EnigmaError> {
+        let &(_, letters, notches) = ROTORS
+            .iter()
+            .find(|&&(n, _, _)| n == name)
+            .ok_or_else(|| EnigmaError::UnknownRotor(name.to_string()))?;
+        let forward = wiring(letters);
+        let mut backward = [0; 26];
+        for (i, &o) in forward.iter().enumerate() {
+            backward[o as usize] = i as u8;
+        }
+        let notches = notches.chars().filter_map(index).collect();
+        Ok(Rotor { forward, backward, notches, ring: 0, position: 0 })
+    }
+
+    fn at_notch(&self) -> bool {
+        self.notches.contains(&self.position)
+    }
+
+    fn advance(&mut self) {
+        self.position = (self.position + 1) % 26;
+    }
+
+    /// The contact a current entering at `i` leaves by, through the wiring
+    /// given, with the rotor turned by its position against its ring
+    fn pass(&self, i: u8, wiring: &[u8; 26]) -> u8 {
+        let shift = (26 + self.position - self.ring) % 26;
+        (wiring[((i + shift) % 26) as usize] + 26 - shift) % 26
+    }
+}
+
+/// A three rotor machine
+///
+/// # Example
+/// ```
+/// use pprust::enigma::Enigma;
+///
+/// let mut machine = Enigma::new(["I", "II", "III"], "B", "AAA", "").unwrap();
+/// assert_eq!("BDZGO", machine.encipher("AAAAA"));
+/// machine.set_positions("AAA").unwrap();
+/// assert_eq!("AAAAA", machine.encipher("BDZGO"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Enigma {
+    /// From left to right, as they show in the windows
+    rotors: [Rotor; 3],
+    reflector: [u8; 26],
+    plugboard: [u8; 26],
+}
+
+impl Enigma {
+    /// A machine with the rotors named from left to right, the reflector
+    /// named, the ring settings as three letters and the plugboard as
+    /// pairs of letters separated by spaces, like "AV BS CG". The rotors
+    /// start at AAA.
+    pub fn new(rotors: [&str; 3], reflector: &str, rings: &str, plugboard: &str) -> Result<Enigma, EnigmaError> {
+        for (i, name) in rotors.iter().enumerate() {
+            if rotors[..i].contains(name) {
+                return Err(EnigmaError::RepeatedRotor(name.to_string()));
+            }
+        }
+        let mut machine = Enigma {
+            rotors: [Rotor::new(rotors[0])?, Rotor::new(rotors[1])?, Rotor::new(rotors[2])?],
+            reflector: REFLECTORS
+                .iter()
+                .find(|&&(n, _)| n == reflector)
+                .map(|&(_, letters)| wiring(letters))
+                .ok_or_else(|| EnigmaError::UnknownReflector(reflector.to_string()))?,
+            plugboard: [0; 26],
+        };
+        for (rotor, ring) in machine.rotors.iter_mut().zip(&setting(rings)?) {
+            rotor.ring = *ring;
+        }
+        for (i, plug) in machine.plugboard.iter_mut().enumerate() {
+            *plug = i as u8;
+        }
+        for pair in plugboard.split_whitespace() {
+            let bad = || EnigmaError::BadPlugboard(pair.to_string());
+            let ends: Vec<u8> = pair.chars().filter_map(|c| index(c.to_ascii_uppercase())).collect();
+            if ends.len() != 2 || pair.chars().count() != 2 {
+                return Err(bad());
+            }
+            let (a, b) = (ends[0] as usize, ends[1] as usize);
+            if a == b || machine.plugboard[a] != a as u8 || machine.plugboard[b] != b as u8 {
+                return Err(bad());
+            }
+            machine.plugboard.swap(a, b);
+        }
+        Ok(machine)
+    }
+
+    /// Turns the rotors so that these letters show in the windows
+    pub fn set_positions(&mut self, positions: &str) -> Result<(), EnigmaError> {
+        for (rotor, position) in self.rotors.iter_mut().zip(&setting(positions)?) {
+            rotor.position = *position;
+        }
+        Ok(())
+    }
+
+    /// The letters showing in the windows
+    pub fn positions(&self) -> String {
+        self.rotors.iter().map(|rotor| letter(rotor.position)).collect()
+    }
+
+    /// Turns the rotors as a key press does
+    fn step(&mut self) {
+        if self.rotors[1].at_notch() {
+            self.rotors[0].advance();
+            self.rotors[1].advance();
+        } else if self.rotors[2].at_notch() {
+            self.rotors[1].advance();
+        }
+        self.rotors[2].advance();
+    }
+
+    /// Presses the key of a letter, of either case, and returns the
+    /// letter that lights up, in upper case. Anything but a letter of the
+    /// alphabet is returned as it is, without turning the rotors.
+    pub fn press(&mut self, key: char) -> char {
+        let mut i = match index(key.to_ascii_uppercase()) {
+            Some(i) => i,
+            None => return key,
+        };
+        self.step();
+        i = self.plugboard[i as usize];
+        for rotor in self.rotors.iter().rev() {
+            i = rotor.pass(i, &rotor.forward);
+        }
+        i = self.reflector[i as usize];
+        for rotor in &self.rotors {
+            i = rotor.pass(i, &rotor.backward);
+        }
+        letter(self.plugboard[i as usize])
+    }
+
+    /// Enciphers, or deciphers, the letters of a text, leaving out
+    /// everything else
+    pub fn encipher(&mut self, text: &str) -> String {
+        text.chars().filter(char::is_ascii_alphabetic).map(|c| self.press(c)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_double_step_the_middle_rotor() {
+        let mut machine = Enigma::new(["I", "II", "III"], "B", "AAA", "").unwrap();
+        machine.set_positions("ADU").unwrap();
+        let mut positions = Vec::new();
+        for _ in 0..4 {
+            machine.press('A');
+            positions.push(machine.positions());
+        }
+        assert_eq!(vec!["ADV", "AEW", "BFX", "BFY"], positions);
+        machine.set_positions("AAZ").unwrap();
+        assert_eq!('.', machine.press('.'));
+        assert_eq!("AAZ", machine.positions(), "Only letters turn the rotors");
+    }
+
+    #[test]
+    fn should_set_up_only_real_machines() {
+        assert_eq!(Some(EnigmaError::UnknownRotor("IX".to_string())), Enigma::new(["I", "II", "IX"], "B", "AAA", "").err());
+        assert_eq!(Some(EnigmaError::RepeatedRotor("I".to_string())), Enigma::new(["I", "II", "I"], "B", "AAA", "").err());
+        assert_eq!(Some(EnigmaError::UnknownReflector("D".to_string())), Enigma::new(["I", "II", "III"], "D", "AAA", "").err());
+        assert_eq!(Some(EnigmaError::BadSetting("AA".to_string())), Enigma::new(["I", "II", "III"], "B", "AA", "").err());
+        assert_eq!(Some(EnigmaError::BadPlugboard("BC".to_string())), Enigma::new(["I", "II", "III"], "B", "AAA", "AB BC").err());
+        let mut machine = Enigma::new(["I", "II", "III"], "B", "AAA", "").unwrap();
+        assert_eq!(Err(EnigmaError::BadSetting("A1B".to_string())), machine.set_positions("A1B"));
+    }
+
+    #[test]
+    fn should_never_encipher_a_letter_as_itself() {
+        let text = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG".repeat(20);
+        let mut machine = Enigma::new(["VI", "VIII", "II"], "C", "QEZ", "AZ BY CX DW").unwrap();
+        machine.set_positions("XYZ").unwrap();
+        let cipher = machine.encipher(&text);
+        assert!(cipher.chars().zip(text.chars()).all(|(c, t)| c != t));
+        machine.set_positions("XYZ").unwrap();
+        assert_eq!(text, machine.encipher(&cipher));
+    }
+
+    #[test]
+    fn should_decipher_the_1930_manual_example() {
+        let mut machine = Enigma::new(["II", "I", "III"], "A", "XMV", "AM FI NV PS TU WZ").unwrap();
+        machine.set_positions("ABL").unwrap();
+        let cipher = "GCDSE AHUGW TQGRK VLFGX UCALX VYMIG MMNMF DXTGN VHVRM MEVOU YFZSL RHDRR XFJWC FHUHM \
+                      UNZEF RDISI KBGPM YVXUZ";
+        assert_eq!(
+            "FEINDLIQEINFANTERIEKOLONNEBEOBAQTETXANFANGSUEDAUSGANGBAERWALDEXENDEDREIKMOSTWAERTSNEUSTADT",
+            machine.encipher(cipher)
+        );
+    }
+
+    #[test]
+    fn should_decipher_the_operation_barbarossa_message() {
+        let mut machine = Enigma::new(["II", "IV", "V"], "B", "BUL", "AV BS CG DL FU HZ IN KM OW RX").unwrap();
+        machine.set_positions("WXC").unwrap();
+        let key = machine.encipher("KCH");
+        assert_eq!("BLA", key, "The message key, enciphered at the starting position of the header");
+        machine.set_positions(&key).unwrap();
+        let cipher = "EDPUD NRGYS ZRCXN UYTPO MRMBO FKTBZ REZKM LXLVE FGUEY SIOZV EQMIK UBPMM YLKLT TDEIS \
+                      MDICA GYKUA CTCDO MOHWX MUUIA UBSTS LRNBZ SZWNR FXWFY SSXJZ VIJHI DISHP RKLKA YUPAD \
+                      TXQSP INQMA TLPIF SVKDA SCTAC DPBOP VHJK";
+        assert_eq!(
+            "AUFKLXABTEILUNGXVONXKURTINOWAXKURTINOWAXNORDWESTLXSEBEZXSEBEZXUAFFLIEGERSTRASZERIQTUNGXDUBROWKIXDUBROWKIXOPOTSCHKAXOPOTSCHKAXUMXEINSAQTDREINULLXUHRANGETRETENXANGRIFFXINFXRGTX",
+            machine.encipher(cipher)
+        );
+    }
+}
@@ -0,0 +1,453 @@
+//!
+//! # Problem description:
+//! #
+//! # Expression Compiler
+//!
+//! Compile arithmetic expressions like `2 * (x + 3) ^ 2` to code for a
+//! stack machine, going through the usual phases of a compiler in
+//! miniature. A recursive descent parser, one function per level of
+//! precedence, builds a syntax tree; `^` binds tightest and to the
+//! right, and unary minus sits between it and the products, so `-2 ^ 2`
+//! is -4. Constant folding then replaces every subtree without variables
+//! by its value. Finally a walk of the tree in postorder emits the code:
+//! operands are pushed and each operator pops its arguments and pushes
+//! the result, just as in a reverse Polish calculator.
+//!
+
+use lexgen::Lexer;
+use std::collections::HashMap;
+use std::fmt;
+use std::result;
+
+/// Errors compiling or running an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprError {
+    /// A character that can't start a token, at a byte offset
+    UnexpectedChar(usize, char),
+    /// A token where it doesn't fit, at a byte offset
+    UnexpectedToken(usize),
+    /// The expression ends too early
+    UnexpectedEnd,
+    /// A variable with no value when running
+    UnknownVariable(String),
+    /// An instruction without enough operands on the stack
+    StackUnderflow,
+    /// Parentheses or signs nested more than `MAX_NESTING` deep, at a
+    /// byte offset
+    TooDeep(usize),
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExprError::UnexpectedChar(at, c) => write!(f, "Unexpected '{}' at {}", c, at),
+            ExprError::UnexpectedToken(at) => write!(f, "Unexpected token at {}", at),
+            ExprError::UnexpectedEnd => write!(f, "Unexpected end of expression"),
+            ExprError::UnknownVariable(ref name) => write!(f, "Unknown variable {}", name),
+            ExprError::StackUnderflow => write!(f, "Stack underflow"),
+            ExprError::TooDeep(at) => write!(f, "Nested more than {} deep at {}", MAX_NESTING, at),
+        }
+    }
+}
+
+pub type ExprResult<T> = result::Result<T, ExprError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl BinOp {
+    pub fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            BinOp::Add => a + b,
+            BinOp::Sub => a - b,
+            BinOp::Mul => a * b,
+            BinOp::Div => a / b,
+            BinOp::Pow => a.powf(b),
+        }
+    }
+}
+
+/// A syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// The value of the expression, with `vars` giving the variables
+    pub fn eval(&self, vars: &HashMap<String, f64>) -> ExprResult<f64> {
+        Ok(match *self {
+            Expr::Num(x) => x,
+            Expr::Var(ref name) => *vars.get(name).ok_or_else(|| ExprError::UnknownVariable(name.clone()))?,
+            Expr::Neg(ref e) => -e.eval(vars)?,
+            Expr::Binary(op, ref a, ref b) => op.apply(a.eval(vars)?, b.eval(vars)?),
+        })
+    }
+}
+
+/// An instruction of the stack machine
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    Push(f64),
+    Load(String),
+    Neg,
+    Binary(BinOp),
+}
+
+/// Shows the instruction as assembly, like `push 2` or `mul`
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Op::Push(x) => write!(f, "push {}", x),
+            Op::Load(ref name) => write!(f, "load {}", name),
+            Op::Neg => write!(f, "neg"),
+            Op::Binary(op) => write!(f, "{}", match op {
+                BinOp::Add => "add",
+                BinOp::Sub => "sub",
+                BinOp::Mul => "mul",
+                BinOp::Div => "div",
+                BinOp::Pow => "pow",
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Symbol(char),
+}
+
+/// The kinds of tokens, as the lexer finds them
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Num,
+    Ident,
+    Symbol,
+}
+
+fn tokenize(text: &str) -> ExprResult<Vec<(usize, Token)>> {
+    // Names may have any letters, so anything past ASCII counts as one
+    let lexer = Lexer::new()
+        .token(Kind::Num, "[0-9.]+")
+        .and_then(|lexer| lexer.token(Kind::Ident, "[A-Za-z_\u{80}-\u{10ffff}][A-Za-z0-9_\u{80}-\u{10ffff}]*"))
+        .and_then(|lexer| lexer.token(Kind::Symbol, "[-+*/^()]"))
+        .and_then(|lexer| lexer.skip("\\s+"))
+        .expect("The patterns of the tokens are valid");
+    let mut tokens = Vec::new();
+    for token in lexer.tokens(text) {
+        let token = token.map_err(|e| ExprError::UnexpectedChar(e.offset, e.found))?;
+        let start = token.offset;
+        tokens.push((start, match token.kind {
+            Kind::Num => {
+                let first = token.text.chars().next().expect("Tokens aren't empty");
+                Token::Num(token.text.parse().map_err(|_| ExprError::UnexpectedChar(start, first))?)
+            }
+            Kind::Ident => Token::Ident(token.text.to_string()),
+            Kind::Symbol => Token::Symbol(token.text.chars().next().expect("Tokens aren't empty")),
+        }));
+    }
+    Ok(tokens)
+}
+
+/// How deep parentheses and unary minus can nest, as the parser and the
+/// passes over the tree recur once for each level
+pub const MAX_NESTING: usize = 256;
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    /// How many unary expressions are being parsed
+    depth: usize,
+}
+
+impl Parser {
+    fn peek_symbol(&self) -> Option<char> {
+        match self.tokens.get(self.next) {
+            Some(&(_, Token::Symbol(c))) => Some(c),
+            _ => None,
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> ExprResult<()> {
+        match self.tokens.get(self.next) {
+            Some(&(_, Token::Symbol(c))) if c == symbol => {
+                self.next += 1;
+                Ok(())
+            }
+            Some(&(at, _)) => Err(ExprError::UnexpectedToken(at)),
+            None => Err(ExprError::UnexpectedEnd),
+        }
+    }
+
+    /// sum = product (('+' | '-') product)*
+    fn sum(&mut self) -> ExprResult<Expr> {
+        let mut e = self.product()?;
+        while let Some(c @ ('+' | '-')) = self.peek_symbol() {
+            self.next += 1;
+            let op = if c == '+' { BinOp::Add } else { BinOp::Sub };
+            e = Expr::Binary(op, Box::new(e), Box::new(self.product()?));
+        }
+        Ok(e)
+    }
+
+    /// product = unary (('*' | '/') unary)*
+    fn product(&mut self) -> ExprResult<Expr> {
+        let mut e = self.unary()?;
+        while let Some(c @ ('*' | '/')) = self.peek_symbol() {
+            self.next += 1;
+            let op = if c == '*' { BinOp::Mul } else { BinOp::Div };
+            e = Expr::Binary(op, Box::new(e), Box::new(self.unary()?));
+        }
+        Ok(e)
+    }
+
+    /// unary = '-' unary | power
+    fn unary(&mut self) -> ExprResult<Expr> {
+        if self.depth == MAX_NESTING {
+            return Err(ExprError::TooDeep(self.tokens.get(self.next).map_or(0, |&(at, _)| at)));
+        }
+        self.depth += 1;
+        let e = if self.peek_symbol() == Some('-') {
+            self.next += 1;
+            self.unary().map(|e| Expr::Neg(Box::new(e)))
+        } else {
+            self.power()
+        };
+        self.depth -= 1;
+        e
+    }
+
+    /// power = primary ('^' unary)?
+    fn power(&mut self) -> ExprResult<Expr> {
+        let base = self.primary()?;
+        if self.peek_symbol() == Some('^') {
+            self.next += 1;
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    /// primary = number | variable | '(' sum ')'
+    fn primary(&mut self) -> ExprResult<Expr> {
+        let (at, token) = self.tokens.get(self.next).cloned().ok_or(ExprError::UnexpectedEnd)?;
+        self.next += 1;
+        match token {
+            Token::Num(x) => Ok(Expr::Num(x)),
+            Token::Ident(name) => Ok(Expr::Var(name)),
+            Token::Symbol('(') => {
+                let e = self.sum()?;
+                self.expect_symbol(')')?;
+                Ok(e)
+            }
+            Token::Symbol(_) => Err(ExprError::UnexpectedToken(at)),
+        }
+    }
+}
+
+/// Parses an infix expression
+///
+/// # Example
+/// ```
+/// use pprust::exprc::{parse, BinOp, Expr};
+///
+/// let e = parse("1 + x * 2").unwrap();
+/// let product = Expr::Binary(BinOp::Mul, Box::new(Expr::Var("x".to_string())), Box::new(Expr::Num(2.0)));
+/// assert_eq!(Expr::Binary(BinOp::Add, Box::new(Expr::Num(1.0)), Box::new(product)), e);
+/// ```
+pub fn parse(text: &str) -> ExprResult<Expr> {
+    let mut parser = Parser { tokens: tokenize(text)?, next: 0, depth: 0 };
+    let e = parser.sum()?;
+    match parser.tokens.get(parser.next) {
+        Some(&(at, _)) => Err(ExprError::UnexpectedToken(at)),
+        None => Ok(e),
+    }
+}
+
+/// Replaces the subtrees without variables by their values
+///
+/// # Example
+/// ```
+/// use pprust::exprc::{fold, parse};
+///
+/// assert_eq!(parse("x * 10").unwrap(), fold(parse("x * ((2 + 3) ^ 2 / 2.5)").unwrap()));
+/// ```
+pub fn fold(expr: Expr) -> Expr {
+    match expr {
+        Expr::Neg(e) => match fold(*e) {
+            Expr::Num(x) => Expr::Num(-x),
+            e => Expr::Neg(Box::new(e)),
+        },
+        Expr::Binary(op, a, b) => match (fold(*a), fold(*b)) {
+            (Expr::Num(x), Expr::Num(y)) => Expr::Num(op.apply(x, y)),
+            (a, b) => Expr::Binary(op, Box::new(a), Box::new(b)),
+        },
+        e => e,
+    }
+}
+
+/// The stack machine code computing `expr`
+pub fn codegen(expr: &Expr) -> Vec<Op> {
+    fn emit(expr: &Expr, code: &mut Vec<Op>) {
+        match *expr {
+            Expr::Num(x) => code.push(Op::Push(x)),
+            Expr::Var(ref name) => code.push(Op::Load(name.clone())),
+            Expr::Neg(ref e) => {
+                emit(e, code);
+                code.push(Op::Neg);
+            }
+            Expr::Binary(op, ref a, ref b) => {
+                emit(a, code);
+                emit(b, code);
+                code.push(Op::Binary(op));
+            }
+        }
+    }
+    let mut code = Vec::new();
+    emit(expr, &mut code);
+    code
+}
+
+/// Parses, folds and generates code for an expression
+///
+/// # Example
+/// ```
+/// use pprust::exprc::compile;
+///
+/// let code: Vec<String> = compile("-(1 + 2) * y").unwrap().iter().map(|op| op.to_string()).collect();
+/// assert_eq!(vec!["push -3", "load y", "mul"], code);
+/// ```
+pub fn compile(text: &str) -> ExprResult<Vec<Op>> {
+    Ok(codegen(&fold(parse(text)?)))
+}
+
+/// Runs stack machine code, with `vars` giving the variables, and
+/// returns the value left on top
+///
+/// # Example
+/// ```
+/// use pprust::exprc::{compile, run};
+/// use std::collections::HashMap;
+///
+/// let code = compile("2 * (x + 3) ^ 2").unwrap();
+/// let vars: HashMap<String, f64> = [("x".to_string(), 1.0)].iter().cloned().collect();
+/// assert_eq!(Ok(32.0), run(&code, &vars));
+/// ```
+pub fn run(code: &[Op], vars: &HashMap<String, f64>) -> ExprResult<f64> {
+    let mut stack: Vec<f64> = Vec::new();
+    for op in code {
+        match *op {
+            Op::Push(x) => stack.push(x),
+            Op::Load(ref name) => stack.push(*vars.get(name).ok_or_else(|| ExprError::UnknownVariable(name.clone()))?),
+            Op::Neg => {
+                let x = stack.pop().ok_or(ExprError::StackUnderflow)?;
+                stack.push(-x);
+            }
+            Op::Binary(op) => {
+                let b = stack.pop().ok_or(ExprError::StackUnderflow)?;
+                let a = stack.pop().ok_or(ExprError::StackUnderflow)?;
+                stack.push(op.apply(a, b));
+            }
+        }
+    }
+    stack.pop().ok_or(ExprError::StackUnderflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn vars() -> HashMap<String, f64> {
+        [("x", 1.5), ("y", -2.0), ("z", 0.25)].iter().map(|&(n, v)| (n.to_string(), v)).collect()
+    }
+
+    fn random_expression(rng: &mut Rng, depth: u32) -> String {
+        if depth == 0 || rng.gen_range(0, 3) == 0 {
+            return match rng.gen_range(0, 5) {
+                0 => "x".to_string(),
+                1 => "y".to_string(),
+                2 => "z".to_string(),
+                _ => rng.gen_range(0, 10).to_string(),
+            };
+        }
+        let a = random_expression(rng, depth - 1);
+        let b = random_expression(rng, depth - 1);
+        match rng.gen_range(0, 7) {
+            0 => format!("{} + {}", a, b),
+            1 => format!("{} - {}", a, b),
+            2 => format!("{} * {}", a, b),
+            3 => format!("{} / {}", a, b),
+            4 => format!("{} ^ {}", a, b),
+            5 => format!("-{}", a),
+            _ => format!("({})", a),
+        }
+    }
+
+    fn same(a: f64, b: f64) -> bool {
+        a == b || (a.is_nan() && b.is_nan())
+    }
+
+    #[test]
+    fn should_respect_precedence_and_associativity() {
+        let value = |text: &str| parse(text).unwrap().eval(&vars()).unwrap();
+        assert_eq!(7.0, value("1 + 2 * 3"));
+        assert_eq!(9.0, value("(1 + 2) * 3"));
+        assert_eq!(2.0, value("8 - 4 - 2"));
+        assert_eq!(1.0, value("8 / 4 / 2"));
+        assert_eq!(512.0, value("2 ^ 3 ^ 2"));
+        assert_eq!(-4.0, value("-2 ^ 2"));
+        assert_eq!(0.25, value("2 ^ -2"));
+        assert_eq!(2.0, value("--2"));
+        assert_eq!(-0.5, value("x + y"));
+    }
+
+    #[test]
+    fn should_run_code_like_the_tree() {
+        let mut rng = Rng::new(258);
+        for _ in 0..2000 {
+            let text = random_expression(&mut rng, 4);
+            let tree = parse(&text).unwrap();
+            let expected = tree.eval(&vars()).unwrap();
+            assert!(same(expected, run(&codegen(&tree), &vars()).unwrap()), "{}", text);
+            assert!(same(expected, run(&compile(&text).unwrap(), &vars()).unwrap()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn should_fold_constants() {
+        assert_eq!(vec![Op::Push(14.0)], compile("2 + 3 * 4").unwrap());
+        assert_eq!(vec![Op::Load("x".to_string()), Op::Push(6.0), Op::Binary(BinOp::Mul)], compile("x * (2 * 3)").unwrap());
+        // Without reassociation, constants apart in the tree stay apart
+        assert_eq!(5, compile("x * 2 * 3").unwrap().len());
+        assert_eq!(vec![Op::Push(-8.0)], compile("-(2 ^ 3)").unwrap());
+    }
+
+    #[test]
+    fn should_report_errors() {
+        assert_eq!(Err(ExprError::UnexpectedEnd), parse("1 +"));
+        assert_eq!(Err(ExprError::UnexpectedEnd), parse(""));
+        assert_eq!(Err(ExprError::UnexpectedEnd), parse("(1 + 2"));
+        assert_eq!(Err(ExprError::UnexpectedToken(4)), parse("1 + )"));
+        assert_eq!(Err(ExprError::UnexpectedToken(2)), parse("1 2"));
+        assert_eq!(Err(ExprError::UnexpectedChar(2, '%')), parse("1 % 2"));
+        assert_eq!(Err(ExprError::UnexpectedChar(0, '1')), parse("1.2.3"));
+        assert_eq!(Err(ExprError::UnknownVariable("w".to_string())), run(&compile("w + 1").unwrap(), &vars()));
+        assert_eq!(Err(ExprError::StackUnderflow), run(&[Op::Push(1.0), Op::Binary(BinOp::Add)], &vars()));
+        assert_eq!(Err(ExprError::StackUnderflow), run(&[], &vars()));
+        let nested = format!("{}1{}", "(".repeat(MAX_NESTING - 1), ")".repeat(MAX_NESTING - 1));
+        assert_eq!(Ok(vec![Op::Push(1.0)]), compile(&nested));
+        assert_eq!(Err(ExprError::TooDeep(256)), compile(&"(".repeat(100_000)));
+        assert_eq!(Err(ExprError::TooDeep(256)), parse(&"-".repeat(1000)));
+    }
+}
@@ -0,0 +1,229 @@
+//!
+//! # Problem description:
+//! #
+//! # External Sorting
+//!
+//! Sort a file of lines that is too large to fit in memory. Read the
+//! input in chunks that fit in a memory budget, sort each chunk and
+//! write it to a temporary file called a run, then merge all the runs
+//! together with a k-way merge that keeps the head of every run in a
+//! priority queue.
+//!
+
+use heap;
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic;
+
+static RUN_COUNTER: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+
+/// Temporary run files, removed when dropped
+struct Runs {
+    paths: Vec<PathBuf>,
+}
+
+impl Runs {
+    fn create(&mut self) -> io::Result<io::BufWriter<fs::File>> {
+        let n = RUN_COUNTER.fetch_add(1, atomic::Ordering::SeqCst);
+        let path = env::temp_dir().join(format!("pprust-extsort-{}-{}.run", process::id(), n));
+        let file = fs::File::create(&path)?;
+        self.paths.push(path);
+        Ok(io::BufWriter::new(file))
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Compares lines numerically by their leading number, like `sort -n`.
+///
+/// Lines without a leading number sort as zero; ties are broken by
+/// comparing the whole lines.
+pub fn numeric_order(a: &str, b: &str) -> Ordering {
+    leading_number(a)
+        .partial_cmp(&leading_number(b))
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| a.cmp(b))
+}
+
+fn leading_number(line: &str) -> f64 {
+    let line = line.trim_start();
+    let end = line
+        .char_indices()
+        .take_while(|&(i, c)| c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+')))
+        .map(|(i, c)| i + c.len_utf8())
+        .last()
+        .unwrap_or(0);
+    line[..end].parse().unwrap_or(0.0)
+}
+
+/// Sorts the lines read from `input` into `output` using at most about
+/// `memory_budget` bytes of line data in memory at once.
+///
+/// Output lines are always terminated by a newline. The sort is stable:
+/// lines comparing equal keep their input order.
+pub fn sort_lines_by<R, W, F>(input: R, output: W, memory_budget: usize, mut compare: F) -> io::Result<()>
+    where R: BufRead, W: Write, F: FnMut(&str, &str) -> Ordering
+{
+    let mut runs = Runs { paths: Vec::new() };
+    let mut chunk: Vec<String> = Vec::new();
+    let mut chunk_size = 0;
+    let mut lines = input.lines();
+    loop {
+        let line = lines.next();
+        let full = chunk_size >= memory_budget && !chunk.is_empty();
+        if full || (line.is_none() && !runs.paths.is_empty() && !chunk.is_empty()) {
+            chunk.sort_by(|a, b| compare(a, b));
+            write_lines(&chunk, runs.create()?)?;
+            chunk.clear();
+            chunk_size = 0;
+        }
+        match line {
+            None => break,
+            Some(line) => {
+                let line = line?;
+                chunk_size += line.len() + ::std::mem::size_of::<String>();
+                chunk.push(line);
+            }
+        }
+    }
+    if runs.paths.is_empty() {
+        chunk.sort_by(|a, b| compare(a, b));
+        return write_lines(&chunk, output);
+    }
+    merge_runs(&runs.paths, output, compare)
+}
+
+/// Sorts lines in their natural (byte-wise) order
+pub fn sort_lines<R: BufRead, W: Write>(input: R, output: W, memory_budget: usize) -> io::Result<()> {
+    sort_lines_by(input, output, memory_budget, |a, b| a.cmp(b))
+}
+
+/// Sorts the lines of the `input` file into the `output` file using at
+/// most about `memory_budget` bytes of memory for line data.
+pub fn sort_file<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q, memory_budget: usize) -> io::Result<()> {
+    let input = io::BufReader::new(fs::File::open(input)?);
+    let output = io::BufWriter::new(fs::File::create(output)?);
+    sort_lines(input, output, memory_budget)
+}
+
+fn write_lines<W: Write>(lines: &[String], mut output: W) -> io::Result<()> {
+    for line in lines {
+        writeln!(output, "{}", line)?;
+    }
+    output.flush()
+}
+
+fn merge_runs<W, F>(paths: &[PathBuf], mut output: W, mut compare: F) -> io::Result<()>
+    where W: Write, F: FnMut(&str, &str) -> Ordering
+{
+    let mut readers = Vec::with_capacity(paths.len());
+    for path in paths {
+        readers.push(io::BufReader::new(fs::File::open(path)?).lines());
+    }
+    // Min-heap of (line, run), ties broken by run index to keep the merge stable
+    let mut is_greater = |a: &(String, usize), b: &(String, usize)| {
+        compare(&a.0, &b.0).then(a.1.cmp(&b.1)) == Ordering::Greater
+    };
+    let mut heads: Vec<(String, usize)> = Vec::with_capacity(readers.len());
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(line) = reader.next() {
+            heads.push((line?, run));
+        }
+    }
+    heap::heapify_by(&mut heads, &mut is_greater);
+    while !heads.is_empty() {
+        let run = heads[0].1;
+        writeln!(output, "{}", heads[0].0)?;
+        match readers[run].next() {
+            Some(line) => heads[0] = (line?, run),
+            None => {
+                let last = heads.len() - 1;
+                heads.swap(0, last);
+                heads.pop();
+            }
+        }
+        heap::sift_down_by(&mut heads, 0, &mut is_greater);
+    }
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sort_with_budget(input: &str, budget: usize) -> String {
+        let mut output = Vec::new();
+        sort_lines(input.as_bytes(), &mut output, budget).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn should_sort_in_memory_when_it_fits() {
+        assert_eq!("a\nb\nc\n", sort_with_budget("c\na\nb\n", 1 << 20));
+    }
+
+    #[test]
+    fn should_sort_with_many_runs() {
+        let input: String = (0..500).map(|i| format!("{:05}\n", (i * 7919) % 500)).collect();
+        let expected: String = (0..500).map(|i| format!("{:05}\n", i)).collect();
+        assert_eq!(expected, sort_with_budget(&input, 100));
+    }
+
+    #[test]
+    fn should_handle_empty_input() {
+        assert_eq!("", sort_with_budget("", 10));
+    }
+
+    #[test]
+    fn should_clean_up_run_files() {
+        let before = RUN_COUNTER.load(atomic::Ordering::SeqCst);
+        sort_with_budget("b\na\nd\nc\n", 1);
+        let after = RUN_COUNTER.load(atomic::Ordering::SeqCst);
+        assert!(after > before);
+        for n in before..after {
+            let path = env::temp_dir().join(format!("pprust-extsort-{}-{}.run", process::id(), n));
+            assert!(!path.exists(), "Run file {:?} should have been removed", path);
+        }
+    }
+
+    #[test]
+    fn should_sort_numerically_and_in_reverse() {
+        let input = "10\n9\n-1\n100\nx\n";
+        let mut output = Vec::new();
+        sort_lines_by(input.as_bytes(), &mut output, 4, numeric_order).unwrap();
+        assert_eq!("-1\nx\n9\n10\n100\n", String::from_utf8(output).unwrap());
+        let mut output = Vec::new();
+        sort_lines_by(input.as_bytes(), &mut output, 4, |a, b| numeric_order(b, a)).unwrap();
+        assert_eq!("100\n10\n9\nx\n-1\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn should_be_stable_across_runs() {
+        let input = "b 1\na 1\nb 2\na 2\nb 3\na 3\n";
+        let mut output = Vec::new();
+        sort_lines_by(input.as_bytes(), &mut output, 8, |a, b| a[..1].cmp(&b[..1])).unwrap();
+        assert_eq!("a 1\na 2\na 3\nb 1\nb 2\nb 3\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn should_sort_files() {
+        let input = env::temp_dir().join(format!("pprust-extsort-test-in-{}", process::id()));
+        let output = env::temp_dir().join(format!("pprust-extsort-test-out-{}", process::id()));
+        fs::write(&input, "pear\napple\nfig\n").unwrap();
+        sort_file(&input, &output, 8).unwrap();
+        assert_eq!("apple\nfig\npear\n", fs::read_to_string(&output).unwrap());
+        fs::remove_file(&input).unwrap();
+        fs::remove_file(&output).unwrap();
+    }
+}
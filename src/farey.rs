@@ -0,0 +1,179 @@
+//!
+//! # Problem description:
+//! #
+//! # Farey Sequences and the Stern-Brocot Tree
+//!
+//! The Farey sequence of order n lists the fractions between 0 and 1 in
+//! lowest terms with denominators at most n, in increasing order:
+//!
+//! ```text
+//! F5 = 0/1 1/5 1/4 1/3 2/5 1/2 3/5 2/3 3/4 4/5 1/1
+//! ```
+//!
+//! Neighbours a/b < c/d satisfy bc - ad = 1, which gives each term from
+//! the two before it: with k = ⌊(n + b) / d⌋, the next term is
+//! (kc - a) / (kd - b).
+//!
+//! The Stern-Brocot tree holds every positive fraction once. Starting
+//! from the bounds 0/1 and 1/0, the mediant (a + c) / (b + d) of two
+//! bounds lies between them; searching for a number x means moving
+//! whichever bound is on the wrong side to the mediant. The bounds are
+//! always the best approximations of x for their denominators, so
+//! stopping when the denominators get too large gives the best rational
+//! approximation within the limit. Runs of moves in the same direction
+//! are taken all at once, making the search as fast as Euclid's
+//! algorithm.
+//!
+
+use rational::Ratio;
+
+/// An iterator over a Farey sequence
+pub struct Farey {
+    order: i64,
+    current: Option<(i64, i64)>,
+    next: (i64, i64),
+}
+
+/// The Farey sequence of order `n`, from 0/1 to 1/1
+///
+/// # Example
+/// ```
+/// use pprust::farey::sequence;
+///
+/// let terms: Vec<String> = sequence(4).map(|r| r.to_string()).collect();
+/// assert_eq!(vec!["0", "1/4", "1/3", "1/2", "2/3", "3/4", "1"], terms);
+/// ```
+pub fn sequence(n: u32) -> Farey {
+    assert!(n >= 1, "Farey sequences start at order 1");
+    Farey { order: n as i64, current: Some((0, 1)), next: (1, n as i64) }
+}
+
+impl Iterator for Farey {
+    type Item = Ratio;
+
+    fn next(&mut self) -> Option<Ratio> {
+        let (a, b) = self.current?;
+        let (c, d) = self.next;
+        self.current = if a == 1 && b == 1 {
+            None
+        } else {
+            let k = (self.order + b) / d;
+            self.next = (k * c - a, k * d - b);
+            Some((c, d))
+        };
+        Some(Ratio::new(a, b))
+    }
+}
+
+/// The fraction closest to `x` with a denominator at most
+/// `max_denominator`, found by descending the Stern-Brocot tree
+///
+/// # Example
+/// ```
+/// use pprust::farey::approximate;
+/// use pprust::rational::Ratio;
+///
+/// assert_eq!(Ratio::new(355, 113), approximate(std::f64::consts::PI, 1000));
+/// assert_eq!(Ratio::new(-7, 5), approximate(-1.4142, 5));
+/// ```
+pub fn approximate(x: f64, max_denominator: i64) -> Ratio {
+    assert!(x.is_finite(), "Cannot approximate {}", x);
+    assert!(max_denominator >= 1, "The denominator limit must be positive");
+    let whole = x.floor();
+    let fraction = x - whole;
+    // Bounds on the fractional part, as (numerator, denominator)
+    let (mut left, mut right) = ((0i64, 1i64), (1i64, 1i64));
+    let value = |(n, d): (i64, i64)| n as f64 / d as f64;
+    loop {
+        let mediant = (left.0 + right.0, left.1 + right.1);
+        if mediant.1 > max_denominator || value(mediant) == fraction {
+            if mediant.1 <= max_denominator {
+                left = mediant;
+                right = mediant;
+            }
+            break;
+        }
+        if fraction < value(mediant) {
+            // Move the right bound towards the left as far as possible
+            let steps = run_length(right, left, fraction, max_denominator, |v| v > fraction);
+            right = (right.0 + steps * left.0, right.1 + steps * left.1);
+        } else {
+            let steps = run_length(left, right, fraction, max_denominator, |v| v < fraction);
+            left = (left.0 + steps * right.0, left.1 + steps * right.1);
+        }
+    }
+    let best = if (fraction - value(left)).abs() <= (value(right) - fraction).abs() { left } else { right };
+    Ratio::new(best.0, best.1) + Ratio::from_integer(whole as i64)
+}
+
+/// How many times in a row `moving` can step towards `fixed`, staying on
+/// the side of `x` that `keeps_side` accepts and within the denominator
+/// limit; at least one step is possible
+fn run_length<F: Fn(f64) -> bool>(moving: (i64, i64), fixed: (i64, i64), x: f64, max_denominator: i64, keeps_side: F) -> i64 {
+    let at = |k: i64| (moving.0 + k * fixed.0) as f64 / (moving.1 + k * fixed.1) as f64;
+    let by_limit = (max_denominator - moving.1) / fixed.1;
+    // Solving for the crossing point, then correcting for rounding
+    let crossing = (x * moving.1 as f64 - moving.0 as f64) / (fixed.0 as f64 - x * fixed.1 as f64);
+    let mut k = if crossing.is_finite() && crossing >= 0.0 { (crossing.floor() as i64).min(by_limit) } else { by_limit };
+    k = k.max(1);
+    while k > 1 && !keeps_side(at(k)) {
+        k -= 1;
+    }
+    while k < by_limit && keeps_side(at(k + 1)) {
+        k += 1;
+    }
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn totient(n: i64) -> i64 {
+        (1..=n).filter(|&k| gcd(k, n) == 1).count() as i64
+    }
+
+    fn gcd(a: i64, b: i64) -> i64 {
+        if b == 0 { a } else { gcd(b, a % b) }
+    }
+
+    #[test]
+    fn should_list_neighbours_in_order() {
+        for n in 1..40 {
+            let terms: Vec<Ratio> = sequence(n).collect();
+            assert_eq!(1 + (1..=n as i64).map(totient).sum::<i64>(), terms.len() as i64);
+            for w in terms.windows(2) {
+                assert!(w[0] < w[1]);
+                assert_eq!(1, w[0].denom() * w[1].numer() - w[0].numer() * w[1].denom());
+                assert!(w[1].denom() <= n as i64);
+            }
+        }
+        let f1: Vec<Ratio> = sequence(1).collect();
+        assert_eq!(vec![Ratio::new(0, 1), Ratio::new(1, 1)], f1);
+    }
+
+    #[test]
+    fn should_agree_with_continued_fractions() {
+        let mut rng = Rng::new(21);
+        for _ in 0..3000 {
+            let d = rng.gen_range(1, 5000) as i64;
+            let n = rng.gen_range(0, 40_000) as i64 - 20_000;
+            let x = Ratio::new(n, d);
+            let max = rng.gen_range(1, 200) as i64;
+            let expected = x.limit_denominator(max);
+            let found = approximate(x.to_f64(), max);
+            // Ties can go either way
+            assert_eq!((expected - x).abs(), (found - x).abs(), "{} within {}: {} vs {}", x, max, expected, found);
+            assert!(found.denom() <= max);
+        }
+    }
+
+    #[test]
+    fn should_approximate_quickly() {
+        assert_eq!(Ratio::new(1, 1_000_000), approximate(1e-6, 1_000_000_000));
+        assert_eq!(Ratio::new(0, 1), approximate(1e-12, 1000));
+        assert_eq!(Ratio::new(3, 1), approximate(2.9999, 100));
+        assert_eq!(Ratio::new(103993, 33102), approximate(std::f64::consts::PI, 33102));
+    }
+}
@@ -0,0 +1,231 @@
+//!
+//! # Problem description:
+//! #
+//! # Galton Board
+//!
+//! Balls dropped into a Galton board bounce off rows of pins, going left
+//! or right with equal chance at each, and collect in bins at the bottom.
+//! After *n* rows a ball lands in bin *k* when it went right *k* times,
+//! which happens with probability C(n, k) / 2^n: the bins fill up in the
+//! shape of the binomial distribution, whose counts are row *n* of
+//! Pascal's triangle.
+//!
+//! Pearson's chi-squared test measures how well the simulated counts fit
+//! the exact distribution. The statistic sums (observed - expected)² /
+//! expected over the bins; bins at the tails expected to hold fewer than
+//! five balls are pooled with their neighbours first, as the test asks.
+//! The p-value is the chance of a worse fit if the balls really follow
+//! the binomial distribution.
+//!
+
+use pascal;
+use rng::Rng;
+
+/// Drops `balls` balls through `rows` rows of pins and returns how many
+/// land in each of the `rows + 1` bins
+///
+/// # Example
+/// ```
+/// use pprust::galton::simulate;
+///
+/// let bins = simulate(10, 1000, 1);
+/// assert_eq!(11, bins.len());
+/// assert_eq!(1000, bins.iter().sum::<u64>());
+/// ```
+pub fn simulate(rows: usize, balls: u64, seed: u64) -> Vec<u64> {
+    let mut rng = Rng::new(seed);
+    let mut bins = vec![0; rows + 1];
+    for _ in 0..balls {
+        let mut bin = 0;
+        for _ in 0..rows {
+            bin += (rng.next_u32() >> 31) as usize;
+        }
+        bins[bin] += 1;
+    }
+    bins
+}
+
+/// The expected count in each bin for `balls` balls through `rows` rows
+pub fn expected(rows: usize, balls: u64) -> Vec<f64> {
+    let total = 2f64.powi(rows as i32);
+    pascal::row(rows).iter().map(|&c| balls as f64 * c as f64 / total).collect()
+}
+
+/// Draws the bins as horizontal bars, the fullest `width` characters
+/// long
+///
+/// # Example
+/// ```
+/// use pprust::galton::histogram;
+///
+/// assert_eq!("0 |##   2\n1 |#### 4\n", histogram(&[2, 4], 4));
+/// ```
+pub fn histogram(bins: &[u64], width: usize) -> String {
+    let most = bins.iter().cloned().max().unwrap_or(0).max(1);
+    let label_width = bins.len().saturating_sub(1).to_string().len();
+    bins.iter().enumerate().map(|(i, &count)| {
+        let bar = (count as f64 * width as f64 / most as f64).round() as usize;
+        format!("{:>lw$} |{:<w$} {}\n", i, "#".repeat(bar), count, lw = label_width, w = width)
+    }).collect()
+}
+
+/// The outcome of a chi-squared goodness-of-fit test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChiSquared {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+}
+
+/// Tests observed counts against expected ones, pooling the tail bins
+/// expected to hold fewer than five
+pub fn chi_squared(observed: &[u64], expected: &[f64]) -> ChiSquared {
+    assert_eq!(observed.len(), expected.len(), "Need an expected count for each bin");
+    let mut pooled: Vec<(f64, f64)> = Vec::new();
+    let mut pending = (0.0, 0.0);
+    for (&o, &e) in observed.iter().zip(expected) {
+        pending = (pending.0 + o as f64, pending.1 + e);
+        if pending.1 >= 5.0 {
+            pooled.push(pending);
+            pending = (0.0, 0.0);
+        }
+    }
+    // Whatever is left of the right tail joins the last group
+    match pooled.last_mut() {
+        Some(last) => {
+            last.0 += pending.0;
+            last.1 += pending.1;
+        }
+        None if pending.1 > 0.0 => pooled.push(pending),
+        None => {}
+    }
+    let statistic = pooled.iter().map(|&(o, e)| (o - e) * (o - e) / e).sum();
+    let degrees_of_freedom = pooled.len().saturating_sub(1);
+    ChiSquared { statistic, degrees_of_freedom, p_value: chi_squared_survival(statistic, degrees_of_freedom) }
+}
+
+/// Compares a simulation against the exact binomial distribution
+///
+/// # Example
+/// ```
+/// use pprust::galton::{simulate, test_binomial};
+///
+/// let fit = test_binomial(&simulate(12, 10_000, 3));
+/// assert!(fit.p_value > 0.001);
+/// ```
+pub fn test_binomial(bins: &[u64]) -> ChiSquared {
+    let rows = bins.len() - 1;
+    chi_squared(bins, &expected(rows, bins.iter().sum()))
+}
+
+/// Probability that a chi-squared variable exceeds `x`
+fn chi_squared_survival(x: f64, degrees_of_freedom: usize) -> f64 {
+    if degrees_of_freedom == 0 {
+        return 1.0;
+    }
+    upper_incomplete_gamma(degrees_of_freedom as f64 / 2.0, x / 2.0)
+}
+
+/// Natural logarithm of the gamma function, by Lanczos' approximation
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series = COEFFICIENTS.iter().enumerate()
+        .fold(1.000000000190015, |s, (i, c)| s + c / (x + 1.0 + i as f64));
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// The regularized upper incomplete gamma function Q(a, x), by its series
+/// for small `x` and its continued fraction otherwise
+fn upper_incomplete_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        let (mut term, mut sum, mut n) = (1.0 / a, 1.0 / a, a);
+        while term.abs() > sum.abs() * 1e-15 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+        }
+        1.0 - sum * log_prefix.exp()
+    } else {
+        // Modified Lentz evaluation of the continued fraction
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        log_prefix.exp() * h
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_fit_the_binomial_distribution() {
+        for &(rows, seed) in &[(1, 1), (6, 2), (20, 3), (40, 4)] {
+            let bins = simulate(rows, 20_000, seed);
+            let fit = test_binomial(&bins);
+            assert!(fit.p_value > 0.001, "{} rows: {:?}", rows, fit);
+        }
+    }
+
+    #[test]
+    fn should_reject_a_biased_board() {
+        // Every ball goes left at the first pin
+        let mut bins = simulate(9, 20_000, 5);
+        bins.insert(0, 0);
+        let fit = test_binomial(&bins);
+        assert!(fit.p_value < 1e-10, "{:?}", fit);
+    }
+
+    #[test]
+    fn should_pool_sparse_tails() {
+        let fit = chi_squared(&[0, 3, 50, 40, 6, 1], &[1.0, 4.0, 45.0, 45.0, 4.0, 1.0]);
+        assert_eq!(3, fit.degrees_of_freedom);
+        let expected_statistic = 2.0 * 2.0 / 5.0 + 25.0 / 45.0 + 25.0 / 45.0 + 2.0 * 2.0 / 5.0;
+        assert!((fit.statistic - expected_statistic).abs() < 1e-12);
+    }
+
+    #[test]
+    fn should_compute_chi_squared_p_values() {
+        // With two degrees of freedom the survival function is exp(-x / 2)
+        for &x in &[0.5, 2.0, 7.0, 30.0] {
+            assert!((chi_squared_survival(x, 2) - (-x / 2.0f64).exp()).abs() < 1e-10);
+        }
+        assert!((chi_squared_survival(3.841459, 1) - 0.05).abs() < 1e-6);
+        assert!((chi_squared_survival(18.307038, 10) - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_draw_histogram() {
+        let drawn = histogram(&[1, 5, 10, 5, 1, 0, 0, 0, 0, 0, 0], 10);
+        assert!(drawn.starts_with(" 0 |#          1\n 1 |#####      5\n 2 |########## 10\n"), "{}", drawn);
+        assert!(drawn.ends_with("10 |           0\n"));
+    }
+}
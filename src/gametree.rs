@@ -0,0 +1,312 @@
+//!
+//! # Game Tree Search
+//!
+//! Two-player, zero-sum games of perfect information can be played by
+//! searching the tree of possible moves. Minimax assumes both players
+//! play their best: each picks the move whose outcome is best for them
+//! and worst for the opponent. Written in negamax form, a score is always
+//! from the point of view of the player to move, and the score of a
+//! position is the negated best score of the positions after each move.
+//!
+//! Alpha-beta pruning gets the same answer while skipping moves that
+//! cannot change it, keeping the window of scores still worth looking at.
+//!
+//! Wins are worth `WIN` minus the number of moves needed to reach them,
+//! so the search prefers quick wins and slow losses.
+//!
+//! Deeper searches can keep a transposition table: positions reached by
+//! different move orders are searched once, and the best move found for a
+//! position is tried first the next time. Iterative deepening searches
+//! one move deeper at a time, which fills the table with good first moves
+//! and makes it easy to stop at any depth.
+//!
+
+/// Score of a won position
+pub const WIN: i32 = 1_000_000;
+
+/// A two-player, zero-sum game
+pub trait Game: Clone {
+    type Move: Copy + PartialEq;
+
+    /// The legal moves of the player to move; empty when the game is over
+    fn moves(&self) -> Vec<Self::Move>;
+
+    /// The position after the player to move makes a legal move
+    fn play(&self, m: Self::Move) -> Self;
+
+    /// Whether the game has ended
+    fn is_over(&self) -> bool;
+
+    /// The value of the position to the player to move: `-WIN` if they
+    /// have lost, `0` for a draw, and an estimate strictly between
+    /// `-WIN / 2` and `WIN / 2` for unfinished games
+    fn evaluate(&self) -> i32;
+}
+
+/// The score of a position and the best move found, if any
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Choice<M> {
+    pub score: i32,
+    pub best: Option<M>,
+}
+
+/// Moves a won or lost score one move closer to zero
+pub fn decay(score: i32) -> i32 {
+    if score > WIN / 2 {
+        score - 1
+    } else if score < -WIN / 2 {
+        score + 1
+    } else {
+        score
+    }
+}
+
+/// The score a child must reach for its parent to reach `score`, the
+/// inverse of `decay` for won and lost scores
+fn widen(score: i32) -> i32 {
+    if score > WIN / 2 {
+        score + 1
+    } else if score < -WIN / 2 {
+        score - 1
+    } else {
+        score
+    }
+}
+
+/// Searches `depth` moves ahead by plain minimax
+pub fn minimax<G: Game>(game: &G, depth: u32) -> Choice<G::Move> {
+    if depth == 0 || game.is_over() {
+        return Choice { score: game.evaluate(), best: None };
+    }
+    let mut choice = Choice { score: i32::MIN, best: None };
+    for m in game.moves() {
+        let score = decay(-minimax(&game.play(m), depth - 1).score);
+        if score > choice.score {
+            choice = Choice { score, best: Some(m) };
+        }
+    }
+    choice
+}
+
+/// Searches `depth` moves ahead by minimax with alpha-beta pruning; gives
+/// the same score as `minimax`
+///
+/// # Example
+/// ```
+/// use pprust::gametree::{alphabeta, minimax};
+/// use pprust::tictactoe::Board;
+///
+/// let board = Board::new().play(4).unwrap();
+/// assert_eq!(minimax(&board, 3).score, alphabeta(&board, 3).score);
+/// ```
+pub fn alphabeta<G: Game>(game: &G, depth: u32) -> Choice<G::Move> {
+    alphabeta_window(game, depth, -WIN - 1, WIN + 1)
+}
+
+/// Alpha-beta search within the window `(alpha, beta)`: scores at or
+/// below `alpha` or at or above `beta` are only bounds
+pub fn alphabeta_window<G: Game>(game: &G, depth: u32, mut alpha: i32, beta: i32) -> Choice<G::Move> {
+    if depth == 0 || game.is_over() {
+        return Choice { score: game.evaluate(), best: None };
+    }
+    let mut choice = Choice { score: i32::MIN, best: None };
+    for m in game.moves() {
+        let score = decay(-alphabeta_window(&game.play(m), depth - 1, -widen(beta), -widen(alpha)).score);
+        if score > choice.score {
+            choice = Choice { score, best: Some(m) };
+        }
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    choice
+}
+
+/// A game whose positions have a hash key for a transposition table
+pub trait Hashed: Game {
+    /// A key that identifies the position
+    fn key(&self) -> u64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry<M> {
+    key: u64,
+    depth: u32,
+    score: i32,
+    bound: Bound,
+    best: Option<M>,
+}
+
+/// A fixed-size transposition table; a new entry replaces whatever was
+/// in its slot
+pub struct Table<M> {
+    entries: Vec<Option<Entry<M>>>,
+}
+
+impl<M: Copy> Table<M> {
+    /// Creates a table with `size` slots
+    pub fn new(size: usize) -> Table<M> {
+        Table { entries: vec![None; size.max(1)] }
+    }
+
+    /// Forgets every entry
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = None;
+        }
+    }
+
+    fn get(&self, key: u64) -> Option<Entry<M>> {
+        self.entries[(key % self.entries.len() as u64) as usize].filter(|e| e.key == key)
+    }
+
+    fn put(&mut self, entry: Entry<M>) {
+        let slot = (entry.key % self.entries.len() as u64) as usize;
+        self.entries[slot] = Some(entry);
+    }
+}
+
+/// Alpha-beta search within the window `(alpha, beta)` that stores
+/// results in, and reuses them from, a transposition table
+pub fn alphabeta_table<G: Hashed>(game: &G, depth: u32, mut alpha: i32, mut beta: i32, table: &mut Table<G::Move>) -> Choice<G::Move> {
+    if depth == 0 || game.is_over() {
+        return Choice { score: game.evaluate(), best: None };
+    }
+    let key = game.key();
+    let original_alpha = alpha;
+    let mut moves = game.moves();
+    if let Some(entry) = table.get(key) {
+        if entry.depth >= depth {
+            match entry.bound {
+                Bound::Exact => return Choice { score: entry.score, best: entry.best },
+                Bound::Lower => alpha = alpha.max(entry.score),
+                Bound::Upper => beta = beta.min(entry.score),
+            }
+            if alpha >= beta {
+                return Choice { score: entry.score, best: entry.best };
+            }
+        }
+        if let Some(i) = entry.best.and_then(|best| moves.iter().position(|&m| m == best)) {
+            let best = moves.remove(i);
+            moves.insert(0, best);
+        }
+    }
+    let mut choice = Choice { score: i32::MIN, best: None };
+    for m in moves {
+        let score = decay(-alphabeta_table(&game.play(m), depth - 1, -widen(beta), -widen(alpha), table).score);
+        if score > choice.score {
+            choice = Choice { score, best: Some(m) };
+        }
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    let bound = if choice.score <= original_alpha {
+        Bound::Upper
+    } else if choice.score >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
+    table.put(Entry { key, depth, score: choice.score, bound, best: choice.best });
+    choice
+}
+
+/// Searches one move deeper at a time up to `max_depth`, stopping early
+/// once the game is known to be won or lost
+pub fn iterative_deepening<G: Hashed>(game: &G, max_depth: u32, table: &mut Table<G::Move>) -> Choice<G::Move> {
+    let mut choice = Choice { score: game.evaluate(), best: None };
+    for depth in 1..=max_depth {
+        choice = alphabeta_table(game, depth, -WIN - 1, WIN + 1, table);
+        if choice.score.abs() > WIN / 2 {
+            break;
+        }
+    }
+    choice
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Players take one to three sticks; whoever takes the last one wins
+    #[derive(Clone)]
+    struct Nim(u32);
+
+    impl Game for Nim {
+        type Move = u32;
+
+        fn moves(&self) -> Vec<u32> {
+            (1..=3.min(self.0)).collect()
+        }
+
+        fn play(&self, m: u32) -> Nim {
+            Nim(self.0 - m)
+        }
+
+        fn is_over(&self) -> bool {
+            self.0 == 0
+        }
+
+        fn evaluate(&self) -> i32 {
+            if self.0 == 0 { -WIN } else { 0 }
+        }
+    }
+
+    #[test]
+    fn should_find_winning_nim_moves() {
+        for sticks in 1..15 {
+            let choice = minimax(&Nim(sticks), 20);
+            if sticks % 4 == 0 {
+                assert!(choice.score < 0, "{} sticks is a loss", sticks);
+            } else {
+                assert!(choice.score > 0, "{} sticks is a win", sticks);
+                assert_eq!(Some(sticks % 4), choice.best);
+            }
+        }
+    }
+
+    #[test]
+    fn should_agree_with_minimax() {
+        for sticks in 1..15 {
+            for depth in 0..8 {
+                assert_eq!(minimax(&Nim(sticks), depth).score, alphabeta(&Nim(sticks), depth).score);
+            }
+        }
+    }
+
+    impl Hashed for Nim {
+        fn key(&self) -> u64 {
+            self.0 as u64
+        }
+    }
+
+    #[test]
+    fn should_agree_with_minimax_using_table() {
+        let mut table = Table::new(7);
+        for sticks in 1..30 {
+            table.clear();
+            let choice = iterative_deepening(&Nim(sticks), 40, &mut table);
+            let plain = alphabeta(&Nim(sticks), 40);
+            assert_eq!(plain.score, choice.score, "{} sticks", sticks);
+            if sticks % 4 != 0 {
+                assert_eq!(Some(sticks % 4), choice.best);
+            }
+        }
+    }
+
+    #[test]
+    fn should_prefer_quick_wins() {
+        assert_eq!(WIN - 1, minimax(&Nim(3), 10).score);
+        assert_eq!(-WIN + 2, minimax(&Nim(4), 10).score);
+    }
+}
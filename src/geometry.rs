@@ -0,0 +1,588 @@
+//!
+//! # Problem description:
+//! #
+//! # Convex Hull
+//!
+//! The convex hull of a set of points is the smallest convex polygon that
+//! contains them all, like a rubber band stretched around nails in a
+//! board. Both algorithms here rest on one test: whether three points
+//! turn left, turn right or lie on a line, given by the sign of a cross
+//! product. With integer coordinates the cross product is computed
+//! exactly in 128 bits, so nearly collinear points never fool it the way
+//! rounding can with floating point.
+//!
+//! Gift wrapping, or Jarvis' march, starts at an extreme point and keeps
+//! choosing the point that leaves all others to its left, in O(nh) for a
+//! hull of h points. Andrew's monotone chain sorts the points and builds
+//! the lower and upper halves of the hull with a stack, in O(n log n).
+//!
+//! # Closest Pair of Points
+//!
+//! The closest of n points can be found without comparing all pairs.
+//! Divide and conquer splits the points by a vertical line, solves each
+//! half, and then only has to look at a strip around the line as wide as
+//! the best distance so far; sorted by y, each point of the strip needs
+//! to be compared with just a few after it. A sweep line instead scans
+//! the points left to right, keeping those closer than the best distance
+//! in x in a tree ordered by y, and looks for neighbours in a window of
+//! that tree. Both take O(n log n).
+//!
+//! # Segments and Polygons
+//!
+//! Two segments cross when each one's ends lie on opposite sides of the
+//! other's line; the care is in the special cases, where an end lies on
+//! the other segment or both lie on one line and may overlap. A point is
+//! inside a polygon when a ray from it crosses the edges an odd number
+//! of times; counting an edge only when it spans the ray's height from
+//! strictly below on one side takes care of rays through vertices.
+//!
+
+use std::collections::BTreeSet;
+
+/// A point with integer coordinates. Geometric tests are exact while
+/// coordinates stay below 2^62 in absolute value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+}
+
+/// Which way the path `a`, `b`, `c` turns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+    Collinear,
+}
+
+/// The cross product of `a - o` and `b - o`: positive when `o`, `a`, `b`
+/// turn counter-clockwise
+pub fn cross(o: Point, a: Point, b: Point) -> i128 {
+    let (ax, ay) = (a.x as i128 - o.x as i128, a.y as i128 - o.y as i128);
+    let (bx, by) = (b.x as i128 - o.x as i128, b.y as i128 - o.y as i128);
+    ax * by - ay * bx
+}
+
+/// Which way the path `a`, `b`, `c` turns
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{orientation, Orientation, Point};
+///
+/// let (a, b) = (Point::new(0, 0), Point::new(4, 4));
+/// assert_eq!(Orientation::CounterClockwise, orientation(a, b, Point::new(1, 2)));
+/// assert_eq!(Orientation::Collinear, orientation(a, b, Point::new(7, 7)));
+/// ```
+pub fn orientation(a: Point, b: Point, c: Point) -> Orientation {
+    match cross(a, b, c) {
+        0 => Orientation::Collinear,
+        d if d > 0 => Orientation::CounterClockwise,
+        _ => Orientation::Clockwise,
+    }
+}
+
+fn distance2(a: Point, b: Point) -> i128 {
+    let (dx, dy) = (a.x as i128 - b.x as i128, a.y as i128 - b.y as i128);
+    dx * dx + dy * dy
+}
+
+/// The convex hull, by the monotone chain algorithm. See
+/// [`monotone_chain`](fn.monotone_chain.html).
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    monotone_chain(points)
+}
+
+/// The vertices of the convex hull in counter-clockwise order, starting
+/// from the leftmost, lowest point. Points on the edges are left out.
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{monotone_chain, Point};
+///
+/// let points: Vec<Point> = [(0, 0), (2, 1), (4, 0), (4, 4), (2, 2), (0, 4), (0, 2)]
+///     .iter().map(|&(x, y)| Point::new(x, y)).collect();
+/// let hull: Vec<(i64, i64)> = monotone_chain(&points).iter().map(|p| (p.x, p.y)).collect();
+/// assert_eq!(vec![(0, 0), (4, 0), (4, 4), (0, 4)], hull);
+/// ```
+pub fn monotone_chain(points: &[Point]) -> Vec<Point> {
+    let mut sorted = points.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+    let mut hull: Vec<Point> = Vec::with_capacity(2 * sorted.len());
+    // The lower hull left to right
+    for &p in &sorted {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    // Then the upper hull back, never popping the rightmost point
+    let lower = hull.len() + 1;
+    for &p in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    // The leftmost point closes the upper hull
+    hull.pop();
+    hull
+}
+
+/// The same hull as [`monotone_chain`](fn.monotone_chain.html), found by
+/// gift wrapping
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{gift_wrapping, monotone_chain, Point};
+///
+/// let points: Vec<Point> = (0..20).map(|i| Point::new(i * 7 % 11, i * i % 13)).collect();
+/// assert_eq!(monotone_chain(&points), gift_wrapping(&points));
+/// ```
+pub fn gift_wrapping(points: &[Point]) -> Vec<Point> {
+    let start = match points.iter().min() {
+        Some(&start) => start,
+        None => return Vec::new(),
+    };
+    let mut hull = Vec::new();
+    let mut p = start;
+    loop {
+        hull.push(p);
+        let mut q = match points.iter().find(|&&q| q != p) {
+            Some(&q) => q,
+            None => break,
+        };
+        for &r in points {
+            let turn = cross(p, q, r);
+            // Keep every point to the left, skipping those on the edge
+            if turn < 0 || (turn == 0 && distance2(p, r) > distance2(p, q)) {
+                q = r;
+            }
+        }
+        p = q;
+        if p == start {
+            break;
+        }
+    }
+    hull
+}
+
+/// Twice the signed area of a polygon, positive when its vertices go
+/// counter-clockwise, exact by the shoelace formula
+pub fn twice_area(polygon: &[Point]) -> i128 {
+    let n = polygon.len();
+    (0..n).map(|i| cross(Point::new(0, 0), polygon[i], polygon[(i + 1) % n])).sum()
+}
+
+/// The area enclosed by the convex hull of `points`
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{hull_area, Point};
+///
+/// let triangle = [Point::new(0, 0), Point::new(3, 0), Point::new(0, 3), Point::new(1, 1)];
+/// assert_eq!(4.5, hull_area(&triangle));
+/// ```
+pub fn hull_area(points: &[Point]) -> f64 {
+    twice_area(&convex_hull(points)) as f64 / 2.0
+}
+
+/// Whether `p` lies on the closed segment from `a` to `b`
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{on_segment, Point};
+///
+/// let (a, b) = (Point::new(0, 0), Point::new(4, 2));
+/// assert!(on_segment(a, b, Point::new(2, 1)));
+/// assert!(on_segment(a, b, b));
+/// assert!(!on_segment(a, b, Point::new(6, 3)));
+/// ```
+pub fn on_segment(a: Point, b: Point, p: Point) -> bool {
+    cross(a, b, p) == 0 && a.x.min(b.x) <= p.x && p.x <= a.x.max(b.x) && a.y.min(b.y) <= p.y && p.y <= a.y.max(b.y)
+}
+
+/// Whether the closed segments `a`-`b` and `c`-`d` have a point in
+/// common, touching or overlapping included
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{segments_intersect, Point};
+///
+/// let p = |x, y| Point::new(x, y);
+/// assert!(segments_intersect(p(0, 0), p(4, 4), p(0, 4), p(4, 0)));
+/// assert!(segments_intersect(p(0, 0), p(4, 4), p(2, 2), p(6, 0)));
+/// assert!(segments_intersect(p(0, 0), p(2, 2), p(1, 1), p(3, 3)));
+/// assert!(!segments_intersect(p(0, 0), p(1, 1), p(2, 2), p(3, 3)));
+/// ```
+pub fn segments_intersect(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    let proper = |x, y| x != y && x != Orientation::Collinear && y != Orientation::Collinear;
+    (proper(o1, o2) && proper(o3, o4))
+        || on_segment(a, b, c)
+        || on_segment(a, b, d)
+        || on_segment(c, d, a)
+        || on_segment(c, d, b)
+}
+
+/// Where a point lies with respect to a polygon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Outside,
+    Boundary,
+}
+
+/// Where `p` lies with respect to `polygon`, given by its vertices in
+/// either order. Self-intersecting polygons follow the even-odd rule.
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{point_in_polygon, Location, Point};
+///
+/// // An L shape
+/// let polygon: Vec<Point> = [(0, 0), (4, 0), (4, 2), (2, 2), (2, 4), (0, 4)]
+///     .iter().map(|&(x, y)| Point::new(x, y)).collect();
+/// assert_eq!(Location::Inside, point_in_polygon(&polygon, Point::new(1, 3)));
+/// assert_eq!(Location::Outside, point_in_polygon(&polygon, Point::new(3, 3)));
+/// assert_eq!(Location::Boundary, point_in_polygon(&polygon, Point::new(3, 2)));
+/// ```
+pub fn point_in_polygon(polygon: &[Point], p: Point) -> Location {
+    let n = polygon.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        if on_segment(a, b, p) {
+            return Location::Boundary;
+        }
+        // Edges spanning p's height, lower end included, upper excluded
+        if (a.y <= p.y) != (b.y <= p.y) {
+            let turn = cross(a, b, p);
+            if (b.y > a.y && turn > 0) || (b.y < a.y && turn < 0) {
+                inside = !inside;
+            }
+        }
+    }
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+/// The closest pair of points, with the square of their distance
+type Closest = (i128, Point, Point);
+
+fn closer(best: &mut Closest, a: Point, b: Point) {
+    let d = distance2(a, b);
+    if d < best.0 {
+        *best = (d, a, b);
+    }
+}
+
+/// Updates `best` with the closest pair of `points`, sorted by x, and
+/// returns them sorted by y
+fn closest_recursive(points: &[Point], best: &mut Closest) -> Vec<Point> {
+    let by_y = |p: &Point| (p.y, p.x);
+    if points.len() <= 3 {
+        for (i, &a) in points.iter().enumerate() {
+            for &b in &points[i + 1..] {
+                closer(best, a, b);
+            }
+        }
+        let mut sorted = points.to_vec();
+        sorted.sort_by_key(by_y);
+        return sorted;
+    }
+    let mid = points.len() / 2;
+    let mid_x = points[mid].x as i128;
+    let left = closest_recursive(&points[..mid], best);
+    let right = closest_recursive(&points[mid..], best);
+    let mut sorted = Vec::with_capacity(points.len());
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() || j < right.len() {
+        if j == right.len() || (i < left.len() && by_y(&left[i]) <= by_y(&right[j])) {
+            sorted.push(left[i]);
+            i += 1;
+        } else {
+            sorted.push(right[j]);
+            j += 1;
+        }
+    }
+    let square = |d: i128| d * d;
+    let strip: Vec<Point> = sorted.iter().cloned().filter(|p| square(p.x as i128 - mid_x) < best.0).collect();
+    for (i, &a) in strip.iter().enumerate() {
+        for &b in &strip[i + 1..] {
+            if square(b.y as i128 - a.y as i128) >= best.0 {
+                break;
+            }
+            closer(best, a, b);
+        }
+    }
+    sorted
+}
+
+/// The two closest points, by divide and conquer; `None` for fewer than
+/// two points
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{closest_pair, Point};
+///
+/// let points = [Point::new(0, 0), Point::new(10, 3), Point::new(5, 5), Point::new(9, 1), Point::new(2, 8)];
+/// assert_eq!(Some((Point::new(9, 1), Point::new(10, 3))), closest_pair(&points));
+/// ```
+pub fn closest_pair(points: &[Point]) -> Option<(Point, Point)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let mut sorted = points.to_vec();
+    sorted.sort();
+    let mut best = (i128::MAX, sorted[0], sorted[1]);
+    closest_recursive(&sorted, &mut best);
+    Some((best.1, best.2))
+}
+
+/// The two closest points, by a sweep line; `None` for fewer than two
+/// points
+///
+/// # Example
+/// ```
+/// use pprust::geometry::{closest_pair_sweep, Point};
+///
+/// let points = [Point::new(0, 0), Point::new(10, 3), Point::new(5, 5), Point::new(9, 1), Point::new(2, 8)];
+/// assert_eq!(Some((Point::new(9, 1), Point::new(10, 3))), closest_pair_sweep(&points));
+/// ```
+pub fn closest_pair_sweep(points: &[Point]) -> Option<(Point, Point)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let mut sorted = points.to_vec();
+    sorted.sort();
+    let mut best = (i128::MAX, sorted[0], sorted[1]);
+    // Points within the best distance in x, by y
+    let mut active: BTreeSet<(i64, i64)> = BTreeSet::new();
+    let mut left = 0;
+    for &p in &sorted {
+        while left < sorted.len() {
+            let dx = p.x as i128 - sorted[left].x as i128;
+            if dx * dx < best.0 {
+                break;
+            }
+            active.remove(&(sorted[left].y, sorted[left].x));
+            left += 1;
+        }
+        // A window a little wider than the best distance; candidates are
+        // then compared exactly
+        let reach = if best.0 == i128::MAX { i64::MAX } else { ((best.0 as f64).sqrt() as i64).saturating_add(1) };
+        let window = (p.y.saturating_sub(reach), i64::MIN)..=(p.y.saturating_add(reach), i64::MAX);
+        let near: Vec<Point> = active.range(window).map(|&(y, x)| Point::new(x, y)).collect();
+        for q in near {
+            closer(&mut best, q, p);
+        }
+        active.insert((p.y, p.x));
+    }
+    Some((best.1, best.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn random_points(rng: &mut Rng, n: usize, range: u64) -> Vec<Point> {
+        (0..n).map(|_| Point::new(rng.gen_range(0, range) as i64, rng.gen_range(0, range) as i64)).collect()
+    }
+
+    fn check_hull(points: &[Point], hull: &[Point]) {
+        let h = hull.len();
+        if h < 3 {
+            return;
+        }
+        for i in 0..h {
+            let (a, b, c) = (hull[i], hull[(i + 1) % h], hull[(i + 2) % h]);
+            assert!(cross(a, b, c) > 0, "Not strictly convex at {:?}", b);
+            for &p in points {
+                assert!(cross(a, b, p) >= 0, "{:?} is outside", p);
+            }
+        }
+    }
+
+    #[test]
+    fn should_agree_on_random_points() {
+        let mut rng = Rng::new(254);
+        for round in 0..300 {
+            let n = rng.gen_range(0, 60) as usize;
+            // Small ranges give many duplicate and collinear points
+            let points = random_points(&mut rng, n, if round % 2 == 0 { 5 } else { 1000 });
+            let hull = monotone_chain(&points);
+            assert_eq!(hull, gift_wrapping(&points), "{:?}", points);
+            check_hull(&points, &hull);
+            assert!(hull.iter().all(|p| points.contains(p)));
+        }
+    }
+
+    #[test]
+    fn should_handle_degenerate_inputs() {
+        let p = |x, y| Point::new(x, y);
+        assert!(convex_hull(&[]).is_empty());
+        assert_eq!(vec![p(1, 1)], convex_hull(&[p(1, 1), p(1, 1), p(1, 1)]));
+        assert_eq!(vec![p(1, 1)], gift_wrapping(&[p(1, 1), p(1, 1)]));
+        let line = [p(2, 2), p(0, 0), p(3, 3), p(1, 1)];
+        assert_eq!(vec![p(0, 0), p(3, 3)], convex_hull(&line));
+        assert_eq!(vec![p(0, 0), p(3, 3)], gift_wrapping(&line));
+        assert_eq!(0.0, hull_area(&line));
+    }
+
+    #[test]
+    fn should_decide_nearly_collinear_points_exactly() {
+        // Floating point cross products round these to zero
+        let big = 1 << 60;
+        let (a, b) = (Point::new(0, 0), Point::new(big, big + 1));
+        let c = Point::new(big - 1, big);
+        assert_eq!(Orientation::CounterClockwise, orientation(a, b, c));
+        assert_eq!(Orientation::Clockwise, orientation(a, c, b));
+        assert_eq!(3, convex_hull(&[a, b, c]).len());
+    }
+
+    #[test]
+    fn should_compute_areas() {
+        let square: Vec<Point> = [(0, 0), (10, 0), (10, 10), (0, 10)].iter().map(|&(x, y)| Point::new(x, y)).collect();
+        assert_eq!(200, twice_area(&square));
+        let reversed: Vec<Point> = square.iter().rev().cloned().collect();
+        assert_eq!(-200, twice_area(&reversed));
+        let mut rng = Rng::new(9);
+        let mut inside = random_points(&mut rng, 100, 10);
+        inside.extend_from_slice(&square);
+        assert_eq!(100.0, hull_area(&inside));
+    }
+
+    fn brute_force_closest(points: &[Point]) -> i128 {
+        let mut best = i128::MAX;
+        for (i, &a) in points.iter().enumerate() {
+            for &b in &points[i + 1..] {
+                best = best.min(distance2(a, b));
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn should_find_the_closest_pair_like_brute_force() {
+        let mut rng = Rng::new(255);
+        for round in 0..300 {
+            let n = rng.gen_range(2, 80) as usize;
+            let points = random_points(&mut rng, n, if round % 3 == 0 { 8 } else { 10000 });
+            let best = brute_force_closest(&points);
+            for pair in &[closest_pair(&points), closest_pair_sweep(&points)] {
+                let (a, b) = pair.unwrap();
+                assert_eq!(best, distance2(a, b), "{:?}", points);
+                let count = |p| points.iter().filter(|&&q| q == p).count();
+                assert!(count(a) > 0 && count(b) > 0 && (a != b || count(a) > 1));
+            }
+        }
+    }
+
+    #[test]
+    fn should_handle_few_and_extreme_points() {
+        assert_eq!(None, closest_pair(&[]));
+        assert_eq!(None, closest_pair_sweep(&[Point::new(1, 1)]));
+        let line: Vec<Point> = (0..100).map(|i| Point::new(0, i * i)).collect();
+        assert_eq!(Some((Point::new(0, 0), Point::new(0, 1))), closest_pair(&line));
+        assert_eq!(Some((Point::new(0, 0), Point::new(0, 1))), closest_pair_sweep(&line));
+        let big = 1 << 61;
+        let far = [Point::new(-big, -big), Point::new(big, big), Point::new(big, -big)];
+        let (a, b) = closest_pair(&far).unwrap();
+        assert_eq!(distance2(a, b), distance2(far[1], far[2]));
+        let (a, b) = closest_pair_sweep(&far).unwrap();
+        assert_eq!(distance2(a, b), distance2(far[1], far[2]));
+    }
+
+    /// Intersection of segments by solving for the parameters along each
+    fn parametric_intersect(a: Point, b: Point, c: Point, d: Point) -> bool {
+        let (rx, ry) = ((b.x - a.x) as i128, (b.y - a.y) as i128);
+        let (sx, sy) = ((d.x - c.x) as i128, (d.y - c.y) as i128);
+        let (qx, qy) = ((c.x - a.x) as i128, (c.y - a.y) as i128);
+        let denom = rx * sy - ry * sx;
+        let within = |num: i128, den: i128| if den > 0 { 0 <= num && num <= den } else { den <= num && num <= 0 };
+        if denom != 0 {
+            // a + t r = c + u s
+            return within(qx * sy - qy * sx, denom) && within(qx * ry - qy * rx, denom);
+        }
+        if qx * ry - qy * rx != 0 {
+            return false;
+        }
+        // Collinear: compare projections on the line, or the points when
+        // a segment is a single point
+        if rx == 0 && ry == 0 {
+            return on_segment(c, d, a);
+        }
+        let project = |p: Point| (p.x - a.x) as i128 * rx + (p.y - a.y) as i128 * ry;
+        let (t0, t1) = (project(c), project(d));
+        let length = rx * rx + ry * ry;
+        t0.min(t1) <= length && t0.max(t1) >= 0
+    }
+
+    #[test]
+    fn should_intersect_segments_like_the_parametric_solution() {
+        let mut rng = Rng::new(256);
+        for _ in 0..20000 {
+            let q = random_points(&mut rng, 4, 5);
+            let expected = parametric_intersect(q[0], q[1], q[2], q[3]);
+            assert_eq!(expected, segments_intersect(q[0], q[1], q[2], q[3]), "{:?}", q);
+            assert_eq!(expected, segments_intersect(q[2], q[3], q[1], q[0]), "{:?}", q);
+        }
+    }
+
+    #[test]
+    fn should_locate_points_in_convex_polygons() {
+        let mut rng = Rng::new(2562);
+        for _ in 0..200 {
+            let hull = convex_hull(&random_points(&mut rng, 12, 20));
+            if hull.len() < 3 {
+                continue;
+            }
+            let clockwise: Vec<Point> = hull.iter().rev().cloned().collect();
+            for p in random_points(&mut rng, 30, 22) {
+                let h = hull.len();
+                let turns: Vec<i128> = (0..h).map(|i| cross(hull[i], hull[(i + 1) % h], p)).collect();
+                let expected = if turns.iter().any(|&t| t < 0) {
+                    Location::Outside
+                } else if turns.contains(&0) {
+                    Location::Boundary
+                } else {
+                    Location::Inside
+                };
+                assert_eq!(expected, point_in_polygon(&hull, p), "{:?} in {:?}", p, hull);
+                assert_eq!(expected, point_in_polygon(&clockwise, p));
+            }
+        }
+    }
+
+    #[test]
+    fn should_count_rays_through_vertices_once() {
+        let p = |x, y| Point::new(x, y);
+        // A zigzag whose vertices lie on the ray from (0, 2)
+        let zigzag = [p(-2, 0), p(8, 0), p(8, 4), p(6, 2), p(4, 4), p(2, 2), p(-2, 4)];
+        assert_eq!(Location::Inside, point_in_polygon(&zigzag, p(0, 2)));
+        assert_eq!(Location::Inside, point_in_polygon(&zigzag, p(4, 3)));
+        assert_eq!(Location::Outside, point_in_polygon(&zigzag, p(6, 3)));
+        assert_eq!(Location::Boundary, point_in_polygon(&zigzag, p(3, 3)));
+        assert_eq!(Location::Boundary, point_in_polygon(&zigzag, p(6, 2)));
+        assert_eq!(Location::Outside, point_in_polygon(&zigzag, p(9, 2)));
+        // Degenerate polygons only have a boundary
+        assert_eq!(Location::Boundary, point_in_polygon(&[p(0, 0), p(4, 0)], p(2, 0)));
+        assert_eq!(Location::Outside, point_in_polygon(&[p(0, 0)], p(1, 1)));
+        assert_eq!(Location::Outside, point_in_polygon(&[], p(1, 1)));
+    }
+}
@@ -0,0 +1,141 @@
+//!
+//! # Problem description:
+//! #
+//! # Goldbach's Conjecture
+//!
+//! Christian Goldbach conjectured in 1742 that every even number greater
+//! than 2 is the sum of two primes: 4 = 2 + 2, 28 = 5 + 23 = 11 + 17,
+//! and so on. Nobody has proved it, but it has been checked by computer
+//! far beyond 10^18.
+//!
+//! Checking a range does not need every partition of every number: it is
+//! enough to find one, trying the small primes p in order until n - p is
+//! prime. That rarely takes long, since the smallest such p grows very
+//! slowly, so the range is sieved a block at a time with the small
+//! primes' worth of numbers below each block, and the record holders,
+//! the numbers that needed the most tries, are reported.
+//!
+
+use sieve::{primes_up_to, segment, sieve, SEGMENT_SIZE};
+
+/// The pairs of primes `(p, q)` with `p <= q` and `p + q = n`
+///
+/// # Example
+/// ```
+/// use pprust::goldbach::partitions;
+///
+/// assert_eq!(vec![(5, 23), (11, 17)], partitions(28));
+/// ```
+pub fn partitions(n: u64) -> Vec<(u64, u64)> {
+    let table = sieve(n as usize);
+    (2..=n / 2)
+        .filter(|&p| table[p as usize] && table[(n - p) as usize])
+        .map(|p| (p, n - p))
+        .collect()
+}
+
+/// The smallest primes tried when verifying a range
+const SMALL_PRIME_LIMIT: usize = 100_000;
+
+/// The outcome of verifying the conjecture over a range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    /// Even numbers checked
+    pub checked: u64,
+    /// The first even number found without a partition, if any
+    pub counterexample: Option<u64>,
+    /// The number that needed the most primes tried before a partition
+    /// turned up
+    pub hardest: u64,
+    /// The smallest prime in a partition of `hardest`
+    pub smallest_prime: u64,
+    /// How many primes were tried for `hardest`, the last one included
+    pub tries: usize,
+}
+
+/// Checks that every even number in `low..=high`, from 4 up, is the sum
+/// of two primes
+///
+/// # Example
+/// ```
+/// use pprust::goldbach::verify;
+///
+/// let report = verify(4, 100);
+/// assert_eq!(None, report.counterexample);
+/// assert_eq!((98, 19), (report.hardest, report.smallest_prime));
+/// ```
+pub fn verify(low: u64, high: u64) -> Verification {
+    let low = low.max(4);
+    let low = low + low % 2;
+    let small = primes_up_to(SMALL_PRIME_LIMIT.min(high as usize / 2));
+    let span = small.last().cloned().unwrap_or(0);
+    let base = primes_up_to((high as f64).sqrt() as usize + 1);
+    let mut report = Verification { checked: 0, counterexample: None, hardest: 0, smallest_prime: 0, tries: 0 };
+    let mut block_low = low;
+    while block_low <= high {
+        let block_high = high.min(block_low + SEGMENT_SIZE as u64 - 1);
+        // Covers n - p for every n in the block and every small p
+        let window_low = block_low.saturating_sub(span);
+        let table = segment(window_low, block_high + 1, &base);
+        let mut n = block_low;
+        while n <= block_high {
+            report.checked += 1;
+            let found = small.iter().take_while(|&&p| p <= n / 2)
+                .position(|&p| table[(n - p - window_low) as usize]);
+            match found {
+                Some(i) if i + 1 > report.tries => {
+                    report.hardest = n;
+                    report.smallest_prime = small[i];
+                    report.tries = i + 1;
+                }
+                Some(_) => {}
+                // Beyond the small primes, check everything
+                None => if partitions(n).is_empty() {
+                    report.counterexample = Some(n);
+                    return report;
+                },
+            }
+            n += 2;
+        }
+        block_low = block_high + 1 + (block_high + 1) % 2;
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_list_partitions() {
+        assert_eq!(vec![(2, 2)], partitions(4));
+        assert_eq!(vec![(3, 7), (5, 5)], partitions(10));
+        assert_eq!(6, partitions(100).len());
+        for n in (4..2000).step_by(2) {
+            assert!(!partitions(n).is_empty(), "{}", n);
+        }
+    }
+
+    #[test]
+    fn should_verify_ranges() {
+        let report = verify(0, 100_000);
+        assert_eq!(None, report.counterexample);
+        assert_eq!(49_999, report.checked);
+        assert_eq!((63274, 293, 62), (report.hardest, report.smallest_prime, report.tries));
+    }
+
+    #[test]
+    fn should_verify_ranges_far_from_zero() {
+        let report = verify(1_000_000_001, 1_000_200_000);
+        assert_eq!(None, report.counterexample);
+        assert_eq!(100_000, report.checked);
+        assert_eq!(1_000_000_002, verify(1_000_000_001, 1_000_000_002).hardest);
+        let p = report.smallest_prime;
+        assert!(partitions_contain(report.hardest, p));
+    }
+
+    fn partitions_contain(n: u64, p: u64) -> bool {
+        let is_prime = |m: u64| m >= 2 && (2..).take_while(|d| d * d <= m).all(|d| !m.is_multiple_of(d));
+        is_prime(p) && is_prime(n - p)
+    }
+}
@@ -0,0 +1,134 @@
+//!
+//! # Problem description:
+//! #
+//! # Gray Code
+//!
+//! A Gray code lists all the n-bit numbers so that consecutive numbers
+//! differ in exactly one bit. The binary-reflected Gray code of *n* is
+//! simply *n* xor *n* shifted right by one; decoding takes the xor of
+//! all the right shifts of the code.
+//!
+//! The bit that flips at step *i* is the lowest set bit of *i*, which is
+//! also the disk moved at step *i* of the optimal solution to the Towers
+//! of Hanoi; the tests check that correspondence.
+//!
+
+/// Returns the binary-reflected Gray code of `n`
+///
+/// # Example
+/// ```
+/// use pprust::graycode::encode;
+///
+/// assert_eq!(0b110, encode(4));
+/// ```
+pub fn encode(n: u64) -> u64 {
+    n ^ (n >> 1)
+}
+
+/// Returns the number whose Gray code is `code`
+///
+/// # Example
+/// ```
+/// use pprust::graycode::decode;
+///
+/// assert_eq!(4, decode(0b110));
+/// ```
+pub fn decode(code: u64) -> u64 {
+    let mut n = code;
+    let mut shift = 1;
+    while shift < 64 {
+        n ^= n >> shift;
+        shift *= 2;
+    }
+    n
+}
+
+/// Iterator over the Gray code sequence of a given width
+pub struct GrayCodes {
+    next: u64,
+    end: u64,
+}
+
+/// Returns an iterator over the 2^n_bits Gray codes in sequence order.
+/// `n_bits` must be at most 63.
+///
+/// # Example
+/// ```
+/// use pprust::graycode::iter;
+///
+/// let codes: Vec<u64> = iter(2).collect();
+/// assert_eq!(vec![0b00, 0b01, 0b11, 0b10], codes);
+/// ```
+pub fn iter(n_bits: usize) -> GrayCodes {
+    assert!(n_bits < 64, "Gray code iterator supports at most 63 bits");
+    GrayCodes { next: 0, end: 1 << n_bits }
+}
+
+impl Iterator for GrayCodes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(encode(self.next - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.end - self.next) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The disks moved, smallest being 0, by the optimal Hanoi solution
+    fn hanoi_moves(disks: u32, moves: &mut Vec<u32>) {
+        if disks == 0 {
+            return;
+        }
+        hanoi_moves(disks - 1, moves);
+        moves.push(disks - 1);
+        hanoi_moves(disks - 1, moves);
+    }
+
+    #[test]
+    fn should_decode_what_was_encoded() {
+        for n in (0..10_000).chain(u64::MAX - 10..=u64::MAX) {
+            assert_eq!(n, decode(encode(n)));
+        }
+    }
+
+    #[test]
+    fn should_change_one_bit_at_a_time() {
+        let codes: Vec<u64> = iter(10).collect();
+        assert_eq!(1024, codes.len());
+        assert!(codes.windows(2).all(|w| (w[0] ^ w[1]).count_ones() == 1));
+        assert_eq!(1, (codes[0] ^ codes[1023]).count_ones(), "The sequence should be cyclic");
+    }
+
+    #[test]
+    fn should_visit_every_code_once() {
+        let mut codes: Vec<u64> = iter(8).collect();
+        codes.sort();
+        assert_eq!((0..256).collect::<Vec<u64>>(), codes);
+    }
+
+    #[test]
+    fn should_handle_zero_bits() {
+        assert_eq!(vec![0], iter(0).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn should_flip_the_bit_of_the_disk_moved_in_hanoi() {
+        let disks = 6;
+        let mut moves = Vec::new();
+        hanoi_moves(disks, &mut moves);
+        let codes: Vec<u64> = iter(disks as usize).collect();
+        let flipped: Vec<u32> = codes.windows(2).map(|w| (w[0] ^ w[1]).trailing_zeros()).collect();
+        assert_eq!(moves, flipped);
+    }
+}
@@ -0,0 +1,292 @@
+//!
+//! # Problem description:
+//! #
+//! # Hangman
+//!
+//! One player thinks of a word and shows how many letters it has. The
+//! other guesses letters; every occurrence of a right letter is revealed,
+//! and every wrong one adds a stroke to the drawing of a hanged man. The
+//! guesser wins by revealing the whole word before the drawing is done,
+//! after six misses.
+//!
+//! The computer guesses by keeping the dictionary words that fit what has
+//! been revealed: the words of the right length whose letters show in the
+//! same places, given the letters guessed so far, and that have none of
+//! the letters missed. Each answer splits those words by the pattern it
+//! would reveal, and only the part with the pattern actually revealed is
+//! kept. The next guess is the letter in most of the words left.
+//!
+
+use rng::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How many misses lose the game
+pub const MAX_MISSES: usize = 6;
+
+/// How a letter not guessed yet shows in a pattern
+pub const HIDDEN: char = '_';
+
+/// The word as the guesser sees it, with the letters not guessed hidden
+///
+/// # Example
+/// ```
+/// use pprust::hangman::pattern;
+///
+/// assert_eq!("_a_a_a", pattern("banana", &['a', 'x'].iter().cloned().collect()));
+/// ```
+pub fn pattern(word: &str, guessed: &BTreeSet<char>) -> String {
+    word.chars().map(|c| if guessed.contains(&c) { c } else { HIDDEN }).collect()
+}
+
+/// What a guess did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The letter is in the word, this many times
+    Hit(usize),
+    /// The letter isn't in the word
+    Miss,
+    /// The letter was already guessed, and nothing changes
+    Repeated,
+}
+
+/// Whether the game goes on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+/// A game, from the side of the player who knows the word
+#[derive(Debug, Clone)]
+pub struct Game {
+    secret: String,
+    guessed: BTreeSet<char>,
+    misses: usize,
+    max_misses: usize,
+}
+
+impl Game {
+    /// A game for a word, lost after `max_misses` misses
+    pub fn new(secret: &str, max_misses: usize) -> Game {
+        Game { secret: secret.to_string(), guessed: BTreeSet::new(), misses: 0, max_misses }
+    }
+
+    /// Reveals a letter, if the game isn't over
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::hangman::{Game, Outcome, Status, MAX_MISSES};
+    ///
+    /// let mut game = Game::new("banana", MAX_MISSES);
+    /// assert_eq!(Outcome::Hit(3), game.guess('a'));
+    /// assert_eq!(Outcome::Miss, game.guess('e'));
+    /// assert_eq!(Outcome::Repeated, game.guess('a'));
+    /// assert_eq!(("_a_a_a".to_string(), 1), (game.pattern(), game.misses()));
+    /// game.guess('b');
+    /// game.guess('n');
+    /// assert_eq!(Status::Won, game.status());
+    /// ```
+    pub fn guess(&mut self, letter: char) -> Outcome {
+        if self.status() != Status::Playing || !self.guessed.insert(letter) {
+            return Outcome::Repeated;
+        }
+        match self.secret.chars().filter(|&c| c == letter).count() {
+            0 => {
+                self.misses += 1;
+                Outcome::Miss
+            }
+            hits => Outcome::Hit(hits),
+        }
+    }
+
+    /// The word with the letters not guessed yet hidden
+    pub fn pattern(&self) -> String {
+        pattern(&self.secret, &self.guessed)
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    pub fn misses_left(&self) -> usize {
+        self.max_misses - self.misses
+    }
+
+    /// The letters guessed so far, right or wrong, in order
+    pub fn guessed(&self) -> &BTreeSet<char> {
+        &self.guessed
+    }
+
+    pub fn status(&self) -> Status {
+        if self.secret.chars().all(|c| self.guessed.contains(&c)) {
+            Status::Won
+        } else if self.misses >= self.max_misses {
+            Status::Lost
+        } else {
+            Status::Playing
+        }
+    }
+
+    /// The word, which the guesser should only see once the game is over
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+}
+
+/// A random word of the dictionary with at least `min_length` letters,
+/// or `None` if there is none
+pub fn random_word<'a>(dictionary: &[&'a str], min_length: usize, rng: &mut Rng) -> Option<&'a str> {
+    let words: Vec<&str> = dictionary.iter().cloned().filter(|w| w.chars().count() >= min_length).collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words[rng.gen_range(0, words.len() as u64) as usize])
+    }
+}
+
+/// The computer as the guesser
+#[derive(Debug, Clone)]
+pub struct Guesser {
+    candidates: Vec<String>,
+    guessed: BTreeSet<char>,
+}
+
+impl Guesser {
+    /// Starts with the words of the dictionary as long as the secret
+    pub fn new<I, S>(dictionary: I, length: usize) -> Guesser
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let candidates = dictionary
+            .into_iter()
+            .map(|word| word.as_ref().to_string())
+            .filter(|word| word.chars().count() == length)
+            .collect();
+        Guesser { candidates, guessed: BTreeSet::new() }
+    }
+
+    /// The words that fit every answer so far
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    /// The letter not guessed yet that the most candidates have, the
+    /// first in order on ties, or `None` if no candidate is left or every
+    /// letter of them was guessed
+    pub fn guess(&self) -> Option<char> {
+        let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+        for word in &self.candidates {
+            let letters: BTreeSet<char> = word.chars().filter(|c| !self.guessed.contains(c)).collect();
+            for c in letters {
+                *counts.entry(c).or_insert(0) += 1;
+            }
+        }
+        // max_by_key keeps the last of equals, so the order is reversed
+        counts.into_iter().rev().max_by_key(|&(_, count)| count).map(|(c, _)| c)
+    }
+
+    /// The candidates split by the pattern guessing `letter` would reveal
+    pub fn partition(&self, letter: char) -> BTreeMap<String, Vec<String>> {
+        let mut guessed = self.guessed.clone();
+        guessed.insert(letter);
+        let mut parts: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for word in &self.candidates {
+            parts.entry(pattern(word, &guessed)).or_default().push(word.clone());
+        }
+        parts
+    }
+
+    /// Keeps the candidates that reveal `revealed` after guessing `letter`
+    pub fn answer(&mut self, letter: char, revealed: &str) {
+        self.candidates = self.partition(letter).remove(revealed).unwrap_or_default();
+        self.guessed.insert(letter);
+    }
+}
+
+/// The computer guessing a word, until it wins or loses: the letters it
+/// guessed, in order, and how the game ended
+///
+/// # Example
+/// ```
+/// use pprust::hangman::{play, Status, MAX_MISSES};
+///
+/// let dictionary = ["banana", "bandit", "cabana", "canvas", "random"];
+/// let (guesses, status) = play("cabana", &dictionary, MAX_MISSES);
+/// assert_eq!((vec!['a', 'b', 'c', 'n'], Status::Won), (guesses, status));
+/// ```
+pub fn play(secret: &str, dictionary: &[&str], max_misses: usize) -> (Vec<char>, Status) {
+    let mut game = Game::new(secret, max_misses);
+    let mut guesser = Guesser::new(dictionary, secret.chars().count());
+    let mut guesses = Vec::new();
+    while game.status() == Status::Playing {
+        let letter = match guesser.guess() {
+            Some(letter) => letter,
+            None => break,
+        };
+        game.guess(letter);
+        guesser.answer(letter, &game.pattern());
+        guesses.push(letter);
+    }
+    (guesses, game.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 12] = [
+        "apple", "angle", "ample", "maple", "jazzy", "fuzzy", "fizzy", "dizzy", "pizza", "pasta", "mango", "tango",
+    ];
+
+    #[test]
+    fn should_play_by_the_rules() {
+        let mut game = Game::new("jazzy", 2);
+        assert_eq!(("_____".to_string(), Status::Playing), (game.pattern(), game.status()));
+        assert_eq!(Outcome::Hit(2), game.guess('z'));
+        assert_eq!(Outcome::Miss, game.guess('e'));
+        assert_eq!(Outcome::Repeated, game.guess('e'));
+        assert_eq!((1, 1), (game.misses(), game.misses_left()));
+        assert_eq!(Outcome::Miss, game.guess('o'));
+        assert_eq!(Status::Lost, game.status());
+        assert_eq!(Outcome::Repeated, game.guess('j'), "The game is over");
+        assert_eq!("__zz_", game.pattern());
+        assert_eq!(vec!['e', 'o', 'z'], game.guessed().iter().cloned().collect::<Vec<char>>());
+    }
+
+    #[test]
+    fn should_narrow_down_the_candidates() {
+        let mut guesser = Guesser::new(WORDS.iter(), 5);
+        assert_eq!(12, guesser.candidates().len());
+        assert_eq!(Some('a'), guesser.guess(), "In nine of the words");
+        let parts = guesser.partition('a');
+        assert_eq!(vec!["apple", "angle", "ample"], parts["a____"]);
+        assert_eq!(vec!["fuzzy", "fizzy", "dizzy"], parts["_____"]);
+        guesser.answer('a', "_____");
+        assert_eq!(Some('y'), guesser.guess(), "'y' and 'z' are in all three, 'y' comes first");
+        guesser.answer('z', "__zz_");
+        assert_eq!(vec!["fuzzy", "fizzy", "dizzy"], guesser.candidates());
+        guesser.answer('y', "__zzy");
+        guesser.answer('f', "f_zzy");
+        assert_eq!(vec!["fuzzy", "fizzy"], guesser.candidates());
+        guesser.answer('u', "f_zzy");
+        assert_eq!(vec!["fizzy"], guesser.candidates());
+        assert_eq!(Some('i'), guesser.guess());
+        guesser.answer('i', "_____");
+        assert!(guesser.candidates().is_empty() && guesser.guess().is_none(), "Inconsistent answers");
+    }
+
+    #[test]
+    fn should_guess_every_word_of_the_dictionary() {
+        for secret in &WORDS {
+            let (guesses, status) = play(secret, &WORDS, MAX_MISSES);
+            assert_eq!(Status::Won, status, "{} with {:?}", secret, guesses);
+        }
+        let (guesses, status) = play("zebra", &WORDS, MAX_MISSES);
+        assert_eq!(Status::Playing, status, "Gave up on a word it doesn't know after {:?}", guesses);
+        let mut rng = Rng::new(271);
+        assert!(WORDS.contains(&random_word(&WORDS, 5, &mut rng).unwrap()));
+        assert_eq!(None, random_word(&WORDS, 6, &mut rng));
+    }
+}
@@ -0,0 +1,209 @@
+//!
+//! # Problem description:
+//! #
+//! # Priority Queues
+//!
+//! Implement a priority queue as a binary heap stored in an array: the
+//! children of the element at index *i* are at *2i + 1* and *2i + 2*,
+//! and every parent is at least as large as its children. Insertion
+//! sifts the new element up from the bottom, and removing the maximum
+//! moves the last element to the root and sifts it down.
+//!
+//! The sift routines are exposed on plain slices, with an ordering
+//! predicate, so other exercises (heapsort, k-way merging) can build on
+//! them without going through `BinaryHeap`.
+//!
+
+/// Moves the element at `i` up until its parent is not less than it,
+/// according to the `is_less` predicate. Returns the number of swaps.
+pub fn sift_up_by<T, F>(data: &mut [T], mut i: usize, is_less: &mut F) -> usize
+    where F: FnMut(&T, &T) -> bool
+{
+    let mut swaps = 0;
+    while i > 0 {
+        let parent = (i - 1) / 2;
+        if !is_less(&data[parent], &data[i]) {
+            break;
+        }
+        data.swap(parent, i);
+        swaps += 1;
+        i = parent;
+    }
+    swaps
+}
+
+/// Moves the element at `i` down until none of its children inside
+/// `data` is greater than it, according to the `is_less` predicate.
+/// Returns the number of swaps.
+pub fn sift_down_by<T, F>(data: &mut [T], mut i: usize, is_less: &mut F) -> usize
+    where F: FnMut(&T, &T) -> bool
+{
+    let len = data.len();
+    let mut swaps = 0;
+    loop {
+        let left = 2 * i + 1;
+        if left >= len {
+            break;
+        }
+        let right = left + 1;
+        let child = if right < len && is_less(&data[left], &data[right]) { right } else { left };
+        if !is_less(&data[i], &data[child]) {
+            break;
+        }
+        data.swap(i, child);
+        swaps += 1;
+        i = child;
+    }
+    swaps
+}
+
+/// `sift_up_by` using the natural ordering, for max-heaps
+pub fn sift_up<T: Ord>(data: &mut [T], i: usize) -> usize {
+    sift_up_by(data, i, &mut |a, b| a < b)
+}
+
+/// `sift_down_by` using the natural ordering, for max-heaps
+pub fn sift_down<T: Ord>(data: &mut [T], i: usize) -> usize {
+    sift_down_by(data, i, &mut |a, b| a < b)
+}
+
+/// Rearranges a slice into a heap in linear time. Returns the number
+/// of swaps.
+pub fn heapify_by<T, F>(data: &mut [T], is_less: &mut F) -> usize
+    where F: FnMut(&T, &T) -> bool
+{
+    (0..data.len() / 2).rev().map(|i| sift_down_by(data, i, is_less)).sum()
+}
+
+/// A max-heap priority queue
+///
+/// # Example
+/// ```
+/// use pprust::heap::BinaryHeap;
+///
+/// let mut heap = BinaryHeap::new();
+/// heap.push(3);
+/// heap.push(7);
+/// heap.push(5);
+/// assert_eq!(Some(7), heap.pop());
+/// assert_eq!(Some(&5), heap.peek());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BinaryHeap<T: Ord> {
+    data: Vec<T>,
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    /// Creates an empty heap
+    pub fn new() -> BinaryHeap<T> {
+        BinaryHeap { data: Vec::new() }
+    }
+
+    /// Builds a heap from a vector in linear time
+    pub fn from_vec(mut data: Vec<T>) -> BinaryHeap<T> {
+        heapify_by(&mut data, &mut |a, b| a < b);
+        BinaryHeap { data }
+    }
+
+    /// Number of elements in the heap
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if the heap has no elements
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the largest element without removing it
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Adds an element to the heap
+    pub fn push(&mut self, value: T) {
+        self.data.push(value);
+        let last = self.data.len() - 1;
+        sift_up(&mut self.data, last);
+    }
+
+    /// Removes and returns the largest element
+    pub fn pop(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let last = self.data.len() - 1;
+        self.data.swap(0, last);
+        let top = self.data.pop();
+        sift_down(&mut self.data, 0);
+        top
+    }
+
+    /// Consumes the heap returning its elements in ascending order
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.data.len());
+        while let Some(value) = self.pop() {
+            sorted.push(value);
+        }
+        sorted.reverse();
+        sorted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Reverse;
+
+    fn is_heap<T: Ord>(data: &[T]) -> bool {
+        (1..data.len()).all(|i| data[(i - 1) / 2] >= data[i])
+    }
+
+    #[test]
+    fn should_pop_in_descending_order() {
+        let mut heap = BinaryHeap::new();
+        for x in &[5, 1, 8, 3, 9, 2, 8] {
+            heap.push(*x);
+        }
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+        assert_eq!(vec![9, 8, 8, 5, 3, 2, 1], popped);
+    }
+
+    #[test]
+    fn should_return_none_when_empty() {
+        let mut heap: BinaryHeap<i32> = BinaryHeap::new();
+        assert!(heap.is_empty());
+        assert_eq!(None, heap.peek());
+        assert_eq!(None, heap.pop());
+    }
+
+    #[test]
+    fn should_build_heap_from_vec() {
+        let heap = BinaryHeap::from_vec(vec![4, 10, 3, 5, 1, 7, 7, 0]);
+        assert!(is_heap(&heap.data));
+        assert_eq!(8, heap.len());
+        assert_eq!(vec![0, 1, 3, 4, 5, 7, 7, 10], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn should_work_as_min_heap_with_reverse() {
+        let mut heap = BinaryHeap::new();
+        for x in &[5, 1, 8] {
+            heap.push(Reverse(*x));
+        }
+        assert_eq!(Some(Reverse(1)), heap.pop());
+    }
+
+    #[test]
+    fn should_sift_with_custom_predicate() {
+        let mut data = vec![3, 1, 2];
+        heapify_by(&mut data, &mut |a: &i32, b: &i32| a > b);
+        assert_eq!(1, data[0]);
+        data.push(0);
+        sift_up_by(&mut data, 3, &mut |a: &i32, b: &i32| a > b);
+        assert_eq!(0, data[0]);
+    }
+}
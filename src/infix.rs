@@ -0,0 +1,559 @@
+//!
+//! # Problem description:
+//! #
+//! # Infix Calculator
+//!
+//! Let the RPN calculator take the expressions people write, like
+//! `(19 + 2.14) * (4.5 - 2 / 4.3)`, by converting them to reverse Polish
+//! notation with Dijkstra's shunting-yard algorithm. Operands go straight
+//! to the output; operators wait on a stack until an operator that binds
+//! less tightly, or a closing parenthesis, sends them to the output after
+//! their operands.
+//!
+//! `^` binds tightest and groups to the right, then unary minus, then
+//! `*`, `/` and `%`, then `+` and `-`, and last the comparisons `<`, `>`,
+//! `=`, `<=` and `>=`. Any operator of the calculator that takes a fixed
+//! number of values can be called as a function, as in `pow(2, 10)`; a
+//! name alone is a constant, a register stored with `sto`, or an operator
+//! or word run on the stack as it is.
+//!
+
+use lexgen::Lexer;
+use rpncalculator::{CalcResult, Number, RpnCalculator, RpnCalculatorError, MAX_WORD_DEPTH};
+use std::result;
+
+/// An operator of infix expressions: the operator of the calculator it
+/// becomes, how tightly it binds and whether it groups to the right
+#[derive(Debug, Clone, Copy)]
+struct Infix {
+    rpn: &'static str,
+    precedence: u8,
+    right: bool,
+}
+
+const fn infix(rpn: &'static str, precedence: u8, right: bool) -> Infix {
+    Infix { rpn, precedence, right }
+}
+
+const BINARY: [(&str, Infix); 11] = [
+    ("<", infix("<", 1, false)),
+    (">", infix(">", 1, false)),
+    ("=", infix("=", 1, false)),
+    ("<=", infix("<=", 1, false)),
+    (">=", infix(">=", 1, false)),
+    ("+", infix("+", 2, false)),
+    ("-", infix("-", 2, false)),
+    ("*", infix("*", 3, false)),
+    ("/", infix("/", 3, false)),
+    ("%", infix("mod", 3, false)),
+    ("^", infix("pow", 5, true)),
+];
+
+/// Unary minus, between the products and `^`, so that `-2 ^ 2` is -4
+const NEGATION: Infix = infix("neg", 4, true);
+
+/// A token of the RPN line, with the token of the infix expression it
+/// came from and where that is, to report errors at
+#[derive(Debug, Clone)]
+struct Converted<'a> {
+    rpn: String,
+    source: &'a str,
+    offset: usize,
+}
+
+/// What waits on the stack of the shunting-yard
+enum Waiting<'a> {
+    Operator(Infix, &'a str, usize),
+    Parenthesis(usize),
+    /// A function and its `(`, with the arguments it takes and the
+    /// commas seen so far
+    Call { name: &'a str, offset: usize, arity: usize, commas: usize },
+}
+
+/// An error at a token of the infix expression
+fn at(token: &str, offset: usize, error: RpnCalculatorError) -> RpnCalculatorError {
+    RpnCalculatorError::At { token: token.to_string(), offset, error: Box::new(error) }
+}
+
+/// The patterns of the tokens of an expression. A number runs up to the
+/// end of `1.5e-3`, `1_000` or `0xff`, or of a literal of the number
+/// type, like `4i`; the sign after an `e` is only part of it when it
+/// isn't hexadecimal.
+const NUMBER: &str = "0|([1-9.]|0[0-9A-WYZa-wyz._]|0[eE][+-])([0-9A-Za-z._]|[eE][+-])*";
+const HEXADECIMAL: &str = "0[xX][0-9A-Za-z._]*";
+const NAME: &str = "[A-Za-z_][A-Za-z0-9_]*";
+const SYMBOL: &str = "<=|>=|[-+*/%^()<>=,]";
+
+/// Splits an expression into numbers, names, operators, parentheses and
+/// commas, with their byte offsets
+fn tokenize(text: &str) -> result::Result<Vec<(&str, usize)>, RpnCalculatorError> {
+    let lexer = Lexer::new()
+        .token((), NUMBER)
+        .and_then(|lexer| lexer.token((), HEXADECIMAL))
+        .and_then(|lexer| lexer.token((), NAME))
+        .and_then(|lexer| lexer.token((), SYMBOL))
+        .and_then(|lexer| lexer.skip("\\s+"))
+        .expect("The patterns of the tokens are valid");
+    lexer
+        .tokens(text)
+        .map(|token| match token {
+            Ok(token) => Ok((token.text, token.offset)),
+            Err(e) => Err(at(&e.found.to_string(), e.offset, RpnCalculatorError::ParsingError)),
+        })
+        .collect()
+}
+
+/// Converts an infix expression to the tokens of an RPN line
+fn convert<'a, T: Number>(calc: &RpnCalculator<T>, text: &'a str) -> result::Result<Vec<Converted<'a>>, RpnCalculatorError> {
+    let tokens = tokenize(text)?;
+    let mut output = Vec::new();
+    let mut stack: Vec<Waiting> = Vec::new();
+    let mut operand_next = true;
+    let mut calling = false;
+    let mut emit = |rpn: &str, source: &'a str, offset: usize| output.push(Converted { rpn: rpn.to_string(), source, offset });
+    for (i, &(token, offset)) in tokens.iter().enumerate() {
+        let first = token.as_bytes()[0];
+        let is_name = first.is_ascii_alphabetic() || first == b'_';
+        if first.is_ascii_digit() || first == b'.' || is_name {
+            if !operand_next {
+                return Err(at(token, offset, RpnCalculatorError::UnexpectedToken));
+            }
+            if is_name && tokens.get(i + 1).map(|&(next, _)| next) == Some("(") {
+                let arity = calc.operator(token).map(|operator| operator.arity);
+                match arity {
+                    Some(Some(arity)) => {
+                        stack.push(Waiting::Call { name: token, offset, arity, commas: 0 });
+                        calling = true;
+                    }
+                    Some(None) => return Err(at(token, offset, RpnCalculatorError::UnexpectedToken)),
+                    None => return Err(at(token, offset, RpnCalculatorError::UnknownName(token.to_string()))),
+                }
+                continue;
+            }
+            if is_name && calc.registers().contains_key(token) {
+                emit("rcl", token, offset);
+            }
+            emit(token, token, offset);
+            operand_next = false;
+            continue;
+        }
+        match token {
+            // The `(` of a call is on the stack with the function
+            "(" if calling => calling = false,
+            "(" if operand_next => stack.push(Waiting::Parenthesis(offset)),
+            "-" if operand_next => stack.push(Waiting::Operator(NEGATION, token, offset)),
+            "+" if operand_next => (),
+            ")" | "," => {
+                let empty_call = operand_next && i > 0 && tokens[i - 1].0 == "(" && matches!(stack.last(), Some(Waiting::Call { .. }));
+                if operand_next && !(empty_call && token == ")") {
+                    return Err(at(token, offset, RpnCalculatorError::UnexpectedToken));
+                }
+                loop {
+                    match stack.pop() {
+                        Some(Waiting::Operator(infix, source, at)) => emit(infix.rpn, source, at),
+                        Some(Waiting::Parenthesis(_)) if token == ")" => break,
+                        Some(Waiting::Call { name, offset: call, arity, commas }) => {
+                            if token == "," {
+                                stack.push(Waiting::Call { name, offset: call, arity, commas: commas + 1 });
+                                break;
+                            }
+                            let got = if empty_call { 0 } else { commas + 1 };
+                            if got != arity {
+                                let error = RpnCalculatorError::WrongArgumentCount { op: name.to_string(), needed: arity, got };
+                                return Err(at(name, call, error));
+                            }
+                            emit(name, name, call);
+                            break;
+                        }
+                        Some(Waiting::Parenthesis(_)) => return Err(at(token, offset, RpnCalculatorError::UnexpectedToken)),
+                        None => return Err(at(token, offset, RpnCalculatorError::UnbalancedParentheses)),
+                    }
+                }
+                operand_next = token == ",";
+            }
+            _ => {
+                let infix = match BINARY.iter().find(|&&(symbol, _)| symbol == token) {
+                    Some(&(_, infix)) if !operand_next => infix,
+                    _ => return Err(at(token, offset, RpnCalculatorError::UnexpectedToken)),
+                };
+                while let Some(&Waiting::Operator(top, source, at)) = stack.last() {
+                    if top.precedence < infix.precedence || (top.precedence == infix.precedence && infix.right) {
+                        break;
+                    }
+                    emit(top.rpn, source, at);
+                    stack.pop();
+                }
+                stack.push(Waiting::Operator(infix, token, offset));
+                operand_next = true;
+            }
+        }
+    }
+    if operand_next && !tokens.is_empty() {
+        return Err(RpnCalculatorError::UnexpectedEnd);
+    }
+    while let Some(waiting) = stack.pop() {
+        match waiting {
+            Waiting::Operator(infix, source, at) => emit(infix.rpn, source, at),
+            Waiting::Parenthesis(offset) => return Err(at("(", offset, RpnCalculatorError::UnbalancedParentheses)),
+            Waiting::Call { name, offset, .. } => return Err(at(name, offset, RpnCalculatorError::UnbalancedParentheses)),
+        }
+    }
+    Ok(output)
+}
+
+/// Converts an infix expression to the RPN line the calculator would
+/// evaluate for it
+///
+/// # Example
+/// ```
+/// use pprust::infix::to_rpn;
+/// use pprust::rpncalculator::RpnCalculator;
+///
+/// let calc: RpnCalculator = RpnCalculator::new();
+/// assert_eq!("19 2.14 + 4.5 2 4.3 / - *", to_rpn(&calc, "(19 + 2.14) * (4.5 - 2 / 4.3)").unwrap());
+/// assert_eq!("2 3 2 pow pow neg", to_rpn(&calc, "-2 ^ 3 ^ 2").unwrap());
+/// assert_eq!("1 2 3 * 4 pow sqrt + 5 <", to_rpn(&calc, "1 + sqrt(pow(2 * 3, 4)) < 5").unwrap());
+/// ```
+pub fn to_rpn<T: Number>(calc: &RpnCalculator<T>, text: &str) -> result::Result<String, RpnCalculatorError> {
+    Ok(convert(calc, text)?.iter().map(|token| token.rpn.as_str()).collect::<Vec<&str>>().join(" "))
+}
+
+/// Evaluates an infix expression in a calculator, leaving its value on
+/// the stack. Errors are at the tokens of the expression, not of the RPN
+/// line.
+///
+/// # Example
+/// ```
+/// use pprust::infix::evaluate;
+/// use pprust::rpncalculator::RpnCalculator;
+///
+/// let mut calc: RpnCalculator = RpnCalculator::new();
+/// evaluate(&mut calc, "2 ^ 10 - 24").unwrap();
+/// calc.evaluate("sto kb").unwrap();
+/// evaluate(&mut calc, "kb / 4").unwrap();
+/// assert_eq!(Some(&250.0), calc.top());
+/// let error = evaluate(&mut calc, "1 + sqrt(1 - 5)").unwrap_err();
+/// assert_eq!("`sqrt` at byte 4: Argument out of the domain", error.to_string());
+/// ```
+pub fn evaluate<T: Number>(calc: &mut RpnCalculator<T>, text: &str) -> CalcResult {
+    let converted = convert(calc, text)?;
+    let line = converted.iter().map(|token| token.rpn.as_str()).collect::<Vec<&str>>().join(" ");
+    calc.evaluate(&line).map_err(|error| match error {
+        RpnCalculatorError::At { offset, error, token } => {
+            let mut start = 0;
+            for converted in &converted {
+                if start == offset {
+                    return RpnCalculatorError::At { token: converted.source.to_string(), offset: converted.offset, error };
+                }
+                start += converted.rpn.len() + 1;
+            }
+            RpnCalculatorError::At { token, offset, error }
+        }
+        error => error,
+    })
+}
+
+/// An expression on the stack of `explain`, and whether it needs
+/// parentheses inside another
+#[derive(Debug, Clone)]
+struct Explained {
+    text: String,
+    compound: bool,
+}
+
+impl Explained {
+    fn operand(&self) -> String {
+        if self.compound {
+            format!("({})", self.text)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+/// Explains the tokens of an RPN line, or of a word it calls, on the
+/// stack of expressions
+fn explain_tokens<T: Number>(
+    calc: &RpnCalculator<T>,
+    tokens: &[(String, usize)],
+    stack: &mut Vec<Explained>,
+    depth: usize,
+) -> CalcResult {
+    let mut tokens = tokens.iter();
+    while let Some((token, offset)) = tokens.next() {
+        let token = token.as_str();
+        let at = |error| at(token, *offset, error);
+        if let Some(body) = calc.words().get(token) {
+            if depth == MAX_WORD_DEPTH {
+                return Err(at(RpnCalculatorError::RecursionTooDeep));
+            }
+            let body: Vec<(String, usize)> = body.iter().map(|word| (word.clone(), *offset)).collect();
+            explain_tokens(calc, &body, stack, depth + 1)?;
+            continue;
+        }
+        let operator = match calc.operator(token) {
+            Some(operator) => operator,
+            None => {
+                stack.push(Explained { text: token.to_string(), compound: false });
+                continue;
+            }
+        };
+        if token == "rcl" {
+            let name = tokens.next().ok_or_else(|| at(RpnCalculatorError::MissingArgument))?;
+            stack.push(Explained { text: name.0.clone(), compound: false });
+            continue;
+        }
+        let needed = match token {
+            "drop" | "dup" => 1,
+            "swap" | "over" => 2,
+            "rot" => 3,
+            _ => operator.arity.ok_or_else(|| at(RpnCalculatorError::NoInfixForm))?,
+        };
+        if stack.len() < needed {
+            return Err(at(RpnCalculatorError::NotEnoughOperands { op: token.to_string(), needed, got: stack.len() }));
+        }
+        let mut operands = stack.split_off(stack.len() - needed);
+        match token {
+            "drop" => (),
+            "dup" => stack.extend(vec![operands[0].clone(), operands[0].clone()]),
+            "swap" => stack.extend(operands.into_iter().rev()),
+            "over" => stack.extend(vec![operands[0].clone(), operands[1].clone(), operands[0].clone()]),
+            "rot" => {
+                operands.rotate_left(1);
+                stack.extend(operands);
+            }
+            "neg" => stack.push(Explained { text: format!("-{}", operands[0].operand()), compound: false }),
+            _ => {
+                let symbol = BINARY.iter().find(|&&(_, infix)| infix.rpn == token).map(|&(symbol, _)| symbol);
+                let text = match symbol {
+                    Some(symbol) if needed == 2 => format!("{} {} {}", operands[0].operand(), symbol, operands[1].operand()),
+                    _ => {
+                        let arguments: Vec<&str> = operands.iter().map(|operand| operand.text.as_str()).collect();
+                        stack.push(Explained { text: format!("{}({})", token, arguments.join(", ")), compound: false });
+                        continue;
+                    }
+                };
+                stack.push(Explained { text, compound: true });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The infix form of an RPN expression, with parentheses around every
+/// operation inside another; what is left on the stack is separated by
+/// commas
+///
+/// # Example
+/// ```
+/// use pprust::infix::explain;
+/// use pprust::rpncalculator::RpnCalculator;
+///
+/// let mut calc: RpnCalculator = RpnCalculator::new();
+/// assert_eq!("(19 + 2.14) * (4.5 - (2 / 4.3))", explain(&calc, "19 2.14 + 4.5 2 4.3 / - *").unwrap());
+/// calc.evaluate(": sq dup * ;").unwrap();
+/// assert_eq!("sqrt((x * x) + (-y * -y)), 2", explain(&calc, "x sq y neg sq + sqrt 2").unwrap());
+/// ```
+pub fn explain<T: Number>(calc: &RpnCalculator<T>, rpn: &str) -> result::Result<String, RpnCalculatorError> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in rpn.char_indices().chain(Some((rpn.len(), ' '))) {
+        match (c.is_whitespace(), start) {
+            (true, Some(from)) => {
+                tokens.push((rpn[from..i].to_string(), from));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => (),
+        }
+    }
+    let mut stack = Vec::new();
+    explain_tokens(calc, &tokens, &mut stack, 0)?;
+    Ok(stack.iter().map(|explained| explained.text.as_str()).collect::<Vec<&str>>().join(", "))
+}
+
+/// A calculator that takes infix expressions
+///
+/// # Example
+/// ```
+/// use pprust::infix::InfixCalculator;
+///
+/// let mut calc: InfixCalculator = InfixCalculator::new();
+/// calc.evaluate("(19 + 2.14) * (4.5 - 2 / 4.3)").unwrap();
+/// assert_eq!(85.2974, (calc.top().unwrap() * 1e4).round() / 1e4);
+/// ```
+pub struct InfixCalculator<T: Number = f64> {
+    calc: RpnCalculator<T>,
+}
+
+impl<T: Number> Default for InfixCalculator<T> {
+    fn default() -> InfixCalculator<T> {
+        InfixCalculator::new()
+    }
+}
+
+impl<T: Number> InfixCalculator<T> {
+    /// Creates a new calculator with the operators of its number type
+    pub fn new() -> InfixCalculator<T> {
+        InfixCalculator::new_with_calculator(RpnCalculator::new())
+    }
+
+    /// Takes infix expressions for an RPN calculator, with its operators
+    /// and whatever it has stored
+    pub fn new_with_calculator(calc: RpnCalculator<T>) -> InfixCalculator<T> {
+        InfixCalculator { calc }
+    }
+
+    pub fn evaluate(&mut self, text: &str) -> CalcResult {
+        evaluate(&mut self.calc, text)
+    }
+
+    pub fn to_rpn(&self, text: &str) -> result::Result<String, RpnCalculatorError> {
+        to_rpn(&self.calc, text)
+    }
+
+    /// The infix form of an RPN expression
+    pub fn explain(&self, rpn: &str) -> result::Result<String, RpnCalculatorError> {
+        explain(&self.calc, rpn)
+    }
+
+    pub fn top(&self) -> Option<&T> {
+        self.calc.top()
+    }
+
+    /// The RPN calculator underneath, for what has no infix form, like
+    /// `sto` or defining words
+    pub fn calculator(&mut self) -> &mut RpnCalculator<T> {
+        &mut self.calc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigint::BigInt;
+    use complex::Complex;
+    use rpncalculator::BigNumber;
+
+    fn check(text: &str, expected: f64) {
+        let mut calc: InfixCalculator = InfixCalculator::new();
+        calc.evaluate(text).unwrap_or_else(|e| panic!("{}: {}", text, e));
+        assert_eq!(Some(&expected), calc.top(), "{} is {}", text, calc.to_rpn(text).unwrap());
+    }
+
+    #[test]
+    fn should_follow_precedence_and_associativity() {
+        check("1 + 2 * 3", 7.0);
+        check("(1 + 2) * 3", 9.0);
+        check("10 - 4 - 3", 3.0);
+        check("64 / 4 / 2", 8.0);
+        check("2 ^ 3 ^ 2", 512.0);
+        check("(2 ^ 3) ^ 2", 64.0);
+        check("-2 ^ 2", -4.0);
+        check("2 ^ -1", 0.5);
+        check("-(3 - 5) * - - 2", 4.0);
+        check("+3 - -3", 6.0);
+        check("17 % 5 * 2", 4.0);
+        check("1 + 2 < 2 * 2", 1.0);
+        check("2 * 3 = 6 + 0", 1.0);
+        check("1.5e3 + 1_000", 2500.0);
+        check("2 * pi / tau", 1.0);
+        check("((((7))))", 7.0);
+    }
+
+    #[test]
+    fn should_call_operators_as_functions() {
+        check("pow(2, 10) - 24", 1000.0);
+        check("mod(17, 5)", 2.0);
+        check("sqrt(9) + abs(-4) * neg(1)", -1.0);
+        check("pow(pow(2, 2), 1 + 1)", 16.0);
+        check("cos(0) * 2 + atan(0)", 2.0);
+        let mut calc: InfixCalculator = InfixCalculator::new();
+        calc.calculator().evaluate(": hyp dup * swap dup * + sqrt ; 10 sto r clear").unwrap();
+        calc.evaluate("r * 2 + depth").unwrap();
+        assert_eq!(Some(&21.0), calc.top());
+        calc.evaluate("3 * 2").unwrap();
+        calc.evaluate("8").unwrap();
+        calc.evaluate("hyp").unwrap();
+        assert_eq!(Some(&10.0), calc.top());
+        assert_eq!("rcl r 2 * depth +", calc.to_rpn("r * 2 + depth").unwrap());
+    }
+
+    #[test]
+    fn should_report_errors_where_they_are() {
+        let calc: RpnCalculator = RpnCalculator::new();
+        let errors = [
+            ("1 + ", "The expression ends too early"),
+            ("(1 + 2", "`(` at byte 0: Parentheses don't match"),
+            ("1 + 2)", "`)` at byte 5: Parentheses don't match"),
+            ("1 2", "`2` at byte 2: Not expected here"),
+            ("1 * * 2", "`*` at byte 4: Not expected here"),
+            ("()", "`)` at byte 1: Not expected here"),
+            ("(1, 2)", "`,` at byte 2: Not expected here"),
+            ("2 (3)", "`(` at byte 2: Not expected here"),
+            ("1 & 2", "`&` at byte 2: Not a number or an operator"),
+            ("pow(2)", "`pow` at byte 0: pow takes 2 arguments, got 1"),
+            ("neg()", "`neg` at byte 0: neg takes 1 argument, got 0"),
+            ("sqrt(4, 9)", "`sqrt` at byte 0: sqrt takes 1 argument, got 2"),
+            ("sum(1, 2)", "`sum` at byte 0: Not expected here"),
+            ("f(1)", "`f` at byte 0: No operator or word named f"),
+            ("sqrt(2", "`sqrt` at byte 0: Parentheses don't match"),
+        ];
+        for &(text, expected) in &errors {
+            assert_eq!(Some(expected.to_string()), to_rpn(&calc, text).err().map(|e| e.to_string()), "{}", text);
+        }
+        assert_eq!(Ok(String::new()), to_rpn(&calc, "  "));
+        let mut calc: RpnCalculator = RpnCalculator::new();
+        let error = evaluate(&mut calc, "2 * (3 - nope)").unwrap_err();
+        assert_eq!("`nope` at byte 9: Not a number or an operator", error.to_string());
+        let error = evaluate(&mut calc, "1 / 2 - -sqrt(-1)").unwrap_err();
+        assert_eq!("`sqrt` at byte 9: Argument out of the domain", error.to_string());
+    }
+
+    #[test]
+    fn should_work_with_any_number_type() {
+        let mut calc: InfixCalculator<Complex> = InfixCalculator::new();
+        calc.evaluate("(1 + 2i) * (3 - i) + sqrt(-4)").unwrap();
+        assert_eq!("5+7i", calc.top().unwrap().to_string());
+        let mut calc: InfixCalculator<BigNumber> = InfixCalculator::new();
+        calc.evaluate("2 ^ 100 + 1").unwrap();
+        assert_eq!(BigNumber::Int("1267650600228229401496703205377".parse::<BigInt>().unwrap()), *calc.top().unwrap());
+        let mut calc: InfixCalculator<i64> = InfixCalculator::new();
+        calc.evaluate("7 / 2 * 2 + 7 % 2").unwrap();
+        assert_eq!(Some(&7), calc.top());
+    }
+
+    #[test]
+    fn should_explain_rpn_in_infix() {
+        let mut calc: RpnCalculator = RpnCalculator::new();
+        let cases = [
+            ("1 2 + 3 4 - *", "(1 + 2) * (3 - 4)"),
+            ("2 3 2 pow pow neg", "-(2 ^ (3 ^ 2))"),
+            ("5 neg 3 17 mod <", "-5 < (3 % 17)"),
+            ("1 2 swap - 3 over", "2 - 1, 3, 2 - 1"),
+            ("a b c rot drop", "b, c"),
+            ("2 10 pow sqrt x 30 sin", "sqrt(2 ^ 10), x, sin(30)"),
+            ("", ""),
+        ];
+        for &(rpn, expected) in &cases {
+            assert_eq!(Ok(expected.to_string()), explain(&calc, rpn), "{}", rpn);
+        }
+        // Explaining the infix form gives it back, with all the parentheses
+        for text in &["1 + 2 * 3 - 4", "-2 ^ 2", "pow(2, 1 + 1) / 4 >= 1"] {
+            let rpn = to_rpn(&calc, text).unwrap();
+            let explained = explain(&calc, &rpn).unwrap();
+            assert_eq!(rpn, to_rpn(&calc, &explained).unwrap(), "{} explained as {}", text, explained);
+        }
+        calc.evaluate(": loop loop ; 2 sto r").unwrap();
+        assert_eq!(Ok("r * 2".to_string()), explain(&calc, "rcl r 2 *"));
+        let errors = [
+            ("1 +", "`+` at byte 2: + takes 2 operands, got 1"),
+            ("1 2 3 sum", "`sum` at byte 6: No infix form"),
+            ("  rcl", "`rcl` at byte 2: Missing name or number after the operator"),
+            ("1 loop", "`loop` at byte 2: Words nested more than 256 deep"),
+        ];
+        for &(rpn, expected) in &errors {
+            assert_eq!(Some(expected.to_string()), explain(&calc, rpn).err().map(|e| e.to_string()), "{}", rpn);
+        }
+    }
+}
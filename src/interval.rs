@@ -0,0 +1,436 @@
+//!
+//! # Problem description:
+//! #
+//! # Interval Arithmetic
+//!
+//! A measurement known only to lie between two bounds is an interval
+//! [lo, hi]. Arithmetic on intervals gives the smallest interval holding
+//! every result of the operation on members of the operands: the sum of
+//! [1, 2] and [10, 20] is [11, 22], and their product's bounds are the
+//! extremes of the products of the bounds. Uncertainty so propagates
+//! through a whole computation.
+//!
+//! To stay sound the bounds are rounded outward. The error of each
+//! floating point operation is found exactly, by Knuth's TwoSum for
+//! sums and by a fused multiply-add for products and quotients, and a
+//! bound only moves to the next float when the operation was inexact, so
+//! exact results such as 1 + 2 stay points.
+//!
+//! Dividing by an interval with zero at one end gives a half-infinite
+//! interval, and by one with zero inside, the whole line; only [0, 0]
+//! can't be divided by.
+//!
+//! Intervals are written `[lo,hi]`, without spaces; a plain number is the
+//! interval holding only that number.
+//!
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::result;
+use std::str::FromStr;
+
+/// Errors parsing an interval
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntervalError {
+    /// The text is not a number or an interval
+    Syntax(String),
+    /// The lower bound is above the upper one
+    Empty(String),
+}
+
+impl fmt::Display for IntervalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IntervalError::Syntax(ref text) => write!(f, "'{}' is not an interval", text),
+            IntervalError::Empty(ref text) => write!(f, "'{}' has its bounds reversed", text),
+        }
+    }
+}
+
+/// The result of parsing an interval
+pub type IntervalResult<T> = result::Result<T, IntervalError>;
+
+/// `x` rounded down, when the exact value is `x + err`
+fn round_down(x: f64, err: f64) -> f64 {
+    // A NaN error means finite operands overflowed
+    if err < 0.0 || (x == f64::INFINITY && err.is_nan()) {
+        x.next_down()
+    } else {
+        x
+    }
+}
+
+/// `x` rounded up, when the exact value is `x + err`
+fn round_up(x: f64, err: f64) -> f64 {
+    if err > 0.0 || (x == f64::NEG_INFINITY && err.is_nan()) {
+        x.next_up()
+    } else {
+        x
+    }
+}
+
+/// `a + b` and the error of the rounded sum, by TwoSum
+fn sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    if !a.is_finite() || !b.is_finite() {
+        return (s, 0.0);
+    }
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// `a * b` and the sign of its error, where zero times anything is zero
+fn product(a: f64, b: f64) -> (f64, f64) {
+    if a == 0.0 || b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let p = a * b;
+    if !a.is_finite() || !b.is_finite() {
+        return (p, 0.0);
+    }
+    (p, a.mul_add(b, -p))
+}
+
+/// `a / b`, for nonzero `b`, and the sign of its error
+fn quotient(a: f64, b: f64) -> (f64, f64) {
+    let q = a / b;
+    if !a.is_finite() || !b.is_finite() {
+        return (q, 0.0);
+    }
+    // The remainder a - qb is exact, and the true quotient is q + rem / b
+    let rem = (-q).mul_add(b, a);
+    (q, if rem == 0.0 { 0.0 } else { rem.signum() * b.signum() })
+}
+
+/// A closed interval of reals; the bounds may be infinite
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    /// The interval `[lo, hi]`
+    ///
+    /// # Panics
+    ///
+    /// If `lo > hi` or either bound is NaN
+    pub fn new(lo: f64, hi: f64) -> Interval {
+        assert!(lo <= hi, "Not an interval: [{}, {}]", lo, hi);
+        Interval { lo, hi }
+    }
+
+    /// The interval holding only `x`
+    pub fn point(x: f64) -> Interval {
+        Interval::new(x, x)
+    }
+
+    /// The numbers within `radius` of `mid`
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::interval::Interval;
+    ///
+    /// assert_eq!(Interval::new(1.5, 2.5), Interval::around(2.0, 0.5));
+    /// ```
+    pub fn around(mid: f64, radius: f64) -> Interval {
+        let radius = radius.abs();
+        let (lo, lo_err) = sum(mid, -radius);
+        let (hi, hi_err) = sum(mid, radius);
+        Interval::new(round_down(lo, lo_err), round_up(hi, hi_err))
+    }
+
+    /// Every real number
+    pub fn entire() -> Interval {
+        Interval { lo: f64::NEG_INFINITY, hi: f64::INFINITY }
+    }
+
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+
+    /// The midpoint
+    pub fn mid(&self) -> f64 {
+        if self.lo == f64::NEG_INFINITY && self.hi == f64::INFINITY {
+            0.0
+        } else {
+            self.lo / 2.0 + self.hi / 2.0
+        }
+    }
+
+    /// `hi - lo`, rounded up
+    pub fn width(&self) -> f64 {
+        let (w, err) = sum(self.hi, -self.lo);
+        round_up(w, err)
+    }
+
+    /// Half the width, rounded up
+    pub fn radius(&self) -> f64 {
+        self.width() / 2.0
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    pub fn is_point(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    /// The quotient, or `None` when dividing by `[0, 0]`. Dividing by an
+    /// interval holding zero gives an unbounded interval.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::interval::Interval;
+    ///
+    /// let one = Interval::point(1.0);
+    /// assert_eq!(Some(Interval::new(0.25, 0.5)), one.checked_div(&Interval::new(2.0, 4.0)));
+    /// assert_eq!(Some(Interval::new(0.5, f64::INFINITY)), one.checked_div(&Interval::new(0.0, 2.0)));
+    /// assert_eq!(Some(Interval::entire()), one.checked_div(&Interval::new(-1.0, 2.0)));
+    /// assert_eq!(None, one.checked_div(&Interval::point(0.0)));
+    /// ```
+    pub fn checked_div(&self, other: &Interval) -> Option<Interval> {
+        let (a, b) = (*self, *other);
+        if b.lo == 0.0 && b.hi == 0.0 {
+            return None;
+        }
+        if a.lo == 0.0 && a.hi == 0.0 {
+            return Some(a);
+        }
+        let down = |x, y| {
+            let (q, err) = quotient(x, y);
+            round_down(q, err)
+        };
+        let up = |x, y| {
+            let (q, err) = quotient(x, y);
+            round_up(q, err)
+        };
+        Some(if !b.contains(0.0) {
+            let lows = [down(a.lo, b.lo), down(a.lo, b.hi), down(a.hi, b.lo), down(a.hi, b.hi)];
+            let highs = [up(a.lo, b.lo), up(a.lo, b.hi), up(a.hi, b.lo), up(a.hi, b.hi)];
+            Interval {
+                lo: lows.iter().cloned().fold(f64::INFINITY, f64::min),
+                hi: highs.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            }
+        } else if b.lo == 0.0 && a.lo >= 0.0 {
+            Interval { lo: down(a.lo, b.hi), hi: f64::INFINITY }
+        } else if b.lo == 0.0 && a.hi <= 0.0 {
+            Interval { lo: f64::NEG_INFINITY, hi: up(a.hi, b.hi) }
+        } else if b.hi == 0.0 && a.lo >= 0.0 {
+            Interval { lo: f64::NEG_INFINITY, hi: up(a.lo, b.lo) }
+        } else if b.hi == 0.0 && a.hi <= 0.0 {
+            Interval { lo: down(a.hi, b.lo), hi: f64::INFINITY }
+        } else {
+            // Zero inside the divisor, or on its edge with a dividend on
+            // both sides of zero: the two pieces of the quotient cover
+            // the whole line
+            Interval::entire()
+        })
+    }
+}
+
+impl From<f64> for Interval {
+    fn from(x: f64) -> Interval {
+        Interval::point(x)
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Interval) -> Interval {
+        let (lo, lo_err) = sum(self.lo, other.lo);
+        let (hi, hi_err) = sum(self.hi, other.hi);
+        Interval { lo: round_down(lo, lo_err), hi: round_up(hi, hi_err) }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Interval) -> Interval {
+        self + -other
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, other: Interval) -> Interval {
+        let (mut lo, mut hi) = (f64::INFINITY, f64::NEG_INFINITY);
+        for &x in &[self.lo, self.hi] {
+            for &y in &[other.lo, other.hi] {
+                let (p, err) = product(x, y);
+                lo = lo.min(round_down(p, err));
+                hi = hi.max(round_up(p, err));
+            }
+        }
+        Interval { lo, hi }
+    }
+}
+
+impl Div for Interval {
+    type Output = Interval;
+
+    /// Panics when dividing by `[0, 0]`
+    fn div(self, other: Interval) -> Interval {
+        self.checked_div(&other).expect("Division by zero")
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        Interval { lo: -self.hi, hi: -self.lo }
+    }
+}
+
+/// An interval is below another when all its members are; overlapping
+/// intervals that differ are not ordered
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Interval) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else if self.hi < other.lo {
+            Some(Ordering::Less)
+        } else if self.lo > other.hi {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_point() {
+            write!(f, "{}", self.lo)
+        } else {
+            write!(f, "[{},{}]", self.lo, self.hi)
+        }
+    }
+}
+
+impl FromStr for Interval {
+    type Err = IntervalError;
+
+    /// Reads `[lo,hi]` or a number
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::interval::Interval;
+    ///
+    /// assert_eq!(Ok(Interval::new(-1.5, 2.0)), "[-1.5,2]".parse());
+    /// assert_eq!(Ok(Interval::point(3.0)), "3".parse());
+    /// assert!("[2,1]".parse::<Interval>().is_err());
+    /// ```
+    fn from_str(text: &str) -> IntervalResult<Interval> {
+        let syntax = || IntervalError::Syntax(text.to_string());
+        let number = |part: &str| match part.parse::<f64>() {
+            Ok(x) if !x.is_nan() => Ok(x),
+            _ => Err(syntax()),
+        };
+        let bounds = match text.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            None => return number(text).map(Interval::point),
+            Some(bounds) => bounds,
+        };
+        let mut parts = bounds.splitn(2, ',');
+        let lo = number(parts.next().unwrap_or(""))?;
+        let hi = number(parts.next().ok_or_else(syntax)?)?;
+        if lo > hi {
+            return Err(IntervalError::Empty(text.to_string()));
+        }
+        Ok(Interval { lo, hi })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn random_interval(rng: &mut Rng) -> Interval {
+        let mut bound = || (rng.gen_range(0, 2001) as f64 - 1000.0) / 7.0;
+        let (a, b) = (bound(), bound());
+        Interval::new(a.min(b), a.max(b))
+    }
+
+    #[test]
+    fn should_contain_every_result() {
+        let mut rng = Rng::new(256);
+        for _ in 0..2000 {
+            let (a, b) = (random_interval(&mut rng), random_interval(&mut rng));
+            // Sample members, bounds included
+            for i in 0..=4 {
+                for j in 0..=4 {
+                    let x = a.lo + (a.hi - a.lo) * i as f64 / 4.0;
+                    let y = b.lo + (b.hi - b.lo) * j as f64 / 4.0;
+                    let (x, y) = (x.min(a.hi), y.min(b.hi));
+                    assert!((a + b).contains(x + y), "{} + {}", a, b);
+                    assert!((a - b).contains(x - y), "{} - {}", a, b);
+                    assert!((a * b).contains(x * y), "{} * {}", a, b);
+                    if y != 0.0 {
+                        assert!((a / b).contains(x / y), "{} / {}", a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_round_outward_only_when_inexact() {
+        assert_eq!(Interval::point(3.0), Interval::point(1.0) + Interval::point(2.0));
+        assert_eq!(Interval::point(0.25), Interval::point(1.0) / Interval::point(4.0));
+        let sum = Interval::point(0.1) + Interval::point(0.2);
+        assert_eq!(Interval::new(0.3, 0.30000000000000004), sum);
+        let third = Interval::point(1.0) / Interval::point(3.0);
+        assert_eq!(third.lo.next_up(), third.hi);
+        assert!(third.lo < 1.0 / 3.0 || third.hi > 1.0 / 3.0);
+        let product = Interval::point(0.1) * Interval::point(0.1);
+        assert!(!product.is_point() && product.contains(0.1 * 0.1));
+        // Overflow keeps a finite lower bound
+        let huge = Interval::point(f64::MAX) + Interval::point(f64::MAX);
+        assert_eq!(Interval::new(f64::MAX, f64::INFINITY), huge);
+    }
+
+    #[test]
+    fn should_widen_division_by_zero() {
+        let (pos, neg, mixed) = (Interval::new(1.0, 2.0), Interval::new(-2.0, -1.0), Interval::new(-1.0, 1.0));
+        let (right, left) = (Interval::new(0.0, 4.0), Interval::new(-4.0, 0.0));
+        assert_eq!(Interval::new(0.25, f64::INFINITY), pos / right);
+        assert_eq!(Interval::new(f64::NEG_INFINITY, -0.25), neg / right);
+        assert_eq!(Interval::new(f64::NEG_INFINITY, -0.25), pos / left);
+        assert_eq!(Interval::new(0.25, f64::INFINITY), neg / left);
+        assert_eq!(Interval::entire(), mixed / right);
+        assert_eq!(Interval::entire(), pos / mixed);
+        assert_eq!(Interval::point(0.0), Interval::point(0.0) / mixed);
+        assert_eq!(None, pos.checked_div(&Interval::point(0.0)));
+        assert_eq!(Interval::new(0.0, 0.0), Interval::point(0.0) * Interval::entire());
+    }
+
+    #[test]
+    fn should_parse_display_and_compare() {
+        for &text in &["[1,2]", "[-0.5,0.25]", "3", "[-inf,inf]", "[1e-300,1e300]"] {
+            let x: Interval = text.parse().unwrap();
+            assert_eq!(x, x.to_string().parse().unwrap(), "{}", text);
+        }
+        assert_eq!("[1,2.5]", Interval::new(1.0, 2.5).to_string());
+        assert_eq!(Ok(Interval::point(2.0)), "[2,2]".parse());
+        assert_eq!(Err(IntervalError::Empty("[2,1]".to_string())), "[2,1]".parse::<Interval>());
+        for &bad in &["", "[]", "[1]", "[1,2", "1,2]", "[1,2,3]", "[a,b]", "[NaN,1]", "x"] {
+            assert!(bad.parse::<Interval>().is_err(), "{}", bad);
+        }
+        assert!(Interval::new(1.0, 2.0) < Interval::new(3.0, 4.0));
+        assert!(Interval::new(3.0, 4.0) > Interval::new(1.0, 2.0));
+        assert_eq!(None, Interval::new(1.0, 3.0).partial_cmp(&Interval::new(2.0, 4.0)));
+        assert_eq!(1.5, Interval::new(1.0, 2.0).mid());
+        assert_eq!(0.5, Interval::new(1.0, 2.0).radius());
+    }
+}
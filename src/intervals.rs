@@ -0,0 +1,231 @@
+//!
+//! # Problem description:
+//! #
+//! # Interval Scheduling and Meeting Rooms
+//!
+//! Three classic problems on a list of intervals, each written as a
+//! half-open pair [start, end):
+//!
+//! * Merge the overlapping intervals into a list of disjoint ones.
+//!   Sorting by start time lets a single pass extend the last merged
+//!   interval or begin a new one.
+//! * Pick as many intervals as possible with no two overlapping. The
+//!   greedy choice of the interval that ends first leaves the most room
+//!   for the rest, and it is optimal.
+//! * Find how many rooms a list of meetings needs. Sweeping through the
+//!   starts and ends in time order, the largest number of meetings going
+//!   on at once is the answer; a meeting that ends frees its room for one
+//!   starting at the same moment.
+//!
+
+use heap::BinaryHeap;
+use std::cmp::Reverse;
+
+fn check(intervals: &[(i64, i64)]) {
+    for &(start, end) in intervals {
+        assert!(start <= end, "Interval [{}, {}) ends before it starts", start, end);
+    }
+}
+
+/// Merges overlapping or touching intervals, giving disjoint intervals
+/// sorted by start. Empty intervals are dropped.
+///
+/// # Example
+/// ```
+/// use pprust::intervals::merge;
+///
+/// assert_eq!(vec![(1, 6), (8, 10)], merge(&[(5, 6), (1, 3), (8, 10), (2, 5)]));
+/// ```
+pub fn merge(intervals: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    check(intervals);
+    let mut sorted: Vec<(i64, i64)> = intervals.iter().cloned().filter(|&(start, end)| start < end).collect();
+    sorted.sort();
+    let mut merged: Vec<(i64, i64)> = Vec::new();
+    for (start, end) in sorted {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// The indices of a largest set of pairwise disjoint intervals, in the
+/// order they happen
+///
+/// # Example
+/// ```
+/// use pprust::intervals::max_non_overlapping;
+///
+/// let talks = [(9, 12), (10, 11), (11, 13), (12, 14), (13, 15)];
+/// assert_eq!(vec![1, 2, 4], max_non_overlapping(&talks));
+/// ```
+pub fn max_non_overlapping(intervals: &[(i64, i64)]) -> Vec<usize> {
+    check(intervals);
+    let mut order: Vec<usize> = (0..intervals.len()).collect();
+    order.sort_by_key(|&i| (intervals[i].1, intervals[i].0));
+    let mut chosen = Vec::new();
+    let mut free_from = i64::MIN;
+    for i in order {
+        let (start, end) = intervals[i];
+        if start >= free_from {
+            chosen.push(i);
+            free_from = end;
+        }
+    }
+    chosen
+}
+
+/// The number of rooms needed to hold all the meetings
+///
+/// # Example
+/// ```
+/// use pprust::intervals::min_rooms;
+///
+/// assert_eq!(2, min_rooms(&[(0, 30), (5, 10), (15, 20)]));
+/// assert_eq!(1, min_rooms(&[(0, 10), (10, 20)]));
+/// ```
+pub fn min_rooms(intervals: &[(i64, i64)]) -> usize {
+    check(intervals);
+    // Ends sort before starts at the same time
+    let mut events: Vec<(i64, bool)> = Vec::with_capacity(2 * intervals.len());
+    for &(start, end) in intervals.iter().filter(|&&(start, end)| start < end) {
+        events.push((start, true));
+        events.push((end, false));
+    }
+    events.sort();
+    let (mut current, mut most) = (0, 0);
+    for (_, starts) in events {
+        if starts {
+            current += 1;
+            most = most.max(current);
+        } else {
+            current -= 1;
+        }
+    }
+    most
+}
+
+/// Gives each meeting a room, numbered from 0, using `min_rooms` rooms.
+/// Meetings are placed by start time in the room that frees up first.
+///
+/// # Example
+/// ```
+/// use pprust::intervals::assign_rooms;
+///
+/// assert_eq!(vec![0, 1, 1], assign_rooms(&[(0, 30), (5, 10), (15, 20)]));
+/// ```
+pub fn assign_rooms(intervals: &[(i64, i64)]) -> Vec<usize> {
+    check(intervals);
+    let mut order: Vec<usize> = (0..intervals.len()).collect();
+    order.sort_by_key(|&i| intervals[i]);
+    let mut rooms = vec![0; intervals.len()];
+    // Busy rooms by the time they free up, earliest on top
+    let mut busy: BinaryHeap<Reverse<(i64, usize)>> = BinaryHeap::new();
+    let mut idle: Vec<usize> = Vec::new();
+    let mut opened = 0;
+    for i in order {
+        let (start, end) = intervals[i];
+        if start == end {
+            // An empty meeting fits anywhere
+            continue;
+        }
+        while let Some(&Reverse((free_at, room))) = busy.peek() {
+            if free_at > start {
+                break;
+            }
+            busy.pop();
+            idle.push(room);
+        }
+        // Reuse the lowest numbered idle room
+        idle.sort_by(|a, b| b.cmp(a));
+        let room = idle.pop().unwrap_or_else(|| {
+            opened += 1;
+            opened - 1
+        });
+        rooms[i] = room;
+        busy.push(Reverse((end, room)));
+    }
+    rooms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use combinatorics::subsets;
+    use rng::Rng;
+
+    fn random_intervals(rng: &mut Rng, n: usize, shortest: u64) -> Vec<(i64, i64)> {
+        (0..n)
+            .map(|_| {
+                let start = rng.gen_range(0, 40) as i64;
+                (start, start + rng.gen_range(shortest, 12) as i64)
+            })
+            .collect()
+    }
+
+    fn overlap(a: (i64, i64), b: (i64, i64)) -> bool {
+        a.0 < a.1 && b.0 < b.1 && a.0 < b.1 && b.0 < a.1
+    }
+
+    /// How many intervals contain each unit [t, t + 1)
+    fn coverage(intervals: &[(i64, i64)]) -> Vec<usize> {
+        (0..60).map(|t| intervals.iter().filter(|&&(start, end)| start <= t && t < end).count()).collect()
+    }
+
+    #[test]
+    fn should_merge_like_brute_force() {
+        let mut rng = Rng::new(1);
+        for _ in 0..500 {
+            let n = rng.gen_range(0, 10) as usize;
+            let intervals = random_intervals(&mut rng, n, 0);
+            let merged = merge(&intervals);
+            let covered: Vec<bool> = coverage(&intervals).iter().map(|&c| c > 0).collect();
+            assert_eq!(covered, coverage(&merged).iter().map(|&c| c > 0).collect::<Vec<_>>());
+            assert!(merged.windows(2).all(|w| w[0].1 < w[1].0), "{:?}", merged);
+            assert!(merged.iter().all(|&(start, end)| start < end));
+        }
+    }
+
+    #[test]
+    fn should_select_as_many_as_brute_force() {
+        let mut rng = Rng::new(2);
+        for _ in 0..300 {
+            let n = rng.gen_range(0, 12) as usize;
+            let intervals = random_intervals(&mut rng, n, 1);
+            let chosen = max_non_overlapping(&intervals);
+            for (k, &i) in chosen.iter().enumerate() {
+                assert!(chosen[k + 1..].iter().all(|&j| !overlap(intervals[i], intervals[j])));
+            }
+            let best = subsets(n)
+                .filter(|&mask| {
+                    let members: Vec<usize> = (0..n).filter(|&i| mask & (1 << i) != 0).collect();
+                    members.iter().enumerate().all(|(k, &i)| members[k + 1..].iter().all(|&j| !overlap(intervals[i], intervals[j])))
+                })
+                .map(|mask| mask.count_ones() as usize)
+                .max()
+                .unwrap();
+            assert_eq!(best, chosen.len(), "{:?}", intervals);
+        }
+    }
+
+    #[test]
+    fn should_need_as_many_rooms_as_deepest_overlap() {
+        let mut rng = Rng::new(3);
+        for _ in 0..500 {
+            let n = rng.gen_range(0, 20) as usize;
+            let intervals = random_intervals(&mut rng, n, 0);
+            let rooms = min_rooms(&intervals);
+            assert_eq!(coverage(&intervals).into_iter().max().unwrap(), rooms);
+            let assigned = assign_rooms(&intervals);
+            assert!(assigned.iter().all(|&room| room < rooms.max(1)));
+            for i in 0..n {
+                for j in i + 1..n {
+                    if assigned[i] == assigned[j] {
+                        assert!(!overlap(intervals[i], intervals[j]), "{:?} {:?}", intervals, assigned);
+                    }
+                }
+            }
+        }
+    }
+}
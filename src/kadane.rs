@@ -0,0 +1,137 @@
+//!
+//! # Problem description:
+//! #
+//! # Maximum Subarray
+//!
+//! Find the contiguous run of an array with the largest sum. Kadane's
+//! algorithm scans once, keeping the best sum of a run ending at the
+//! current element: either the element alone or the element added to the
+//! best run ending just before it.
+//!
+//! In two dimensions, fix a band of rows, add up each column inside the
+//! band, and run Kadane on those column sums; trying every band finds the
+//! rectangle with the largest sum in O(rows² × columns).
+//!
+
+use std::ops::Range;
+
+/// The largest sum of a non-empty contiguous run, and where it is;
+/// `None` for an empty array
+///
+/// # Example
+/// ```
+/// use pprust::kadane::max_subarray;
+///
+/// assert_eq!(Some((6, 3..7)), max_subarray(&[-2, 1, -3, 4, -1, 2, 1, -5, 4]));
+/// assert_eq!(Some((-1, 1..2)), max_subarray(&[-3, -1, -2]));
+/// assert_eq!(None, max_subarray(&[]));
+/// ```
+pub fn max_subarray(xs: &[i64]) -> Option<(i64, Range<usize>)> {
+    let mut best: Option<(i64, Range<usize>)> = None;
+    // The best run ending at the current element
+    let (mut sum, mut start) = (0i64, 0);
+    for (i, &x) in xs.iter().enumerate() {
+        if i == 0 || sum <= 0 {
+            sum = x;
+            start = i;
+        } else {
+            sum += x;
+        }
+        if best.as_ref().is_none_or(|&(value, _)| sum > value) {
+            best = Some((sum, start..i + 1));
+        }
+    }
+    best
+}
+
+/// The largest sum of a non-empty rectangle of `matrix`, with its rows
+/// and columns; `None` if the matrix is empty. Rows must have the same
+/// length.
+///
+/// # Example
+/// ```
+/// use pprust::kadane::max_submatrix;
+///
+/// let matrix = vec![
+///     vec![0, -2, -7, 0],
+///     vec![9, 2, -6, 2],
+///     vec![-4, 1, -4, 1],
+///     vec![-1, 8, 0, -2],
+/// ];
+/// assert_eq!(Some((15, 1..4, 0..2)), max_submatrix(&matrix));
+/// ```
+pub fn max_submatrix(matrix: &[Vec<i64>]) -> Option<(i64, Range<usize>, Range<usize>)> {
+    let columns = matrix.first().map_or(0, |row| row.len());
+    assert!(matrix.iter().all(|row| row.len() == columns), "Rows must have the same length");
+    let mut best: Option<(i64, Range<usize>, Range<usize>)> = None;
+    for top in 0..matrix.len() {
+        let mut sums = vec![0i64; columns];
+        for (bottom, row) in matrix.iter().enumerate().skip(top) {
+            for (sum, &x) in sums.iter_mut().zip(row) {
+                *sum += x;
+            }
+            if let Some((value, cols)) = max_subarray(&sums) {
+                if best.as_ref().is_none_or(|&(b, _, _)| value > b) {
+                    best = Some((value, top..bottom + 1, cols));
+                }
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn random(rng: &mut Rng, n: usize) -> Vec<i64> {
+        (0..n).map(|_| rng.gen_range(0, 21) as i64 - 10).collect()
+    }
+
+    #[test]
+    fn should_agree_with_cubic_brute_force() {
+        let mut rng = Rng::new(253);
+        for _ in 0..1000 {
+            let n = rng.gen_range(1, 15) as usize;
+            let xs = random(&mut rng, n);
+            let mut best = i64::MIN;
+            for i in 0..n {
+                for j in i + 1..=n {
+                    best = best.max(xs[i..j].iter().sum());
+                }
+            }
+            let (value, range) = max_subarray(&xs).unwrap();
+            assert_eq!(best, value, "{:?}", xs);
+            assert!(!range.is_empty());
+            assert_eq!(value, xs[range].iter().sum::<i64>());
+        }
+    }
+
+    #[test]
+    fn should_agree_with_brute_force_in_two_dimensions() {
+        let mut rng = Rng::new(2);
+        for _ in 0..200 {
+            let (rows, columns) = (rng.gen_range(1, 6) as usize, rng.gen_range(1, 6) as usize);
+            let matrix: Vec<Vec<i64>> = (0..rows).map(|_| random(&mut rng, columns)).collect();
+            let sum = |r: &Range<usize>, c: &Range<usize>| -> i64 {
+                matrix[r.clone()].iter().map(|row| row[c.clone()].iter().sum::<i64>()).sum()
+            };
+            let mut best = i64::MIN;
+            for r0 in 0..rows {
+                for r1 in r0 + 1..=rows {
+                    for c0 in 0..columns {
+                        for c1 in c0 + 1..=columns {
+                            best = best.max(sum(&(r0..r1), &(c0..c1)));
+                        }
+                    }
+                }
+            }
+            let (value, r, c) = max_submatrix(&matrix).unwrap();
+            assert_eq!(best, value, "{:?}", matrix);
+            assert_eq!(value, sum(&r, &c));
+        }
+        assert_eq!(None, max_submatrix(&[]));
+        assert_eq!(None, max_submatrix(&[vec![], vec![]]));
+    }
+}
@@ -0,0 +1,173 @@
+//!
+//! # Problem description:
+//! #
+//! # Latin Squares
+//!
+//! A latin square of order *n* is an *n* by *n* grid of *n* symbols in
+//! which each symbol appears exactly once in every row and every column.
+//! Two latin squares are orthogonal when superimposing them gives each
+//! of the n² ordered pairs of symbols exactly once; Euler looked for
+//! such pairs, famously finding none of order 6.
+//!
+//! Random squares are built by constraint propagation: every empty cell
+//! keeps the set of symbols not yet used in its row and column, the cell
+//! with fewest candidates is filled next with a random one of them, and
+//! the search backs up when some cell runs out of candidates.
+//!
+
+use rng::Rng;
+
+/// A square grid of symbols `0..n`, row by row
+pub type Square = Vec<Vec<usize>>;
+
+/// Builds a random latin square of order `n` (at most 64) on the symbols
+/// `0..n`; equal seeds give equal squares
+///
+/// # Example
+/// ```
+/// use pprust::latin::{random_square, is_latin};
+///
+/// let square = random_square(7, 2024);
+/// assert!(is_latin(&square));
+/// assert_eq!(square, random_square(7, 2024));
+/// ```
+pub fn random_square(n: usize, seed: u64) -> Square {
+    assert!(n <= 64, "Latin squares are limited to order 64");
+    let mut rng = Rng::new(seed);
+    let all = if n == 64 { u64::MAX } else { (1 << n) - 1 };
+    let mut cells = vec![None; n * n];
+    let mut candidates = vec![all; n * n];
+    let filled = fill(n, &mut cells, &mut candidates, &mut rng);
+    assert!(filled, "Every order has a latin square");
+    cells.chunks(n.max(1))
+        .map(|row| row.iter().map(|c| c.expect("Filled square")).collect())
+        .collect()
+}
+
+fn fill(n: usize, cells: &mut [Option<usize>], candidates: &mut Vec<u64>, rng: &mut Rng) -> bool {
+    let next = (0..n * n)
+        .filter(|&c| cells[c].is_none())
+        .min_by_key(|&c| candidates[c].count_ones());
+    let cell = match next {
+        Some(cell) => cell,
+        None => return true,
+    };
+    let (row, col) = (cell / n, cell % n);
+    let mut options: Vec<usize> = (0..n).filter(|&s| candidates[cell] & (1 << s) != 0).collect();
+    while !options.is_empty() {
+        let symbol = options.swap_remove(rng.gen_range(0, options.len() as u64) as usize);
+        let saved = candidates.clone();
+        cells[cell] = Some(symbol);
+        let mut dead_end = false;
+        for peer in (0..n).map(|j| row * n + j).chain((0..n).map(|i| i * n + col)) {
+            if cells[peer].is_none() {
+                candidates[peer] &= !(1 << symbol);
+                dead_end |= candidates[peer] == 0;
+            }
+        }
+        if !dead_end && fill(n, cells, candidates, rng) {
+            return true;
+        }
+        cells[cell] = None;
+        *candidates = saved;
+    }
+    false
+}
+
+/// Checks that every row and column holds each of the symbols `0..n` once
+///
+/// # Example
+/// ```
+/// use pprust::latin::is_latin;
+///
+/// assert!(is_latin(&[vec![0, 1], vec![1, 0]]));
+/// assert!(!is_latin(&[vec![0, 1], vec![0, 1]]));
+/// ```
+pub fn is_latin(square: &[Vec<usize>]) -> bool {
+    let n = square.len();
+    if square.iter().any(|row| row.len() != n) {
+        return false;
+    }
+    (0..n).all(|i| is_permutation(n, square[i].iter().cloned()))
+        && (0..n).all(|j| is_permutation(n, square.iter().map(|row| row[j])))
+}
+
+fn is_permutation<I: Iterator<Item = usize>>(n: usize, mut line: I) -> bool {
+    let mut seen = vec![false; n];
+    line.all(|s| s < n && !std::mem::replace(&mut seen[s], true))
+}
+
+/// Checks that two latin squares of the same order are orthogonal
+///
+/// # Example
+/// ```
+/// use pprust::latin::are_orthogonal;
+///
+/// let a = vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1]];
+/// let b = vec![vec![0, 1, 2], vec![2, 0, 1], vec![1, 2, 0]];
+/// assert!(are_orthogonal(&a, &b));
+/// assert!(!are_orthogonal(&a, &a));
+/// ```
+pub fn are_orthogonal(a: &[Vec<usize>], b: &[Vec<usize>]) -> bool {
+    let n = a.len();
+    if b.len() != n || !is_latin(a) || !is_latin(b) {
+        return false;
+    }
+    let mut seen = vec![false; n * n];
+    for i in 0..n {
+        for j in 0..n {
+            let pair = a[i][j] * n + b[i][j];
+            if seen[pair] {
+                return false;
+            }
+            seen[pair] = true;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_generate_latin_squares() {
+        for n in 0..=16 {
+            for seed in 0..5 {
+                let square = random_square(n, seed);
+                assert_eq!(n, square.len());
+                assert!(is_latin(&square), "Order {}, seed {}: {:?}", n, seed, square);
+            }
+        }
+    }
+
+    #[test]
+    fn should_vary_with_seed() {
+        let squares: Vec<Square> = (0..10).map(|seed| random_square(6, seed)).collect();
+        assert!(squares.iter().any(|s| *s != squares[0]));
+    }
+
+    #[test]
+    fn should_reject_bad_squares() {
+        assert!(!is_latin(&[vec![0, 1], vec![1]]));
+        assert!(!is_latin(&[vec![0, 2], vec![2, 0]]));
+        assert!(!is_latin(&[vec![0, 1], vec![0, 1]]));
+        assert!(is_latin(&[]));
+    }
+
+    #[test]
+    fn should_find_no_orthogonal_mate_of_order_two() {
+        let a = vec![vec![0, 1], vec![1, 0]];
+        let b = vec![vec![1, 0], vec![0, 1]];
+        assert!(!are_orthogonal(&a, &b));
+    }
+
+    #[test]
+    fn should_check_orthogonality_of_order_four() {
+        let a = vec![vec![0, 1, 2, 3], vec![1, 0, 3, 2], vec![2, 3, 0, 1], vec![3, 2, 1, 0]];
+        let b = vec![vec![0, 1, 2, 3], vec![2, 3, 0, 1], vec![3, 2, 1, 0], vec![1, 0, 3, 2]];
+        assert!(are_orthogonal(&a, &b));
+        assert!(are_orthogonal(&b, &a));
+        assert!(!are_orthogonal(&a, &b[..3]));
+    }
+}
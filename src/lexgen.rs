@@ -0,0 +1,214 @@
+//!
+//! # Problem description:
+//! #
+//! # Lexer Generator
+//!
+//! Write a lexer generator in the spirit of lex: the kinds of tokens are
+//! given by regular expressions, and the lexer it makes splits text into
+//! tokens of those kinds, one at a time, as they are asked for.
+//!
+//! At each position every pattern is tried, anchored there, and the
+//! longest match wins; between matches of the same length the pattern
+//! given first wins, so keywords go before the pattern for names.
+//! Patterns marked to skip, like whitespace, make no token. A pattern
+//! only matching the empty string never makes a token, so a position no
+//! pattern can take a character at is an error.
+//!
+
+use regex_lite::{Regex, RegexResult};
+use std::error;
+use std::fmt;
+use std::result;
+
+/// A character no pattern matches, at a byte offset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LexError {
+    pub offset: usize,
+    pub found: char,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unexpected '{}' at {}", self.found, self.offset)
+    }
+}
+
+impl error::Error for LexError {}
+
+/// A token: its kind, its text and the byte offset where it starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Token<'a, K> {
+    pub kind: K,
+    pub text: &'a str,
+    pub offset: usize,
+}
+
+/// A lexer made from a list of patterns, each with the kind of token it
+/// makes, or none for text to skip
+///
+/// # Example
+/// ```
+/// use pprust::lexgen::Lexer;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Kind { Let, Name, Number, Equals }
+///
+/// let lexer = Lexer::new()
+///     .token(Kind::Let, "let").unwrap()
+///     .token(Kind::Name, "[a-z]+").unwrap()
+///     .token(Kind::Number, "[0-9]+").unwrap()
+///     .token(Kind::Equals, "=").unwrap()
+///     .skip("\\s+").unwrap();
+/// let kinds: Vec<Kind> = lexer.tokens("let lettuce = 12").map(|t| t.unwrap().kind).collect();
+/// assert_eq!(vec![Kind::Let, Kind::Name, Kind::Equals, Kind::Number], kinds);
+/// ```
+#[derive(Debug)]
+pub struct Lexer<K> {
+    rules: Vec<(Option<K>, Regex)>,
+}
+
+impl<K: Copy> Default for Lexer<K> {
+    fn default() -> Self {
+        Lexer::new()
+    }
+}
+
+impl<K: Copy> Lexer<K> {
+    /// A lexer with no patterns yet
+    pub fn new() -> Lexer<K> {
+        Lexer { rules: Vec::new() }
+    }
+
+    /// Adds a pattern for tokens of a kind
+    pub fn token(mut self, kind: K, pattern: &str) -> RegexResult<Lexer<K>> {
+        self.rules.push((Some(kind), Regex::new(pattern)?));
+        Ok(self)
+    }
+
+    /// Adds a pattern for text between tokens, like whitespace or comments
+    pub fn skip(mut self, pattern: &str) -> RegexResult<Lexer<K>> {
+        self.rules.push((None, Regex::new(pattern)?));
+        Ok(self)
+    }
+
+    /// The tokens of a text, made as they are taken; the iterator ends
+    /// after the first error
+    pub fn tokens<'l, 't>(&'l self, text: &'t str) -> Tokens<'l, 't, K> {
+        Tokens { lexer: self, text, offset: 0 }
+    }
+
+    /// All the tokens of a text, or the first error
+    pub fn tokenize<'t>(&self, text: &'t str) -> result::Result<Vec<Token<'t, K>>, LexError> {
+        self.tokens(text).collect()
+    }
+
+    /// The longest match at the start of the text, and the kind of token
+    /// of its pattern, the first on a tie
+    fn longest(&self, text: &str) -> Option<(usize, Option<K>)> {
+        let mut best: Option<(usize, Option<K>)> = None;
+        for &(kind, ref regex) in &self.rules {
+            match regex.match_prefix(text) {
+                Some(length) if length > best.map_or(0, |(longest, _)| longest) => best = Some((length, kind)),
+                _ => (),
+            }
+        }
+        best
+    }
+}
+
+/// The iterator over the tokens of a text
+#[derive(Debug)]
+pub struct Tokens<'l, 't, K: 'l> {
+    lexer: &'l Lexer<K>,
+    text: &'t str,
+    offset: usize,
+}
+
+impl<'l, 't, K: Copy> Iterator for Tokens<'l, 't, K> {
+    type Item = result::Result<Token<'t, K>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.text.len() {
+            let rest = &self.text[self.offset..];
+            let offset = self.offset;
+            match self.lexer.longest(rest) {
+                Some((length, kind)) => {
+                    self.offset += length;
+                    if let Some(kind) = kind {
+                        return Some(Ok(Token { kind, text: &rest[..length], offset }));
+                    }
+                }
+                None => {
+                    self.offset = self.text.len();
+                    let found = rest.chars().next().expect("Not at the end of the text");
+                    return Some(Err(LexError { offset, found }));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Kind {
+        If,
+        Name,
+        Number,
+        Less,
+        LessEqual,
+    }
+
+    fn lexer() -> Lexer<Kind> {
+        Lexer::new()
+            .token(Kind::If, "if")
+            .and_then(|l| l.token(Kind::Name, "[a-z]\\w*"))
+            .and_then(|l| l.token(Kind::Number, "\\d+(\\.\\d+)?"))
+            .and_then(|l| l.token(Kind::Less, "<"))
+            .and_then(|l| l.token(Kind::LessEqual, "<="))
+            .and_then(|l| l.skip("\\s+|#[^\n]*"))
+            .expect("Patterns should compile")
+    }
+
+    #[test]
+    fn should_take_the_longest_match_then_the_first_pattern() {
+        let lexer = lexer();
+        let tokens = lexer.tokenize("if iffy<=3.5 # if\n<2").unwrap();
+        let expected = [
+            (Kind::If, "if", 0),
+            (Kind::Name, "iffy", 3),
+            (Kind::LessEqual, "<=", 7),
+            (Kind::Number, "3.5", 9),
+            (Kind::Less, "<", 18),
+            (Kind::Number, "2", 19),
+        ];
+        let tokens: Vec<(Kind, &str, usize)> = tokens.iter().map(|t| (t.kind, t.text, t.offset)).collect();
+        assert_eq!(expected.to_vec(), tokens);
+        assert_eq!(Ok(vec![]), lexer.tokenize("  # nothing"));
+    }
+
+    #[test]
+    fn should_stop_at_a_character_no_pattern_takes() {
+        let lexer = lexer();
+        let mut tokens = lexer.tokens("a é b");
+        assert_eq!(Some(Ok(Token { kind: Kind::Name, text: "a", offset: 0 })), tokens.next());
+        assert_eq!(Some(Err(LexError { offset: 2, found: 'é' })), tokens.next());
+        assert_eq!(None, tokens.next());
+        let empty = Lexer::new().token(Kind::Name, "x*").unwrap();
+        assert_eq!(Err(LexError { offset: 0, found: 'y' }), empty.tokenize("y"));
+    }
+
+    #[test]
+    fn should_take_linear_time() {
+        // Each match only reads as far as it goes; reading the rest of the
+        // text for every token would take minutes here
+        let lexer = lexer();
+        let text = "if x1 <= 25 ".repeat(20_000);
+        let start = ::std::time::Instant::now();
+        assert_eq!(80_000, lexer.tokens(&text).count());
+        assert!(start.elapsed() < ::std::time::Duration::from_secs(10), "Took {:?}", start.elapsed());
+    }
+}
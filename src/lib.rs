@@ -2,4 +2,82 @@
 //! My attempt at implementing problems from [Programming Praxis](https://programmingpraxis.com)
 //!
 
+extern crate serde;
+extern crate serde_json;
+
+pub mod baseenc;
+pub mod bigint;
+pub mod bingo;
+pub mod bitio;
+pub mod blackjack;
+pub mod boggle;
+pub mod cache;
+pub mod checksum;
+pub mod combinatorics;
+pub mod complex;
+pub mod connect4;
+pub mod consistent_hash;
+pub mod crossword;
+pub mod cryptanalysis;
+pub mod cryptarithm;
+pub mod decimal;
+pub mod dh;
+pub mod digest;
+pub mod egyptian;
+pub mod enigma;
+pub mod exprc;
+pub mod extsort;
+pub mod farey;
+pub mod galton;
+pub mod gametree;
+pub mod geometry;
+pub mod goldbach;
+pub mod graycode;
+pub mod hangman;
+pub mod heap;
+pub mod infix;
+pub mod interval;
+pub mod intervals;
+pub mod kadane;
+pub mod latin;
+pub mod lexgen;
+pub mod lis;
+pub mod magic;
+pub mod mastermind;
+pub mod matching;
+pub mod matrix;
+pub mod mdlite;
+pub mod memo;
+pub mod money;
+pub mod numtheory;
+pub mod pascal;
+pub mod pegsolitaire;
+pub mod poly;
+pub mod puzzle8;
+pub mod pythag;
+pub mod rational;
+pub mod regex_lite;
+pub mod rng;
+pub mod rngcrack;
+pub mod roman;
+pub mod rope;
 pub mod rpncalculator;
+pub mod scheduler;
+pub mod select;
+pub mod shuffle;
+pub mod sieve;
+pub mod snakes;
+pub mod sort;
+pub mod stego;
+pub mod strsearch;
+pub mod suffix;
+pub mod tail;
+pub mod tictactoe;
+pub mod tinybasic;
+pub mod trie;
+pub mod ulam;
+pub mod units;
+pub mod utf8lite;
+pub mod wordle;
+pub mod wordsearch;
+pub mod zeckendorf;
@@ -0,0 +1,149 @@
+//!
+//! # Problem description:
+//! #
+//! # Longest Increasing Subsequence
+//!
+//! Find the longest strictly increasing subsequence of a sequence, not
+//! necessarily contiguous: 3 1 4 1 5 9 2 6 has 1 4 5 9 among others.
+//!
+//! The fast method comes from a solitaire game. Deal the cards one at a
+//! time onto piles, always on the leftmost pile whose top card is not
+//! smaller, or on a new pile to the right when there is none. The tops
+//! of the piles stay in increasing order, so the right pile is found by
+//! binary search, and the number of piles at the end is the length of the
+//! longest increasing subsequence. Remembering, for each card, the top of
+//! the pile to its left when it was dealt lets the subsequence be read
+//! back from the last pile, for O(n log n) in all.
+//!
+
+/// Deals `cards` into patience piles, each listed from the bottom up
+///
+/// # Example
+/// ```
+/// use pprust::lis::deal;
+///
+/// let piles = deal(&[7, 2, 8, 1, 3, 4, 10, 6, 9, 5]);
+/// assert_eq!(vec![vec![7, 2, 1], vec![8, 3], vec![4], vec![10, 6, 5], vec![9]], piles);
+/// ```
+pub fn deal<T: Ord + Clone>(cards: &[T]) -> Vec<Vec<T>> {
+    let mut piles: Vec<Vec<T>> = Vec::new();
+    for card in cards {
+        let pile = piles.partition_point(|pile| pile.last().unwrap() < card);
+        if pile == piles.len() {
+            piles.push(Vec::new());
+        }
+        piles[pile].push(card.clone());
+    }
+    piles
+}
+
+/// The length of the longest strictly increasing subsequence
+///
+/// # Example
+/// ```
+/// use pprust::lis::length;
+///
+/// assert_eq!(4, length(&[3, 1, 4, 1, 5, 9, 2, 6]));
+/// assert_eq!(0, length::<i32>(&[]));
+/// ```
+pub fn length<T: Ord>(seq: &[T]) -> usize {
+    let mut tops: Vec<&T> = Vec::new();
+    for x in seq {
+        let pile = tops.partition_point(|&top| top < x);
+        if pile == tops.len() {
+            tops.push(x);
+        } else {
+            tops[pile] = x;
+        }
+    }
+    tops.len()
+}
+
+/// The indices of a longest strictly increasing subsequence
+///
+/// # Example
+/// ```
+/// use pprust::lis::reconstruct;
+///
+/// let seq = [3, 1, 4, 1, 5, 9, 2, 6];
+/// let indices = reconstruct(&seq);
+/// let values: Vec<i32> = indices.iter().map(|&i| seq[i]).collect();
+/// assert_eq!(vec![1, 4, 5, 6], values);
+/// ```
+pub fn reconstruct<T: Ord>(seq: &[T]) -> Vec<usize> {
+    // Index of the card on top of each pile
+    let mut tops: Vec<usize> = Vec::new();
+    let mut predecessor: Vec<Option<usize>> = Vec::with_capacity(seq.len());
+    for (i, x) in seq.iter().enumerate() {
+        let pile = tops.partition_point(|&top| seq[top] < *x);
+        predecessor.push(if pile == 0 { None } else { Some(tops[pile - 1]) });
+        if pile == tops.len() {
+            tops.push(i);
+        } else {
+            tops[pile] = i;
+        }
+    }
+    let mut indices = Vec::with_capacity(tops.len());
+    let mut next = tops.last().cloned();
+    while let Some(i) = next {
+        indices.push(i);
+        next = predecessor[i];
+    }
+    indices.reverse();
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use combinatorics::subsets;
+    use rng::Rng;
+    use shuffle::permutation;
+
+    fn brute_force(seq: &[u64]) -> usize {
+        subsets(seq.len())
+            .filter(|&mask| {
+                let chosen: Vec<u64> = (0..seq.len()).filter(|&i| mask & (1 << i) != 0).map(|i| seq[i]).collect();
+                chosen.windows(2).all(|w| w[0] < w[1])
+            })
+            .map(|mask| mask.count_ones() as usize)
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_agree_with_brute_force() {
+        let mut rng = Rng::new(252);
+        for _ in 0..300 {
+            let n = rng.gen_range(0, 13) as usize;
+            let seq: Vec<u64> = (0..n).map(|_| rng.gen_range(0, 8)).collect();
+            let best = brute_force(&seq);
+            assert_eq!(best, length(&seq), "{:?}", seq);
+            assert_eq!(best, deal(&seq).len());
+            let indices = reconstruct(&seq);
+            assert_eq!(best, indices.len());
+            assert!(indices.windows(2).all(|w| w[0] < w[1] && seq[w[0]] < seq[w[1]]), "{:?} {:?}", seq, indices);
+        }
+    }
+
+    #[test]
+    fn should_handle_monotone_sequences() {
+        let up: Vec<u32> = (0..1000).collect();
+        assert_eq!(1000, length(&up));
+        assert_eq!(up.iter().map(|&x| x as usize).collect::<Vec<_>>(), reconstruct(&up));
+        let down: Vec<u32> = (0..1000).rev().collect();
+        assert_eq!(1, length(&down));
+        assert_eq!(1, deal(&down).len());
+        assert_eq!(1, length(&[5; 10]));
+    }
+
+    #[test]
+    fn should_deal_about_two_root_n_piles() {
+        // Shuffled decks of 52 cards average about 11.6 piles
+        let mut rng = Rng::new(52);
+        let deals = 2000;
+        let total: usize = (0..deals).map(|_| deal(&permutation(52, &mut rng)).len()).sum();
+        let mean = total as f64 / deals as f64;
+        assert!(10.8 < mean && mean < 12.4, "Mean was {}", mean);
+    }
+}
@@ -0,0 +1,199 @@
+//!
+//! # Problem description:
+//! #
+//! # Magic Squares
+//!
+//! A magic square of order *n* holds the numbers 1 to n² so that every
+//! row, every column and both main diagonals add up to the same magic
+//! constant, n(n² + 1) / 2. There are magic squares of every order
+//! except 2, built three different ways:
+//!
+//! * odd orders with the Siamese method, walking diagonally up and right
+//!   and stepping down when the next cell is taken;
+//! * doubly-even orders (multiples of 4) by writing the numbers in order
+//!   and complementing the cells on the diagonals of each 4x4 block;
+//! * singly-even orders (4k + 2) with Strachey's method, which builds
+//!   four odd squares in the quadrants and swaps some of their columns.
+//!
+
+/// A square grid of numbers, row by row
+pub type Grid = Vec<Vec<u64>>;
+
+/// Returns the magic constant of a normal magic square of order `n`
+pub fn magic_constant(n: usize) -> u64 {
+    let n = n as u64;
+    n * (n * n + 1) / 2
+}
+
+/// Builds a magic square of order `n`, or `None` if there is none
+/// (orders 0 and 2)
+///
+/// # Example
+/// ```
+/// use pprust::magic::{generate, is_magic};
+///
+/// let square = generate(3).unwrap();
+/// assert_eq!(vec![vec![8, 1, 6], vec![3, 5, 7], vec![4, 9, 2]], square);
+/// assert!(is_magic(&square));
+/// assert_eq!(None, generate(2));
+/// ```
+pub fn generate(n: usize) -> Option<Grid> {
+    match n {
+        0 | 2 => None,
+        n if n % 2 == 1 => Some(siamese(n)),
+        n if n % 4 == 0 => Some(doubly_even(n)),
+        n => Some(singly_even(n)),
+    }
+}
+
+fn siamese(n: usize) -> Grid {
+    let mut grid = vec![vec![0; n]; n];
+    let (mut row, mut col) = (0, n / 2);
+    for value in 1..=(n * n) as u64 {
+        grid[row][col] = value;
+        let (up, right) = ((row + n - 1) % n, (col + 1) % n);
+        if grid[up][right] == 0 {
+            row = up;
+            col = right;
+        } else {
+            row = (row + 1) % n;
+        }
+    }
+    grid
+}
+
+fn doubly_even(n: usize) -> Grid {
+    let total = (n * n) as u64 + 1;
+    (0..n).map(|i| {
+        (0..n).map(|j| {
+            let value = (i * n + j) as u64 + 1;
+            let on_block_diagonal = i % 4 == j % 4 || (i % 4) + (j % 4) == 3;
+            if on_block_diagonal { total - value } else { value }
+        }).collect()
+    }).collect()
+}
+
+fn singly_even(n: usize) -> Grid {
+    let m = n / 2;
+    let quarter = (m * m) as u64;
+    let sub = siamese(m);
+    let mut grid = vec![vec![0; n]; n];
+    // Quadrants A (top left), B (bottom right), C (top right) and D (bottom left)
+    for i in 0..m {
+        for j in 0..m {
+            grid[i][j] = sub[i][j];
+            grid[i + m][j + m] = sub[i][j] + quarter;
+            grid[i][j + m] = sub[i][j] + 2 * quarter;
+            grid[i + m][j] = sub[i][j] + 3 * quarter;
+        }
+    }
+    let k = (n - 2) / 4;
+    for i in 0..m {
+        // The middle row of A swaps the k columns after its first one instead
+        let left = if i == m / 2 { 1..k + 1 } else { 0..k };
+        for j in left.chain(n - k + 1..n) {
+            let lower = grid[i + m][j];
+            grid[i + m][j] = grid[i][j];
+            grid[i][j] = lower;
+        }
+    }
+    grid
+}
+
+/// Checks that a grid is a normal magic square: square, holding each of
+/// the numbers 1 to n² once, with all rows, columns and both diagonals
+/// adding up to the magic constant
+pub fn is_magic(grid: &[Vec<u64>]) -> bool {
+    let n = grid.len();
+    if n == 0 || grid.iter().any(|row| row.len() != n) {
+        return false;
+    }
+    let mut seen = vec![false; n * n + 1];
+    for &value in grid.iter().flat_map(|row| row.iter()) {
+        if value == 0 || value as usize > n * n || seen[value as usize] {
+            return false;
+        }
+        seen[value as usize] = true;
+    }
+    let target = magic_constant(n);
+    let rows = grid.iter().all(|row| row.iter().sum::<u64>() == target);
+    let columns = (0..n).all(|j| grid.iter().map(|row| row[j]).sum::<u64>() == target);
+    let diagonal = (0..n).map(|i| grid[i][i]).sum::<u64>() == target;
+    let anti_diagonal = (0..n).map(|i| grid[i][n - 1 - i]).sum::<u64>() == target;
+    rows && columns && diagonal && anti_diagonal
+}
+
+/// Formats a grid with right-aligned columns inside a box
+///
+/// # Example
+/// ```
+/// use pprust::magic::{generate, format_grid};
+///
+/// let expected = "\
+/// +-------+
+/// | 8 1 6 |
+/// | 3 5 7 |
+/// | 4 9 2 |
+/// +-------+
+/// ";
+/// assert_eq!(expected, format_grid(&generate(3).unwrap()));
+/// ```
+pub fn format_grid(grid: &[Vec<u64>]) -> String {
+    let width = grid.iter().flat_map(|row| row.iter()).map(|v| v.to_string().len()).max().unwrap_or(1);
+    let columns = grid.first().map_or(0, |row| row.len());
+    let border = format!("+{}+\n", "-".repeat(columns * (width + 1) + 1));
+    let mut result = border.clone();
+    for row in grid {
+        result.push('|');
+        for value in row {
+            result.push_str(&format!(" {:>width$}", value, width = width));
+        }
+        result.push_str(" |\n");
+    }
+    result.push_str(&border);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_generate_magic_squares_of_every_order() {
+        for n in (1..=20).filter(|&n| n != 2) {
+            let square = generate(n).unwrap_or_else(|| panic!("Order {} should have a magic square", n));
+            assert!(is_magic(&square), "Order {} square is not magic:\n{}", n, format_grid(&square));
+        }
+    }
+
+    #[test]
+    fn should_not_generate_impossible_orders() {
+        assert_eq!(None, generate(0));
+        assert_eq!(None, generate(2));
+    }
+
+    #[test]
+    fn should_generate_durers_square_layout() {
+        let square = generate(4).unwrap();
+        assert_eq!(vec![16, 2, 3, 13], square[0]);
+        assert_eq!(34, magic_constant(4));
+    }
+
+    #[test]
+    fn should_reject_non_magic_grids() {
+        assert!(!is_magic(&[]));
+        assert!(!is_magic(&[vec![1, 2], vec![3, 4]]));
+        assert!(!is_magic(&[vec![1, 2, 3]]));
+        let mut square = generate(5).unwrap();
+        square[0].swap(0, 1);
+        assert!(!is_magic(&square));
+        let repeated = vec![vec![5, 5, 5], vec![5, 5, 5], vec![5, 5, 5]];
+        assert!(!is_magic(&repeated), "Values must be 1 to n² exactly once");
+    }
+
+    #[test]
+    fn should_align_wide_values() {
+        let formatted = format_grid(&generate(4).unwrap());
+        assert!(formatted.contains("| 16  2  3 13 |"), "{}", formatted);
+    }
+}
@@ -0,0 +1,245 @@
+//!
+//! # Problem description:
+//! #
+//! # Mastermind
+//!
+//! One player chooses a secret code of four pegs, each one of six
+//! colours. The other guesses codes, and after each guess is told how
+//! many pegs are the right colour in the right place (black) and how
+//! many more are the right colour in the wrong place (white).
+//!
+//! Knuth showed that the code can always be found in five guesses. Keep
+//! the codes still consistent with every answer so far; start with
+//! 1122, and then guess the code, among all 1296, whose worst answer
+//! leaves the fewest candidates. Ties go to a guess that could itself be
+//! the secret, then to the smallest code.
+//!
+
+use rng::Rng;
+
+/// Number of pegs in a code
+pub const PEGS: usize = 4;
+/// Number of colours, numbered from 0
+pub const COLORS: u8 = 6;
+
+/// A code, one colour per peg
+pub type Code = [u8; PEGS];
+
+/// The answer to a guess
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    /// Right colour in the right place
+    pub black: u8,
+    /// Right colour in the wrong place
+    pub white: u8,
+}
+
+impl Score {
+    fn index(self) -> usize {
+        self.black as usize * (PEGS + 1) + self.white as usize
+    }
+}
+
+/// Scores a guess against the secret
+///
+/// # Example
+/// ```
+/// use pprust::mastermind::{score, Score};
+///
+/// assert_eq!(Score { black: 1, white: 2 }, score(&[0, 1, 2, 3], &[0, 2, 1, 4]));
+/// ```
+pub fn score(secret: &Code, guess: &Code) -> Score {
+    let black = secret.iter().zip(guess.iter()).filter(|&(s, g)| s == g).count() as u8;
+    let mut counts = [0i8; COLORS as usize];
+    for &c in secret.iter() {
+        counts[c as usize] += 1;
+    }
+    let mut common = 0;
+    for &c in guess.iter() {
+        if counts[c as usize] > 0 {
+            counts[c as usize] -= 1;
+            common += 1;
+        }
+    }
+    Score { black, white: common - black }
+}
+
+/// Every possible code, in increasing order
+pub fn all_codes() -> Vec<Code> {
+    let total = (COLORS as usize).pow(PEGS as u32);
+    (0..total).map(|mut n| {
+        let mut code = [0; PEGS];
+        for peg in code.iter_mut().rev() {
+            *peg = (n % COLORS as usize) as u8;
+            n /= COLORS as usize;
+        }
+        code
+    }).collect()
+}
+
+/// A random code
+pub fn random_code(rng: &mut Rng) -> Code {
+    let mut code = [0; PEGS];
+    for peg in code.iter_mut() {
+        *peg = rng.gen_range(0, COLORS as u64) as u8;
+    }
+    code
+}
+
+/// Knuth's choice of the next guess given the codes still possible
+pub fn next_guess(candidates: &[Code]) -> Code {
+    if candidates.len() == 1 {
+        return candidates[0];
+    }
+    if candidates.len() == (COLORS as usize).pow(PEGS as u32) {
+        return [0, 0, 1, 1];
+    }
+    let mut best = None;
+    for guess in all_codes() {
+        let mut partitions = [0usize; (PEGS + 1) * (PEGS + 1)];
+        for candidate in candidates {
+            partitions[score(candidate, &guess).index()] += 1;
+        }
+        let worst = *partitions.iter().max().unwrap();
+        let key = (worst, !candidates.contains(&guess));
+        if best.as_ref().is_none_or(|&(k, _)| key < k) {
+            best = Some((key, guess));
+        }
+    }
+    best.expect("There are codes to guess").1
+}
+
+/// Plays Knuth's strategy, keeping the candidates between guesses
+pub struct Solver {
+    candidates: Vec<Code>,
+}
+
+impl Solver {
+    /// Starts with every code possible
+    pub fn new() -> Solver {
+        Solver { candidates: all_codes() }
+    }
+
+    /// The codes consistent with every answer so far
+    pub fn candidates(&self) -> &[Code] {
+        &self.candidates
+    }
+
+    /// The next guess, or `None` if the answers were inconsistent
+    pub fn guess(&self) -> Option<Code> {
+        if self.candidates.is_empty() {
+            None
+        } else {
+            Some(next_guess(&self.candidates))
+        }
+    }
+
+    /// Keeps only the codes that would have given this answer to this
+    /// guess
+    pub fn answer(&mut self, guess: &Code, answer: Score) {
+        self.candidates.retain(|c| score(c, guess) == answer);
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Solver {
+        Solver::new()
+    }
+}
+
+/// The guesses Knuth's strategy makes to find a secret, the last one
+/// being the secret
+///
+/// # Example
+/// ```
+/// use pprust::mastermind::solve;
+///
+/// let guesses = solve(&[5, 4, 3, 2]);
+/// assert!(guesses.len() <= 5);
+/// assert_eq!(Some(&[5, 4, 3, 2]), guesses.last());
+/// ```
+pub fn solve(secret: &Code) -> Vec<Code> {
+    let mut solver = Solver::new();
+    let mut guesses = Vec::new();
+    while let Some(guess) = solver.guess() {
+        guesses.push(guess);
+        if guess == *secret {
+            break;
+        }
+        solver.answer(&guess, score(secret, &guess));
+    }
+    guesses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_score_guesses() {
+        assert_eq!(Score { black: 4, white: 0 }, score(&[1, 2, 3, 4], &[1, 2, 3, 4]));
+        assert_eq!(Score { black: 0, white: 4 }, score(&[1, 2, 3, 4], &[4, 3, 2, 1]));
+        assert_eq!(Score { black: 0, white: 0 }, score(&[1, 1, 1, 1], &[2, 2, 2, 2]));
+        assert_eq!(Score { black: 1, white: 1 }, score(&[1, 1, 2, 2], &[1, 3, 3, 1]));
+        assert_eq!(Score { black: 0, white: 1 }, score(&[1, 2, 2, 2], &[3, 1, 1, 1]), "Only one 1 can be white");
+        assert_eq!(Score { black: 2, white: 0 }, score(&[0, 0, 1, 1], &[0, 0, 0, 0]));
+    }
+
+    /// Follows every branch of the strategy and returns how many secrets
+    /// are found and the most guesses needed
+    fn explore(candidates: Vec<Code>, guesses: usize) -> (usize, usize) {
+        let guess = next_guess(&candidates);
+        let mut found = 0;
+        let mut most = 0;
+        let mut branches: Vec<(Score, Vec<Code>)> = Vec::new();
+        for c in candidates {
+            let s = score(&c, &guess);
+            if s.black as usize == PEGS {
+                found += 1;
+                most = guesses + 1;
+                continue;
+            }
+            match branches.iter_mut().find(|b| b.0 == s) {
+                Some(b) => b.1.push(c),
+                None => branches.push((s, vec![c])),
+            }
+        }
+        for (_, branch) in branches {
+            let (f, m) = explore(branch, guesses + 1);
+            found += f;
+            most = most.max(m);
+        }
+        (found, most)
+    }
+
+    #[test]
+    fn should_find_every_code_within_five_guesses() {
+        assert_eq!((1296, 5), explore(all_codes(), 0));
+    }
+
+    #[test]
+    fn should_solve_with_solver_api() {
+        let secret = [3, 0, 5, 5];
+        let guesses = solve(&secret);
+        assert_eq!([0, 0, 1, 1], guesses[0]);
+        assert_eq!(secret, guesses[guesses.len() - 1]);
+        assert!(guesses.len() <= 5);
+    }
+
+    #[test]
+    fn should_notice_inconsistent_answers() {
+        let mut solver = Solver::new();
+        solver.answer(&[0, 0, 1, 1], Score { black: 4, white: 0 });
+        solver.answer(&[0, 0, 1, 1], Score { black: 0, white: 0 });
+        assert_eq!(None, solver.guess());
+    }
+
+    #[test]
+    fn should_make_random_codes() {
+        let mut rng = Rng::new(5);
+        let codes: Vec<Code> = (0..50).map(|_| random_code(&mut rng)).collect();
+        assert!(codes.iter().all(|c| c.iter().all(|&p| p < COLORS)));
+        assert!(codes.iter().any(|c| *c != codes[0]));
+        assert_eq!(1296, all_codes().len());
+    }
+}
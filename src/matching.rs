@@ -0,0 +1,297 @@
+//!
+//! # Problem description:
+//! #
+//! # Stable Marriage
+//!
+//! Given n men and n women, each ranking all members of the other group,
+//! find a matching with no blocking pair: a man and a woman who both
+//! prefer each other to their partners. Gale and Shapley showed one
+//! always exists. Each free man proposes to the best woman he has not
+//! tried yet, and she holds on to the best proposal so far, dropping
+//! whoever she held before. The result is the best stable matching for
+//! every proposer at once, and the worst for every receiver.
+//!
+//! The hospitals and residents problem is the same with capacities:
+//! every hospital takes up to a given number of residents, and either
+//! side may leave the other off its list. Residents propose, and each
+//! hospital keeps its best applicants in a heap with the worst on top.
+//!
+
+use heap::BinaryHeap;
+
+/// `rank[x][y]` is the position of `y` in `x`'s list, or `usize::MAX`
+/// when `x` does not list `y`
+fn ranks(prefs: &[Vec<usize>], others: usize) -> Vec<Vec<usize>> {
+    prefs
+        .iter()
+        .map(|list| {
+            let mut rank = vec![usize::MAX; others];
+            for (i, &y) in list.iter().enumerate() {
+                assert!(y < others, "Unknown choice {}", y);
+                assert!(rank[y] == usize::MAX, "Choice {} listed twice", y);
+                rank[y] = i;
+            }
+            rank
+        })
+        .collect()
+}
+
+/// Finds the stable matching that is best for the proposers. Both sides
+/// rank everyone on the other; `prefs_a[a]` lists `a`'s choices, best
+/// first. Returns the partner of each proposer.
+///
+/// # Example
+/// ```
+/// use pprust::matching::stable_marriage;
+///
+/// let men = vec![vec![0, 1, 2], vec![1, 0, 2], vec![0, 1, 2]];
+/// let women = vec![vec![1, 0, 2], vec![0, 1, 2], vec![0, 1, 2]];
+/// assert_eq!(vec![0, 1, 2], stable_marriage(&men, &women));
+/// ```
+pub fn stable_marriage(prefs_a: &[Vec<usize>], prefs_b: &[Vec<usize>]) -> Vec<usize> {
+    let n = prefs_a.len();
+    assert_eq!(n, prefs_b.len(), "Both sides must have the same size");
+    assert!(prefs_a.iter().chain(prefs_b).all(|list| list.len() == n), "Preference lists must be complete");
+    ranks(prefs_a, n);
+    let rank_b = ranks(prefs_b, n);
+    let mut next = vec![0; n];
+    let mut held: Vec<Option<usize>> = vec![None; n];
+    let mut free: Vec<usize> = (0..n).rev().collect();
+    while let Some(a) = free.pop() {
+        let b = prefs_a[a][next[a]];
+        next[a] += 1;
+        match held[b] {
+            None => held[b] = Some(a),
+            Some(current) if rank_b[b][a] < rank_b[b][current] => {
+                held[b] = Some(a);
+                free.push(current);
+            }
+            Some(_) => free.push(a),
+        }
+    }
+    let mut partner = vec![0; n];
+    for (b, a) in held.into_iter().enumerate() {
+        partner[a.unwrap()] = b;
+    }
+    partner
+}
+
+/// A pair that would rather be together than with their partners in
+/// `matching`, which gives the partner of each member of `a`; `None` if
+/// the matching is stable
+///
+/// # Example
+/// ```
+/// use pprust::matching::blocking_pair;
+///
+/// let men = vec![vec![0, 1], vec![0, 1]];
+/// let women = vec![vec![1, 0], vec![0, 1]];
+/// assert_eq!(Some((1, 0)), blocking_pair(&men, &women, &[0, 1]));
+/// assert_eq!(None, blocking_pair(&men, &women, &[1, 0]));
+/// ```
+pub fn blocking_pair(prefs_a: &[Vec<usize>], prefs_b: &[Vec<usize>], matching: &[usize]) -> Option<(usize, usize)> {
+    let n = prefs_a.len();
+    let rank_b = ranks(prefs_b, n);
+    let mut partner_b = vec![usize::MAX; n];
+    for (a, &b) in matching.iter().enumerate() {
+        assert!(partner_b[b] == usize::MAX, "{} is matched twice", b);
+        partner_b[b] = a;
+    }
+    for (a, list) in prefs_a.iter().enumerate() {
+        for &b in list.iter().take_while(|&&b| b != matching[a]) {
+            if rank_b[b][a] < rank_b[b][partner_b[b]] {
+                return Some((a, b));
+            }
+        }
+    }
+    None
+}
+
+/// Whether no pair blocks `matching`
+pub fn is_stable(prefs_a: &[Vec<usize>], prefs_b: &[Vec<usize>], matching: &[usize]) -> bool {
+    blocking_pair(prefs_a, prefs_b, matching).is_none()
+}
+
+/// Assigns residents to hospitals, best for the residents. Lists may be
+/// partial, and a resident only goes to a hospital that both lists
+/// each other. Returns the hospital of each resident, if any.
+///
+/// # Example
+/// ```
+/// use pprust::matching::hospital_residents;
+///
+/// let residents = vec![vec![0, 1], vec![0], vec![0, 1], vec![1]];
+/// let hospitals = vec![vec![2, 0, 1], vec![0, 2]];
+/// assert_eq!(vec![Some(0), None, Some(0), None], hospital_residents(&residents, &hospitals, &[2, 1]));
+/// ```
+pub fn hospital_residents(resident_prefs: &[Vec<usize>], hospital_prefs: &[Vec<usize>], capacities: &[usize]) -> Vec<Option<usize>> {
+    let (residents, hospitals) = (resident_prefs.len(), hospital_prefs.len());
+    assert_eq!(hospitals, capacities.len(), "Every hospital needs a capacity");
+    ranks(resident_prefs, hospitals);
+    let rank_h = ranks(hospital_prefs, residents);
+    // Each hospital holds (rank, resident), its worst applicant on top
+    let mut held: Vec<BinaryHeap<(usize, usize)>> = (0..hospitals).map(|_| BinaryHeap::new()).collect();
+    let mut assigned = vec![None; residents];
+    let mut next = vec![0; residents];
+    let mut free: Vec<usize> = (0..residents).rev().collect();
+    while let Some(r) = free.pop() {
+        let h = match resident_prefs[r].get(next[r]) {
+            Some(&h) => h,
+            None => continue,
+        };
+        next[r] += 1;
+        let rank = rank_h[h][r];
+        if rank == usize::MAX || capacities[h] == 0 {
+            free.push(r);
+            continue;
+        }
+        if held[h].len() < capacities[h] {
+            held[h].push((rank, r));
+            assigned[r] = Some(h);
+        } else if held[h].peek().unwrap().0 > rank {
+            let (_, dropped) = held[h].pop().unwrap();
+            assigned[dropped] = None;
+            free.push(dropped);
+            held[h].push((rank, r));
+            assigned[r] = Some(h);
+        } else {
+            free.push(r);
+        }
+    }
+    assigned
+}
+
+/// A resident and a hospital that list each other and would both gain
+/// from the resident moving there: the resident prefers it to where they
+/// are, and the hospital has room or prefers them to someone it took
+pub fn hospital_blocking_pair(resident_prefs: &[Vec<usize>], hospital_prefs: &[Vec<usize>], capacities: &[usize],
+                              assignment: &[Option<usize>]) -> Option<(usize, usize)> {
+    let rank_h = ranks(hospital_prefs, resident_prefs.len());
+    let mut taken = vec![0; hospital_prefs.len()];
+    // The rank of the worst resident each hospital took
+    let mut worst = vec![0; hospital_prefs.len()];
+    for (r, &h) in assignment.iter().enumerate() {
+        if let Some(h) = h {
+            assert!(rank_h[h][r] != usize::MAX, "Resident {} is not acceptable to hospital {}", r, h);
+            taken[h] += 1;
+            worst[h] = worst[h].max(rank_h[h][r]);
+        }
+    }
+    assert!((0..capacities.len()).all(|h| taken[h] <= capacities[h]), "A hospital is over capacity");
+    for (r, list) in resident_prefs.iter().enumerate() {
+        for &h in list.iter().take_while(|&&h| Some(h) != assignment[r]) {
+            let rank = rank_h[h][r];
+            if rank != usize::MAX && (taken[h] < capacities[h] || rank < worst[h]) {
+                return Some((r, h));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use combinatorics::lexicographic_permutations;
+    use rng::Rng;
+    use shuffle::permutation;
+
+    fn random_prefs(n: usize, rng: &mut Rng) -> Vec<Vec<usize>> {
+        (0..n).map(|_| permutation(n, rng)).collect()
+    }
+
+    #[test]
+    fn should_find_stable_matchings() {
+        let mut rng = Rng::new(250);
+        for n in 0..30 {
+            let men = random_prefs(n, &mut rng);
+            let women = random_prefs(n, &mut rng);
+            let matching = stable_marriage(&men, &women);
+            let mut sorted = matching.clone();
+            sorted.sort();
+            assert_eq!((0..n).collect::<Vec<_>>(), sorted);
+            assert!(is_stable(&men, &women, &matching));
+        }
+    }
+
+    #[test]
+    fn should_be_best_for_proposers() {
+        let mut rng = Rng::new(7);
+        for _ in 0..50 {
+            let n = rng.gen_range(1, 7) as usize;
+            let men = random_prefs(n, &mut rng);
+            let women = random_prefs(n, &mut rng);
+            let matching = stable_marriage(&men, &women);
+            let rank_men = ranks(&men, n);
+            let rank_women = ranks(&women, n);
+            // No stable matching gives any man a better partner, or any
+            // woman a worse one
+            for other in lexicographic_permutations(n).filter(|m| is_stable(&men, &women, m)) {
+                for a in 0..n {
+                    assert!(rank_men[a][matching[a]] <= rank_men[a][other[a]]);
+                    let b = matching[a];
+                    let other_a = other.iter().position(|&x| x == b).unwrap();
+                    assert!(rank_women[b][a] >= rank_women[b][other_a]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_spot_blocking_pairs() {
+        let mut rng = Rng::new(3);
+        for _ in 0..100 {
+            let n = rng.gen_range(2, 8) as usize;
+            let men = random_prefs(n, &mut rng);
+            let women = random_prefs(n, &mut rng);
+            let matching = permutation(n, &mut rng);
+            if let Some((a, b)) = blocking_pair(&men, &women, &matching) {
+                let rank_men = ranks(&men, n);
+                let rank_women = ranks(&women, n);
+                let partner_b = matching.iter().position(|&x| x == b).unwrap();
+                assert!(rank_men[a][b] < rank_men[a][matching[a]]);
+                assert!(rank_women[b][a] < rank_women[b][partner_b]);
+            }
+        }
+    }
+
+    #[test]
+    fn should_match_marriage_with_unit_capacities() {
+        let mut rng = Rng::new(11);
+        for n in 1..20 {
+            let men = random_prefs(n, &mut rng);
+            let women = random_prefs(n, &mut rng);
+            let expected: Vec<Option<usize>> = stable_marriage(&men, &women).into_iter().map(Some).collect();
+            assert_eq!(expected, hospital_residents(&men, &women, &vec![1; n]));
+        }
+    }
+
+    #[test]
+    fn should_assign_residents_stably() {
+        let mut rng = Rng::new(42);
+        for _ in 0..200 {
+            let residents = rng.gen_range(1, 30) as usize;
+            let hospitals = rng.gen_range(1, 6) as usize;
+            let partial = |n: usize, others: usize, rng: &mut Rng| -> Vec<Vec<usize>> {
+                (0..n)
+                    .map(|_| {
+                        let mut list = permutation(others, rng);
+                        let keep = rng.gen_range(0, others as u64 + 1) as usize;
+                        list.truncate(keep);
+                        list
+                    })
+                    .collect()
+            };
+            let resident_prefs = partial(residents, hospitals, &mut rng);
+            let hospital_prefs = partial(hospitals, residents, &mut rng);
+            let capacities: Vec<usize> = (0..hospitals).map(|_| rng.gen_range(0, 6) as usize).collect();
+            let assignment = hospital_residents(&resident_prefs, &hospital_prefs, &capacities);
+            assert_eq!(None, hospital_blocking_pair(&resident_prefs, &hospital_prefs, &capacities, &assignment));
+            for (r, &h) in assignment.iter().enumerate() {
+                if let Some(h) = h {
+                    assert!(resident_prefs[r].contains(&h));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//!
+//! # Problem description:
+//! #
+//! # Matrix Operations
+//!
+//! Dense matrices of floating-point numbers, stored row by row, with the
+//! basic arithmetic and the solution of linear systems A x = b by
+//! Gaussian elimination. Each step of the elimination picks as pivot the
+//! largest entry left in its column (partial pivoting), which keeps the
+//! rounding errors small.
+//!
+//! Recording the elimination gives the LU decomposition P A = L U, with
+//! P a permutation, L lower triangular with a unit diagonal and U upper
+//! triangular. Once it is known, each system costs only two triangular
+//! substitutions, the determinant is the product of the diagonal of U
+//! with the sign of P, and the inverse comes from solving for each column
+//! of the identity.
+//!
+
+use std::ops::{Index, IndexMut, Mul};
+
+/// Pivots smaller than this are taken as zero
+const EPSILON: f64 = 1e-12;
+
+/// A dense matrix of `f64`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    /// A matrix of zeros
+    pub fn zeros(rows: usize, cols: usize) -> Matrix {
+        Matrix { rows, cols, data: vec![0.0; rows * cols] }
+    }
+
+    /// The `n` by `n` identity matrix
+    pub fn identity(n: usize) -> Matrix {
+        let mut m = Matrix::zeros(n, n);
+        for i in 0..n {
+            m[(i, i)] = 1.0;
+        }
+        m
+    }
+
+    /// A matrix from its rows, which must all have the same length
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::matrix::Matrix;
+    ///
+    /// let m = Matrix::from_rows(&[vec![1.0, 2.0], vec![3.0, 4.0]]);
+    /// assert_eq!(3.0, m[(1, 0)]);
+    /// ```
+    pub fn from_rows(rows: &[Vec<f64>]) -> Matrix {
+        let cols = rows.first().map_or(0, |r| r.len());
+        assert!(rows.iter().all(|r| r.len() == cols), "All rows must have the same length");
+        Matrix { rows: rows.len(), cols, data: rows.concat() }
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The transposed matrix
+    pub fn transpose(&self) -> Matrix {
+        let mut t = Matrix::zeros(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                t[(j, i)] = self[(i, j)];
+            }
+        }
+        t
+    }
+
+    /// The LU decomposition with partial pivoting, or `None` if the
+    /// matrix is singular
+    pub fn lu(&self) -> Option<Lu> {
+        assert_eq!(self.rows, self.cols, "Matrix must be square");
+        let n = self.rows;
+        let mut a = self.clone();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+        for k in 0..n {
+            let pivot = (k..n).max_by(|&i, &j| a[(i, k)].abs().total_cmp(&a[(j, k)].abs()))?;
+            if a[(pivot, k)].abs() < EPSILON {
+                return None;
+            }
+            if pivot != k {
+                a.swap_rows(k, pivot);
+                permutation.swap(k, pivot);
+                sign = -sign;
+            }
+            for i in k + 1..n {
+                let factor = a[(i, k)] / a[(k, k)];
+                a[(i, k)] = factor;
+                for j in k + 1..n {
+                    a[(i, j)] -= factor * a[(k, j)];
+                }
+            }
+        }
+        Some(Lu { factors: a, permutation, sign })
+    }
+
+    /// Solves A x = b for a square matrix A, returning `None` if A is
+    /// singular
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::matrix::Matrix;
+    ///
+    /// let a = Matrix::from_rows(&[vec![2.0, 1.0], vec![1.0, 3.0]]);
+    /// let x = a.solve(&[3.0, 5.0]).unwrap();
+    /// assert!((x[0] - 0.8).abs() < 1e-12 && (x[1] - 1.4).abs() < 1e-12);
+    /// ```
+    pub fn solve(&self, b: &[f64]) -> Option<Vec<f64>> {
+        assert_eq!(self.rows, b.len(), "Right-hand side must match the matrix");
+        self.lu().map(|lu| lu.solve(b))
+    }
+
+    /// The determinant of a square matrix
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::matrix::Matrix;
+    ///
+    /// let a = Matrix::from_rows(&[vec![1.0, 2.0], vec![3.0, 4.0]]);
+    /// assert!((a.determinant() + 2.0).abs() < 1e-12);
+    /// ```
+    pub fn determinant(&self) -> f64 {
+        self.lu().map_or(0.0, |lu| lu.determinant())
+    }
+
+    /// The inverse of a square matrix, or `None` if it is singular
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::matrix::Matrix;
+    ///
+    /// let a = Matrix::from_rows(&[vec![4.0, 7.0], vec![2.0, 6.0]]);
+    /// let inverse = a.inverse().unwrap();
+    /// assert!((inverse[(0, 0)] - 0.6).abs() < 1e-12 && (inverse[(0, 1)] + 0.7).abs() < 1e-12);
+    /// ```
+    pub fn inverse(&self) -> Option<Matrix> {
+        self.lu().map(|lu| lu.inverse())
+    }
+
+    fn swap_rows(&mut self, i: usize, j: usize) {
+        if i != j {
+            for col in 0..self.cols {
+                self.data.swap(i * self.cols + col, j * self.cols + col);
+            }
+        }
+    }
+}
+
+/// An LU decomposition P A = L U with partial pivoting
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lu {
+    /// L below the diagonal, without its unit diagonal, and U on and
+    /// above it
+    factors: Matrix,
+    /// Row `i` of P A is row `permutation[i]` of A
+    permutation: Vec<usize>,
+    /// The determinant of P
+    sign: f64,
+}
+
+impl Lu {
+    /// The lower triangular factor, with ones on the diagonal
+    pub fn lower(&self) -> Matrix {
+        let n = self.factors.rows;
+        let mut l = Matrix::identity(n);
+        for i in 0..n {
+            for j in 0..i {
+                l[(i, j)] = self.factors[(i, j)];
+            }
+        }
+        l
+    }
+
+    /// The upper triangular factor
+    pub fn upper(&self) -> Matrix {
+        let n = self.factors.rows;
+        let mut u = Matrix::zeros(n, n);
+        for i in 0..n {
+            for j in i..n {
+                u[(i, j)] = self.factors[(i, j)];
+            }
+        }
+        u
+    }
+
+    /// The permutation matrix P
+    pub fn permutation(&self) -> Matrix {
+        let n = self.factors.rows;
+        let mut p = Matrix::zeros(n, n);
+        for (i, &j) in self.permutation.iter().enumerate() {
+            p[(i, j)] = 1.0;
+        }
+        p
+    }
+
+    /// Solves A x = b by forward and back substitution
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::matrix::Matrix;
+    ///
+    /// let lu = Matrix::from_rows(&[vec![0.0, 1.0], vec![2.0, 0.0]]).lu().unwrap();
+    /// assert_eq!(vec![2.0, 3.0], lu.solve(&[3.0, 4.0]));
+    /// ```
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.factors.rows;
+        assert_eq!(n, b.len(), "Right-hand side must match the matrix");
+        let a = &self.factors;
+        let mut x: Vec<f64> = self.permutation.iter().map(|&i| b[i]).collect();
+        for i in 0..n {
+            let sum: f64 = (0..i).map(|j| a[(i, j)] * x[j]).sum();
+            x[i] -= sum;
+        }
+        for i in (0..n).rev() {
+            let sum: f64 = (i + 1..n).map(|j| a[(i, j)] * x[j]).sum();
+            x[i] = (x[i] - sum) / a[(i, i)];
+        }
+        x
+    }
+
+    /// The determinant of A
+    pub fn determinant(&self) -> f64 {
+        (0..self.factors.rows).fold(self.sign, |d, i| d * self.factors[(i, i)])
+    }
+
+    /// The inverse of A, one column of the identity at a time
+    pub fn inverse(&self) -> Matrix {
+        let n = self.factors.rows;
+        let mut inverse = Matrix::zeros(n, n);
+        let mut unit = vec![0.0; n];
+        for j in 0..n {
+            unit[j] = 1.0;
+            for (i, x) in self.solve(&unit).into_iter().enumerate() {
+                inverse[(i, j)] = x;
+            }
+            unit[j] = 0.0;
+        }
+        inverse
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        assert!(i < self.rows && j < self.cols, "Index ({}, {}) out of bounds", i, j);
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        assert!(i < self.rows && j < self.cols, "Index ({}, {}) out of bounds", i, j);
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl Mul for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, other: &Matrix) -> Matrix {
+        assert_eq!(self.cols, other.rows, "Matrix dimensions do not match");
+        let mut product = Matrix::zeros(self.rows, other.cols);
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(i, k)];
+                for j in 0..other.cols {
+                    product[(i, j)] += a * other[(k, j)];
+                }
+            }
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_multiply_matrices() {
+        let a = Matrix::from_rows(&[vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+        let b = a.transpose();
+        assert_eq!(Matrix::from_rows(&[vec![14.0, 32.0], vec![32.0, 77.0]]), &a * &b);
+        assert_eq!(a, &Matrix::identity(2) * &a);
+    }
+
+    #[test]
+    fn should_solve_systems_needing_pivoting() {
+        let a = Matrix::from_rows(&[vec![0.0, 2.0, 1.0], vec![1.0, -2.0, -3.0], vec![-1.0, 1.0, 2.0]]);
+        let x = a.solve(&[-8.0, 0.0, 3.0]).unwrap();
+        for (got, want) in x.iter().zip(&[-4.0, -5.0, 2.0]) {
+            assert!((got - want).abs() < 1e-12, "{:?}", x);
+        }
+    }
+
+    #[test]
+    fn should_detect_singular_matrices() {
+        let a = Matrix::from_rows(&[vec![1.0, 2.0], vec![2.0, 4.0]]);
+        assert_eq!(None, a.solve(&[1.0, 2.0]));
+    }
+
+    fn assert_close(a: &Matrix, b: &Matrix) {
+        assert_eq!((a.rows(), a.cols()), (b.rows(), b.cols()));
+        for i in 0..a.rows() {
+            for j in 0..a.cols() {
+                assert!((a[(i, j)] - b[(i, j)]).abs() < 1e-9, "{:?} vs {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn should_factor_with_pivoting() {
+        let a = Matrix::from_rows(&[vec![0.0, 2.0, 1.0], vec![1.0, -2.0, -3.0], vec![-1.0, 1.0, 2.0]]);
+        let lu = a.lu().unwrap();
+        assert_close(&(&lu.permutation() * &a), &(&lu.lower() * &lu.upper()));
+        let u = lu.upper();
+        for i in 0..3 {
+            assert!(lu.lower()[(i, i)] == 1.0);
+            for j in 0..i {
+                assert_eq!(0.0, u[(i, j)]);
+            }
+        }
+    }
+
+    #[test]
+    fn should_compute_determinants() {
+        let a = Matrix::from_rows(&[vec![2.0, -3.0, 1.0], vec![2.0, 0.0, -1.0], vec![1.0, 4.0, 5.0]]);
+        assert!((a.determinant() - 49.0).abs() < 1e-9);
+        assert!((Matrix::identity(4).determinant() - 1.0).abs() < 1e-12);
+        let swapped = Matrix::from_rows(&[vec![0.0, 1.0], vec![1.0, 0.0]]);
+        assert!((swapped.determinant() + 1.0).abs() < 1e-12);
+        assert_eq!(0.0, Matrix::from_rows(&[vec![1.0, 2.0], vec![2.0, 4.0]]).determinant());
+    }
+
+    #[test]
+    fn should_invert_matrices() {
+        let a = Matrix::from_rows(&[
+            vec![4.0, -2.0, 1.0, 0.5],
+            vec![3.0, 6.0, -4.0, 2.0],
+            vec![2.0, 1.0, 8.0, -1.0],
+            vec![0.0, 3.0, 1.0, 5.0],
+        ]);
+        let inverse = a.inverse().unwrap();
+        assert_close(&Matrix::identity(4), &(&a * &inverse));
+        assert_close(&Matrix::identity(4), &(&inverse * &a));
+        assert_eq!(None, Matrix::from_rows(&[vec![1.0, 2.0], vec![2.0, 4.0]]).inverse());
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_check_bounds() {
+        let _ = Matrix::zeros(2, 2)[(0, 2)];
+    }
+}
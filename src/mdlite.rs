@@ -0,0 +1,361 @@
+//!
+//! # Problem description:
+//! #
+//! # Markdown to HTML
+//!
+//! Convert a subset of Markdown to HTML: headings written with one to
+//! six `#`, paragraphs separated by blank lines, bulleted lists with
+//! `-`, `*` or `+`, numbered lists, code blocks fenced with three
+//! backquotes, and inside text `*emphasis*`, `**strong**`, `` `code` ``
+//! and `[links](url)`.
+//!
+//! The converter reads a line at a time and writes each block as soon as
+//! it ends, so it works on input of any length. Only the paragraph or
+//! list item being read is kept, since emphasis may run across its
+//! lines. Characters special to HTML are escaped everywhere, and a
+//! backslash makes the character after it plain text.
+//!
+
+use std::io;
+use std::io::prelude::*;
+
+/// The block the converter is in the middle of
+enum Block {
+    None,
+    /// The lines of a paragraph
+    Paragraph(Vec<String>),
+    /// A list, with the lines of its last item
+    List { ordered: bool, item: Vec<String> },
+    /// A fenced code block, with the fence that closes it
+    Code(String),
+}
+
+/// Converts Markdown fed to it a line at a time, writing HTML as each
+/// block ends
+///
+/// # Example
+/// ```
+/// use pprust::mdlite::Converter;
+///
+/// let mut converter = Converter::new(Vec::new());
+/// for line in &["# Title", "", "Some *nice*", "text."] {
+///     converter.line(line).unwrap();
+/// }
+/// let html = converter.finish().unwrap();
+/// assert_eq!("<h1>Title</h1>\n<p>Some <em>nice</em>\ntext.</p>\n", String::from_utf8(html).unwrap());
+/// ```
+pub struct Converter<W: Write> {
+    output: W,
+    block: Block,
+}
+
+impl<W: Write> Converter<W> {
+    pub fn new(output: W) -> Converter<W> {
+        Converter { output, block: Block::None }
+    }
+
+    /// Reads one line, without its line ending
+    pub fn line(&mut self, line: &str) -> io::Result<()> {
+        let line = line.trim_end_matches('\r');
+        if let Block::Code(ref fence) = self.block {
+            if line.trim_start().starts_with(fence.as_str()) {
+                writeln!(self.output, "</code></pre>")?;
+                self.block = Block::None;
+            } else {
+                writeln!(self.output, "{}", escape(line))?;
+            }
+            return Ok(());
+        }
+
+        let text = line.trim();
+        if text.is_empty() {
+            return self.close();
+        }
+        if let Some(fence) = fence(text) {
+            self.close()?;
+            let language = text[fence.len()..].trim();
+            if language.is_empty() {
+                write!(self.output, "<pre><code>")?;
+            } else {
+                write!(self.output, "<pre><code class=\"language-{}\">", escape(language))?;
+            }
+            self.block = Block::Code(fence.to_string());
+        } else if let Some((level, title)) = heading(text) {
+            self.close()?;
+            writeln!(self.output, "<h{}>{}</h{}>", level, inline(title), level)?;
+        } else if let Some((number, item)) = list_item(text) {
+            let ordered = number.is_some();
+            match self.block {
+                Block::List { ordered: current, .. } if current == ordered => self.end_item()?,
+                _ => {
+                    self.close()?;
+                    match number {
+                        Some(start) if start != 1 => writeln!(self.output, "<ol start=\"{}\">", start)?,
+                        Some(_) => writeln!(self.output, "<ol>")?,
+                        None => writeln!(self.output, "<ul>")?,
+                    }
+                }
+            }
+            self.block = Block::List { ordered, item: vec![item.to_string()] };
+        } else {
+            match self.block {
+                Block::Paragraph(ref mut lines) | Block::List { item: ref mut lines, .. } => lines.push(text.to_string()),
+                _ => self.block = Block::Paragraph(vec![text.to_string()]),
+            }
+        }
+        Ok(())
+    }
+
+    /// Ends the last block and returns the output
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Block::Code(_) = self.block {
+            writeln!(self.output, "</code></pre>")?;
+            self.block = Block::None;
+        }
+        self.close()?;
+        Ok(self.output)
+    }
+
+    /// Writes the last item of the list the converter is in
+    fn end_item(&mut self) -> io::Result<()> {
+        if let Block::List { ref item, .. } = self.block {
+            writeln!(self.output, "<li>{}</li>", inline(&item.join("\n")))?;
+        }
+        Ok(())
+    }
+
+    /// Ends a paragraph or list; code blocks only end at their fence
+    fn close(&mut self) -> io::Result<()> {
+        match self.block {
+            Block::Paragraph(ref lines) => writeln!(self.output, "<p>{}</p>", inline(&lines.join("\n")))?,
+            Block::List { ordered, .. } => {
+                self.end_item()?;
+                writeln!(self.output, "{}", if ordered { "</ol>" } else { "</ul>" })?;
+            }
+            Block::None | Block::Code(_) => return Ok(()),
+        }
+        self.block = Block::None;
+        Ok(())
+    }
+}
+
+/// Converts Markdown read from `input`, writing the HTML to `output`
+pub fn convert<R: BufRead, W: Write>(input: R, output: W) -> io::Result<()> {
+    let mut converter = Converter::new(output);
+    for line in input.lines() {
+        converter.line(&line?)?;
+    }
+    converter.finish()?;
+    Ok(())
+}
+
+/// The HTML for a Markdown text
+///
+/// # Example
+/// ```
+/// use pprust::mdlite::to_html;
+///
+/// let html = to_html("## See [the **docs**](https://example.com/?a=1&b=2)\n\n- one\n- `two`\n");
+/// let expected = "<h2>See <a href=\"https://example.com/?a=1&amp;b=2\">the <strong>docs</strong></a></h2>\n\
+///                 <ul>\n<li>one</li>\n<li><code>two</code></li>\n</ul>\n";
+/// assert_eq!(expected, html);
+/// ```
+pub fn to_html(markdown: &str) -> String {
+    let mut converter = Converter::new(Vec::new());
+    for line in markdown.lines() {
+        converter.line(line).expect("Writing to a vector can't fail");
+    }
+    let html = converter.finish().expect("Writing to a vector can't fail");
+    String::from_utf8(html).expect("The HTML is made of UTF-8 strings")
+}
+
+/// The backquotes opening a fenced code block
+fn fence(text: &str) -> Option<&str> {
+    let n = text.bytes().take_while(|&b| b == b'`').count();
+    if n >= 3 && !text[n..].contains('`') {
+        Some(&text[..n])
+    } else {
+        None
+    }
+}
+
+/// The level and title of a heading line
+fn heading(text: &str) -> Option<(usize, &str)> {
+    let level = text.bytes().take_while(|&b| b == b'#').count();
+    let rest = &text[level..];
+    if level == 0 || level > 6 || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    // Closing hashes are decoration, if set off by a space
+    let title = rest.trim();
+    let unclosed = title.trim_end_matches('#');
+    if unclosed.is_empty() || unclosed.ends_with(' ') {
+        Some((level, unclosed.trim_end()))
+    } else {
+        Some((level, title))
+    }
+}
+
+/// The number, for numbered lists, and the text of a list item line
+fn list_item(text: &str) -> Option<(Option<u64>, &str)> {
+    for bullet in &["- ", "* ", "+ "] {
+        if let Some(item) = text.strip_prefix(bullet) {
+            return Some((None, item.trim_start()));
+        }
+    }
+    let digits = text.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 || digits > 9 || !text[digits..].starts_with(". ") {
+        return None;
+    }
+    Some((text[..digits].parse().ok(), text[digits + 2..].trim_start()))
+}
+
+/// Escapes the characters special to HTML
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Converts the spans inside a block of text
+fn inline(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let c = rest.chars().next().expect("i is inside the text");
+        if c == '\\' {
+            if let Some(escaped) = rest[1..].chars().next().filter(|c| c.is_ascii_punctuation()) {
+                html.push_str(&escape(&escaped.to_string()));
+                i += 2;
+                continue;
+            }
+        } else if c == '`' {
+            let ticks = rest.bytes().take_while(|&b| b == b'`').count();
+            if let Some(end) = rest[ticks..].find(&rest[..ticks]) {
+                html.push_str(&format!("<code>{}</code>", escape(rest[ticks..ticks + end].trim())));
+                i += 2 * ticks + end;
+                continue;
+            }
+            html.push_str(&rest[..ticks]);
+            i += ticks;
+            continue;
+        } else if c == '[' {
+            if let Some((label, url, length)) = link(rest) {
+                html.push_str(&format!("<a href=\"{}\">{}</a>", escape(url), inline(label)));
+                i += length;
+                continue;
+            }
+        } else if c == '*' || c == '_' {
+            let after_word = text[..i].chars().next_back().is_some_and(|p| p.is_alphanumeric());
+            if c == '*' || !after_word {
+                if let Some((tag, content, length)) = emphasis(rest, c) {
+                    html.push_str(&format!("<{}>{}</{}>", tag, inline(content), tag));
+                    i += length;
+                    continue;
+                }
+            }
+        }
+        html.push_str(&escape(&c.to_string()));
+        i += c.len_utf8();
+    }
+    html
+}
+
+/// The label, the address and the length of a link at the start of
+/// `text`
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let mut depth = 0;
+    let close = text.char_indices().find(|&(_, c)| {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ => (),
+        }
+        depth == 0
+    })?.0;
+    let rest = text[close + 1..].strip_prefix('(')?;
+    let end = rest.find(')')?;
+    Some((&text[1..close], rest[..end].trim(), close + end + 3))
+}
+
+/// The tag, the content and the length of an emphasis at the start of
+/// `text`, marked with one `marker` for `em` or two for `strong`. The
+/// content can't start or end with a space.
+fn emphasis(text: &str, marker: char) -> Option<(&'static str, &str, usize)> {
+    let double: String = [marker, marker].iter().collect();
+    let (tag, width) = if text.starts_with(&double) { ("strong", 2) } else { ("em", 1) };
+    let body = &text[width..];
+    let mut j = 0;
+    while j < body.len() {
+        let rest = &body[j..];
+        if rest.starts_with('\\') || (width == 1 && rest.starts_with(&double)) {
+            // An escaped character, or a strong span inside the emphasis
+            j += 1 + rest[1..].chars().next().map_or(0, char::len_utf8);
+            continue;
+        }
+        if rest.starts_with(&double[..width]) && j > 0 {
+            let content = &body[..j];
+            if !content.starts_with(' ') && !content.ends_with(' ') {
+                return Some((tag, content, j + 2 * width));
+            }
+        }
+        j += rest.chars().next().expect("j is inside the body").len_utf8();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn should_match_golden_files() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/mdlite");
+        let mut checked = 0;
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|e| e != "md") {
+                continue;
+            }
+            let markdown = fs::read_to_string(&path).unwrap();
+            let expected = fs::read_to_string(path.with_extension("html")).unwrap();
+            assert_eq!(expected, to_html(&markdown), "{}", path.display());
+            checked += 1;
+        }
+        assert!(checked > 0, "No golden files in {}", dir.display());
+    }
+
+    #[test]
+    fn should_convert_spans() {
+        assert_eq!("<em>a</em> <strong>b</strong> <em>c</em> <strong>d</strong>", inline("*a* **b** _c_ __d__"));
+        assert_eq!("<em>x <strong>y</strong> z</em>", inline("*x **y** z*"));
+        assert_eq!("snake_case_name and 2 * 3 * 4", inline("snake_case_name and 2 * 3 * 4"));
+        assert_eq!("<code>a * b &lt; c</code> *literal*", inline("`a * b < c` \\*literal\\*"));
+        assert_eq!("<code>x ` y</code>", inline("`` x ` y ``"));
+        assert_eq!("[not a link] (here) `unclosed", inline("[not a link] (here) `unclosed"));
+        assert_eq!("<a href=\"u\">[x] y</a>", inline("[[x] y](u)"));
+    }
+
+    #[test]
+    fn should_stream_blocks_as_they_end() {
+        let mut converter = Converter::new(Vec::new());
+        converter.line("Para").unwrap();
+        assert!(converter.output.is_empty());
+        converter.line("# Head").unwrap();
+        assert_eq!("<p>Para</p>\n<h1>Head</h1>\n", String::from_utf8(converter.output.clone()).unwrap());
+        let mut output = Vec::new();
+        convert("```\n<b>\n".as_bytes(), &mut output).unwrap();
+        assert_eq!("<pre><code>&lt;b&gt;\n</code></pre>\n", String::from_utf8(output).unwrap());
+    }
+}
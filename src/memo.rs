@@ -0,0 +1,184 @@
+//!
+//! # Problem description:
+//! #
+//! # Memoization
+//!
+//! Write a function that memoizes another: it remembers the value for
+//! every argument it has seen and returns it again without calling the
+//! function. Use it on recursive functions like the Fibonacci numbers,
+//! the lengths of Collatz sequences and the edit distance between two
+//! strings, where the same arguments come up over and over.
+//!
+//! For the recursion to go through the memo too, the function doesn't
+//! call itself: it gets, besides its argument, the function to call for
+//! the smaller cases. Values are kept in a hash map, or when memory is
+//! short in an LRU cache that forgets the values used longest ago.
+//!
+
+use cache::LruCache;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Where the values are kept
+enum Store<K, V> {
+    Unbounded(HashMap<K, V>),
+    Bounded(LruCache<K, V>),
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Store<K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        match *self {
+            Store::Unbounded(ref map) => map.get(key).cloned(),
+            Store::Bounded(ref mut cache) => cache.get(key).cloned(),
+        }
+    }
+
+    fn put(&mut self, key: K, value: V) {
+        match *self {
+            Store::Unbounded(ref mut map) => {
+                map.insert(key, value);
+            }
+            Store::Bounded(ref mut cache) => {
+                cache.put(key, value);
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match *self {
+            Store::Unbounded(ref map) => map.len(),
+            Store::Bounded(ref cache) => cache.len(),
+        }
+    }
+}
+
+/// A memoized function, called with its argument and the function to
+/// recur with
+///
+/// # Example
+/// ```
+/// use pprust::memo::Memoized;
+///
+/// let mut fibonacci = Memoized::new(|fibonacci: &mut dyn FnMut(u64) -> u64, n: u64| {
+///     if n < 2 { n } else { fibonacci(n - 1) + fibonacci(n - 2) }
+/// });
+/// assert_eq!(2_880_067_194_370_816_120, fibonacci.get(90));
+/// assert_eq!(91, fibonacci.len());
+/// ```
+pub struct Memoized<K, V, F> {
+    function: F,
+    store: Store<K, V>,
+}
+
+/// Calls the function through the store, for the function to recur with
+fn call<K, V, F>(function: &F, store: &mut Store<K, V>, key: K) -> V
+    where K: Hash + Eq + Clone,
+          V: Clone,
+          F: Fn(&mut dyn FnMut(K) -> V, K) -> V
+{
+    if let Some(value) = store.get(&key) {
+        return value;
+    }
+    let value = function(&mut |key| call(function, store, key), key.clone());
+    store.put(key, value.clone());
+    value
+}
+
+impl<K, V, F> Memoized<K, V, F>
+    where K: Hash + Eq + Clone,
+          V: Clone,
+          F: Fn(&mut dyn FnMut(K) -> V, K) -> V
+{
+    /// Memoizes a function, keeping every value
+    pub fn new(function: F) -> Memoized<K, V, F> {
+        Memoized { function, store: Store::Unbounded(HashMap::new()) }
+    }
+
+    /// Memoizes a function, keeping up to `capacity` values, the ones
+    /// used most recently
+    pub fn bounded(capacity: usize, function: F) -> Memoized<K, V, F> {
+        Memoized { function, store: Store::Bounded(LruCache::new(capacity)) }
+    }
+
+    /// The value of the function for an argument
+    pub fn get(&mut self, key: K) -> V {
+        call(&self.function, &mut self.store, key)
+    }
+
+    /// The number of values kept
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets every value
+    pub fn clear(&mut self) {
+        self.store = match self.store {
+            Store::Unbounded(_) => Store::Unbounded(HashMap::new()),
+            Store::Bounded(ref cache) => Store::Bounded(LruCache::new(cache.capacity())),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn should_find_the_longest_collatz_sequence() {
+        let calls = Cell::new(0);
+        let mut steps = Memoized::new(|steps: &mut dyn FnMut(u64) -> u32, n: u64| {
+            calls.set(calls.get() + 1);
+            match n {
+                1 => 0,
+                n if n % 2 == 0 => 1 + steps(n / 2),
+                n => 1 + steps(3 * n + 1),
+            }
+        });
+        let longest = (1..10_000).max_by_key(|&n| steps.get(n)).unwrap();
+        assert_eq!((6171, 261), (longest, steps.get(longest)));
+        // Each number was worked out once
+        assert_eq!(calls.get(), steps.len());
+        steps.clear();
+        assert!(steps.is_empty());
+        assert_eq!(111, steps.get(27));
+    }
+
+    #[test]
+    fn should_find_edit_distances() {
+        let distance = |a: &str, b: &str| {
+            let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+            let mut memo = Memoized::new(|distance: &mut dyn FnMut((usize, usize)) -> usize, (i, j): (usize, usize)| {
+                if i == 0 || j == 0 {
+                    return i + j;
+                }
+                let substitution = distance((i - 1, j - 1)) + (a[i - 1] != b[j - 1]) as usize;
+                substitution.min(distance((i - 1, j)) + 1).min(distance((i, j - 1)) + 1)
+            });
+            memo.get((a.len(), b.len()))
+        };
+        assert_eq!(3, distance("kitten", "sitting"));
+        assert_eq!(0, distance("praxis", "praxis"));
+        assert_eq!(5, distance("", "abcde"));
+        assert_eq!(10, distance("intention execution", "execution intention"));
+    }
+
+    #[test]
+    fn should_stay_within_its_bound() {
+        let calls = Cell::new(0);
+        let mut fibonacci = Memoized::bounded(3, |fibonacci: &mut dyn FnMut(u64) -> u64, n: u64| {
+            calls.set(calls.get() + 1);
+            if n < 2 { n } else { fibonacci(n - 1) + fibonacci(n - 2) }
+        });
+        assert_eq!(12_586_269_025, fibonacci.get(50));
+        assert_eq!(3, fibonacci.len());
+        // The values a call needs are still the recent ones
+        assert_eq!(51, calls.get());
+        assert_eq!(12_586_269_025, fibonacci.get(50));
+        assert_eq!(51, calls.get());
+    }
+}
@@ -0,0 +1,457 @@
+//!
+//! # Problem description:
+//! #
+//! # Money Arithmetic
+//!
+//! Binary floating point cannot hold 0.10 exactly, so sums of money
+//! drift by fractions of a cent. Storing amounts as whole numbers of the
+//! smallest unit avoids that: a fixed-point number is an integer count of
+//! units together with its scale, the number of decimal places, so 12.34
+//! is 1234 units at scale 2.
+//!
+//! Addition and subtraction are exact. Products, quotients and changes
+//! of scale round to the nearest unit, halves going to the even
+//! neighbour (banker's rounding), so rounding errors do not lean one way
+//! over many operations. Splitting an amount into parts hands out the
+//! units left over after rounding one at a time, so the parts always add
+//! up to the whole.
+//!
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::result;
+use std::str::FromStr;
+
+/// The most decimal places an amount can have
+pub const MAX_SCALE: u32 = 18;
+
+/// Errors parsing an amount
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoneyError {
+    /// The text is not a decimal number
+    Syntax(String),
+    /// The amount has more than `MAX_SCALE` decimal places
+    TooPrecise(u32),
+    /// The amount does not fit
+    Overflow,
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MoneyError::Syntax(ref text) => write!(f, "'{}' is not an amount", text),
+            MoneyError::TooPrecise(scale) => write!(f, "{} decimal places, at most {} allowed", scale, MAX_SCALE),
+            MoneyError::Overflow => write!(f, "amount too large"),
+        }
+    }
+}
+
+/// The result of parsing an amount
+pub type MoneyResult<T> = result::Result<T, MoneyError>;
+
+/// How amounts are written: the digit group and decimal separators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub group: char,
+    pub decimal: char,
+}
+
+impl Locale {
+    /// `1,234.56`
+    pub const US: Locale = Locale { group: ',', decimal: '.' };
+    /// `1.234,56`
+    pub const EUROPE: Locale = Locale { group: '.', decimal: ',' };
+    /// `1 234,56`
+    pub const FRANCE: Locale = Locale { group: ' ', decimal: ',' };
+    /// `1'234.56`
+    pub const SWISS: Locale = Locale { group: '\'', decimal: '.' };
+}
+
+/// A decimal amount: `units` of 10^-`scale`
+#[derive(Debug, Clone, Copy)]
+pub struct Fixed {
+    units: i64,
+    scale: u32,
+}
+
+fn pow10(scale: u32) -> i128 {
+    10i128.pow(scale)
+}
+
+/// `n / d` rounded to the nearest integer, halves to even; `d` must be
+/// positive
+fn div_round_half_even(n: i128, d: i128) -> i128 {
+    let q = n.div_euclid(d);
+    let r = n.rem_euclid(d);
+    match (2 * r).cmp(&d) {
+        Ordering::Less => q,
+        Ordering::Greater => q + 1,
+        Ordering::Equal => q + (q & 1),
+    }
+}
+
+fn narrow(units: i128) -> Option<i64> {
+    if units < i64::MIN as i128 || units > i64::MAX as i128 { None } else { Some(units as i64) }
+}
+
+impl Fixed {
+    /// The amount `units` / 10^`scale`
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::money::Fixed;
+    ///
+    /// assert_eq!("12.34", Fixed::new(1234, 2).to_string());
+    /// ```
+    pub fn new(units: i64, scale: u32) -> Fixed {
+        assert!(scale <= MAX_SCALE, "At most {} decimal places", MAX_SCALE);
+        Fixed { units, scale }
+    }
+
+    /// Zero at the given scale
+    pub fn zero(scale: u32) -> Fixed {
+        Fixed::new(0, scale)
+    }
+
+    /// The count of smallest units
+    pub fn units(&self) -> i64 {
+        self.units
+    }
+
+    /// The number of decimal places
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Whether the amount is negative
+    pub fn is_negative(&self) -> bool {
+        self.units < 0
+    }
+
+    /// The same amount with `scale` decimal places, rounding halves to
+    /// even when places are dropped
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::money::Fixed;
+    ///
+    /// assert_eq!(Fixed::new(12, 0), Fixed::new(125, 1).rescale(0));
+    /// assert_eq!(Fixed::new(14, 0), Fixed::new(135, 1).rescale(0));
+    /// assert_eq!("1.500", Fixed::new(15, 1).rescale(3).to_string());
+    /// ```
+    pub fn rescale(&self, scale: u32) -> Fixed {
+        self.checked_rescale(scale).expect("Amount overflow")
+    }
+
+    /// Like `rescale`, or `None` on overflow
+    pub fn checked_rescale(&self, scale: u32) -> Option<Fixed> {
+        assert!(scale <= MAX_SCALE, "At most {} decimal places", MAX_SCALE);
+        let units = if scale >= self.scale {
+            self.units as i128 * pow10(scale - self.scale)
+        } else {
+            div_round_half_even(self.units as i128, pow10(self.scale - scale))
+        };
+        narrow(units).map(|units| Fixed { units, scale })
+    }
+
+    /// The sum at the larger of the two scales, or `None` on overflow
+    pub fn checked_add(&self, other: &Fixed) -> Option<Fixed> {
+        let scale = self.scale.max(other.scale);
+        let units = self.units_at(scale) + other.units_at(scale);
+        narrow(units).map(|units| Fixed { units, scale })
+    }
+
+    /// The difference at the larger of the two scales, or `None` on
+    /// overflow
+    pub fn checked_sub(&self, other: &Fixed) -> Option<Fixed> {
+        self.checked_add(&-*other)
+    }
+
+    /// The product at the larger of the two scales, rounded, or `None`
+    /// on overflow
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::money::Fixed;
+    ///
+    /// let price: Fixed = "19.99".parse().unwrap();
+    /// let rate: Fixed = "0.075".parse().unwrap();
+    /// assert_eq!("1.499", price.checked_mul(&rate).unwrap().to_string());
+    /// ```
+    pub fn checked_mul(&self, other: &Fixed) -> Option<Fixed> {
+        let scale = self.scale.max(other.scale);
+        let product = self.units as i128 * other.units as i128;
+        let units = div_round_half_even(product, pow10(self.scale + other.scale - scale));
+        narrow(units).map(|units| Fixed { units, scale })
+    }
+
+    /// The quotient at the larger of the two scales, rounded, or `None`
+    /// on overflow or division by zero
+    pub fn checked_div(&self, other: &Fixed) -> Option<Fixed> {
+        if other.units == 0 {
+            return None;
+        }
+        let scale = self.scale.max(other.scale);
+        let numerator = self.units as i128 * pow10(scale + other.scale - self.scale);
+        let (n, d) = if other.units < 0 { (-numerator, -(other.units as i128)) } else { (numerator, other.units as i128) };
+        narrow(div_round_half_even(n, d)).map(|units| Fixed { units, scale })
+    }
+
+    fn units_at(&self, scale: u32) -> i128 {
+        self.units as i128 * pow10(scale - self.scale)
+    }
+
+    /// Splits the amount in proportion to `weights`, the parts adding up
+    /// exactly to the amount. Each part gets its share rounded down, and
+    /// the units left over go one each to the parts that lost the most
+    /// to rounding, earlier parts first among equals.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::money::Fixed;
+    ///
+    /// let parts = Fixed::new(100, 2).allocate(&[1, 1, 1]);
+    /// let shown: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+    /// assert_eq!(vec!["0.34", "0.33", "0.33"], shown);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If no weight is positive, as there is then no share to give
+    pub fn allocate(&self, weights: &[u64]) -> Vec<Fixed> {
+        let total: u128 = weights.iter().map(|&w| w as u128).sum();
+        assert!(total > 0, "Need a positive weight");
+        let magnitude = self.units.unsigned_abs() as u128;
+        let mut shares: Vec<(u128, u128)> = weights.iter()
+            .map(|&w| (magnitude * w as u128 / total, magnitude * w as u128 % total))
+            .collect();
+        let given: u128 = shares.iter().map(|s| s.0).sum();
+        let mut order: Vec<usize> = (0..shares.len()).collect();
+        order.sort_by(|&a, &b| shares[b].1.cmp(&shares[a].1).then(a.cmp(&b)));
+        for &i in order.iter().take((magnitude - given) as usize) {
+            shares[i].0 += 1;
+        }
+        let sign = if self.units < 0 { -1 } else { 1 };
+        shares.iter().map(|&(units, _)| Fixed { units: sign * units as i64, scale: self.scale }).collect()
+    }
+
+    /// Splits the amount into `parts` as equal as possible
+    ///
+    /// # Panics
+    ///
+    /// If `parts` is 0
+    pub fn split(&self, parts: usize) -> Vec<Fixed> {
+        self.allocate(&vec![1; parts])
+    }
+
+    /// Writes the amount with the locale's separators
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::money::{Fixed, Locale};
+    ///
+    /// let amount = Fixed::new(-123456789, 2);
+    /// assert_eq!("-1,234,567.89", amount.format(&Locale::US));
+    /// assert_eq!("-1.234.567,89", amount.format(&Locale::EUROPE));
+    /// ```
+    pub fn format(&self, locale: &Locale) -> String {
+        self.write(Some(locale.group), locale.decimal)
+    }
+
+    fn write(&self, group: Option<char>, decimal: char) -> String {
+        let digits = self.units.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let digits = if digits.len() <= scale { format!("{:0>w$}", digits, w = scale + 1) } else { digits };
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        let mut text = String::new();
+        if self.units < 0 {
+            text.push('-');
+        }
+        for (i, c) in whole.chars().enumerate() {
+            if let Some(separator) = group {
+                if i > 0 && (whole.len() - i) % 3 == 0 {
+                    text.push(separator);
+                }
+            }
+            text.push(c);
+        }
+        if scale > 0 {
+            text.push(decimal);
+            text.push_str(fraction);
+        }
+        text
+    }
+}
+
+impl PartialEq for Fixed {
+    fn eq(&self, other: &Fixed) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Fixed {}
+
+impl Ord for Fixed {
+    /// Compares values, whatever their scales
+    fn cmp(&self, other: &Fixed) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.units_at(scale).cmp(&other.units_at(scale))
+    }
+}
+
+impl PartialOrd for Fixed {
+    fn partial_cmp(&self, other: &Fixed) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, other: Fixed) -> Fixed {
+        self.checked_add(&other).expect("Amount overflow")
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, other: Fixed) -> Fixed {
+        self.checked_sub(&other).expect("Amount overflow")
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, other: Fixed) -> Fixed {
+        self.checked_mul(&other).expect("Amount overflow")
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed { units: self.units.checked_neg().expect("Amount overflow"), scale: self.scale }
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.write(None, '.'))
+    }
+}
+
+impl FromStr for Fixed {
+    type Err = MoneyError;
+
+    /// Parses a decimal like `-12.340`, keeping every place written
+    fn from_str(s: &str) -> MoneyResult<Fixed> {
+        let syntax = || MoneyError::Syntax(s.to_string());
+        let text = s.trim();
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (whole, fraction) = match text.find('.') {
+            Some(i) => (&text[..i], &text[i + 1..]),
+            None => (text, ""),
+        };
+        if whole.is_empty() && fraction.is_empty()
+            || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(syntax());
+        }
+        let scale = fraction.len() as u32;
+        if scale > MAX_SCALE {
+            return Err(MoneyError::TooPrecise(scale));
+        }
+        let mut units: i128 = 0;
+        for c in whole.chars().chain(fraction.chars()) {
+            units = units * 10 + c.to_digit(10).unwrap() as i128;
+            if units > i64::MAX as i128 + 1 {
+                return Err(MoneyError::Overflow);
+            }
+        }
+        let units = narrow(if negative { -units } else { units }).ok_or(MoneyError::Overflow)?;
+        Ok(Fixed { units, scale })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f(s: &str) -> Fixed {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn should_add_exactly() {
+        let dime = f("0.10");
+        let total = (0..10).fold(Fixed::zero(2), |sum, _| sum + dime);
+        assert_eq!(f("1.00"), total);
+        assert_eq!("1.00", total.to_string());
+        assert_eq!("0.105", (f("0.1") + f("0.005")).to_string());
+        assert_eq!(f("-0.05"), f("0.05") - f("0.10"));
+    }
+
+    #[test]
+    fn should_round_half_to_even() {
+        let rounded: Vec<String> = ["0.125", "0.135", "-0.125", "-0.135", "0.1251", "2.5", "3.5"].iter()
+            .map(|s| f(s).rescale(if s.len() > 3 { 2 } else { 0 }).to_string())
+            .collect();
+        assert_eq!(vec!["0.12", "0.14", "-0.12", "-0.14", "0.13", "2", "4"], rounded);
+    }
+
+    #[test]
+    fn should_multiply_and_divide() {
+        assert_eq!(f("2.50"), f("1.25") * f("2"));
+        assert_eq!("0.33", f("1.00").checked_div(&f("3")).unwrap().to_string());
+        assert_eq!("-0.67", f("2.00").checked_div(&f("-3")).unwrap().to_string());
+        assert_eq!("0.12", f("0.25").checked_div(&f("2.00")).unwrap().to_string());
+        assert_eq!(None, f("1").checked_div(&f("0.00")));
+        assert_eq!(None, Fixed::new(i64::MAX, 0).checked_mul(&f("2")));
+    }
+
+    #[test]
+    fn should_allocate_every_unit() {
+        let amount = f("100.00");
+        for weights in &[vec![1, 1, 1], vec![70, 20, 10], vec![3, 7], vec![1; 7], vec![0, 1]] {
+            let parts = amount.allocate(weights);
+            assert_eq!(amount, parts.iter().fold(Fixed::zero(2), |s, &p| s + p), "{:?}", weights);
+        }
+        let shown: Vec<String> = f("-0.05").split(3).iter().map(|p| p.to_string()).collect();
+        assert_eq!(vec!["-0.02", "-0.02", "-0.01"], shown);
+        let shown: Vec<String> = f("0.10").allocate(&[1, 2]).iter().map(|p| p.to_string()).collect();
+        assert_eq!(vec!["0.03", "0.07"], shown);
+    }
+
+    #[test]
+    #[should_panic(expected = "Need a positive weight")]
+    fn should_not_split_into_no_parts() {
+        f("1.00").split(0);
+    }
+
+    #[test]
+    fn should_compare_across_scales() {
+        assert_eq!(f("1.5"), f("1.500"));
+        assert!(f("1.499") < f("1.5"));
+        assert!(f("-2") < f("-1.99"));
+    }
+
+    #[test]
+    fn should_parse_and_format() {
+        assert_eq!("0.05", f("0.05").to_string());
+        assert_eq!("-0.5", f("-.5").to_string());
+        assert_eq!("7", f("+7").to_string());
+        assert_eq!(Err(MoneyError::Syntax("1.2.3".to_string())), "1.2.3".parse::<Fixed>());
+        assert_eq!(Err(MoneyError::Syntax(".".to_string())), ".".parse::<Fixed>());
+        assert_eq!(Err(MoneyError::TooPrecise(19)), "0.1234567890123456789".parse::<Fixed>());
+        assert_eq!(Err(MoneyError::Overflow), "99999999999999999999".parse::<Fixed>());
+        assert_eq!("1 000", f("1000").format(&Locale::FRANCE));
+        assert_eq!("123'456.00", f("123456.00").format(&Locale::SWISS));
+        assert_eq!("0,07", f("0.07").format(&Locale::EUROPE));
+    }
+}
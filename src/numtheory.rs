@@ -0,0 +1,231 @@
+//!
+//! # Problem description:
+//! #
+//! # Modular Arithmetic and Discrete Logarithms
+//!
+//! Working modulo a prime p, the powers of a generator g run through
+//! every nonzero residue, so every h has a discrete logarithm: an x with
+//! g^x ≡ h (mod p). Computing g^x takes a few dozen squarings, but going
+//! back is hard, which is what Diffie-Hellman key exchange relies on.
+//!
+//! Shanks' baby-step giant-step method finds x in about √p steps instead
+//! of p. Write x = i m + j with m = ⌈√(p - 1)⌉: store the baby steps g^j
+//! for j < m in a hash table, then take giant steps h, h g^-m, h g^-2m,
+//! ... until one lands in the table, which gives i and j.
+//!
+//! The module also has the usual helpers: multiplication and powers
+//! modulo a 64-bit number, inverses by the extended Euclidean algorithm,
+//! and a Miller-Rabin primality test that is exact for 64-bit numbers.
+//!
+
+use std::collections::HashMap;
+
+/// `a * b mod m`, without overflow
+pub fn mod_mul(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// `base^exponent mod m`, by repeated squaring
+///
+/// # Example
+/// ```
+/// use pprust::numtheory::mod_pow;
+///
+/// assert_eq!(445, mod_pow(4, 13, 497));
+/// ```
+pub fn mod_pow(base: u64, mut exponent: u64, m: u64) -> u64 {
+    assert!(m > 0, "The modulus must be positive");
+    let mut result = 1 % m;
+    let mut base = base % m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// The greatest common divisor
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// The inverse of `a` modulo `m`, if they are coprime
+///
+/// # Example
+/// ```
+/// use pprust::numtheory::mod_inverse;
+///
+/// assert_eq!(Some(4), mod_inverse(3, 11));
+/// assert_eq!(None, mod_inverse(4, 10));
+/// ```
+pub fn mod_inverse(a: u64, m: u64) -> Option<u64> {
+    // Extended Euclid, tracking only the coefficient of a
+    let (mut old_r, mut r) = (a as i128 % m as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        let next_r = old_r - q * r;
+        old_r = r;
+        r = next_r;
+        let next_s = old_s - q * s;
+        old_s = s;
+        s = next_s;
+    }
+    if old_r != 1 {
+        return None;
+    }
+    Some(old_s.rem_euclid(m as i128) as u64)
+}
+
+/// Whether `n` is prime, by the Miller-Rabin test with the first twelve
+/// primes as witnesses, which is exact below 2^64
+///
+/// # Example
+/// ```
+/// use pprust::numtheory::is_prime;
+///
+/// assert!(is_prime(1_000_000_007));
+/// assert!(!is_prime(3_215_031_751));
+/// ```
+pub fn is_prime(n: u64) -> bool {
+    const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    for &p in &WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    WITNESSES.iter().all(|&a| {
+        let mut x = mod_pow(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mod_mul(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Whether `p` is a safe prime: a prime with (p - 1) / 2 also prime
+pub fn is_safe_prime(p: u64) -> bool {
+    p > 4 && is_prime(p) && is_prime((p - 1) / 2)
+}
+
+/// The smallest `x` with `g^x ≡ h (mod p)`, for a prime `p`, by
+/// baby-step giant-step; `None` if `h` is not a power of `g`
+///
+/// # Example
+/// ```
+/// use pprust::numtheory::{bsgs_dlog, mod_pow};
+///
+/// assert_eq!(Some(9), bsgs_dlog(2, mod_pow(2, 9, 23), 23));
+/// // 2 generates only the squares modulo 23, and 5 is not one
+/// assert_eq!(None, bsgs_dlog(2, 5, 23));
+/// ```
+pub fn bsgs_dlog(g: u64, h: u64, p: u64) -> Option<u64> {
+    assert!(p >= 2, "The modulus must be a prime");
+    let (g, h) = (g % p, h % p);
+    if g == 0 {
+        return match h {
+            1 => Some(0),
+            0 => Some(1),
+            _ => None,
+        };
+    }
+    let order = p - 1;
+    let mut m = (order as f64).sqrt().ceil() as u64;
+    while m * m < order {
+        m += 1;
+    }
+    let mut baby_steps = HashMap::with_capacity(m as usize);
+    let mut power = 1 % p;
+    for j in 0..m {
+        baby_steps.entry(power).or_insert(j);
+        power = mod_mul(power, g, p);
+    }
+    // g^-m, by Fermat's little theorem
+    let giant = mod_pow(g, order - m % order, p);
+    let mut gamma = h;
+    for i in 0..m {
+        if let Some(&j) = baby_steps.get(&gamma) {
+            return Some(i * m + j);
+        }
+        gamma = mod_mul(gamma, giant, p);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sieve::sieve;
+
+    #[test]
+    fn should_test_primality_exactly() {
+        let table = sieve(100_000);
+        for (n, &p) in table.iter().enumerate() {
+            assert_eq!(p, is_prime(n as u64), "{}", n);
+        }
+        // Strong pseudoprimes to several small bases
+        for &n in &[2_047u64, 1_373_653, 25_326_001, 3_215_031_751, 2_152_302_898_747, 3_474_749_660_383, 341_550_071_728_321, 3_825_123_056_546_413_051] {
+            assert!(!is_prime(n), "{}", n);
+        }
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(is_safe_prime(1_000_000_000_547));
+        assert!(!is_safe_prime(1_000_000_009));
+    }
+
+    #[test]
+    fn should_invert() {
+        for m in 2..200u64 {
+            for a in 0..m {
+                match mod_inverse(a, m) {
+                    Some(inverse) => assert_eq!(1, mod_mul(a, inverse, m)),
+                    None => assert!(gcd(a, m) != 1),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_find_logarithms_in_safe_prime_groups() {
+        for &p in &[5u64, 7, 11, 23, 47, 59, 83, 107, 167, 179, 227, 263, 347, 359, 383, 467, 479, 503, 563, 587, 719, 839, 863, 887, 983, 1019] {
+            assert!(is_safe_prime(p));
+            for g in 2..p.min(30) {
+                let mut expected = HashMap::new();
+                let mut power = 1;
+                for x in 0..p - 1 {
+                    expected.entry(power).or_insert(x);
+                    power = power * g % p;
+                }
+                for h in 1..p {
+                    assert_eq!(expected.get(&h).cloned(), bsgs_dlog(g, h, p), "log of {} to base {} mod {}", h, g, p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_find_large_logarithms() {
+        let p = 1_000_000_000_547;
+        assert_eq!(556_944_832_428, mod_pow(2, 123_456_789, p));
+        assert_eq!(Some(123_456_789), bsgs_dlog(2, 556_944_832_428, p));
+        assert_eq!(Some(0), bsgs_dlog(2, 1, p));
+    }
+}
@@ -0,0 +1,102 @@
+//!
+//! # Problem description:
+//! #
+//! # Pascal's Triangle
+//!
+//! Each row of Pascal's triangle starts and ends with 1, and every other
+//! number is the sum of the two numbers above it:
+//!
+//! ```text
+//!         1
+//!        1 1
+//!       1 2 1
+//!      1 3 3 1
+//!     1 4 6 4 1
+//! ```
+//!
+//! Row *n* holds the binomial coefficients C(n, k), so it is built by
+//! additions alone. The numbers fit in 64 bits up to row 67.
+//!
+
+/// Largest row whose numbers fit in a `u64`
+pub const MAX_ROW: usize = 67;
+
+/// Row `n` of the triangle, counting from 0
+///
+/// # Example
+/// ```
+/// use pprust::pascal::row;
+///
+/// assert_eq!(vec![1, 4, 6, 4, 1], row(4));
+/// ```
+pub fn row(n: usize) -> Vec<u64> {
+    assert!(n <= MAX_ROW, "Row {} overflows 64 bits", n);
+    let mut current = vec![1];
+    for _ in 0..n {
+        current = next_row(&current);
+    }
+    current
+}
+
+fn next_row(previous: &[u64]) -> Vec<u64> {
+    let mut next = Vec::with_capacity(previous.len() + 1);
+    next.push(1);
+    next.extend(previous.windows(2).map(|w| w[0] + w[1]));
+    next.push(1);
+    next
+}
+
+/// The first `rows` rows of the triangle
+pub fn triangle(rows: usize) -> Vec<Vec<u64>> {
+    let mut result: Vec<Vec<u64>> = Vec::with_capacity(rows);
+    for n in 0..rows {
+        let next = if n == 0 { vec![1] } else { next_row(&result[n - 1]) };
+        result.push(next);
+    }
+    result
+}
+
+/// Formats the first `rows` rows as a centred triangle
+///
+/// # Example
+/// ```
+/// use pprust::pascal::format_triangle;
+///
+/// assert_eq!("  1\n 1 1\n1 2 1\n", format_triangle(3));
+/// ```
+pub fn format_triangle(rows: usize) -> String {
+    let lines: Vec<String> = triangle(rows).iter()
+        .map(|r| r.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" "))
+        .collect();
+    let width = lines.last().map_or(0, |l| l.len());
+    lines.iter().map(|l| format!("{}{}\n", " ".repeat((width - l.len()) / 2), l)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use combinatorics::binomial;
+
+    #[test]
+    fn should_hold_binomial_coefficients() {
+        for (n, r) in triangle(MAX_ROW + 1).iter().enumerate() {
+            assert_eq!(n + 1, r.len());
+            for (k, &c) in r.iter().enumerate() {
+                assert_eq!(binomial(n as u64, k as u64), c, "C({}, {})", n, k);
+            }
+        }
+    }
+
+    #[test]
+    fn should_sum_rows_to_powers_of_two() {
+        for n in 0..60 {
+            assert_eq!(1u64 << n, row(n).iter().sum::<u64>());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_refuse_rows_that_overflow() {
+        row(MAX_ROW + 1);
+    }
+}
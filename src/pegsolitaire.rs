@@ -0,0 +1,278 @@
+//!
+//! # Problem description:
+//! #
+//! # Peg Solitaire
+//!
+//! The English board has 33 holes in the shape of a cross. At the start
+//! every hole but the central one holds a peg. A move jumps a peg over a
+//! neighbouring peg, horizontally or vertically, into an empty hole just
+//! beyond, and removes the peg jumped over. The goal is to finish with a
+//! single peg, in the centre.
+//!
+//! The solver is a depth-first search that remembers the positions from
+//! which it could not finish. Positions that are rotations or reflections
+//! of each other are equally hopeless, since the goal is symmetric, so
+//! each position is stored in a canonical form: the smallest of its eight
+//! symmetric images.
+//!
+
+use std::collections::HashSet;
+use std::fmt;
+
+const SIZE: usize = 7;
+const CENTRE: usize = 3 * SIZE + 3;
+
+/// A jump, as `(row, column)` positions on the 7x7 grid that holds the
+/// board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jump {
+    pub from: (usize, usize),
+    pub over: (usize, usize),
+    pub to: (usize, usize),
+}
+
+/// The pegs on an English board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Board {
+    pegs: u64,
+}
+
+fn is_hole(row: usize, col: usize) -> bool {
+    row < SIZE && col < SIZE && ((2..5).contains(&row) || (2..5).contains(&col))
+}
+
+fn bit(row: usize, col: usize) -> u64 {
+    1 << (row * SIZE + col)
+}
+
+/// Every possible jump on the board, in a fixed order
+fn all_jumps() -> Vec<Jump> {
+    let mut jumps = Vec::new();
+    for row in 0..SIZE {
+        for col in 0..SIZE {
+            let (r, c) = (row as isize, col as isize);
+            for &(dr, dc) in &[(0, 1), (1, 0), (0, -1), (-1, 0)] {
+                let (r2, c2) = (r + 2 * dr, c + 2 * dc);
+                if r2 < 0 || c2 < 0 {
+                    continue;
+                }
+                let (over, to) = (((r + dr) as usize, (c + dc) as usize), (r2 as usize, c2 as usize));
+                if is_hole(row, col) && is_hole(over.0, over.1) && is_hole(to.0, to.1) {
+                    jumps.push(Jump { from: (row, col), over, to });
+                }
+            }
+        }
+    }
+    jumps
+}
+
+impl Board {
+    /// The starting position: every hole filled except the centre
+    pub fn english() -> Board {
+        let mut pegs = 0;
+        for row in 0..SIZE {
+            for col in 0..SIZE {
+                if is_hole(row, col) {
+                    pegs |= bit(row, col);
+                }
+            }
+        }
+        Board { pegs: pegs & !(1 << CENTRE) }
+    }
+
+    /// A board with pegs at the given positions, or `None` if one of them
+    /// is not a hole
+    pub fn from_pegs(positions: &[(usize, usize)]) -> Option<Board> {
+        let mut pegs = 0;
+        for &(row, col) in positions {
+            if !is_hole(row, col) {
+                return None;
+            }
+            pegs |= bit(row, col);
+        }
+        Some(Board { pegs })
+    }
+
+    /// Number of pegs left
+    pub fn count(&self) -> u32 {
+        self.pegs.count_ones()
+    }
+
+    /// Whether there is a peg at a position
+    pub fn has_peg(&self, row: usize, col: usize) -> bool {
+        is_hole(row, col) && self.pegs & bit(row, col) != 0
+    }
+
+    /// Whether only one peg is left, in the centre
+    pub fn is_solved(&self) -> bool {
+        self.pegs == 1 << CENTRE
+    }
+
+    /// Makes a jump, or returns `None` if it is not legal here
+    pub fn apply(&self, jump: &Jump) -> Option<Board> {
+        let (from, over, to) = (bit(jump.from.0, jump.from.1), bit(jump.over.0, jump.over.1), bit(jump.to.0, jump.to.1));
+        let legal = self.has_peg(jump.from.0, jump.from.1) && self.has_peg(jump.over.0, jump.over.1)
+            && is_hole(jump.to.0, jump.to.1) && self.pegs & to == 0
+            && all_jumps().contains(jump);
+        if legal {
+            Some(Board { pegs: (self.pegs & !from & !over) | to })
+        } else {
+            None
+        }
+    }
+
+    /// The smallest encoding among the eight rotations and reflections
+    fn canonical(&self) -> u64 {
+        let mut images = [0u64; 8];
+        let mut pegs = self.pegs;
+        while pegs != 0 {
+            let i = pegs.trailing_zeros() as usize;
+            pegs &= pegs - 1;
+            let (r, c) = (i / SIZE, i % SIZE);
+            let (rr, rc) = (SIZE - 1 - r, SIZE - 1 - c);
+            let positions = [(r, c), (c, rr), (rr, rc), (rc, r), (r, rc), (rr, c), (c, r), (rc, rr)];
+            for (image, &(row, col)) in images.iter_mut().zip(positions.iter()) {
+                *image |= bit(row, col);
+            }
+        }
+        *images.iter().min().unwrap()
+    }
+}
+
+impl Default for Board {
+    fn default() -> Board {
+        Board::english()
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..SIZE {
+            let line: Vec<&str> = (0..SIZE).map(|col| {
+                if !is_hole(row, col) {
+                    " "
+                } else if self.has_peg(row, col) {
+                    "o"
+                } else {
+                    "."
+                }
+            }).collect();
+            writeln!(f, "{}", line.join(" ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+struct Solver {
+    jumps: Vec<Jump>,
+    masks: Vec<(u64, u64, u64)>,
+    dead: HashSet<u64>,
+    path: Vec<Jump>,
+}
+
+impl Solver {
+    fn search(&mut self, pegs: u64) -> bool {
+        if pegs == 1 << CENTRE {
+            return true;
+        }
+        let key = Board { pegs }.canonical();
+        if self.dead.contains(&key) {
+            return false;
+        }
+        for i in 0..self.jumps.len() {
+            let (from, over, to) = self.masks[i];
+            if pegs & from != 0 && pegs & over != 0 && pegs & to == 0 {
+                self.path.push(self.jumps[i]);
+                if self.search((pegs & !from & !over) | to) {
+                    return true;
+                }
+                self.path.pop();
+            }
+        }
+        self.dead.insert(key);
+        false
+    }
+}
+
+/// Finds a sequence of jumps leaving a single peg in the centre, or
+/// `None` if there is none
+///
+/// # Example
+/// ```
+/// use pprust::pegsolitaire::{solve, Board};
+///
+/// let board = Board::english();
+/// let jumps = solve(&board).unwrap();
+/// assert_eq!(31, jumps.len());
+/// let end = jumps.iter().fold(board, |b, j| b.apply(j).unwrap());
+/// assert!(end.is_solved());
+/// ```
+pub fn solve(board: &Board) -> Option<Vec<Jump>> {
+    let jumps = all_jumps();
+    let masks = jumps.iter().map(|j| (bit(j.from.0, j.from.1), bit(j.over.0, j.over.1), bit(j.to.0, j.to.1))).collect();
+    let mut solver = Solver { jumps, masks, dead: HashSet::new(), path: Vec::new() };
+    if solver.search(board.pegs) {
+        Some(solver.path)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_set_up_english_board() {
+        let board = Board::english();
+        assert_eq!(32, board.count());
+        assert!(!board.has_peg(3, 3));
+        assert!(board.has_peg(0, 2));
+        assert!(!board.has_peg(0, 0));
+        assert_eq!(76, all_jumps().len());
+    }
+
+    #[test]
+    fn should_print_board() {
+        let expected = "    o o o\n    o o o\no o o o o o o\no o o . o o o\no o o o o o o\n    o o o\n    o o o\n";
+        assert_eq!(expected, Board::english().to_string());
+    }
+
+    #[test]
+    fn should_apply_only_legal_jumps() {
+        let board = Board::english();
+        let jump = Jump { from: (1, 3), over: (2, 3), to: (3, 3) };
+        let next = board.apply(&jump).unwrap();
+        assert_eq!(31, next.count());
+        assert!(next.has_peg(3, 3) && !next.has_peg(2, 3) && !next.has_peg(1, 3));
+        assert_eq!(None, next.apply(&jump));
+        assert_eq!(None, board.apply(&Jump { from: (3, 0), over: (3, 1), to: (3, 2) }));
+        assert_eq!(None, board.apply(&Jump { from: (3, 1), over: (3, 3), to: (3, 5) }), "Jumps must be to the next hole but one");
+    }
+
+    #[test]
+    fn should_solve_english_board() {
+        let board = Board::english();
+        let jumps = solve(&board).unwrap();
+        let end = jumps.iter().fold(board, |b, j| b.apply(j).expect("Every jump should be legal"));
+        assert!(end.is_solved());
+    }
+
+    #[test]
+    fn should_treat_symmetric_boards_alike() {
+        let a = Board::from_pegs(&[(0, 2), (1, 2), (3, 3)]).unwrap();
+        let b = Board::from_pegs(&[(2, 6), (2, 5), (3, 3)]).unwrap();
+        let c = Board::from_pegs(&[(6, 4), (5, 4), (3, 3)]).unwrap();
+        assert_eq!(a.canonical(), b.canonical());
+        assert_eq!(a.canonical(), c.canonical());
+        assert_ne!(a.canonical(), Board::english().canonical());
+    }
+
+    #[test]
+    fn should_find_no_solution_when_impossible() {
+        let board = Board::from_pegs(&[(0, 2), (6, 4)]).unwrap();
+        assert_eq!(None, solve(&board));
+        assert_eq!(None, Board::from_pegs(&[(0, 0)]));
+        assert_eq!(Some(vec![]), solve(&Board::from_pegs(&[(3, 3)]).unwrap()));
+    }
+}
@@ -0,0 +1,411 @@
+//!
+//! # Problem description:
+//! #
+//! # Polynomial Arithmetic
+//!
+//! Polynomials with real coefficients: addition, subtraction,
+//! multiplication, division with remainder, evaluation, differentiation
+//! and the greatest common divisor.
+//!
+//! Multiplying term by term takes time proportional to the product of
+//! the degrees. The fast Fourier transform does better: it evaluates both
+//! polynomials at the complex roots of unity, multiplies the values
+//! pointwise and interpolates back, all in O(n log n). Horner's rule
+//! evaluates a polynomial with one multiplication and one addition per
+//! coefficient, and the greatest common divisor comes from Euclid's
+//! algorithm, as for integers.
+//!
+
+use complex::Complex;
+use std::f64::consts::PI;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// Coefficients smaller than this, relative to the largest, count as
+/// zero when computing a greatest common divisor
+const EPSILON: f64 = 1e-9;
+
+/// Products of polynomials with at least this many coefficients each use
+/// the FFT
+const FFT_THRESHOLD: usize = 64;
+
+/// A polynomial, its coefficients stored from the constant term up
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+    /// A polynomial from its coefficients, constant term first
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::poly::Polynomial;
+    ///
+    /// let p = Polynomial::new(&[1.0, -2.0, 3.0, 0.0]);
+    /// assert_eq!(Some(2), p.degree());
+    /// assert_eq!("3x^2 - 2x + 1", p.to_string());
+    /// ```
+    pub fn new(coefficients: &[f64]) -> Polynomial {
+        let mut p = Polynomial { coefficients: coefficients.to_vec() };
+        p.trim(0.0);
+        p
+    }
+
+    /// The zero polynomial
+    pub fn zero() -> Polynomial {
+        Polynomial { coefficients: Vec::new() }
+    }
+
+    /// The coefficients, constant term first, without trailing zeros
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    /// The degree, or `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    /// Whether this is the zero polynomial
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// The coefficient of the highest power, 0 for the zero polynomial
+    pub fn leading(&self) -> f64 {
+        self.coefficients.last().cloned().unwrap_or(0.0)
+    }
+
+    /// Evaluates the polynomial at `x` by Horner's rule
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::poly::Polynomial;
+    ///
+    /// assert_eq!(22.0, Polynomial::new(&[1.0, -2.0, 3.0]).eval(3.0));
+    /// ```
+    pub fn eval(&self, x: f64) -> f64 {
+        self.coefficients.iter().rev().fold(0.0, |acc, &c| acc * x + c)
+    }
+
+    /// The derivative
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::poly::Polynomial;
+    ///
+    /// let p = Polynomial::new(&[1.0, -2.0, 3.0]);
+    /// assert_eq!(Polynomial::new(&[-2.0, 6.0]), p.derivative());
+    /// ```
+    pub fn derivative(&self) -> Polynomial {
+        let coefficients: Vec<f64> = self.coefficients.iter().enumerate().skip(1)
+            .map(|(i, &c)| i as f64 * c)
+            .collect();
+        Polynomial::new(&coefficients)
+    }
+
+    /// The same polynomial divided by its leading coefficient
+    pub fn monic(&self) -> Polynomial {
+        let leading = self.leading();
+        if leading == 0.0 {
+            return Polynomial::zero();
+        }
+        Polynomial::new(&self.coefficients.iter().map(|c| c / leading).collect::<Vec<_>>())
+    }
+
+    /// The product computed term by term
+    pub fn mul_naive(&self, other: &Polynomial) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut product = vec![0.0; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, &a) in self.coefficients.iter().enumerate() {
+            for (j, &b) in other.coefficients.iter().enumerate() {
+                product[i + j] += a * b;
+            }
+        }
+        Polynomial::new(&product)
+    }
+
+    /// The product computed by the fast Fourier transform
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::poly::Polynomial;
+    ///
+    /// let p = Polynomial::new(&[1.0, 1.0]).mul_fft(&Polynomial::new(&[-1.0, 1.0]));
+    /// let expected = [-1.0, 0.0, 1.0];
+    /// assert!(p.coefficients().iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-12));
+    /// ```
+    pub fn mul_fft(&self, other: &Polynomial) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let len = self.coefficients.len() + other.coefficients.len() - 1;
+        let size = len.next_power_of_two();
+        let mut a = to_complex(&self.coefficients, size);
+        let mut b = to_complex(&other.coefficients, size);
+        fft(&mut a, false);
+        fft(&mut b, false);
+        for (x, y) in a.iter_mut().zip(&b) {
+            *x = *x * *y;
+        }
+        fft(&mut a, true);
+        let product: Vec<f64> = a[..len].iter().map(|c| c.re / size as f64).collect();
+        Polynomial::new(&product)
+    }
+
+    /// Divides by `divisor`, returning the quotient and the remainder
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::poly::Polynomial;
+    ///
+    /// // x^3 - 2x^2 - 4 = (x - 3)(x^2 + x + 3) + 5
+    /// let (q, r) = Polynomial::new(&[-4.0, 0.0, -2.0, 1.0]).div_rem(&Polynomial::new(&[-3.0, 1.0]));
+    /// assert_eq!(Polynomial::new(&[3.0, 1.0, 1.0]), q);
+    /// assert_eq!(Polynomial::new(&[5.0]), r);
+    /// ```
+    pub fn div_rem(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        let d = divisor.degree().expect("Division by the zero polynomial");
+        let n = match self.degree() {
+            Some(n) if n >= d => n,
+            _ => return (Polynomial::zero(), self.clone()),
+        };
+        let leading = divisor.leading();
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![0.0; n - d + 1];
+        for k in (0..=n - d).rev() {
+            let factor = remainder[k + d] / leading;
+            quotient[k] = factor;
+            for (j, &c) in divisor.coefficients.iter().enumerate() {
+                remainder[k + j] -= factor * c;
+            }
+            remainder[k + d] = 0.0;
+        }
+        remainder.truncate(d);
+        (Polynomial::new(&quotient), Polynomial::new(&remainder))
+    }
+
+    /// The monic greatest common divisor, by Euclid's algorithm
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::poly::Polynomial;
+    ///
+    /// // (x - 1)(x - 2) and (x - 1)(x + 3)
+    /// let a = Polynomial::new(&[2.0, -3.0, 1.0]);
+    /// let b = Polynomial::new(&[-3.0, 2.0, 1.0]);
+    /// let g = a.gcd(&b);
+    /// assert_eq!(Some(1), g.degree());
+    /// assert!((g.coefficients()[0] + 1.0).abs() < 1e-9);
+    /// ```
+    pub fn gcd(&self, other: &Polynomial) -> Polynomial {
+        let mut a = self.monic();
+        let mut b = other.monic();
+        while !b.is_zero() {
+            let (_, mut r) = a.div_rem(&b);
+            let scale = a.coefficients.iter().chain(&b.coefficients).fold(1.0f64, |m, c| m.max(c.abs()));
+            r.trim(EPSILON * scale);
+            a = b;
+            b = r.monic();
+        }
+        a
+    }
+
+    /// Drops the highest terms while they are no larger than `tolerance`
+    fn trim(&mut self, tolerance: f64) {
+        while self.coefficients.last().is_some_and(|c| c.abs() <= tolerance) {
+            self.coefficients.pop();
+        }
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, other: &Polynomial) -> Polynomial {
+        let len = self.coefficients.len().max(other.coefficients.len());
+        let sum: Vec<f64> = (0..len)
+            .map(|i| self.coefficients.get(i).unwrap_or(&0.0) + other.coefficients.get(i).unwrap_or(&0.0))
+            .collect();
+        Polynomial::new(&sum)
+    }
+}
+
+impl Neg for &Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Polynomial {
+        Polynomial { coefficients: self.coefficients.iter().map(|c| -c).collect() }
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, other: &Polynomial) -> Polynomial {
+        self + &-other
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, other: &Polynomial) -> Polynomial {
+        if self.coefficients.len().min(other.coefficients.len()) >= FFT_THRESHOLD {
+            self.mul_fft(other)
+        } else {
+            self.mul_naive(other)
+        }
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (power, &c) in self.coefficients.iter().enumerate().rev() {
+            if c == 0.0 {
+                continue;
+            }
+            if first {
+                if c < 0.0 {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", if c < 0.0 { "-" } else { "+" })?;
+            }
+            first = false;
+            let magnitude = c.abs();
+            if magnitude != 1.0 || power == 0 {
+                write!(f, "{}", magnitude)?;
+            }
+            match power {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", power)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_complex(coefficients: &[f64], size: usize) -> Vec<Complex> {
+    let mut values = vec![Complex { re: 0.0, im: 0.0 }; size];
+    for (v, &c) in values.iter_mut().zip(coefficients) {
+        v.re = c;
+    }
+    values
+}
+
+/// In-place iterative radix-2 FFT; the inverse transform is left
+/// unscaled
+fn fft(values: &mut [Complex], inverse: bool) {
+    let n = values.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = 2.0 * PI / len as f64 * if inverse { -1.0 } else { 1.0 };
+        let root = Complex { re: angle.cos(), im: angle.sin() };
+        for start in (0..n).step_by(len) {
+            let mut w = Complex { re: 1.0, im: 0.0 };
+            for k in 0..len / 2 {
+                let u = values[start + k];
+                let v = values[start + k + len / 2] * w;
+                values[start + k] = u + v;
+                values[start + k + len / 2] = u - v;
+                w = w * root;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn random(degree: usize, rng: &mut Rng) -> Polynomial {
+        let coefficients: Vec<f64> = (0..=degree).map(|_| rng.gen_range(0, 21) as f64 - 10.0).collect();
+        Polynomial::new(&coefficients)
+    }
+
+    fn assert_close(a: &Polynomial, b: &Polynomial, tolerance: f64) {
+        assert_eq!(a.degree(), b.degree(), "{} vs {}", a, b);
+        for (x, y) in a.coefficients().iter().zip(b.coefficients()) {
+            assert!((x - y).abs() < tolerance, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn should_add_and_subtract() {
+        let a = Polynomial::new(&[1.0, 2.0, 3.0]);
+        let b = Polynomial::new(&[1.0, 1.0, -3.0]);
+        assert_eq!(Polynomial::new(&[2.0, 3.0]), &a + &b);
+        assert_eq!(Polynomial::new(&[0.0, 1.0, 6.0]), &a - &b);
+        assert!((&a - &a).is_zero());
+    }
+
+    #[test]
+    fn should_multiply_by_fft_like_naively() {
+        let mut rng = Rng::new(11);
+        for &(m, n) in &[(0, 0), (1, 3), (10, 7), (100, 150), (300, 300)] {
+            let a = random(m, &mut rng);
+            let b = random(n, &mut rng);
+            assert_close(&a.mul_naive(&b), &a.mul_fft(&b), 1e-6);
+            assert_close(&a.mul_naive(&b), &(&a * &b), 1e-6);
+        }
+        assert!(Polynomial::zero().mul_fft(&Polynomial::new(&[1.0])).is_zero());
+    }
+
+    #[test]
+    fn should_divide_with_remainder() {
+        let mut rng = Rng::new(12);
+        for &(m, n) in &[(5, 2), (8, 8), (3, 6), (20, 1)] {
+            let a = random(m, &mut rng);
+            let b = random(n, &mut rng);
+            let (q, r) = a.div_rem(&b);
+            assert!(r.degree() < b.degree());
+            assert_close(&a, &(&(&q * &b) + &r), 1e-6);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_refuse_division_by_zero() {
+        Polynomial::new(&[1.0]).div_rem(&Polynomial::zero());
+    }
+
+    #[test]
+    fn should_find_common_factors() {
+        let common = Polynomial::new(&[-2.0, 0.0, 1.0]);
+        let a = &common * &Polynomial::new(&[1.0, 1.0]);
+        let b = &common * &Polynomial::new(&[5.0, 0.0, 3.0]);
+        assert_close(&common, &a.gcd(&b), 1e-9);
+        assert_eq!(Polynomial::new(&[1.0]), Polynomial::new(&[1.0, 1.0]).gcd(&Polynomial::new(&[-1.0, 1.0])));
+        assert_close(&a.monic(), &a.gcd(&Polynomial::zero()), 1e-12);
+    }
+
+    #[test]
+    fn should_format() {
+        assert_eq!("0", Polynomial::zero().to_string());
+        assert_eq!("-x^3 + 2.5x - 1", Polynomial::new(&[-1.0, 2.5, 0.0, -1.0]).to_string());
+        assert_eq!("x", Polynomial::new(&[0.0, 1.0]).to_string());
+    }
+}
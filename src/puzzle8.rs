@@ -0,0 +1,296 @@
+//!
+//! # Problem description:
+//! #
+//! # Eight Puzzle
+//!
+//! Eight numbered tiles sit in a 3x3 frame with one empty square. A move
+//! slides a tile next to the empty square into it. The goal is to bring
+//! the tiles into order:
+//!
+//! ```text
+//!     1 2 3
+//!     4 5 6
+//!     7 8 _
+//! ```
+//!
+//! Only half of the arrangements can reach the goal: a move never changes
+//! the parity of the number of inversions among the tiles, and the goal
+//! has none.
+//!
+//! The solver uses IDA*, a depth-first search bounded by an estimate of
+//! the total cost that is raised after each failed iteration, so it finds
+//! a shortest solution in little memory. The estimate is the Manhattan
+//! distance of each tile to its place, plus two moves for each tile that
+//! must leave its goal row or column to let another tile in the same line
+//! past it (linear conflict).
+//!
+
+/// Tiles in row order, with 0 for the empty square
+pub type Board = [u8; 9];
+
+/// The solved board
+pub const GOAL: Board = [1, 2, 3, 4, 5, 6, 7, 8, 0];
+
+/// The direction in which the empty square moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Move {
+    fn opposite(self) -> Move {
+        match self {
+            Move::Up => Move::Down,
+            Move::Down => Move::Up,
+            Move::Left => Move::Right,
+            Move::Right => Move::Left,
+        }
+    }
+}
+
+const MOVES: [Move; 4] = [Move::Up, Move::Down, Move::Left, Move::Right];
+
+fn is_valid(board: &Board) -> bool {
+    let mut seen = [false; 9];
+    board.iter().all(|&t| t < 9 && !std::mem::replace(&mut seen[t as usize], true))
+}
+
+/// Checks that a board holds each tile once and can reach the goal, that
+/// is, has an even number of inversions
+///
+/// # Example
+/// ```
+/// use pprust::puzzle8::is_solvable;
+///
+/// assert!(is_solvable(&[1, 2, 3, 4, 5, 6, 7, 0, 8]));
+/// assert!(!is_solvable(&[2, 1, 3, 4, 5, 6, 7, 8, 0]));
+/// ```
+pub fn is_solvable(board: &Board) -> bool {
+    if !is_valid(board) {
+        return false;
+    }
+    let tiles: Vec<u8> = board.iter().cloned().filter(|&t| t != 0).collect();
+    let inversions = (0..tiles.len())
+        .map(|i| tiles[i + 1..].iter().filter(|&&t| t < tiles[i]).count())
+        .sum::<usize>();
+    inversions % 2 == 0
+}
+
+/// Applies a move, or returns `None` if it would leave the frame
+pub fn apply(board: &Board, m: Move) -> Option<Board> {
+    let blank = board.iter().position(|&t| t == 0)?;
+    let (row, col) = (blank / 3, blank % 3);
+    let target = match m {
+        Move::Up if row > 0 => blank - 3,
+        Move::Down if row < 2 => blank + 3,
+        Move::Left if col > 0 => blank - 1,
+        Move::Right if col < 2 => blank + 1,
+        _ => return None,
+    };
+    let mut next = *board;
+    next.swap(blank, target);
+    Some(next)
+}
+
+/// Replays a move sequence, returning every board along the way starting
+/// with `board`, or `None` if some move is impossible
+///
+/// # Example
+/// ```
+/// use pprust::puzzle8::{replay, Move, GOAL};
+///
+/// let boards = replay(&[1, 2, 3, 4, 5, 6, 0, 7, 8], &[Move::Right, Move::Right]).unwrap();
+/// assert_eq!(3, boards.len());
+/// assert_eq!(GOAL, boards[2]);
+/// ```
+pub fn replay(board: &Board, moves: &[Move]) -> Option<Vec<Board>> {
+    let mut boards = vec![*board];
+    for &m in moves {
+        let next = apply(&boards[boards.len() - 1], m)?;
+        boards.push(next);
+    }
+    Some(boards)
+}
+
+fn manhattan(board: &Board) -> u32 {
+    board.iter().enumerate().filter(|&(_, &t)| t != 0).map(|(i, &t)| {
+        let goal = (t - 1) as usize;
+        ((i / 3) as i32 - (goal / 3) as i32).unsigned_abs() + ((i % 3) as i32 - (goal % 3) as i32).unsigned_abs()
+    }).sum()
+}
+
+/// Extra moves forced by tiles in their goal line but in the wrong order
+fn linear_conflict(board: &Board) -> u32 {
+    let mut extra = 0;
+    for line in 0..3 {
+        // Goal positions along the line of the tiles that belong in it
+        let rows: Vec<usize> = (0..3).map(|c| board[3 * line + c])
+            .filter(|&t| t != 0 && (t as usize - 1) / 3 == line)
+            .map(|t| (t as usize - 1) % 3)
+            .collect();
+        let cols: Vec<usize> = (0..3).map(|r| board[3 * r + line])
+            .filter(|&t| t != 0 && (t as usize - 1) % 3 == line)
+            .map(|t| (t as usize - 1) / 3)
+            .collect();
+        extra += 2 * (conflicts_to_remove(rows) + conflicts_to_remove(cols));
+    }
+    extra
+}
+
+/// How many tiles must leave the line so the rest are in order
+fn conflicts_to_remove(mut goals: Vec<usize>) -> u32 {
+    let mut removed = 0;
+    loop {
+        let conflicts = |i: usize, goals: &[usize]| {
+            (0..goals.len()).filter(|&j| (j < i && goals[j] > goals[i]) || (j > i && goals[j] < goals[i])).count()
+        };
+        let worst = (0..goals.len()).max_by_key(|&i| conflicts(i, &goals));
+        match worst {
+            Some(i) if conflicts(i, &goals) > 0 => {
+                goals.remove(i);
+                removed += 1;
+            }
+            _ => return removed,
+        }
+    }
+}
+
+fn estimate(board: &Board) -> u32 {
+    manhattan(board) + linear_conflict(board)
+}
+
+enum Outcome {
+    Found,
+    Exceeded(u32),
+}
+
+fn search(board: &Board, cost: u32, bound: u32, last: Option<Move>, path: &mut Vec<Move>) -> Outcome {
+    let f = cost + estimate(board);
+    if f > bound {
+        return Outcome::Exceeded(f);
+    }
+    if *board == GOAL {
+        return Outcome::Found;
+    }
+    let mut next_bound = u32::MAX;
+    for &m in MOVES.iter().filter(|&&m| Some(m.opposite()) != last) {
+        if let Some(next) = apply(board, m) {
+            path.push(m);
+            match search(&next, cost + 1, bound, Some(m), path) {
+                Outcome::Found => return Outcome::Found,
+                Outcome::Exceeded(f) => next_bound = next_bound.min(f),
+            }
+            path.pop();
+        }
+    }
+    Outcome::Exceeded(next_bound)
+}
+
+/// Finds a shortest sequence of moves from `board` to the goal, or `None`
+/// if the board is not solvable
+///
+/// # Example
+/// ```
+/// use pprust::puzzle8::{solve, replay, GOAL};
+///
+/// let board = [4, 1, 3, 7, 2, 6, 0, 5, 8];
+/// let moves = solve(&board).unwrap();
+/// assert_eq!(6, moves.len());
+/// assert_eq!(GOAL, *replay(&board, &moves).unwrap().last().unwrap());
+/// ```
+pub fn solve(board: &Board) -> Option<Vec<Move>> {
+    if !is_solvable(board) {
+        return None;
+    }
+    let mut bound = estimate(board);
+    let mut path = Vec::new();
+    loop {
+        match search(board, 0, bound, None, &mut path) {
+            Outcome::Found => return Some(path),
+            Outcome::Exceeded(f) => bound = f,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, VecDeque};
+
+    /// Distance to the goal of every solvable board, by breadth-first search
+    fn distances() -> HashMap<Board, u32> {
+        let mut distance = HashMap::new();
+        let mut queue = VecDeque::new();
+        distance.insert(GOAL, 0);
+        queue.push_back(GOAL);
+        while let Some(board) = queue.pop_front() {
+            let d = distance[&board];
+            for &m in MOVES.iter() {
+                if let Some(next) = apply(&board, m) {
+                    distance.entry(next).or_insert_with(|| {
+                        queue.push_back(next);
+                        d + 1
+                    });
+                }
+            }
+        }
+        distance
+    }
+
+    #[test]
+    fn should_find_optimal_solutions() {
+        let distance = distances();
+        assert_eq!(181_440, distance.len(), "Half of the 9! boards are reachable");
+        let mut x: u64 = 88172645463325252;
+        for _ in 0..40 {
+            let mut board = GOAL;
+            for _ in 0..60 {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                if let Some(next) = apply(&board, MOVES[(x % 4) as usize]) {
+                    board = next;
+                }
+            }
+            let moves = solve(&board).unwrap();
+            assert_eq!(distance[&board], moves.len() as u32, "Board {:?}", board);
+            assert_eq!(GOAL, *replay(&board, &moves).unwrap().last().unwrap());
+        }
+    }
+
+    #[test]
+    fn should_keep_estimate_admissible() {
+        for (board, &d) in distances().iter().filter(|&(b, _)| b[0] % 3 == 0) {
+            assert!(estimate(board) <= d, "Estimate {} over {} for {:?}", estimate(board), d, board);
+        }
+    }
+
+    #[test]
+    fn should_solve_a_hardest_board() {
+        let moves = solve(&[8, 6, 7, 2, 5, 4, 3, 0, 1]).unwrap();
+        assert_eq!(31, moves.len());
+    }
+
+    #[test]
+    fn should_refuse_unsolvable_boards() {
+        assert_eq!(None, solve(&[1, 2, 3, 4, 5, 6, 8, 7, 0]));
+        assert_eq!(None, solve(&[1, 1, 3, 4, 5, 6, 7, 8, 0]));
+        assert_eq!(Some(vec![]), solve(&GOAL));
+    }
+
+    #[test]
+    fn should_count_linear_conflicts() {
+        assert_eq!(0, linear_conflict(&GOAL));
+        assert_eq!(2, linear_conflict(&[2, 1, 3, 4, 5, 6, 7, 8, 0]));
+        assert_eq!(4, linear_conflict(&[3, 2, 1, 4, 5, 6, 7, 8, 0]), "Two of three reversed tiles must move aside");
+    }
+
+    #[test]
+    fn should_reject_impossible_replays() {
+        assert_eq!(None, apply(&GOAL, Move::Down));
+        assert_eq!(None, replay(&GOAL, &[Move::Up, Move::Up, Move::Up]));
+    }
+}
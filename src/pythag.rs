@@ -0,0 +1,154 @@
+//!
+//! # Problem description:
+//! #
+//! # Pythagorean Triples
+//!
+//! A Pythagorean triple is three positive integers with a² + b² = c²,
+//! like 3, 4, 5; it is primitive when the three have no common factor.
+//! Euclid's formula gives every primitive triple exactly once: for
+//! coprime m > n > 0 of opposite parity, a = m² - n², b = 2mn and
+//! c = m² + n², and every other triple is a multiple of a primitive one.
+//!
+//! The primitive triples also form a ternary tree rooted at 3, 4, 5
+//! (Berggren's tree): three fixed linear maps take each triple to three
+//! larger ones, and every primitive triple appears once. Walking the tree
+//! and cutting off branches whose hypotenuse is too large lists the
+//! triples below a limit without any gcd tests.
+//!
+
+/// A triple `(a, b, c)` with `a < b < c` and a² + b² = c²
+pub type Triple = (u64, u64, u64);
+
+/// The triple given by Euclid's formula for `m > n > 0`, legs in order
+///
+/// # Example
+/// ```
+/// use pprust::pythag::euclid;
+///
+/// assert_eq!((3, 4, 5), euclid(2, 1));
+/// assert_eq!((20, 21, 29), euclid(5, 2));
+/// ```
+pub fn euclid(m: u64, n: u64) -> Triple {
+    assert!(m > n && n > 0, "Need m > n > 0");
+    let (a, b) = (m * m - n * n, 2 * m * n);
+    (a.min(b), a.max(b), m * m + n * n)
+}
+
+/// An iterator over the primitive triples with hypotenuse below a limit,
+/// walking Berggren's tree depth first
+pub struct PrimitiveTriples {
+    limit: u64,
+    stack: Vec<(i64, i64, i64)>,
+}
+
+/// The primitive triples with `c < limit`, in no particular order
+///
+/// # Example
+/// ```
+/// use pprust::pythag::primitive_triples_below;
+///
+/// let mut triples: Vec<_> = primitive_triples_below(30).collect();
+/// triples.sort_by_key(|t| t.2);
+/// assert_eq!(vec![(3, 4, 5), (5, 12, 13), (8, 15, 17), (7, 24, 25), (20, 21, 29)], triples);
+/// ```
+pub fn primitive_triples_below(limit: u64) -> PrimitiveTriples {
+    let stack = if limit > 5 { vec![(3, 4, 5)] } else { Vec::new() };
+    PrimitiveTriples { limit, stack }
+}
+
+impl Iterator for PrimitiveTriples {
+    type Item = Triple;
+
+    fn next(&mut self) -> Option<Triple> {
+        let (a, b, c) = self.stack.pop()?;
+        let children = [
+            (a - 2 * b + 2 * c, 2 * a - b + 2 * c, 2 * a - 2 * b + 3 * c),
+            (a + 2 * b + 2 * c, 2 * a + b + 2 * c, 2 * a + 2 * b + 3 * c),
+            (-a + 2 * b + 2 * c, -2 * a + b + 2 * c, -2 * a + 2 * b + 3 * c),
+        ];
+        let limit = self.limit;
+        self.stack.extend(children.iter().filter(|child| (child.2 as u64) < limit));
+        let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+        Some((a.min(b), a.max(b), c as u64))
+    }
+}
+
+/// Every triple, primitive or not, whose sides add up to `perimeter`,
+/// ordered by the shortest side
+///
+/// # Example
+/// ```
+/// use pprust::pythag::triples_with_perimeter;
+///
+/// assert_eq!(vec![(200, 375, 425)], triples_with_perimeter(1000));
+/// ```
+pub fn triples_with_perimeter(perimeter: u64) -> Vec<Triple> {
+    // The perimeter of Euclid's triple is 2m(m + n), times the multiple k
+    let mut triples = Vec::new();
+    let mut m = 2;
+    while 2 * m * (m + 1) <= perimeter {
+        for n in 1..m {
+            let primitive = 2 * m * (m + n);
+            if (m - n) % 2 == 1 && gcd(m, n) == 1 && perimeter.is_multiple_of(primitive) {
+                let k = perimeter / primitive;
+                let (a, b, c) = euclid(m, n);
+                triples.push((k * a, k * b, k * c));
+            }
+        }
+        m += 1;
+    }
+    triples.sort();
+    triples
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(limit: u64) -> Vec<Triple> {
+        let mut triples = Vec::new();
+        for c in 1..limit {
+            for a in 1..c {
+                let b2 = c * c - a * a;
+                let b = (b2 as f64).sqrt().round() as u64;
+                if a < b && b * b == b2 && gcd(gcd(a, b), c) == 1 {
+                    triples.push((a, b, c));
+                }
+            }
+        }
+        triples.sort();
+        triples
+    }
+
+    #[test]
+    fn should_walk_every_primitive_triple() {
+        for &limit in &[0, 5, 6, 100, 1000] {
+            let mut triples: Vec<Triple> = primitive_triples_below(limit).collect();
+            triples.sort();
+            assert_eq!(brute_force(limit), triples, "below {}", limit);
+        }
+        assert_eq!(16, primitive_triples_below(100).count());
+    }
+
+    #[test]
+    fn should_find_triples_by_perimeter() {
+        assert_eq!(vec![(20, 48, 52), (24, 45, 51), (30, 40, 50)], triples_with_perimeter(120));
+        assert!(triples_with_perimeter(11).is_empty());
+        for p in 1..500 {
+            let mut expected = Vec::new();
+            for a in 1..p {
+                for b in a + 1..p - a {
+                    let c = p - a - b;
+                    if a * a + b * b == c * c {
+                        expected.push((a, b, c));
+                    }
+                }
+            }
+            assert_eq!(expected, triples_with_perimeter(p), "perimeter {}", p);
+        }
+    }
+}
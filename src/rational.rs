@@ -0,0 +1,451 @@
+//!
+//! # Problem description:
+//! #
+//! # Rational Numbers
+//!
+//! A rational number is a fraction n / d of integers with d nonzero.
+//! Kept in lowest terms, with the sign on the numerator, each number has
+//! exactly one representation, so equality is just equality of the parts.
+//! Arithmetic follows the school rules, reducing by the greatest common
+//! divisor after each operation; intermediate results are computed in
+//! 128 bits, so an operation only overflows when its reduced result does
+//! not fit in 64.
+//!
+//! Every rational number also has a finite continued fraction
+//! a0 + 1 / (a1 + 1 / (a2 + ...)), found by Euclid's algorithm. Cutting
+//! it short gives the convergents, the best approximations with small
+//! denominators.
+//!
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::result;
+use std::str::FromStr;
+
+/// Errors parsing a rational number
+#[derive(Debug, Clone, PartialEq)]
+pub enum RatioError {
+    /// The text is not an integer or a fraction of integers
+    Syntax(String),
+    /// The denominator is zero
+    ZeroDenominator,
+}
+
+impl fmt::Display for RatioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RatioError::Syntax(ref text) => write!(f, "'{}' is not a rational number", text),
+            RatioError::ZeroDenominator => write!(f, "zero denominator"),
+        }
+    }
+}
+
+/// The result of parsing a rational number
+pub type RatioResult<T> = result::Result<T, RatioError>;
+
+/// A rational number in lowest terms, with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    numer: i64,
+    denom: i64,
+}
+
+/// The greatest common divisor of two integers, never negative
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+impl Ratio {
+    /// The fraction `numer / denom` in lowest terms
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rational::Ratio;
+    ///
+    /// let r = Ratio::new(6, -4);
+    /// assert_eq!((-3, 2), (r.numer(), r.denom()));
+    /// ```
+    pub fn new(numer: i64, denom: i64) -> Ratio {
+        assert!(denom != 0, "Zero denominator");
+        Ratio::reduce(numer as i128, denom as i128).expect("Ratio overflow")
+    }
+
+    /// The integer `n`
+    pub fn from_integer(n: i64) -> Ratio {
+        Ratio { numer: n, denom: 1 }
+    }
+
+    /// Reduces `numer / denom`, or `None` if the result does not fit
+    fn reduce(numer: i128, denom: i128) -> Option<Ratio> {
+        let g = gcd(numer, denom);
+        let sign = if denom < 0 { -1 } else { 1 };
+        let (numer, denom) = (sign * numer / g, sign * denom / g);
+        if numer < i64::MIN as i128 || numer > i64::MAX as i128 || denom > i64::MAX as i128 {
+            return None;
+        }
+        Some(Ratio { numer: numer as i64, denom: denom as i64 })
+    }
+
+    /// The numerator
+    pub fn numer(&self) -> i64 {
+        self.numer
+    }
+
+    /// The denominator, always positive
+    pub fn denom(&self) -> i64 {
+        self.denom
+    }
+
+    /// Whether the number is an integer
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    /// The largest integer not greater than the number
+    pub fn floor(&self) -> i64 {
+        self.numer.div_euclid(self.denom)
+    }
+
+    /// The absolute value
+    pub fn abs(&self) -> Ratio {
+        if self.numer < 0 { -*self } else { *self }
+    }
+
+    /// The reciprocal, or `None` for zero
+    pub fn recip(&self) -> Option<Ratio> {
+        if self.numer == 0 {
+            return None;
+        }
+        Ratio::reduce(self.denom as i128, self.numer as i128)
+    }
+
+    /// The nearest `f64`
+    pub fn to_f64(&self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+
+    /// The sum, or `None` on overflow
+    pub fn checked_add(&self, other: &Ratio) -> Option<Ratio> {
+        let (a, b, c, d) = self.parts(other);
+        Ratio::reduce(a * d + c * b, b * d)
+    }
+
+    /// The difference, or `None` on overflow
+    pub fn checked_sub(&self, other: &Ratio) -> Option<Ratio> {
+        let (a, b, c, d) = self.parts(other);
+        Ratio::reduce(a * d - c * b, b * d)
+    }
+
+    /// The product, or `None` on overflow
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rational::Ratio;
+    ///
+    /// let big = Ratio::from_integer(i64::MAX);
+    /// assert_eq!(None, big.checked_mul(&Ratio::from_integer(2)));
+    /// assert_eq!(Some(Ratio::from_integer(1)), big.checked_mul(&Ratio::new(1, i64::MAX)));
+    /// ```
+    pub fn checked_mul(&self, other: &Ratio) -> Option<Ratio> {
+        let (a, b, c, d) = self.parts(other);
+        Ratio::reduce(a * c, b * d)
+    }
+
+    /// The quotient, or `None` on overflow or division by zero
+    pub fn checked_div(&self, other: &Ratio) -> Option<Ratio> {
+        let (a, b, c, d) = self.parts(other);
+        if c == 0 {
+            return None;
+        }
+        Ratio::reduce(a * d, b * c)
+    }
+
+    fn parts(&self, other: &Ratio) -> (i128, i128, i128, i128) {
+        (self.numer as i128, self.denom as i128, other.numer as i128, other.denom as i128)
+    }
+
+    /// The terms of the continued fraction, the last one greater than 1
+    /// unless it is the only one
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rational::Ratio;
+    ///
+    /// assert_eq!(vec![3, 7, 15, 1, 292], Ratio::new(103993, 33102).continued_fraction());
+    /// assert_eq!(vec![-1, 1, 2], Ratio::new(-1, 3).continued_fraction());
+    /// ```
+    pub fn continued_fraction(&self) -> Vec<i64> {
+        let mut terms = Vec::new();
+        let (mut n, mut d) = (self.numer as i128, self.denom as i128);
+        while d != 0 {
+            let q = n.div_euclid(d);
+            terms.push(q as i64);
+            let r = n - q * d;
+            n = d;
+            d = r;
+        }
+        terms
+    }
+
+    /// The number with the given continued fraction, or `None` if there
+    /// are no terms, a term after the first is not positive, or the
+    /// result overflows
+    pub fn from_continued_fraction(terms: &[i64]) -> Option<Ratio> {
+        let (&last, rest) = terms.split_last()?;
+        if terms.iter().skip(1).any(|&a| a <= 0) {
+            return None;
+        }
+        let mut value = Ratio::from_integer(last);
+        for &a in rest.iter().rev() {
+            value = Ratio::from_integer(a).checked_add(&value.recip()?)?;
+        }
+        Some(value)
+    }
+
+    /// The convergents of the continued fraction, ending with the number
+    /// itself
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rational::Ratio;
+    ///
+    /// let pi = Ratio::new(103993, 33102);
+    /// assert_eq!(Ratio::new(355, 113), pi.convergents()[3]);
+    /// ```
+    pub fn convergents(&self) -> Vec<Ratio> {
+        let (mut h, mut k) = ((1i128, 0i128), (0i128, 1i128));
+        self.continued_fraction().iter().map(|&a| {
+            let a = a as i128;
+            h = (a * h.0 + h.1, h.0);
+            k = (a * k.0 + k.1, k.0);
+            Ratio::reduce(h.0, k.0).expect("Convergents are no larger than the number")
+        }).collect()
+    }
+
+    /// The closest number with a denominator at most `max_denom`
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rational::Ratio;
+    ///
+    /// let pi = Ratio::new(314159265, 100000000);
+    /// assert_eq!(Ratio::new(22, 7), pi.limit_denominator(10));
+    /// assert_eq!(Ratio::new(355, 113), pi.limit_denominator(1000));
+    /// ```
+    pub fn limit_denominator(&self, max_denom: i64) -> Ratio {
+        assert!(max_denom >= 1, "The denominator limit must be positive");
+        if self.denom <= max_denom {
+            return *self;
+        }
+        let (mut p0, mut q0, mut p1, mut q1) = (0i128, 1i128, 1i128, 0i128);
+        let (mut n, mut d) = (self.numer as i128, self.denom as i128);
+        loop {
+            let a = n.div_euclid(d);
+            let q2 = q0 + a * q1;
+            if q2 > max_denom as i128 {
+                break;
+            }
+            let p2 = p0 + a * p1;
+            p0 = p1;
+            q0 = q1;
+            p1 = p2;
+            q1 = q2;
+            let r = n - a * d;
+            n = d;
+            d = r;
+        }
+        // The best approximation is the last convergent or the largest
+        // semiconvergent still within the limit
+        let k = (max_denom as i128 - q0) / q1;
+        let semi = Ratio::reduce(p0 + k * p1, q0 + k * q1).expect("Within the limit");
+        let convergent = Ratio::reduce(p1, q1).expect("Within the limit");
+        if (semi - *self).abs() < (convergent - *self).abs() { semi } else { convergent }
+    }
+}
+
+impl Default for Ratio {
+    fn default() -> Ratio {
+        Ratio::from_integer(0)
+    }
+}
+
+impl From<i64> for Ratio {
+    fn from(n: i64) -> Ratio {
+        Ratio::from_integer(n)
+    }
+}
+
+impl Add for Ratio {
+    type Output = Ratio;
+
+    fn add(self, other: Ratio) -> Ratio {
+        self.checked_add(&other).expect("Ratio overflow")
+    }
+}
+
+impl Sub for Ratio {
+    type Output = Ratio;
+
+    fn sub(self, other: Ratio) -> Ratio {
+        self.checked_sub(&other).expect("Ratio overflow")
+    }
+}
+
+impl Mul for Ratio {
+    type Output = Ratio;
+
+    fn mul(self, other: Ratio) -> Ratio {
+        self.checked_mul(&other).expect("Ratio overflow")
+    }
+}
+
+impl Div for Ratio {
+    type Output = Ratio;
+
+    fn div(self, other: Ratio) -> Ratio {
+        assert!(other.numer != 0, "Division by zero");
+        self.checked_div(&other).expect("Ratio overflow")
+    }
+}
+
+impl Neg for Ratio {
+    type Output = Ratio;
+
+    fn neg(self) -> Ratio {
+        Ratio { numer: self.numer.checked_neg().expect("Ratio overflow"), denom: self.denom }
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Ratio) -> Ordering {
+        let (a, b, c, d) = self.parts(other);
+        (a * d).cmp(&(c * b))
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Ratio) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl FromStr for Ratio {
+    type Err = RatioError;
+
+    /// Parses an integer or a fraction like `-3/4`
+    fn from_str(s: &str) -> RatioResult<Ratio> {
+        let syntax = || RatioError::Syntax(s.to_string());
+        let mut parts = s.splitn(2, '/');
+        let numer: i64 = parts.next().unwrap_or("").trim().parse().map_err(|_| syntax())?;
+        let denom: i64 = match parts.next() {
+            Some(d) => d.trim().parse().map_err(|_| syntax())?,
+            None => 1,
+        };
+        if denom == 0 {
+            return Err(RatioError::ZeroDenominator);
+        }
+        Ratio::reduce(numer as i128, denom as i128).ok_or_else(syntax)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(n: i64, d: i64) -> Ratio {
+        Ratio::new(n, d)
+    }
+
+    #[test]
+    fn should_normalize() {
+        assert_eq!(r(1, 2), r(2, 4));
+        assert_eq!(r(-1, 2), r(1, -2));
+        assert_eq!(r(0, 1), r(0, -7));
+        assert_eq!(1, r(0, -7).denom());
+    }
+
+    #[test]
+    fn should_do_arithmetic() {
+        assert_eq!(r(5, 6), r(1, 2) + r(1, 3));
+        assert_eq!(r(1, 6), r(1, 2) - r(1, 3));
+        assert_eq!(r(1, 6), r(1, 2) * r(1, 3));
+        assert_eq!(r(3, 2), r(1, 2) / r(1, 3));
+        assert_eq!(r(-1, 2), -r(1, 2));
+        assert_eq!(Some(r(-3, 1)), r(-1, 3).recip());
+        assert_eq!(None, r(0, 1).recip());
+        assert_eq!(None, r(1, 2).checked_div(&r(0, 1)));
+    }
+
+    #[test]
+    fn should_detect_overflow() {
+        let max = Ratio::from_integer(i64::MAX);
+        assert_eq!(None, max.checked_add(&r(1, 1)));
+        assert_eq!(None, Ratio::from_integer(i64::MIN).checked_sub(&r(1, 1)));
+        assert_eq!(None, r(1, i64::MAX).checked_mul(&r(1, 2)));
+        // Large intermediate products are fine when the result is small
+        assert_eq!(Some(r(1, 1)), r(i64::MAX, 3).checked_div(&r(i64::MAX, 3)));
+        assert_eq!(Some(r(2, 3)), r(i64::MAX - 1, i64::MAX).checked_add(&r(1, i64::MAX)).and_then(|x| x.checked_mul(&r(2, 3))));
+    }
+
+    #[test]
+    fn should_order() {
+        let mut values = vec![r(1, 2), r(-3, 4), r(2, 3), r(0, 1), r(-1, 1)];
+        values.sort();
+        assert_eq!(vec![r(-1, 1), r(-3, 4), r(0, 1), r(1, 2), r(2, 3)], values);
+        assert!(r(i64::MAX, 2) > r(i64::MAX - 1, 2));
+        assert_eq!(-1, r(-1, 2).floor());
+    }
+
+    #[test]
+    fn should_round_trip_continued_fractions() {
+        for &(n, d) in &[(0, 1), (7, 1), (-7, 3), (415, 93), (103993, 33102), (i64::MAX, i64::MAX - 1)] {
+            let x = r(n, d);
+            assert_eq!(Some(x), Ratio::from_continued_fraction(&x.continued_fraction()), "{}", x);
+        }
+        assert_eq!(vec![4, 2, 6, 7], r(415, 93).continued_fraction());
+        assert_eq!(None, Ratio::from_continued_fraction(&[]));
+        assert_eq!(None, Ratio::from_continued_fraction(&[1, 0]));
+    }
+
+    #[test]
+    fn should_limit_denominators() {
+        let x = r(415, 93);
+        for max in 1..100 {
+            let best = x.limit_denominator(max);
+            assert!(best.denom() <= max);
+            for d in 1..=max {
+                let candidate = r((x * Ratio::from_integer(d)).floor(), d);
+                for c in &[candidate, candidate + Ratio::new(1, d)] {
+                    assert!((best - x).abs() <= (*c - x).abs(), "{} beats {} at {}", c, best, max);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_parse_and_format() {
+        assert_eq!(Ok(r(-3, 4)), "-6/8".parse());
+        assert_eq!(Ok(r(5, 1)), " 5 ".parse());
+        assert_eq!(Err(RatioError::ZeroDenominator), "1/0".parse::<Ratio>());
+        assert_eq!(Err(RatioError::Syntax("a/2".to_string())), "a/2".parse::<Ratio>());
+        assert_eq!("-3/4", r(-3, 4).to_string());
+        assert_eq!("2", r(4, 2).to_string());
+    }
+}
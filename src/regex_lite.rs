@@ -0,0 +1,622 @@
+//!
+//! # Problem description:
+//! #
+//! # Regular Expressions
+//!
+//! Write a regular expression matcher supporting literal characters,
+//! the `.` wildcard, character classes like `[a-z]` and `[^0-9]`, the
+//! `*`, `+` and `?` repetition operators, alternation with `|`,
+//! grouping with parentheses and the `^` and `$` anchors.
+//!
+//! The pattern is parsed into a small syntax tree, compiled to a
+//! Thompson NFA and simulated breadth-first, so matching never
+//! backtracks and runs in O(pattern * text) time.
+//!
+
+use std::fmt;
+use std::iter;
+use std::result;
+
+/// Errors found while parsing a pattern
+#[derive(Debug, PartialEq)]
+pub enum RegexError {
+    /// The pattern ended in the middle of an escape or a group
+    UnexpectedEnd,
+    /// A `(` without a `)` or a `)` without a `(`
+    UnbalancedParenthesis,
+    /// A `[` without a closing `]`
+    UnclosedClass,
+    /// A repetition operator with nothing before it, like `*a`
+    NothingToRepeat(char),
+    /// A class range going backwards, like `[z-a]`
+    InvalidRange(char, char),
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RegexError::UnexpectedEnd => write!(f, "unexpected end of pattern"),
+            RegexError::UnbalancedParenthesis => write!(f, "unbalanced parenthesis"),
+            RegexError::UnclosedClass => write!(f, "unclosed character class"),
+            RegexError::NothingToRepeat(c) => write!(f, "nothing to repeat before '{}'", c),
+            RegexError::InvalidRange(a, b) => write!(f, "invalid range {}-{}", a, b),
+        }
+    }
+}
+
+/// The result of compiling a pattern
+pub type RegexResult<T> = result::Result<T, RegexError>;
+
+#[derive(Debug, Clone)]
+struct Class {
+    negated: bool,
+    ranges: Vec<(char, char)>,
+}
+
+impl Class {
+    fn contains(&self, c: char) -> bool {
+        let found = self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+        found != self.negated
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternate(Box<Node>, Box<Node>),
+    Star(Box<Node>),
+    Plus(Box<Node>),
+    Quest(Box<Node>),
+}
+
+struct Parser<'a> {
+    chars: iter::Peekable<::std::str::Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(pattern: &str) -> RegexResult<Node> {
+        let mut parser = Parser { chars: pattern.chars().peekable() };
+        let node = parser.alternation()?;
+        match parser.chars.next() {
+            None => Ok(node),
+            Some(_) => Err(RegexError::UnbalancedParenthesis),
+        }
+    }
+
+    fn alternation(&mut self) -> RegexResult<Node> {
+        let left = self.concatenation()?;
+        if self.chars.peek() == Some(&'|') {
+            self.chars.next();
+            let right = self.alternation()?;
+            Ok(Node::Alternate(Box::new(left), Box::new(right)))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn concatenation(&mut self) -> RegexResult<Node> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.chars.peek() {
+                None | Some(&'|') | Some(&')') => break,
+                _ => nodes.push(self.repetition()?),
+            }
+        }
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.pop().expect("Already checked length")),
+            _ => Ok(Node::Concat(nodes)),
+        }
+    }
+
+    fn repetition(&mut self) -> RegexResult<Node> {
+        let mut node = self.atom()?;
+        loop {
+            node = match self.chars.peek() {
+                Some(&'*') => Node::Star(Box::new(node)),
+                Some(&'+') => Node::Plus(Box::new(node)),
+                Some(&'?') => Node::Quest(Box::new(node)),
+                _ => return Ok(node),
+            };
+            self.chars.next();
+        }
+    }
+
+    fn atom(&mut self) -> RegexResult<Node> {
+        match self.chars.next() {
+            None => Err(RegexError::UnexpectedEnd),
+            Some('(') => {
+                let node = self.alternation()?;
+                match self.chars.next() {
+                    Some(')') => Ok(node),
+                    _ => Err(RegexError::UnbalancedParenthesis),
+                }
+            }
+            Some(c @ '*') | Some(c @ '+') | Some(c @ '?') => Err(RegexError::NothingToRepeat(c)),
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Start),
+            Some('$') => Ok(Node::End),
+            Some('[') => self.class(),
+            Some('\\') => self.escape(),
+            Some(c) => Ok(Node::Char(c)),
+        }
+    }
+
+    fn escape(&mut self) -> RegexResult<Node> {
+        let c = self.chars.next().ok_or(RegexError::UnexpectedEnd)?;
+        Ok(match escape_class(c) {
+            Some(class) => Node::Class(class),
+            None => Node::Char(escape_char(c)),
+        })
+    }
+
+    fn class(&mut self) -> RegexResult<Node> {
+        let mut class = Class { negated: false, ranges: Vec::new() };
+        if self.chars.peek() == Some(&'^') {
+            self.chars.next();
+            class.negated = true;
+        }
+        let mut first = true;
+        loop {
+            let c = self.chars.next().ok_or(RegexError::UnclosedClass)?;
+            let lo = match c {
+                ']' if !first => return Ok(Node::Class(class)),
+                '\\' => {
+                    let e = self.chars.next().ok_or(RegexError::UnclosedClass)?;
+                    if let Some(escaped) = escape_class(e) {
+                        if escaped.negated {
+                            class.ranges.extend(complement(escaped.ranges));
+                        } else {
+                            class.ranges.extend(escaped.ranges);
+                        }
+                        first = false;
+                        continue;
+                    }
+                    escape_char(e)
+                }
+                c => c,
+            };
+            first = false;
+            if self.chars.peek() == Some(&'-') {
+                self.chars.next();
+                match self.chars.next() {
+                    None => return Err(RegexError::UnclosedClass),
+                    Some(']') => {
+                        class.ranges.push((lo, lo));
+                        class.ranges.push(('-', '-'));
+                        return Ok(Node::Class(class));
+                    }
+                    Some(hi) => {
+                        if hi < lo {
+                            return Err(RegexError::InvalidRange(lo, hi));
+                        }
+                        class.ranges.push((lo, hi));
+                    }
+                }
+            } else {
+                class.ranges.push((lo, lo));
+            }
+        }
+    }
+}
+
+fn escape_char(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        c => c,
+    }
+}
+
+fn escape_class(c: char) -> Option<Class> {
+    let (negated, ranges) = match c {
+        'd' => (false, vec![('0', '9')]),
+        'D' => (true, vec![('0', '9')]),
+        'w' => (false, vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')]),
+        'W' => (true, vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')]),
+        's' => (false, vec![(' ', ' '), ('\t', '\r')]),
+        'S' => (true, vec![(' ', ' '), ('\t', '\r')]),
+        _ => return None,
+    };
+    Some(Class { negated, ranges })
+}
+
+/// Returns the ranges of all chars not covered by the given ranges
+fn complement(mut ranges: Vec<(char, char)>) -> Vec<(char, char)> {
+    ranges.sort();
+    let mut result = Vec::new();
+    let mut next = 0u32;
+    for (lo, hi) in ranges {
+        if (lo as u32) > next {
+            result.extend(char_range(next, lo as u32 - 1));
+        }
+        next = next.max(hi as u32 + 1);
+    }
+    result.extend(char_range(next, char::MAX as u32));
+    result
+}
+
+/// Builds a char range from code points, trimming invalid ones at both ends
+fn char_range(lo: u32, hi: u32) -> Option<(char, char)> {
+    let lo = (lo..=hi).filter_map(::std::char::from_u32).next()?;
+    let hi = (lo as u32..=hi).rev().filter_map(::std::char::from_u32).next()?;
+    Some((lo, hi))
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    AssertStart,
+    AssertEnd,
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn compile(node: &Node) -> Vec<Inst> {
+        let mut compiler = Compiler { program: Vec::new() };
+        compiler.emit(node);
+        compiler.program.push(Inst::Match);
+        compiler.program
+    }
+
+    fn push(&mut self, inst: Inst) -> usize {
+        self.program.push(inst);
+        self.program.len() - 1
+    }
+
+    fn emit(&mut self, node: &Node) {
+        match *node {
+            Node::Empty => (),
+            Node::Char(c) => {
+                self.push(Inst::Char(c));
+            }
+            Node::Any => {
+                self.push(Inst::Any);
+            }
+            Node::Class(ref class) => {
+                self.push(Inst::Class(class.clone()));
+            }
+            Node::Start => {
+                self.push(Inst::AssertStart);
+            }
+            Node::End => {
+                self.push(Inst::AssertEnd);
+            }
+            Node::Concat(ref nodes) => {
+                for n in nodes {
+                    self.emit(n);
+                }
+            }
+            Node::Alternate(ref left, ref right) => {
+                let split = self.push(Inst::Split(0, 0));
+                self.emit(left);
+                let jump = self.push(Inst::Jump(0));
+                let right_start = self.program.len();
+                self.emit(right);
+                let end = self.program.len();
+                self.program[split] = Inst::Split(split + 1, right_start);
+                self.program[jump] = Inst::Jump(end);
+            }
+            Node::Star(ref inner) => {
+                let split = self.push(Inst::Split(0, 0));
+                self.emit(inner);
+                self.push(Inst::Jump(split));
+                let end = self.program.len();
+                self.program[split] = Inst::Split(split + 1, end);
+            }
+            Node::Plus(ref inner) => {
+                let start = self.program.len();
+                self.emit(inner);
+                let end = self.program.len() + 1;
+                self.push(Inst::Split(start, end));
+            }
+            Node::Quest(ref inner) => {
+                let split = self.push(Inst::Split(0, 0));
+                self.emit(inner);
+                let end = self.program.len();
+                self.program[split] = Inst::Split(split + 1, end);
+            }
+        }
+    }
+}
+
+/// A compiled regular expression
+///
+/// # Example
+/// ```
+/// use pprust::regex_lite::Regex;
+///
+/// let re = Regex::new("^h(a|e)llo+$").unwrap();
+/// assert!(re.is_match("hellooo"));
+/// assert!(!re.is_match("hillo"));
+/// ```
+#[derive(Debug)]
+pub struct Regex {
+    program: Vec<Inst>,
+    case_insensitive: bool,
+}
+
+/// A thread of the NFA simulation: a program counter and the position
+/// where the match it belongs to started
+#[derive(Clone, Copy)]
+struct Thread {
+    pc: usize,
+    start: usize,
+}
+
+impl Regex {
+    /// Compiles a pattern
+    pub fn new(pattern: &str) -> RegexResult<Regex> {
+        let node = Parser::parse(pattern)?;
+        Ok(Regex { program: Compiler::compile(&node), case_insensitive: false })
+    }
+
+    /// Compiles a pattern that ignores letter case when matching
+    pub fn case_insensitive(pattern: &str) -> RegexResult<Regex> {
+        let mut regex = Regex::new(pattern)?;
+        regex.case_insensitive = true;
+        Ok(regex)
+    }
+
+    /// Returns true if the pattern matches anywhere in the text
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// Returns the byte range of the leftmost-longest match in the text
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::regex_lite::Regex;
+    ///
+    /// let re = Regex::new("[0-9]+").unwrap();
+    /// assert_eq!(Some((4, 7)), re.find("abc 123 456"));
+    /// ```
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        self.search(text, false)
+    }
+
+    /// Returns the length in bytes of the longest match at the start of
+    /// the text, as if the pattern began with `^`
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::regex_lite::Regex;
+    ///
+    /// let re = Regex::new("[0-9]+").unwrap();
+    /// assert_eq!(Some(3), re.match_prefix("123 456"));
+    /// assert_eq!(None, re.match_prefix("abc 123"));
+    /// ```
+    pub fn match_prefix(&self, text: &str) -> Option<usize> {
+        self.search(text, true).map(|(_, end)| end)
+    }
+
+    /// The leftmost-longest match, only starting at the beginning of the
+    /// text if anchored
+    fn search(&self, text: &str, anchored: bool) -> Option<(usize, usize)> {
+        // Reading the text as it goes, so that an anchored search stops
+        // where the match does, however long the text is
+        let mut chars = text.char_indices().peekable();
+        let mut current: Vec<Thread> = Vec::new();
+        let mut next: Vec<Thread> = Vec::new();
+        let mut seen = vec![usize::MAX; self.program.len()];
+        let mut best: Option<(usize, usize)> = None;
+
+        for step in 0.. {
+            let position = chars.next();
+            let (offset, at_end) = position.map_or((text.len(), true), |(o, _)| (o, false));
+            if best.is_none() && (step == 0 || !anchored) {
+                let thread = Thread { pc: 0, start: offset };
+                self.add_thread(&mut current, &mut seen, thread, step, offset == 0, at_end);
+            }
+            let mut i = 0;
+            while i < current.len() {
+                let thread = current[i];
+                i += 1;
+                let consumes = match self.program[thread.pc] {
+                    Inst::Match => {
+                        let better = match best {
+                            None => true,
+                            Some((s, e)) => thread.start < s || (thread.start == s && offset > e),
+                        };
+                        if better {
+                            best = Some((thread.start, offset));
+                        }
+                        false
+                    }
+                    _ if at_end => false,
+                    // Threads that started after the best match can never beat it
+                    _ if best.is_some_and(|(s, _)| thread.start > s) => false,
+                    Inst::Char(c) => position.is_some_and(|(_, found)| self.char_eq(c, found)),
+                    Inst::Any => true,
+                    Inst::Class(ref class) => position.is_some_and(|(_, found)| self.class_contains(class, found)),
+                    _ => false,
+                };
+                if consumes {
+                    let thread = Thread { pc: thread.pc + 1, start: thread.start };
+                    let next_at_end = chars.peek().is_none();
+                    self.add_thread(&mut next, &mut seen, thread, step + 1, false, next_at_end);
+                }
+            }
+            if at_end || (next.is_empty() && (best.is_some() || anchored)) {
+                break;
+            }
+            current.clear();
+            ::std::mem::swap(&mut current, &mut next);
+        }
+        best
+    }
+
+    fn add_thread(&self, list: &mut Vec<Thread>, seen: &mut [usize], thread: Thread,
+                  step: usize, at_start: bool, at_end: bool) {
+        if seen[thread.pc] == step {
+            return;
+        }
+        seen[thread.pc] = step;
+        match self.program[thread.pc] {
+            Inst::Jump(to) => self.add_thread(list, seen, Thread { pc: to, ..thread }, step, at_start, at_end),
+            Inst::Split(a, b) => {
+                self.add_thread(list, seen, Thread { pc: a, ..thread }, step, at_start, at_end);
+                self.add_thread(list, seen, Thread { pc: b, ..thread }, step, at_start, at_end);
+            }
+            Inst::AssertStart => {
+                if at_start {
+                    self.add_thread(list, seen, Thread { pc: thread.pc + 1, ..thread }, step, at_start, at_end);
+                }
+            }
+            Inst::AssertEnd => {
+                if at_end {
+                    self.add_thread(list, seen, Thread { pc: thread.pc + 1, ..thread }, step, at_start, at_end);
+                }
+            }
+            _ => list.push(thread),
+        }
+    }
+
+    fn char_eq(&self, expected: char, c: char) -> bool {
+        if self.case_insensitive {
+            expected.to_lowercase().eq(c.to_lowercase())
+        } else {
+            expected == c
+        }
+    }
+
+    fn class_contains(&self, class: &Class, c: char) -> bool {
+        if self.case_insensitive {
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            let upper = c.to_uppercase().next().unwrap_or(c);
+            if class.negated {
+                class.contains(lower) && class.contains(upper)
+            } else {
+                class.contains(lower) || class.contains(upper)
+            }
+        } else {
+            class.contains(c)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern).expect("Pattern should compile").is_match(text)
+    }
+
+    #[test]
+    fn should_match_literals_anywhere() {
+        assert!(matches("abc", "xxabcxx"));
+        assert!(!matches("abc", "abxc"));
+    }
+
+    #[test]
+    fn should_match_empty_pattern() {
+        assert!(matches("", ""));
+        assert!(matches("", "anything"));
+    }
+
+    #[test]
+    fn should_match_wildcard_and_repetitions() {
+        assert!(matches("a.c", "abc"));
+        assert!(matches("ab*c", "ac"));
+        assert!(matches("ab+c", "abbbc"));
+        assert!(!matches("ab+c", "ac"));
+        assert!(matches("colou?r", "color"));
+        assert!(matches("colou?r", "colour"));
+    }
+
+    #[test]
+    fn should_respect_anchors() {
+        assert!(matches("^abc$", "abc"));
+        assert!(!matches("^abc$", "abcd"));
+        assert!(!matches("^bc", "abc"));
+        assert!(matches("bc$", "abc"));
+    }
+
+    #[test]
+    fn should_match_alternations_and_groups() {
+        assert!(matches("^(cat|dog)s?$", "dogs"));
+        assert!(matches("^(cat|dog)s?$", "cat"));
+        assert!(!matches("^(cat|dog)s?$", "cow"));
+        assert!(matches("^(ab)+$", "ababab"));
+        assert!(!matches("^(ab)+$", "ababa"));
+    }
+
+    #[test]
+    fn should_match_classes() {
+        assert!(matches("^[a-c]+$", "abcabc"));
+        assert!(!matches("^[a-c]+$", "abcd"));
+        assert!(matches("^[^0-9]+$", "abc"));
+        assert!(!matches("^[^0-9]+$", "a1c"));
+        assert!(matches("^[]a]$", "]"));
+        assert!(matches("^[a-]$", "-"));
+        assert!(matches("^\\d+\\.\\d+$", "3.14"));
+        assert!(matches("^[\\w]+$", "snake_case"));
+        assert!(matches("^[\\D]+$", "abc"));
+        assert!(!matches("^[\\D]+$", "a1"));
+    }
+
+    #[test]
+    fn should_not_blow_up_on_pathological_patterns() {
+        let pattern = format!("^{}{}$", "a?".repeat(30), "a".repeat(30));
+        assert!(matches(&pattern, &"a".repeat(30)));
+    }
+
+    #[test]
+    fn should_find_leftmost_longest_match() {
+        let re = Regex::new("a+|b").unwrap();
+        assert_eq!(Some((1, 4)), re.find("xaaab"));
+        assert_eq!(Some((0, 1)), re.find("baaa"));
+        assert_eq!(None, re.find("ccc"));
+        let re = Regex::new("a|ab").unwrap();
+        assert_eq!(Some((0, 2)), re.find("abc"));
+    }
+
+    #[test]
+    fn should_match_only_at_the_start_when_asked() {
+        let re = Regex::new("a+|ab*").unwrap();
+        assert_eq!(Some(4), re.match_prefix("abbbaa"));
+        assert_eq!(Some(2), re.match_prefix("aacab"));
+        assert_eq!(None, re.match_prefix("caab"));
+        assert_eq!(Some(0), Regex::new("x*").unwrap().match_prefix("abc"));
+    }
+
+    #[test]
+    fn should_return_byte_offsets_for_multibyte_text() {
+        let re = Regex::new("é+").unwrap();
+        assert_eq!(Some((1, 5)), re.find("aééb"));
+    }
+
+    #[test]
+    fn should_match_ignoring_case() {
+        let re = Regex::case_insensitive("^hello [a-z]+$").unwrap();
+        assert!(re.is_match("HeLLo World"));
+        let re = Regex::case_insensitive("[^a-z]").unwrap();
+        assert!(!re.is_match("ABC"));
+    }
+
+    #[test]
+    fn should_report_syntax_errors() {
+        assert_eq!(Some(RegexError::UnbalancedParenthesis), Regex::new("(ab").err());
+        assert_eq!(Some(RegexError::UnbalancedParenthesis), Regex::new("ab)").err());
+        assert_eq!(Some(RegexError::UnclosedClass), Regex::new("[ab").err());
+        assert_eq!(Some(RegexError::NothingToRepeat('*')), Regex::new("*a").err());
+        assert_eq!(Some(RegexError::InvalidRange('z', 'a')), Regex::new("[z-a]").err());
+        assert_eq!(Some(RegexError::UnexpectedEnd), Regex::new("ab\\").err());
+    }
+}
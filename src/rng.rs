@@ -0,0 +1,118 @@
+//!
+//! # Problem description:
+//! #
+//! # Pseudo-Random Numbers
+//!
+//! A linear congruential generator computes each state from the previous
+//! one as x' = (a x + c) mod m. With m = 2^64 the modulus is free, and
+//! Knuth's MMIX constants give the full period. The low bits of an LCG
+//! have short periods, so outputs are taken from the high half of the
+//! state.
+//!
+//! The generator is seedable so that simulations and puzzles built on it
+//! are reproducible. It is not suitable for cryptography.
+//!
+
+/// Multiplier of Knuth's MMIX generator
+pub const MULTIPLIER: u64 = 6364136223846793005;
+
+/// Increment of Knuth's MMIX generator
+pub const INCREMENT: u64 = 1442695040888963407;
+
+/// Seedable linear congruential generator
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator from a seed; equal seeds give equal sequences
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// Returns the next 32 random bits, the high half of the new state
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+        (self.state >> 32) as u32
+    }
+
+    /// Returns the next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        let high = self.next_u32() as u64;
+        (high << 32) | self.next_u32() as u64
+    }
+
+    /// Returns a number uniformly distributed in `[low, high)`
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rng::Rng;
+    ///
+    /// let mut rng = Rng::new(42);
+    /// for _ in 0..100 {
+    ///     let die = rng.gen_range(1, 7);
+    ///     assert!(1 <= die && die <= 6);
+    /// }
+    /// ```
+    pub fn gen_range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "Empty range {}..{}", low, high);
+        let span = high - low;
+        // Reject the top values that would make some results more likely
+        let zone = u64::MAX - (u64::MAX - span + 1) % span;
+        loop {
+            let x = self.next_u64();
+            if x <= zone {
+                return low + x % span;
+            }
+        }
+    }
+
+    /// Returns a number uniformly distributed in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_repeat_sequence_for_same_seed() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let mut c = Rng::new(8);
+        let xs: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+        let zs: Vec<u64> = (0..10).map(|_| c.next_u64()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+    }
+
+    #[test]
+    fn should_spread_range_evenly() {
+        let mut rng = Rng::new(1);
+        let mut counts = [0u32; 6];
+        for _ in 0..60_000 {
+            counts[rng.gen_range(0, 6) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| 9_500 < c && c < 10_500), "{:?}", counts);
+    }
+
+    #[test]
+    fn should_handle_full_width_ranges() {
+        let mut rng = Rng::new(3);
+        assert_eq!(5, rng.gen_range(5, 6));
+        assert!(rng.gen_range(0, u64::MAX) < u64::MAX);
+    }
+
+    #[test]
+    fn should_generate_unit_floats() {
+        let mut rng = Rng::new(99);
+        let xs: Vec<f64> = (0..10_000).map(|_| rng.next_f64()).collect();
+        assert!(xs.iter().all(|&x| (0.0..1.0).contains(&x)));
+        let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+        assert!((mean - 0.5).abs() < 0.02, "Mean was {}", mean);
+    }
+}
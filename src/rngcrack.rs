@@ -0,0 +1,210 @@
+//!
+//! # Problem description:
+//! #
+//! # Cracking a Linear Congruential Generator
+//!
+//! A linear congruential generator keeps no secrets from anyone who sees
+//! a few consecutive states. If the modulus m is known, the differences
+//! t_i = x_{i+1} - x_i satisfy t_{i+1} ≡ a t_i (mod m), so the multiplier
+//! is a quotient of two differences and the increment follows from
+//! c ≡ x_1 - a x_0. When t_i shares a factor with m the quotient is not
+//! unique, and each candidate is checked against the rest of the outputs.
+//!
+//! If the modulus is unknown, t_{i+2} t_i - t_{i+1}^2 ≡ a^2 t_i^2 -
+//! a^2 t_i^2 ≡ 0 (mod m), so m divides every such number. Their gcd is m
+//! or a small multiple of it, and small factors are removed for as long
+//! as the outputs still fit.
+//!
+//! The attack needs whole states. The generator in the `rng` module
+//! only shows the high half of each state, which turns the problem into
+//! a search over the hidden low bits.
+//!
+
+use bigint::BigInt;
+
+/// Largest number of multipliers tried when the differences do not
+/// determine a single one
+const MAX_CANDIDATES: u128 = 1 << 16;
+
+/// Parameters of a generator x' = (a x + c) mod m
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lcg {
+    pub multiplier: u64,
+    pub increment: u64,
+    /// At most 2^64, so it does not always fit a `u64`
+    pub modulus: u128,
+}
+
+impl Lcg {
+    /// The state that follows `x`
+    pub fn next(&self, x: u64) -> u64 {
+        ((self.multiplier as u128 * x as u128 + self.increment as u128) % self.modulus) as u64
+    }
+}
+
+/// Recovers the generator that produced consecutive `outputs` when the
+/// modulus, at most 2^64, is known
+///
+/// # Example
+/// ```
+/// use pprust::rngcrack::recover_lcg_with_modulus;
+///
+/// let lcg = recover_lcg_with_modulus(&[1, 58598, 127215, 79852], 233280).unwrap();
+/// assert_eq!((9301, 49297), (lcg.multiplier, lcg.increment));
+/// ```
+pub fn recover_lcg_with_modulus(outputs: &[u64], modulus: u128) -> Option<Lcg> {
+    assert!(1 < modulus && modulus <= 1 << 64, "The modulus must be between 2 and 2^64");
+    let m = modulus;
+    if outputs.len() < 3 || outputs.iter().any(|&x| x as u128 >= m) {
+        return None;
+    }
+    let t: Vec<u128> = outputs.windows(2).map(|w| (w[1] as u128 + m - w[0] as u128) % m).collect();
+    // a t_i ≡ t_{i+1} has gcd(t_i, m) solutions, so take the fewest
+    let (i, g) = (0..t.len() - 1).map(|i| (i, gcd(t[i], m))).min_by_key(|&(_, g)| g)?;
+    if g > MAX_CANDIDATES || !t[i + 1].is_multiple_of(g) {
+        return None;
+    }
+    let step = m / g;
+    let base = (t[i + 1] / g) % step * inverse(t[i] / g, step)? % step;
+    let (x0, x1) = (outputs[0] as u128, outputs[1] as u128);
+    (0..g).map(|k| base + k * step).find_map(|a| {
+        let c = (x1 + m - a * x0 % m) % m;
+        let lcg = Lcg { multiplier: a as u64, increment: c as u64, modulus: m };
+        if outputs.windows(2).all(|w| lcg.next(w[0]) == w[1]) {
+            Some(lcg)
+        } else {
+            None
+        }
+    })
+}
+
+/// Recovers the generator that produced consecutive `outputs` without
+/// knowing the modulus. At least four outputs are needed, and a few
+/// more make a wrong multiple of the modulus unlikely.
+///
+/// # Example
+/// ```
+/// use pprust::rngcrack::recover_lcg;
+///
+/// let mut xs = vec![42u64];
+/// for i in 0..8 {
+///     xs.push(xs[i] * 16807 % 2147483647);
+/// }
+/// let lcg = recover_lcg(&xs).unwrap();
+/// assert_eq!((16807, 0, 2147483647), (lcg.multiplier, lcg.increment, lcg.modulus));
+/// ```
+pub fn recover_lcg(outputs: &[u64]) -> Option<Lcg> {
+    if outputs.len() < 4 {
+        return None;
+    }
+    let t: Vec<BigInt> = outputs.windows(2).map(|w| &BigInt::from(w[1]) - &BigInt::from(w[0])).collect();
+    let multiple = t
+        .windows(3)
+        .map(|w| &(&w[2] * &w[0]) - &(&w[1] * &w[1]))
+        .fold(BigInt::zero(), |g, u| g.gcd(&u));
+    if multiple.is_zero() || multiple.bits() > 128 {
+        return None;
+    }
+    let two64 = BigInt::from(1u64 << 32).pow(2);
+    let (high, low) = multiple.div_rem(&two64);
+    let mut m = ((high.to_u64()? as u128) << 64) | low.to_u64()? as u128;
+    let largest = *outputs.iter().max()? as u128;
+    let fits = |m: u128| m > largest && (m > 1 << 64 || recover_lcg_with_modulus(outputs, m).is_some());
+    for p in 2..1000 {
+        while m.is_multiple_of(p) && fits(m / p) {
+            m /= p;
+        }
+    }
+    if m <= largest || m > 1 << 64 {
+        return None;
+    }
+    recover_lcg_with_modulus(outputs, m)
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// The inverse of `a` modulo `m`, for `m` up to 2^64
+fn inverse(a: u128, m: u128) -> Option<u128> {
+    if m == 1 {
+        return Some(0);
+    }
+    let (mut old_r, mut r) = ((a % m) as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        let next_r = old_r - q * r;
+        old_r = r;
+        r = next_r;
+        let next_s = old_s - q * s;
+        old_s = s;
+        s = next_s;
+    }
+    if old_r != 1 {
+        return None;
+    }
+    Some(old_s.rem_euclid(m as i128) as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::{INCREMENT, MULTIPLIER};
+
+    fn states(lcg: &Lcg, seed: u64, count: usize) -> Vec<u64> {
+        let mut xs = vec![seed];
+        while xs.len() < count {
+            let x = lcg.next(*xs.last().unwrap());
+            xs.push(x);
+        }
+        xs
+    }
+
+    fn mmix() -> Lcg {
+        Lcg { multiplier: MULTIPLIER, increment: INCREMENT, modulus: 1 << 64 }
+    }
+
+    #[test]
+    fn should_recover_with_known_modulus() {
+        for seed in 0..20 {
+            let xs = states(&mmix(), seed, 6);
+            assert_eq!(Some(mmix()), recover_lcg_with_modulus(&xs, 1 << 64), "seed {}", seed);
+        }
+        let small = Lcg { multiplier: 9301, increment: 49297, modulus: 233280 };
+        assert_eq!(Some(small), recover_lcg_with_modulus(&states(&small, 7, 5), 233280));
+    }
+
+    #[test]
+    fn should_recover_unknown_modulus() {
+        let generators = [
+            Lcg { multiplier: 16807, increment: 0, modulus: 2147483647 },
+            Lcg { multiplier: 9301, increment: 49297, modulus: 233280 },
+            Lcg { multiplier: 1103515245, increment: 12345, modulus: 1 << 31 },
+            mmix(),
+        ];
+        for lcg in &generators {
+            let xs = states(lcg, 12345, 12);
+            let found = recover_lcg(&xs).unwrap();
+            assert_eq!(lcg.modulus, found.modulus);
+            // The recovered generator predicts what comes next
+            let last = *xs.last().unwrap();
+            assert_eq!(lcg.next(last), found.next(last));
+        }
+    }
+
+    #[test]
+    fn should_reject_sequences_that_are_not_lcgs() {
+        let squares: Vec<u64> = (1..10).map(|i| i * i).collect();
+        assert_eq!(None, recover_lcg(&squares));
+        assert_eq!(None, recover_lcg_with_modulus(&[1, 2, 4, 7, 11], 100));
+        assert_eq!(None, recover_lcg(&[1, 2, 3]));
+        // Outputs must be smaller than the modulus
+        assert_eq!(None, recover_lcg_with_modulus(&[1, 200, 3], 100));
+    }
+}
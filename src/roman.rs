@@ -0,0 +1,261 @@
+//!
+//! # Problem description:
+//! #
+//! # Roman Numerals
+//!
+//! Roman numerals write a number with the letters I, V, X, L, C, D and M
+//! for 1, 5, 10, 50, 100, 500 and 1000, largest first, adding them up.
+//! A smaller letter written before a larger one is subtracted instead,
+//! but only in the six pairs IV, IX, XL, XC, CD and CM, and no letter
+//! repeats more than three times, so every number from 1 to 3999 has
+//! exactly one numeral. Writing a number greedily from the table of
+//! letters and pairs gives that numeral; reading one is checked by
+//! writing the value back.
+//!
+//! The Romans had no numeral for zero; medieval tables wrote N, for
+//! nulla, which is used here.
+//!
+
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::result;
+use std::str::FromStr;
+
+/// The largest number with a Roman numeral
+pub const MAX: u32 = 3999;
+
+const NUMERALS: [(u32, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// Errors reading a Roman numeral
+#[derive(Debug, Clone, PartialEq)]
+pub enum RomanError {
+    /// The text is not a Roman numeral in its standard form
+    Syntax(String),
+    /// The number is above `MAX`
+    OutOfRange(u32),
+}
+
+impl fmt::Display for RomanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RomanError::Syntax(ref text) => write!(f, "'{}' is not a Roman numeral", text),
+            RomanError::OutOfRange(n) => write!(f, "{} has no Roman numeral", n),
+        }
+    }
+}
+
+/// The result of reading a Roman numeral
+pub type RomanResult<T> = result::Result<T, RomanError>;
+
+/// The Roman numeral for `n`, or `None` above `MAX`
+///
+/// # Example
+/// ```
+/// use pprust::roman::to_roman;
+///
+/// assert_eq!(Some("MCMXCIV".to_string()), to_roman(1994));
+/// assert_eq!(Some("N".to_string()), to_roman(0));
+/// assert_eq!(None, to_roman(4000));
+/// ```
+pub fn to_roman(mut n: u32) -> Option<String> {
+    if n > MAX {
+        return None;
+    }
+    if n == 0 {
+        return Some("N".to_string());
+    }
+    let mut numeral = String::new();
+    for &(value, letters) in &NUMERALS {
+        while n >= value {
+            numeral.push_str(letters);
+            n -= value;
+        }
+    }
+    Some(numeral)
+}
+
+/// The value of a Roman numeral in standard form, in upper case
+///
+/// # Example
+/// ```
+/// use pprust::roman::from_roman;
+///
+/// assert_eq!(Some(1994), from_roman("MCMXCIV"));
+/// assert_eq!(None, from_roman("IIII"));
+/// assert_eq!(None, from_roman("IC"));
+/// ```
+pub fn from_roman(numeral: &str) -> Option<u32> {
+    if numeral == "N" {
+        return Some(0);
+    }
+    let mut rest = numeral;
+    let mut n = 0;
+    for &(value, letters) in &NUMERALS {
+        while let Some(tail) = rest.strip_prefix(letters) {
+            n += value;
+            rest = tail;
+        }
+    }
+    // Non-standard numerals read to a value written differently
+    if !numeral.is_empty() && rest.is_empty() && to_roman(n).as_deref() == Some(numeral) {
+        Some(n)
+    } else {
+        None
+    }
+}
+
+/// A whole number from 0 to `MAX`, written in Roman numerals.
+///
+/// The operators panic when the result is out of range, like those of
+/// the integer types; the `checked_*` methods return `None` instead.
+/// Division rounds down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Roman(u32);
+
+impl Roman {
+    /// `n`, or `None` above `MAX`
+    pub fn new(n: u32) -> Option<Roman> {
+        if n <= MAX {
+            Some(Roman(n))
+        } else {
+            None
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    pub fn checked_add(&self, other: &Roman) -> Option<Roman> {
+        Roman::new(self.0 + other.0)
+    }
+
+    pub fn checked_sub(&self, other: &Roman) -> Option<Roman> {
+        self.0.checked_sub(other.0).map(Roman)
+    }
+
+    pub fn checked_mul(&self, other: &Roman) -> Option<Roman> {
+        self.0.checked_mul(other.0).and_then(Roman::new)
+    }
+
+    pub fn checked_div(&self, other: &Roman) -> Option<Roman> {
+        self.0.checked_div(other.0).map(Roman)
+    }
+}
+
+impl Add for Roman {
+    type Output = Roman;
+
+    fn add(self, other: Roman) -> Roman {
+        self.checked_add(&other).expect("Roman numeral overflow")
+    }
+}
+
+impl Sub for Roman {
+    type Output = Roman;
+
+    fn sub(self, other: Roman) -> Roman {
+        self.checked_sub(&other).expect("Roman numerals can't be negative")
+    }
+}
+
+impl Mul for Roman {
+    type Output = Roman;
+
+    fn mul(self, other: Roman) -> Roman {
+        self.checked_mul(&other).expect("Roman numeral overflow")
+    }
+}
+
+impl Div for Roman {
+    type Output = Roman;
+
+    fn div(self, other: Roman) -> Roman {
+        self.checked_div(&other).expect("Division by zero")
+    }
+}
+
+impl fmt::Display for Roman {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&to_roman(self.0).expect("Roman values are in range"))
+    }
+}
+
+impl FromStr for Roman {
+    type Err = RomanError;
+
+    /// Reads a Roman numeral, in either case, or a number in digits
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::roman::Roman;
+    ///
+    /// let xix: Roman = "xix".parse().unwrap();
+    /// assert_eq!(19, xix.value());
+    /// assert_eq!("XXI", (xix + "2".parse().unwrap()).to_string());
+    /// ```
+    fn from_str(text: &str) -> RomanResult<Roman> {
+        if let Ok(n) = text.parse::<u32>() {
+            return Roman::new(n).ok_or(RomanError::OutOfRange(n));
+        }
+        from_roman(&text.to_uppercase()).map(Roman).ok_or_else(|| RomanError::Syntax(text.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_every_numeral() {
+        for n in 0..=MAX {
+            let numeral = to_roman(n).unwrap();
+            assert_eq!(Some(n), from_roman(&numeral), "{}", numeral);
+        }
+        assert_eq!("MMMCMXCIX", to_roman(MAX).unwrap());
+    }
+
+    #[test]
+    fn should_write_known_numerals() {
+        for &(n, numeral) in &[(1, "I"), (4, "IV"), (9, "IX"), (14, "XIV"), (40, "XL"), (90, "XC"), (400, "CD"),
+                               (1666, "MDCLXVI"), (2024, "MMXXIV"), (3888, "MMMDCCCLXXXVIII")] {
+            assert_eq!(numeral, to_roman(n).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_reject_non_standard_numerals() {
+        for &bad in &["", "IIII", "VV", "IL", "XM", "IIX", "MMMM", "IVI", "NN", "NI", "iv", "I V", "A"] {
+            assert_eq!(None, from_roman(bad), "{}", bad);
+        }
+        assert_eq!(Err(RomanError::Syntax("IIII".to_string())), "IIII".parse::<Roman>());
+        assert_eq!(Err(RomanError::OutOfRange(4000)), "4000".parse::<Roman>());
+    }
+
+    #[test]
+    fn should_check_arithmetic_range() {
+        let r = |n| Roman::new(n).unwrap();
+        assert_eq!(Some(r(3999)), r(3998).checked_add(&r(1)));
+        assert_eq!(None, r(3999).checked_add(&r(1)));
+        assert_eq!(Some(r(0)), r(5).checked_sub(&r(5)));
+        assert_eq!(None, r(5).checked_sub(&r(6)));
+        assert_eq!(None, r(100).checked_mul(&r(40)));
+        assert_eq!(Some(r(3)), r(7).checked_div(&r(2)));
+        assert_eq!(None, r(7).checked_div(&r(0)));
+        assert_eq!("   IV", format!("{:>5}", r(4)));
+    }
+}
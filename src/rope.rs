@@ -0,0 +1,410 @@
+//!
+//! # Problem description:
+//! #
+//! # Ropes
+//!
+//! Editing a large text held in a single string costs time proportional
+//! to its length for every insertion or deletion, as everything after
+//! the change has to move. A rope holds the text in the leaves of a
+//! binary tree instead, each inner node recording how many characters
+//! are under it, so finding the *i*-th character, splitting the text in
+//! two and concatenating two texts all take time proportional to the
+//! depth of the tree; insertions and deletions are a couple of splits
+//! and concatenations.
+//!
+//! Concatenation alone can make the tree as deep as it has leaves, so
+//! the rope is rebalanced when it gets too deep, following Boehm, Atkinson
+//! and Plass: a tree of depth *d* is balanced enough when it has at least
+//! *F(d + 2)* leaves, *F* being the Fibonacci numbers, which keeps the
+//! depth logarithmic. Nodes are shared through reference counts, so
+//! splitting and concatenating never copy text, and a clone of a rope is
+//! a snapshot that later edits leave alone.
+//!
+
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// The most bytes a leaf holds, apart from a single character longer
+/// than that
+pub const LEAF_SIZE: usize = 1024;
+
+#[derive(Debug)]
+enum Node {
+    Leaf(String),
+    Branch {
+        left: Rc<Node>,
+        right: Rc<Node>,
+        chars: usize,
+        depth: usize,
+        leaves: usize,
+    },
+}
+
+impl Node {
+    fn chars(&self) -> usize {
+        match *self {
+            Node::Leaf(ref text) => text.chars().count(),
+            Node::Branch { chars, .. } => chars,
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match *self {
+            Node::Leaf(_) => 0,
+            Node::Branch { depth, .. } => depth,
+        }
+    }
+
+    fn leaves(&self) -> usize {
+        match *self {
+            Node::Leaf(_) => 1,
+            Node::Branch { leaves, .. } => leaves,
+        }
+    }
+}
+
+/// A branch over two nodes, without rebalancing
+fn branch(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    Rc::new(Node::Branch {
+        chars: left.chars() + right.chars(),
+        depth: 1 + left.depth().max(right.depth()),
+        leaves: left.leaves() + right.leaves(),
+        left,
+        right,
+    })
+}
+
+/// Whether a tree meets the balance condition of Boehm et al.
+fn is_balanced(node: &Node) -> bool {
+    let (mut a, mut b) = (1usize, 1usize);
+    // b becomes F(depth + 2)
+    for _ in 0..node.depth() {
+        let c = a.saturating_add(b);
+        a = b;
+        b = c;
+    }
+    node.leaves() >= b
+}
+
+/// The leaves of a tree, left to right
+fn collect_leaves(node: &Rc<Node>, leaves: &mut Vec<Rc<Node>>) {
+    match **node {
+        Node::Leaf(_) => leaves.push(node.clone()),
+        Node::Branch { ref left, ref right, .. } => {
+            collect_leaves(left, leaves);
+            collect_leaves(right, leaves);
+        }
+    }
+}
+
+/// A perfectly balanced tree over a non-empty run of leaves
+fn build(leaves: &[Rc<Node>]) -> Rc<Node> {
+    if leaves.len() == 1 {
+        return leaves[0].clone();
+    }
+    let middle = leaves.len() / 2;
+    branch(build(&leaves[..middle]), build(&leaves[middle..]))
+}
+
+/// Concatenates two trees, merging small leaves and rebalancing if the
+/// result gets too deep
+fn join(left: Option<Rc<Node>>, right: Option<Rc<Node>>) -> Option<Rc<Node>> {
+    let (left, right) = match (left, right) {
+        (None, node) | (node, None) => return node,
+        (Some(left), Some(right)) => (left, right),
+    };
+    if let (Node::Leaf(a), Node::Leaf(b)) = (&*left, &*right) {
+        if a.len() + b.len() <= LEAF_SIZE {
+            return Some(Rc::new(Node::Leaf(format!("{}{}", a, b))));
+        }
+    }
+    let node = branch(left, right);
+    if is_balanced(&node) {
+        return Some(node);
+    }
+    let mut leaves = Vec::with_capacity(node.leaves());
+    collect_leaves(&node, &mut leaves);
+    Some(build(&leaves))
+}
+
+/// Splits a tree before the character at `at`, which is at most its
+/// length
+fn split(node: &Rc<Node>, at: usize) -> (Option<Rc<Node>>, Option<Rc<Node>>) {
+    match **node {
+        Node::Leaf(ref text) => {
+            let byte = text.char_indices().nth(at).map_or(text.len(), |(i, _)| i);
+            let leaf = |s: &str| if s.is_empty() { None } else { Some(Rc::new(Node::Leaf(s.to_string()))) };
+            if byte == 0 {
+                (None, Some(node.clone()))
+            } else if byte == text.len() {
+                (Some(node.clone()), None)
+            } else {
+                (leaf(&text[..byte]), leaf(&text[byte..]))
+            }
+        }
+        Node::Branch { ref left, ref right, .. } => {
+            let in_left = left.chars();
+            if at < in_left {
+                let (a, b) = split(left, at);
+                (a, join(b, Some(right.clone())))
+            } else if at == in_left {
+                (Some(left.clone()), Some(right.clone()))
+            } else {
+                let (a, b) = split(right, at - in_left);
+                (join(Some(left.clone()), a), b)
+            }
+        }
+    }
+}
+
+/// A text stored as a tree of strings, cheap to edit anywhere. Positions
+/// count characters, not bytes.
+///
+/// # Example
+/// ```
+/// use pprust::rope::Rope;
+///
+/// let mut rope = Rope::from("Hello world");
+/// rope.insert(5, ", dear");
+/// rope.delete(0..5);
+/// rope.insert(0, "Goodbye");
+/// assert_eq!("Goodbye, dear world", rope.to_string());
+/// assert_eq!(Some('d'), rope.char_at(9));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Rope {
+    root: Option<Rc<Node>>,
+}
+
+impl Rope {
+    pub fn new() -> Rope {
+        Rope { root: None }
+    }
+
+    /// The number of characters
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, |node| node.chars())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// The depth of the tree, 0 for a single leaf
+    pub fn depth(&self) -> usize {
+        self.root.as_ref().map_or(0, |node| node.depth())
+    }
+
+    /// The text of this rope followed by the text of `other`
+    pub fn concat(self, other: Rope) -> Rope {
+        Rope { root: join(self.root, other.root) }
+    }
+
+    /// The text before character `at` and the text from it on. Panics if
+    /// `at` is past the end.
+    pub fn split(self, at: usize) -> (Rope, Rope) {
+        assert!(at <= self.len(), "Split at {} of a rope of {} characters", at, self.len());
+        match self.root {
+            None => (Rope::new(), Rope::new()),
+            Some(ref node) => {
+                let (left, right) = split(node, at);
+                (Rope { root: left }, Rope { root: right })
+            }
+        }
+    }
+
+    /// Inserts text before character `at`
+    pub fn insert(&mut self, at: usize, text: &str) {
+        let (left, right) = self.take().split(at);
+        *self = left.concat(Rope::from(text)).concat(right);
+    }
+
+    /// Deletes the characters in `range`
+    pub fn delete(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end, "Deleting a backwards range");
+        let (left, rest) = self.take().split(range.start);
+        let (_, right) = rest.split(range.end - range.start);
+        *self = left.concat(right);
+    }
+
+    /// The character at position `i`
+    pub fn char_at(&self, mut i: usize) -> Option<char> {
+        let mut node = self.root.as_ref()?;
+        loop {
+            match **node {
+                Node::Leaf(ref text) => return text.chars().nth(i),
+                Node::Branch { ref left, ref right, .. } => {
+                    let in_left = left.chars();
+                    if i < in_left {
+                        node = left;
+                    } else {
+                        i -= in_left;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The strings in the leaves, in order
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks { stack: self.root.iter().map(|node| &**node).collect() }
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+
+    fn take(&mut self) -> Rope {
+        Rope { root: self.root.take() }
+    }
+}
+
+/// An iterator over the leaves of a rope
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            match *self.stack.pop()? {
+                Node::Leaf(ref text) => return Some(text),
+                Node::Branch { ref left, ref right, .. } => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Rope {
+    /// A balanced rope over leaves of up to `LEAF_SIZE` bytes
+    fn from(text: &'a str) -> Rope {
+        let mut leaves = Vec::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let mut end = LEAF_SIZE.min(rest.len());
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            if end == 0 {
+                end = rest.chars().next().expect("rest is not empty").len_utf8();
+            }
+            leaves.push(Rc::new(Node::Leaf(rest[..end].to_string())));
+            rest = &rest[end..];
+        }
+        Rope { root: if leaves.is_empty() { None } else { Some(build(&leaves)) } }
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Rope) -> bool {
+        self.len() == other.len() && self.chars().eq(other.chars())
+    }
+}
+
+impl Eq for Rope {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    /// The byte offset of character `i` of a string
+    fn byte(text: &str, i: usize) -> usize {
+        text.char_indices().nth(i).map_or(text.len(), |(b, _)| b)
+    }
+
+    #[test]
+    fn should_edit_like_a_string() {
+        let mut rng = Rng::new(264);
+        let pieces = ["a", "bc", "ñandú", "日本語", "🦀", "xyz\n"];
+        let mut rope = Rope::new();
+        let mut model = String::new();
+        for _ in 0..3000 {
+            let len = model.chars().count();
+            if len > 0 && rng.gen_range(0, 3) == 0 {
+                let start = rng.gen_range(0, len as u64) as usize;
+                let end = (start + rng.gen_range(0, 40) as usize).min(len);
+                rope.delete(start..end);
+                let (a, b) = (byte(&model, start), byte(&model, end));
+                model.replace_range(a..b, "");
+            } else {
+                let at = rng.gen_range(0, len as u64 + 1) as usize;
+                let piece: String = (0..rng.gen_range(1, 60)).map(|_| pieces[rng.gen_range(0, 6) as usize]).collect();
+                rope.insert(at, &piece);
+                model.insert_str(byte(&model, at), &piece);
+            }
+            assert_eq!(model.chars().count(), rope.len());
+        }
+        assert_eq!(model, rope.to_string());
+        for (i, c) in model.chars().enumerate().step_by(7) {
+            assert_eq!(Some(c), rope.char_at(i));
+        }
+        assert_eq!(None, rope.char_at(rope.len()));
+        assert!(rope.chunks().all(|chunk| chunk.len() <= LEAF_SIZE));
+    }
+
+    #[test]
+    fn should_stay_shallow() {
+        let mut rope = Rope::new();
+        for i in 0..5000 {
+            // Appending leaves too big to merge makes the deepest trees
+            rope = rope.concat(Rope::from(&*"x".repeat(LEAF_SIZE - i % 2)));
+        }
+        let leaves = rope.chunks().count();
+        assert_eq!(5000, leaves);
+        // F(d + 2) <= leaves, and F(25) > 5000
+        assert!(rope.depth() <= 22, "depth {} for {} leaves", rope.depth(), leaves);
+        let mut rope = Rope::new();
+        for i in 0..20_000 {
+            rope.insert(i / 2, "ab");
+        }
+        assert_eq!(40_000, rope.len());
+        assert!(rope.depth() <= 30, "depth {}", rope.depth());
+    }
+
+    #[test]
+    fn should_split_and_concat() {
+        let text = "The quick brown fox jumps over the lazy dog".repeat(100);
+        let rope = Rope::from(&*text);
+        for &at in &[0, 1, 1000, 2049, text.len() - 1, text.len()] {
+            let (left, right) = rope.clone().split(at);
+            assert_eq!(&text[..at], left.to_string());
+            assert_eq!(&text[at..], right.to_string());
+            assert_eq!(rope, left.concat(right));
+        }
+        assert!(Rope::from("").is_empty());
+        assert_eq!((Rope::new(), Rope::new()), Rope::new().split(0));
+    }
+
+    #[test]
+    fn should_keep_snapshots() {
+        let mut rope = Rope::from("unchanged");
+        let snapshot = rope.clone();
+        rope.insert(2, "--");
+        rope.delete(0..2);
+        assert_eq!("--changed", rope.to_string());
+        assert_eq!("unchanged", snapshot.to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_not_split_past_the_end() {
+        Rope::from("abc").split(4);
+    }
+}
@@ -12,186 +12,2529 @@
 //! the operand stack between expressions.
 //!
 
+use bigint::BigInt;
+use complex::Complex;
+use decimal::{self, Decimal};
+use farey;
+use interval::Interval;
+use rational::Ratio;
+use roman::{self, Roman};
+use select;
+use units::Quantity;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
 use std::num;
 use std::collections;
+use std::convert::TryFrom;
+use std::f64;
+use std::ops::{Add, Deref, DerefMut, Div, Mul, Sub};
 use std::result;
-use std::io;
+use std::io::{self, BufRead, Read, Write};
+use std::mem;
+use std::str::FromStr;
 
 /// All RPN Calculator errors
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RpnCalculatorError {
     /// Error parsing input
     ParsingError,
-    /// Not enough operands in the stack for doing the operation
-    NotEnoughOperands,
+    /// Not enough operands in the stack for doing the operation: the
+    /// operator, how many values it takes and how many there were
+    NotEnoughOperands { op: String, needed: usize, got: usize },
+    /// Division by zero in a number type that can't represent the result
+    DivisionByZero,
+    /// The result doesn't fit in the number type
+    Overflow,
+    /// The operands have units of different dimensions
+    UnitMismatch,
+    /// An operator that takes a name, like `sto`, came last
+    MissingArgument,
+    /// Recalling a register nothing was stored in
+    UnknownRegister(String),
+    /// A definition started with `:` has no `;`
+    UnterminatedDefinition,
+    /// A quotation started with `{` has no matching `}`
+    UnterminatedQuotation,
+    /// `if` or `ifelse` found fewer quotations than it takes
+    MissingQuotation,
+    /// `times` was asked to run, or `times` and `repeat` ran, more than
+    /// `MAX_TIMES` times
+    TooManyIterations,
+    /// `pick` or `roll` got a position that isn't a whole number
+    InvalidIndex,
+    /// A function got an argument it isn't defined for, like `-1 sqrt`
+    DomainError,
+    /// The number type can't do the operation, like an irrational
+    /// result with fractions
+    NotSupported,
+    /// User-defined words called each other too deeply
+    RecursionTooDeep,
+    /// `undo` found no earlier state to go back to
+    NothingToUndo,
+    /// `redo` found no undone state to go forward to
+    NothingToRedo,
+    /// `!n` named a line that wasn't entered, or `!!` came first
+    NotInHistory,
+    /// `help` was asked about a name that is no operator or word
+    UnknownName(String),
+    /// An infix expression has a `(` without `)` or the other way round
+    UnbalancedParentheses,
+    /// An infix expression has a token where it doesn't fit, like an
+    /// operator after another
+    UnexpectedToken,
+    /// An infix expression ends where an operand should come
+    UnexpectedEnd,
+    /// A function in an infix expression called with the wrong number of
+    /// arguments
+    WrongArgumentCount { op: String, needed: usize, got: usize },
+    /// `explain` found an operator with no infix form, like `sum`, which
+    /// takes however many values there are
+    NoInfixForm,
     /// This error signals that the calculator has to quit (maybe should not be an error?)
     Quit,
     /// This is used when there is an IO error outside the calc, maybe should be done some other way.
     /// Both Quit and IOError could be replaced by some standardized way of defining custom returns
     /// for calculator operators.
     IOError,
+    /// Another error, at a token of the line evaluated: the token and its
+    /// byte offset in the line. An error in a word or a quotation is at
+    /// the token of the line that ran it.
+    At { token: String, offset: usize, error: Box<RpnCalculatorError> },
+}
+
+impl RpnCalculatorError {
+    /// The error itself, without the token `At` adds
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::{RpnCalculator, RpnCalculatorError};
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// let error = calc.evaluate("1 2 + +").unwrap_err();
+    /// assert_eq!("`+` at byte 6: + takes 2 operands, got 1", error.to_string());
+    /// let expected = RpnCalculatorError::NotEnoughOperands { op: "+".to_string(), needed: 2, got: 1 };
+    /// assert_eq!(&expected, error.kind());
+    /// ```
+    pub fn kind(&self) -> &RpnCalculatorError {
+        match *self {
+            RpnCalculatorError::At { ref error, .. } => error.kind(),
+            ref error => error,
+        }
+    }
+}
+
+impl fmt::Display for RpnCalculatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::RpnCalculatorError::*;
+        match *self {
+            ParsingError => write!(f, "Not a number or an operator"),
+            NotEnoughOperands { ref op, needed, got } => {
+                write!(f, "{} takes {} operand{}, got {}", op, needed, if needed == 1 { "" } else { "s" }, got)
+            }
+            DivisionByZero => write!(f, "Division by zero"),
+            Overflow => write!(f, "Overflow"),
+            UnitMismatch => write!(f, "The units don't match"),
+            MissingArgument => write!(f, "Missing name or number after the operator"),
+            UnknownRegister(ref name) => write!(f, "Nothing stored in {}", name),
+            UnterminatedDefinition => write!(f, "Definition without ;"),
+            UnterminatedQuotation => write!(f, "Quotation without }} or ]"),
+            MissingQuotation => write!(f, "Missing quotation"),
+            TooManyIterations => write!(f, "More than {} iterations", MAX_TIMES),
+            InvalidIndex => write!(f, "Not a position in the stack"),
+            DomainError => write!(f, "Argument out of the domain"),
+            NotSupported => write!(f, "Not supported by this number type"),
+            RecursionTooDeep => write!(f, "Words nested more than {} deep", MAX_WORD_DEPTH),
+            NothingToUndo => write!(f, "Nothing to undo"),
+            NothingToRedo => write!(f, "Nothing to redo"),
+            NotInHistory => write!(f, "No such line in the history"),
+            UnknownName(ref name) => write!(f, "No operator or word named {}", name),
+            UnbalancedParentheses => write!(f, "Parentheses don't match"),
+            UnexpectedToken => write!(f, "Not expected here"),
+            UnexpectedEnd => write!(f, "The expression ends too early"),
+            WrongArgumentCount { ref op, needed, got } => {
+                write!(f, "{} takes {} argument{}, got {}", op, needed, if needed == 1 { "" } else { "s" }, got)
+            }
+            NoInfixForm => write!(f, "No infix form"),
+            Quit => write!(f, "Quit"),
+            IOError => write!(f, "Input or output error"),
+            At { ref token, offset, ref error } => write!(f, "`{}` at byte {}: {}", token, offset, error),
+        }
+    }
+}
+
+impl error::Error for RpnCalculatorError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            RpnCalculatorError::At { ref error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The result used fo all calculator operations
+pub type CalcResult = result::Result<(), RpnCalculatorError>;
+
+/// An error in a script, with the line it happened in, counting from 1
+#[derive(Debug)]
+pub struct LineError {
+    pub line: usize,
+    pub error: RpnCalculatorError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl error::Error for LineError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Checks there are at least `needed` values on the stack for an
+/// operator, failing with how many there are
+///
+/// # Example
+/// ```
+/// use pprust::rpncalculator::{require, RpnCalculatorError};
+///
+/// assert_eq!(Ok(()), require(&[1.0, 2.0], "+", 2));
+/// let expected = RpnCalculatorError::NotEnoughOperands { op: "+".to_string(), needed: 2, got: 1 };
+/// assert_eq!(Err(expected), require(&[1.0], "+", 2));
+/// ```
+pub fn require<T>(stack: &[T], op: &str, needed: usize) -> CalcResult {
+    if stack.len() < needed {
+        return Err(RpnCalculatorError::NotEnoughOperands { op: op.to_string(), needed, got: stack.len() });
+    }
+    Ok(())
+}
+/// The stack used by the calculator
+pub type CalcStack<T = f64> = Vec<T>;
+/// The function each operator uses for mutating the calculator state
+pub type OperatorFn<T = f64> = fn(&mut CalcState<T>) -> CalcResult;
+/// A mapping of string symbols to operators
+pub type OperatorsMap<T = f64> = collections::BTreeMap<&'static str, Operator<T>>;
+
+/// An operator: the function that runs it and what `help` tells of it
+pub struct Operator<T = f64> {
+    pub run: OperatorFn<T>,
+    /// How many values it takes from the stack, if that is fixed
+    pub arity: Option<usize>,
+    /// What it does, in a few words
+    pub description: &'static str,
+    /// An input using it, which `help` evaluates to show the result
+    pub example: &'static str,
+}
+
+impl<T> Operator<T> {
+    /// An operator with no arity or description given
+    pub fn new(run: OperatorFn<T>) -> Operator<T> {
+        Operator { run, arity: None, description: "", example: "" }
+    }
+}
+
+impl<T> Clone for Operator<T> {
+    fn clone(&self) -> Operator<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Operator<T> {}
+
+impl<T> fmt::Debug for Operator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Operator")
+            .field("arity", &self.arity)
+            .field("description", &self.description)
+            .field("example", &self.example)
+            .finish()
+    }
+}
+
+/// How deeply user-defined words may call each other
+pub const MAX_WORD_DEPTH: usize = 256;
+
+/// How many times `times` and `repeat` may run their quotations in a
+/// line
+pub const MAX_TIMES: usize = 1_000_000;
+
+/// What `times` leaves after its quotation, followed by the runs left,
+/// to run it again; no token of the input has a space, so none looks
+/// like it
+const TIMES_LEFT: &str = "times ";
+
+/// How many states `undo` can go back by default
+pub const DEFAULT_UNDO_LIMIT: usize = 100;
+
+/// The name that shows the help of the operators, which the calculator
+/// runs itself, as only it knows them all
+const HELP: &str = "help";
+
+const HELP_DESCRIPTION: &str = "Shows this list, or what the operator or word named next does";
+
+/// Everything operators can see and change: the stack, which the state
+/// dereferences to, the registers, the user-defined words and the rest
+/// of the input.
+#[derive(Debug, Clone)]
+pub struct CalcState<T = f64> {
+    pub stack: CalcStack<T>,
+    /// Values kept by `sto`, by name
+    pub registers: collections::BTreeMap<String, T>,
+    /// Words defined with `: name ... ;`, by name, with the tokens they
+    /// stand for
+    pub words: collections::BTreeMap<String, Vec<String>>,
+    /// The base results are shown in, set by `hex`, `bin`, `oct` and
+    /// `dec`
+    pub base: Base,
+    /// The unit of the angles the trigonometric functions take and give,
+    /// set by `deg` and `rad`
+    pub angle: Angle,
+    /// How results are shown in decimal, set by `fix`, `sci`, `eng` and
+    /// `std`
+    pub format: Format,
+    /// Token groups quoted with `{ ... }` or `[ ... ]`, waiting for `if`
+    /// or `ifelse`
+    pub quotations: Vec<Vec<String>>,
+    /// How many digits after the point quotients keep, for number types
+    /// that round them, like decimals
+    pub precision: u32,
+    /// How many times `times` and `repeat` have run a quotation in the
+    /// line being evaluated
+    repeats: usize,
+    /// The tokens of the input not read yet, each with the index in the
+    /// line of the token it came from
+    pending: collections::VecDeque<(String, usize)>,
+    /// The index in the line of the token running now, or of the one the
+    /// running word or quotation came from
+    source: usize,
+    history: History<T>,
+}
+
+/// Everything `undo` brings back, and `save` writes with the values as
+/// text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Snapshot<T> {
+    stack: CalcStack<T>,
+    registers: collections::BTreeMap<String, T>,
+    words: collections::BTreeMap<String, Vec<String>>,
+    base: Base,
+    angle: Angle,
+    format: Format,
+    quotations: Vec<Vec<String>>,
+    /// Missing from sessions saved before it was kept
+    #[serde(default = "default_precision")]
+    precision: u32,
+}
+
+fn default_precision() -> u32 {
+    decimal::DEFAULT_PRECISION
+}
+
+impl<T> Snapshot<T> {
+    /// The same state with each value converted, or the first error
+    fn convert<U, F>(self, mut f: F) -> result::Result<Snapshot<U>, RpnCalculatorError>
+    where
+        F: FnMut(T) -> result::Result<U, RpnCalculatorError>,
+    {
+        let mut registers = collections::BTreeMap::new();
+        for (name, value) in self.registers {
+            registers.insert(name, f(value)?);
+        }
+        Ok(Snapshot {
+            stack: self.stack.into_iter().map(&mut f).collect::<result::Result<_, _>>()?,
+            registers,
+            words: self.words,
+            base: self.base,
+            angle: self.angle,
+            format: self.format,
+            quotations: self.quotations,
+            precision: self.precision,
+        })
+    }
+}
+
+/// The states `undo` and `redo` move between
+#[derive(Debug, Clone)]
+struct History<T> {
+    /// Before each of the last lines that changed something, oldest first
+    undo: collections::VecDeque<Snapshot<T>>,
+    /// The states undone, the last one undone last
+    redo: Vec<Snapshot<T>>,
+    limit: usize,
+    /// How many times `undo` or `redo` ran, so the lines running them
+    /// aren't recorded as changes of their own
+    moves: usize,
+}
+
+impl<T> CalcState<T> {
+    pub fn new() -> CalcState<T> {
+        CalcState {
+            stack: Vec::new(),
+            registers: collections::BTreeMap::new(),
+            words: collections::BTreeMap::new(),
+            base: Base::Dec,
+            angle: Angle::Rad,
+            format: Format::Std,
+            quotations: Vec::new(),
+            precision: decimal::DEFAULT_PRECISION,
+            repeats: 0,
+            pending: collections::VecDeque::new(),
+            source: 0,
+            history: History {
+                undo: collections::VecDeque::new(),
+                redo: Vec::new(),
+                limit: DEFAULT_UNDO_LIMIT,
+                moves: 0,
+            },
+        }
+    }
+
+    /// Takes the next token of the input, for operators that read an
+    /// argument, like the name after `sto`
+    pub fn next_word(&mut self) -> Option<String> {
+        self.pending.pop_front().map(|(token, _)| token)
+    }
+
+    /// Puts tokens back in front of the rest of the input, so they run
+    /// next, as `if` does with the quotation it picks
+    pub fn run_next(&mut self, tokens: Vec<String>) {
+        for token in tokens.into_iter().rev() {
+            self.pending.push_front((token, self.source));
+        }
+    }
+}
+
+impl<T> Default for CalcState<T> {
+    fn default() -> CalcState<T> {
+        CalcState::new()
+    }
+}
+
+impl<T: Clone> CalcState<T> {
+    fn snapshot(&self) -> Snapshot<T> {
+        Snapshot {
+            stack: self.stack.clone(),
+            registers: self.registers.clone(),
+            words: self.words.clone(),
+            base: self.base,
+            angle: self.angle,
+            format: self.format,
+            quotations: self.quotations.clone(),
+            precision: self.precision,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot<T>) {
+        self.stack = snapshot.stack;
+        self.registers = snapshot.registers;
+        self.words = snapshot.words;
+        self.base = snapshot.base;
+        self.angle = snapshot.angle;
+        self.format = snapshot.format;
+        self.quotations = snapshot.quotations;
+        self.precision = snapshot.precision;
+    }
+
+    /// Keeps the state before a change for `undo`, forgetting the oldest
+    /// beyond the limit and what was undone before
+    fn record(&mut self, before: Snapshot<T>) {
+        let history = &mut self.history;
+        history.undo.push_back(before);
+        while history.undo.len() > history.limit {
+            history.undo.pop_front();
+        }
+        history.redo.clear();
+    }
+}
+
+impl<T: Number> CalcState<T> {
+    /// A value as the calculator shows it: whole numbers in the base if
+    /// it isn't decimal, everything else in the format
+    pub fn show(&self, value: &T) -> String {
+        self.base.format_whole(value).unwrap_or_else(|| self.format.format(value))
+    }
+}
+
+impl<T> Deref for CalcState<T> {
+    type Target = CalcStack<T>;
+
+    fn deref(&self) -> &CalcStack<T> {
+        &self.stack
+    }
+}
+
+impl<T> DerefMut for CalcState<T> {
+    fn deref_mut(&mut self) -> &mut CalcStack<T> {
+        &mut self.stack
+    }
+}
+
+/// The bases the calculator can show results in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Base {
+    #[default]
+    Dec,
+    Hex,
+    Bin,
+    Oct,
+}
+
+/// The units angles can be in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Angle {
+    #[default]
+    Rad,
+    Deg,
+}
+
+impl Angle {
+    /// The sine of an angle in this unit. In degrees the multiples of
+    /// 30 are exact, so `180 sin` is 0 rather than a rounding error and
+    /// `30 sin` is a half even with fractions.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::Angle;
+    ///
+    /// assert_eq!(0.0, Angle::Deg.sin(180.0));
+    /// assert_eq!(-1.0, Angle::Deg.sin(-90.0));
+    /// assert!((Angle::Deg.sin(30.0) - Angle::Rad.sin(std::f64::consts::PI / 6.0)).abs() < 1e-15);
+    /// ```
+    pub fn sin(self, x: f64) -> f64 {
+        match self {
+            Angle::Rad => x.sin(),
+            Angle::Deg => {
+                let x = x % 360.0;
+                if x % 90.0 == 0.0 {
+                    [0.0, 1.0, 0.0, -1.0][(x / 90.0).rem_euclid(4.0) as usize]
+                } else if matches!(x.rem_euclid(360.0), 30.0 | 150.0) {
+                    0.5
+                } else if matches!(x.rem_euclid(360.0), 210.0 | 330.0) {
+                    -0.5
+                } else {
+                    x.to_radians().sin()
+                }
+            }
+        }
+    }
+
+    pub fn cos(self, x: f64) -> f64 {
+        match self {
+            Angle::Rad => x.cos(),
+            Angle::Deg => self.sin(x % 360.0 + 90.0),
+        }
+    }
+
+    /// The tangent, not a number where the cosine is exactly 0, and exact
+    /// at the multiples of 45 degrees
+    pub fn tan(self, x: f64) -> f64 {
+        match self {
+            Angle::Rad => x.tan(),
+            Angle::Deg if self.cos(x) == 0.0 => f64::NAN,
+            Angle::Deg if self.sin(x) == 0.0 => 0.0,
+            Angle::Deg if x % 45.0 == 0.0 => if (x / 45.0).rem_euclid(4.0) == 1.0 { 1.0 } else { -1.0 },
+            Angle::Deg => (x % 360.0).to_radians().tan(),
+        }
+    }
+
+    /// An angle in radians in this unit, for the inverse functions
+    pub fn from_radians(self, x: f64) -> f64 {
+        match self {
+            Angle::Rad => x,
+            Angle::Deg => x.to_degrees(),
+        }
+    }
+}
+
+impl Base {
+    /// A value in this base, with a `0x`, `0b` or `0o` prefix as the
+    /// calculator reads them back. Values that aren't whole numbers, or
+    /// don't fit in 64 bits, are shown as usual.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::Base;
+    ///
+    /// assert_eq!("0xff", Base::Hex.format(&255.0));
+    /// assert_eq!("-0b101", Base::Bin.format(&-5i64));
+    /// assert_eq!("2.5", Base::Oct.format(&2.5));
+    /// ```
+    pub fn format<T: Number>(self, value: &T) -> String {
+        self.format_whole(value).unwrap_or_else(|| value.to_string())
+    }
+
+    /// A value in this base, if it isn't decimal and the value is whole
+    fn format_whole<T: Number>(self, value: &T) -> Option<String> {
+        let n = match value.to_integer() {
+            Ok(n) if self != Base::Dec && T::from_integer(n).ok().as_ref() == Some(value) => n,
+            _ => return None,
+        };
+        let sign = if n < 0 { "-" } else { "" };
+        let m = n.unsigned_abs();
+        Some(match self {
+            Base::Dec => unreachable!("Decimal values are left to the format"),
+            Base::Hex => format!("{}0x{:x}", sign, m),
+            Base::Bin => format!("{}0b{:b}", sign, m),
+            Base::Oct => format!("{}0o{:o}", sign, m),
+        })
+    }
+}
+
+/// How results are shown in decimal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Format {
+    /// As the number type shows itself
+    #[default]
+    Std,
+    /// With a fixed number of digits after the point
+    Fix(usize),
+    /// In scientific notation, with a number of digits after the point
+    Sci(usize),
+    /// In scientific notation with exponents that are multiples of 3, and
+    /// as many significant digits as `Sci` of the same number shows
+    Eng(usize),
+}
+
+/// The digits `eng` uses when no `fix` or `sci` set any
+pub const DEFAULT_DIGITS: usize = 3;
+
+impl Format {
+    /// A value in this format. Values that don't convert to floats, like
+    /// complex numbers or quantities with units, and values that aren't
+    /// finite, are shown as usual.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::Format;
+    ///
+    /// assert_eq!("3.1416", Format::Fix(4).format(&3.14159265));
+    /// assert_eq!("1.235e4", Format::Sci(3).format(&12346.0));
+    /// assert_eq!("12.35e3", Format::Eng(3).format(&12346.0));
+    /// assert_eq!("-987.7e-6", Format::Eng(3).format(&-0.00098767));
+    /// ```
+    pub fn format<T: Number>(self, value: &T) -> String {
+        let x = match value.to_float() {
+            Ok(x) if x.is_finite() && self != Format::Std => x,
+            _ => return value.to_string(),
+        };
+        match self {
+            Format::Std => value.to_string(),
+            Format::Fix(digits) => format!("{:.*}", digits, x),
+            Format::Sci(digits) => format!("{:.*e}", digits, x),
+            Format::Eng(digits) => {
+                // Rounded as in scientific notation, then the point moved
+                // right until the exponent is a multiple of 3
+                let sci = format!("{:.*e}", digits, x);
+                let (mantissa, exponent) = sci.split_at(sci.find('e').expect("Scientific notation has an exponent"));
+                let exponent: i32 = exponent[1..].parse().expect("The exponent is a number");
+                let shift = exponent.rem_euclid(3);
+                let mantissa: f64 = mantissa.parse().expect("The mantissa is a number");
+                let mantissa = mantissa * 10f64.powi(shift);
+                format!("{:.*}e{}", digits.saturating_sub(shift as usize), mantissa, exponent - shift)
+            }
+        }
+    }
+
+    /// The digits after the point this format shows, for `eng` to keep
+    fn digits(self) -> usize {
+        match self {
+            Format::Std => DEFAULT_DIGITS,
+            Format::Fix(digits) | Format::Sci(digits) | Format::Eng(digits) => digits,
+        }
+    }
+}
+
+/// The numbers a calculator can work with.
+///
+/// The arithmetic methods default to the operators, which is right for
+/// floating point; types that can overflow or can't divide by zero
+/// override them to report an error instead of panicking.
+pub trait Number: Clone + PartialOrd + FromStr + fmt::Display
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    /// Converts a small constant, like the 2 in a mean
+    fn from_i32(n: i32) -> Self;
+
+    /// The operators a calculator for this type starts with
+    fn operators() -> OperatorsMap<Self> {
+        default_operators()
+    }
+
+    fn try_add(self, other: Self) -> result::Result<Self, RpnCalculatorError> {
+        Ok(self + other)
+    }
+
+    fn try_sub(self, other: Self) -> result::Result<Self, RpnCalculatorError> {
+        Ok(self - other)
+    }
+
+    fn try_mul(self, other: Self) -> result::Result<Self, RpnCalculatorError> {
+        Ok(self * other)
+    }
+
+    fn try_div(self, other: Self) -> result::Result<Self, RpnCalculatorError> {
+        Ok(self / other)
+    }
+
+    fn try_neg(self) -> result::Result<Self, RpnCalculatorError> {
+        Self::from_i32(0).try_sub(self)
+    }
+
+    /// The absolute value; types whose values aren't points, like
+    /// intervals, or have units, like quantities, work it out themselves
+    fn try_abs(self) -> result::Result<Self, RpnCalculatorError> {
+        if self < Self::from_i32(0) {
+            self.try_neg()
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Whether the value is zero, in whatever unit it has
+    fn is_zero(&self) -> bool {
+        *self == Self::from_i32(0)
+    }
+
+    /// The value as a float, for the scientific functions
+    fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+        Err(RpnCalculatorError::NotSupported)
+    }
+
+    /// The result of a scientific function, if the type can hold it
+    fn from_float(_x: f64) -> result::Result<Self, RpnCalculatorError> {
+        Err(RpnCalculatorError::NotSupported)
+    }
+
+    /// The value of a named constant like `pi`, if the type can hold it
+    fn from_constant(x: f64) -> result::Result<Self, RpnCalculatorError> {
+        Self::from_float(x)
+    }
+
+    /// The value truncated to an integer, for the bitwise operators
+    fn to_integer(&self) -> result::Result<i64, RpnCalculatorError> {
+        let x = self.to_float()?.trunc();
+        if x.is_nan() {
+            return Err(RpnCalculatorError::DomainError);
+        }
+        // i64::MAX rounds up to 2^63 as a float, which is already too big
+        if x < i64::MIN as f64 || x >= i64::MAX as f64 {
+            return Err(RpnCalculatorError::Overflow);
+        }
+        Ok(x as i64)
+    }
+
+    /// The result of a bitwise operator, if the type can hold it
+    fn from_integer(n: i64) -> result::Result<Self, RpnCalculatorError> {
+        Self::from_float(n as f64)
+    }
+
+    /// The unit named by a token, which the calculator multiplies the top
+    /// of the stack by, as in `3 m`; types without units have none
+    fn unit(_name: &str) -> Option<Self> {
+        None
+    }
+
+    /// Reads a value back from how `Display` shows it, for `load`
+    fn from_display(text: &str) -> Option<Self> {
+        text.parse().ok()
+    }
+}
+
+macro_rules! float_number {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            fn from_i32(n: i32) -> $t {
+                n as $t
+            }
+
+            fn try_neg(self) -> result::Result<$t, RpnCalculatorError> {
+                Ok(-self)
+            }
+
+            fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+                Ok(*self as f64)
+            }
+
+            fn from_float(x: f64) -> result::Result<$t, RpnCalculatorError> {
+                Ok(x as $t)
+            }
+        }
+    )*};
+}
+
+macro_rules! integer_number {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            fn from_i32(n: i32) -> $t {
+                n as $t
+            }
+
+            fn try_add(self, other: $t) -> result::Result<$t, RpnCalculatorError> {
+                self.checked_add(other).ok_or(RpnCalculatorError::Overflow)
+            }
+
+            fn try_sub(self, other: $t) -> result::Result<$t, RpnCalculatorError> {
+                self.checked_sub(other).ok_or(RpnCalculatorError::Overflow)
+            }
+
+            fn try_mul(self, other: $t) -> result::Result<$t, RpnCalculatorError> {
+                self.checked_mul(other).ok_or(RpnCalculatorError::Overflow)
+            }
+
+            fn try_div(self, other: $t) -> result::Result<$t, RpnCalculatorError> {
+                if other == 0 {
+                    return Err(RpnCalculatorError::DivisionByZero);
+                }
+                self.checked_div(other).ok_or(RpnCalculatorError::Overflow)
+            }
+
+            fn try_neg(self) -> result::Result<$t, RpnCalculatorError> {
+                self.checked_neg().ok_or(RpnCalculatorError::Overflow)
+            }
+
+            fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+                Ok(*self as f64)
+            }
+
+            /// Truncated, as integer division is
+            fn from_float(x: f64) -> result::Result<$t, RpnCalculatorError> {
+                let x = x.trunc();
+                if x < <$t>::MIN as f64 || x > <$t>::MAX as f64 {
+                    return Err(RpnCalculatorError::Overflow);
+                }
+                Ok(x as $t)
+            }
+
+            fn to_integer(&self) -> result::Result<i64, RpnCalculatorError> {
+                Ok(*self as i64)
+            }
+
+            /// None, as truncating them would give a different number
+            fn from_constant(_x: f64) -> result::Result<$t, RpnCalculatorError> {
+                Err(RpnCalculatorError::NotSupported)
+            }
+
+            fn from_integer(n: i64) -> result::Result<$t, RpnCalculatorError> {
+                <$t>::try_from(n).map_err(|_| RpnCalculatorError::Overflow)
+            }
+        }
+    )*};
+}
+
+float_number!(f32, f64);
+integer_number!(i32, i64);
+
+impl Number for Roman {
+    fn from_i32(n: i32) -> Roman {
+        Roman::new(n as u32).expect("Small constants have Roman numerals")
+    }
+
+    fn try_add(self, other: Roman) -> result::Result<Roman, RpnCalculatorError> {
+        self.checked_add(&other).ok_or(RpnCalculatorError::Overflow)
+    }
+
+    fn try_sub(self, other: Roman) -> result::Result<Roman, RpnCalculatorError> {
+        self.checked_sub(&other).ok_or(RpnCalculatorError::Overflow)
+    }
+
+    fn try_mul(self, other: Roman) -> result::Result<Roman, RpnCalculatorError> {
+        self.checked_mul(&other).ok_or(RpnCalculatorError::Overflow)
+    }
+
+    fn try_div(self, other: Roman) -> result::Result<Roman, RpnCalculatorError> {
+        self.checked_div(&other).ok_or(RpnCalculatorError::DivisionByZero)
+    }
+
+    fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+        Ok(self.value() as f64)
+    }
+
+    /// Only whole results, as Roman numerals have no fractions
+    fn from_float(x: f64) -> result::Result<Roman, RpnCalculatorError> {
+        if x.fract() != 0.0 || x < 0.0 {
+            return Err(RpnCalculatorError::NotSupported);
+        }
+        if x > roman::MAX as f64 {
+            return Err(RpnCalculatorError::Overflow);
+        }
+        Ok(Roman::new(x as u32).expect("Already checked the range"))
+    }
+}
+
+impl Number for Ratio {
+    fn from_i32(n: i32) -> Ratio {
+        Ratio::from_integer(n as i64)
+    }
+
+    fn try_add(self, other: Ratio) -> result::Result<Ratio, RpnCalculatorError> {
+        self.checked_add(&other).ok_or(RpnCalculatorError::Overflow)
+    }
+
+    fn try_sub(self, other: Ratio) -> result::Result<Ratio, RpnCalculatorError> {
+        self.checked_sub(&other).ok_or(RpnCalculatorError::Overflow)
+    }
+
+    fn try_mul(self, other: Ratio) -> result::Result<Ratio, RpnCalculatorError> {
+        self.checked_mul(&other).ok_or(RpnCalculatorError::Overflow)
+    }
+
+    fn try_div(self, other: Ratio) -> result::Result<Ratio, RpnCalculatorError> {
+        if other.numer() == 0 {
+            return Err(RpnCalculatorError::DivisionByZero);
+        }
+        self.checked_div(&other).ok_or(RpnCalculatorError::Overflow)
+    }
+
+    fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+        Ok(self.to_f64())
+    }
+
+    /// Only results that are the float of a fraction with a denominator
+    /// up to a million, like the 4/3 of `16/9 sqrt`, taken to be exact
+    fn from_float(x: f64) -> result::Result<Ratio, RpnCalculatorError> {
+        if x.abs() >= i64::MAX as f64 {
+            return Err(RpnCalculatorError::Overflow);
+        }
+        if x.fract() == 0.0 {
+            return Ok(Ratio::from_integer(x as i64));
+        }
+        let ratio = farey::approximate(x, 1_000_000);
+        if ratio.to_f64() == x {
+            Ok(ratio)
+        } else {
+            Err(RpnCalculatorError::NotSupported)
+        }
+    }
+}
+
+/// Defines new operators and putting them in an operators map.
+///
+/// The operator is a closure that doesn't capture anything, so it
+/// becomes a plain `OperatorFn` for whatever number type the map holds.
+/// That type has to be known where the macro is used.
+///
+/// Any of the forms below can take a description and an example for
+/// `help` after the name, as in
+/// `new_operator!(ops, "+", "Adds two values", "2 3 +", [y, x], { x + y })`.
+/// The forms that take operands from the stack record how many.
+///
+/// There are three forms of this macro:
+///
+/// * Define an operator that takes *n* operands and returns a value to be pushed into the stack.
+///   The operands are only popped if the code doesn't return early with an error.
+///
+/// ```
+/// #[macro_use]
+/// extern crate pprust;
+/// # fn main() {
+/// use pprust::rpncalculator::*;
+///
+/// let mut ops: OperatorsMap = default_operators();
+/// new_operator!(ops, "+", [x, y], { x + y });
+/// let mut state: CalcState = CalcState::new();
+/// let f = ops.get("+").unwrap().run;
+/// state.push(1.0);
+/// state.push(2.0);
+/// f(&mut state);
+/// assert_eq!(3.0, *state.last().unwrap());
+/// # }
+/// ```
+///
+/// * Define an operator that takes *n* operands and pushes any number of
+///   values back, for rearranging the stack. The first operand is the
+///   top of the stack and the values are pushed in the order written.
+///
+/// ```
+/// #[macro_use]
+/// extern crate pprust;
+/// # fn main() {
+/// use pprust::rpncalculator::*;
+///
+/// let mut ops: OperatorsMap = default_operators();
+/// new_operator!(ops, "tuck", [y, x] => [y.clone(), x, y]);
+/// let mut state: CalcState = CalcState::new();
+/// state.extend(vec![1.0, 2.0]);
+/// (ops["tuck"].run)(&mut state).unwrap();
+/// assert_eq!(Some(2), ops["tuck"].arity);
+/// assert_eq!(vec![2.0, 1.0, 2.0], state.stack);
+/// # }
+/// ```
+///
+/// * Define an operator that operates directly on the calculator state,
+///   which dereferences to the stack
+///
+/// ```
+/// #[macro_use]
+/// extern crate pprust;
+/// # fn main() {
+/// use pprust::rpncalculator::*;
+/// let mut ops: OperatorsMap = default_operators();
+/// let mut state: CalcState = CalcState::new();
+/// state.push(1.0);
+/// new_operator!(ops, "p", s, { require(s, "p", 1)?; s.pop(); Ok(()) });
+/// let f = ops.get("p").unwrap().run;
+/// let res = f(&mut state);
+/// assert!(res.is_ok());
+/// assert_eq!(0, state.len());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! new_operator {
+    ($ops:expr, $name:expr, $description:literal, $example:literal, $( $rest:tt )*) => {{
+        new_operator!($ops, $name, $( $rest )*);
+        let operator = $ops.get_mut($name).expect("The operator was just inserted");
+        operator.description = $description;
+        operator.example = $example;
+    }};
+    ($ops:expr, $name:expr, [ $( $var:ident ),* ], $code:block) => {{
+        const ARITY: usize = <[&str]>::len(&[$( stringify!($var) ),*]);
+        $ops.insert($name, $crate::rpncalculator::Operator {
+            run: |s| {
+                $crate::rpncalculator::require(s, $name, ARITY)?;
+                let i = s.len();
+                $(
+                    let $var = s[i - 1].clone();
+                    let i = i - 1;
+                )*
+                let result = { $code };
+                s.truncate(i);
+                s.push(result);
+                Ok(())
+            },
+            arity: Some(ARITY),
+            description: "",
+            example: "",
+        });
+    }};
+    ($ops:expr, $name:expr, [ $( $var:ident ),* ] => [ $( $result:expr ),* ]) => {{
+        const ARITY: usize = <[&str]>::len(&[$( stringify!($var) ),*]);
+        $ops.insert($name, $crate::rpncalculator::Operator {
+            run: |s| {
+                $crate::rpncalculator::require(s, $name, ARITY)?;
+                let i = s.len();
+                $(
+                    let $var = s[i - 1].clone();
+                    let i = i - 1;
+                )*
+                s.truncate(i);
+                $( s.push($result); )*
+                Ok(())
+            },
+            arity: Some(ARITY),
+            description: "",
+            example: "",
+        });
+    }};
+    ($ops:expr, $name:expr, $stackvar:ident, $code:block) => {{
+        $ops.insert($name, $crate::rpncalculator::Operator::new(|s| {
+            let $stackvar = s;
+            $code
+        }));
+    }};
+}
+
+/// This function builds an operators map with all the default
+/// operators the calculator supports. This map can be used to add
+/// more operators before creating a new calculator.
+/// See
+///
+/// # Example
+/// ```
+/// use pprust::rpncalculator::{default_operators, CalcResult, CalcState, Operator};
+///
+/// let mut ops = default_operators();
+/// fn op(s: &mut CalcState) -> CalcResult {
+///     s.push(2.0);
+///     Ok(())
+/// }
+/// ops.insert("?", Operator { description: "Pushes 2", ..Operator::new(op) });
+/// ```
+pub fn default_operators<T: Number>() -> OperatorsMap<T> {
+    let mut ops: OperatorsMap<T> = collections::BTreeMap::new();
+    new_operator!(ops, "+", "Adds the top two values", "2 3 +", [y, x], { x.try_add(y)? });
+    new_operator!(ops, "-", "Subtracts the top value from the one below", "5 3 -", [y, x], { x.try_sub(y)? });
+    new_operator!(ops, "*", "Multiplies the top two values", "4 3 *", [y, x], { x.try_mul(y)? });
+    new_operator!(ops, "/", "Divides the value below by the top one", "7 2 /", [y, x], { x.try_div(y)? });
+    new_operator!(ops, "median", "Replaces the stack with its median", "3 1 2 median", s, { median(s) });
+    new_operator!(ops, "sum", "Replaces the stack with its sum", "1 2 3 sum", s, { reduce(s, "sum", sum) });
+    new_operator!(ops, "prod", "Replaces the stack with its product", "2 3 4 prod", s, { reduce(s, "prod", |values| values.iter().cloned().try_fold(T::from_i32(1), T::try_mul)) });
+    new_operator!(ops, "mean", "Replaces the stack with its mean", "1 2 3 4 mean", s, { reduce(s, "mean", mean) });
+    new_operator!(ops, "stddev", "Replaces the stack with its sample standard deviation", "2 4 4 4 5 5 7 9 stddev", s, { reduce(s, "stddev", stddev) });
+    new_operator!(ops, "min", "Replaces the stack with its smallest value", "3 1 2 min", s, { reduce(s, "min", |values| Ok(extreme(values, |x, m| x < m))) });
+    new_operator!(ops, "max", "Replaces the stack with its largest value", "3 1 2 max", s, { reduce(s, "max", |values| Ok(extreme(values, |x, m| x > m))) });
+    new_operator!(ops, "count", "Replaces the stack with how many values it had", "5 6 7 count", s, {
+        let count = count(s.len())?;
+        s.clear();
+        s.push(count);
+        Ok(())
+    });
+    new_operator!(ops, "sto", "Stores the top value in the register named next", "9.81 sto g", s, {
+        require(s, "sto", 1)?;
+        let name = s.next_word().ok_or(RpnCalculatorError::MissingArgument)?;
+        let top = s[s.len() - 1].clone();
+        s.registers.insert(name, top);
+        Ok(())
+    });
+    new_operator!(ops, "rcl", "Pushes the value of the register named next", "2 sto x rcl x *", s, {
+        let name = s.next_word().ok_or(RpnCalculatorError::MissingArgument)?;
+        let value = s.registers.get(&name).cloned().ok_or(RpnCalculatorError::UnknownRegister(name))?;
+        s.push(value);
+        Ok(())
+    });
+    new_operator!(ops, ":", "Defines the word named next as the tokens up to ;", ": sq dup * ; 3 sq", s, {
+        let name = match s.next_word() {
+            Some(name) if name != ";" => name,
+            _ => return Err(RpnCalculatorError::MissingArgument),
+        };
+        let mut body = Vec::new();
+        loop {
+            match s.next_word() {
+                None => return Err(RpnCalculatorError::UnterminatedDefinition),
+                Some(token) if token == ";" => break,
+                Some(token) => body.push(token),
+            }
+        }
+        s.words.insert(name, body);
+        Ok(())
+    });
+    new_operator!(ops, "<", "1 if the value below is less than the top one, else 0", "1 2 <", [y, x], { truth(x < y) });
+    new_operator!(ops, ">", "1 if the value below is greater than the top one, else 0", "1 2 >", [y, x], { truth(x > y) });
+    new_operator!(ops, "=", "1 if the top two values are equal, else 0", "2 2 =", [y, x], { truth(x == y) });
+    new_operator!(ops, "<=", "1 if the value below is at most the top one, else 0", "2 2 <=", [y, x], { truth(x <= y) });
+    new_operator!(ops, ">=", "1 if the value below is at least the top one, else 0", "1 2 >=", [y, x], { truth(x >= y) });
+    new_operator!(ops, "neg", "Changes the sign", "3 neg", [x], { x.try_neg()? });
+    new_operator!(ops, "abs", "The absolute value", "-3 abs", [x], { x.try_abs()? });
+    new_operator!(ops, "pow", "Raises the value below to the power of the top one", "2 10 pow", [y, x], { power(x, y)? });
+    new_operator!(ops, "sqrt", "The square root", "16 sqrt", [x], { function(x, f64::sqrt, |x| x >= 0.0)? });
+    new_operator!(ops, "exp", "e to the power of the value", "0 exp", [x], { function(x, f64::exp, |_| true)? });
+    new_operator!(ops, "ln", "The natural logarithm", "1 ln", [x], { function(x, f64::ln, |x| x > 0.0)? });
+    new_operator!(ops, "log", "The base 10 logarithm", "100 log", [x], { function(x, f64::log10, |x| x > 0.0)? });
+    new_operator!(ops, "sin", "The sine, in the angle unit", "deg 30 sin", s, { angular(s, "sin", |angle, x| angle.sin(x), |_| true) });
+    new_operator!(ops, "cos", "The cosine, in the angle unit", "deg 60 cos", s, { angular(s, "cos", |angle, x| angle.cos(x), |_| true) });
+    new_operator!(ops, "tan", "The tangent, in the angle unit", "deg 45 tan", s, { angular(s, "tan", |angle, x| angle.tan(x), |_| true) });
+    new_operator!(ops, "asin", "The arcsine, in the angle unit", "deg 0.5 asin", s, { angular(s, "asin", |angle, x| angle.from_radians(x.asin()), |x| (-1.0..=1.0).contains(&x)) });
+    new_operator!(ops, "acos", "The arccosine, in the angle unit", "deg 0.5 acos", s, { angular(s, "acos", |angle, x| angle.from_radians(x.acos()), |x| (-1.0..=1.0).contains(&x)) });
+    new_operator!(ops, "atan", "The arctangent, in the angle unit", "deg 1 atan", s, { angular(s, "atan", |angle, x| angle.from_radians(x.atan()), |_| true) });
+    // They need the whole state, for the angle unit, but take one value
+    for name in ["sin", "cos", "tan", "asin", "acos", "atan"] {
+        ops.get_mut(name).expect("Just inserted").arity = Some(1);
+    }
+    new_operator!(ops, "deg", "Takes and gives angles in degrees", "deg 90 sin", s, {
+        s.angle = Angle::Deg;
+        Ok(())
+    });
+    new_operator!(ops, "rad", "Takes and gives angles in radians", "rad 0 cos", s, {
+        s.angle = Angle::Rad;
+        Ok(())
+    });
+    new_operator!(ops, "dup", "Copies the top value", "3 dup *", [x] => [x.clone(), x]);
+    new_operator!(ops, "drop", "Drops the top value", "1 2 drop", [_x] => []);
+    new_operator!(ops, "swap", "Swaps the top two values", "1 2 swap -", [y, x] => [y, x]);
+    new_operator!(ops, "over", "Copies the value below the top", "1 2 over", [y, x] => [x.clone(), y, x]);
+    new_operator!(ops, "rot", "Brings the third value to the top", "1 2 3 rot", [z, y, x] => [y, z, x]);
+    new_operator!(ops, "pick", "Copies the value as many places below the top one as it says", "10 20 30 1 pick", s, {
+        let k = stack_index(s, "pick")?;
+        s.pop();
+        let value = s[s.len() - 1 - k].clone();
+        s.push(value);
+        Ok(())
+    });
+    new_operator!(ops, "roll", "Moves the value as many places below the top one as it says to the top", "10 20 30 2 roll", s, {
+        let k = stack_index(s, "roll")?;
+        s.pop();
+        let len = s.len();
+        let value = s.remove(len - 1 - k);
+        s.push(value);
+        Ok(())
+    });
+    new_operator!(ops, "and", "Bitwise and of the integer parts", "12 10 and", [y, x], { T::from_integer(x.to_integer()? & y.to_integer()?)? });
+    new_operator!(ops, "or", "Bitwise or of the integer parts", "12 10 or", [y, x], { T::from_integer(x.to_integer()? | y.to_integer()?)? });
+    new_operator!(ops, "xor", "Bitwise exclusive or of the integer parts", "12 10 xor", [y, x], { T::from_integer(x.to_integer()? ^ y.to_integer()?)? });
+    new_operator!(ops, "not", "Bitwise complement of the integer part", "0 not", [x], { T::from_integer(!x.to_integer()?)? });
+    new_operator!(ops, "shl", "Shifts the value below left by the top one", "1 4 shl", [y, x], { T::from_integer(shift_left(x.to_integer()?, y.to_integer()?)?)? });
+    new_operator!(ops, "shr", "Shifts the value below right by the top one", "256 4 shr", [y, x], { T::from_integer(shift_right(x.to_integer()?, y.to_integer()?)?)? });
+    new_operator!(ops, "mod", "The remainder of dividing the integer parts", "17 5 mod", [y, x], {
+        let (x, y) = (x.to_integer()?, y.to_integer()?);
+        if y == 0 {
+            return Err(RpnCalculatorError::DivisionByZero);
+        }
+        T::from_integer(x.checked_rem(y).ok_or(RpnCalculatorError::Overflow)?)?
+    });
+    new_operator!(ops, "hex", "Shows whole numbers in hexadecimal", "255 hex", s, {
+        s.base = Base::Hex;
+        Ok(())
+    });
+    new_operator!(ops, "bin", "Shows whole numbers in binary", "5 bin", s, {
+        s.base = Base::Bin;
+        Ok(())
+    });
+    new_operator!(ops, "oct", "Shows whole numbers in octal", "8 oct", s, {
+        s.base = Base::Oct;
+        Ok(())
+    });
+    new_operator!(ops, "dec", "Shows numbers in decimal", "hex 255 dec", s, {
+        s.base = Base::Dec;
+        Ok(())
+    });
+    new_operator!(ops, "fix", "Shows numbers with the digits after the point given next", "fix 2 3.14159", s, {
+        s.format = Format::Fix(digits_argument(s)?);
+        Ok(())
+    });
+    new_operator!(ops, "sci", "Shows numbers in scientific notation with the digits given next", "sci 2 12345", s, {
+        s.format = Format::Sci(digits_argument(s)?);
+        Ok(())
+    });
+    new_operator!(ops, "eng", "Shows numbers with exponents that are multiples of 3", "eng 12345", s, {
+        s.format = Format::Eng(s.format.digits());
+        Ok(())
+    });
+    new_operator!(ops, "std", "Shows numbers as short as they read back", "fix 2 std 0.5", s, {
+        s.format = Format::Std;
+        Ok(())
+    });
+    new_operator!(ops, "undo", "Goes back to before the last line that changed something", "", s, {
+        let snapshot = s.history.undo.pop_back().ok_or(RpnCalculatorError::NothingToUndo)?;
+        let current = s.snapshot();
+        s.history.redo.push(current);
+        s.restore(snapshot);
+        s.history.moves += 1;
+        Ok(())
+    });
+    new_operator!(ops, "redo", "Brings back what undo took back", "", s, {
+        let snapshot = s.history.redo.pop().ok_or(RpnCalculatorError::NothingToRedo)?;
+        let current = s.snapshot();
+        s.history.undo.push_back(current);
+        s.restore(snapshot);
+        s.history.moves += 1;
+        Ok(())
+    });
+    new_operator!(ops, "clear", "Empties the stack", "1 2 clear 3", s, {
+        s.clear();
+        Ok(())
+    });
+    new_operator!(ops, "depth", "Pushes how many values the stack has", "7 8 depth", s, {
+        let depth = count(s.len())?;
+        s.push(depth);
+        Ok(())
+    });
+    new_operator!(ops, "{", "Quotes the tokens up to the matching } for if, ifelse, times and repeat", "5 { 1 + } 1 if", s, {
+        quote(s, "}")
+    });
+    new_operator!(ops, "[", "Quotes the tokens up to the matching ], like {", "5 [ 2 * ] 3 times", s, { quote(s, "]") });
+    new_operator!(ops, "if", "Runs the last quotation if the top value isn't 0", "5 { 1 + } 1 if", s, {
+        if s.quotations.is_empty() {
+            return Err(RpnCalculatorError::MissingQuotation);
+        }
+        let condition = pop_condition(s, "if")?;
+        let then = s.quotations.pop().expect("Already checked there is a quotation");
+        if condition {
+            s.run_next(then);
+        }
+        Ok(())
+    });
+    new_operator!(ops, "ifelse", "Runs the next to last quotation if the top value isn't 0, else the last", "{ 1 } { 2 } 0 ifelse", s, {
+        if s.quotations.len() < 2 {
+            return Err(RpnCalculatorError::MissingQuotation);
+        }
+        let condition = pop_condition(s, "ifelse")?;
+        let otherwise = s.quotations.pop().expect("Already checked there are two quotations");
+        let then = s.quotations.pop().expect("Already checked there are two quotations");
+        s.run_next(if condition { then } else { otherwise });
+        Ok(())
+    });
+    new_operator!(ops, "times", "Runs the last quotation as many times as the top value", "1 { 2 * } 10 times", s, {
+        if s.quotations.is_empty() {
+            return Err(RpnCalculatorError::MissingQuotation);
+        }
+        require(s, "times", 1)?;
+        let times = iterations(s[s.len() - 1].clone())?;
+        s.pop();
+        let body = s.quotations.pop().expect("Already checked there is a quotation");
+        run_times(s, body, times)
+    });
+    new_operator!(ops, "repeat", "Runs the last quotation until it leaves 0 on top", "1 { 2 * dup 100 < } repeat", s, {
+        let body = s.quotations.pop().ok_or(RpnCalculatorError::MissingQuotation)?;
+        if s.repeats == MAX_TIMES {
+            return Err(RpnCalculatorError::TooManyIterations);
+        }
+        s.repeats += 1;
+        // The body, then `{ { body } repeat } if` on the condition it left
+        let mut tokens = body.clone();
+        tokens.extend(vec!["{".to_string(), "{".to_string()]);
+        tokens.extend(body);
+        tokens.extend(vec!["}".to_string(), "repeat".to_string(), "}".to_string(), "if".to_string()]);
+        s.run_next(tokens);
+        Ok(())
+    });
+    ops
+}
+
+/// 1 for true and 0 for false, as the comparisons push them
+fn truth<T: Number>(condition: bool) -> T {
+    T::from_i32(condition as i32)
+}
+
+/// Applies a scientific function through floats, checking the argument
+/// is in its domain and the result is finite
+fn function<T: Number, F: Fn(f64) -> f64>(x: T, f: F, domain: fn(f64) -> bool) -> result::Result<T, RpnCalculatorError> {
+    let x = x.to_float()?;
+    if !domain(x) {
+        return Err(RpnCalculatorError::DomainError);
+    }
+    let y = f(x);
+    if y.is_nan() {
+        Err(RpnCalculatorError::DomainError)
+    } else if y.is_infinite() && x.is_finite() {
+        Err(RpnCalculatorError::Overflow)
+    } else {
+        T::from_float(y)
+    }
+}
+
+/// Applies a trigonometric function to the top of the stack, in the
+/// angle unit of the calculator
+fn angular<T: Number>(s: &mut CalcState<T>, op: &str, f: fn(Angle, f64) -> f64, domain: fn(f64) -> bool) -> CalcResult {
+    require(s, op, 1)?;
+    let x = s[s.len() - 1].clone();
+    let angle = s.angle;
+    let y = function(x, |x| f(angle, x), domain)?;
+    s.pop();
+    s.push(y);
+    Ok(())
+}
+
+/// `x` to the power `y`: exact by repeated squaring for whole exponents,
+/// through floats otherwise
+fn power<T: Number>(x: T, y: T) -> result::Result<T, RpnCalculatorError> {
+    let exponent = y.to_float()?;
+    if exponent.fract() != 0.0 {
+        let result = x.to_float()?.powf(exponent);
+        return if result.is_nan() {
+            Err(RpnCalculatorError::DomainError)
+        } else if result.is_infinite() {
+            Err(RpnCalculatorError::Overflow)
+        } else {
+            T::from_float(result)
+        };
+    }
+    let one = T::from_i32(1);
+    if exponent < 0.0 && x.is_zero() {
+        return Err(RpnCalculatorError::DivisionByZero);
+    }
+    let mut n = exponent.abs() as u64;
+    let mut square = x;
+    let mut result = one.clone();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = result.try_mul(square.clone())?;
+        }
+        n >>= 1;
+        if n > 0 {
+            square = square.clone().try_mul(square)?;
+        }
+    }
+    if result.to_float().is_ok_and(f64::is_infinite) {
+        return Err(RpnCalculatorError::Overflow);
+    }
+    if exponent < 0.0 {
+        one.try_div(result)
+    } else {
+        Ok(result)
+    }
+}
+
+/// `x` times 2 to the `n`, failing if it doesn't fit
+fn shift_left(x: i64, n: i64) -> result::Result<i64, RpnCalculatorError> {
+    if n < 0 {
+        return Err(RpnCalculatorError::DomainError);
+    }
+    if x == 0 {
+        return Ok(0);
+    }
+    if n >= 64 {
+        return Err(RpnCalculatorError::Overflow);
+    }
+    i64::try_from((x as i128) << n).map_err(|_| RpnCalculatorError::Overflow)
+}
+
+/// `x` divided by 2 to the `n`, rounding down as an arithmetic shift does
+fn shift_right(x: i64, n: i64) -> result::Result<i64, RpnCalculatorError> {
+    if n < 0 {
+        return Err(RpnCalculatorError::DomainError);
+    }
+    Ok(x >> n.min(63))
+}
+
+/// The number of digits after `fix` or `sci`, which floats can show up
+/// to 17 of
+fn digits_argument<T>(s: &mut CalcState<T>) -> result::Result<usize, RpnCalculatorError> {
+    let word = s.next_word().ok_or(RpnCalculatorError::MissingArgument)?;
+    match word.parse() {
+        Ok(digits) if digits <= 17 => Ok(digits),
+        Ok(_) => Err(RpnCalculatorError::DomainError),
+        Err(_) => Err(RpnCalculatorError::ParsingError),
+    }
+}
+
+/// About how many bits an exact power of big integers may take, so that
+/// `pow` fails rather than working on a number too big to show
+pub const MAX_POWER_BITS: u64 = 1 << 20;
+
+/// The constants the calculator reads as numbers
+const CONSTANTS: [(&str, f64); 3] = [("pi", f64::consts::PI), ("e", f64::consts::E), ("tau", f64::consts::TAU)];
+
+/// The largest exponent a literal in scientific notation may have when
+/// the number type doesn't read the notation itself and the digits are
+/// written out
+const MAX_EXPONENT: u64 = 4096;
+
+/// Reads a literal: whatever the number type reads, `0x`, `0b` and `0o`
+/// integers, scientific notation, digits grouped with `_`, the constants,
+/// and any of those negated with a leading `-`
+///
+/// # Example
+/// ```
+/// use pprust::rpncalculator::parse_literal;
+///
+/// assert_eq!(Ok(1_500_000), parse_literal::<i64>("1.5e6"));
+/// assert_eq!(Ok(-0xff_ff), parse_literal::<i64>("-0xff_ff"));
+/// assert_eq!(Ok(-std::f64::consts::TAU), parse_literal::<f64>("-tau"));
+/// assert!(parse_literal::<i64>("pi").is_err());
+/// ```
+pub fn parse_literal<T: Number>(token: &str) -> result::Result<T, RpnCalculatorError> {
+    // Written out first, so that types with integers keep `1e30` exact
+    if let Some(Ok(value)) = expand_exponent(token).map(|digits| digits.parse()) {
+        return Ok(value);
+    }
+    if let Ok(value) = token.parse() {
+        return Ok(value);
+    }
+    if let Some(digits) = without_separators(token) {
+        return parse_literal(&digits);
+    }
+    if let Some(n) = parse_integer_literal(token) {
+        return T::from_integer(n);
+    }
+    if let Some(&(_, x)) = CONSTANTS.iter().find(|&&(name, _)| name == token) {
+        return T::from_constant(x);
+    }
+    match token.strip_prefix('-') {
+        Some(rest) if !rest.starts_with(['-', '+']) => parse_literal::<T>(rest)?.try_neg(),
+        _ => Err(RpnCalculatorError::ParsingError),
+    }
+}
+
+/// A number with its digits grouped by `_`, as in `1_000_000`, without
+/// them; every `_` must be between two digits
+fn without_separators(token: &str) -> Option<String> {
+    let digits = token.strip_prefix('-').unwrap_or(token);
+    if !digits.contains('_') || !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let bytes = digits.as_bytes();
+    let grouped = bytes.iter().enumerate().filter(|&(_, &b)| b == b'_').all(|(i, _)| {
+        i + 1 < bytes.len() && bytes[i - 1].is_ascii_alphanumeric() && bytes[i + 1].is_ascii_alphanumeric()
+    });
+    if grouped {
+        Some(token.replace('_', ""))
+    } else {
+        None
+    }
+}
+
+/// A number in scientific notation, like `1.5e-3`, written out in plain
+/// digits, `0.0015`, for the types that read only those; trailing zeros
+/// after the point are dropped, so that whole numbers read as integers
+fn expand_exponent(token: &str) -> Option<String> {
+    let (mantissa, exponent) = token.split_once(['e', 'E'])?;
+    let exponent: i64 = exponent.parse().ok()?;
+    if exponent.unsigned_abs() > MAX_EXPONENT {
+        return None;
+    }
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", whole, fraction);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // Where the point goes in the digits
+    let point = whole.len() as i64 + exponent;
+    let (whole, fraction) = if point <= 0 {
+        (String::new(), "0".repeat(-point as usize) + &digits)
+    } else if point as usize >= digits.len() {
+        (digits.clone() + &"0".repeat(point as usize - digits.len()), String::new())
+    } else {
+        (digits[..point as usize].to_string(), digits[point as usize..].to_string())
+    };
+    let whole = whole.trim_start_matches('0');
+    let fraction = fraction.trim_end_matches('0');
+    let whole = if whole.is_empty() { "0" } else { whole };
+    if fraction.is_empty() {
+        Some(format!("{}{}", sign, whole))
+    } else {
+        Some(format!("{}{}.{}", sign, whole, fraction))
+    }
+}
+
+/// Reads integer literals in other bases, like `0xff`, `-0b101` or `0o17`
+fn parse_integer_literal(token: &str) -> Option<i64> {
+    let (negative, digits) = match token.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, token),
+    };
+    let radix = match digits.get(..2)? {
+        "0x" | "0X" => 16,
+        "0b" | "0B" => 2,
+        "0o" | "0O" => 8,
+        _ => return None,
+    };
+    let magnitude = u64::from_str_radix(digits.get(2..)?, radix).ok()?;
+    if negative {
+        0i64.checked_sub_unsigned(magnitude)
+    } else {
+        i64::try_from(magnitude).ok()
+    }
+}
+
+/// The position `pick` and `roll` take from the top of the stack, counting
+/// from 0 for the value under it, checked against the values there are
+fn stack_index<T: Number>(s: &CalcStack<T>, op: &str) -> result::Result<usize, RpnCalculatorError> {
+    require(s, op, 1)?;
+    let n = &s[s.len() - 1];
+    let below = s.len() - 1;
+    let mut i = T::from_i32(0);
+    let mut k = 0;
+    while i < *n && k < below {
+        i = i.try_add(T::from_i32(1))?;
+        k += 1;
+    }
+    if i < *n || (i == *n && k == below) {
+        // The position itself and the values down to the one it names
+        let needed = n.to_integer().ok().and_then(|n| usize::try_from(n).ok()).map_or(below + 2, |n| n.saturating_add(2));
+        Err(RpnCalculatorError::NotEnoughOperands { op: op.to_string(), needed, got: s.len() })
+    } else if i == *n {
+        Ok(k)
+    } else {
+        Err(RpnCalculatorError::InvalidIndex)
+    }
+}
+
+/// Reads a quotation up to the closing token, `}` or `]`, counting
+/// quotations nested in it of either kind
+fn quote<T>(s: &mut CalcState<T>, close: &str) -> CalcResult {
+    let mut quotation = Vec::new();
+    let mut nesting = 0;
+    loop {
+        match s.next_word() {
+            None => return Err(RpnCalculatorError::UnterminatedQuotation),
+            Some(token) if token == close && nesting == 0 => break,
+            Some(token) => {
+                if token == "{" || token == "[" {
+                    nesting += 1;
+                } else if token == "}" || token == "]" {
+                    nesting -= 1;
+                }
+                quotation.push(token);
+            }
+        }
+    }
+    s.quotations.push(quotation);
+    Ok(())
+}
+
+/// Runs the body of `times` once, followed by `{ body }` and the token
+/// that runs it the other times left
+fn run_times<T>(s: &mut CalcState<T>, body: Vec<String>, left: usize) -> CalcResult {
+    if left == 0 {
+        return Ok(());
+    }
+    if s.repeats == MAX_TIMES {
+        return Err(RpnCalculatorError::TooManyIterations);
+    }
+    s.repeats += 1;
+    let mut tokens = body.clone();
+    tokens.push("{".to_string());
+    tokens.extend(body);
+    tokens.push("}".to_string());
+    tokens.push(format!("{}{}", TIMES_LEFT, left - 1));
+    s.run_next(tokens);
+    Ok(())
+}
+
+/// How many times `times` runs for a count: once for every whole number
+/// from 0 below it
+fn iterations<T: Number>(count: T) -> result::Result<usize, RpnCalculatorError> {
+    let one = T::from_i32(1);
+    let mut i = T::from_i32(0);
+    let mut times = 0;
+    while i < count {
+        if times == MAX_TIMES {
+            return Err(RpnCalculatorError::TooManyIterations);
+        }
+        i = i.try_add(one.clone())?;
+        times += 1;
+    }
+    Ok(times)
+}
+
+/// Pops the condition of `if` or `ifelse`: anything but 0 is true
+fn pop_condition<T: Number>(s: &mut CalcStack<T>, op: &str) -> result::Result<bool, RpnCalculatorError> {
+    require(s, op, 1)?;
+    let condition = s.pop().expect("Already checked the stack is not empty");
+    Ok(condition != T::from_i32(0))
+}
+
+/// Replaces the whole stack with a value computed from all of it, or
+/// leaves it alone on an error. An empty stack has no value to give.
+fn reduce<T: Number>(s: &mut CalcStack<T>, op: &str, f: fn(&[T]) -> result::Result<T, RpnCalculatorError>) -> CalcResult {
+    require(s, op, 1)?;
+    let value = f(s)?;
+    s.clear();
+    s.push(value);
+    Ok(())
+}
+
+/// `n` as a number, counting up by ones so it works for every type
+fn count<T: Number>(n: usize) -> result::Result<T, RpnCalculatorError> {
+    let mut count = T::from_i32(0);
+    for _ in 0..n {
+        count = count.try_add(T::from_i32(1))?;
+    }
+    Ok(count)
+}
+
+fn sum<T: Number>(values: &[T]) -> result::Result<T, RpnCalculatorError> {
+    require(values, "sum", 1)?;
+    let (first, rest) = values.split_first().expect("Already checked there are values");
+    // Starting from the first value rather than 0 keeps its units
+    rest.iter().cloned().try_fold(first.clone(), T::try_add)
+}
+
+fn mean<T: Number>(values: &[T]) -> result::Result<T, RpnCalculatorError> {
+    sum(values)?.try_div(count(values.len())?)
 }
 
-/// The result used fo all calculator operations
-pub type CalcResult = result::Result<(), RpnCalculatorError>;
-/// The stack used by the calculator
-pub type CalcStack = Vec<f64>;
-/// The function each operator uses for mutating the calculator stack
-pub type OperatorFn = fn(&mut CalcStack) -> CalcResult;
-/// A mapping of string symbols to operator functions
-pub type OperatorsMap = collections::BTreeMap<&'static str, OperatorFn>;
+/// The sample standard deviation, dividing by one less than the number
+/// of values, so it needs at least two
+fn stddev<T: Number>(values: &[T]) -> result::Result<T, RpnCalculatorError> {
+    require(values, "stddev", 2)?;
+    let mean = mean(values)?;
+    let squares = values
+        .iter()
+        .map(|x| {
+            let d = x.clone().try_sub(mean.clone())?;
+            d.clone().try_mul(d)
+        })
+        .collect::<result::Result<Vec<T>, RpnCalculatorError>>()?;
+    let variance = sum(&squares)?.try_div(count(values.len() - 1)?)?;
+    function(variance, f64::sqrt, |x| x >= 0.0)
+}
 
-/// Defines new operators and putting them in an operators map.
-///
-/// There are two forms of this macro:
-///
-/// * Define an operator that takes *n* operands and returns a value to be pushed into the stack
-///
-/// ```
-/// #[macro_use]
-/// extern crate pprust;
-/// # fn main() {
-/// use pprust::rpncalculator::*;
-///
-/// let mut ops = default_operators();
-/// new_operator!(ops, "+", [x, y], { x + y });
-/// let mut stack : Vec<f64> = Vec::new();
-/// let f = ops.get("+").unwrap();
-/// stack.push(1.0);
-/// stack.push(2.0);
-/// f(&mut stack);
-/// assert_eq!(3.0, *stack.last().unwrap());
-/// # }
-/// ```
-///
-/// * Define an operator that operates directly on the stack
-///
-/// ```
-/// #[macro_use]
-/// extern crate pprust;
-/// # fn main() {
-/// use pprust::rpncalculator::*;
-/// let mut ops = default_operators();
-/// let mut stack : Vec<f64> = Vec::new();
-/// stack.push(1.0);
-/// new_operator!(ops, "p", s, { s.pop().ok_or(RpnCalculatorError::NotEnoughOperands)?; Ok(()) });
-/// let f = ops.get("p").unwrap();
-/// let res = f(&mut stack);
-/// assert!(res.is_ok());
-/// assert_eq!(0, stack.len());
-/// # }
-/// ```
-#[macro_export]
-macro_rules! new_operator {
-    ($ops:expr, $name:expr, [ $( $var:ident ),* ], $code:block) => {{
-        fn opfn(s: &mut CalcStack) -> CalcResult {
-            let i = s.len();
-            $(
-                let $var: f64;
-                if i == 0 {
-                    return Err(RpnCalculatorError::NotEnoughOperands);
-                } else {
-                    $var = s[i - 1];
+/// The value no other value beats, the first of them on ties
+fn extreme<T: Number>(values: &[T], beats: fn(&T, &T) -> bool) -> T {
+    let mut best = &values[0];
+    for x in &values[1..] {
+        if beats(x, best) {
+            best = x;
+        }
+    }
+    best.clone()
+}
+
+/// Replaces the whole stack with its median, the mean of the two middle
+/// values when the stack has an even number of values.
+fn median<T: Number>(s: &mut CalcStack<T>) -> CalcResult {
+    require(s, "median", 1)?;
+    let n = s.len();
+    let upper = select::quickselect_by(s, n / 2, |a, b| a < b).expect("Already checked the stack is not empty").clone();
+    let median = if n % 2 == 1 {
+        upper
+    } else {
+        // After selection everything before n / 2 is no greater than upper
+        let lower = s[..n / 2]
+            .iter()
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .expect("An even stack has values below the middle")
+            .clone();
+        let half = upper.try_sub(lower.clone())?.try_div(T::from_i32(2))?;
+        lower.try_add(half)?
+    };
+    s.clear();
+    s.push(median);
+    Ok(())
+}
+
+impl Number for Complex {
+    fn from_i32(n: i32) -> Complex {
+        Complex::from(n as f64)
+    }
+
+    /// Only real numbers; the scientific functions work on the real line
+    fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+        if !self.is_real() {
+            return Err(RpnCalculatorError::NotSupported);
+        }
+        Ok(self.re)
+    }
+
+    fn from_float(x: f64) -> result::Result<Complex, RpnCalculatorError> {
+        Ok(Complex::from(x))
+    }
+
+    fn try_div(self, other: Complex) -> result::Result<Complex, RpnCalculatorError> {
+        if other == Complex::default() {
+            return Err(RpnCalculatorError::DivisionByZero);
+        }
+        Ok(self / other)
+    }
+
+    /// The default operators plus `re`, `im`, `abs` and `arg`, which
+    /// give real results, and `sqrt` giving the principal root of any
+    /// number, so `-4 sqrt` is `2i`
+    fn operators() -> OperatorsMap<Complex> {
+        let mut ops: OperatorsMap<Complex> = default_operators();
+        new_operator!(ops, "sqrt", "The principal square root", "-4 sqrt", [z], {
+            let r = z.abs();
+            let im = ((r - z.re) / 2.0).sqrt();
+            Complex::new(((r + z.re) / 2.0).sqrt(), if z.im < 0.0 { -im } else { im })
+        });
+        new_operator!(ops, "re", "The real part", "3+4i re", [z], { Complex::from(z.re) });
+        new_operator!(ops, "im", "The imaginary part", "3+4i im", [z], { Complex::from(z.im) });
+        new_operator!(ops, "abs", "The modulus", "3+4i abs", [z], { Complex::from(z.abs()) });
+        new_operator!(ops, "arg", "The argument, in radians", "-1 arg", [z], { Complex::from(z.arg()) });
+        ops
+    }
+}
+
+/// A number for the big integer mode: integers stay exact, of any size,
+/// until an operator can only give a floating point result
+#[derive(Debug, Clone, PartialEq)]
+pub enum BigNumber {
+    Int(BigInt),
+    /// An inexact result, shown with a decimal point or an exponent
+    Float(f64),
+}
+
+impl BigNumber {
+    pub fn to_f64(&self) -> f64 {
+        match *self {
+            BigNumber::Int(ref n) => n.to_f64(),
+            BigNumber::Float(x) => x,
+        }
+    }
+
+    /// Integer powers with a non-negative exponent are exact, and fail
+    /// with `Overflow` when the result would take more than about
+    /// `MAX_POWER_BITS` bits
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::{BigNumber, RpnCalculatorError};
+    ///
+    /// let two = BigNumber::Int(2.into());
+    /// assert_eq!("1024", two.clone().pow(BigNumber::Int(10.into())).unwrap().to_string());
+    /// assert_eq!("0.25", two.clone().pow(BigNumber::Int((-2).into())).unwrap().to_string());
+    /// assert_eq!(Err(RpnCalculatorError::Overflow), two.pow(BigNumber::Int(4_000_000_000u64.into())));
+    /// ```
+    pub fn pow(self, exponent: BigNumber) -> result::Result<BigNumber, RpnCalculatorError> {
+        match (self, exponent) {
+            (BigNumber::Int(base), BigNumber::Int(exponent)) if !exponent.is_negative() => {
+                let exponent = exponent.to_u64().ok_or(RpnCalculatorError::Overflow)?;
+                // 0, 1 and -1 stay small whatever the exponent
+                let bits = base.bits().saturating_sub(1).saturating_mul(exponent);
+                if exponent > u32::MAX as u64 || bits > MAX_POWER_BITS {
+                    return Err(RpnCalculatorError::Overflow);
                 }
-                let i = i - 1;
-            )*;
-            let n = s.len() - i;
-            if n > 0 {
-                for _ in 0..n {
-                    s.pop();
+                Ok(BigNumber::Int(base.pow(exponent as u32)))
+            }
+            (base, exponent) => Ok(BigNumber::Float(base.to_f64().powf(exponent.to_f64()))),
+        }
+    }
+
+    pub fn sqrt(self) -> BigNumber {
+        BigNumber::Float(self.to_f64().sqrt())
+    }
+}
+
+impl Add for BigNumber {
+    type Output = BigNumber;
+
+    fn add(self, other: BigNumber) -> BigNumber {
+        match (self, other) {
+            (BigNumber::Int(a), BigNumber::Int(b)) => BigNumber::Int(&a + &b),
+            (a, b) => BigNumber::Float(a.to_f64() + b.to_f64()),
+        }
+    }
+}
+
+impl Sub for BigNumber {
+    type Output = BigNumber;
+
+    fn sub(self, other: BigNumber) -> BigNumber {
+        match (self, other) {
+            (BigNumber::Int(a), BigNumber::Int(b)) => BigNumber::Int(&a - &b),
+            (a, b) => BigNumber::Float(a.to_f64() - b.to_f64()),
+        }
+    }
+}
+
+impl Mul for BigNumber {
+    type Output = BigNumber;
+
+    fn mul(self, other: BigNumber) -> BigNumber {
+        match (self, other) {
+            (BigNumber::Int(a), BigNumber::Int(b)) => BigNumber::Int(&a * &b),
+            (a, b) => BigNumber::Float(a.to_f64() * b.to_f64()),
+        }
+    }
+}
+
+impl Div for BigNumber {
+    type Output = BigNumber;
+
+    /// Exact when the division leaves no remainder
+    fn div(self, other: BigNumber) -> BigNumber {
+        if let (BigNumber::Int(a), BigNumber::Int(b)) = (&self, &other) {
+            if !b.is_zero() {
+                let (q, r) = a.div_rem(b);
+                if r.is_zero() {
+                    return BigNumber::Int(q);
                 }
             }
-            let result = { $code };
-            s.push(result);
-            Ok(())
         }
-        $ops.insert($name, opfn);
-    }};
-    ($ops:expr, $name:expr, $stackvar:ident, $code:block) => {{
-        fn opfn(s: &mut CalcStack) -> CalcResult {
-            let $stackvar = s;
-            $code
+        BigNumber::Float(self.to_f64() / other.to_f64())
+    }
+}
+
+impl PartialOrd for BigNumber {
+    fn partial_cmp(&self, other: &BigNumber) -> Option<Ordering> {
+        match (self, other) {
+            (BigNumber::Int(a), BigNumber::Int(b)) => Some(a.cmp(b)),
+            (a, b) => a.to_f64().partial_cmp(&b.to_f64()),
         }
-        $ops.insert($name, opfn);
-    }};
+    }
 }
 
-/// This function builds an operators map with all the default
-/// operators the calculator supports. This map can be used to add
-/// more operators before creating a new calculator.
-/// See
-///
-/// # Example
-/// ```
-/// use pprust::rpncalculator::{default_operators, CalcResult};
-///
-/// let mut ops = default_operators();
-/// fn op(s: &mut Vec<f64>) -> CalcResult {
-///     s.push(2.0);
-///     Ok(())
-/// }
-/// ops.insert("?", op);
-/// ```
-pub fn default_operators() -> OperatorsMap {
-    let mut ops: OperatorsMap = collections::BTreeMap::new();
-    new_operator!(ops, "+", [y, x], { x + y });
-    new_operator!(ops, "-", [y, x], { x - y });
-    new_operator!(ops, "*", [y, x], { x * y });
-    new_operator!(ops, "/", [y, x], { x / y });
-    ops
+impl fmt::Display for BigNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BigNumber::Int(ref n) => write!(f, "{}", n),
+            // With a point, so that a rounded result doesn't look exact
+            BigNumber::Float(x) => write!(f, "{:?}", x),
+        }
+    }
+}
+
+impl FromStr for BigNumber {
+    type Err = num::ParseFloatError;
+
+    fn from_str(text: &str) -> result::Result<BigNumber, num::ParseFloatError> {
+        match text.parse() {
+            Ok(n) => Ok(BigNumber::Int(n)),
+            Err(_) => text.parse().map(BigNumber::Float),
+        }
+    }
+}
+
+impl Number for BigNumber {
+    fn from_i32(n: i32) -> BigNumber {
+        BigNumber::Int(BigInt::from(n))
+    }
+
+    fn try_div(self, other: BigNumber) -> result::Result<BigNumber, RpnCalculatorError> {
+        match (&self, &other) {
+            (BigNumber::Int(_), BigNumber::Int(b)) if b.is_zero() => Err(RpnCalculatorError::DivisionByZero),
+            _ => Ok(self / other),
+        }
+    }
+
+    fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+        Ok(self.to_f64())
+    }
+
+    fn from_float(x: f64) -> result::Result<BigNumber, RpnCalculatorError> {
+        Ok(BigNumber::Float(x))
+    }
+
+    fn to_integer(&self) -> result::Result<i64, RpnCalculatorError> {
+        match *self {
+            BigNumber::Int(ref n) => n.to_i64().ok_or(RpnCalculatorError::Overflow),
+            BigNumber::Float(x) => x.to_integer(),
+        }
+    }
+
+    fn from_integer(n: i64) -> result::Result<BigNumber, RpnCalculatorError> {
+        Ok(BigNumber::Int(BigInt::from(n)))
+    }
+
+    /// The default operators with `pow` exact for integers
+    fn operators() -> OperatorsMap<BigNumber> {
+        let mut ops: OperatorsMap<BigNumber> = default_operators();
+        new_operator!(ops, "pow", "Raises the value below to the power of the top one, exactly for integers", "2 100 pow", [y, x], { x.pow(y)? });
+        ops
+    }
+}
+
+impl Number for Decimal {
+    fn from_i32(n: i32) -> Decimal {
+        Decimal::from(n as i64)
+    }
+
+    fn try_div(self, other: Decimal) -> result::Result<Decimal, RpnCalculatorError> {
+        self.checked_div(&other, decimal::precision()).ok_or(RpnCalculatorError::DivisionByZero)
+    }
+
+    fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+        Ok(self.to_f64())
+    }
+
+    fn from_float(x: f64) -> result::Result<Decimal, RpnCalculatorError> {
+        x.to_string().parse().map_err(|_| RpnCalculatorError::Overflow)
+    }
+}
+
+impl Number for Interval {
+    fn from_i32(n: i32) -> Interval {
+        Interval::point(n as f64)
+    }
+
+    fn try_div(self, other: Interval) -> result::Result<Interval, RpnCalculatorError> {
+        self.checked_div(&other).ok_or(RpnCalculatorError::DivisionByZero)
+    }
+
+    fn try_neg(self) -> result::Result<Interval, RpnCalculatorError> {
+        Ok(-self)
+    }
+
+    /// The absolute values of the members, from 0 if it has both signs
+    fn try_abs(self) -> result::Result<Interval, RpnCalculatorError> {
+        Ok(if self.lo() >= 0.0 {
+            self
+        } else if self.hi() <= 0.0 {
+            -self
+        } else {
+            Interval::new(0.0, self.hi().max(-self.lo()))
+        })
+    }
+
+    /// The default operators plus `pm`, widening a value by an
+    /// uncertainty as in `9.81 0.02 pm`, and `mid`, `radius` and `width`
+    fn operators() -> OperatorsMap<Interval> {
+        let mut ops: OperatorsMap<Interval> = default_operators();
+        new_operator!(ops, "pm", "Widens the value below by the top one as an uncertainty", "9.81 0.02 pm", [r, x], {
+            let m = r.lo().abs().max(r.hi().abs());
+            x + Interval::new(-m, m)
+        });
+        new_operator!(ops, "mid", "The midpoint", "9.81 0.02 pm mid", [x], { Interval::point(x.mid()) });
+        new_operator!(ops, "radius", "The radius", "9.81 0.02 pm radius", [x], { Interval::point(x.radius()) });
+        new_operator!(ops, "width", "The width", "9.81 0.02 pm width", [x], { Interval::point(x.width()) });
+        ops
+    }
+}
+
+impl Number for Quantity {
+    fn from_i32(n: i32) -> Quantity {
+        Quantity::number(n as f64)
+    }
+
+    fn try_add(self, other: Quantity) -> result::Result<Quantity, RpnCalculatorError> {
+        self.checked_add(&other).ok_or(RpnCalculatorError::UnitMismatch)
+    }
+
+    fn try_sub(self, other: Quantity) -> result::Result<Quantity, RpnCalculatorError> {
+        self.checked_sub(&other).ok_or(RpnCalculatorError::UnitMismatch)
+    }
+
+    fn try_neg(self) -> result::Result<Quantity, RpnCalculatorError> {
+        Ok(Quantity { value: -self.value, dimension: self.dimension })
+    }
+
+    fn try_abs(self) -> result::Result<Quantity, RpnCalculatorError> {
+        Ok(Quantity { value: self.value.abs(), dimension: self.dimension })
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0.0
+    }
+
+    /// Only dimensionless quantities, as `sin 3 m` means nothing
+    fn to_float(&self) -> result::Result<f64, RpnCalculatorError> {
+        if !self.dimension.is_none() {
+            return Err(RpnCalculatorError::UnitMismatch);
+        }
+        Ok(self.value)
+    }
+
+    fn from_float(x: f64) -> result::Result<Quantity, RpnCalculatorError> {
+        Ok(Quantity::number(x))
+    }
+
+    /// Compound units like `m/s^2` as well as single ones
+    fn unit(name: &str) -> Option<Quantity> {
+        Quantity::parse_unit(name).ok()
+    }
+
+    /// Quantities show a space between the number and the unit, as in
+    /// `1.5 m/s`, which reading them doesn't take
+    fn from_display(text: &str) -> Option<Quantity> {
+        text.replacen(' ', "", 1).parse().ok()
+    }
+
+    /// The default operators plus `sqrt`, for quantities whose dimension
+    /// has a square root, and `value`, dropping the unit
+    fn operators() -> OperatorsMap<Quantity> {
+        let mut ops: OperatorsMap<Quantity> = default_operators();
+        new_operator!(ops, "sqrt", "The square root, of the units too", "9 m^2 sqrt", [x], {
+            if x.value < 0.0 {
+                return Err(RpnCalculatorError::DomainError);
+            }
+            x.sqrt().ok_or(RpnCalculatorError::UnitMismatch)?
+        });
+        new_operator!(ops, "value", "The number without the unit", "3 km value", [x], { Quantity::number(x.value) });
+        ops
+    }
+}
+
+/// Takes the results of the lines `evaluate_stream` reads
+pub trait LineSink<T: Number> {
+    /// Evaluates a line, for front ends to handle commands of their own or
+    /// another notation; by default the calculator evaluates it
+    fn evaluate(&mut self, calc: &mut RpnCalculator<T>, input: &str) -> CalcResult {
+        calc.evaluate(input)
+    }
+
+    /// Takes the result of a line, numbered from 1, returning whether to
+    /// go on with the next one
+    fn line(&mut self, calc: &mut RpnCalculator<T>, number: usize, input: &str, result: CalcResult) -> bool;
+}
+
+impl<T: Number, F> LineSink<T> for F
+    where F: FnMut(&mut RpnCalculator<T>, usize, &str, CalcResult) -> bool
+{
+    fn line(&mut self, calc: &mut RpnCalculator<T>, number: usize, input: &str, result: CalcResult) -> bool {
+        self(calc, number, input, result)
+    }
 }
 
 /// The calculator
-pub struct RpnCalculator {
-    stack: CalcStack,
-    operators: OperatorsMap,
+pub struct RpnCalculator<T: Number = f64> {
+    state: CalcState<T>,
+    operators: OperatorsMap<T>,
+    /// How many user-defined words are running
+    depth: usize,
+    /// The lines evaluated, with recalled lines filled in
+    lines: Vec<String>,
+    /// What `help` printed, until it is taken
+    output: Vec<String>,
+}
+
+impl<T: Number> Default for RpnCalculator<T> {
+    fn default() -> RpnCalculator<T> {
+        RpnCalculator::new()
+    }
 }
 
-impl RpnCalculator {
-    /// Creates a new calculator with default operators
-    pub fn new() -> RpnCalculator {
-        RpnCalculator { stack: Vec::new(), operators: default_operators() }
+impl<T: Number> RpnCalculator<T> {
+    /// Creates a new calculator with the operators of its number type
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::complex::Complex;
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+    /// calc.evaluate("7 2 /").unwrap();
+    /// assert_eq!(Some(&3), calc.top());
+    ///
+    /// let mut calc: RpnCalculator<Complex> = RpnCalculator::new();
+    /// calc.evaluate("3+2i 1-i *").unwrap();
+    /// assert_eq!("5-i", calc.top().unwrap().to_string());
+    /// ```
+    pub fn new() -> RpnCalculator<T> {
+        RpnCalculator::new_with_operators(T::operators())
     }
 
     /// Creates a new calculator with the operators passed
-    pub fn new_with_operators(operators: OperatorsMap) -> RpnCalculator {
-        RpnCalculator { stack: Vec::new(), operators: operators }
+    pub fn new_with_operators(operators: OperatorsMap<T>) -> RpnCalculator<T> {
+        RpnCalculator { state: CalcState::new(), operators, depth: 0, lines: Vec::new(), output: Vec::new() }
     }
 
     /// Returns the top of the calculator's stack
-    pub fn top(&self) -> Option<&f64> {
-        self.stack.last()
+    pub fn top(&self) -> Option<&T> {
+        self.state.last()
+    }
+
+    /// How many values are on the stack
+    pub fn stack_len(&self) -> usize {
+        self.state.stack.len()
+    }
+
+    /// The base `hex`, `bin`, `oct` and `dec` set for showing results
+    pub fn base(&self) -> Base {
+        self.state.base
+    }
+
+    /// The angle unit `deg` and `rad` set for the trigonometric functions
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::{Angle, RpnCalculator};
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate("deg 90 sin").unwrap();
+    /// assert_eq!((Angle::Deg, Some(&1.0)), (calc.angle(), calc.top()));
+    /// ```
+    pub fn angle(&self) -> Angle {
+        self.state.angle
+    }
+
+    /// How results are shown in decimal
+    pub fn format(&self) -> Format {
+        self.state.format
+    }
+
+    /// The top of the stack shown in the current base and format, as the
+    /// calculator binary prints it
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+    /// calc.evaluate("0xf0 0b1100 xor hex").unwrap();
+    /// assert_eq!(Some("0xfc".to_string()), calc.format_top());
+    /// calc.evaluate("dec fix 2").unwrap();
+    /// assert_eq!(Some("252.00".to_string()), calc.format_top());
+    /// ```
+    pub fn format_top(&self) -> Option<String> {
+        self.top().map(|top| self.state.show(top))
+    }
+
+    /// The registers stored with `sto`, by name
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate("2 sto a 3 * sto b rcl a +").unwrap();
+    /// assert_eq!(Some(&8.0), calc.top());
+    /// let registers: Vec<(&String, &f64)> = calc.registers().iter().collect();
+    /// assert_eq!(vec![(&"a".to_string(), &2.0), (&"b".to_string(), &6.0)], registers);
+    /// ```
+    pub fn registers(&self) -> &collections::BTreeMap<String, T> {
+        &self.state.registers
+    }
+
+    pub fn clear_registers(&mut self) {
+        self.state.registers.clear();
+    }
+
+    /// The words defined with `: name ... ;`. Words are looked up when
+    /// they run, so they can call words defined later, or themselves,
+    /// and they take precedence over the built-in operators.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate(": avg + 2 / ;").unwrap();
+    /// calc.evaluate("3 8 avg").unwrap();
+    /// assert_eq!(Some(&5.5), calc.top());
+    /// assert_eq!(Some(&vec!["+".to_string(), "2".to_string(), "/".to_string()]), calc.words().get("avg"));
+    /// ```
+    pub fn words(&self) -> &collections::BTreeMap<String, Vec<String>> {
+        &self.state.words
+    }
+
+    /// The operator with a name, if the calculator has one
+    pub fn operator(&self, name: &str) -> Option<&Operator<T>> {
+        self.operators.get(name)
+    }
+
+    /// The operators and the words defined, in order and without repeats,
+    /// as a line editor would complete them
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate(": square dup * ;").unwrap();
+    /// let names: Vec<&str> = calc.names().into_iter().filter(|n| n.starts_with("sq")).collect();
+    /// assert_eq!(vec!["sqrt", "square"], names);
+    /// ```
+    pub fn names(&self) -> Vec<&str> {
+        let operators = self.operators.keys().cloned().chain(Some(HELP));
+        let words = self.state.words.keys().map(String::as_str);
+        operators.chain(words).collect::<collections::BTreeSet<&str>>().into_iter().collect()
+    }
+
+    /// The help `help` prints: with no name, a line for each operator and
+    /// word; with a name, what it does, how many values it takes and its
+    /// example, evaluated in a new calculator with the same operators
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// assert!(calc.help(None).unwrap().contains("swap     Swaps the top two values"));
+    /// let help = calc.help(Some("+")).unwrap();
+    /// assert_eq!("+: Adds the top two values\nTakes 2 values\nExample: 2 3 + gives 5", help);
+    /// calc.evaluate(": sq dup * ;").unwrap();
+    /// assert_eq!(": sq dup * ;", calc.help(Some("sq")).unwrap());
+    /// assert!(calc.help(Some("nope")).is_err());
+    /// ```
+    pub fn help(&self, name: Option<&str>) -> result::Result<String, RpnCalculatorError> {
+        let name = match name {
+            Some(name) => name,
+            None => {
+                let operators = self.operators.iter().map(|(name, operator)| format!("{:<8} {}", name, operator.description));
+                let words = self.state.words.iter().map(|(name, body)| format!("{:<8} : {} {} ;", name, name, body.join(" ")));
+                let help = format!("{:<8} {}", HELP, HELP_DESCRIPTION);
+                return Ok(operators.chain(words).chain(Some(help)).collect::<Vec<String>>().join("\n"));
+            }
+        };
+        if let Some(body) = self.state.words.get(name) {
+            return Ok(format!(": {} {} ;", name, body.join(" ")));
+        }
+        if name == HELP {
+            return Ok(format!("{}: {}", HELP, HELP_DESCRIPTION));
+        }
+        let operator = self.operators.get(name).ok_or_else(|| RpnCalculatorError::UnknownName(name.to_string()))?;
+        let mut lines = vec![format!("{}: {}", name, operator.description)];
+        if let Some(arity) = operator.arity {
+            lines.push(format!("Takes {} value{}", arity, if arity == 1 { "" } else { "s" }));
+        }
+        if !operator.example.is_empty() {
+            let mut calc = RpnCalculator::new_with_operators(self.operators.clone());
+            lines.push(match calc.evaluate(operator.example).map(|()| calc.format_top()) {
+                Ok(Some(top)) => format!("Example: {} gives {}", operator.example, top),
+                _ => format!("Example: {}", operator.example),
+            });
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Takes what `help` printed since the last call, a string for each
+    /// time it ran
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate("1 help dup 2").unwrap();
+    /// assert_eq!(vec![calc.help(Some("dup")).unwrap()], calc.take_output());
+    /// assert_eq!((Some(&2.0), 2), (calc.top(), calc.stack_len()));
+    /// assert!(calc.take_output().is_empty());
+    /// ```
+    pub fn take_output(&mut self) -> Vec<String> {
+        mem::take(&mut self.output)
     }
 
     /// evaluates an input string and mutates the calculator
+    ///
+    /// A line that changes anything can be taken back with `undo`, which
+    /// returns to the state before it, even if it failed halfway, and
+    /// `redo` brings it back until another change is made.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate("3 4 +").unwrap();
+    /// calc.evaluate("clear").unwrap();
+    /// calc.evaluate("undo").unwrap();
+    /// assert_eq!(Some(&7.0), calc.top());
+    /// calc.evaluate("undo").unwrap();
+    /// assert_eq!(None, calc.top());
+    /// calc.evaluate("redo").unwrap();
+    /// assert_eq!(Some(&7.0), calc.top());
+    /// ```
+    ///
+    /// `!!` anywhere in a line stands for the previous line, and `!n` for
+    /// line `n` of `history`, counting from 1.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate("2 3 +").unwrap();
+    /// calc.evaluate("!! *").unwrap();
+    /// assert_eq!(Some(&25.0), calc.top());
+    /// calc.evaluate("!1 -").unwrap();
+    /// assert_eq!(Some(&20.0), calc.top());
+    /// assert_eq!(&["2 3 +", "2 3 + *", "2 3 + -"], calc.history());
+    /// ```
     pub fn evaluate(&mut self, input: &str) -> CalcResult {
-        let mut tokens = input.split_whitespace();
-        loop {
-            let next = tokens.next();
-            match next {
-                None => break,
-                Some(token) => self.parse_token(token)?,
+        let tokens = self.recall(input)?;
+        if !tokens.is_empty() {
+            self.lines.push(tokens.iter().map(|(token, _)| token.as_str()).collect::<Vec<&str>>().join(" "));
+        }
+        let before = self.state.snapshot();
+        let moves = self.state.history.moves;
+        self.state.pending = tokens.iter().enumerate().map(|(i, (token, _))| (token.clone(), i)).collect();
+        self.state.repeats = 0;
+        let result = decimal::with_precision(self.state.precision, || self.run_pending()).map_err(|error| match error {
+            RpnCalculatorError::Quit => error,
+            error => {
+                let (token, offset) = tokens[self.state.source].clone();
+                RpnCalculatorError::At { token, offset, error: Box::new(error) }
+            }
+        });
+        self.state.pending.clear();
+        if self.state.history.moves == moves && self.state.snapshot() != before {
+            self.state.record(before);
+        }
+        result
+    }
+
+    /// The lines evaluated so far, oldest first, with the lines `!!` and
+    /// `!n` recalled written out. Blank lines are left out, lines that
+    /// failed are kept.
+    pub fn history(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// The tokens of a line, with their byte offsets, and with `!!` and
+    /// `!n` replaced by the tokens of the lines they recall, all at the
+    /// offset of the `!`
+    fn recall(&self, input: &str) -> result::Result<Vec<(String, usize)>, RpnCalculatorError> {
+        let mut tokens = Vec::new();
+        for token in input.split_whitespace() {
+            let offset = token.as_ptr() as usize - input.as_ptr() as usize;
+            let line = if token == "!!" {
+                self.lines.last()
+            } else if let Some(n) = token.strip_prefix('!').and_then(|n| n.parse::<usize>().ok()) {
+                n.checked_sub(1).and_then(|i| self.lines.get(i))
+            } else {
+                tokens.push((token.to_string(), offset));
+                continue;
+            };
+            let line = line.ok_or_else(|| RpnCalculatorError::At {
+                token: token.to_string(),
+                offset,
+                error: Box::new(RpnCalculatorError::NotInHistory),
+            })?;
+            tokens.extend(line.split_whitespace().map(|recalled| (recalled.to_string(), offset)));
+        }
+        Ok(tokens)
+    }
+
+    /// Writes the stack, registers, words, quotations and settings as
+    /// JSON, with the values as they are shown in `std` format
+    pub fn save<W: Write>(&self, writer: W) -> CalcResult {
+        let saved = self.state.snapshot().convert(|value| Ok(value.to_string()))?;
+        serde_json::to_writer_pretty(writer, &saved).map_err(|_| RpnCalculatorError::IOError)
+    }
+
+    /// Replaces the stack, registers, words, quotations and settings with
+    /// what `save` wrote. Nothing changes if any of it can't be read, and
+    /// `undo` goes back to the state before.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::{Format, RpnCalculator};
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate("1 3 / sto third fix 2 : sq dup * ; 4 sq").unwrap();
+    /// let mut saved = Vec::new();
+    /// calc.save(&mut saved).unwrap();
+    ///
+    /// let mut restored: RpnCalculator = RpnCalculator::new();
+    /// restored.load(&saved[..]).unwrap();
+    /// assert_eq!(Some(&16.0), restored.top());
+    /// assert_eq!((Some(&(1.0 / 3.0)), Format::Fix(2)), (restored.registers().get("third"), restored.format()));
+    /// restored.evaluate("sq").unwrap();
+    /// assert_eq!(Some(&256.0), restored.top());
+    /// ```
+    pub fn load<R: Read>(&mut self, reader: R) -> CalcResult {
+        let saved: Snapshot<String> = serde_json::from_reader(reader).map_err(|e| {
+            if e.is_io() {
+                RpnCalculatorError::IOError
+            } else {
+                RpnCalculatorError::ParsingError
+            }
+        })?;
+        let loaded = saved.convert(|text| T::from_display(&text).ok_or(RpnCalculatorError::ParsingError))?;
+        let before = self.state.snapshot();
+        self.state.restore(loaded);
+        self.state.record(before);
+        Ok(())
+    }
+
+    /// Evaluates a script a line at a time, as if typed in, skipping
+    /// lines starting with `#`. Stops at the first line that fails, which
+    /// has changed the calculator as far as it got.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// calc.evaluate_script("# Squares\n: sq dup * ;\n\n3 sq").unwrap();
+    /// assert_eq!(Some(&9.0), calc.top());
+    /// let error = calc.evaluate_script("1 +\n+").unwrap_err();
+    /// assert_eq!("line 2: `+` at byte 0: + takes 2 operands, got 1", error.to_string());
+    /// ```
+    pub fn evaluate_script(&mut self, script: &str) -> result::Result<(), LineError> {
+        let mut failed = None;
+        self.evaluate_stream(script.as_bytes(), &mut |_: &mut RpnCalculator<T>, line, _: &str, result: CalcResult| {
+            failed = result.err().map(|error| LineError { line, error });
+            failed.is_none()
+        }).map_err(|_| LineError { line: 0, error: RpnCalculatorError::IOError })?;
+        failed.map_or(Ok(()), Err)
+    }
+
+    /// Evaluates the lines of a reader as they come, skipping blank lines
+    /// and lines starting with `#`, and hands the result of each to a
+    /// sink, until the sink says to stop or the reader ends. Fails only
+    /// when the reader does.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::rpncalculator::{CalcResult, RpnCalculator};
+    ///
+    /// let mut calc: RpnCalculator = RpnCalculator::new();
+    /// let mut tops = Vec::new();
+    /// let input = "1 2 +\n\n# nothing\n+\n10 *";
+    /// calc.evaluate_stream(input.as_bytes(), &mut |calc: &mut RpnCalculator, line, _: &str, result: CalcResult| {
+    ///     tops.push((line, result.map(|()| calc.top().cloned()).map_err(|e| e.to_string())));
+    ///     true
+    /// }).unwrap();
+    /// let error = "`+` at byte 0: + takes 2 operands, got 1".to_string();
+    /// assert_eq!(vec![(1, Ok(Some(3.0))), (4, Err(error)), (5, Ok(Some(30.0)))], tops);
+    /// ```
+    pub fn evaluate_stream<R: BufRead, S: LineSink<T>>(&mut self, reader: R, sink: &mut S) -> io::Result<()> {
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let result = sink.evaluate(self, &line);
+            if !sink.line(self, number + 1, &line, result) {
+                break;
             }
         }
         Ok(())
     }
 
+    /// How many lines `undo` can take back
+    pub fn history_len(&self) -> usize {
+        self.state.history.undo.len()
+    }
+
+    /// Keeps at most `limit` states for `undo`, forgetting the oldest
+    pub fn set_undo_limit(&mut self, limit: usize) {
+        let history = &mut self.state.history;
+        history.limit = limit;
+        while history.undo.len() > limit {
+            history.undo.pop_front();
+        }
+    }
+
+    fn run_pending(&mut self) -> CalcResult {
+        while let Some((token, source)) = self.state.pending.pop_front() {
+            self.state.source = source;
+            self.parse_token(&token)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the tokens of a word as if they were the input, leaving the
+    /// stack as it was if any of them fails
+    fn call_word(&mut self, body: Vec<String>) -> CalcResult {
+        if self.depth == MAX_WORD_DEPTH {
+            return Err(RpnCalculatorError::RecursionTooDeep);
+        }
+        let saved = self.state.stack.clone();
+        let source = self.state.source;
+        let outer = mem::replace(&mut self.state.pending, body.into_iter().map(|token| (token, source)).collect());
+        self.depth += 1;
+        let result = self.run_pending();
+        self.depth -= 1;
+        self.state.pending = outer;
+        if result.is_err() {
+            self.state.stack = saved;
+        }
+        result
+    }
+
     fn parse_token(&mut self, token: &str) -> CalcResult {
-        if self.operators.contains_key(token) {
+        if let Some(body) = self.state.words.get(token).cloned() {
+            self.call_word(body)
+        } else if let Some(left) = token.strip_prefix(TIMES_LEFT) {
+            let left = left.parse().expect("Only times leaves tokens like this");
+            let body = self.state.quotations.pop().expect("The body was quoted just before");
+            run_times(&mut self.state, body, left)
+        } else if token == HELP {
+            let name = self.state.next_word();
+            let help = self.help(name.as_deref())?;
+            self.output.push(help);
+            Ok(())
+        } else if self.operators.contains_key(token) {
             let operator = self.operators.get(token).expect("Already checked if operators contains token");
-            operator(&mut self.stack)
+            (operator.run)(&mut self.state)
         } else {
             self.parse_and_push(token)
         }
     }
 
     fn parse_and_push(&mut self, token: &str) -> CalcResult {
-        let value: f64 = token.parse()?;
-        self.stack.push(value);
+        match parse_literal(token) {
+            Ok(value) => {
+                self.state.push(value);
+                return Ok(());
+            }
+            Err(RpnCalculatorError::ParsingError) => (),
+            Err(error) => return Err(error),
+        }
+        let unit = T::unit(token).ok_or(RpnCalculatorError::ParsingError)?;
+        require(&self.state, token, 1)?;
+        let value = self.state.pop().expect("Already checked the stack is not empty");
+        self.state.push(value.try_mul(unit)?);
         Ok(())
     }
 }
 
+impl RpnCalculator<Decimal> {
+    /// Sets how many digits after the decimal point quotients keep. Each
+    /// calculator has its own precision, which `save` keeps.
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::decimal::Decimal;
+    /// use pprust::rpncalculator::RpnCalculator;
+    ///
+    /// let mut calc: RpnCalculator<Decimal> = RpnCalculator::new();
+    /// calc.set_precision(4);
+    /// calc.evaluate("2 3 /").unwrap();
+    /// assert_eq!("0.6667", calc.top().unwrap().to_string());
+    /// ```
+    pub fn set_precision(&mut self, digits: u32) {
+        self.state.precision = digits;
+    }
+
+    /// How many digits after the decimal point quotients keep
+    pub fn precision(&self) -> u32 {
+        self.state.precision
+    }
+}
+
 impl From<num::ParseFloatError> for RpnCalculatorError {
     fn from(_: num::ParseFloatError) -> RpnCalculatorError {
         RpnCalculatorError::ParsingError
@@ -209,6 +2552,11 @@ mod tests {
     use super::*;
     use std::collections;
 
+    /// The error of a result without the token it happened at
+    fn kind(result: CalcResult) -> CalcResult {
+        result.map_err(|e| e.kind().clone())
+    }
+
     fn make_calculator() -> RpnCalculator {
         RpnCalculator::new()
     }
@@ -258,20 +2606,20 @@ mod tests {
         let mut calc = make_calculator();
         let result = calc.evaluate("+");
         assert!(result.is_err(), "Should return error because '+' expects two operands");
-        match result {
-            Err(RpnCalculatorError::NotEnoughOperands) => (),
-            _ => assert!(false, "Should return NotEnoughOperands error"),
+        match kind(result) {
+            Err(RpnCalculatorError::NotEnoughOperands { .. }) => (),
+            _ => panic!("Should return NotEnoughOperands error"),
         }
     }
 
     #[test]
     fn should_use_operators_passed_at_construction_time() {
         let mut operators: OperatorsMap = collections::BTreeMap::new();
-        fn test_op(s: &mut CalcStack) -> CalcResult {
+        fn test_op(s: &mut CalcState) -> CalcResult {
             s.push(10.0);
             Ok(())
         }
-        operators.insert("?", test_op);
+        operators.insert("?", Operator::new(test_op));
         let mut calc = make_calculator_with_operators(operators);
         let result = calc.evaluate("?");
         assert!(result.is_ok(), "Should return ok as input is valid");
@@ -302,9 +2650,9 @@ mod tests {
         calc.evaluate("1.0").expect("Should push to the stack");
         let result = calc.evaluate("+");
         assert!(result.is_err(), "Should return error because '+' expects two operands");
-        match result {
-            Err(RpnCalculatorError::NotEnoughOperands) => (),
-            _ => assert!(false, "Should return NotEnoughOperands error"),
+        match kind(result) {
+            Err(RpnCalculatorError::NotEnoughOperands { .. }) => (),
+            _ => panic!("Should return NotEnoughOperands error"),
         }
         assert_eq!(1.0, *calc.top().expect("Stack should not be popped since there was not enough operands"),
                    "Stack should not be popped since there was not enough operands");
@@ -335,8 +2683,1086 @@ mod tests {
         check_evaluation("6 2 *", 12.0);
     }
 
+    #[test]
+    fn should_calculate_median_of_odd_stack() {
+        check_evaluation("5 1 9 3 7 median", 5.0);
+    }
+
+    #[test]
+    fn should_calculate_median_of_even_stack() {
+        let mut calc = make_calculator();
+        calc.evaluate("4 1 3 2 median").unwrap();
+        assert_eq!(2.5, *calc.top().unwrap());
+        assert_eq!(1, calc.state.stack.len(), "median should consume the whole stack");
+    }
+
+    #[test]
+    fn should_return_error_for_median_of_empty_stack() {
+        let mut calc = make_calculator();
+        match kind(calc.evaluate("median")) {
+            Err(RpnCalculatorError::NotEnoughOperands { .. }) => (),
+            _ => panic!("Should return NotEnoughOperands error"),
+        }
+    }
+
+    #[test]
+    fn should_summarize_the_whole_stack() {
+        let check = |input: &str, expected: f64| {
+            let mut calc = make_calculator();
+            calc.evaluate(input).unwrap();
+            assert_eq!(vec![expected], calc.state.stack, "{}", input);
+        };
+        check("2 4 4 4 5 5 7 9 sum", 40.0);
+        check("2 4 4 4 5 5 7 9 prod", 201600.0);
+        check("2 4 4 4 5 5 7 9 mean", 5.0);
+        check("2 4 4 4 5 5 7 9 min", 2.0);
+        check("2 4 4 4 5 5 7 9 max", 9.0);
+        check("2 4 4 4 5 5 7 9 count", 8.0);
+        check("1 3 stddev", 2f64.sqrt());
+        check("-3 sum", -3.0);
+        check("count", 0.0);
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        calc.evaluate("1 2 4 mean").unwrap();
+        assert_eq!(vec![2], calc.state.stack, "Integer means truncate");
+        let mut calc: RpnCalculator<Quantity> = RpnCalculator::new();
+        calc.evaluate("3 m 50 cm 2 km max").unwrap();
+        assert_eq!("2000 m", calc.top().unwrap().to_string());
+        calc.evaluate("1 km sum 2 / 1 s /").unwrap();
+        assert_eq!("1500 m/s", calc.top().unwrap().to_string());
+    }
+
+    #[test]
+    fn should_keep_the_stack_when_summaries_fail() {
+        let mut calc = make_calculator();
+        for op in &["sum", "prod", "mean", "median", "stddev", "min", "max"] {
+            match kind(calc.evaluate(op)) {
+                Err(RpnCalculatorError::NotEnoughOperands { .. }) => (),
+                other => panic!("{} should return NotEnoughOperands, got {:?}", op, other),
+            }
+        }
+        calc.evaluate("5").unwrap();
+        assert!(calc.evaluate("stddev").is_err(), "One value has no sample deviation");
+        assert_eq!(vec![5.0], calc.state.stack);
+        let mut calc: RpnCalculator<i32> = RpnCalculator::new();
+        match kind(calc.evaluate("65536 65536 3 prod")) {
+            Err(RpnCalculatorError::Overflow) => (),
+            other => panic!("Should return Overflow, got {:?}", other),
+        }
+        assert_eq!(vec![65536, 65536, 3], calc.state.stack);
+        let mut calc: RpnCalculator<Quantity> = RpnCalculator::new();
+        assert!(calc.evaluate("1 m 1 s sum").is_err());
+        assert_eq!(2, calc.state.stack.len());
+    }
+
+    #[test]
+    fn should_operate_on_bits() {
+        let check = |input: &str, expected: f64| {
+            let mut calc = make_calculator();
+            calc.evaluate(input).unwrap();
+            assert_eq!(vec![expected], calc.state.stack, "{}", input);
+        };
+        check("12 10 and", 8.0);
+        check("12 10 or", 14.0);
+        check("12 10 xor", 6.0);
+        check("0 not", -1.0);
+        check("5.9 not", -6.0);
+        check("3 4 shl", 48.0);
+        check("-48 4 shr", -3.0);
+        check("-1 100 shr", -1.0);
+        check("17 5 mod", 2.0);
+        check("-17 5 mod", -2.0);
+        check("17.8 5.2 mod", 2.0);
+        check("0xff 0b1010 - 0o10 +", 253.0);
+        check("-0x10", -16.0);
+        let mut calc: RpnCalculator<BigNumber> = RpnCalculator::new();
+        calc.evaluate("1 62 shl 4 *").unwrap();
+        assert_eq!("18446744073709551616", calc.top().unwrap().to_string());
+        match kind(calc.evaluate("1 and")) {
+            Err(RpnCalculatorError::Overflow) => (),
+            other => panic!("Should return Overflow, got {:?}", other),
+        }
+        calc.evaluate("clear 2.5 3 and").unwrap();
+        assert_eq!(BigNumber::Int(2.into()), *calc.top().unwrap());
+    }
+
+    #[test]
+    fn should_read_literals_in_every_notation() {
+        let mut calc = make_calculator();
+        calc.evaluate("1.5e-3 1_000_000 -2.5E2 -pi e tau 0xff_ff -1_0.2_5").unwrap();
+        let expected = vec![0.0015, 1e6, -250.0, -f64::consts::PI, f64::consts::E, f64::consts::TAU, 65535.0, -10.25];
+        assert_eq!(expected, calc.state.stack);
+        for bad in &["1__0", "_1", "1_", "1_.5", "--1", "1e", "p_i", "1e5000x"] {
+            assert_eq!(Err(RpnCalculatorError::ParsingError), kind(make_calculator().evaluate(bad)), "{}", bad);
+        }
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        calc.evaluate("1.5e3 -2e0 1_024 12.50e-1 pi").unwrap_err();
+        assert_eq!(vec![1500, -2, 1024], calc.state.stack, "12.50e-1 isn't whole");
+        assert_eq!(Err(RpnCalculatorError::NotSupported), kind(calc.evaluate("pi")));
+        calc.evaluate("clear 1_024 125e-2 -").unwrap_err();
+        assert_eq!(vec![1024], calc.state.stack);
+        let mut calc: RpnCalculator<Decimal> = RpnCalculator::new();
+        calc.evaluate("1.5e-3 -12_345e-2").unwrap();
+        assert_eq!(vec!["0.0015", "-123.45"], calc.state.stack.iter().map(|d| d.to_string()).collect::<Vec<_>>());
+        let mut calc: RpnCalculator<BigNumber> = RpnCalculator::new();
+        calc.evaluate("1e30 1 +").unwrap();
+        assert_eq!("1000000000000000000000000000001", calc.top().unwrap().to_string());
+        let mut calc: RpnCalculator<Complex> = RpnCalculator::new();
+        calc.evaluate("-e").unwrap();
+        assert_eq!(Complex::from(-f64::consts::E), *calc.top().unwrap());
+    }
+
+    #[test]
+    fn should_check_bitwise_operands() {
+        let mut calc: RpnCalculator<i32> = RpnCalculator::new();
+        for &(input, expected) in &[("1 31 shl", "Overflow"), ("1 -1 shl", "DomainError"),
+                                     ("1 0 mod", "DivisionByZero"), ("2147483647 1 shl", "Overflow"),
+                                     ("0x80000000", "Overflow"),
+                                     ("1 and", r#"NotEnoughOperands { op: "and", needed: 2, got: 1 }"#)] {
+            calc.state.stack.clear();
+            let result = calc.evaluate(input);
+            assert_eq!(Some(expected.to_string()), result.err().map(|e| format!("{:?}", e.kind())), "{}", input);
+        }
+        assert_eq!(vec![1], calc.state.stack);
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        assert!(calc.evaluate("-9223372036854775808 -1 mod").is_err());
+        let mut calc = make_calculator();
+        assert!(calc.evaluate("1e19 1 and").is_err());
+        assert!(calc.evaluate("0xg").is_err());
+        let mut calc: RpnCalculator<Quantity> = RpnCalculator::new();
+        match kind(calc.evaluate("3 m 1 or")) {
+            Err(RpnCalculatorError::UnitMismatch) => (),
+            other => panic!("Should return UnitMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_show_results_in_a_base() {
+        let mut calc = make_calculator();
+        assert_eq!(None, calc.format_top());
+        calc.evaluate("255 hex").unwrap();
+        assert_eq!((Base::Hex, Some("0xff".to_string())), (calc.base(), calc.format_top()));
+        calc.evaluate("neg bin").unwrap();
+        assert_eq!(Some("-0b11111111".to_string()), calc.format_top());
+        calc.evaluate("oct 8 *").unwrap();
+        assert_eq!(Some("-0o3770".to_string()), calc.format_top());
+        calc.evaluate("0.5 +").unwrap();
+        assert_eq!(Some("-2039.5".to_string()), calc.format_top(), "Fractions stay in decimal");
+        calc.evaluate("dec 0.5 +").unwrap();
+        assert_eq!(Some("-2039".to_string()), calc.format_top());
+        let mut calc: RpnCalculator<Roman> = RpnCalculator::new();
+        calc.evaluate("XX hex").unwrap();
+        assert_eq!(Some("0x14".to_string()), calc.format_top());
+    }
+
+    #[test]
+    fn should_show_results_in_a_format() {
+        let mut calc = make_calculator();
+        let mut check = |input: &str, expected: &str| {
+            calc.evaluate(input).unwrap();
+            assert_eq!(Some(expected.to_string()), calc.format_top(), "{}", input);
+        };
+        check("2 3 /", "0.6666666666666666");
+        check("fix 4", "0.6667");
+        check("fix 0", "1");
+        check("1000000 *", "666667");
+        check("sci 2", "6.67e5");
+        check("eng", "667e3");
+        check("100 /", "6.67e3");
+        check("100 /", "66.7e0");
+        check("neg 1000000 /", "-66.7e-6");
+        check("sci 17", "-6.66666666666666563e-5");
+        check("std", "-0.00006666666666666666");
+        check("0 fix 3", "0.000");
+        check("eng", "0.000e0");
+        check("1 0 / fix 2", "inf");
+        check("hex 255", "0xff");
+        check("0.5 +", "255.50");
+        assert_eq!(Format::Fix(2), calc.format());
+    }
+
+    #[test]
+    fn should_check_format_arguments() {
+        let mut calc = make_calculator();
+        for &(input, expected) in &[("fix", "MissingArgument"), ("sci x", "ParsingError"), ("fix -1", "ParsingError"),
+                                     ("sci 18", "DomainError")] {
+            let result = calc.evaluate(input);
+            assert_eq!(Some(expected.to_string()), result.err().map(|e| format!("{:?}", e.kind())), "{}", input);
+        }
+        assert_eq!(Format::Std, calc.format());
+        let mut calc: RpnCalculator<Complex> = RpnCalculator::new();
+        calc.evaluate("1+2i sci 2").unwrap();
+        assert_eq!(Some("1+2i".to_string()), calc.format_top(), "Complex numbers show as usual");
+        calc.evaluate("1234 re").unwrap();
+        assert_eq!(Some("1.23e3".to_string()), calc.format_top());
+        let mut calc: RpnCalculator<Quantity> = RpnCalculator::new();
+        calc.evaluate("fix 2 1 m 3 /").unwrap();
+        assert_eq!(Some(calc.top().unwrap().to_string()), calc.format_top());
+    }
+
     #[test]
     fn should_calculate_the_example_from_the_site() {
         check_evaluation("19 2.14 + 4.5 2 4.3 / - *", 85.2974);
     }
+
+    #[test]
+    fn should_calculate_with_integers() {
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        calc.evaluate("19 2 + 7 2 / *").unwrap();
+        assert_eq!(63, *calc.top().unwrap());
+        assert!(calc.evaluate("2.5").is_err(), "Integers don't parse decimals");
+        calc.state.stack.clear();
+        calc.evaluate("4 1 3 2 median").unwrap();
+        assert_eq!(2, *calc.top().unwrap());
+    }
+
+    #[test]
+    fn should_keep_operands_when_integer_operations_fail() {
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        match kind(calc.evaluate("1 0 /")) {
+            Err(RpnCalculatorError::DivisionByZero) => (),
+            other => panic!("Should return DivisionByZero, got {:?}", other),
+        }
+        assert_eq!(vec![1, 0], calc.state.stack);
+        calc.state.stack.clear();
+        match kind(calc.evaluate("9223372036854775807 1 +")) {
+            Err(RpnCalculatorError::Overflow) => (),
+            other => panic!("Should return Overflow, got {:?}", other),
+        }
+        assert_eq!(2, calc.state.stack.len());
+    }
+
+    #[test]
+    fn should_calculate_with_f32() {
+        let mut calc: RpnCalculator<f32> = RpnCalculator::new();
+        calc.evaluate("1.5 2 * 1 0 /").unwrap();
+        assert_eq!(f32::INFINITY, *calc.top().unwrap());
+    }
+
+    #[test]
+    fn should_calculate_with_complex_numbers() {
+        let mut calc: RpnCalculator<Complex> = RpnCalculator::new();
+        calc.evaluate("3+2i 1-i + 2i * 1+i /").unwrap();
+        assert_eq!(Complex::new(3.0, 5.0), *calc.top().unwrap());
+        calc.evaluate("3-4i abs").unwrap();
+        assert_eq!(Complex::from(5.0), *calc.top().unwrap());
+        calc.evaluate("-1 arg").unwrap();
+        assert_eq!(Complex::from(std::f64::consts::PI), *calc.top().unwrap());
+        calc.state.stack.clear();
+        calc.evaluate("2.5-7i re 2.5-7i im").unwrap();
+        assert_eq!(vec![Complex::from(2.5), Complex::from(-7.0)], calc.state.stack);
+        assert!(calc.evaluate("3+2").is_err());
+        match kind(calc.evaluate("1 0 /")) {
+            Err(RpnCalculatorError::DivisionByZero) => (),
+            other => panic!("Should return DivisionByZero, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_keep_big_integers_exact() {
+        let mut calc: RpnCalculator<BigNumber> = RpnCalculator::new();
+        calc.evaluate("2 1000 pow").unwrap();
+        let text = calc.top().unwrap().to_string();
+        assert_eq!(302, text.len());
+        assert!(text.starts_with("10715086071862673") && text.ends_with("68069376"), "{}", text);
+        calc.evaluate("1 -").unwrap();
+        assert!(calc.top().unwrap().to_string().ends_with("68069375"));
+        calc.evaluate("10 20 pow 1 + 3 *").unwrap();
+        assert_eq!("300000000000000000003", calc.top().unwrap().to_string());
+        calc.evaluate("3 /").unwrap();
+        assert_eq!("100000000000000000001", calc.top().unwrap().to_string());
+    }
+
+    #[test]
+    fn should_switch_big_integers_to_floats() {
+        let mut calc: RpnCalculator<BigNumber> = RpnCalculator::new();
+        calc.evaluate("7 2 /").unwrap();
+        assert_eq!(BigNumber::Float(3.5), *calc.top().unwrap());
+        calc.evaluate("2 sqrt").unwrap();
+        assert_eq!(BigNumber::Float(2f64.sqrt()), *calc.top().unwrap());
+        calc.evaluate("2 -1 pow 2.5 2 *").unwrap();
+        assert_eq!(BigNumber::Float(5.0), *calc.top().unwrap());
+        calc.evaluate("4 1 3 2 median").unwrap();
+        assert_eq!(BigNumber::Float(2.5), *calc.top().unwrap());
+        match kind(calc.evaluate("1 0 /")) {
+            Err(RpnCalculatorError::DivisionByZero) => (),
+            other => panic!("Should return DivisionByZero, got {:?}", other),
+        }
+        calc.state.stack.clear();
+        calc.evaluate("1.0 0 /").unwrap();
+        assert_eq!(BigNumber::Float(f64::INFINITY), *calc.top().unwrap());
+        assert!(calc.evaluate("2 99999999999 pow").is_err());
+        // Rounded results don't look like integers
+        for &(input, expected) in &[("2 100 pow 3 /", "4.2255020007607644e29"), ("6 3 /", "2"), ("2.5 2 *", "5.0")] {
+            calc.evaluate(input).unwrap();
+            assert_eq!(expected, calc.top().unwrap().to_string(), "{}", input);
+            assert_eq!(calc.top(), BigNumber::from_display(expected).as_ref(), "{} reads back", expected);
+        }
+        for input in &["2 2000000 pow", "1000 200000 pow", "2 4000000000 pow"] {
+            assert_eq!(Some(RpnCalculatorError::Overflow), calc.evaluate(input).err().map(|e| e.kind().clone()), "{}", input);
+        }
+        calc.evaluate("-1 4000000000 pow 2 100000 pow").unwrap();
+        assert_eq!(30103, calc.top().unwrap().to_string().len());
+    }
+
+    #[test]
+    fn should_calculate_with_fractions() {
+        let mut calc: RpnCalculator<Ratio> = RpnCalculator::new();
+        new_operator!(calc.operators, "half", [x], { x * Ratio::new(1, 2) });
+        calc.evaluate("1/3 1/6 + half").unwrap();
+        assert_eq!(Ratio::new(1, 4), *calc.top().unwrap());
+        assert!(calc.evaluate("0 /").is_err());
+    }
+
+    #[test]
+    fn should_calculate_with_exact_decimals() {
+        let mut calc: RpnCalculator<Decimal> = RpnCalculator::new();
+        calc.evaluate("0.1 0.2 +").unwrap();
+        assert_eq!("0.3", calc.top().unwrap().to_string());
+        calc.set_precision(30);
+        calc.evaluate("1 3 /").unwrap();
+        assert_eq!(format!("0.{}", "3".repeat(30)), calc.top().unwrap().to_string());
+        calc.set_precision(2);
+        assert_eq!(2, calc.precision());
+        calc.evaluate("2 3 /").unwrap();
+        assert_eq!("0.67", calc.top().unwrap().to_string());
+        calc.state.stack.clear();
+        calc.evaluate("1.5 2.25 3.75 median").unwrap();
+        assert_eq!("2.25", calc.top().unwrap().to_string());
+        match kind(calc.evaluate("0 /")) {
+            Err(RpnCalculatorError::DivisionByZero) => (),
+            other => panic!("Should return DivisionByZero, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_keep_the_decimal_precision_in_each_calculator() {
+        let mut short: RpnCalculator<Decimal> = RpnCalculator::new();
+        let mut long: RpnCalculator<Decimal> = RpnCalculator::new();
+        short.set_precision(3);
+        long.set_precision(8);
+        short.evaluate("1 3 /").unwrap();
+        long.evaluate("1 3 /").unwrap();
+        short.evaluate("2 3 /").unwrap();
+        let stack: Vec<String> = short.state.stack.iter().map(|x| x.to_string()).collect();
+        assert_eq!(vec!["0.333", "0.667"], stack);
+        assert_eq!("0.33333333", long.top().unwrap().to_string());
+        assert_eq!(decimal::DEFAULT_PRECISION, decimal::precision());
+        let mut saved = Vec::new();
+        short.save(&mut saved).unwrap();
+        let mut loaded: RpnCalculator<Decimal> = RpnCalculator::new();
+        loaded.load(&saved[..]).unwrap();
+        assert_eq!(3, loaded.precision());
+        loaded.evaluate("1 7 /").unwrap();
+        assert_eq!("0.143", loaded.top().unwrap().to_string());
+        let older = r#"{"stack": ["1"], "registers": {}, "words": {}, "base": "Dec", "angle": "Rad", "format": "Std", "quotations": []}"#;
+        loaded.load(older.as_bytes()).unwrap();
+        assert_eq!(decimal::DEFAULT_PRECISION, loaded.precision());
+    }
+
+    #[test]
+    fn should_propagate_uncertainty_with_intervals() {
+        let mut calc: RpnCalculator<Interval> = RpnCalculator::new();
+        calc.evaluate("[1,2] [10,20] + 2 *").unwrap();
+        assert_eq!(Interval::new(22.0, 44.0), *calc.top().unwrap());
+        calc.evaluate("10 0.5 pm 4 0.5 pm *").unwrap();
+        assert_eq!(Interval::new(33.25, 47.25), *calc.top().unwrap());
+        calc.evaluate("deg radius").unwrap();
+        assert_eq!(Interval::point(7.0), *calc.top().unwrap());
+        assert_eq!(Angle::Deg, calc.angle());
+        // `rad` is still the angle mode, not the radius
+        calc.evaluate("rad").unwrap();
+        assert_eq!(Interval::point(7.0), *calc.top().unwrap());
+        assert_eq!(Angle::Rad, calc.angle());
+        calc.evaluate("1 [0,2] /").unwrap();
+        assert_eq!(Interval::new(0.5, f64::INFINITY), *calc.top().unwrap());
+        match kind(calc.evaluate("1 0 /")) {
+            Err(RpnCalculatorError::DivisionByZero) => (),
+            other => panic!("Should return DivisionByZero, got {:?}", other),
+        }
+        assert!(calc.evaluate("[2,1]").is_err());
+    }
+
+    #[test]
+    fn should_negate_intervals_without_widening_them() {
+        let mut calc: RpnCalculator<Interval> = RpnCalculator::new();
+        for &(input, lo, hi) in &[("[1,2] neg", -2.0, -1.0), ("-[1,2]", -2.0, -1.0), ("[-2,1] abs", 0.0, 2.0),
+                                  ("[-3,-1] abs", 1.0, 3.0), ("[1,3] abs", 1.0, 3.0), ("[-1,4] abs", 0.0, 4.0)] {
+            calc.evaluate(input).unwrap();
+            assert_eq!(Interval::new(lo, hi), *calc.top().unwrap(), "{}", input);
+        }
+        let mut calc: RpnCalculator<Quantity> = RpnCalculator::new();
+        calc.evaluate("-3 m abs 2 s neg").unwrap();
+        assert_eq!(vec!["3 m", "-2 s"], calc.state.stack.iter().map(|x| x.to_string()).collect::<Vec<String>>());
+        assert_eq!(Some(RpnCalculatorError::DivisionByZero), calc.evaluate("0 m -1 pow").err().map(|e| e.kind().clone()));
+    }
+
+    #[test]
+    fn should_calculate_with_roman_numerals() {
+        let mut calc: RpnCalculator<Roman> = RpnCalculator::new();
+        calc.evaluate("XIX II +").unwrap();
+        assert_eq!("XXI", calc.top().unwrap().to_string());
+        calc.evaluate("mcm 94 + X /").unwrap();
+        assert_eq!("CXCIX", calc.top().unwrap().to_string());
+        calc.evaluate("V V -").unwrap();
+        assert_eq!("N", calc.top().unwrap().to_string());
+        assert!(calc.evaluate("IIII").is_err());
+        for (input, error) in [("I II -", "Overflow"), ("MM II *", "Overflow"), ("I N /", "DivisionByZero")] {
+            match kind(calc.evaluate(input)) {
+                Err(e) => assert_eq!(error, format!("{:?}", e.kind())),
+                Ok(_) => panic!("{} should fail", input),
+            }
+            calc.state.stack.clear();
+        }
+    }
+
+    #[test]
+    fn should_check_dimensions_of_units() {
+        let mut calc: RpnCalculator<Quantity> = RpnCalculator::new();
+        calc.evaluate("3 m 4 s /").unwrap();
+        assert_eq!("0.75 m/s", calc.top().unwrap().to_string());
+        calc.evaluate("1.5 km/h 3.6 * +").unwrap();
+        assert_eq!("2.25 m/s", calc.top().unwrap().to_string());
+        calc.evaluate("2 s * 9 m^2 sqrt -").unwrap();
+        assert_eq!("1.5 m", calc.top().unwrap().to_string());
+        match kind(calc.evaluate("1 s +")) {
+            Err(RpnCalculatorError::UnitMismatch) => (),
+            other => panic!("Should return UnitMismatch, got {:?}", other),
+        }
+        assert_eq!(2, calc.state.stack.len(), "Mismatched operands stay on the stack");
+        calc.state.stack.clear();
+        match kind(calc.evaluate("m")) {
+            Err(RpnCalculatorError::NotEnoughOperands { .. }) => (),
+            other => panic!("Should return NotEnoughOperands, got {:?}", other),
+        }
+        assert!(calc.evaluate("3 furlong").is_err());
+        calc.evaluate("2kg 10m/s^2 * value").unwrap();
+        assert_eq!(Quantity::number(20.0), *calc.top().unwrap());
+    }
+
+    #[test]
+    fn should_store_and_recall_registers() {
+        let mut calc = make_calculator();
+        calc.evaluate("1.5 sto x 2 sto y *").unwrap();
+        assert_eq!(3.0, *calc.top().unwrap());
+        calc.evaluate("rcl x rcl y rcl x + +").unwrap();
+        assert_eq!(5.0, *calc.top().unwrap());
+        calc.evaluate("10 sto x").unwrap();
+        assert_eq!(Some(&10.0), calc.registers().get("x"));
+        assert_eq!(2, calc.registers().len());
+        match kind(calc.evaluate("rcl z 1 +")) {
+            Err(RpnCalculatorError::UnknownRegister(ref name)) if name == "z" => (),
+            other => panic!("Should return UnknownRegister, got {:?}", other),
+        }
+        match kind(calc.evaluate("sto")) {
+            Err(RpnCalculatorError::MissingArgument) => (),
+            other => panic!("Should return MissingArgument, got {:?}", other),
+        }
+        calc.state.stack.clear();
+        match kind(calc.evaluate("sto w")) {
+            Err(RpnCalculatorError::NotEnoughOperands { .. }) => (),
+            other => panic!("Should return NotEnoughOperands, got {:?}", other),
+        }
+        // The name stays in the input when there's nothing to store
+        calc.state.run_next(vec!["w".to_string()]);
+        assert!((calc.operators["sto"].run)(&mut calc.state).is_err());
+        assert_eq!(Some("w".to_string()), calc.state.next_word());
+        calc.clear_registers();
+        assert!(calc.registers().is_empty());
+        assert!(calc.evaluate("rcl x").is_err());
+    }
+
+    #[test]
+    fn should_define_words() {
+        let mut calc = make_calculator();
+        calc.evaluate(": sq sto t rcl t * ;").unwrap();
+        calc.evaluate(": cube sto c sq rcl c * ;").unwrap();
+        calc.evaluate("3 cube 2 sq -").unwrap();
+        assert_eq!(vec![23.0], calc.state.stack);
+        // Words are looked up when they run
+        calc.evaluate(": twice half half ; : half 2 / ;").unwrap();
+        calc.evaluate("twice").unwrap();
+        assert_eq!(vec![5.75], calc.state.stack);
+        // A redefinition replaces the word, even shadowing built-ins
+        calc.evaluate(": + * ;").unwrap();
+        calc.evaluate("2 +").unwrap();
+        assert_eq!(vec![11.5], calc.state.stack);
+        assert_eq!(5, calc.words().len());
+    }
+
+    #[test]
+    fn should_undo_and_redo_lines() {
+        let mut calc = make_calculator();
+        match kind(calc.evaluate("undo")) {
+            Err(RpnCalculatorError::NothingToUndo) => (),
+            other => panic!("Should return NothingToUndo, got {:?}", other),
+        }
+        calc.evaluate("2 sto x hex").unwrap();
+        calc.evaluate(": sq dup * ; 3 sq").unwrap();
+        calc.evaluate("clear fix 2 deg").unwrap();
+        calc.evaluate("dup drop").unwrap_or(());
+        calc.evaluate("").unwrap();
+        assert_eq!(3, calc.history_len(), "Lines changing nothing are not recorded");
+        calc.evaluate("undo").unwrap();
+        assert_eq!((vec![2.0, 9.0], Format::Std, Angle::Rad), (calc.state.stack.clone(), calc.format(), calc.angle()));
+        calc.evaluate("undo").unwrap();
+        assert_eq!((vec![2.0], 0), (calc.state.stack.clone(), calc.words().len()));
+        calc.evaluate("undo").unwrap();
+        assert!(calc.state.stack.is_empty() && calc.registers().is_empty());
+        assert_eq!(Base::Dec, calc.base());
+        calc.evaluate("redo redo").unwrap();
+        assert_eq!((vec![2.0, 9.0], Base::Hex), (calc.state.stack.clone(), calc.base()));
+        assert_eq!(2, calc.history_len());
+        calc.evaluate("1 +").unwrap();
+        match kind(calc.evaluate("redo")) {
+            Err(RpnCalculatorError::NothingToRedo) => (),
+            other => panic!("Changes should forget what was undone, got {:?}", other),
+        }
+        calc.evaluate("undo").unwrap();
+        assert_eq!(vec![2.0, 9.0], calc.state.stack);
+    }
+
+    #[test]
+    fn should_recall_lines_from_history() {
+        let mut calc = make_calculator();
+        match kind(calc.evaluate("!!")) {
+            Err(RpnCalculatorError::NotInHistory) => (),
+            other => panic!("Should return NotInHistory, got {:?}", other),
+        }
+        calc.evaluate("1 2 +").unwrap();
+        calc.evaluate("   ").unwrap();
+        calc.evaluate("!! !!").unwrap();
+        assert_eq!(vec![3.0, 3.0, 3.0], calc.state.stack);
+        calc.evaluate("clear !2 drop").unwrap();
+        assert_eq!(vec![3.0], calc.state.stack);
+        calc.evaluate("nosuchword").unwrap_or(());
+        calc.evaluate("!0").unwrap_or(());
+        calc.evaluate("!5 !x").unwrap_or(());
+        let lines = ["1 2 +", "1 2 + 1 2 +", "clear 1 2 + 1 2 + drop", "nosuchword"];
+        assert_eq!(&lines, calc.history(), "Only lines that were evaluated are kept");
+        calc.evaluate("!4").unwrap_or(());
+        assert_eq!(5, calc.history().len());
+    }
+
+    fn save_and_load<T: Number>(input: &str) -> (RpnCalculator<T>, RpnCalculator<T>) {
+        let mut calc: RpnCalculator<T> = RpnCalculator::new();
+        calc.evaluate(input).unwrap();
+        let mut saved = Vec::new();
+        calc.save(&mut saved).unwrap();
+        let mut loaded = RpnCalculator::new();
+        loaded.load(&saved[..]).unwrap();
+        (calc, loaded)
+    }
+
+    #[test]
+    fn should_save_and_load_sessions() {
+        let (calc, loaded) = save_and_load::<f64>("0.1 0.2 + sto x hex deg sci 4 : sq dup * ; 3 { 1 } 1e300");
+        assert_eq!(calc.state.snapshot(), loaded.state.snapshot());
+        let (calc, loaded) = save_and_load::<Quantity>("3 m 2 s / 9.81 m/s^2 1 kg * sto w 5");
+        assert_eq!(calc.state.snapshot(), loaded.state.snapshot());
+        let (calc, loaded) = save_and_load::<BigNumber>("2 100 pow 0.5 -7");
+        assert_eq!(calc.state.snapshot(), loaded.state.snapshot());
+        let (calc, loaded) = save_and_load::<Complex>("3+2i 1-i / sto z 0-i");
+        assert_eq!(calc.state.snapshot(), loaded.state.snapshot());
+        let (calc, loaded) = save_and_load::<Interval>("1 3 / sto t 2 7 /");
+        assert_eq!(calc.state.snapshot(), loaded.state.snapshot());
+    }
+
+    #[test]
+    fn should_save_and_load_values_that_are_not_finite() {
+        let (calc, loaded) = save_and_load::<Complex>("1e308 10 * dup neg i * 1e308i 10 * dup - 1 + i 1e308 10 * *");
+        let shown = |calc: &RpnCalculator<Complex>| calc.state.stack.iter().map(|z| z.to_string()).collect::<Vec<String>>();
+        assert_eq!(vec!["inf", "NaN-infi", "1+NaNi", "NaN+infi"], shown(&calc));
+        assert_eq!(shown(&calc), shown(&loaded));
+    }
+
+    #[test]
+    fn should_load_all_or_nothing() {
+        let mut calc = make_calculator();
+        calc.evaluate("1 2").unwrap();
+        for bad in &["", "{", r#"{"stack": []}"#, r#"{"stack": ["x"], "registers": {}, "words": {}, "base": "Dec",
+            "angle": "Rad", "format": "Std", "quotations": []}"#] {
+            match calc.load(bad.as_bytes()) {
+                Err(RpnCalculatorError::ParsingError) => (),
+                other => panic!("Should return ParsingError for {}, got {:?}", bad, other),
+            }
+        }
+        assert_eq!(vec![1.0, 2.0], calc.state.stack);
+        let (_, mut loaded) = save_and_load::<f64>("7 sto a deg");
+        loaded.evaluate("undo").unwrap();
+        assert!(loaded.state.stack.is_empty() && loaded.registers().is_empty());
+        assert_eq!(Angle::Rad, loaded.angle());
+    }
+
+    #[test]
+    fn should_report_where_errors_happen() {
+        let mut calc = make_calculator();
+        let error = calc.evaluate("1  2 + 3 x").unwrap_err();
+        assert_eq!(
+            RpnCalculatorError::At { token: "x".to_string(), offset: 9, error: Box::new(RpnCalculatorError::ParsingError) },
+            error
+        );
+        calc.evaluate("clear : add3 + + ; 1 2").unwrap();
+        let error = calc.evaluate("  add3").unwrap_err();
+        let expected = RpnCalculatorError::NotEnoughOperands { op: "+".to_string(), needed: 2, got: 1 };
+        assert_eq!(&expected, error.kind());
+        assert_eq!("`add3` at byte 2: + takes 2 operands, got 1", error.to_string());
+        let error = calc.evaluate("1 { -1 sqrt } if").unwrap_err();
+        assert_eq!("`if` at byte 14: Argument out of the domain", error.to_string());
+        let error = calc.evaluate("1 !99").unwrap_err();
+        assert_eq!("`!99` at byte 2: No such line in the history", error.to_string());
+        let error = calc.evaluate("clear 5 !3").unwrap_err();
+        assert_eq!("`add3` at byte 8: + takes 2 operands, got 1", error.to_string(), "Recalled tokens are at the `!`");
+        let source = error::Error::source(&error).map(|e| e.to_string());
+        assert_eq!(Some("+ takes 2 operands, got 1".to_string()), source);
+        let composed = || -> result::Result<(), Box<dyn error::Error>> {
+            make_calculator().evaluate("sqrt")?;
+            Ok(())
+        };
+        assert_eq!("`sqrt` at byte 0: sqrt takes 1 operand, got 0", composed().unwrap_err().to_string());
+    }
+
+    /// Evaluates the example of each operator that has one, which should
+    /// leave a value on the stack
+    fn check_examples<T: Number>(names: &[&str]) {
+        let calc: RpnCalculator<T> = RpnCalculator::new();
+        for (name, operator) in calc.operators.iter().filter(|(name, _)| names.is_empty() || names.contains(name)) {
+            assert!(!operator.description.is_empty(), "{} has no description", name);
+            if operator.example.is_empty() {
+                continue;
+            }
+            let mut example: RpnCalculator<T> = RpnCalculator::new();
+            assert_eq!(Ok(()), example.evaluate(operator.example), "{}: {}", name, operator.example);
+            assert!(example.top().is_some(), "{}: {} leaves no value", name, operator.example);
+            assert!(calc.help(Some(name)).unwrap().ends_with(&format!("gives {}", example.format_top().unwrap())));
+        }
+    }
+
+    #[test]
+    fn should_describe_every_operator() {
+        check_examples::<f64>(&[]);
+        check_examples::<Complex>(&["sqrt", "re", "im", "abs", "arg"]);
+        check_examples::<BigNumber>(&["pow"]);
+        check_examples::<Interval>(&["pm", "mid", "radius", "width"]);
+        check_examples::<Quantity>(&["sqrt", "value"]);
+        let calc = make_calculator();
+        let arities: Vec<Option<usize>> = ["neg", "+", "rot", "sum", "sto"].iter().map(|&name| calc.operators[name].arity).collect();
+        assert_eq!(vec![Some(1), Some(2), Some(3), None, None], arities);
+    }
+
+    #[test]
+    fn should_print_help_when_asked() {
+        let mut calc = make_calculator();
+        calc.evaluate(": sq dup * ; 3 help").unwrap();
+        let catalog = calc.take_output();
+        assert_eq!(1, catalog.len());
+        let lines: Vec<&str> = catalog[0].lines().collect();
+        assert_eq!(calc.operators.len() + 2, lines.len(), "The operators, the words and help itself");
+        assert_eq!("sq       : sq dup * ;", lines[lines.len() - 2]);
+        calc.evaluate("help help help sq sq").unwrap();
+        assert_eq!(vec![format!("help: {}", HELP_DESCRIPTION), ": sq dup * ;".to_string()], calc.take_output());
+        assert_eq!((Some(&9.0), 1), (calc.top(), calc.stack_len()));
+        calc.evaluate("help times").unwrap();
+        let expected = "times: Runs the last quotation as many times as the top value\n\
+                        Example: 1 { 2 * } 10 times gives 1024";
+        assert_eq!(vec![expected.to_string()], calc.take_output());
+        assert_eq!(Some("No operator or word named nope".to_string()), kind(calc.evaluate("help nope")).err().map(|e| e.to_string()));
+        assert!(calc.names().contains(&"help"));
+    }
+
+    #[test]
+    fn should_evaluate_scripts_line_by_line() {
+        let mut calc = make_calculator();
+        let script = "# Constants\n  # indented comment\n9.81 sto g\n\n: fall rcl g * 2 / ;\n2 2 * fall\n";
+        calc.evaluate_script(script).unwrap();
+        assert_eq!(vec![9.81, 19.62], calc.state.stack);
+        assert_eq!(3, calc.history().len(), "Comments and blank lines aren't evaluated");
+        let error = calc.evaluate_script(": half 2 / ;\n\n# next\n8 half nosuchword 1").unwrap_err();
+        assert_eq!((4, &RpnCalculatorError::ParsingError), (error.line, error.error.kind()));
+        assert_eq!("line 4: `nosuchword` at byte 7: Not a number or an operator", error.to_string());
+        assert_eq!(vec![9.81, 19.62, 4.0], calc.state.stack, "Stops where the line failed");
+        let error = calc.evaluate_script(": open 1").unwrap_err();
+        assert_eq!("line 1: `:` at byte 0: Definition without ;", error.to_string());
+    }
+
+    /// Runs `twice LINE` lines twice and stops at `stop`, keeping the top
+    /// of the stack or the error of every line
+    struct Twice {
+        results: Vec<(usize, result::Result<Option<f64>, RpnCalculatorError>)>,
+    }
+
+    impl LineSink<f64> for Twice {
+        fn evaluate(&mut self, calc: &mut RpnCalculator, input: &str) -> CalcResult {
+            match input.strip_prefix("twice ") {
+                Some(rest) => calc.evaluate(rest).and_then(|()| calc.evaluate(rest)),
+                None => calc.evaluate(input),
+            }
+        }
+
+        fn line(&mut self, calc: &mut RpnCalculator, number: usize, input: &str, result: CalcResult) -> bool {
+            self.results.push((number, result.map(|()| calc.top().cloned())));
+            input != "stop"
+        }
+    }
+
+    #[test]
+    fn should_stream_lines_to_a_sink() {
+        let mut calc = make_calculator();
+        calc.evaluate("0 : stop ;").unwrap();
+        let mut sink = Twice { results: Vec::new() };
+        let input = "1 +\n   \ntwice 10 *\n  # comment\n1 2 3 4 5 drop drop drop drop drop drop drop\nstop\n7";
+        calc.evaluate_stream(io::BufReader::new(input.as_bytes()), &mut sink).unwrap();
+        let underflow = RpnCalculatorError::NotEnoughOperands { op: "drop".to_string(), needed: 1, got: 0 };
+        let results: Vec<(usize, result::Result<Option<f64>, &RpnCalculatorError>)> =
+            sink.results.iter().map(|(line, result)| (*line, result.as_ref().map(|&top| top).map_err(|e| e.kind()))).collect();
+        assert_eq!(vec![(1, Ok(Some(1.0))), (3, Ok(Some(100.0))), (5, Err(&underflow)), (6, Ok(None))], results);
+        assert_eq!(None, calc.top(), "Stops after the sink says so");
+    }
+
+    #[test]
+    fn should_undo_failed_lines_within_the_limit() {
+        let mut calc = make_calculator();
+        calc.set_undo_limit(2);
+        for input in &["1", "2", "3", "4"] {
+            calc.evaluate(input).unwrap();
+        }
+        assert_eq!(2, calc.history_len());
+        assert!(calc.evaluate("5 6 + oops").is_err());
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0, 11.0], calc.state.stack);
+        calc.evaluate("undo").unwrap();
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0], calc.state.stack);
+        calc.evaluate("undo").unwrap();
+        assert!(calc.evaluate("undo").is_err());
+        assert_eq!(vec![1.0, 2.0, 3.0], calc.state.stack);
+        calc.set_undo_limit(0);
+        calc.evaluate("clear").unwrap();
+        assert_eq!(0, calc.history_len());
+        assert!(calc.evaluate("undo").is_err());
+    }
+
+    #[test]
+    fn should_restore_the_stack_when_words_fail() {
+        let mut calc = make_calculator();
+        calc.evaluate(": add3 + + ; : forever forever ;").unwrap();
+        calc.evaluate("1 2").unwrap();
+        match kind(calc.evaluate("add3")) {
+            Err(RpnCalculatorError::NotEnoughOperands { .. }) => (),
+            other => panic!("Should return NotEnoughOperands, got {:?}", other),
+        }
+        assert_eq!(vec![1.0, 2.0], calc.state.stack);
+        match kind(calc.evaluate("5 forever")) {
+            Err(RpnCalculatorError::RecursionTooDeep) => (),
+            other => panic!("Should return RecursionTooDeep, got {:?}", other),
+        }
+        assert_eq!(vec![1.0, 2.0, 5.0], calc.state.stack);
+        match kind(calc.evaluate(": open 1 2")) {
+            Err(RpnCalculatorError::UnterminatedDefinition) => (),
+            other => panic!("Should return UnterminatedDefinition, got {:?}", other),
+        }
+        assert!(calc.evaluate(": ;").is_err());
+        assert!(!calc.words().contains_key("open"));
+        // The rest of a failed input is dropped
+        assert!(calc.evaluate("+ + + 7").is_err());
+        calc.evaluate("").unwrap();
+        assert_eq!(Some(&8.0), calc.top());
+    }
+
+    #[test]
+    fn should_compare_to_zero_or_one() {
+        let mut calc = make_calculator();
+        calc.evaluate("1 2 < 1 2 > 2 2 = 2 3 <= 3 3 >= 3 2 =").unwrap();
+        assert_eq!(vec![1.0, 0.0, 1.0, 1.0, 1.0, 0.0], calc.state.stack);
+        let mut calc: RpnCalculator<Ratio> = RpnCalculator::new();
+        calc.evaluate("1/3 2/6 =").unwrap();
+        assert_eq!("1", calc.top().unwrap().to_string());
+    }
+
+    #[test]
+    fn should_branch_on_quotations() {
+        let mut calc = make_calculator();
+        calc.evaluate("-3 sto x 0 < { rcl x -1 * } if").unwrap();
+        assert_eq!(vec![3.0], calc.state.stack);
+        calc.evaluate("0 { 100 } if").unwrap();
+        assert_eq!(vec![3.0], calc.state.stack);
+        calc.evaluate("5 { 1 } { 2 } ifelse 0 { 1 } { 2 } ifelse").unwrap();
+        assert_eq!(vec![3.0, 1.0, 2.0], calc.state.stack);
+        // Quotations nest, and only the branch taken runs
+        calc.evaluate("1 { 0 { 1 / } { 10 } ifelse } { garbage } ifelse").unwrap();
+        assert_eq!(vec![3.0, 1.0, 2.0, 10.0], calc.state.stack);
+        assert!(calc.state.quotations.is_empty());
+    }
+
+    #[test]
+    fn should_branch_inside_words() {
+        let mut calc = make_calculator();
+        calc.evaluate(": abs sto t 0 < { rcl t -1 * } { rcl t } ifelse ;").unwrap();
+        calc.evaluate("-4 abs 6 abs +").unwrap();
+        assert_eq!(vec![10.0], calc.state.stack);
+        // Sums n + (n - 1) + ... + 1, keeping the total in a register
+        calc.evaluate(": sum sto n 0 > { rcl n rcl s + sto s rcl n 1 - sum } if ;").unwrap();
+        calc.evaluate("0 sto s 100 sum rcl s").unwrap();
+        assert_eq!(Some(&5050.0), calc.top());
+    }
+
+    #[test]
+    fn should_report_missing_quotations() {
+        let mut calc = make_calculator();
+        match kind(calc.evaluate("1 { 2")) {
+            Err(RpnCalculatorError::UnterminatedQuotation) => (),
+            other => panic!("Should return UnterminatedQuotation, got {:?}", other),
+        }
+        match kind(calc.evaluate("1 if")) {
+            Err(RpnCalculatorError::MissingQuotation) => (),
+            other => panic!("Should return MissingQuotation, got {:?}", other),
+        }
+        match kind(calc.evaluate("{ 1 } ifelse")) {
+            Err(RpnCalculatorError::MissingQuotation) => (),
+            other => panic!("Should return MissingQuotation, got {:?}", other),
+        }
+        calc.state.stack.clear();
+        match kind(calc.evaluate("{ 2 } if")) {
+            Err(RpnCalculatorError::NotEnoughOperands { .. }) => (),
+            other => panic!("Should return NotEnoughOperands, got {:?}", other),
+        }
+        assert_eq!(2, calc.state.quotations.len());
+    }
+
+    #[test]
+    fn should_run_quotations_a_number_of_times() {
+        let mut calc = make_calculator();
+        // 1000 at 5% a year for 10 years
+        calc.evaluate("1000 10 { 1.05 * } times").unwrap();
+        assert!((calc.top().unwrap() - 1628.894627).abs() < 1e-6);
+        calc.evaluate("0 { 2 * } times -1 { 2 * } times").unwrap();
+        assert_eq!(1, calc.state.stack.len());
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        calc.evaluate("1 10 { 2 * } times").unwrap();
+        assert_eq!(vec![1024], calc.state.stack);
+        calc.evaluate("1 3 { 2 { 10 * } times } times").unwrap();
+        assert_eq!(Some(&1_000_000), calc.top());
+        // The count is the top value, and the quotation can come either side of it
+        calc.evaluate("clear 3 5 [ 2 * ] times 3 [ 2 * ] 5 times").unwrap();
+        assert_eq!(vec![96, 96], calc.state.stack);
+        calc.evaluate("clear 1 2 [ 3 { 2 * } times [ 1 + ] 1 if ] times").unwrap();
+        assert_eq!(vec![73], calc.state.stack);
+        match kind(calc.evaluate("[ 1 + }")) {
+            Err(RpnCalculatorError::UnterminatedQuotation) => (),
+            other => panic!("Should return UnterminatedQuotation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_repeat_while_the_body_leaves_a_true_condition() {
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        // Halves 100 until it is 1, counting the steps on the stack
+        calc.evaluate("100 sto x 0 * { 1 + rcl x 2 / sto x 1 > } repeat").unwrap();
+        assert_eq!(vec![6], calc.state.stack);
+        assert_eq!(Some(&1), calc.registers().get("x"));
+        assert!(calc.state.quotations.is_empty());
+    }
+
+    #[test]
+    fn should_check_loop_arguments() {
+        let mut calc = make_calculator();
+        match kind(calc.evaluate("1e9 { 1 } times")) {
+            Err(RpnCalculatorError::TooManyIterations) => (),
+            other => panic!("Should return TooManyIterations, got {:?}", other),
+        }
+        assert_eq!(vec![1e9], calc.state.stack);
+        assert_eq!(1, calc.state.quotations.len());
+        calc.state.quotations.clear();
+        match kind(calc.evaluate("times")) {
+            Err(RpnCalculatorError::MissingQuotation) => (),
+            other => panic!("Should return MissingQuotation, got {:?}", other),
+        }
+        match kind(calc.evaluate("repeat")) {
+            Err(RpnCalculatorError::MissingQuotation) => (),
+            other => panic!("Should return MissingQuotation, got {:?}", other),
+        }
+        calc.state.stack.clear();
+        match kind(calc.evaluate("0 { 1 + 1 } repeat")) {
+            Err(RpnCalculatorError::TooManyIterations) => (),
+            other => panic!("Should return TooManyIterations, got {:?}", other),
+        }
+        assert_eq!(vec![MAX_TIMES as f64], calc.state.stack);
+        calc.evaluate("clear 0 { 1 + dup 10 < } repeat").unwrap();
+        assert_eq!(vec![10.0], calc.state.stack, "The count starts again on every line");
+        // Nested loops run one step at a time and share the limit
+        calc.state.stack.clear();
+        match kind(calc.evaluate("0 { { 1 + } 1000000 times } 1000000 times")) {
+            Err(RpnCalculatorError::TooManyIterations) => (),
+            other => panic!("Should return TooManyIterations, got {:?}", other),
+        }
+        assert!(calc.state.pending.is_empty());
+        calc.evaluate("clear 0 { { 1 + } 100 times } 100 times").unwrap();
+        assert_eq!(vec![10000.0], calc.state.stack);
+    }
+
+    #[test]
+    fn should_rearrange_the_stack() {
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        let mut check = |input: &str, expected: Vec<i64>| {
+            calc.state.stack = vec![1, 2, 3];
+            calc.evaluate(input).unwrap();
+            assert_eq!(expected, calc.state.stack, "{}", input);
+        };
+        check("dup", vec![1, 2, 3, 3]);
+        check("drop", vec![1, 2]);
+        check("swap", vec![1, 3, 2]);
+        check("over", vec![1, 2, 3, 2]);
+        check("rot", vec![2, 3, 1]);
+        check("0 pick", vec![1, 2, 3, 3]);
+        check("2 pick", vec![1, 2, 3, 1]);
+        check("1 roll", vec![1, 3, 2]);
+        check("2 roll", vec![2, 3, 1]);
+        check("0 roll", vec![1, 2, 3]);
+        check("depth", vec![1, 2, 3, 3]);
+        check("clear depth", vec![0]);
+        check("dup * swap dup * +", vec![1, 13]);
+    }
+
+    #[test]
+    fn should_check_stack_positions() {
+        let mut calc = make_calculator();
+        for &(input, expected) in &[("drop", r#"NotEnoughOperands { op: "drop", needed: 1, got: 0 }"#),
+                                     ("1 swap", r#"NotEnoughOperands { op: "swap", needed: 2, got: 1 }"#),
+                                     ("1 2 rot", r#"NotEnoughOperands { op: "rot", needed: 3, got: 2 }"#),
+                                     ("pick", r#"NotEnoughOperands { op: "pick", needed: 1, got: 0 }"#),
+                                     ("1 2 2 pick", r#"NotEnoughOperands { op: "pick", needed: 4, got: 3 }"#),
+                                     ("1 2 5 roll", r#"NotEnoughOperands { op: "roll", needed: 7, got: 3 }"#),
+                                     ("1 2 0.5 pick", "InvalidIndex"), ("1 2 -1 roll", "InvalidIndex")] {
+            calc.state.stack.clear();
+            let result = calc.evaluate(input);
+            assert_eq!(Some(expected.to_string()), result.err().map(|e| format!("{:?}", e.kind())), "{}", input);
+        }
+        // A failed pick keeps its position on the stack
+        assert_eq!(vec![1.0, 2.0, -1.0], calc.state.stack);
+        let mut calc: RpnCalculator<Roman> = RpnCalculator::new();
+        calc.evaluate("X XX XXX depth 3 roll").unwrap();
+        assert_eq!("X", calc.top().unwrap().to_string());
+    }
+
+    #[test]
+    fn should_calculate_scientific_functions() {
+        let check = |input: &str, expected: f64| {
+            let mut calc = make_calculator();
+            calc.evaluate(input).unwrap();
+            assert!((calc.top().unwrap() - expected).abs() < 1e-12, "{} gave {:?}", input, calc.top());
+        };
+        check("0.5 asin sin", 0.5);
+        check("0.5 acos cos", 0.5);
+        check("1 atan 4 *", std::f64::consts::PI);
+        check("1 atan tan", 1.0);
+        check("1 exp ln", 1.0);
+        check("1000 log", 3.0);
+        check("2 sqrt dup *", 2.0);
+        check("-3 abs 3 neg abs +", 6.0);
+        check("2 10 pow", 1024.0);
+        check("2 -2 pow", 0.25);
+        check("27 1 3 / pow", 3.0);
+    }
+
+    #[test]
+    fn should_switch_angle_units() {
+        let mut calc = make_calculator();
+        assert_eq!(Angle::Rad, calc.angle());
+        let mut check = |input: &str, expected: f64| {
+            calc.state.stack.clear();
+            calc.evaluate(input).unwrap();
+            assert!((calc.top().unwrap() - expected).abs() < 1e-12, "{} gave {:?}", input, calc.top());
+        };
+        check("deg 90 sin", 1.0);
+        check("60 cos", 0.5);
+        check("45 tan", 1.0);
+        check("-135 tan", 1.0);
+        check("1 atan", 45.0);
+        check("0.5 asin", 30.0);
+        check("-1 acos", 180.0);
+        check("720 sin 540 cos +", -1.0);
+        check("rad 90 deg 90 sin", 1.0);
+        check("rad 1 atan 4 *", std::f64::consts::PI);
+        calc.evaluate("deg").unwrap();
+        assert_eq!(vec![0.0, 0.0, -1.0, 0.5], ["180 sin", "270 cos", "-90 sin", "-330 sin"].iter().map(|input| {
+            calc.evaluate(input).unwrap();
+            calc.state.stack.pop().unwrap()
+        }).collect::<Vec<f64>>(), "Exact in degrees");
+        for input in &["90 tan", "-270 tan", "2 acos"] {
+            match kind(calc.evaluate(input)) {
+                Err(RpnCalculatorError::DomainError) => (),
+                other => panic!("{} should return DomainError, got {:?}", input, other),
+            }
+            calc.state.stack.clear();
+        }
+    }
+
+    #[test]
+    fn should_keep_exact_trigonometry_exact_in_degrees() {
+        let mut calc: RpnCalculator<Ratio> = RpnCalculator::new();
+        calc.evaluate("deg 30 sin 60 cos + 225 tan").unwrap();
+        assert_eq!(vec!["1", "1"], calc.state.stack.iter().map(|x| x.to_string()).collect::<Vec<String>>());
+        assert!(calc.evaluate("rad 30 sin").is_err());
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        calc.evaluate("deg 0 acos").unwrap();
+        assert_eq!(Some(&90), calc.top());
+        let mut calc = make_calculator();
+        for &(input, expected) in &[("60 sin", 0.75f64.sqrt()), ("30 cos", 0.75f64.sqrt()), ("120 sin", 0.75f64.sqrt()),
+                                     ("-60 sin", -0.75f64.sqrt()), ("150 sin", 0.5), ("-150 sin", -0.5),
+                                     ("120 cos", -0.5), ("330 sin", -0.5)] {
+            calc.evaluate(&format!("deg {}", input)).unwrap();
+            let top = *calc.top().unwrap();
+            assert!((top - expected).abs() < 1e-15, "{} gave {}", input, top);
+        }
+    }
+
+    #[test]
+    fn should_report_domain_errors() {
+        let mut calc = make_calculator();
+        for &(input, expected) in &[("-1 sqrt", "DomainError"), ("0 ln", "DomainError"), ("-10 log", "DomainError"),
+                                     ("2 asin", "DomainError"), ("-1.5 acos", "DomainError"), ("-8 0.5 pow", "DomainError"),
+                                     ("1000 exp", "Overflow"), ("10 400 pow", "Overflow"), ("0 -1 pow", "DivisionByZero")] {
+            calc.state.stack.clear();
+            let result = calc.evaluate(input);
+            assert_eq!(Some(expected.to_string()), result.err().map(|e| format!("{:?}", e.kind())), "{}", input);
+        }
+        // The argument stays on the stack
+        assert_eq!(vec![0.0, -1.0], calc.state.stack);
+    }
+
+    #[test]
+    fn should_keep_exact_types_exact_in_functions() {
+        let mut calc: RpnCalculator<i64> = RpnCalculator::new();
+        calc.evaluate("3 39 pow 10 sqrt -7 abs 7 neg").unwrap();
+        assert_eq!(vec![4052555153018976267, 3, 7, -7], calc.state.stack);
+        assert!(calc.evaluate("2 64 pow").is_err());
+        let mut calc: RpnCalculator<Ratio> = RpnCalculator::new();
+        calc.evaluate("2/3 3 pow 16/9 sqrt").unwrap();
+        assert_eq!("8/27 4/3", calc.state.stack.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(" "));
+        calc.state.stack.clear();
+        match kind(calc.evaluate("2 sqrt")) {
+            Err(RpnCalculatorError::NotSupported) => (),
+            other => panic!("Should return NotSupported, got {:?}", other),
+        }
+        let mut calc: RpnCalculator<Roman> = RpnCalculator::new();
+        calc.evaluate("XII II pow CXLIV sqrt").unwrap();
+        assert_eq!("XII", calc.top().unwrap().to_string());
+        assert!(calc.evaluate("I neg").is_err());
+        let mut calc: RpnCalculator<Complex> = RpnCalculator::new();
+        assert!(calc.evaluate("1+i sin").is_err());
+        calc.state.stack.clear();
+        calc.evaluate("1+i 2 pow neg -4 sqrt 3-4i sqrt").unwrap();
+        assert_eq!("-2i 2i 2-i", calc.state.stack.iter().map(|z| z.to_string()).collect::<Vec<_>>().join(" "));
+    }
+
+    #[test]
+    fn should_apply_functions_to_dimensionless_quantities() {
+        let mut calc: RpnCalculator<Quantity> = RpnCalculator::new();
+        calc.evaluate("3 m 2 m / ln 2 m neg abs").unwrap();
+        assert!((calc.state.stack[0].value - 1.5f64.ln()).abs() < 1e-12);
+        assert_eq!("2 m", calc.top().unwrap().to_string());
+        calc.evaluate("3 m 2 pow").unwrap();
+        assert_eq!("9 m^2", calc.top().unwrap().to_string());
+        for &(input, expected) in &[("2 m sin", "UnitMismatch"), ("4 m^2 neg sqrt", "DomainError")] {
+            let result = calc.evaluate(input);
+            assert_eq!(Some(expected.to_string()), result.err().map(|e| format!("{:?}", e.kind())), "{}", input);
+        }
+    }
 }
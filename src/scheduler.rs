@@ -0,0 +1,245 @@
+//!
+//! # Problem description:
+//! #
+//! # Job Scheduling
+//!
+//! An operating system that runs one job at a time has to choose which of
+//! the waiting jobs goes next whenever the processor is free. Simulate a
+//! stream of jobs, each with an arrival time, a duration and a priority,
+//! under three policies: first come first served, shortest job first,
+//! and highest priority first, and compare how long jobs wait before
+//! they start and how long they take from arrival to completion, their
+//! turnaround.
+//!
+//! Jobs run to completion once started. The jobs that have arrived wait
+//! in a priority queue ordered by the policy, ties going to the earlier
+//! arrival. With every job there from the start, shortest job first gives
+//! the least mean wait of any order; first come first served makes short
+//! jobs wait behind long ones.
+//!
+
+use heap::BinaryHeap;
+use rng::Rng;
+use std::cmp::Reverse;
+use std::fmt;
+
+/// A job to run: time units are arbitrary, but the same for all jobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Job {
+    pub id: usize,
+    pub arrival: u64,
+    pub duration: u64,
+    /// Smaller numbers run first, as with Unix nice values
+    pub priority: u32,
+}
+
+/// How the scheduler picks the next job among those waiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// First come first served
+    Fifo,
+    /// Shortest job first
+    ShortestFirst,
+    /// Smallest priority number first
+    Priority,
+}
+
+/// All the policies, for comparing them
+pub const POLICIES: [Policy; 3] = [Policy::Fifo, Policy::ShortestFirst, Policy::Priority];
+
+impl Policy {
+    /// What the policy orders waiting jobs by, smallest first
+    fn key(self, job: &Job) -> u64 {
+        match self {
+            Policy::Fifo => 0,
+            Policy::ShortestFirst => job.duration,
+            Policy::Priority => job.priority as u64,
+        }
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match *self {
+            Policy::Fifo => "FIFO",
+            Policy::ShortestFirst => "SJF",
+            Policy::Priority => "Priority",
+        })
+    }
+}
+
+/// When a job ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub job: Job,
+    pub start: u64,
+    pub finish: u64,
+}
+
+impl Run {
+    /// The time between the job arriving and starting
+    pub fn wait(&self) -> u64 {
+        self.start - self.job.arrival
+    }
+
+    /// The time between the job arriving and finishing
+    pub fn turnaround(&self) -> u64 {
+        self.finish - self.job.arrival
+    }
+}
+
+/// Runs the jobs under a policy, returning the runs in the order they
+/// happened
+///
+/// # Example
+/// ```
+/// use pprust::scheduler::{schedule, Job, Policy};
+///
+/// let jobs = [Job { id: 0, arrival: 0, duration: 8, priority: 1 },
+///             Job { id: 1, arrival: 1, duration: 2, priority: 0 },
+///             Job { id: 2, arrival: 2, duration: 1, priority: 2 }];
+/// let order = |policy| -> Vec<usize> { schedule(&jobs, policy).iter().map(|r| r.job.id).collect() };
+/// assert_eq!(vec![0, 1, 2], order(Policy::Fifo));
+/// assert_eq!(vec![0, 2, 1], order(Policy::ShortestFirst));
+/// assert_eq!(vec![0, 1, 2], order(Policy::Priority));
+/// ```
+pub fn schedule(jobs: &[Job], policy: Policy) -> Vec<Run> {
+    let mut arrivals = jobs.to_vec();
+    arrivals.sort_by_key(|job| (job.arrival, job.id));
+    let mut arrivals = arrivals.into_iter().peekable();
+    let mut waiting = BinaryHeap::new();
+    let mut runs = Vec::with_capacity(jobs.len());
+    let mut time = 0;
+    loop {
+        if waiting.is_empty() {
+            // The processor idles until the next job arrives
+            match arrivals.peek() {
+                Some(job) => time = time.max(job.arrival),
+                None => return runs,
+            }
+        }
+        while let Some(job) = arrivals.next_if(|job| job.arrival <= time) {
+            waiting.push(Reverse((policy.key(&job), job.arrival, job.id, job)));
+        }
+        let Reverse((_, _, _, job)) = waiting.pop().expect("A job has arrived");
+        runs.push(Run { job, start: time, finish: time + job.duration });
+        time += job.duration;
+    }
+}
+
+/// Summary of a schedule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub mean_wait: f64,
+    pub max_wait: u64,
+    pub mean_turnaround: f64,
+    pub max_turnaround: u64,
+    /// When the last job finished
+    pub makespan: u64,
+}
+
+/// The statistics of the runs of a schedule; all zero with no runs
+pub fn stats(runs: &[Run]) -> Stats {
+    let n = runs.len().max(1) as f64;
+    Stats {
+        mean_wait: runs.iter().map(|r| r.wait() as f64).sum::<f64>() / n,
+        max_wait: runs.iter().map(Run::wait).max().unwrap_or(0),
+        mean_turnaround: runs.iter().map(|r| r.turnaround() as f64).sum::<f64>() / n,
+        max_turnaround: runs.iter().map(Run::turnaround).max().unwrap_or(0),
+        makespan: runs.iter().map(|r| r.finish).max().unwrap_or(0),
+    }
+}
+
+/// The statistics of the jobs under every policy
+///
+/// # Example
+/// ```
+/// use pprust::scheduler::{compare, Job, Policy};
+///
+/// let jobs: Vec<Job> = [10, 1, 1].iter().enumerate()
+///     .map(|(id, &duration)| Job { id, arrival: 0, duration, priority: 0 })
+///     .collect();
+/// let results = compare(&jobs);
+/// assert_eq!((Policy::Fifo, 7.0), (results[0].0, results[0].1.mean_wait));
+/// assert_eq!((Policy::ShortestFirst, 1.0), (results[1].0, results[1].1.mean_wait));
+/// ```
+pub fn compare(jobs: &[Job]) -> Vec<(Policy, Stats)> {
+    POLICIES.iter().map(|&policy| (policy, stats(&schedule(jobs, policy)))).collect()
+}
+
+/// `n` jobs arriving at random, up to `max_gap` apart, with durations
+/// from 1 to `max_duration` and priorities from 0 to `levels - 1`
+pub fn random_jobs(rng: &mut Rng, n: usize, max_gap: u64, max_duration: u64, levels: u32) -> Vec<Job> {
+    let mut arrival = 0;
+    (0..n)
+        .map(|id| {
+            arrival += rng.gen_range(0, max_gap + 1);
+            Job {
+                id,
+                arrival,
+                duration: rng.gen_range(1, max_duration + 1),
+                priority: rng.gen_range(0, levels as u64) as u32,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: usize, arrival: u64, duration: u64, priority: u32) -> Job {
+        Job { id, arrival, duration, priority }
+    }
+
+    #[test]
+    fn should_schedule_the_textbook_example() {
+        let jobs = [job(1, 0, 24, 3), job(2, 0, 3, 1), job(3, 0, 3, 4)];
+        let fifo = schedule(&jobs, Policy::Fifo);
+        assert_eq!(vec![0, 24, 27], fifo.iter().map(Run::wait).collect::<Vec<_>>());
+        assert_eq!(17.0, stats(&fifo).mean_wait);
+        let sjf = stats(&schedule(&jobs, Policy::ShortestFirst));
+        assert_eq!((3.0, 6, 13.0, 30, 30), (sjf.mean_wait, sjf.max_wait, sjf.mean_turnaround, sjf.max_turnaround, sjf.makespan));
+        let priority = schedule(&jobs, Policy::Priority);
+        assert_eq!(vec![2, 1, 3], priority.iter().map(|r| r.job.id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_idle_until_jobs_arrive() {
+        let runs = schedule(&[job(0, 5, 2, 0), job(1, 20, 1, 0), job(2, 6, 1, 0)], Policy::Fifo);
+        let times: Vec<(usize, u64, u64)> = runs.iter().map(|r| (r.job.id, r.start, r.finish)).collect();
+        assert_eq!(vec![(0, 5, 7), (2, 7, 8), (1, 20, 21)], times);
+        assert_eq!(stats(&[]), Stats { mean_wait: 0.0, max_wait: 0, mean_turnaround: 0.0, max_turnaround: 0, makespan: 0 });
+    }
+
+    #[test]
+    fn should_run_every_job_once_without_overlap() {
+        let mut rng = Rng::new(261);
+        for _ in 0..50 {
+            let jobs = random_jobs(&mut rng, 40, 6, 10, 4);
+            for &policy in &POLICIES {
+                let runs = schedule(&jobs, policy);
+                let mut ids: Vec<usize> = runs.iter().map(|r| r.job.id).collect();
+                ids.sort();
+                assert_eq!((0..40).collect::<Vec<_>>(), ids);
+                for pair in runs.windows(2) {
+                    assert!(pair[0].finish <= pair[1].start);
+                }
+                assert!(runs.iter().all(|r| r.start >= r.job.arrival));
+            }
+            // The processor is never idle while jobs wait, whatever the policy
+            let makespans: Vec<u64> = compare(&jobs).iter().map(|&(_, s)| s.makespan).collect();
+            assert!(makespans.iter().all(|&m| m == makespans[0]));
+        }
+    }
+
+    #[test]
+    fn should_minimize_mean_wait_with_shortest_first() {
+        let mut rng = Rng::new(2612);
+        for _ in 0..50 {
+            let jobs = random_jobs(&mut rng, 20, 0, 50, 4);
+            let results = compare(&jobs);
+            assert!(results.iter().all(|&(_, s)| results[1].1.mean_wait <= s.mean_wait));
+        }
+    }
+}
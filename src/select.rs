@@ -0,0 +1,232 @@
+//!
+//! # Problem description:
+//! #
+//! # Selection
+//!
+//! Find the *k*-th smallest item of an unsorted array without sorting
+//! it. Quickselect partitions the array like quicksort but only recurses
+//! into the side holding the *k*-th item, which takes linear time on
+//! average. The median-of-medians algorithm picks its pivot as the
+//! median of the medians of groups of five, which guarantees a good
+//! split and so linear time in the worst case too.
+//!
+//! Both functions leave the slice partially ordered: after the call the
+//! selected item is at index *k*, nothing before it is greater and
+//! nothing after it is smaller.
+//!
+
+/// Returns the `k`-th smallest item (counting from zero), or `None` if
+/// `k` is out of bounds, using quickselect
+///
+/// # Example
+/// ```
+/// use pprust::select::quickselect;
+///
+/// let mut v = vec![9, 2, 7, 4, 5];
+/// assert_eq!(Some(&5), quickselect(&mut v, 2));
+/// assert_eq!(None, quickselect(&mut v, 5));
+/// ```
+pub fn quickselect<T: Ord>(slice: &mut [T], k: usize) -> Option<&T> {
+    quickselect_by(slice, k, |a, b| a < b)
+}
+
+/// `quickselect` with an `is_less` predicate
+pub fn quickselect_by<T, F>(slice: &mut [T], k: usize, mut is_less: F) -> Option<&T>
+    where F: FnMut(&T, &T) -> bool
+{
+    if k >= slice.len() {
+        return None;
+    }
+    select(slice, k, &mut is_less, &mut |s, less| median_of_three(s, less));
+    Some(&slice[k])
+}
+
+/// Returns the `k`-th smallest item (counting from zero), or `None` if
+/// `k` is out of bounds, using the worst-case linear median-of-medians
+/// algorithm
+///
+/// # Example
+/// ```
+/// use pprust::select::median_of_medians;
+///
+/// let mut v: Vec<u32> = (0..100).rev().collect();
+/// assert_eq!(Some(&42), median_of_medians(&mut v, 42));
+/// ```
+pub fn median_of_medians<T: Ord>(slice: &mut [T], k: usize) -> Option<&T> {
+    median_of_medians_by(slice, k, |a, b| a < b)
+}
+
+/// `median_of_medians` with an `is_less` predicate
+pub fn median_of_medians_by<T, F>(slice: &mut [T], k: usize, mut is_less: F) -> Option<&T>
+    where F: FnMut(&T, &T) -> bool
+{
+    if k >= slice.len() {
+        return None;
+    }
+    mom_select(slice, k, &mut is_less);
+    Some(&slice[k])
+}
+
+/// Moves the `k`-th item into place, choosing pivots with `choose_pivot`
+fn select<T, F, P>(mut slice: &mut [T], mut k: usize, is_less: &mut F, choose_pivot: &mut P)
+    where F: FnMut(&T, &T) -> bool, P: FnMut(&mut [T], &mut F) -> usize
+{
+    while slice.len() > 1 {
+        let pivot = choose_pivot(slice, is_less);
+        let (lt, gt) = partition3(slice, pivot, is_less);
+        if k < lt {
+            slice = &mut slice[..lt];
+        } else if k < gt {
+            return;
+        } else {
+            k -= gt;
+            slice = &mut slice[gt..];
+        }
+    }
+}
+
+fn mom_select<T, F>(slice: &mut [T], k: usize, is_less: &mut F)
+    where F: FnMut(&T, &T) -> bool
+{
+    select(slice, k, is_less, &mut |s, less| median_of_medians_pivot(s, less));
+}
+
+/// Gathers the medians of each group of five at the front of the slice
+/// and returns the index of their median
+fn median_of_medians_pivot<T, F>(slice: &mut [T], is_less: &mut F) -> usize
+    where F: FnMut(&T, &T) -> bool
+{
+    if slice.len() <= 5 {
+        insertion_sort(slice, is_less);
+        return slice.len() / 2;
+    }
+    let groups = slice.len().div_ceil(5);
+    for g in 0..groups {
+        let start = 5 * g;
+        let end = (start + 5).min(slice.len());
+        insertion_sort(&mut slice[start..end], is_less);
+        slice.swap(g, start + (end - start) / 2);
+    }
+    let middle = groups / 2;
+    mom_select(&mut slice[..groups], middle, is_less);
+    middle
+}
+
+fn median_of_three<T, F>(slice: &mut [T], is_less: &mut F) -> usize
+    where F: FnMut(&T, &T) -> bool
+{
+    let (a, b, c) = (0, slice.len() / 2, slice.len() - 1);
+    if is_less(&slice[b], &slice[a]) {
+        slice.swap(a, b);
+    }
+    if is_less(&slice[c], &slice[b]) {
+        slice.swap(b, c);
+        if is_less(&slice[b], &slice[a]) {
+            slice.swap(a, b);
+        }
+    }
+    b
+}
+
+fn insertion_sort<T, F>(slice: &mut [T], is_less: &mut F)
+    where F: FnMut(&T, &T) -> bool
+{
+    for i in 1..slice.len() {
+        let mut j = i;
+        while j > 0 && is_less(&slice[j], &slice[j - 1]) {
+            slice.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// Three-way partition around the item at `pivot`, returning the range
+/// of the items equal to it
+fn partition3<T, F>(slice: &mut [T], pivot: usize, is_less: &mut F) -> (usize, usize)
+    where F: FnMut(&T, &T) -> bool
+{
+    slice.swap(0, pivot);
+    let (mut lt, mut i, mut gt) = (0, 1, slice.len());
+    while i < gt {
+        if is_less(&slice[i], &slice[lt]) {
+            slice.swap(lt, i);
+            lt += 1;
+            i += 1;
+        } else if is_less(&slice[lt], &slice[i]) {
+            gt -= 1;
+            slice.swap(i, gt);
+        } else {
+            i += 1;
+        }
+    }
+    (lt, gt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(n: usize, modulus: u64) -> Vec<u64> {
+        let mut x: u64 = 88172645463325252;
+        (0..n).map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x % modulus
+        }).collect()
+    }
+
+    fn check_against_sorting<S>(select: S)
+        where S: Fn(&mut [u64], usize) -> Option<u64>
+    {
+        for &n in &[1, 2, 5, 6, 25, 101, 1000] {
+            for &modulus in &[1, 3, 50, u64::MAX] {
+                let input = pseudo_random(n, modulus);
+                let mut sorted = input.clone();
+                sorted.sort();
+                for &k in &[0, n / 3, n / 2, n - 1] {
+                    let mut v = input.clone();
+                    assert_eq!(Some(sorted[k]), select(&mut v, k), "n = {}, modulus = {}, k = {}", n, modulus, k);
+                    assert!(v[..k].iter().all(|x| *x <= v[k]), "Items before k should not be greater");
+                    assert!(v[k..].iter().all(|x| *x >= v[k]), "Items after k should not be smaller");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_quickselect_like_sorting() {
+        check_against_sorting(|v, k| quickselect(v, k).cloned());
+    }
+
+    #[test]
+    fn should_select_median_of_medians_like_sorting() {
+        check_against_sorting(|v, k| median_of_medians(v, k).cloned());
+    }
+
+    #[test]
+    fn should_return_none_out_of_bounds() {
+        let mut empty: Vec<u64> = Vec::new();
+        assert_eq!(None, quickselect(&mut empty, 0));
+        assert_eq!(None, median_of_medians(&mut empty, 0));
+        assert_eq!(None, quickselect(&mut [1], 1));
+    }
+
+    #[test]
+    fn should_select_with_predicate() {
+        let mut v = vec![1.5, -2.0, 8.25, 0.0];
+        assert_eq!(Some(&1.5), quickselect_by(&mut v, 1, |a, b| a > b));
+        assert_eq!(Some(&-2.0), median_of_medians_by(&mut v, 0, |a, b| a < b));
+    }
+
+    #[test]
+    fn should_make_linear_comparisons_with_median_of_medians() {
+        let mut v: Vec<u64> = (0..10_000).collect();
+        let mut comparisons = 0;
+        median_of_medians_by(&mut v, 5_000, |a, b| {
+            comparisons += 1;
+            a < b
+        });
+        assert!(comparisons < 30 * 10_000, "Made {} comparisons", comparisons);
+    }
+}
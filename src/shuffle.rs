@@ -0,0 +1,65 @@
+//!
+//! # Problem description:
+//! #
+//! # Shuffling
+//!
+//! The Fisher-Yates shuffle walks an array from the end, swapping each
+//! item with one chosen at random from those not yet placed, itself
+//! included. Each of the n! orders comes out with the same probability,
+//! provided the random choices are uniform; the usual mistake of choosing
+//! among all the items at every step favours some orders.
+//!
+
+use rng::Rng;
+
+/// Shuffles a slice in place
+///
+/// # Example
+/// ```
+/// use pprust::rng::Rng;
+/// use pprust::shuffle::shuffle;
+///
+/// let mut deck: Vec<u32> = (0..52).collect();
+/// shuffle(&mut deck, &mut Rng::new(1));
+/// deck.sort();
+/// assert_eq!((0..52).collect::<Vec<u32>>(), deck);
+/// ```
+pub fn shuffle<T>(slice: &mut [T], rng: &mut Rng) {
+    for i in (1..slice.len()).rev() {
+        let j = rng.gen_range(0, i as u64 + 1) as usize;
+        slice.swap(i, j);
+    }
+}
+
+/// A random permutation of `0..n`
+pub fn permutation(n: usize, rng: &mut Rng) -> Vec<usize> {
+    let mut items: Vec<usize> = (0..n).collect();
+    shuffle(&mut items, rng);
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use combinatorics::rank;
+
+    #[test]
+    fn should_produce_every_order_equally() {
+        let mut rng = Rng::new(2024);
+        let mut counts = [0u32; 24];
+        for _ in 0..48_000 {
+            counts[rank(&permutation(4, &mut rng)) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| 1_800 < c && c < 2_200), "{:?}", counts);
+    }
+
+    #[test]
+    fn should_handle_tiny_slices() {
+        let mut rng = Rng::new(0);
+        let mut empty: [u8; 0] = [];
+        shuffle(&mut empty, &mut rng);
+        let mut one = [7];
+        shuffle(&mut one, &mut rng);
+        assert_eq!([7], one);
+    }
+}
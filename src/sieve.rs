@@ -0,0 +1,168 @@
+//!
+//! # Problem description:
+//! #
+//! # Sieve of Eratosthenes
+//!
+//! List the numbers from 2 up to a limit, then repeatedly take the
+//! smallest number not yet crossed out, which is prime, and cross out its
+//! multiples, starting from its square since the smaller ones were
+//! crossed out already by smaller primes. Once the primes reach the
+//! square root of the limit, the numbers left are exactly the primes.
+//!
+//! The table needs a byte for every number up to the limit. The
+//! segmented sieve avoids that: it first finds the primes up to the
+//! square root of the limit, then sieves the range a block at a time,
+//! crossing out in each block the multiples of those primes only. Each
+//! block fits in the processor cache, and the range can start anywhere.
+//!
+
+/// A table telling, for each number from 0 to `limit`, whether it is
+/// prime
+///
+/// # Example
+/// ```
+/// use pprust::sieve::sieve;
+///
+/// let table = sieve(10);
+/// assert_eq!(vec![false, false, true, true, false, true, false, true, false, false, false], table);
+/// ```
+pub fn sieve(limit: usize) -> Vec<bool> {
+    let mut prime = vec![true; limit + 1];
+    prime[0] = false;
+    if limit >= 1 {
+        prime[1] = false;
+    }
+    let mut p = 2;
+    while p * p <= limit {
+        if prime[p] {
+            for multiple in (p * p..=limit).step_by(p) {
+                prime[multiple] = false;
+            }
+        }
+        p += 1;
+    }
+    prime
+}
+
+/// The primes up to `limit`, inclusive
+///
+/// # Example
+/// ```
+/// use pprust::sieve::primes_up_to;
+///
+/// assert_eq!(vec![2, 3, 5, 7, 11, 13], primes_up_to(13));
+/// ```
+pub fn primes_up_to(limit: usize) -> Vec<u64> {
+    sieve(limit).iter().enumerate().filter(|&(_, &p)| p).map(|(n, _)| n as u64).collect()
+}
+
+/// Numbers sieved per block by the segmented sieve
+pub const SEGMENT_SIZE: usize = 1 << 16;
+
+/// A table telling, for each number in `low..high`, whether it is prime,
+/// crossing out multiples of `base`, which must hold every prime up to
+/// the square root of `high`
+///
+/// # Example
+/// ```
+/// use pprust::sieve::{primes_up_to, segment};
+///
+/// let table = segment(90, 100, &primes_up_to(10));
+/// assert_eq!(Some(7), table.iter().position(|&p| p));
+/// ```
+pub fn segment(low: u64, high: u64, base: &[u64]) -> Vec<bool> {
+    let mut prime = vec![true; high.saturating_sub(low) as usize];
+    for n in low..low.max(2).min(high) {
+        prime[(n - low) as usize] = false;
+    }
+    for &p in base {
+        if p * p >= high {
+            break;
+        }
+        let first = (p * p).max(low.div_ceil(p) * p);
+        for multiple in (first..high).step_by(p as usize) {
+            prime[(multiple - low) as usize] = false;
+        }
+    }
+    prime
+}
+
+/// An iterator over the primes in a range, sieved a block at a time
+pub struct Primes {
+    base: Vec<u64>,
+    block: Vec<bool>,
+    block_start: u64,
+    next: u64,
+    high: u64,
+}
+
+/// The primes in `low..high`, by the segmented sieve
+///
+/// # Example
+/// ```
+/// use pprust::sieve::primes_between;
+///
+/// let primes: Vec<u64> = primes_between(1_000_000_000, 1_000_000_100).collect();
+/// assert_eq!(vec![1_000_000_007, 1_000_000_009, 1_000_000_021, 1_000_000_033, 1_000_000_087, 1_000_000_093, 1_000_000_097], primes);
+/// ```
+pub fn primes_between(low: u64, high: u64) -> Primes {
+    let root = (high as f64).sqrt() as u64 + 1;
+    Primes { base: primes_up_to(root as usize), block: Vec::new(), block_start: low, next: low, high }
+}
+
+impl Iterator for Primes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.next < self.high {
+            let end = self.block_start + self.block.len() as u64;
+            if self.next >= end {
+                self.block_start = self.next;
+                let block_end = self.high.min(self.next + SEGMENT_SIZE as u64);
+                self.block = segment(self.block_start, block_end, &self.base);
+            }
+            let n = self.next;
+            self.next += 1;
+            if self.block[(n - self.block_start) as usize] {
+                return Some(n);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prime(n: usize) -> bool {
+        n >= 2 && (2..n).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn should_agree_with_trial_division() {
+        let table = sieve(5000);
+        for (n, &p) in table.iter().enumerate() {
+            assert_eq!(is_prime(n), p, "{}", n);
+        }
+    }
+
+    #[test]
+    fn should_count_primes() {
+        assert_eq!(0, primes_up_to(1).len());
+        assert_eq!(vec![2], primes_up_to(2));
+        assert_eq!(168, primes_up_to(1000).len());
+        assert_eq!(78498, primes_up_to(1_000_000).len());
+    }
+
+    #[test]
+    fn should_sieve_segments_like_the_whole_table() {
+        let table = sieve(300_000);
+        let base = primes_up_to(600);
+        for &(low, high) in &[(0, 10), (1, 2), (2, 3), (100, 100), (17, 1000), (250_000, 300_000)] {
+            assert_eq!(&table[low..high], &segment(low as u64, high as u64, &base)[..], "{}..{}", low, high);
+        }
+        let primes: Vec<u64> = primes_between(0, 300_000).collect();
+        assert_eq!(primes_up_to(299_999), primes);
+    }
+}
@@ -0,0 +1,172 @@
+//!
+//! # Problem description:
+//! #
+//! # Snakes and Ladders
+//!
+//! Players start off the board, on square 0, and take turns rolling a
+//! die and moving forward that many squares. Landing at the foot of a
+//! ladder climbs it; landing on the head of a snake slides down to its
+//! tail. The first to reach the last square wins. Depending on the
+//! house rules, a roll that would go past the last square either wins
+//! anyway or is lost, the player staying put.
+//!
+//! How long does a game last? Simulation gives an estimate, but the game
+//! is a Markov chain whose only absorbing state is the last square, so
+//! the expected number of turns t from each other square satisfies
+//! t = 1 + Q t, where Q holds the probabilities of moving between the
+//! other squares. Solving (I - Q) t = 1 gives the exact answer.
+//!
+
+use matrix::Matrix;
+use rng::Rng;
+use std::collections::BTreeMap;
+
+/// A board: its squares and where each snake and ladder leads
+#[derive(Debug, Clone)]
+pub struct Board {
+    size: usize,
+    jumps: BTreeMap<usize, usize>,
+    exact_finish: bool,
+}
+
+impl Board {
+    /// A board of squares 1 to `size`, with snakes and ladders as
+    /// `(from, to)` pairs; with `exact_finish` a roll past the last
+    /// square is lost
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::snakes::Board;
+    ///
+    /// let board = Board::new(10, &[(3, 8), (9, 2)], true);
+    /// assert_eq!(8, board.advance(1, 2));
+    /// assert_eq!(7, board.advance(7, 6));
+    /// ```
+    pub fn new(size: usize, jumps: &[(usize, usize)], exact_finish: bool) -> Board {
+        assert!(size > 0, "A board needs squares");
+        for &(from, to) in jumps {
+            assert!(from >= 1 && from < size && to >= 1 && to <= size && from != to, "Bad jump {} to {}", from, to);
+        }
+        Board { size, jumps: jumps.iter().cloned().collect(), exact_finish }
+    }
+
+    /// The classic 100-square board, where any roll reaching the last
+    /// square wins
+    pub fn standard() -> Board {
+        let ladders = [(1, 38), (4, 14), (9, 31), (21, 42), (28, 84), (36, 44), (51, 67), (71, 91), (80, 100)];
+        let snakes = [(16, 6), (47, 26), (49, 11), (56, 53), (62, 19), (64, 60), (87, 24), (93, 73), (95, 75), (98, 78)];
+        let jumps: Vec<(usize, usize)> = ladders.iter().chain(snakes.iter()).cloned().collect();
+        Board::new(100, &jumps, false)
+    }
+
+    /// Number of squares
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The square reached from `square` with a roll of `roll`
+    pub fn advance(&self, square: usize, roll: usize) -> usize {
+        let target = square + roll;
+        if target > self.size {
+            return if self.exact_finish { square } else { self.size };
+        }
+        *self.jumps.get(&target).unwrap_or(&target)
+    }
+
+    /// Plays a game alone and returns the number of turns taken
+    pub fn play(&self, rng: &mut Rng) -> usize {
+        let mut square = 0;
+        let mut turns = 0;
+        while square != self.size {
+            square = self.advance(square, rng.gen_range(1, 7) as usize);
+            turns += 1;
+        }
+        turns
+    }
+
+    /// Estimates the expected number of turns by playing `games` games
+    pub fn simulate(&self, games: usize, seed: u64) -> f64 {
+        let mut rng = Rng::new(seed);
+        let total: usize = (0..games).map(|_| self.play(&mut rng)).sum();
+        total as f64 / games as f64
+    }
+
+    /// The probabilities of moving between squares in one turn, for
+    /// squares 0 to `size`
+    pub fn transition_matrix(&self) -> Matrix {
+        let mut p = Matrix::zeros(self.size + 1, self.size + 1);
+        for square in 0..self.size {
+            for roll in 1..=6 {
+                p[(square, self.advance(square, roll))] += 1.0 / 6.0;
+            }
+        }
+        p[(self.size, self.size)] = 1.0;
+        p
+    }
+
+    /// The exact expected number of turns from the start, from the
+    /// absorbing Markov chain
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::snakes::Board;
+    ///
+    /// let board = Board::standard();
+    /// let exact = board.expected_turns();
+    /// assert!((board.simulate(20_000, 1) - exact).abs() < 1.0);
+    /// ```
+    pub fn expected_turns(&self) -> f64 {
+        let p = self.transition_matrix();
+        let n = self.size;
+        let mut a = Matrix::identity(n);
+        for i in 0..n {
+            for j in 0..n {
+                a[(i, j)] -= p[(i, j)];
+            }
+        }
+        let turns = a.solve(&vec![1.0; n]).expect("The last square can be reached from every square");
+        turns[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_follow_snakes_and_ladders() {
+        let board = Board::standard();
+        assert_eq!(38, board.advance(0, 1));
+        assert_eq!(6, board.advance(10, 6));
+        assert_eq!(100, board.advance(97, 5));
+        assert_eq!(78, board.advance(97, 1));
+    }
+
+    #[test]
+    fn should_compute_small_boards_exactly() {
+        assert!((Board::new(1, &[], false).expected_turns() - 1.0).abs() < 1e-12);
+        // From 0 a roll of 1 needs one more turn; anything else finishes
+        assert!((Board::new(2, &[], false).expected_turns() - 7.0 / 6.0).abs() < 1e-12);
+        // With an exact finish, each turn finishes with probability 1/6
+        assert!((Board::new(6, &[(1, 6), (2, 6), (3, 6), (4, 6), (5, 6)], true).expected_turns() - 1.0).abs() < 1e-12);
+        assert!((Board::new(7, &[(1, 6), (2, 6), (3, 6), (4, 6), (5, 6)], true).expected_turns() - 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_agree_with_simulation() {
+        for board in &[Board::standard(), Board { exact_finish: true, ..Board::standard() }] {
+            let exact = board.expected_turns();
+            let simulated = board.simulate(20_000, 7);
+            assert!((simulated - exact).abs() / exact < 0.03, "Exact {}, simulated {}", exact, simulated);
+        }
+    }
+
+    #[test]
+    fn should_have_stochastic_rows() {
+        let p = Board::standard().transition_matrix();
+        for i in 0..p.rows() {
+            let sum: f64 = (0..p.cols()).map(|j| p[(i, j)]).sum();
+            assert!((sum - 1.0).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,750 @@
+//!
+//! # Problem description:
+//! #
+//! # Sorting
+//!
+//! Implement the classic sorting algorithms: insertion sort, selection
+//! sort, shell sort, heapsort and quicksort, the last with median-of-three pivot
+//! selection and a fallback to heapsort when the recursion gets too deep
+//! (introsort). Count the comparisons and swaps each one makes, so the
+//! algorithms can be compared empirically instead of only by their
+//! asymptotic bounds.
+//!
+//! # Mergesort
+//!
+//! Implement mergesort bottom-up, merging runs of width 1, 2, 4 and so
+//! on, and natural mergesort, which starts from the runs already present
+//! in the input and so sorts presorted data in linear time. Both must be
+//! stable. Then write a variant that only uses a small, fixed-size merge
+//! buffer, falling back to merging by rotations when a run does not fit.
+//!
+//! # Dutch National Flag
+//!
+//! Given an array of red, white and blue items, rearrange it so that all
+//! the reds come first, then the whites, then the blues, in a single
+//! pass and without extra storage. This is Dijkstra's Dutch national
+//! flag problem, and it is exactly the partitioning step a quicksort
+//! needs to stay fast on inputs with many duplicate keys: the items
+//! equal to the pivot are gathered in the middle and never looked at
+//! again.
+//!
+
+use heap;
+use std::cmp::Ordering;
+
+pub mod radix;
+
+/// Partitions `slice` into the items less than, equal to and greater
+/// than `pivot`, in that order, returning the range `(lt, gt)` of the
+/// items equal to the pivot.
+///
+/// # Example
+/// ```
+/// use pprust::sort::dutch_flag;
+///
+/// let mut flags = vec![2, 0, 1, 2, 1, 0];
+/// let (lt, gt) = dutch_flag(&mut flags, &1);
+/// assert_eq!(vec![0, 0, 1, 1, 2, 2], flags);
+/// assert_eq!((2, 4), (lt, gt));
+/// ```
+pub fn dutch_flag<T: Ord>(slice: &mut [T], pivot: &T) -> (usize, usize) {
+    let mut lt = 0;
+    let mut i = 0;
+    let mut gt = slice.len();
+    while i < gt {
+        match slice[i].cmp(pivot) {
+            Ordering::Less => {
+                slice.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                slice.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (lt, gt)
+}
+
+/// Like `dutch_flag`, but using the item at `pivot` as the pivot, so the
+/// pivot value does not have to be copied out of the slice.
+fn partition3<T: Ord>(slice: &mut [T], pivot: usize) -> (usize, usize) {
+    slice.swap(0, pivot);
+    // slice[lt] is always equal to the pivot, as slice[lt..i] holds the equal items
+    let mut lt = 0;
+    let mut i = 1;
+    let mut gt = slice.len();
+    while i < gt {
+        match slice[i].cmp(&slice[lt]) {
+            Ordering::Less => {
+                slice.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                slice.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (lt, gt)
+}
+
+/// Sorts a slice with a three-way partitioning quicksort.
+///
+/// Inputs with few distinct keys sort in linear time, since each
+/// partitioning step removes every copy of its pivot.
+///
+/// # Example
+/// ```
+/// use pprust::sort::quicksort3;
+///
+/// let mut v = vec![3, 1, 3, 3, 2, 1, 3];
+/// quicksort3(&mut v);
+/// assert_eq!(vec![1, 1, 2, 3, 3, 3, 3], v);
+/// ```
+pub fn quicksort3<T: Ord>(mut slice: &mut [T]) {
+    // Recurse into the smaller side and loop over the larger one to bound the stack depth
+    while slice.len() > 1 {
+        let middle = slice.len() / 2;
+        let (lt, gt) = partition3(slice, middle);
+        let (left, rest) = slice.split_at_mut(lt);
+        let right = &mut rest[gt - lt..];
+        if left.len() < right.len() {
+            quicksort3(left);
+            slice = right;
+        } else {
+            quicksort3(right);
+            slice = left;
+        }
+    }
+}
+
+/// Sorts a slice with a stable bottom-up mergesort using a buffer as
+/// large as the input
+///
+/// # Example
+/// ```
+/// use pprust::sort::mergesort;
+///
+/// let mut v = vec![5, 2, 4, 1, 3];
+/// mergesort(&mut v);
+/// assert_eq!(vec![1, 2, 3, 4, 5], v);
+/// ```
+pub fn mergesort<T: Ord + Clone>(slice: &mut [T]) {
+    mergesort_by(slice, |a, b| a < b)
+}
+
+/// `mergesort` with an `is_less` predicate
+pub fn mergesort_by<T, F>(slice: &mut [T], mut is_less: F)
+    where T: Clone, F: FnMut(&T, &T) -> bool
+{
+    let len = slice.len();
+    let mut buffer = Vec::with_capacity(len / 2 + 1);
+    let mut width = 1;
+    while width < len {
+        let mut start = 0;
+        while start + width < len {
+            let end = (start + 2 * width).min(len);
+            merge_buffered(&mut slice[start..end], width, &mut buffer, &mut is_less);
+            start = end;
+        }
+        width *= 2;
+    }
+}
+
+/// Sorts a slice with a stable natural mergesort, merging the ascending
+/// runs already present in the input
+///
+/// # Example
+/// ```
+/// use pprust::sort::natural_mergesort;
+///
+/// let mut v = vec![1, 2, 3, 7, 4, 5, 6];
+/// natural_mergesort(&mut v);
+/// assert_eq!(vec![1, 2, 3, 4, 5, 6, 7], v);
+/// ```
+pub fn natural_mergesort<T: Ord + Clone>(slice: &mut [T]) {
+    natural_mergesort_by(slice, |a, b| a < b)
+}
+
+/// `natural_mergesort` with an `is_less` predicate
+pub fn natural_mergesort_by<T, F>(slice: &mut [T], mut is_less: F)
+    where T: Clone, F: FnMut(&T, &T) -> bool
+{
+    let len = slice.len();
+    let mut runs = vec![0];
+    for i in 1..len {
+        if is_less(&slice[i], &slice[i - 1]) {
+            runs.push(i);
+        }
+    }
+    runs.push(len);
+    let mut buffer = Vec::new();
+    while runs.len() > 2 {
+        let mut merged = vec![0];
+        for pair in runs[1..].chunks(2) {
+            let start = *merged.last().expect("Always starts with 0");
+            if pair.len() == 2 {
+                merge_buffered(&mut slice[start..pair[1]], pair[0] - start, &mut buffer, &mut is_less);
+            }
+            merged.push(*pair.last().expect("Chunks are never empty"));
+        }
+        runs = merged;
+    }
+}
+
+/// Sorts a slice with a stable mergesort that uses a merge buffer of at
+/// most `buffer_len` items; runs that do not fit are merged in place by
+/// rotations, at the cost of extra moves.
+///
+/// # Example
+/// ```
+/// use pprust::sort::mergesort_with_buffer;
+///
+/// let mut v = vec![9, 3, 7, 1, 8, 2];
+/// mergesort_with_buffer(&mut v, 2);
+/// assert_eq!(vec![1, 2, 3, 7, 8, 9], v);
+/// ```
+pub fn mergesort_with_buffer<T: Ord + Clone>(slice: &mut [T], buffer_len: usize) {
+    mergesort_with_buffer_by(slice, buffer_len, |a, b| a < b)
+}
+
+/// `mergesort_with_buffer` with an `is_less` predicate
+pub fn mergesort_with_buffer_by<T, F>(slice: &mut [T], buffer_len: usize, mut is_less: F)
+    where T: Clone, F: FnMut(&T, &T) -> bool
+{
+    let len = slice.len();
+    let mut buffer = Vec::with_capacity(buffer_len);
+    let mut width = 1;
+    while width < len {
+        let mut start = 0;
+        while start + width < len {
+            let end = (start + 2 * width).min(len);
+            merge_small_buffer(&mut slice[start..end], width, buffer_len, &mut buffer, &mut is_less);
+            start = end;
+        }
+        width *= 2;
+    }
+}
+
+/// Merges the sorted halves `slice[..mid]` and `slice[mid..]`, copying
+/// only the left half into the buffer
+fn merge_buffered<T, F>(slice: &mut [T], mid: usize, buffer: &mut Vec<T>, is_less: &mut F)
+    where T: Clone, F: FnMut(&T, &T) -> bool
+{
+    buffer.clear();
+    buffer.extend_from_slice(&slice[..mid]);
+    let (mut i, mut j, mut k) = (0, mid, 0);
+    while i < buffer.len() && j < slice.len() {
+        // Take from the right only when strictly less, to keep the merge stable
+        if is_less(&slice[j], &buffer[i]) {
+            slice[k] = slice[j].clone();
+            j += 1;
+        } else {
+            slice[k] = buffer[i].clone();
+            i += 1;
+        }
+        k += 1;
+    }
+    while i < buffer.len() {
+        slice[k] = buffer[i].clone();
+        i += 1;
+        k += 1;
+    }
+}
+
+fn merge_small_buffer<T, F>(slice: &mut [T], mid: usize, buffer_len: usize, buffer: &mut Vec<T>, is_less: &mut F)
+    where T: Clone, F: FnMut(&T, &T) -> bool
+{
+    let len = slice.len();
+    if mid == 0 || mid == len {
+        return;
+    }
+    if mid <= buffer_len {
+        merge_buffered(slice, mid, buffer, is_less);
+        return;
+    }
+    if len == 2 {
+        if is_less(&slice[1], &slice[0]) {
+            slice.swap(0, 1);
+        }
+        return;
+    }
+    // Split the larger run in half, find where its middle item goes in the
+    // other run, and rotate so both halves can be merged independently
+    let (first_cut, second_cut) = if mid > len - mid {
+        let first_cut = mid / 2;
+        let second_cut = mid + slice[mid..].iter().take_while(|x| is_less(x, &slice[first_cut])).count();
+        (first_cut, second_cut)
+    } else {
+        let second_cut = mid + (len - mid) / 2;
+        let first_cut = slice[..mid].iter().take_while(|x| !is_less(&slice[second_cut], x)).count();
+        (first_cut, second_cut)
+    };
+    slice[first_cut..second_cut].rotate_left(mid - first_cut);
+    let new_mid = first_cut + (second_cut - mid);
+    merge_small_buffer(&mut slice[..new_mid], first_cut, buffer_len, buffer, is_less);
+    merge_small_buffer(&mut slice[new_mid..], second_cut - new_mid, buffer_len, buffer, is_less);
+}
+
+/// Sorts a slice with heapsort: in place, unstable and O(n log n) even
+/// in the worst case
+///
+/// # Example
+/// ```
+/// use pprust::sort::heapsort;
+///
+/// let mut v = vec![4, 1, 3, 1];
+/// heapsort(&mut v);
+/// assert_eq!(vec![1, 1, 3, 4], v);
+/// ```
+pub fn heapsort<T: Ord>(slice: &mut [T]) {
+    HeapSort.sort(slice);
+}
+
+/// Comparisons and swaps made by a sort
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of times the ordering predicate was called
+    pub comparisons: u64,
+    /// Number of times two items were exchanged
+    pub swaps: u64,
+}
+
+/// An ordering predicate that counts how it is used
+struct Counter<F> {
+    is_less: F,
+    stats: Stats,
+}
+
+impl<F> Counter<F> {
+    fn less<T>(&mut self, a: &T, b: &T) -> bool
+        where F: FnMut(&T, &T) -> bool
+    {
+        self.stats.comparisons += 1;
+        (self.is_less)(a, b)
+    }
+
+    fn swap<T>(&mut self, slice: &mut [T], i: usize, j: usize) {
+        self.stats.swaps += 1;
+        slice.swap(i, j);
+    }
+}
+
+/// A sorting algorithm
+///
+/// # Example
+/// ```
+/// use pprust::sort::{Sorter, InsertionSort};
+///
+/// let mut v = vec![3, 1, 2];
+/// let stats = InsertionSort.sort(&mut v);
+/// assert_eq!(vec![1, 2, 3], v);
+/// assert_eq!(2, stats.swaps);
+/// ```
+pub trait Sorter {
+    /// The name of the algorithm, for reports
+    fn name(&self) -> &'static str;
+
+    /// Sorts a slice with an `is_less` predicate, returning how many
+    /// comparisons and swaps were made
+    fn sort_by<T, F>(&self, slice: &mut [T], is_less: F) -> Stats
+        where F: FnMut(&T, &T) -> bool;
+
+    /// Sorts a slice in ascending order
+    fn sort<T: Ord>(&self, slice: &mut [T]) -> Stats {
+        self.sort_by(slice, |a, b| a < b)
+    }
+}
+
+/// Insertion sort: quadratic, but linear on nearly sorted input
+pub struct InsertionSort;
+
+/// Selection sort: always quadratic comparisons, at most n swaps
+pub struct SelectionSort;
+
+/// Shell sort with Ciura's gap sequence
+pub struct ShellSort;
+
+/// Heapsort, built on the sift routines of the `heap` module
+pub struct HeapSort;
+
+/// Introsort: quicksort with median-of-three pivots, insertion sort for
+/// small slices and heapsort when the recursion gets too deep
+pub struct QuickSort;
+
+impl Sorter for InsertionSort {
+    fn name(&self) -> &'static str {
+        "insertion"
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], is_less: F) -> Stats
+        where F: FnMut(&T, &T) -> bool
+    {
+        let mut counter = Counter { is_less, stats: Stats::default() };
+        gapped_insertion(slice, 1, &mut counter);
+        counter.stats
+    }
+}
+
+impl Sorter for SelectionSort {
+    fn name(&self) -> &'static str {
+        "selection"
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], is_less: F) -> Stats
+        where F: FnMut(&T, &T) -> bool
+    {
+        let mut counter = Counter { is_less, stats: Stats::default() };
+        for i in 0..slice.len() {
+            let mut min = i;
+            for j in i + 1..slice.len() {
+                if counter.less(&slice[j], &slice[min]) {
+                    min = j;
+                }
+            }
+            if min != i {
+                counter.swap(slice, i, min);
+            }
+        }
+        counter.stats
+    }
+}
+
+impl Sorter for ShellSort {
+    fn name(&self) -> &'static str {
+        "shell"
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], is_less: F) -> Stats
+        where F: FnMut(&T, &T) -> bool
+    {
+        let mut counter = Counter { is_less, stats: Stats::default() };
+        for gap in shell_gaps(slice.len()).into_iter().rev() {
+            gapped_insertion(slice, gap, &mut counter);
+        }
+        counter.stats
+    }
+}
+
+impl Sorter for HeapSort {
+    fn name(&self) -> &'static str {
+        "heapsort"
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], is_less: F) -> Stats
+        where F: FnMut(&T, &T) -> bool
+    {
+        let mut counter = Counter { is_less, stats: Stats::default() };
+        heapsort_counted(slice, &mut counter);
+        counter.stats
+    }
+}
+
+impl Sorter for QuickSort {
+    fn name(&self) -> &'static str {
+        "quicksort"
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], is_less: F) -> Stats
+        where F: FnMut(&T, &T) -> bool
+    {
+        let mut counter = Counter { is_less, stats: Stats::default() };
+        let depth = 2 * (usize::BITS - slice.len().leading_zeros()) as usize;
+        introsort(slice, depth, &mut counter);
+        counter.stats
+    }
+}
+
+/// Ciura's gaps, extended geometrically for large inputs, in ascending order
+fn shell_gaps(len: usize) -> Vec<usize> {
+    let mut gaps: Vec<usize> = [1, 4, 10, 23, 57, 132, 301, 701].iter().cloned().take_while(|&g| g < len.max(2)).collect();
+    let mut next = 1750;
+    while next < len {
+        gaps.push(next);
+        next = next * 9 / 4;
+    }
+    gaps
+}
+
+fn gapped_insertion<T, F>(slice: &mut [T], gap: usize, counter: &mut Counter<F>)
+    where F: FnMut(&T, &T) -> bool
+{
+    for i in gap..slice.len() {
+        let mut j = i;
+        while j >= gap && counter.less(&slice[j], &slice[j - gap]) {
+            counter.swap(slice, j, j - gap);
+            j -= gap;
+        }
+    }
+}
+
+/// Slices this short are finished with insertion sort
+const INSERTION_THRESHOLD: usize = 16;
+
+fn introsort<T, F>(mut slice: &mut [T], mut depth: usize, counter: &mut Counter<F>)
+    where F: FnMut(&T, &T) -> bool
+{
+    loop {
+        if slice.len() <= INSERTION_THRESHOLD {
+            gapped_insertion(slice, 1, counter);
+            return;
+        }
+        if depth == 0 {
+            heapsort_counted(slice, counter);
+            return;
+        }
+        depth -= 1;
+        let p = partition(slice, counter);
+        let (left, right) = slice.split_at_mut(p);
+        let right = &mut right[1..];
+        if left.len() < right.len() {
+            introsort(left, depth, counter);
+            slice = right;
+        } else {
+            introsort(right, depth, counter);
+            slice = left;
+        }
+    }
+}
+
+/// Orders the first, middle and last items and returns the index of the median
+fn median_of_three<T, F>(slice: &mut [T], counter: &mut Counter<F>) -> usize
+    where F: FnMut(&T, &T) -> bool
+{
+    let (a, b, c) = (0, slice.len() / 2, slice.len() - 1);
+    if counter.less(&slice[b], &slice[a]) {
+        counter.swap(slice, a, b);
+    }
+    if counter.less(&slice[c], &slice[b]) {
+        counter.swap(slice, b, c);
+        if counter.less(&slice[b], &slice[a]) {
+            counter.swap(slice, a, b);
+        }
+    }
+    b
+}
+
+/// Hoare partition around the median of three, returning the final
+/// index of the pivot. Items equal to the pivot stop both scans, so runs
+/// of duplicates are split evenly.
+fn partition<T, F>(slice: &mut [T], counter: &mut Counter<F>) -> usize
+    where F: FnMut(&T, &T) -> bool
+{
+    let m = median_of_three(slice, counter);
+    counter.swap(slice, 0, m);
+    let mut i = 1;
+    let mut j = slice.len() - 1;
+    loop {
+        while i <= j && counter.less(&slice[i], &slice[0]) {
+            i += 1;
+        }
+        while i <= j && counter.less(&slice[0], &slice[j]) {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        counter.swap(slice, i, j);
+        i += 1;
+        j -= 1;
+    }
+    counter.swap(slice, 0, j);
+    j
+}
+
+fn heapsort_counted<T, F>(slice: &mut [T], counter: &mut Counter<F>)
+    where F: FnMut(&T, &T) -> bool
+{
+    let mut comparisons = 0;
+    let mut swaps = 0;
+    {
+        let is_less = &mut counter.is_less;
+        let mut less = |a: &T, b: &T| {
+            comparisons += 1;
+            is_less(a, b)
+        };
+        swaps += heap::heapify_by(slice, &mut less);
+        for end in (1..slice.len()).rev() {
+            slice.swap(0, end);
+            swaps += 1 + heap::sift_down_by(&mut slice[..end], 0, &mut less);
+        }
+    }
+    counter.stats.comparisons += comparisons;
+    counter.stats.swaps += swaps as u64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(n: usize, modulus: u64) -> Vec<u64> {
+        let mut x: u64 = 88172645463325252;
+        (0..n).map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x % modulus
+        }).collect()
+    }
+
+    #[test]
+    fn should_partition_around_pivot() {
+        let mut v = vec![5, 1, 9, 5, 3, 5, 7];
+        let (lt, gt) = dutch_flag(&mut v, &5);
+        assert!(v[..lt].iter().all(|&x| x < 5));
+        assert!(v[lt..gt].iter().all(|&x| x == 5));
+        assert!(v[gt..].iter().all(|&x| x > 5));
+        assert_eq!((2, 5), (lt, gt));
+    }
+
+    #[test]
+    fn should_partition_with_absent_pivot() {
+        let mut v = vec![4, 8, 2, 6];
+        assert_eq!((2, 2), dutch_flag(&mut v, &5));
+    }
+
+    #[test]
+    fn should_partition_empty_slice() {
+        let mut v: Vec<i32> = Vec::new();
+        assert_eq!((0, 0), dutch_flag(&mut v, &1));
+    }
+
+    #[test]
+    fn should_sort_like_std() {
+        for &modulus in &[2, 10, 1000, u64::MAX] {
+            let mut v = pseudo_random(1000, modulus);
+            let mut expected = v.clone();
+            expected.sort();
+            quicksort3(&mut v);
+            assert_eq!(expected, v, "Failed with modulus {}", modulus);
+        }
+    }
+
+    #[test]
+    fn should_sort_small_and_sorted_inputs() {
+        let mut empty: Vec<i32> = vec![];
+        quicksort3(&mut empty);
+        let mut single = vec![1];
+        quicksort3(&mut single);
+        assert_eq!(vec![1], single);
+        let mut sorted: Vec<i32> = (0..100).collect();
+        quicksort3(&mut sorted);
+        assert_eq!((0..100).collect::<Vec<i32>>(), sorted);
+        let mut reversed: Vec<i32> = (0..100).rev().collect();
+        quicksort3(&mut reversed);
+        assert_eq!((0..100).collect::<Vec<i32>>(), reversed);
+    }
+
+    fn check_sorter<S: Sorter>(sorter: S) {
+        for &n in &[0, 1, 2, 3, 17, 100, 1000] {
+            for &modulus in &[1, 3, 1000, u64::MAX] {
+                let mut v = pseudo_random(n, modulus);
+                let mut expected = v.clone();
+                expected.sort();
+                sorter.sort(&mut v);
+                assert_eq!(expected, v, "{} failed with n = {}, modulus = {}", sorter.name(), n, modulus);
+            }
+        }
+        let mut v = pseudo_random(100, 50);
+        sorter.sort_by(&mut v, |a, b| a > b);
+        assert!(v.windows(2).all(|w| w[0] >= w[1]), "{} failed sorting in reverse", sorter.name());
+    }
+
+    #[test]
+    fn should_sort_with_every_algorithm() {
+        check_sorter(InsertionSort);
+        check_sorter(SelectionSort);
+        check_sorter(ShellSort);
+        check_sorter(HeapSort);
+        check_sorter(QuickSort);
+    }
+
+    #[test]
+    fn should_count_insertion_sort_work() {
+        let mut sorted: Vec<u32> = (0..10).collect();
+        assert_eq!(Stats { comparisons: 9, swaps: 0 }, InsertionSort.sort(&mut sorted));
+        let mut reversed: Vec<u32> = (0..10).rev().collect();
+        assert_eq!(Stats { comparisons: 45, swaps: 45 }, InsertionSort.sort(&mut reversed));
+    }
+
+    #[test]
+    fn should_count_selection_sort_work() {
+        let mut reversed: Vec<u32> = (0..10).rev().collect();
+        let stats = SelectionSort.sort(&mut reversed);
+        assert_eq!(45, stats.comparisons);
+        assert_eq!(5, stats.swaps);
+    }
+
+    #[test]
+    fn should_make_fewer_comparisons_with_faster_algorithms() {
+        let input = pseudo_random(2000, u64::MAX);
+        let count = |sorter: &dyn Fn(&mut Vec<u64>) -> Stats| sorter(&mut input.clone()).comparisons;
+        let insertion = count(&|v| InsertionSort.sort(v));
+        let shell = count(&|v| ShellSort.sort(v));
+        let quick = count(&|v| QuickSort.sort(v));
+        assert!(shell < insertion / 10, "shell {} vs insertion {}", shell, insertion);
+        assert!(quick < shell, "quick {} vs shell {}", quick, shell);
+    }
+
+    #[test]
+    fn should_fall_back_to_heapsort_when_too_deep() {
+        let mut v = pseudo_random(500, 100);
+        let mut expected = v.clone();
+        expected.sort();
+        let mut counter = Counter { is_less: |a: &u64, b: &u64| a < b, stats: Stats::default() };
+        introsort(&mut v, 0, &mut counter);
+        assert_eq!(expected, v);
+        assert!(counter.stats.comparisons > 0 && counter.stats.swaps > 0);
+    }
+
+    /// Records sorted by key only; the sequence number checks stability
+    fn keyed_records(n: usize, keys: u64) -> Vec<(u64, usize)> {
+        pseudo_random(n, keys).into_iter().enumerate().map(|(i, k)| (k, i)).collect()
+    }
+
+    fn check_stable_sort<S: Fn(&mut Vec<(u64, usize)>)>(name: &str, sort: S) {
+        for &n in &[0, 1, 2, 5, 64, 1000] {
+            for &keys in &[1, 4, 100, u64::MAX] {
+                let mut records = keyed_records(n, keys);
+                let mut expected = records.clone();
+                expected.sort_by_key(|r| r.0);
+                sort(&mut records);
+                assert_eq!(expected, records, "{} failed with n = {}, keys = {}", name, n, keys);
+            }
+        }
+    }
+
+    #[test]
+    fn should_mergesort_stably() {
+        check_stable_sort("mergesort", |v| mergesort_by(v, |a, b| a.0 < b.0));
+    }
+
+    #[test]
+    fn should_natural_mergesort_stably() {
+        check_stable_sort("natural mergesort", |v| natural_mergesort_by(v, |a, b| a.0 < b.0));
+    }
+
+    #[test]
+    fn should_mergesort_stably_with_small_buffers() {
+        for &buffer in &[0, 1, 3, 16] {
+            check_stable_sort("small buffer mergesort", |v| mergesort_with_buffer_by(v, buffer, |a, b| a.0 < b.0));
+        }
+    }
+
+    #[test]
+    fn should_natural_mergesort_presorted_runs_in_one_pass() {
+        let mut comparisons = 0;
+        let mut v: Vec<u32> = (0..100).chain(0..100).collect();
+        natural_mergesort_by(&mut v, |a, b| {
+            comparisons += 1;
+            a < b
+        });
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+        assert!(comparisons < 400, "Should detect runs and merge once, made {} comparisons", comparisons);
+    }
+}
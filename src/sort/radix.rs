@@ -0,0 +1,214 @@
+//!
+//! # Radix Sorting
+//!
+//! Radix sorts never compare two keys: they look at one digit of a key
+//! at a time. LSD (least significant digit first) radix sort makes one
+//! stable counting-sort pass per byte of a fixed-width integer key, so
+//! it runs in linear time. For strings, whose lengths vary, MSD radix
+//! sort looks at the first character, then the second, and so on; the
+//! three-way variant partitions around a pivot character like quicksort,
+//! which avoids the cost of 256 buckets on small subarrays.
+//!
+
+/// Fixed-width keys that LSD radix sort can take apart byte by byte
+pub trait RadixKey: Copy {
+    /// Number of bytes in the key
+    const BYTES: usize;
+
+    /// The `i`-th byte of the key, least significant first
+    fn byte(&self, i: usize) -> u8;
+}
+
+impl RadixKey for u32 {
+    const BYTES: usize = 4;
+
+    fn byte(&self, i: usize) -> u8 {
+        (*self >> (8 * i)) as u8
+    }
+}
+
+impl RadixKey for u64 {
+    const BYTES: usize = 8;
+
+    fn byte(&self, i: usize) -> u8 {
+        (*self >> (8 * i)) as u8
+    }
+}
+
+/// Sorts integer keys with LSD radix sort, one pass per byte
+///
+/// # Example
+/// ```
+/// use pprust::sort::radix::lsd_sort;
+///
+/// let mut v: Vec<u32> = vec![70000, 3, 256, 1];
+/// lsd_sort(&mut v);
+/// assert_eq!(vec![1, 3, 256, 70000], v);
+/// ```
+pub fn lsd_sort<K: RadixKey>(keys: &mut [K]) {
+    lsd_sort_by_key(keys, |&k| k)
+}
+
+/// Sorts items by an integer key with a stable LSD radix sort
+pub fn lsd_sort_by_key<T, K, F>(items: &mut [T], key: F)
+    where T: Copy, K: RadixKey, F: Fn(&T) -> K
+{
+    if items.len() < 2 {
+        return;
+    }
+    let mut buffer = items.to_vec();
+    for byte in 0..K::BYTES {
+        let mut counts = [0usize; 257];
+        for item in items.iter() {
+            counts[key(item).byte(byte) as usize + 1] += 1;
+        }
+        // Every item has the same byte here, so this pass would not move anything
+        if counts.contains(&items.len()) {
+            continue;
+        }
+        for i in 0..256 {
+            counts[i + 1] += counts[i];
+        }
+        for item in items.iter() {
+            let b = key(item).byte(byte) as usize;
+            buffer[counts[b]] = *item;
+            counts[b] += 1;
+        }
+        items.copy_from_slice(&buffer);
+    }
+}
+
+/// Sorts strings (or any byte sequences) with three-way radix
+/// quicksort, in byte-wise lexicographic order
+///
+/// # Example
+/// ```
+/// use pprust::sort::radix::msd_sort;
+///
+/// let mut words = vec!["she", "sells", "seashells", "by", "the", "sea"];
+/// msd_sort(&mut words);
+/// assert_eq!(vec!["by", "sea", "seashells", "sells", "she", "the"], words);
+/// ```
+pub fn msd_sort<S: AsRef<[u8]>>(strings: &mut [S]) {
+    three_way_sort(strings, 0);
+}
+
+/// Subarrays this short are finished with insertion sort
+const INSERTION_THRESHOLD: usize = 10;
+
+/// The byte at `depth`, or -1 past the end of the string so shorter
+/// strings sort first
+fn byte_at<S: AsRef<[u8]>>(s: &S, depth: usize) -> i32 {
+    s.as_ref().get(depth).map_or(-1, |&b| b as i32)
+}
+
+fn three_way_sort<S: AsRef<[u8]>>(mut strings: &mut [S], mut depth: usize) {
+    loop {
+        if strings.len() <= INSERTION_THRESHOLD {
+            insertion_sort(strings, depth);
+            return;
+        }
+        let pivot = byte_at(&strings[strings.len() / 2], depth);
+        let (mut lt, mut i, mut gt) = (0, 0, strings.len());
+        while i < gt {
+            let b = byte_at(&strings[i], depth);
+            if b < pivot {
+                strings.swap(lt, i);
+                lt += 1;
+                i += 1;
+            } else if b > pivot {
+                gt -= 1;
+                strings.swap(i, gt);
+            } else {
+                i += 1;
+            }
+        }
+        let (less, rest) = strings.split_at_mut(lt);
+        let (equal, greater) = rest.split_at_mut(gt - lt);
+        three_way_sort(less, depth);
+        three_way_sort(greater, depth);
+        // Strings that ended at this depth are all equal, so are already sorted
+        if pivot < 0 {
+            return;
+        }
+        strings = equal;
+        depth += 1;
+    }
+}
+
+fn insertion_sort<S: AsRef<[u8]>>(strings: &mut [S], depth: usize) {
+    for i in 1..strings.len() {
+        let mut j = i;
+        while j > 0 && strings[j].as_ref()[depth..] < strings[j - 1].as_ref()[depth..] {
+            strings.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(n: usize) -> Vec<u64> {
+        let mut x: u64 = 88172645463325252;
+        (0..n).map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        }).collect()
+    }
+
+    #[test]
+    fn should_lsd_sort_u64() {
+        let mut v = pseudo_random(1000);
+        let mut expected = v.clone();
+        expected.sort();
+        lsd_sort(&mut v);
+        assert_eq!(expected, v);
+    }
+
+    #[test]
+    fn should_lsd_sort_u32_with_extremes() {
+        let mut v: Vec<u32> = vec![u32::MAX, 0, 1 << 24, 255, 256, u32::MAX, 0];
+        lsd_sort(&mut v);
+        assert_eq!(vec![0, 0, 255, 256, 1 << 24, u32::MAX, u32::MAX], v);
+    }
+
+    #[test]
+    fn should_lsd_sort_by_key_stably() {
+        let mut records: Vec<(u32, usize)> = pseudo_random(500).into_iter().enumerate().map(|(i, k)| ((k % 7) as u32, i)).collect();
+        let mut expected = records.clone();
+        expected.sort_by_key(|r| r.0);
+        lsd_sort_by_key(&mut records, |r| r.0);
+        assert_eq!(expected, records);
+    }
+
+    #[test]
+    fn should_msd_sort_strings() {
+        let mut words: Vec<String> = pseudo_random(2000).into_iter()
+            .map(|x| format!("{:x}", x % 100_000).chars().take((x % 6) as usize).collect())
+            .collect();
+        let mut expected = words.clone();
+        expected.sort();
+        msd_sort(&mut words);
+        assert_eq!(expected, words);
+    }
+
+    #[test]
+    fn should_msd_sort_prefixes_and_duplicates() {
+        let mut words = vec!["ab", "a", "", "abc", "a", "b", "", "abc", "aa", "ab", "b", "ba"];
+        let mut expected = words.clone();
+        expected.sort();
+        msd_sort(&mut words);
+        assert_eq!(expected, words);
+    }
+
+    #[test]
+    fn should_msd_sort_non_ascii_bytes() {
+        let mut words = vec!["émile", "zoe", "eve", "ébène"];
+        msd_sort(&mut words);
+        assert_eq!(vec!["eve", "zoe", "ébène", "émile"], words);
+    }
+}
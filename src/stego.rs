@@ -0,0 +1,312 @@
+//!
+//! # Problem description:
+//! #
+//! # Steganography
+//!
+//! Hide a message in a picture, so that nobody looking at it suspects
+//! there is a message at all. Each color of each pixel is a byte, and
+//! changing its lowest bit changes the color so little that no eye can
+//! see it; eight pixel bytes carry a byte of the message.
+//!
+//! The pictures are PPM files, the simplest format there is: a header in
+//! text with the width, the height and the largest value of a color,
+//! then the red, green and blue of each pixel, row by row, as bytes (P6)
+//! or as decimal numbers (P3). Only pictures with one byte per color can
+//! be read.
+//!
+//! The message is preceded by its length, four bytes, most significant
+//! first, and each of its bytes is written from the most significant bit
+//! down. A key, if given, is XORed over the message, repeating as needed;
+//! it keeps a casual look at the low bits from reading the message, but
+//! it is no encryption.
+//!
+
+use rng::Rng;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+
+/// The bytes that hold the length of the message
+const HEADER: usize = 4;
+
+/// Errors reading pictures and hiding or finding messages
+#[derive(Debug, PartialEq, Eq)]
+pub enum StegoError {
+    /// The file isn't a PPM picture this module reads
+    BadFormat(String),
+    /// The message needs more bytes than the picture can hide
+    TooLong { length: usize, capacity: usize },
+    /// The length hidden is more than the picture could hold, so there is
+    /// no message, or the key is wrong
+    NoMessage,
+    Io(String),
+}
+
+impl fmt::Display for StegoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StegoError::BadFormat(ref message) => write!(f, "Not a PPM picture: {}", message),
+            StegoError::TooLong { length, capacity } => {
+                write!(f, "A message of {} bytes doesn't fit in {} bytes", length, capacity)
+            }
+            StegoError::NoMessage => write!(f, "The picture has no message"),
+            StegoError::Io(ref message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for StegoError {
+    fn from(e: io::Error) -> StegoError {
+        StegoError::Io(e.to_string())
+    }
+}
+
+/// A picture with one byte for each of the red, green and blue of a pixel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    /// Red, green and blue of each pixel, row by row
+    samples: Vec<u8>,
+}
+
+/// Reads the fields of a PPM header: numbers in decimal separated by
+/// whitespace, where `#` starts a comment to the end of the line
+struct Header<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Header<'a> {
+    fn number(&mut self, field: &str) -> Result<usize, StegoError> {
+        loop {
+            match self.bytes.get(self.at) {
+                Some(b'#') => {
+                    while self.bytes.get(self.at).is_some_and(|&b| b != b'\n') {
+                        self.at += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.at += 1,
+                _ => break,
+            }
+        }
+        let start = self.at;
+        while self.bytes.get(self.at).is_some_and(u8::is_ascii_digit) {
+            self.at += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.at])
+            .ok()
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| StegoError::BadFormat(format!("no {}", field)))
+    }
+}
+
+impl Image {
+    /// A black picture
+    pub fn new(width: usize, height: usize) -> Image {
+        Image { width, height, samples: vec![0; width * height * 3] }
+    }
+
+    /// A picture of random noise, where a message hides best
+    pub fn random(width: usize, height: usize, rng: &mut Rng) -> Image {
+        let samples = (0..width * height * 3).map(|_| rng.gen_range(0, 256) as u8).collect();
+        Image { width, height, samples }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The red, green and blue of a pixel
+    pub fn get(&self, x: usize, y: usize) -> Option<[u8; 3]> {
+        if x < self.width && y < self.height {
+            let i = (y * self.width + x) * 3;
+            Some([self.samples[i], self.samples[i + 1], self.samples[i + 2]])
+        } else {
+            None
+        }
+    }
+
+    /// Paints a pixel, if it is in the picture
+    pub fn set(&mut self, x: usize, y: usize, rgb: [u8; 3]) {
+        if x < self.width && y < self.height {
+            let i = (y * self.width + x) * 3;
+            self.samples[i..i + 3].copy_from_slice(&rgb);
+        }
+    }
+
+    /// Reads a PPM picture, binary (P6) or plain (P3), with colors up to
+    /// 255
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::stego::Image;
+    ///
+    /// let image = Image::read_ppm("P3\n# a comment\n2 1\n255\n255 0 0  0 0 255\n".as_bytes()).unwrap();
+    /// assert_eq!(Some([0, 0, 255]), image.get(1, 0));
+    /// ```
+    pub fn read_ppm<R: Read>(mut reader: R) -> Result<Image, StegoError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let plain = match bytes.get(..2) {
+            Some(b"P6") => false,
+            Some(b"P3") => true,
+            _ => return Err(StegoError::BadFormat("no P6 or P3 at the start".to_string())),
+        };
+        let mut header = Header { bytes: &bytes, at: 2 };
+        let width = header.number("width")?;
+        let height = header.number("height")?;
+        let max = header.number("largest value")?;
+        if max != 255 {
+            return Err(StegoError::BadFormat(format!("colors go up to {}, not 255", max)));
+        }
+        let count = width * height * 3;
+        let samples = if plain {
+            let mut samples = Vec::with_capacity(count);
+            for _ in 0..count {
+                let sample = header.number("color")?;
+                if sample > max {
+                    return Err(StegoError::BadFormat(format!("color {} over {}", sample, max)));
+                }
+                samples.push(sample as u8);
+            }
+            samples
+        } else {
+            // A single whitespace byte separates the header from the pixels
+            let start = header.at + 1;
+            match bytes.get(start..start + count) {
+                Some(samples) => samples.to_vec(),
+                None => return Err(StegoError::BadFormat("missing pixels".to_string())),
+            }
+        };
+        Ok(Image { width, height, samples })
+    }
+
+    /// Writes the picture as a binary PPM (P6)
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.samples)?;
+        writer.flush()
+    }
+
+    /// How many bytes of message the picture can hide
+    pub fn capacity(&self) -> usize {
+        (self.samples.len() / 8).saturating_sub(HEADER)
+    }
+
+    /// Hides a message in the lowest bits of the colors, XORed with the key
+    /// if it isn't empty
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::stego::Image;
+    ///
+    /// let mut image = Image::new(4, 4);
+    /// assert_eq!(2, image.capacity());
+    /// image.embed(b"hi", b"").unwrap();
+    /// assert_eq!(b"hi".to_vec(), image.extract(b"").unwrap());
+    /// assert!(image.embed(b"hey", b"").is_err());
+    /// ```
+    pub fn embed(&mut self, message: &[u8], key: &[u8]) -> Result<(), StegoError> {
+        if message.len() > self.capacity() || message.len() > u32::MAX as usize {
+            return Err(StegoError::TooLong { length: message.len(), capacity: self.capacity() });
+        }
+        let length = (message.len() as u32).to_be_bytes();
+        let bytes = length.iter().cloned().chain(xor(message, key));
+        let bits = bytes.flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1));
+        for (sample, bit) in self.samples.iter_mut().zip(bits) {
+            *sample = (*sample & !1) | bit;
+        }
+        Ok(())
+    }
+
+    /// Finds the message hidden by `embed` with the same key
+    pub fn extract(&self, key: &[u8]) -> Result<Vec<u8>, StegoError> {
+        let mut bytes = self.samples.chunks(8).map(|bits| bits.iter().fold(0, |byte, sample| byte << 1 | sample & 1));
+        let mut length = [0; HEADER];
+        for (byte, hidden) in length.iter_mut().zip(&mut bytes) {
+            *byte = hidden;
+        }
+        let length = u32::from_be_bytes(length) as usize;
+        if self.samples.len() < 8 * HEADER || length > self.capacity() {
+            return Err(StegoError::NoMessage);
+        }
+        let message: Vec<u8> = bytes.take(length).collect();
+        Ok(xor(&message, key).collect())
+    }
+}
+
+/// The bytes XORed with the key repeated, or as they are with no key
+fn xor<'a>(bytes: &'a [u8], key: &'a [u8]) -> impl Iterator<Item = u8> + 'a {
+    bytes.iter().enumerate().map(move |(i, &byte)| if key.is_empty() { byte } else { byte ^ key[i % key.len()] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A picture with a gradient, so that the bits around the message
+    /// follow a pattern
+    fn gradient(width: usize, height: usize) -> Image {
+        let mut image = Image::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                image.set(x, y, [(x * 255 / width) as u8, (y * 255 / height) as u8, 128]);
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn should_write_and_read_pictures() {
+        let image = Image::random(7, 5, &mut Rng::new(275));
+        let mut file = Vec::new();
+        image.write_ppm(&mut file).unwrap();
+        assert!(file.starts_with(b"P6\n7 5\n255\n"));
+        assert_eq!(image, Image::read_ppm(&file[..]).unwrap());
+        let plain = "P3 2 2 255\n1 2 3 4 5 6\n# last row\n7 8 9 10 11 12";
+        let image = Image::read_ppm(plain.as_bytes()).unwrap();
+        assert_eq!((2, 2, Some([10, 11, 12])), (image.width(), image.height(), image.get(1, 1)));
+        assert_eq!(None, image.get(2, 0));
+        assert!(Image::read_ppm("P5 1 1 255 0".as_bytes()).is_err(), "A gray picture");
+        assert!(Image::read_ppm("P3 1 1 65535 0 0 0".as_bytes()).is_err(), "Two bytes per color");
+        assert!(Image::read_ppm("P3 1 1 255 0 0 256".as_bytes()).is_err());
+        assert!(Image::read_ppm(&file[..file.len() - 1]).is_err(), "A missing pixel");
+    }
+
+    #[test]
+    fn should_hide_messages_without_changing_the_picture_much() {
+        let message = "Attack at dawn. Bring the maps and the codebook.".as_bytes();
+        for original in [gradient(16, 16), Image::random(16, 16, &mut Rng::new(2))] {
+            let mut image = original.clone();
+            image.embed(message, b"").unwrap();
+            assert_eq!(message.to_vec(), image.extract(b"").unwrap());
+            assert!(image.samples.iter().zip(&original.samples).all(|(a, b)| (*a as i32 - *b as i32).abs() <= 1));
+            let mut file = Vec::new();
+            image.write_ppm(&mut file).unwrap();
+            assert_eq!(message.to_vec(), Image::read_ppm(&file[..]).unwrap().extract(b"").unwrap());
+        }
+    }
+
+    #[test]
+    fn should_hide_messages_under_a_key() {
+        let mut rng = Rng::new(27);
+        let message: Vec<u8> = (0..100).map(|_| rng.gen_range(0, 256) as u8).collect();
+        let mut image = gradient(20, 10);
+        assert_eq!(71, image.capacity());
+        assert_eq!(Err(StegoError::TooLong { length: 100, capacity: 71 }), image.embed(&message, b"key"));
+        image.embed(&message[..71], b"key").unwrap();
+        assert_eq!(message[..71].to_vec(), image.extract(b"key").unwrap());
+        assert_ne!(message[..71].to_vec(), image.extract(b"").unwrap());
+        image.embed(b"", b"").unwrap();
+        assert_eq!(Vec::<u8>::new(), image.extract(b"").unwrap());
+        let mut noise = Image::new(20, 10);
+        noise.samples.iter_mut().for_each(|sample| *sample = 255);
+        assert_eq!(Err(StegoError::NoMessage), noise.extract(b""));
+        assert_eq!(Err(StegoError::NoMessage), Image::new(1, 1).extract(b""));
+    }
+}
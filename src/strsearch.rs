@@ -0,0 +1,512 @@
+//!
+//! # Problem description:
+//! #
+//! # String Searching
+//!
+//! Find every occurrence of a pattern in a text. Knuth, Morris and Pratt
+//! precompute, for each prefix of the pattern, the longest proper prefix
+//! that is also a suffix of it; on a mismatch the search falls back to
+//! that prefix instead of backing up in the text, so it reads each
+//! character of the text once.
+//!
+//! The Z-array holds the same information the other way round: for each
+//! position, the length of the longest substring starting there that is
+//! also a prefix. It is built left to right reusing the rightmost match
+//! found so far, also in linear time, and gives the borders of a string,
+//! the prefixes that are also suffixes, and whether one string is a
+//! rotation of another, a substring of the other twice over.
+//!
+//! Searching for many patterns at once, Aho and Corasick's algorithm
+//! generalizes the failure function: the patterns go in a trie, and each
+//! node gets a failure link to the node of the longest proper suffix of
+//! its string that is also in the trie. The text then goes through the trie
+//! one character at a time, following failure links on mismatches, and
+//! reports every pattern ending at each character, all in time linear in
+//! the text plus the matches, however many patterns there are. Since it
+//! never looks back, it can search a stream without holding it all.
+//!
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, Read};
+
+/// A pattern compiled for the Knuth-Morris-Pratt search
+///
+/// # Example
+/// ```
+/// use pprust::strsearch::Kmp;
+///
+/// let kmp = Kmp::new(b"aba");
+/// assert_eq!(vec![0, 2, 6], kmp.find_all(b"ababa abab"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Kmp {
+    pattern: Vec<u8>,
+    /// The length of the longest proper border of each prefix, by the
+    /// length of the prefix less one
+    failure: Vec<usize>,
+}
+
+impl Kmp {
+    /// Panics if the pattern is empty
+    pub fn new(pattern: &[u8]) -> Kmp {
+        assert!(!pattern.is_empty(), "Searching for an empty pattern");
+        Kmp { pattern: pattern.to_vec(), failure: failure_function(pattern) }
+    }
+
+    /// Where the pattern starts in the text, overlaps included
+    pub fn find_all(&self, text: &[u8]) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut k = 0;
+        for (i, &c) in text.iter().enumerate() {
+            while k > 0 && c != self.pattern[k] {
+                k = self.failure[k - 1];
+            }
+            if c == self.pattern[k] {
+                k += 1;
+            }
+            if k == self.pattern.len() {
+                found.push(i + 1 - k);
+                k = self.failure[k - 1];
+            }
+        }
+        found
+    }
+}
+
+/// The KMP failure function: for each prefix, by its length less one, the
+/// length of its longest proper border
+///
+/// # Example
+/// ```
+/// use pprust::strsearch::failure_function;
+///
+/// assert_eq!(vec![0, 0, 1, 2, 0, 1], failure_function(b"ababca"));
+/// ```
+pub fn failure_function(s: &[u8]) -> Vec<usize> {
+    let mut failure = vec![0; s.len()];
+    let mut k = 0;
+    for i in 1..s.len() {
+        while k > 0 && s[i] != s[k] {
+            k = failure[k - 1];
+        }
+        if s[i] == s[k] {
+            k += 1;
+        }
+        failure[i] = k;
+    }
+    failure
+}
+
+/// The Z-array: for each position, the length of the longest common
+/// prefix of the string and the suffix starting there, the whole length
+/// at position 0
+///
+/// # Example
+/// ```
+/// use pprust::strsearch::z_array;
+///
+/// assert_eq!(vec![7, 0, 1, 0, 3, 0, 1], z_array(b"abacaba"));
+/// ```
+pub fn z_array(s: &[u8]) -> Vec<usize> {
+    let n = s.len();
+    let mut z = vec![0; n];
+    if n == 0 {
+        return z;
+    }
+    z[0] = n;
+    // s[left..right] is the match with a prefix reaching furthest right
+    let (mut left, mut right) = (0, 0);
+    for i in 1..n {
+        if i < right {
+            z[i] = z[i - left].min(right - i);
+        }
+        while i + z[i] < n && s[z[i]] == s[i + z[i]] {
+            z[i] += 1;
+        }
+        if i + z[i] > right {
+            left = i;
+            right = i + z[i];
+        }
+    }
+    z
+}
+
+/// The lengths of the proper borders of a string, the prefixes that are
+/// also suffixes, longest first
+///
+/// # Example
+/// ```
+/// use pprust::strsearch::borders;
+///
+/// assert_eq!(vec![5, 2, 1], borders(b"aabaaabaa"));
+/// ```
+pub fn borders(s: &[u8]) -> Vec<usize> {
+    let z = z_array(s);
+    (1..s.len()).filter(|&i| i + z[i] == s.len()).map(|i| s.len() - i).collect()
+}
+
+/// Whether `b` is `a` turned around, some of its start moved to its end
+///
+/// # Example
+/// ```
+/// use pprust::strsearch::is_rotation;
+///
+/// assert!(is_rotation(b"waterbottle", b"erbottlewat"));
+/// assert!(!is_rotation(b"waterbottle", b"erbottlewta"));
+/// ```
+pub fn is_rotation(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    if b.is_empty() {
+        return true;
+    }
+    // b followed by a twice: b is a rotation if it occurs after itself
+    let text: Vec<u8> = b.iter().chain(a).chain(a).cloned().collect();
+    let z = z_array(&text);
+    (b.len()..text.len()).any(|i| z[i] >= b.len())
+}
+
+/// An occurrence of a pattern: its index among the patterns and where it
+/// starts and ends in the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: BTreeMap<u8, usize>,
+    /// The node of the longest proper suffix in the trie
+    fail: usize,
+    /// The nearest node on the failure chain where a pattern ends
+    output: Option<usize>,
+    /// The patterns ending at this node
+    patterns: Vec<usize>,
+}
+
+const ROOT: usize = 0;
+
+/// A set of patterns compiled for the Aho-Corasick search
+///
+/// # Example
+/// ```
+/// use pprust::strsearch::AhoCorasick;
+///
+/// let ac = AhoCorasick::new(["he", "she", "his", "hers"]);
+/// let found: Vec<(usize, usize)> = ac.find_all(b"ushers").iter().map(|m| (m.pattern, m.start)).collect();
+/// assert_eq!(vec![(1, 1), (0, 2), (3, 2)], found);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    nodes: Vec<Node>,
+    lengths: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// Panics if a pattern is empty
+    pub fn new<I, P>(patterns: I) -> AhoCorasick
+        where I: IntoIterator<Item = P>, P: AsRef<[u8]>
+    {
+        let mut nodes = vec![Node::default()];
+        let mut lengths = Vec::new();
+        for (index, pattern) in patterns.into_iter().enumerate() {
+            let pattern = pattern.as_ref();
+            assert!(!pattern.is_empty(), "Searching for an empty pattern");
+            let mut node = ROOT;
+            for &c in pattern {
+                node = match nodes[node].children.get(&c) {
+                    Some(&child) => child,
+                    None => {
+                        let child = nodes.len();
+                        nodes.push(Node::default());
+                        nodes[node].children.insert(c, child);
+                        child
+                    }
+                };
+            }
+            nodes[node].patterns.push(index);
+            lengths.push(pattern.len());
+        }
+        // Breadth first, so the failure links of shallower nodes are done
+        let mut queue: VecDeque<usize> = nodes[ROOT].children.values().cloned().collect();
+        while let Some(node) = queue.pop_front() {
+            let children: Vec<(u8, usize)> = nodes[node].children.iter().map(|(&c, &n)| (c, n)).collect();
+            for (c, child) in children {
+                let mut fail = nodes[node].fail;
+                let target = loop {
+                    if let Some(&next) = nodes[fail].children.get(&c) {
+                        break next;
+                    }
+                    if fail == ROOT {
+                        break ROOT;
+                    }
+                    fail = nodes[fail].fail;
+                };
+                nodes[child].fail = target;
+                nodes[child].output = if nodes[target].patterns.is_empty() { nodes[target].output } else { Some(target) };
+                queue.push_back(child);
+            }
+        }
+        AhoCorasick { nodes, lengths }
+    }
+
+    /// The number of patterns
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    /// The node after reading `c` in `node`
+    fn step(&self, mut node: usize, c: u8) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[node].children.get(&c) {
+                return next;
+            }
+            if node == ROOT {
+                return ROOT;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// Adds the matches ending at `end` in `node`, longest first
+    fn matches_at(&self, node: usize, end: usize, found: &mut VecDeque<Match>) {
+        let mut next = if self.nodes[node].patterns.is_empty() { self.nodes[node].output } else { Some(node) };
+        while let Some(n) = next {
+            for &pattern in &self.nodes[n].patterns {
+                found.push_back(Match { pattern, start: end - self.lengths[pattern], end });
+            }
+            next = self.nodes[n].output;
+        }
+    }
+
+    /// Every occurrence of every pattern, overlaps included, by where
+    /// they end and then longest first
+    pub fn find_all(&self, text: &[u8]) -> Vec<Match> {
+        let mut found = VecDeque::new();
+        let mut node = ROOT;
+        for (i, &c) in text.iter().enumerate() {
+            node = self.step(node, c);
+            self.matches_at(node, i + 1, &mut found);
+        }
+        found.into_iter().collect()
+    }
+
+    /// The occurrences in a stream, as `find_all` finds them, read as they
+    /// are needed
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::strsearch::AhoCorasick;
+    /// use std::io::Cursor;
+    ///
+    /// let ac = AhoCorasick::new(["needle", "pin"]);
+    /// let input = Cursor::new("haystack with a needle and a pin");
+    /// let starts: Vec<usize> = ac.stream(input).map(|m| m.unwrap().start).collect();
+    /// assert_eq!(vec![16, 29], starts);
+    /// ```
+    pub fn stream<R: Read>(&self, reader: R) -> StreamMatches<'_, R> {
+        StreamMatches {
+            searcher: self,
+            reader,
+            buffer: vec![0; 8192],
+            position: 0,
+            filled: 0,
+            offset: 0,
+            node: ROOT,
+            found: VecDeque::new(),
+            failed: false,
+        }
+    }
+}
+
+/// An iterator over the matches in a stream, stopping after the first
+/// read error
+pub struct StreamMatches<'a, R> {
+    searcher: &'a AhoCorasick,
+    reader: R,
+    buffer: Vec<u8>,
+    position: usize,
+    filled: usize,
+    /// How many bytes of the stream were searched
+    offset: usize,
+    node: usize,
+    found: VecDeque<Match>,
+    failed: bool,
+}
+
+impl<'a, R: Read> Iterator for StreamMatches<'a, R> {
+    type Item = io::Result<Match>;
+
+    fn next(&mut self) -> Option<io::Result<Match>> {
+        loop {
+            if let Some(m) = self.found.pop_front() {
+                return Some(Ok(m));
+            }
+            if self.failed {
+                return None;
+            }
+            if self.position == self.filled {
+                match self.reader.read(&mut self.buffer) {
+                    Ok(0) => return None,
+                    Ok(n) => {
+                        self.position = 0;
+                        self.filled = n;
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        self.failed = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            self.node = self.searcher.step(self.node, self.buffer[self.position]);
+            self.position += 1;
+            self.offset += 1;
+            self.searcher.matches_at(self.node, self.offset, &mut self.found);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn naive(patterns: &[Vec<u8>], text: &[u8]) -> Vec<Match> {
+        let mut found = Vec::new();
+        for (pattern, p) in patterns.iter().enumerate() {
+            for start in 0..(text.len() + 1).saturating_sub(p.len()) {
+                if text[start..].starts_with(p) {
+                    found.push(Match { pattern, start, end: start + p.len() });
+                }
+            }
+        }
+        found.sort_by_key(|m| (m.end, m.start, m.pattern));
+        found
+    }
+
+    fn random_text(rng: &mut Rng, n: usize) -> Vec<u8> {
+        (0..n).map(|_| b'a' + rng.gen_range(0, 3) as u8).collect()
+    }
+
+    /// A reader handing out a few bytes at a time, then failing if asked
+    struct Trickle<'a> {
+        data: &'a [u8],
+        fail: bool,
+    }
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() && self.fail {
+                return Err(io::Error::other("broken"));
+            }
+            let n = buf.len().min(self.data.len()).min(3);
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn should_find_what_a_naive_search_finds() {
+        let mut rng = Rng::new(266);
+        for _ in 0..100 {
+            let patterns: Vec<Vec<u8>> = (0..rng.gen_range(1, 8)).map(|_| {
+                let n = rng.gen_range(1, 5) as usize;
+                random_text(&mut rng, n)
+            }).collect();
+            let text = random_text(&mut rng, 200);
+            let ac = AhoCorasick::new(&patterns);
+            let mut found = ac.find_all(&text);
+            found.sort_by_key(|m| (m.end, m.start, m.pattern));
+            assert_eq!(naive(&patterns, &text), found);
+            for (index, pattern) in patterns.iter().enumerate() {
+                let starts: Vec<usize> = found.iter().filter(|m| m.pattern == index).map(|m| m.start).collect();
+                assert_eq!(starts, Kmp::new(pattern).find_all(&text));
+            }
+        }
+    }
+
+    #[test]
+    fn should_report_longest_first_and_repeated_patterns() {
+        let ac = AhoCorasick::new(["a", "aa", "aaa", "aa"]);
+        assert_eq!(4, ac.len());
+        let found: Vec<(usize, usize)> = ac.find_all(b"aaa").iter().map(|m| (m.pattern, m.end)).collect();
+        assert_eq!(vec![(0, 1), (1, 2), (3, 2), (0, 2), (2, 3), (1, 3), (3, 3), (0, 3)], found);
+        assert!(AhoCorasick::new(["xyz"]).find_all(b"").is_empty());
+        assert!(AhoCorasick::new(Vec::<&str>::new()).find_all(b"abc").is_empty());
+    }
+
+    #[test]
+    fn should_search_streams_across_reads() {
+        let mut rng = Rng::new(2662);
+        let text = random_text(&mut rng, 20_000);
+        let ac = AhoCorasick::new(["abcab", "cc", "bacb", "aaaa"]);
+        let streamed: Vec<Match> = ac.stream(Trickle { data: &text, fail: false }).map(Result::unwrap).collect();
+        assert_eq!(ac.find_all(&text), streamed);
+        assert_eq!(streamed, ac.stream(&text[..]).collect::<io::Result<Vec<Match>>>().unwrap());
+
+        let mut matches = ac.stream(Trickle { data: b"cc", fail: true });
+        assert_eq!(Some(Match { pattern: 1, start: 0, end: 2 }), matches.next().map(Result::unwrap));
+        assert!(matches.next().unwrap().is_err());
+        assert!(matches.next().is_none());
+    }
+
+    #[test]
+    fn should_relate_the_z_array_to_the_failure_function() {
+        let mut rng = Rng::new(267);
+        for n in 0..200 {
+            let s = random_text(&mut rng, n % 40);
+            let z = z_array(&s);
+            for (i, &zi) in z.iter().enumerate() {
+                assert!(s[i..].starts_with(&s[..zi]));
+                assert!(i + zi == s.len() || s[zi] != s[i + zi]);
+            }
+            // Each match with a prefix starting at j > 0 is a border of every
+            // prefix ending inside it; the failure function takes the longest
+            let mut failure = vec![0; s.len()];
+            for (j, &zj) in z.iter().enumerate().skip(1) {
+                for (i, f) in failure.iter_mut().enumerate().skip(j).take(zj) {
+                    *f = (*f).max(i + 1 - j);
+                }
+            }
+            assert_eq!(failure, failure_function(&s));
+            let naive: Vec<usize> = (1..s.len()).rev().filter(|&k| s[..k] == s[s.len() - k..]).collect();
+            assert_eq!(naive, borders(&s));
+            if let Some(&longest) = failure.last() {
+                assert_eq!(longest, naive.first().cloned().unwrap_or(0));
+            }
+        }
+    }
+
+    #[test]
+    fn should_recognize_rotations() {
+        let mut rng = Rng::new(2672);
+        for n in 0..50 {
+            let s = random_text(&mut rng, n);
+            for k in 0..n {
+                let rotated: Vec<u8> = s[k..].iter().chain(&s[..k]).cloned().collect();
+                assert!(is_rotation(&s, &rotated));
+                let mut other = rotated.clone();
+                other[0] = b'z';
+                assert!(!is_rotation(&s, &other));
+            }
+        }
+        assert!(is_rotation(b"", b""));
+        assert!(!is_rotation(b"ab", b"aba"));
+        assert!(!is_rotation(b"abab", b"baab"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_not_search_for_empty_patterns() {
+        AhoCorasick::new(["a", ""]);
+    }
+}
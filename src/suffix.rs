@@ -0,0 +1,280 @@
+//!
+//! # Problem description:
+//! #
+//! # Suffix Arrays
+//!
+//! The suffix array of a text lists the starting positions of its
+//! suffixes in sorted order, so every substring is a prefix of a run of
+//! neighbouring suffixes. Build it by prefix doubling: sort the suffixes
+//! by their first character, then by their first two, four, eight and so
+//! on, each round sorting on the pair of ranks of the two halves found in
+//! the round before. With a radix sort each round takes linear time and
+//! there are at most log *n* rounds.
+//!
+//! The LCP array gives the length of the longest common prefix of each
+//! suffix with the one before it in the suffix array, which Kasai's
+//! algorithm finds in linear time. The longest repeated substring is the
+//! largest of these, and the number of distinct substrings is the number
+//! of substrings less the prefixes each suffix shares with the one
+//! before it.
+//!
+//! The suffix array also gives the Burrows-Wheeler transform, which
+//! sorts the rotations of the text ended by a sentinel smaller than any
+//! character and keeps their last characters. Those rotations sort as
+//! the suffixes do.
+//!
+
+/// The starting positions of the suffixes of `text`, in sorted order
+///
+/// # Example
+/// ```
+/// use pprust::suffix::suffix_array;
+///
+/// // a, ana, anana, banana, na, nana
+/// assert_eq!(vec![5, 3, 1, 0, 4, 2], suffix_array(b"banana"));
+/// ```
+pub fn suffix_array(text: &[u8]) -> Vec<usize> {
+    let n = text.len();
+    let mut rank: Vec<usize> = text.iter().map(|&b| b as usize).collect();
+    let mut sa: Vec<usize> = (0..n).collect();
+    sa.sort_by_key(|&i| text[i]);
+    if n < 2 {
+        return sa;
+    }
+    let mut by_second = Vec::with_capacity(n);
+    let mut next_rank = vec![0; n];
+    let mut counts = vec![0; n.max(256) + 1];
+    let mut k = 1;
+    loop {
+        // Suffixes with no second half come first, then the others in the
+        // order of their second halves
+        by_second.clear();
+        by_second.extend(n - k.min(n)..n);
+        by_second.extend(sa.iter().filter(|&&i| i >= k).map(|&i| i - k));
+        // A stable counting sort on the first halves
+        for c in counts.iter_mut() {
+            *c = 0;
+        }
+        for &i in &by_second {
+            counts[rank[i] + 1] += 1;
+        }
+        for r in 1..counts.len() {
+            counts[r] += counts[r - 1];
+        }
+        for &i in &by_second {
+            sa[counts[rank[i]]] = i;
+            counts[rank[i]] += 1;
+        }
+        let key = |i: usize| (rank[i], rank.get(i + k).map(|&r| r + 1));
+        next_rank[sa[0]] = 0;
+        for j in 1..n {
+            next_rank[sa[j]] = next_rank[sa[j - 1]] + (key(sa[j - 1]) != key(sa[j])) as usize;
+        }
+        rank.copy_from_slice(&next_rank);
+        if rank[sa[n - 1]] == n - 1 {
+            break;
+        }
+        k *= 2;
+    }
+    sa
+}
+
+/// The length of the longest common prefix of each suffix in `sa` with
+/// the one before it, 0 for the first
+///
+/// # Example
+/// ```
+/// use pprust::suffix::{lcp_array, suffix_array};
+///
+/// let text = b"banana";
+/// assert_eq!(vec![0, 1, 3, 0, 0, 2], lcp_array(text, &suffix_array(text)));
+/// ```
+pub fn lcp_array(text: &[u8], sa: &[usize]) -> Vec<usize> {
+    let n = text.len();
+    let mut position = vec![0; n];
+    for (j, &i) in sa.iter().enumerate() {
+        position[i] = j;
+    }
+    let mut lcp = vec![0; n];
+    let mut h: usize = 0;
+    // Going through the suffixes in text order, each shares at least one
+    // less than the one before with its predecessor
+    for i in 0..n {
+        if position[i] == 0 {
+            h = 0;
+            continue;
+        }
+        let j = sa[position[i] - 1];
+        while i + h < n && j + h < n && text[i + h] == text[j + h] {
+            h += 1;
+        }
+        lcp[position[i]] = h;
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+/// The longest substring appearing more than once, the first in sorted
+/// order on ties; empty if no character repeats
+///
+/// # Example
+/// ```
+/// use pprust::suffix::longest_repeated_substring;
+///
+/// assert_eq!(b"ana", longest_repeated_substring(b"banana"));
+/// assert_eq!(b"", longest_repeated_substring(b"abc"));
+/// ```
+pub fn longest_repeated_substring(text: &[u8]) -> &[u8] {
+    let sa = suffix_array(text);
+    let lcp = lcp_array(text, &sa);
+    match lcp.iter().enumerate().rev().max_by_key(|&(_, &h)| h) {
+        Some((j, &h)) => &text[sa[j]..sa[j] + h],
+        None => &text[..0],
+    }
+}
+
+/// The number of different non-empty substrings
+///
+/// # Example
+/// ```
+/// use pprust::suffix::count_distinct_substrings;
+///
+/// // a, b, n, an, ba, na, ana, ban, nan, anan, bana, nana, anana, banan, banana
+/// assert_eq!(15, count_distinct_substrings(b"banana"));
+/// ```
+pub fn count_distinct_substrings(text: &[u8]) -> usize {
+    let n = text.len();
+    let shared: usize = lcp_array(text, &suffix_array(text)).iter().sum();
+    n * (n + 1) / 2 - shared
+}
+
+/// The Burrows-Wheeler transform of `text` followed by a sentinel: the
+/// last characters of its sorted rotations, leaving out the sentinel,
+/// and the row the sentinel was in
+///
+/// # Example
+/// ```
+/// use pprust::suffix::{bwt, inverse_bwt};
+///
+/// // The transform of banana$ is annb$aa
+/// let (last, row) = bwt(b"banana");
+/// assert_eq!((&b"annbaa"[..], 4), (&last[..], row));
+/// assert_eq!(b"banana".to_vec(), inverse_bwt(&last, row));
+/// ```
+pub fn bwt(text: &[u8]) -> (Vec<u8>, usize) {
+    let n = text.len();
+    let mut last = Vec::with_capacity(n);
+    // The rotation starting at the sentinel comes first, ending in the
+    // last character of the text
+    last.extend(text.last());
+    let mut row = 0;
+    for (j, &i) in suffix_array(text).iter().enumerate() {
+        if i == 0 {
+            row = j + 1;
+        } else {
+            last.push(text[i - 1]);
+        }
+    }
+    (last, row)
+}
+
+/// Undoes `bwt`, given the characters and the row of the sentinel it
+/// returned. Panics if `row` is past the end.
+pub fn inverse_bwt(last: &[u8], row: usize) -> Vec<u8> {
+    assert!(row <= last.len(), "The sentinel row {} is past the end", row);
+    // The transform with the sentinel put back, as symbol 0
+    let symbols: Vec<usize> = last[..row].iter().map(|&b| b as usize + 1)
+        .chain(Some(0))
+        .chain(last[row..].iter().map(|&b| b as usize + 1))
+        .collect();
+    // Where the rows starting with each symbol begin in the sorted rotations
+    let mut first = [0; 258];
+    for &c in &symbols {
+        first[c + 1] += 1;
+    }
+    for c in 1..first.len() {
+        first[c] += first[c - 1];
+    }
+    // The row of the rotation one character back: the k-th occurrence of
+    // a symbol in the last column is its k-th occurrence in the first
+    let mut seen = [0; 257];
+    let mut previous = Vec::with_capacity(symbols.len());
+    for &c in &symbols {
+        previous.push(first[c] + seen[c]);
+        seen[c] += 1;
+    }
+    let mut text = vec![0; last.len()];
+    let mut j = 0;
+    for i in (0..last.len()).rev() {
+        text[i] = (symbols[j] - 1) as u8;
+        j = previous[j];
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    fn random_text(rng: &mut Rng, n: usize, alphabet: u64) -> Vec<u8> {
+        (0..n).map(|_| b'a' + rng.gen_range(0, alphabet) as u8).collect()
+    }
+
+    #[test]
+    fn should_sort_suffixes_like_a_naive_sort() {
+        let mut rng = Rng::new(265);
+        for &(n, alphabet) in &[(0, 2), (1, 2), (2, 1), (50, 1), (200, 2), (300, 4), (500, 26)] {
+            let text = random_text(&mut rng, n, alphabet);
+            let mut naive: Vec<usize> = (0..n).collect();
+            naive.sort_by_key(|&i| &text[i..]);
+            let sa = suffix_array(&text);
+            assert_eq!(naive, sa);
+            let lcp = lcp_array(&text, &sa);
+            for j in 1..n {
+                let (a, b) = (&text[sa[j - 1]..], &text[sa[j]..]);
+                assert_eq!(a.iter().zip(b).take_while(|(x, y)| x == y).count(), lcp[j]);
+            }
+        }
+        assert_eq!(vec![2, 1, 0], suffix_array(&[255, 255, 0]));
+    }
+
+    #[test]
+    fn should_find_repeats_and_distinct_substrings() {
+        assert_eq!(b"abcab", longest_repeated_substring(b"abcabcab"));
+        assert_eq!(b"aaa", longest_repeated_substring(b"aaaa"));
+        assert_eq!(b"", longest_repeated_substring(b""));
+        assert_eq!(0, count_distinct_substrings(b""));
+        assert_eq!(4, count_distinct_substrings(b"aaaa"));
+        let mut rng = Rng::new(2652);
+        for _ in 0..20 {
+            let text = random_text(&mut rng, 40, 3);
+            let mut naive = ::std::collections::HashSet::new();
+            for i in 0..text.len() {
+                for j in i + 1..=text.len() {
+                    naive.insert(&text[i..j]);
+                }
+            }
+            assert_eq!(naive.len(), count_distinct_substrings(&text));
+            let repeat = longest_repeated_substring(&text);
+            let occurrences = text.windows(repeat.len().max(1)).filter(|w| *w == repeat).count();
+            assert!(repeat.is_empty() || occurrences >= 2);
+            let longer = repeat.len() + 1;
+            assert!(text.windows(longer).all(|w| text.windows(longer).filter(|v| v == &w).count() == 1));
+        }
+    }
+
+    #[test]
+    fn should_invert_the_transform() {
+        let mut rng = Rng::new(2653);
+        for &n in &[0, 1, 2, 10, 1000] {
+            let text: Vec<u8> = (0..n).map(|_| rng.gen_range(0, 256) as u8).collect();
+            let (last, row) = bwt(&text);
+            assert_eq!(n, last.len());
+            assert_eq!(text, inverse_bwt(&last, row));
+        }
+        // Runs of equal characters gather together
+        let (last, _) = bwt(b"abracadabra abracadabra");
+        assert!(last.windows(2).filter(|w| w[0] == w[1]).count() > 10);
+    }
+}
@@ -0,0 +1,224 @@
+//!
+//! # Problem description:
+//! #
+//! # Unix Tail
+//!
+//! Implement the Unix `tail` command, which prints the last *n* lines of
+//! a file. The file may be huge, so instead of reading it from the start
+//! the program should read it backwards in blocks until enough newlines
+//! have been seen. With the `-f` option `tail` keeps following the file,
+//! printing lines as they are appended, and should cope with the file
+//! being truncated or replaced by log rotation.
+//!
+
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Size of the blocks read while scanning a file backwards
+pub const BLOCK_SIZE: usize = 4096;
+
+/// Returns the bytes of the last `n` lines of a seekable reader.
+///
+/// Only the blocks at the end of the input that contain those lines are
+/// read. A final newline does not count as the start of an extra line.
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+/// use pprust::tail::last_lines;
+///
+/// let mut input = Cursor::new("one\ntwo\nthree\n");
+/// assert_eq!(b"two\nthree\n".to_vec(), last_lines(&mut input, 2).unwrap());
+/// ```
+pub fn last_lines<R: Read + Seek>(reader: &mut R, n: usize) -> io::Result<Vec<u8>> {
+    let len = reader.seek(io::SeekFrom::End(0))?;
+    let start = start_of_last_lines(reader, len, n)?;
+    reader.seek(io::SeekFrom::Start(start))?;
+    let mut result = Vec::with_capacity((len - start) as usize);
+    reader.take(len - start).read_to_end(&mut result)?;
+    Ok(result)
+}
+
+fn start_of_last_lines<R: Read + Seek>(reader: &mut R, len: u64, n: usize) -> io::Result<u64> {
+    if n == 0 {
+        return Ok(len);
+    }
+    let mut block = vec![0u8; BLOCK_SIZE];
+    let mut end = len;
+    let mut newlines = 0;
+    let mut skip_trailing = true;
+    while end > 0 {
+        let start = end.saturating_sub(BLOCK_SIZE as u64);
+        let size = (end - start) as usize;
+        reader.seek(io::SeekFrom::Start(start))?;
+        reader.read_exact(&mut block[..size])?;
+        for i in (0..size).rev() {
+            if block[i] != b'\n' {
+                skip_trailing = false;
+                continue;
+            }
+            if skip_trailing {
+                skip_trailing = false;
+                continue;
+            }
+            newlines += 1;
+            if newlines == n {
+                return Ok(start + i as u64 + 1);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+/// Follows a file as it grows, like `tail -f`.
+///
+/// Each call to `poll` returns the bytes appended since the previous
+/// call. If the file shrinks (it was truncated) or the path now points to
+/// a different file (it was rotated), reading restarts from the beginning
+/// of the new contents.
+pub struct Follower {
+    path: PathBuf,
+    file: fs::File,
+    position: u64,
+    identity: Option<(u64, u64)>,
+}
+
+impl Follower {
+    /// Starts following a file from its current end
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Follower> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = fs::File::open(&path)?;
+        let position = file.seek(io::SeekFrom::End(0))?;
+        let identity = identity(&file.metadata()?);
+        Ok(Follower { path, file, position, identity })
+    }
+
+    /// Starts following a file from a given byte offset
+    pub fn from_position<P: AsRef<Path>>(path: P, position: u64) -> io::Result<Follower> {
+        let mut follower = Follower::new(path)?;
+        follower.position = position;
+        Ok(follower)
+    }
+
+    /// Returns the data appended to the file since the last poll
+    pub fn poll(&mut self) -> io::Result<Vec<u8>> {
+        if let Ok(metadata) = fs::metadata(&self.path) {
+            let current = identity(&metadata);
+            if current != self.identity {
+                self.file = fs::File::open(&self.path)?;
+                self.identity = current;
+                self.position = 0;
+            }
+        }
+        let len = self.file.metadata()?.len();
+        if len < self.position {
+            self.position = 0;
+        }
+        let mut data = Vec::new();
+        if len > self.position {
+            self.file.seek(io::SeekFrom::Start(self.position))?;
+            (&mut self.file).take(len - self.position).read_to_end(&mut data)?;
+            self.position += data.len() as u64;
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(unix)]
+fn identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(_: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::io::Cursor;
+
+    fn tail(input: &str, n: usize) -> String {
+        let mut cursor = Cursor::new(input.as_bytes().to_vec());
+        String::from_utf8(last_lines(&mut cursor, n).unwrap()).unwrap()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("pprust-tail-{}-{}", name, ::std::process::id()))
+    }
+
+    #[test]
+    fn should_return_last_lines() {
+        assert_eq!("c\nd\n", tail("a\nb\nc\nd\n", 2));
+    }
+
+    #[test]
+    fn should_handle_missing_final_newline() {
+        assert_eq!("c\nd", tail("a\nb\nc\nd", 2));
+    }
+
+    #[test]
+    fn should_return_everything_when_file_is_shorter() {
+        assert_eq!("a\nb\n", tail("a\nb\n", 10));
+        assert_eq!("", tail("", 10));
+    }
+
+    #[test]
+    fn should_return_nothing_for_zero_lines() {
+        assert_eq!("", tail("a\nb\n", 0));
+    }
+
+    #[test]
+    fn should_keep_empty_lines() {
+        assert_eq!("\n\nx\n", tail("a\n\n\nx\n", 3));
+    }
+
+    #[test]
+    fn should_scan_across_block_boundaries() {
+        let line = "x".repeat(BLOCK_SIZE / 3);
+        let input: String = (0..20).map(|i| format!("{}{}\n", i, line)).collect();
+        let expected: String = (17..20).map(|i| format!("{}{}\n", i, line)).collect();
+        assert_eq!(expected, tail(&input, 3));
+    }
+
+    #[test]
+    fn should_follow_appended_data() {
+        let path = temp_path("append");
+        fs::write(&path, "old\n").unwrap();
+        let mut follower = Follower::new(&path).unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+        fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"new\n").unwrap();
+        assert_eq!(b"new\n".to_vec(), follower.poll().unwrap());
+        assert!(follower.poll().unwrap().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_restart_after_truncation() {
+        let path = temp_path("truncate");
+        fs::write(&path, "a long first line\n").unwrap();
+        let mut follower = Follower::new(&path).unwrap();
+        fs::OpenOptions::new().write(true).truncate(true).open(&path).unwrap().write_all(b"short\n").unwrap();
+        assert_eq!(b"short\n".to_vec(), follower.poll().unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_reopen_rotated_file() {
+        let path = temp_path("rotate");
+        let rotated = temp_path("rotate.1");
+        fs::write(&path, "before rotation\n").unwrap();
+        let mut follower = Follower::new(&path).unwrap();
+        fs::rename(&path, &rotated).unwrap();
+        fs::write(&path, "after rotation, and a bit longer\n").unwrap();
+        assert_eq!(b"after rotation, and a bit longer\n".to_vec(), follower.poll().unwrap());
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&rotated).unwrap();
+    }
+}
@@ -0,0 +1,245 @@
+//!
+//! # Problem description:
+//! #
+//! # Tic-Tac-Toe
+//!
+//! Two players take turns marking the cells of a 3x3 grid, X first; the
+//! first to get three marks in a row, column or diagonal wins. The game
+//! tree is small enough to search to the end, so the computer plays
+//! perfectly, and two perfect players always draw.
+//!
+//! Cells are numbered 0 to 8 in row order.
+//!
+
+use gametree::{self, Game, WIN};
+use std::fmt;
+
+/// One of the two players
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Player {
+    X,
+    O,
+}
+
+impl Player {
+    /// The other player
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::X => Player::O,
+            Player::O => Player::X,
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Player::X => write!(f, "X"),
+            Player::O => write!(f, "O"),
+        }
+    }
+}
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2], [3, 4, 5], [6, 7, 8],
+    [0, 3, 6], [1, 4, 7], [2, 5, 8],
+    [0, 4, 8], [2, 4, 6],
+];
+
+/// A tic-tac-toe position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Board {
+    cells: [Option<Player>; 9],
+    to_move: Player,
+}
+
+impl Board {
+    /// The empty board, X to move
+    pub fn new() -> Board {
+        Board { cells: [None; 9], to_move: Player::X }
+    }
+
+    /// The mark in a cell
+    pub fn cell(&self, i: usize) -> Option<Player> {
+        self.cells[i]
+    }
+
+    /// The player whose turn it is
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    /// The position after the player to move marks cell `i`, or `None`
+    /// if the cell does not exist or is taken, or the game is over
+    pub fn play(&self, i: usize) -> Option<Board> {
+        if i >= 9 || self.cells[i].is_some() || self.is_over() {
+            return None;
+        }
+        let mut next = *self;
+        next.cells[i] = Some(self.to_move);
+        next.to_move = self.to_move.opponent();
+        Some(next)
+    }
+
+    /// The player with three in a line, if any
+    pub fn winner(&self) -> Option<Player> {
+        LINES.iter().filter_map(|line| {
+            let first = self.cells[line[0]];
+            if first.is_some() && line.iter().all(|&i| self.cells[i] == first) { first } else { None }
+        }).next()
+    }
+
+    /// Whether the game has ended in a win or a draw
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some() || self.cells.iter().all(|c| c.is_some())
+    }
+
+    /// The empty cells, or none if the game is over
+    pub fn free_cells(&self) -> Vec<usize> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        (0..9).filter(|&i| self.cells[i].is_none()).collect()
+    }
+
+    /// A perfect move for the player to move, or `None` if the game is
+    /// over
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::tictactoe::Board;
+    ///
+    /// // X threatens the top row; O must block at cell 2
+    /// let board = Board::new().play(0).unwrap().play(4).unwrap().play(1).unwrap();
+    /// assert_eq!(Some(2), board.best_move());
+    /// ```
+    pub fn best_move(&self) -> Option<usize> {
+        gametree::alphabeta(self, 9).best
+    }
+}
+
+impl Default for Board {
+    fn default() -> Board {
+        Board::new()
+    }
+}
+
+impl Game for Board {
+    type Move = usize;
+
+    fn moves(&self) -> Vec<usize> {
+        self.free_cells()
+    }
+
+    fn play(&self, m: usize) -> Board {
+        Board::play(self, m).expect("Legal move")
+    }
+
+    fn is_over(&self) -> bool {
+        Board::is_over(self)
+    }
+
+    /// Won and lost positions are worth `WIN` and `-WIN`; otherwise each
+    /// line still open to only one player counts for them, more so the
+    /// more marks it has
+    fn evaluate(&self) -> i32 {
+        match self.winner() {
+            Some(p) if p == self.to_move => return WIN,
+            Some(_) => return -WIN,
+            None => {}
+        }
+        LINES.iter().map(|line| {
+            let mine = line.iter().filter(|&&i| self.cells[i] == Some(self.to_move)).count() as i32;
+            let theirs = line.iter().filter(|&&i| self.cells[i] == Some(self.to_move.opponent())).count() as i32;
+            match (mine, theirs) {
+                (m, 0) => m * m,
+                (0, t) => -t * t,
+                _ => 0,
+            }
+        }).sum()
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..3 {
+            let cells: Vec<String> = (0..3).map(|col| {
+                let i = 3 * row + col;
+                self.cells[i].map_or((i + 1).to_string(), |p| p.to_string())
+            }).collect();
+            writeln!(f, " {}", cells.join(" | "))?;
+            if row < 2 {
+                writeln!(f, "---+---+---")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_all(moves: &[usize]) -> Board {
+        moves.iter().fold(Board::new(), |b, &m| b.play(m).unwrap())
+    }
+
+    #[test]
+    fn should_detect_wins_and_draws() {
+        let won = play_all(&[0, 3, 1, 4, 2]);
+        assert_eq!(Some(Player::X), won.winner());
+        assert!(won.is_over());
+        assert_eq!(None, won.play(5), "No moves after a win");
+        let drawn = play_all(&[0, 1, 2, 4, 3, 5, 7, 6, 8]);
+        assert_eq!(None, drawn.winner());
+        assert!(drawn.is_over());
+    }
+
+    #[test]
+    fn should_reject_taken_cells() {
+        let board = play_all(&[4]);
+        assert_eq!(None, board.play(4));
+        assert_eq!(None, board.play(9));
+        assert_eq!(Player::O, board.to_move());
+    }
+
+    #[test]
+    fn should_draw_with_perfect_play() {
+        let mut board = Board::new();
+        while let Some(m) = board.best_move() {
+            board = board.play(m).unwrap();
+        }
+        assert!(board.is_over());
+        assert_eq!(None, board.winner());
+    }
+
+    #[test]
+    fn should_take_a_win_over_a_block() {
+        // X to move can win at 2, or block O's middle row at 5
+        let board = play_all(&[0, 3, 1, 4]);
+        assert_eq!(Some(2), board.best_move());
+    }
+
+    #[test]
+    fn should_never_lose_to_any_opponent() {
+        fn check(board: Board, engine: Player) {
+            if board.is_over() {
+                assert_ne!(Some(engine.opponent()), board.winner(), "Engine lost:\n{}", board);
+            } else if board.to_move() == engine {
+                check(board.play(board.best_move().unwrap()).unwrap(), engine);
+            } else {
+                for m in board.free_cells() {
+                    check(board.play(m).unwrap(), engine);
+                }
+            }
+        }
+        check(Board::new(), Player::X);
+        check(Board::new(), Player::O);
+    }
+
+    #[test]
+    fn should_print_board() {
+        let board = play_all(&[4, 0]);
+        assert_eq!(" O | 2 | 3\n---+---+---\n 4 | X | 6\n---+---+---\n 7 | 8 | 9\n", board.to_string());
+    }
+}
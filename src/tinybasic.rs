@@ -0,0 +1,656 @@
+//!
+//! # Problem description:
+//! #
+//! # Tiny BASIC
+//!
+//! Tiny BASIC was specified in 1975 to fit in the few kilobytes of the
+//! first home computers. It has 26 integer variables, A to Z, and a
+//! handful of statements:
+//!
+//! ```text
+//! PRINT item, item; ...        strings or expressions
+//! IF expr relop expr THEN statement
+//! GOTO expr        GOSUB expr        RETURN
+//! INPUT var, ...
+//! LET var = expr   (LET may be left out)
+//! REM comment      END
+//! RUN      LIST      CLEAR
+//! ```
+//!
+//! A line starting with a number is stored in the program, replacing the
+//! line with that number, or deleting it if nothing follows; any other
+//! line runs at once. Lines are parsed when they are entered, so syntax
+//! errors show up right away, and the program runs from the stored
+//! syntax trees. Arithmetic is on 64-bit integers, division truncating.
+//!
+//! In PRINT, a comma puts a space between items, a semicolon nothing,
+//! and either at the end keeps the next PRINT on the same line.
+//!
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::result;
+
+/// Errors entering or running a program
+#[derive(Debug, Clone, PartialEq)]
+pub enum BasicError {
+    /// The line is not a statement; the message says what was expected
+    Syntax(String),
+    /// A jump to a line that isn't in the program
+    UndefinedLine(i64),
+    ReturnWithoutGosub,
+    DivisionByZero,
+    Overflow,
+    /// INPUT read something that isn't a number
+    BadInput(String),
+    /// INPUT found no more input
+    EndOfInput,
+    Io(String),
+    /// An error while running the program line
+    AtLine(u32, Box<BasicError>),
+}
+
+impl fmt::Display for BasicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BasicError::Syntax(ref message) => write!(f, "Syntax error: {}", message),
+            BasicError::UndefinedLine(n) => write!(f, "No line {}", n),
+            BasicError::ReturnWithoutGosub => write!(f, "RETURN without GOSUB"),
+            BasicError::DivisionByZero => write!(f, "Division by zero"),
+            BasicError::Overflow => write!(f, "Overflow"),
+            BasicError::BadInput(ref text) => write!(f, "Not a number: {}", text),
+            BasicError::EndOfInput => write!(f, "End of input"),
+            BasicError::Io(ref message) => write!(f, "{}", message),
+            BasicError::AtLine(line, ref error) => write!(f, "{} in line {}", error, line),
+        }
+    }
+}
+
+impl From<io::Error> for BasicError {
+    fn from(e: io::Error) -> BasicError {
+        BasicError::Io(e.to_string())
+    }
+}
+
+pub type BasicResult<T> = result::Result<T, BasicError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Num(i64),
+    Var(usize),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelOp {
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+    GreaterEqual,
+    Greater,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PrintItem {
+    Text(String),
+    Value(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Statement {
+    /// The items, each with the separator after it, if any
+    Print(Vec<(PrintItem, Option<char>)>),
+    If(Expr, RelOp, Expr, Box<Statement>),
+    Goto(Expr),
+    Gosub(Expr),
+    Return,
+    Input(Vec<usize>),
+    Let(usize, Expr),
+    Rem,
+    End,
+    Run,
+    List,
+    Clear,
+}
+
+/// Reads a line of source, ignoring spaces between tokens
+struct Scanner<'a> {
+    text: &'a str,
+    at: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn skip_spaces(&mut self) {
+        while self.text[self.at..].starts_with(' ') || self.text[self.at..].starts_with('\t') {
+            self.at += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_spaces();
+        self.text[self.at..].chars().next()
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.peek().is_none()
+    }
+
+    fn error<T>(&self, expected: &str) -> BasicResult<T> {
+        Err(BasicError::Syntax(format!("expected {} at '{}'", expected, &self.text[self.at..])))
+    }
+
+    /// Takes `symbol` if it comes next
+    fn symbol(&mut self, symbol: &str) -> bool {
+        self.skip_spaces();
+        if self.text[self.at..].starts_with(symbol) {
+            self.at += symbol.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Takes `keyword` if it comes next, in any case
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.skip_spaces();
+        let rest = &self.text[self.at..];
+        if rest.len() >= keyword.len() && rest.is_char_boundary(keyword.len()) && rest[..keyword.len()].eq_ignore_ascii_case(keyword) {
+            self.at += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        self.skip_spaces();
+        let digits = self.text[self.at..].bytes().take_while(|b| b.is_ascii_digit()).count();
+        let n = self.text[self.at..self.at + digits].parse().ok()?;
+        self.at += digits;
+        Some(n)
+    }
+
+    /// A variable: one letter not followed by another
+    fn variable(&mut self) -> Option<usize> {
+        self.skip_spaces();
+        let mut chars = self.text[self.at..].chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), next) if c.is_ascii_alphabetic() && !next.is_some_and(|n| n.is_ascii_alphabetic()) => {
+                self.at += 1;
+                Some((c.to_ascii_uppercase() as u8 - b'A') as usize)
+            }
+            _ => None,
+        }
+    }
+
+    fn string(&mut self) -> BasicResult<Option<String>> {
+        if !self.symbol("\"") {
+            return Ok(None);
+        }
+        match self.text[self.at..].find('"') {
+            Some(end) => {
+                let text = self.text[self.at..self.at + end].to_string();
+                self.at += end + 1;
+                Ok(Some(text))
+            }
+            None => self.error("a closing quote"),
+        }
+    }
+
+    /// expr = ('+' | '-')? term (('+' | '-') term)*
+    fn expr(&mut self) -> BasicResult<Expr> {
+        let mut e = if self.symbol("-") {
+            Expr::Neg(Box::new(self.term()?))
+        } else {
+            self.symbol("+");
+            self.term()?
+        };
+        loop {
+            let op = if self.symbol("+") {
+                BinOp::Add
+            } else if self.symbol("-") {
+                BinOp::Sub
+            } else {
+                return Ok(e);
+            };
+            e = Expr::Binary(op, Box::new(e), Box::new(self.term()?));
+        }
+    }
+
+    /// term = factor (('*' | '/') factor)*
+    fn term(&mut self) -> BasicResult<Expr> {
+        let mut e = self.factor()?;
+        loop {
+            let op = if self.symbol("*") {
+                BinOp::Mul
+            } else if self.symbol("/") {
+                BinOp::Div
+            } else {
+                return Ok(e);
+            };
+            e = Expr::Binary(op, Box::new(e), Box::new(self.factor()?));
+        }
+    }
+
+    /// factor = number | var | '(' expr ')'
+    fn factor(&mut self) -> BasicResult<Expr> {
+        if let Some(n) = self.number() {
+            Ok(Expr::Num(n))
+        } else if let Some(v) = self.variable() {
+            Ok(Expr::Var(v))
+        } else if self.symbol("(") {
+            let e = self.expr()?;
+            if !self.symbol(")") {
+                return self.error("')'");
+            }
+            Ok(e)
+        } else {
+            self.error("a number, a variable or '('")
+        }
+    }
+
+    fn relop(&mut self) -> BasicResult<RelOp> {
+        for &(symbol, op) in &[
+            ("<=", RelOp::LessEqual),
+            ("<>", RelOp::NotEqual),
+            ("><", RelOp::NotEqual),
+            (">=", RelOp::GreaterEqual),
+            ("<", RelOp::Less),
+            (">", RelOp::Greater),
+            ("=", RelOp::Equal),
+        ] {
+            if self.symbol(symbol) {
+                return Ok(op);
+            }
+        }
+        self.error("a comparison")
+    }
+
+    fn statement(&mut self) -> BasicResult<Statement> {
+        let statement = if self.keyword("PRINT") {
+            let mut items = Vec::new();
+            while !self.at_end() {
+                let item = match self.string()? {
+                    Some(text) => PrintItem::Text(text),
+                    None => PrintItem::Value(self.expr()?),
+                };
+                let separator = if self.symbol(",") {
+                    Some(',')
+                } else if self.symbol(";") {
+                    Some(';')
+                } else {
+                    None
+                };
+                items.push((item, separator));
+                if separator.is_none() {
+                    break;
+                }
+            }
+            Statement::Print(items)
+        } else if self.keyword("IF") {
+            let left = self.expr()?;
+            let op = self.relop()?;
+            let right = self.expr()?;
+            if !self.keyword("THEN") {
+                return self.error("THEN");
+            }
+            Statement::If(left, op, right, Box::new(self.statement()?))
+        } else if self.keyword("GOTO") {
+            Statement::Goto(self.expr()?)
+        } else if self.keyword("GOSUB") {
+            Statement::Gosub(self.expr()?)
+        } else if self.keyword("RETURN") {
+            Statement::Return
+        } else if self.keyword("INPUT") {
+            let mut vars = vec![self.variable().map_or_else(|| self.error("a variable"), Ok)?];
+            while self.symbol(",") {
+                vars.push(self.variable().map_or_else(|| self.error("a variable"), Ok)?);
+            }
+            Statement::Input(vars)
+        } else if self.keyword("REM") {
+            self.at = self.text.len();
+            Statement::Rem
+        } else if self.keyword("END") {
+            Statement::End
+        } else if self.keyword("RUN") {
+            Statement::Run
+        } else if self.keyword("LIST") {
+            Statement::List
+        } else if self.keyword("CLEAR") {
+            Statement::Clear
+        } else {
+            self.keyword("LET");
+            let var = self.variable().map_or_else(|| self.error("a statement"), Ok)?;
+            if !self.symbol("=") {
+                return self.error("'='");
+            }
+            Statement::Let(var, self.expr()?)
+        };
+        Ok(statement)
+    }
+}
+
+/// Parses a whole statement
+fn parse_statement(text: &str) -> BasicResult<Statement> {
+    let mut scanner = Scanner { text, at: 0 };
+    let statement = scanner.statement()?;
+    if !scanner.at_end() {
+        return scanner.error("the end of the line");
+    }
+    Ok(statement)
+}
+
+/// Where to go after a statement
+enum Flow {
+    Next,
+    Jump(i64),
+    Gosub(i64),
+    Return,
+    End,
+}
+
+/// A Tiny BASIC machine: the stored program and the variables
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    /// Each line with its source, for LIST, and its parse
+    program: BTreeMap<u32, (String, Statement)>,
+    vars: [i64; 26],
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter { program: BTreeMap::new(), vars: [0; 26] }
+    }
+
+    /// The value of a variable, by letter
+    pub fn var(&self, name: char) -> Option<i64> {
+        let index = (name.to_ascii_uppercase() as usize).checked_sub('A' as usize)?;
+        self.vars.get(index).cloned()
+    }
+
+    /// Stores a numbered line, or runs any other line at once
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::tinybasic::Interpreter;
+    /// use std::io;
+    ///
+    /// let mut basic = Interpreter::new();
+    /// let mut output = Vec::new();
+    /// for line in &["10 LET N = 5", "20 LET F = 1", "30 IF N = 0 THEN GOTO 70", "40 F = F * N",
+    ///               "50 N = N - 1", "60 GOTO 30", "70 PRINT \"5! = \"; F", "RUN"] {
+    ///     basic.enter(line, &mut io::empty(), &mut output).unwrap();
+    /// }
+    /// assert_eq!("5! = 120\n", String::from_utf8(output).unwrap());
+    /// ```
+    pub fn enter<R: BufRead, W: Write>(&mut self, line: &str, input: &mut R, output: &mut W) -> BasicResult<()> {
+        let line = line.trim();
+        let digits = line.bytes().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            if line.is_empty() {
+                return Ok(());
+            }
+            return self.immediate(&parse_statement(line)?, input, output);
+        }
+        let number: u32 = line[..digits].parse().map_err(|_| BasicError::Syntax(format!("bad line number {}", &line[..digits])))?;
+        let source = line[digits..].trim();
+        if source.is_empty() {
+            self.program.remove(&number);
+        } else {
+            self.program.insert(number, (source.to_string(), parse_statement(source)?));
+        }
+        Ok(())
+    }
+
+    /// Stores every line of a program
+    pub fn load(&mut self, source: &str) -> BasicResult<()> {
+        for line in source.lines() {
+            self.enter(line, &mut io::empty(), &mut io::sink())?;
+        }
+        Ok(())
+    }
+
+    /// Runs the stored program from its first line, with all variables
+    /// at zero
+    pub fn run<R: BufRead, W: Write>(&mut self, input: &mut R, output: &mut W) -> BasicResult<()> {
+        self.vars = [0; 26];
+        match self.program.keys().next() {
+            Some(&first) => self.run_from(first, input, output),
+            None => Ok(()),
+        }
+    }
+
+    /// The program as LIST shows it
+    pub fn listing(&self) -> String {
+        self.program.iter().map(|(n, (source, _))| format!("{} {}\n", n, source)).collect()
+    }
+
+    fn immediate<R: BufRead, W: Write>(&mut self, statement: &Statement, input: &mut R, output: &mut W) -> BasicResult<()> {
+        match self.execute(statement, input, output)? {
+            Flow::Next | Flow::End => Ok(()),
+            Flow::Jump(n) | Flow::Gosub(n) => self.run_from(self.line(n)?, input, output),
+            Flow::Return => Err(BasicError::ReturnWithoutGosub),
+        }
+    }
+
+    fn line(&self, n: i64) -> BasicResult<u32> {
+        if n >= 0 && n <= u32::MAX as i64 && self.program.contains_key(&(n as u32)) {
+            Ok(n as u32)
+        } else {
+            Err(BasicError::UndefinedLine(n))
+        }
+    }
+
+    fn run_from<R: BufRead, W: Write>(&mut self, start: u32, input: &mut R, output: &mut W) -> BasicResult<()> {
+        let mut returns: Vec<Option<u32>> = Vec::new();
+        let mut current = Some(start);
+        while let Some(line) = current {
+            let statement = self.program[&line].1.clone();
+            let next = self.program.range(line + 1..).next().map(|(&n, _)| n);
+            let flow = self.execute(&statement, input, output).map_err(|e| BasicError::AtLine(line, Box::new(e)))?;
+            current = match flow {
+                Flow::Next => next,
+                Flow::Jump(n) => Some(self.line(n).map_err(|e| BasicError::AtLine(line, Box::new(e)))?),
+                Flow::Gosub(n) => {
+                    returns.push(next);
+                    Some(self.line(n).map_err(|e| BasicError::AtLine(line, Box::new(e)))?)
+                }
+                Flow::Return => returns.pop().ok_or(BasicError::AtLine(line, Box::new(BasicError::ReturnWithoutGosub)))?,
+                Flow::End => None,
+            };
+        }
+        Ok(())
+    }
+
+    fn execute<R: BufRead, W: Write>(&mut self, statement: &Statement, input: &mut R, output: &mut W) -> BasicResult<Flow> {
+        match *statement {
+            Statement::Print(ref items) => {
+                for &(ref item, separator) in items {
+                    match *item {
+                        PrintItem::Text(ref text) => write!(output, "{}", text)?,
+                        PrintItem::Value(ref e) => write!(output, "{}", self.eval(e)?)?,
+                    }
+                    if separator == Some(',') {
+                        write!(output, " ")?;
+                    }
+                }
+                if items.last().is_none_or(|&(_, separator)| separator.is_none()) {
+                    writeln!(output)?;
+                }
+            }
+            Statement::If(ref left, op, ref right, ref then) => {
+                let (a, b) = (self.eval(left)?, self.eval(right)?);
+                let holds = match op {
+                    RelOp::Less => a < b,
+                    RelOp::LessEqual => a <= b,
+                    RelOp::Equal => a == b,
+                    RelOp::NotEqual => a != b,
+                    RelOp::GreaterEqual => a >= b,
+                    RelOp::Greater => a > b,
+                };
+                if holds {
+                    return self.execute(then, input, output);
+                }
+            }
+            Statement::Goto(ref e) => return Ok(Flow::Jump(self.eval(e)?)),
+            Statement::Gosub(ref e) => return Ok(Flow::Gosub(self.eval(e)?)),
+            Statement::Return => return Ok(Flow::Return),
+            Statement::Input(ref vars) => {
+                for &v in vars {
+                    write!(output, "? ")?;
+                    output.flush()?;
+                    let mut line = String::new();
+                    if input.read_line(&mut line)? == 0 {
+                        return Err(BasicError::EndOfInput);
+                    }
+                    let text = line.trim();
+                    self.vars[v] = text.parse().map_err(|_| BasicError::BadInput(text.to_string()))?;
+                }
+            }
+            Statement::Let(v, ref e) => self.vars[v] = self.eval(e)?,
+            Statement::Rem => {}
+            Statement::End => return Ok(Flow::End),
+            Statement::Run => {
+                self.run(input, output)?;
+                return Ok(Flow::End);
+            }
+            Statement::List => write!(output, "{}", self.listing())?,
+            Statement::Clear => self.program.clear(),
+        }
+        Ok(Flow::Next)
+    }
+
+    fn eval(&self, e: &Expr) -> BasicResult<i64> {
+        match *e {
+            Expr::Num(n) => Ok(n),
+            Expr::Var(v) => Ok(self.vars[v]),
+            Expr::Neg(ref e) => self.eval(e)?.checked_neg().ok_or(BasicError::Overflow),
+            Expr::Binary(op, ref a, ref b) => {
+                let (a, b) = (self.eval(a)?, self.eval(b)?);
+                match op {
+                    BinOp::Add => a.checked_add(b).ok_or(BasicError::Overflow),
+                    BinOp::Sub => a.checked_sub(b).ok_or(BasicError::Overflow),
+                    BinOp::Mul => a.checked_mul(b).ok_or(BasicError::Overflow),
+                    BinOp::Div if b == 0 => Err(BasicError::DivisionByZero),
+                    BinOp::Div => a.checked_div(b).ok_or(BasicError::Overflow),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str, input: &str) -> (BasicResult<()>, String) {
+        let mut basic = Interpreter::new();
+        basic.load(source).unwrap();
+        let mut output = Vec::new();
+        let result = basic.run(&mut input.as_bytes(), &mut output);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn should_run_loops_and_subroutines() {
+        let program = "
+            10 REM Sum of the squares from 1 to N
+            20 INPUT N
+            30 LET S = 0
+            40 LET I = 1
+            50 IF I > N THEN GOTO 100
+            60 GOSUB 200
+            70 S = S + Q
+            80 I = I + 1
+            90 GOTO 50
+            100 PRINT \"Sum:\", S
+            110 END
+            200 Q = I * I
+            210 RETURN";
+        assert_eq!((Ok(()), "? Sum: 385\n".to_string()), run(program, "10\n"));
+    }
+
+    #[test]
+    fn should_evaluate_expressions() {
+        let (result, output) = run("10 PRINT 1 + 2 * 3, (1 + 2) * 3, -7 / 2, 7 - 2 - 1, -(2 - 5)", "");
+        assert_eq!((Ok(()), "7 9 -3 4 3\n".to_string()), (result, output));
+        let (_, output) = run("10 let a = 6\n20 b=a*a\n30 print b;\"!\";\n40 print", "");
+        assert_eq!("36!\n", output);
+    }
+
+    #[test]
+    fn should_compare_with_every_operator() {
+        let program = "
+            10 IF 1 < 2 THEN PRINT \"<\";
+            20 IF 2 <= 2 THEN PRINT \"<=\";
+            30 IF 2 = 2 THEN PRINT \"=\";
+            40 IF 1 <> 2 THEN PRINT \"<>\";
+            50 IF 1 >< 2 THEN PRINT \"><\";
+            60 IF 3 >= 2 THEN PRINT \">=\";
+            70 IF 3 > 2 THEN PRINT \">\";
+            80 IF 3 < 2 THEN PRINT \"never\";
+            90 PRINT";
+        assert_eq!("<<==<>><>=>\n", run(program, "").1);
+    }
+
+    #[test]
+    fn should_report_runtime_errors_with_lines() {
+        let at = |line, e| Err(BasicError::AtLine(line, Box::new(e)));
+        assert_eq!(at(20, BasicError::UndefinedLine(99)), run("10 PRINT 1\n20 GOTO 99", "").0);
+        assert_eq!(at(10, BasicError::ReturnWithoutGosub), run("10 RETURN", "").0);
+        assert_eq!(at(10, BasicError::DivisionByZero), run("10 PRINT 1 / (2 - 2)", "").0);
+        assert_eq!(at(10, BasicError::Overflow), run("10 A = 9223372036854775807 + 1", "").0);
+        assert_eq!(at(10, BasicError::BadInput("x".to_string())), run("10 INPUT A", "x\n").0);
+        assert_eq!(at(10, BasicError::EndOfInput), run("10 INPUT A, B", "1\n").0);
+    }
+
+    #[test]
+    fn should_report_syntax_errors_on_entry() {
+        let mut basic = Interpreter::new();
+        for &bad in &["10 PRINT \"open", "10 IF 1 THEN END", "10 IF 1 < 2 END", "10 GOTO", "10 LET = 5",
+                      "10 A = (1", "10 INPUT 5", "10 FOO", "PRINT 1 2", "10 AB = 1"] {
+            match basic.enter(bad, &mut io::empty(), &mut io::sink()) {
+                Err(BasicError::Syntax(_)) => (),
+                other => panic!("{} gave {:?}", bad, other),
+            }
+        }
+        assert_eq!("", basic.listing());
+    }
+
+    #[test]
+    fn should_edit_list_and_run_immediately() {
+        let mut basic = Interpreter::new();
+        let mut output = Vec::new();
+        for line in &["20 PRINT \"second\"", "10 PRINT \"first\"", "30 PRINT \"gone\"", "30", "15 print x",
+                      "LIST", "X = 4", "GOTO 15", "PRINT X * 2", "GOSUB 20"] {
+            basic.enter(line, &mut io::empty(), &mut output).unwrap();
+        }
+        let expected = "10 PRINT \"first\"\n15 print x\n20 PRINT \"second\"\n4\nsecond\n8\nsecond\n";
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+        assert_eq!(Some(4), basic.var('x'));
+        basic.enter("RUN", &mut io::empty(), &mut io::sink()).unwrap();
+        assert_eq!(Some(0), basic.var('X'), "RUN clears the variables");
+        basic.enter("CLEAR", &mut io::empty(), &mut io::sink()).unwrap();
+        assert_eq!("", basic.listing());
+        assert_eq!(Err(BasicError::UndefinedLine(10)), basic.enter("GOTO 10", &mut io::empty(), &mut io::sink()));
+        assert_eq!(Err(BasicError::ReturnWithoutGosub), basic.enter("RETURN", &mut io::empty(), &mut io::sink()));
+    }
+}
@@ -0,0 +1,232 @@
+//!
+//! # Problem description:
+//! #
+//! # Tries
+//!
+//! A trie stores a set of words as a tree of letters: each word is a path
+//! from the root, and the node where it ends is marked. Looking a word up
+//! takes time proportional to its length, whatever the size of the set,
+//! and every prefix of a stored word is a node, so a search that builds
+//! words a letter at a time, as word puzzles do, can walk the trie along
+//! with it and give up as soon as no word starts the way it is going.
+//!
+
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+
+/// The letter that matches any letter in `Trie::matching`
+pub const WILDCARD: char = '?';
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: BTreeMap<char, usize>,
+    word: bool,
+}
+
+/// A set of words
+///
+/// # Example
+/// ```
+/// use pprust::trie::Trie;
+///
+/// let trie: Trie = ["car", "cart", "cat"].iter().collect();
+/// assert!(trie.contains("cart"));
+/// assert!(!trie.contains("ca"));
+/// assert!(trie.has_prefix("ca"));
+/// let ca = trie.root().step('c').and_then(|c| c.step('a')).unwrap();
+/// assert!(ca.step('r').unwrap().is_word());
+/// assert!(ca.step('b').is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Trie {
+    nodes: Vec<Node>,
+    len: usize,
+}
+
+impl Default for Trie {
+    fn default() -> Trie {
+        Trie::new()
+    }
+}
+
+impl Trie {
+    pub fn new() -> Trie {
+        Trie { nodes: vec![Node::default()], len: 0 }
+    }
+
+    /// Adds a word, returning false if it was already there
+    pub fn insert(&mut self, word: &str) -> bool {
+        let mut node = 0;
+        for c in word.chars() {
+            node = match self.nodes[node].children.get(&c) {
+                Some(&child) => child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[node].children.insert(c, child);
+                    child
+                }
+            };
+        }
+        let new = !self.nodes[node].word;
+        self.nodes[node].word = true;
+        self.len += new as usize;
+        new
+    }
+
+    /// The node reached reading `prefix` from the root
+    fn find(&self, prefix: &str) -> Option<Cursor<'_>> {
+        prefix.chars().try_fold(self.root(), |cursor, c| cursor.step(c))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.find(word).is_some_and(|cursor| cursor.is_word())
+    }
+
+    /// Whether some word starts with `prefix`
+    pub fn has_prefix(&self, prefix: &str) -> bool {
+        self.find(prefix).is_some()
+    }
+
+    /// The number of words
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The starting point for walking the trie a letter at a time
+    pub fn root(&self) -> Cursor<'_> {
+        Cursor { trie: self, node: 0 }
+    }
+
+    /// The words, in order
+    pub fn words(&self) -> Vec<String> {
+        let mut words = Vec::with_capacity(self.len);
+        let mut prefix = String::new();
+        self.collect(0, &mut prefix, &mut words);
+        words
+    }
+
+    /// The words, in order, as long as the pattern and with its letters
+    /// where it has letters; `WILDCARD` matches any letter
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::trie::Trie;
+    ///
+    /// let trie: Trie = ["bat", "bet", "boat", "but", "cat"].iter().collect();
+    /// assert_eq!(vec!["bat", "bet", "but"], trie.matching("b?t"));
+    /// assert_eq!(vec!["bat", "cat"], trie.matching("?at"));
+    /// ```
+    pub fn matching(&self, pattern: &str) -> Vec<String> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let mut words = Vec::new();
+        let mut prefix = String::new();
+        self.root().matching(&pattern, &mut prefix, &mut words);
+        words
+    }
+
+    fn collect(&self, node: usize, prefix: &mut String, words: &mut Vec<String>) {
+        if self.nodes[node].word {
+            words.push(prefix.clone());
+        }
+        for (&c, &child) in &self.nodes[node].children {
+            prefix.push(c);
+            self.collect(child, prefix, words);
+            prefix.pop();
+        }
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for Trie {
+    fn from_iter<I: IntoIterator<Item = S>>(words: I) -> Trie {
+        let mut trie = Trie::new();
+        for word in words {
+            trie.insert(word.as_ref());
+        }
+        trie
+    }
+}
+
+/// A position in a trie: the prefix read so far
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'a> {
+    trie: &'a Trie,
+    node: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// The position after reading `c`, if some word goes on that way
+    pub fn step(self, c: char) -> Option<Cursor<'a>> {
+        self.trie.nodes[self.node].children.get(&c).map(|&node| Cursor { trie: self.trie, node })
+    }
+
+    /// Whether the prefix read so far is a word
+    pub fn is_word(self) -> bool {
+        self.trie.nodes[self.node].word
+    }
+
+    /// The letters some word goes on with, in order, and the positions
+    /// after each
+    pub fn children(self) -> impl Iterator<Item = (char, Cursor<'a>)> {
+        let trie = self.trie;
+        trie.nodes[self.node].children.iter().map(move |(&c, &node)| (c, Cursor { trie, node }))
+    }
+
+    fn matching(self, pattern: &[char], prefix: &mut String, words: &mut Vec<String>) {
+        let (&first, rest) = match pattern.split_first() {
+            Some(split) => split,
+            None => {
+                if self.is_word() {
+                    words.push(prefix.clone());
+                }
+                return;
+            }
+        };
+        for (c, next) in self.children() {
+            if first == WILDCARD || first == c {
+                prefix.push(c);
+                next.matching(rest, prefix, words);
+                prefix.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_store_words_and_prefixes() {
+        let mut trie = Trie::new();
+        assert!(trie.is_empty() && !trie.contains(""));
+        assert!(trie.insert("tea"));
+        assert!(trie.insert("ten"));
+        assert!(trie.insert("te"));
+        assert!(!trie.insert("tea"));
+        assert!(trie.insert("été"));
+        assert_eq!(4, trie.len());
+        assert_eq!(vec!["te", "tea", "ten", "été"], trie.words());
+        assert!(trie.contains("te") && !trie.contains("t") && !trie.contains("teas"));
+        assert!(trie.has_prefix("") && trie.has_prefix("ét") && !trie.has_prefix("to"));
+        assert!(trie.insert(""));
+        assert!(trie.contains("") && trie.root().is_word());
+    }
+
+    #[test]
+    fn should_match_patterns_with_wildcards() {
+        let trie: Trie = ["tea", "ten", "te", "toe", "tent", "été"].iter().collect();
+        assert_eq!(vec!["tea", "ten", "toe"], trie.matching("t??"));
+        assert_eq!(vec!["tea", "ten"], trie.matching("te?"));
+        assert_eq!(vec!["tent"], trie.matching("????"));
+        assert_eq!(vec!["été"], trie.matching("?t?"));
+        assert!(trie.matching("").is_empty(), "The empty word wasn't added");
+        assert!(trie.matching("x?").is_empty() && trie.matching("?????").is_empty());
+        let letters: Vec<char> = trie.root().step('t').unwrap().children().map(|(c, _)| c).collect();
+        assert_eq!(vec!['e', 'o'], letters);
+    }
+}
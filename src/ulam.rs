@@ -0,0 +1,223 @@
+//!
+//! # Problem description:
+//! #
+//! # Ulam Spiral
+//!
+//! Write the numbers 1, 2, 3, ... in a square spiral, starting in the
+//! middle and turning counterclockwise, then mark the primes. Stanislaw
+//! Ulam did this doodling during a dull talk in 1963 and found that the
+//! primes cluster along diagonal lines, which correspond to quadratic
+//! polynomials rich in primes.
+//!
+//! ```text
+//! 17 16 15 14 13
+//! 18  5  4  3 12
+//! 19  6  1  2 11
+//! 20  7  8  9 10
+//! 21 22 23 24 25
+//! ```
+//!
+//! Coordinates have the 1 at (0, 0), x growing to the right and y
+//! upwards. The square ring at distance k from the centre ends with
+//! (2k + 1)², in its bottom right corner, so both directions of the
+//! conversion between numbers and coordinates take constant time.
+//!
+
+use sieve::sieve;
+use std::fmt;
+
+/// The coordinates of `n`, counting from 1 at the centre
+///
+/// # Example
+/// ```
+/// use pprust::ulam::position;
+///
+/// assert_eq!((1, 0), position(2));
+/// assert_eq!((-2, 2), position(17));
+/// assert_eq!((2, -2), position(25));
+/// ```
+pub fn position(n: u64) -> (i64, i64) {
+    assert!(n >= 1, "The spiral starts at 1");
+    if n == 1 {
+        return (0, 0);
+    }
+    // The ring k is the first whose corner (2k + 1)² reaches n
+    let ring_end = |k: i64| ((2 * k + 1) * (2 * k + 1)) as u64;
+    let mut k = (((n as f64).sqrt() - 1.0) / 2.0).ceil() as i64;
+    while k > 0 && ring_end(k - 1) >= n {
+        k -= 1;
+    }
+    while ring_end(k) < n {
+        k += 1;
+    }
+    let side = 2 * k;
+    let mut corner = ring_end(k);
+    let n_from = |corner: u64| (corner - n) as i64;
+    if n >= corner - side as u64 {
+        return (k - n_from(corner), -k);
+    }
+    corner -= side as u64;
+    if n >= corner - side as u64 {
+        return (-k, -k + n_from(corner));
+    }
+    corner -= side as u64;
+    if n >= corner - side as u64 {
+        return (-k + n_from(corner), k);
+    }
+    (k, k - (n_from(corner) - side))
+}
+
+/// The number at coordinates `(x, y)`
+///
+/// # Example
+/// ```
+/// use pprust::ulam::number;
+///
+/// assert_eq!(1, number(0, 0));
+/// assert_eq!(11, number(2, 0));
+/// assert_eq!(21, number(-2, -2));
+/// ```
+pub fn number(x: i64, y: i64) -> u64 {
+    let k = x.abs().max(y.abs());
+    let corner = (2 * k + 1) * (2 * k + 1);
+    let n = if y == -k {
+        corner - (k - x)
+    } else if x == -k {
+        corner - 2 * k - (y + k)
+    } else if y == k {
+        corner - 4 * k - (x + k)
+    } else {
+        corner - 7 * k + y
+    };
+    n as u64
+}
+
+/// A square of the spiral with its primes marked
+#[derive(Debug, Clone)]
+pub struct Spiral {
+    size: usize,
+    numbers: Vec<u64>,
+    primes: Vec<bool>,
+}
+
+/// The spiral of the numbers 1 to `size`², for an odd `size`
+///
+/// # Example
+/// ```
+/// use pprust::ulam::spiral;
+///
+/// let s = spiral(5);
+/// assert_eq!(17, s.number(0, 0));
+/// assert!(s.is_prime(0, 0));
+/// ```
+pub fn spiral(size: usize) -> Spiral {
+    assert!(size % 2 == 1, "The spiral needs an odd size to have a centre");
+    let k = (size / 2) as i64;
+    let numbers: Vec<u64> = (0..size * size)
+        .map(|i| number(i as i64 % size as i64 - k, k - i as i64 / size as i64))
+        .collect();
+    let table = sieve(size * size);
+    let primes = numbers.iter().map(|&n| table[n as usize]).collect();
+    Spiral { size, numbers, primes }
+}
+
+impl Spiral {
+    /// Width and height
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number in a cell, by row from the top and column from the left
+    pub fn number(&self, row: usize, col: usize) -> u64 {
+        self.numbers[row * self.size + col]
+    }
+
+    /// Whether the number in a cell is prime
+    pub fn is_prime(&self, row: usize, col: usize) -> bool {
+        self.primes[row * self.size + col]
+    }
+
+    /// One character per cell: `prime` for the primes, `other` for the
+    /// rest
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::ulam::spiral;
+    ///
+    /// assert_eq!("#.#\n..#\n#..\n", spiral(3).render(b'#', b'.'));
+    /// ```
+    pub fn render(&self, prime: u8, other: u8) -> String {
+        let mut text = String::with_capacity(self.size * (self.size + 1));
+        for row in self.primes.chunks(self.size) {
+            text.extend(row.iter().map(|&p| if p { prime as char } else { other as char }));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// A binary PPM image, one pixel per cell, the primes black on white
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut image = format!("P6\n{} {}\n255\n", self.size, self.size).into_bytes();
+        for &p in &self.primes {
+            let shade = if p { 0 } else { 255 };
+            image.extend_from_slice(&[shade, shade, shade]);
+        }
+        image
+    }
+}
+
+impl fmt::Display for Spiral {
+    /// The numbers themselves, right aligned
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = (self.size * self.size).to_string().len();
+        for row in self.numbers.chunks(self.size) {
+            let cells: Vec<String> = row.iter().map(|n| format!("{:>w$}", n, w = width)).collect();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_both_ways() {
+        for n in 1..20_000 {
+            let (x, y) = position(n);
+            assert_eq!(n, number(x, y), "{} at {:?}", n, (x, y));
+        }
+        for x in -50..=50 {
+            for y in -50..=50 {
+                assert_eq!((x, y), position(number(x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn should_step_to_neighbours() {
+        for n in 1..10_000 {
+            let ((x0, y0), (x1, y1)) = (position(n), position(n + 1));
+            assert_eq!(1, (x1 - x0).abs() + (y1 - y0).abs(), "{} to {}", n, n + 1);
+        }
+    }
+
+    #[test]
+    fn should_lay_out_the_spiral() {
+        let expected = "17 16 15 14 13\n18  5  4  3 12\n19  6  1  2 11\n20  7  8  9 10\n21 22 23 24 25\n";
+        assert_eq!(expected, spiral(5).to_string());
+        let (x, y) = position(1_000_000_007);
+        assert_eq!(1_000_000_007, number(x, y));
+    }
+
+    #[test]
+    fn should_write_ppm() {
+        let image = spiral(3).to_ppm();
+        let header = b"P6\n3 3\n255\n";
+        assert_eq!(&header[..], &image[..header.len()]);
+        assert_eq!(header.len() + 27, image.len());
+        // The centre is 1, not prime
+        assert_eq!(255, image[header.len() + 12]);
+    }
+}
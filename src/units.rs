@@ -0,0 +1,371 @@
+//!
+//! # Problem description:
+//! #
+//! # Units and Dimensions
+//!
+//! A physical quantity is a number times a unit, and its dimension says
+//! how it is built from the base quantities: a speed is a length over a
+//! time. Dimensional analysis checks formulas by the dimensions alone.
+//! Quantities of any dimensions can be multiplied or divided, their
+//! dimensions multiplying along, but only quantities of the same
+//! dimension can be added, subtracted or compared.
+//!
+//! Here a dimension is the list of exponents of the seven SI base units,
+//! and quantities are kept in base units, so `3 km` is `3000 m`. Units
+//! are written like `m`, `kg*m/s^2`, `1/s` or `s^-1`.
+//!
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::result;
+use std::str::FromStr;
+
+/// The SI base units, in the order of a dimension's exponents
+pub const BASE_UNITS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+/// Errors reading a quantity
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitsError {
+    /// The text is not a number followed by a unit
+    Syntax(String),
+    /// The unit is not known
+    UnknownUnit(String),
+}
+
+impl fmt::Display for UnitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UnitsError::Syntax(ref text) => write!(f, "'{}' is not a quantity", text),
+            UnitsError::UnknownUnit(ref unit) => write!(f, "Unknown unit '{}'", unit),
+        }
+    }
+}
+
+/// The result of reading a quantity
+pub type UnitsResult<T> = result::Result<T, UnitsError>;
+
+/// The exponents of the base units in a quantity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Dimension(pub [i32; 7]);
+
+impl Dimension {
+    /// The dimension of pure numbers
+    pub fn none() -> Dimension {
+        Dimension([0; 7])
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.0 == [0; 7]
+    }
+
+    fn combine(&self, other: &Dimension, sign: i32) -> Dimension {
+        let mut exponents = self.0;
+        for (e, &o) in exponents.iter_mut().zip(&other.0) {
+            *e += sign * o;
+        }
+        Dimension(exponents)
+    }
+
+    /// The dimension raised to the `n`th power
+    pub fn pow(&self, n: i32) -> Dimension {
+        Dimension(self.0.map(|e| e * n))
+    }
+
+    /// The `n`th root, if every exponent divides evenly
+    pub fn root(&self, n: i32) -> Option<Dimension> {
+        if self.0.iter().all(|e| e % n == 0) {
+            Some(Dimension(self.0.map(|e| e / n)))
+        } else {
+            None
+        }
+    }
+}
+
+impl Mul for Dimension {
+    type Output = Dimension;
+
+    fn mul(self, other: Dimension) -> Dimension {
+        self.combine(&other, 1)
+    }
+}
+
+impl Div for Dimension {
+    type Output = Dimension;
+
+    fn div(self, other: Dimension) -> Dimension {
+        self.combine(&other, -1)
+    }
+}
+
+/// Shows the base units with positive exponents, then those with
+/// negative ones after a slash, as in `kg*m/s^2` or `m/(kg*s)`; with
+/// only negative exponents, as in `s^-1`
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let part = |sign: i32| -> Vec<String> {
+            BASE_UNITS
+                .iter()
+                .zip(&self.0)
+                .filter(|&(_, &e)| e * sign > 0)
+                .map(|(unit, &e)| if e * sign == 1 { unit.to_string() } else { format!("{}^{}", unit, e * sign) })
+                .collect()
+        };
+        let (above, below) = (part(1), part(-1));
+        let text = if above.is_empty() {
+            let exponents = BASE_UNITS.iter().zip(&self.0).filter(|&(_, &e)| e != 0);
+            exponents.map(|(unit, &e)| format!("{}^{}", unit, e)).collect::<Vec<_>>().join("*")
+        } else {
+            match below.len() {
+                0 => above.join("*"),
+                1 => format!("{}/{}", above.join("*"), below[0]),
+                _ => format!("{}/({})", above.join("*"), below.join("*")),
+            }
+        };
+        f.pad(&text)
+    }
+}
+
+/// A number with a dimension, in base units.
+///
+/// Adding or subtracting quantities of different dimensions panics; the
+/// `checked_*` methods return `None` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Quantity {
+    pub value: f64,
+    pub dimension: Dimension,
+}
+
+impl Quantity {
+    pub fn new(value: f64, dimension: Dimension) -> Quantity {
+        Quantity { value, dimension }
+    }
+
+    /// The pure number `value`
+    pub fn number(value: f64) -> Quantity {
+        Quantity { value, dimension: Dimension::none() }
+    }
+
+    /// A named unit, as a quantity in base units
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::units::Quantity;
+    ///
+    /// assert_eq!("1000 m", Quantity::unit("km").unwrap().to_string());
+    /// assert_eq!("1 kg*m/s^2", Quantity::unit("N").unwrap().to_string());
+    /// assert_eq!(None, Quantity::unit("furlong"));
+    /// ```
+    pub fn unit(name: &str) -> Option<Quantity> {
+        let (kg, m, s, a, k, mol, cd) = (0, 1, 2, 3, 4, 5, 6);
+        let base = |i: usize| {
+            let mut exponents = [0; 7];
+            exponents[i] = 1;
+            Dimension(exponents)
+        };
+        let newton = base(kg) * base(m) / base(s).pow(2);
+        let (value, dimension) = match name {
+            "kg" => (1.0, base(kg)),
+            "g" => (1e-3, base(kg)),
+            "t" => (1e3, base(kg)),
+            "m" => (1.0, base(m)),
+            "km" => (1e3, base(m)),
+            "cm" => (1e-2, base(m)),
+            "mm" => (1e-3, base(m)),
+            "s" => (1.0, base(s)),
+            "ms" => (1e-3, base(s)),
+            "min" => (60.0, base(s)),
+            "h" => (3600.0, base(s)),
+            "A" => (1.0, base(a)),
+            "K" => (1.0, base(k)),
+            "mol" => (1.0, base(mol)),
+            "cd" => (1.0, base(cd)),
+            "Hz" => (1.0, Dimension::none() / base(s)),
+            "N" => (1.0, newton),
+            "Pa" => (1.0, newton / base(m).pow(2)),
+            "J" => (1.0, newton * base(m)),
+            "W" => (1.0, newton * base(m) / base(s)),
+            "C" => (1.0, base(a) * base(s)),
+            "V" => (1.0, newton * base(m) / base(s) / base(a)),
+            _ => return None,
+        };
+        Some(Quantity { value, dimension })
+    }
+
+    /// A unit expression like `kg*m/s^2`, where every unit after a slash
+    /// divides
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::units::Quantity;
+    ///
+    /// let speed = Quantity::parse_unit("km/h").unwrap();
+    /// assert_eq!(Quantity::parse_unit("m/s").unwrap().dimension, speed.dimension);
+    /// assert!((speed.value - 1.0 / 3.6).abs() < 1e-12);
+    /// ```
+    pub fn parse_unit(text: &str) -> UnitsResult<Quantity> {
+        let unknown = || UnitsError::UnknownUnit(text.to_string());
+        let mut result = Quantity::number(1.0);
+        for (i, group) in text.split('/').enumerate() {
+            for factor in group.split('*') {
+                let (name, exponent) = match factor.find('^') {
+                    Some(k) => (&factor[..k], factor[k + 1..].parse::<i32>().map_err(|_| unknown())?),
+                    None => (factor, 1),
+                };
+                let unit = if name == "1" { Quantity::number(1.0) } else { Quantity::unit(name).ok_or_else(unknown)? };
+                let exponent = if i == 0 { exponent } else { -exponent };
+                result = result * Quantity { value: unit.value.powi(exponent), dimension: unit.dimension.pow(exponent) };
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn checked_add(&self, other: &Quantity) -> Option<Quantity> {
+        if self.dimension == other.dimension {
+            Some(Quantity { value: self.value + other.value, dimension: self.dimension })
+        } else {
+            None
+        }
+    }
+
+    pub fn checked_sub(&self, other: &Quantity) -> Option<Quantity> {
+        if self.dimension == other.dimension {
+            Some(Quantity { value: self.value - other.value, dimension: self.dimension })
+        } else {
+            None
+        }
+    }
+
+    /// The square root, if the dimension has one
+    pub fn sqrt(&self) -> Option<Quantity> {
+        self.dimension.root(2).map(|dimension| Quantity { value: self.value.sqrt(), dimension })
+    }
+}
+
+impl Add for Quantity {
+    type Output = Quantity;
+
+    fn add(self, other: Quantity) -> Quantity {
+        self.checked_add(&other).expect("Adding quantities of different dimensions")
+    }
+}
+
+impl Sub for Quantity {
+    type Output = Quantity;
+
+    fn sub(self, other: Quantity) -> Quantity {
+        self.checked_sub(&other).expect("Subtracting quantities of different dimensions")
+    }
+}
+
+impl Mul for Quantity {
+    type Output = Quantity;
+
+    fn mul(self, other: Quantity) -> Quantity {
+        Quantity { value: self.value * other.value, dimension: self.dimension * other.dimension }
+    }
+}
+
+impl Div for Quantity {
+    type Output = Quantity;
+
+    fn div(self, other: Quantity) -> Quantity {
+        Quantity { value: self.value / other.value, dimension: self.dimension / other.dimension }
+    }
+}
+
+/// Only quantities of the same dimension are ordered
+impl PartialOrd for Quantity {
+    fn partial_cmp(&self, other: &Quantity) -> Option<Ordering> {
+        if self.dimension == other.dimension {
+            self.value.partial_cmp(&other.value)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dimension.is_none() {
+            write!(f, "{}", self.value)
+        } else {
+            write!(f, "{} {}", self.value, self.dimension)
+        }
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = UnitsError;
+
+    /// Reads a number, optionally followed by a unit: `3`, `9.81m/s^2`
+    ///
+    /// # Example
+    /// ```
+    /// use pprust::units::Quantity;
+    ///
+    /// assert_eq!("2500 m", "2.5km".parse::<Quantity>().unwrap().to_string());
+    /// assert!("km".parse::<Quantity>().is_err());
+    /// ```
+    fn from_str(text: &str) -> UnitsResult<Quantity> {
+        // The longest prefix that is a number
+        let split = (1..=text.len())
+            .rev()
+            .filter(|&k| text.is_char_boundary(k))
+            .find(|&k| text[..k].parse::<f64>().is_ok())
+            .ok_or_else(|| UnitsError::Syntax(text.to_string()))?;
+        let value: f64 = text[..split].parse().expect("Already checked it is a number");
+        if split == text.len() {
+            return Ok(Quantity::number(value));
+        }
+        // `2/s` is two per second
+        let unit = &text[split..];
+        let unit = if unit.starts_with('/') { format!("1{}", unit) } else { unit.to_string() };
+        Ok(Quantity::number(value) * Quantity::parse_unit(&unit)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(text: &str) -> Quantity {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn should_track_dimensions() {
+        assert_eq!("0.75 m/s", (q("3m") / q("4s")).to_string());
+        assert_eq!("12 m^2", (q("3m") * q("4m")).to_string());
+        assert_eq!("2", (q("6m") / q("3m")).to_string());
+        assert_eq!("5 s^-1", (q("10") / q("2s")).to_string());
+        assert_eq!("2 kg*m^2/s^2", (q("1N") * q("2m")).to_string());
+        assert_eq!(q("1J").dimension, (q("1kg") * q("1m/s") * q("1m/s")).dimension);
+        assert_eq!("1 m/(kg*s)", (q("1m") / q("1kg") / q("1s")).to_string());
+        assert_eq!("1 s*A", q("1C").to_string());
+    }
+
+    #[test]
+    fn should_only_add_matching_dimensions() {
+        assert_eq!(Some(q("1100m")), q("1km").checked_add(&q("100m")));
+        assert_eq!(None, q("1km").checked_add(&q("1s")));
+        assert_eq!(None, q("1km").checked_sub(&q("1")));
+        assert!(q("1km") > q("999m"));
+        assert_eq!(None, q("1km").partial_cmp(&q("1kg")));
+    }
+
+    #[test]
+    fn should_parse_units() {
+        assert_eq!(q("1N").dimension, q("1kg*m/s^2").dimension);
+        assert_eq!(q("1N").dimension, q("1kg*m*s^-2").dimension);
+        assert_eq!(q("1Hz"), q("1/s"));
+        assert_eq!(q("2s^-1"), q("2/s"));
+        assert_eq!(Quantity::number(-1.5e3), q("-1.5e3"));
+        assert_eq!(q("60s"), q("1min"));
+        assert_eq!(Some(q("3m")), q("9m^2").sqrt());
+        assert_eq!(None, q("9m").sqrt());
+        for &bad in &["", "m", "3furlong", "3m/", "3m^x", "3 m"] {
+            assert!(bad.parse::<Quantity>().is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//!
+//! # Problem description:
+//! #
+//! # UTF-8
+//!
+//! UTF-8 writes each Unicode code point in one to four bytes. Code points
+//! below 0x80 are one byte, themselves, so ASCII text is UTF-8 already.
+//! Larger ones start with a byte whose high bits, 110, 1110 or 11110, say
+//! how many bytes follow, and each of those starts with 10 and carries six
+//! more bits of the code point.
+//!
+//! A decoder must not take everything that fits that pattern. A code point
+//! written with more bytes than it needs, an overlong form, could sneak a
+//! `/` or a NUL past a check on the bytes; the surrogates, U+D800 to
+//! U+DFFF, are halves of UTF-16 pairs and not characters; and nothing
+//! above U+10FFFF exists. The decoder here finds each of those, and after
+//! an error goes on from the end of the longest start of a valid
+//! sequence, which Unicode recommends replacing with a single U+FFFD.
+//!
+
+use std::error;
+use std::fmt;
+
+/// What is wrong with some bytes or a code point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A continuation byte, 10xxxxxx, where a sequence should start
+    UnexpectedContinuation,
+    /// A sequence cut short by the end of the bytes or by a byte that
+    /// isn't a continuation
+    Truncated,
+    /// A code point in more bytes than it needs, or a first byte, C0 or
+    /// C1, that can only start one
+    Overlong,
+    /// A UTF-16 surrogate, U+D800 to U+DFFF
+    Surrogate,
+    /// A code point above U+10FFFF, or a first byte, F5 to FF, that can
+    /// only start one
+    TooLarge,
+}
+
+/// An invalid sequence in bytes decoded: where it starts, how many bytes
+/// it takes, which a decoder skips, and what is wrong with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf8Error {
+    pub offset: usize,
+    pub length: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ErrorKind::UnexpectedContinuation => write!(f, "Continuation byte without a first byte"),
+            ErrorKind::Truncated => write!(f, "Sequence cut short"),
+            ErrorKind::Overlong => write!(f, "Overlong form"),
+            ErrorKind::Surrogate => write!(f, "UTF-16 surrogate"),
+            ErrorKind::TooLarge => write!(f, "Beyond U+10FFFF"),
+        }
+    }
+}
+
+impl fmt::Display for Utf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl error::Error for ErrorKind {}
+
+impl error::Error for Utf8Error {}
+
+/// Encodes a code point, failing for surrogates and beyond U+10FFFF
+///
+/// # Example
+/// ```
+/// use pprust::utf8lite::{encode, ErrorKind};
+///
+/// assert_eq!(Ok(vec![0x41]), encode(0x41));
+/// assert_eq!(Ok(vec![0xe2, 0x82, 0xac]), encode(0x20ac));
+/// assert_eq!(Ok(vec![0xf0, 0x9f, 0x98, 0x80]), encode(0x1f600));
+/// assert_eq!(Err(ErrorKind::Surrogate), encode(0xd800));
+/// ```
+pub fn encode(code_point: u32) -> Result<Vec<u8>, ErrorKind> {
+    let c = code_point;
+    match c {
+        0..=0x7f => Ok(vec![c as u8]),
+        0x80..=0x7ff => Ok(vec![0xc0 | (c >> 6) as u8, continuation(c)]),
+        0xd800..=0xdfff => Err(ErrorKind::Surrogate),
+        0x800..=0xffff => Ok(vec![0xe0 | (c >> 12) as u8, continuation(c >> 6), continuation(c)]),
+        0x1_0000..=0x10_ffff => {
+            Ok(vec![0xf0 | (c >> 18) as u8, continuation(c >> 12), continuation(c >> 6), continuation(c)])
+        }
+        _ => Err(ErrorKind::TooLarge),
+    }
+}
+
+/// The continuation byte for the low six bits
+fn continuation(bits: u32) -> u8 {
+    0x80 | (bits & 0x3f) as u8
+}
+
+/// Encodes code points one after the other
+pub fn encode_all(code_points: &[u32]) -> Result<Vec<u8>, ErrorKind> {
+    let mut bytes = Vec::with_capacity(code_points.len());
+    for &c in code_points {
+        bytes.extend(encode(c)?);
+    }
+    Ok(bytes)
+}
+
+/// Decodes the sequence at the start of some bytes, which mustn't be
+/// empty: the code point and its length, or what is wrong and the length
+/// of the longest start of a valid sequence, at least 1
+fn decode_first(bytes: &[u8]) -> Result<(u32, usize), (ErrorKind, usize)> {
+    let first = bytes[0];
+    // How many bytes follow, the bits of the first byte, and the range of
+    // the second byte with what is wrong outside it
+    let (following, bits, second, outside) = match first {
+        0x00..=0x7f => return Ok((first as u32, 1)),
+        0x80..=0xbf => return Err((ErrorKind::UnexpectedContinuation, 1)),
+        0xc0 | 0xc1 => return Err((ErrorKind::Overlong, 1)),
+        0xc2..=0xdf => (1, first & 0x1f, 0x80..=0xbf, ErrorKind::Truncated),
+        0xe0 => (2, first & 0x0f, 0xa0..=0xbf, ErrorKind::Overlong),
+        0xed => (2, first & 0x0f, 0x80..=0x9f, ErrorKind::Surrogate),
+        0xe1..=0xec | 0xee | 0xef => (2, first & 0x0f, 0x80..=0xbf, ErrorKind::Truncated),
+        0xf0 => (3, first & 0x07, 0x90..=0xbf, ErrorKind::Overlong),
+        0xf4 => (3, first & 0x07, 0x80..=0x8f, ErrorKind::TooLarge),
+        0xf1..=0xf3 => (3, first & 0x07, 0x80..=0xbf, ErrorKind::Truncated),
+        0xf5..=0xff => return Err((ErrorKind::TooLarge, 1)),
+    };
+    let mut code_point = bits as u32;
+    for i in 1..=following {
+        let byte = match bytes.get(i) {
+            Some(&byte) if byte & 0xc0 == 0x80 => byte,
+            _ => return Err((ErrorKind::Truncated, i)),
+        };
+        if i == 1 && !second.contains(&byte) {
+            return Err((outside, 1));
+        }
+        code_point = (code_point << 6) | (byte & 0x3f) as u32;
+    }
+    Ok((code_point, following + 1))
+}
+
+/// The characters of some bytes, or the invalid sequences among them, in
+/// order; see `chars`
+#[derive(Debug, Clone)]
+pub struct Chars<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+/// Iterates over the characters of some bytes, validating them. Each
+/// invalid sequence is an error, and the characters after it follow.
+///
+/// # Example
+/// ```
+/// use pprust::utf8lite::{chars, ErrorKind, Utf8Error};
+///
+/// let decoded: Vec<_> = chars(b"a\xc0\xafb\xe2\x82").collect();
+/// let overlong = Utf8Error { offset: 1, length: 1, kind: ErrorKind::Overlong };
+/// let stray = Utf8Error { offset: 2, length: 1, kind: ErrorKind::UnexpectedContinuation };
+/// let truncated = Utf8Error { offset: 4, length: 2, kind: ErrorKind::Truncated };
+/// assert_eq!(vec![Ok('a'), Err(overlong), Err(stray), Ok('b'), Err(truncated)], decoded);
+/// ```
+pub fn chars(bytes: &[u8]) -> Chars<'_> {
+    Chars { bytes, offset: 0 }
+}
+
+impl<'a> Iterator for Chars<'a> {
+    type Item = Result<char, Utf8Error>;
+
+    fn next(&mut self) -> Option<Result<char, Utf8Error>> {
+        if self.offset == self.bytes.len() {
+            return None;
+        }
+        let offset = self.offset;
+        match decode_first(&self.bytes[offset..]) {
+            Ok((code_point, length)) => {
+                self.offset += length;
+                Some(Ok(char::from_u32(code_point).expect("The checks leave only scalar values")))
+            }
+            Err((kind, length)) => {
+                self.offset += length;
+                Some(Err(Utf8Error { offset, length, kind }))
+            }
+        }
+    }
+}
+
+/// Decodes bytes that must be valid UTF-8
+///
+/// # Example
+/// ```
+/// use pprust::utf8lite::{decode, ErrorKind, Utf8Error};
+///
+/// assert_eq!(Ok("\u{3ba}\u{1f79}\u{3c3}\u{3bc}\u{3b5}".to_string()), decode(b"\xce\xba\xe1\xbd\xb9\xcf\x83\xce\xbc\xce\xb5"));
+/// let surrogate = Utf8Error { offset: 2, length: 1, kind: ErrorKind::Surrogate };
+/// assert_eq!(Err(surrogate), decode(b"ok\xed\xa0\x80"));
+/// ```
+pub fn decode(bytes: &[u8]) -> Result<String, Utf8Error> {
+    chars(bytes).collect()
+}
+
+/// Decodes bytes, putting U+FFFD for each invalid sequence
+///
+/// # Example
+/// ```
+/// use pprust::utf8lite::decode_lossy;
+///
+/// assert_eq!("a\u{fffd}b\u{fffd}", decode_lossy(b"a\xf0\x9f\x98b\xff"));
+/// ```
+pub fn decode_lossy(bytes: &[u8]) -> String {
+    chars(bytes).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// The cases of the stress test: name, bytes and what decoding them
+    /// gives, a code point or `None` for an invalid sequence
+    fn stress_test() -> Vec<(String, Vec<u8>, Vec<Option<u32>>)> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/utf8lite/stress.txt");
+        let text = fs::read_to_string(&path).unwrap();
+        let cases = text.lines().filter(|line| !line.starts_with('#')).map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let bytes = fields[1].split_whitespace().map(|byte| u8::from_str_radix(byte, 16).unwrap()).collect();
+            let decoded = fields[2].split_whitespace().map(|c| u32::from_str_radix(c, 16).ok()).collect();
+            (fields[0].to_string(), bytes, decoded)
+        });
+        cases.collect()
+    }
+
+    #[test]
+    fn should_pass_the_stress_test() {
+        let cases = stress_test();
+        assert_eq!(80, cases.len());
+        for (name, bytes, expected) in &cases {
+            let decoded: Vec<Option<u32>> = chars(bytes).map(|c| c.ok().map(u32::from)).collect();
+            assert_eq!(expected, &decoded, "{}", name);
+            assert_eq!(String::from_utf8_lossy(bytes), decode_lossy(bytes), "{}", name);
+            assert_eq!(std::str::from_utf8(bytes).is_ok(), decode(bytes).is_ok(), "{}", name);
+            if let Ok(text) = decode(bytes) {
+                let code_points: Vec<u32> = text.chars().map(u32::from).collect();
+                assert_eq!(Ok(bytes.clone()), encode_all(&code_points), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn should_tell_what_is_wrong() {
+        let kind = |bytes: &[u8]| decode(bytes).map_err(|e| e.kind);
+        assert_eq!(Err(ErrorKind::Overlong), kind(b"\xc0\xaf"));
+        assert_eq!(Err(ErrorKind::Overlong), kind(b"\xe0\x80\xaf"));
+        assert_eq!(Err(ErrorKind::Overlong), kind(b"\xf0\x8f\xbf\xbf"));
+        assert_eq!(Err(ErrorKind::Surrogate), kind(b"\xed\xbf\xbf"));
+        assert_eq!(Err(ErrorKind::TooLarge), kind(b"\xf4\x90\x80\x80"));
+        assert_eq!(Err(ErrorKind::TooLarge), kind(b"\xf8\x88\x80\x80\x80"));
+        assert_eq!(Err(ErrorKind::Truncated), kind(b"\xe2\x82 "));
+        assert_eq!(Err(ErrorKind::UnexpectedContinuation), kind(b"\xbf"));
+        let error = decode(b"abc\xf0\x9f\x98").unwrap_err();
+        assert_eq!(Utf8Error { offset: 3, length: 3, kind: ErrorKind::Truncated }, error);
+        assert_eq!("Sequence cut short at byte 3", error.to_string());
+        assert_eq!(Err(ErrorKind::TooLarge), encode(0x11_0000));
+        assert_eq!(Err(ErrorKind::Surrogate), encode_all(&[0x41, 0xdfff]));
+    }
+
+    #[test]
+    fn should_encode_every_scalar_value_as_the_standard_library() {
+        let mut buffer = [0; 4];
+        for c in (0..=0x10_ffff).filter_map(char::from_u32) {
+            let expected = c.encode_utf8(&mut buffer).as_bytes();
+            assert_eq!(Ok(expected.to_vec()), encode(c as u32));
+            assert_eq!(Some(Ok(c)), chars(expected).next());
+        }
+    }
+}
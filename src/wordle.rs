@@ -0,0 +1,264 @@
+//!
+//! # Problem description:
+//! #
+//! # Wordle
+//!
+//! The player has six tries to guess a hidden five-letter word. After
+//! each guess, every letter is marked green if it is in the word in that
+//! place, yellow if it is in the word somewhere else, and gray if it
+//! isn't. A letter guessed twice is yellow twice only if the word has it
+//! twice; greens are counted first.
+//!
+//! A solver keeps the answers still possible: those that would have
+//! given the same marks to every guess so far. Each guess splits them by
+//! the marks it would get, and a good guess splits them evenly, so that
+//! whatever the marks, few answers are left. The suggester picks the
+//! guess whose split has the most entropy, the expected information in
+//! bits, among all the words allowed, not only the possible answers.
+//!
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// How many guesses the player has
+pub const TRIES: usize = 6;
+
+/// The mark a letter of a guess gets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mark {
+    Gray,
+    Yellow,
+    Green,
+}
+
+/// The marks of each letter of a guess against an answer of the same
+/// length
+///
+/// # Example
+/// ```
+/// use pprust::wordle::feedback;
+/// use pprust::wordle::Mark::*;
+///
+/// assert_eq!(vec![Gray, Gray, Yellow, Gray, Yellow], feedback("speed", "abide"));
+/// assert_eq!(vec![Green, Gray, Gray, Gray, Green], feedback("eerie", "elope"));
+/// ```
+pub fn feedback(guess: &str, answer: &str) -> Vec<Mark> {
+    let guess: Vec<char> = guess.chars().collect();
+    let answer: Vec<char> = answer.chars().collect();
+    assert_eq!(guess.len(), answer.len(), "The guess and the answer have different lengths");
+    let mut marks = vec![Mark::Gray; guess.len()];
+    // The letters of the answer not matched by a green, for the yellows
+    let mut left: BTreeMap<char, usize> = BTreeMap::new();
+    for (i, (&g, &a)) in guess.iter().zip(&answer).enumerate() {
+        if g == a {
+            marks[i] = Mark::Green;
+        } else {
+            *left.entry(a).or_insert(0) += 1;
+        }
+    }
+    for (mark, g) in marks.iter_mut().zip(&guess) {
+        if *mark == Mark::Gray {
+            if let Some(count) = left.get_mut(g).filter(|count| **count > 0) {
+                *count -= 1;
+                *mark = Mark::Yellow;
+            }
+        }
+    }
+    marks
+}
+
+/// The marks as a line of colored squares, the way players share them
+pub fn show(marks: &[Mark]) -> String {
+    marks
+        .iter()
+        .map(|mark| match mark {
+            Mark::Green => '🟩',
+            Mark::Yellow => '🟨',
+            Mark::Gray => '⬜',
+        })
+        .collect()
+}
+
+/// The candidates that would have given these marks to the guess
+///
+/// # Example
+/// ```
+/// use pprust::wordle::{feedback, filter};
+///
+/// let candidates = ["crane", "crate", "trace", "grace"];
+/// let marks = feedback("caret", "trace");
+/// assert_eq!(vec!["trace"], filter(&candidates, "caret", &marks));
+/// ```
+pub fn filter<'a>(candidates: &[&'a str], guess: &str, marks: &[Mark]) -> Vec<&'a str> {
+    candidates.iter().cloned().filter(|c| feedback(guess, c) == marks).collect()
+}
+
+/// How many candidates would get each set of marks from the guess
+fn partition(guess: &str, candidates: &[&str]) -> BTreeMap<Vec<Mark>, usize> {
+    let mut parts = BTreeMap::new();
+    for candidate in candidates {
+        *parts.entry(feedback(guess, candidate)).or_insert(0) += 1;
+    }
+    parts
+}
+
+/// The expected information, in bits, the marks for a guess give when
+/// any of the candidates is as likely to be the answer
+///
+/// # Example
+/// ```
+/// use pprust::wordle::entropy;
+///
+/// let candidates = ["bills", "fills", "hills", "kills"];
+/// assert_eq!(0.0, entropy("pills", &candidates));
+/// assert_eq!(1.5, entropy("bfzzz", &candidates));
+/// assert_eq!(2.0, entropy("bhfkz", &candidates));
+/// ```
+pub fn entropy(guess: &str, candidates: &[&str]) -> f64 {
+    let total = candidates.len() as f64;
+    partition(guess, candidates)
+        .values()
+        .map(|&n| {
+            let p = n as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// The guess among `allowed` with the most entropy for the candidates.
+/// Ties go to a guess that could be the answer, and then to the first.
+/// With one or two candidates left, guessing one of them is at least as
+/// good, so that is the choice. `None` if there are no candidates.
+pub fn suggest<'a>(allowed: &[&'a str], candidates: &[&'a str]) -> Option<&'a str> {
+    if candidates.len() <= 2 {
+        return candidates.first().cloned();
+    }
+    let mut best: Option<((f64, bool), &str)> = None;
+    for &guess in allowed.iter().chain(candidates) {
+        let key = (entropy(guess, candidates), candidates.contains(&guess));
+        if best.is_none_or(|(k, _)| key.partial_cmp(&k) == Some(Ordering::Greater)) {
+            best = Some((key, guess));
+        }
+    }
+    best.map(|(_, guess)| guess)
+}
+
+/// The guesses the suggester makes to find an answer, the last one being
+/// the answer, starting with `opening`
+///
+/// # Example
+/// ```
+/// use pprust::wordle::solve;
+///
+/// let answers = ["crane", "crate", "trace", "grace", "brace", "caret"];
+/// let guesses = solve("grace", "crane", &answers, &answers);
+/// assert_eq!(Some(&"grace"), guesses.last());
+/// ```
+pub fn solve<'a>(answer: &str, opening: &'a str, allowed: &[&'a str], answers: &[&'a str]) -> Vec<&'a str> {
+    let mut candidates = answers.to_vec();
+    let mut guesses = vec![opening];
+    let mut guess = opening;
+    while guess != answer {
+        candidates = filter(&candidates, guess, &feedback(guess, answer));
+        guess = match suggest(allowed, &candidates) {
+            Some(guess) => guess,
+            None => break,
+        };
+        guesses.push(guess);
+    }
+    guesses
+}
+
+/// How the suggester did over a list of answers
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    /// The first guess, the same for every game
+    pub opening: String,
+    /// How many games were played, one per answer
+    pub games: usize,
+    /// The guesses over all games, including the right ones
+    pub guesses: usize,
+    /// The most guesses a game took
+    pub worst: usize,
+    /// How many games took more than `TRIES` guesses
+    pub lost: usize,
+}
+
+impl Simulation {
+    /// The average number of guesses per game
+    pub fn average(&self) -> f64 {
+        self.guesses as f64 / self.games as f64
+    }
+}
+
+/// Plays a game for each of the answers, with the suggester knowing the
+/// list, and reports the guesses needed
+///
+/// # Example
+/// ```
+/// use pprust::wordle::simulate;
+///
+/// let answers = ["crane", "crate", "trace", "grace", "brace", "caret", "cater", "react"];
+/// let report = simulate(&answers, &answers);
+/// assert_eq!((8, 0), (report.games, report.lost));
+/// assert!(report.average() < 3.0);
+/// ```
+pub fn simulate(allowed: &[&str], answers: &[&str]) -> Simulation {
+    let opening = suggest(allowed, answers).expect("There are answers");
+    let mut simulation = Simulation { opening: opening.to_string(), games: 0, guesses: 0, worst: 0, lost: 0 };
+    for answer in answers {
+        let n = solve(answer, opening, allowed, answers).len();
+        simulation.games += 1;
+        simulation.guesses += n;
+        simulation.worst = simulation.worst.max(n);
+        if n > TRIES {
+            simulation.lost += 1;
+        }
+    }
+    simulation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::Mark::*;
+
+    const ANSWERS: [&str; 20] = [
+        "about", "above", "abuse", "actor", "acute", "admit", "adopt", "adult", "after", "again", "agent", "agree",
+        "ahead", "alarm", "album", "alert", "alike", "alive", "allow", "alone",
+    ];
+
+    #[test]
+    fn should_mark_repeated_letters() {
+        assert_eq!(vec![Green; 5], feedback("alone", "alone"));
+        assert_eq!(vec![Gray; 5], feedback("bbbbb", "alone"));
+        assert_eq!(vec![Gray, Green, Yellow, Gray, Green], feedback("llama", "alpha"), "One l for one l");
+        assert_eq!(vec![Green, Yellow, Gray, Yellow, Gray], feedback("array", "alarm"));
+        assert_eq!(vec![Gray, Green, Yellow, Gray, Gray], feedback("geese", "level"), "Greens come first");
+        assert_eq!("🟩🟨⬜🟩⬜", show(&feedback("abcde", "axbdy")));
+    }
+
+    #[test]
+    fn should_narrow_candidates_down() {
+        let marks = feedback("adult", "alert");
+        let left = filter(&ANSWERS, "adult", &marks);
+        assert!(left.contains(&"alert"));
+        assert!(left.iter().all(|c| feedback("adult", c) == marks));
+        assert_eq!(0.0, entropy("about", &["about"]));
+        let e = entropy("alert", &ANSWERS);
+        assert!(e > 0.0 && e <= (ANSWERS.len() as f64).log2());
+        assert_eq!(None, suggest(&ANSWERS, &[]));
+        assert_eq!(Some("alive"), suggest(&ANSWERS, &["alive", "alike"]));
+    }
+
+    #[test]
+    fn should_simulate_every_answer() {
+        let report = simulate(&ANSWERS, &ANSWERS);
+        assert_eq!((20, 0), (report.games, report.lost));
+        assert!(report.worst <= 4 && report.average() < 3.5, "{:?}", report);
+        for answer in &ANSWERS {
+            let guesses = solve(answer, &report.opening, &ANSWERS, &ANSWERS);
+            assert_eq!(Some(answer), guesses.last());
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//!
+//! # Problem description:
+//! #
+//! # Word Search
+//!
+//! A word search puzzle hides words in a grid of letters, written in a
+//! straight line in any of eight directions: across, down, diagonally,
+//! and backwards in each of those. Find every word of a dictionary in a
+//! grid, and make puzzles: place a list of words in a grid, letting them
+//! cross where they share a letter, and fill the rest with random letters.
+//!
+//! The solver starts from every cell in every direction and walks a trie
+//! of the dictionary along with the letters, so it stops as soon as no
+//! word begins that way instead of trying every word everywhere. The
+//! generator places the longest words first, each at a random position
+//! where it fits, and fills the gaps with letters drawn from the words
+//! themselves, so the filler doesn't stand out.
+//!
+
+use rng::Rng;
+use shuffle::shuffle;
+use std::fmt;
+use std::str::FromStr;
+use trie::Trie;
+
+/// A direction as the change of row and of column for each letter
+pub type Direction = (isize, isize);
+
+/// The eight directions, starting across and going clockwise
+pub const DIRECTIONS: [Direction; 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+
+/// A rectangular grid of letters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+}
+
+/// Errors reading a grid
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError {
+    /// No letters at all
+    Empty,
+    /// A row, counting from 0, with a different number of letters than
+    /// the first
+    Ragged(usize),
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GridError::Empty => write!(f, "The grid has no letters"),
+            GridError::Ragged(row) => write!(f, "Row {} has a different length than the first", row),
+        }
+    }
+}
+
+impl Grid {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The letter at a row and column
+    pub fn get(&self, row: usize, col: usize) -> Option<char> {
+        if row < self.height && col < self.width {
+            Some(self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    /// The cell `steps` letters away in a direction, if it is in the grid
+    fn walk(&self, row: usize, col: usize, direction: Direction, steps: usize) -> Option<(usize, usize)> {
+        let row = row as isize + direction.0 * steps as isize;
+        let col = col as isize + direction.1 * steps as isize;
+        if row < 0 || col < 0 || row as usize >= self.height || col as usize >= self.width {
+            None
+        } else {
+            Some((row as usize, col as usize))
+        }
+    }
+}
+
+impl FromStr for Grid {
+    type Err = GridError;
+
+    /// One row per line, ignoring spaces between letters and blank lines
+    fn from_str(text: &str) -> Result<Grid, GridError> {
+        let rows: Vec<Vec<char>> = text
+            .lines()
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>())
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.first().ok_or(GridError::Empty)?.len();
+        if let Some(ragged) = rows.iter().position(|row| row.len() != width) {
+            return Err(GridError::Ragged(ragged));
+        }
+        Ok(Grid { width, height: rows.len(), cells: rows.concat() })
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width) {
+            let letters: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+            writeln!(f, "{}", letters.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A word in the grid: where its first letter is and which way it goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found {
+    pub word: String,
+    pub row: usize,
+    pub col: usize,
+    pub direction: Direction,
+}
+
+/// Every word of the dictionary in the grid, by the cell of its first
+/// letter and then by direction. Words are found as many times as they
+/// appear, so a palindrome shows up going both ways.
+///
+/// # Example
+/// ```
+/// use pprust::trie::Trie;
+/// use pprust::wordsearch::find_words;
+///
+/// let grid = "C A T\nX O X\nT A C".parse().unwrap();
+/// let dictionary: Trie = ["CAT", "TOT", "DOG"].iter().collect();
+/// let found: Vec<(String, usize, usize)> = find_words(&grid, &dictionary).into_iter()
+///     .map(|f| (f.word, f.row, f.col)).collect();
+/// assert_eq!(vec![("CAT".to_string(), 0, 0), ("TOT".to_string(), 0, 2),
+///                 ("TOT".to_string(), 2, 0), ("CAT".to_string(), 2, 2)], found);
+/// ```
+pub fn find_words(grid: &Grid, dictionary: &Trie) -> Vec<Found> {
+    let mut found = Vec::new();
+    for row in 0..grid.height {
+        for col in 0..grid.width {
+            for &direction in &DIRECTIONS {
+                let mut cursor = dictionary.root();
+                let mut word = String::new();
+                let mut steps = 0;
+                while let Some((r, c)) = grid.walk(row, col, direction, steps) {
+                    let letter = grid.cells[r * grid.width + c];
+                    cursor = match cursor.step(letter) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                    word.push(letter);
+                    if cursor.is_word() {
+                        found.push(Found { word: word.clone(), row, col, direction });
+                    }
+                    steps += 1;
+                }
+            }
+        }
+    }
+    found
+}
+
+/// A puzzle with the words in a grid of the given size, and where each
+/// word went, in the order given. Words may cross on shared letters but
+/// each covers at least one cell of its own. `None` if a word didn't fit,
+/// which, as words aren't moved once placed, can happen even if some
+/// arrangement has room for all of them.
+///
+/// # Example
+/// ```
+/// use pprust::rng::Rng;
+/// use pprust::trie::Trie;
+/// use pprust::wordsearch::{find_words, generate};
+///
+/// let words = ["RUST", "CARGO", "CRATE", "TRAIT"];
+/// let (grid, placed) = generate(&words, 8, 8, &mut Rng::new(1)).unwrap();
+/// let dictionary: Trie = words.iter().collect();
+/// let found = find_words(&grid, &dictionary);
+/// assert!(placed.iter().all(|p| found.contains(p)));
+/// ```
+pub fn generate(words: &[&str], width: usize, height: usize, rng: &mut Rng) -> Option<(Grid, Vec<Found>)> {
+    let mut cells: Vec<Option<char>> = vec![None; width * height];
+    let mut order: Vec<usize> = (0..words.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(words[i].chars().count()));
+    let mut starts: Vec<(usize, usize, Direction)> = Vec::with_capacity(width * height * DIRECTIONS.len());
+    for row in 0..height {
+        for col in 0..width {
+            starts.extend(DIRECTIONS.iter().map(|&direction| (row, col, direction)));
+        }
+    }
+    // Only the size matters for walking, not the letters
+    let shape = Grid { width, height, cells: Vec::new() };
+    let mut placed: Vec<Option<Found>> = vec![None; words.len()];
+    for i in order {
+        let letters: Vec<char> = words[i].chars().collect();
+        assert!(!letters.is_empty(), "Hiding an empty word");
+        shuffle(&mut starts, rng);
+        let fits = |&(row, col, direction): &(usize, usize, Direction)| {
+            let mut new_cells = 0;
+            for (steps, &letter) in letters.iter().enumerate() {
+                match shape.walk(row, col, direction, steps) {
+                    Some((r, c)) => match cells[r * width + c] {
+                        None => new_cells += 1,
+                        Some(existing) if existing == letter => (),
+                        Some(_) => return false,
+                    },
+                    None => return false,
+                }
+            }
+            new_cells > 0
+        };
+        let (row, col, direction) = *starts.iter().find(|start| fits(start))?;
+        for (steps, &letter) in letters.iter().enumerate() {
+            let (r, c) = shape.walk(row, col, direction, steps).expect("The word fits");
+            cells[r * width + c] = Some(letter);
+        }
+        placed[i] = Some(Found { word: words[i].to_string(), row, col, direction });
+    }
+    let filler: Vec<char> = words.iter().flat_map(|word| word.chars()).collect();
+    let cells = cells
+        .into_iter()
+        .map(|cell| cell.unwrap_or_else(|| filler[rng.gen_range(0, filler.len() as u64) as usize]))
+        .collect();
+    let placed = placed.into_iter().map(|p| p.expect("Every word was placed")).collect();
+    Some((Grid { width, height, cells }, placed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "
+        S O L U T I O N
+        R U S T Y X E K
+        A C R A B T M O
+        T K E E R A E O
+        X R G O N E T L
+        S O C E A N S A
+    ";
+
+    fn dictionary() -> Trie {
+        ["SOLUTION", "RUST", "CRAB", "OCEAN", "SURE", "TEN", "NET", "SEA", "LOOK", "AT", "ZEBRA"].iter().collect()
+    }
+
+    #[test]
+    fn should_find_words_in_every_direction() {
+        let grid: Grid = PUZZLE.parse().unwrap();
+        assert_eq!((8, 6), (grid.width(), grid.height()));
+        let found = find_words(&grid, &dictionary());
+        let has = |word: &str, row: usize, col: usize, direction: Direction| {
+            found.contains(&Found { word: word.to_string(), row, col, direction })
+        };
+        assert!(has("SOLUTION", 0, 0, (0, 1)));
+        assert!(has("RUST", 1, 0, (0, 1)));
+        assert!(has("CRAB", 2, 1, (0, 1)));
+        assert!(has("OCEAN", 5, 1, (0, 1)));
+        assert!(has("LOOK", 4, 7, (-1, 0)), "Upwards");
+        assert!(has("SURE", 0, 0, (1, 1)), "Diagonally");
+        assert!(has("NET", 4, 4, (0, 1)));
+        assert!(has("TEN", 4, 6, (0, -1)), "Backwards");
+        assert!(!found.iter().any(|f| f.word == "ZEBRA"));
+        for f in &found {
+            let letters: String = (0..f.word.chars().count())
+                .map(|k| {
+                    let (r, c) = grid.walk(f.row, f.col, f.direction, k).unwrap();
+                    grid.get(r, c).unwrap()
+                })
+                .collect();
+            assert_eq!(f.word, letters);
+        }
+    }
+
+    #[test]
+    fn should_read_and_print_grids() {
+        let grid: Grid = "ab\ncd\n".parse().unwrap();
+        assert_eq!("a b\nc d\n", grid.to_string());
+        assert_eq!(grid, grid.to_string().parse().unwrap());
+        assert_eq!(Some('c'), grid.get(1, 0));
+        assert_eq!(None, grid.get(0, 2));
+        assert_eq!(Err(GridError::Empty), "\n  \n".parse::<Grid>());
+        assert_eq!(Err(GridError::Ragged(1)), "abc\nab".parse::<Grid>());
+    }
+
+    #[test]
+    fn should_generate_solvable_puzzles() {
+        let words = ["HASKELL", "PYTHON", "RUST", "SCHEME", "FORTRAN", "COBOL", "PASCAL", "PROLOG", "LISP", "ADA", "GO"];
+        let dictionary: Trie = words.iter().collect();
+        let mut rng = Rng::new(268);
+        for _ in 0..20 {
+            let (grid, placed) = generate(&words, 10, 10, &mut rng).expect("Ten by ten has room");
+            assert_eq!((10, 10), (grid.width(), grid.height()));
+            let found = find_words(&grid, &dictionary);
+            for (word, p) in words.iter().zip(&placed) {
+                assert_eq!(*word, p.word);
+                assert!(found.contains(p), "{:?} not found in\n{}", p, grid);
+            }
+            let letters: String = words.concat();
+            assert!(grid.cells.iter().all(|&c| letters.contains(c)));
+        }
+        assert!(generate(&["TOOLONG"], 3, 3, &mut rng).is_none());
+        assert!(generate(&["ABC", "ABC"], 3, 1, &mut rng).is_none(), "A word needs a cell of its own");
+        let (grid, _) = generate(&["ABC", "CBA"], 3, 2, &mut rng).unwrap();
+        assert_eq!(2, grid.height());
+    }
+}
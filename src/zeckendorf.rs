@@ -0,0 +1,245 @@
+//!
+//! # Problem description:
+//! #
+//! # Zeckendorf Representation and Fibonacci Coding
+//!
+//! Zeckendorf's theorem says every positive integer is, in exactly one
+//! way, a sum of distinct Fibonacci numbers no two of which are
+//! consecutive: 100 = 89 + 8 + 3. Taking the largest Fibonacci number
+//! that fits and repeating on the rest finds it.
+//!
+//! Fibonacci coding writes that sum as bits, one per Fibonacci number
+//! 1, 2, 3, 5, 8, ... from the smallest up, and appends an extra 1. Since
+//! the representation never has two consecutive ones, the pair "11" only
+//! occurs at the end of a codeword, so codewords can be concatenated and
+//! still decoded, and a corrupted bit only garbles a couple of numbers.
+//!
+
+use bitio::{BitReader, BitWriter};
+use std::io;
+use std::io::prelude::*;
+
+/// The Fibonacci numbers 1, 2, 3, 5, ... that fit in a `u64`
+fn fibonacci() -> Vec<u64> {
+    let mut fibs = vec![1u64, 2];
+    while let Some(next) = fibs[fibs.len() - 1].checked_add(fibs[fibs.len() - 2]) {
+        fibs.push(next);
+    }
+    fibs
+}
+
+/// The Zeckendorf representation of `n`, largest term first
+///
+/// # Example
+/// ```
+/// use pprust::zeckendorf::decompose;
+///
+/// assert_eq!(vec![89, 8, 3], decompose(100));
+/// assert!(decompose(0).is_empty());
+/// ```
+pub fn decompose(mut n: u64) -> Vec<u64> {
+    let mut terms = Vec::new();
+    for &f in fibonacci().iter().rev() {
+        if f <= n {
+            terms.push(f);
+            n -= f;
+        }
+    }
+    terms
+}
+
+/// The Fibonacci codeword for `n`, which must be positive
+///
+/// # Example
+/// ```
+/// use pprust::zeckendorf::fib_encode;
+///
+/// // 11 = 8 + 3: bits for 1, 2, 3, 5, 8, then the terminating 1
+/// assert_eq!(vec![false, false, true, false, true, true], fib_encode(11));
+/// ```
+pub fn fib_encode(mut n: u64) -> Vec<bool> {
+    assert!(n > 0, "Zero has no Fibonacci code");
+    let fibs = fibonacci();
+    let top = fibs.iter().rposition(|&f| f <= n).unwrap();
+    let mut bits = vec![false; top + 2];
+    for i in (0..=top).rev() {
+        if fibs[i] <= n {
+            bits[i] = true;
+            n -= fibs[i];
+        }
+    }
+    bits[top + 1] = true;
+    bits
+}
+
+/// Decodes a single codeword, which must end in the only "11" it has
+///
+/// # Example
+/// ```
+/// use pprust::zeckendorf::fib_decode;
+///
+/// assert_eq!(Some(11), fib_decode(&[false, false, true, false, true, true]));
+/// assert_eq!(None, fib_decode(&[true, false]));
+/// ```
+pub fn fib_decode(bits: &[bool]) -> Option<u64> {
+    let n = bits.len();
+    if n < 2 || !bits[n - 2] || !bits[n - 1] {
+        return None;
+    }
+    let mut decoder = Decoder::new();
+    for &bit in &bits[..n - 1] {
+        if decoder.push(bit)?.is_some() {
+            return None;
+        }
+    }
+    decoder.push(true)?
+}
+
+/// Accumulates the bits of a single codeword
+struct Decoder {
+    fibs: Vec<u64>,
+    index: usize,
+    value: u64,
+    previous: bool,
+}
+
+impl Decoder {
+    fn new() -> Decoder {
+        Decoder { fibs: fibonacci(), index: 0, value: 0, previous: false }
+    }
+
+    /// Takes the next bit, giving the value when the codeword ends, or
+    /// `None` if it doesn't fit in a `u64`
+    fn push(&mut self, bit: bool) -> Option<Option<u64>> {
+        if bit && self.previous {
+            return Some(Some(self.value));
+        }
+        if bit {
+            let f = *self.fibs.get(self.index)?;
+            self.value = self.value.checked_add(f)?;
+        }
+        self.index += 1;
+        self.previous = bit;
+        Some(None)
+    }
+
+    fn is_clean(&self) -> bool {
+        self.value == 0
+    }
+}
+
+/// Appends the codeword for `n`, which must be positive
+pub fn write_fib<W: Write>(writer: &mut BitWriter<W>, n: u64) -> io::Result<()> {
+    for bit in fib_encode(n) {
+        writer.write_bit(bit)?;
+    }
+    Ok(())
+}
+
+/// Reads the next codeword, or `None` at the end of the input. The zeros
+/// padding the last byte are not a codeword.
+///
+/// # Example
+/// ```
+/// use pprust::bitio::{BitReader, BitWriter};
+/// use pprust::zeckendorf::{read_fib, write_fib};
+///
+/// let mut writer = BitWriter::new(Vec::new());
+/// for &n in &[3, 1, 4, 1, 5] {
+///     write_fib(&mut writer, n).unwrap();
+/// }
+/// let bytes = writer.finish().unwrap();
+/// let mut reader = BitReader::new(&bytes[..]);
+/// let mut numbers = Vec::new();
+/// while let Some(n) = read_fib(&mut reader).unwrap() {
+///     numbers.push(n);
+/// }
+/// assert_eq!(vec![3, 1, 4, 1, 5], numbers);
+/// ```
+pub fn read_fib<R: Read>(reader: &mut BitReader<R>) -> io::Result<Option<u64>> {
+    let mut decoder = Decoder::new();
+    while let Some(bit) = reader.read_bit()? {
+        match decoder.push(bit) {
+            Some(Some(n)) => return Ok(Some(n)),
+            Some(None) => (),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "Fibonacci code too long")),
+        }
+    }
+    if decoder.is_clean() {
+        Ok(None)
+    } else {
+        Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated Fibonacci code"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    #[test]
+    fn should_decompose_without_consecutive_fibonacci_numbers() {
+        let fibs = fibonacci();
+        for n in 1..20_000u64 {
+            let terms = decompose(n);
+            assert_eq!(n, terms.iter().sum::<u64>());
+            let indices: Vec<usize> = terms.iter().map(|t| fibs.iter().position(|f| f == t).unwrap()).collect();
+            assert!(indices.windows(2).all(|w| w[0] >= w[1] + 2), "{}: {:?}", n, terms);
+        }
+        assert_eq!(u64::MAX, decompose(u64::MAX).iter().sum::<u64>());
+    }
+
+    #[test]
+    fn should_encode_known_codewords() {
+        let text = |n| fib_encode(n).iter().map(|&b| if b { '1' } else { '0' }).collect::<String>();
+        assert_eq!("11", text(1));
+        assert_eq!("011", text(2));
+        assert_eq!("0011", text(3));
+        assert_eq!("1011", text(4));
+        assert_eq!("00011", text(5));
+        assert_eq!("10101011", text(33));
+    }
+
+    #[test]
+    fn should_round_trip_codewords() {
+        let mut rng = Rng::new(5);
+        let numbers = (1..1000).chain((0..1000).map(|_| rng.next_u64().max(1))).chain(vec![u64::MAX]);
+        for n in numbers {
+            let bits = fib_encode(n);
+            // "11" only at the end
+            assert!(bits[..bits.len() - 1].windows(2).all(|w| !(w[0] && w[1])), "{}", n);
+            assert_eq!(Some(n), fib_decode(&bits));
+        }
+        assert_eq!(None, fib_decode(&[true, true, true]));
+        assert_eq!(None, fib_decode(&[true, true, false, true, true]));
+        // More Fibonacci numbers than a u64 holds
+        let mut too_long = vec![false; 100];
+        too_long.extend_from_slice(&[true, true]);
+        assert_eq!(None, fib_decode(&too_long));
+    }
+
+    #[test]
+    fn should_stream_codewords() {
+        let mut rng = Rng::new(9);
+        let numbers: Vec<u64> = (0..2000)
+            .map(|_| {
+                let bits = rng.gen_range(1, 64);
+                1 + rng.gen_range(0, 1 << bits)
+            })
+            .collect();
+        let mut writer = BitWriter::new(Vec::new());
+        for &n in &numbers {
+            write_fib(&mut writer, n).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+        let mut reader = BitReader::new(&bytes[..]);
+        let mut decoded = Vec::new();
+        while let Some(n) = read_fib(&mut reader).unwrap() {
+            decoded.push(n);
+        }
+        assert_eq!(numbers, decoded);
+        // A codeword cut short
+        let mut reader = BitReader::new(&[0b1000_0000u8][..]);
+        assert_eq!(io::ErrorKind::UnexpectedEof, read_fib(&mut reader).unwrap_err().kind());
+    }
+}